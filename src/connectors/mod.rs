@@ -3,3 +3,5 @@ pub mod mongodb;
 
 #[cfg(any(feature = "data-source-mysql", feature = "data-source-postgres", feature = "data-source-sqlite"))]
 pub mod sql;
+
+pub(crate) mod replicated;
@@ -1,15 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use bson::{Bson, doc, Document, Regex as BsonRegex};
 
+use key_path::path;
 use maplit::hashmap;
+use crate::core::error::Error;
 use crate::core::field::r#type::{FieldType, FieldTypeOwner};
 use crate::core::input::Input;
 use crate::core::model::Model;
 use crate::core::relation::Relation;
+use crate::core::relation::cardinality::Cardinality;
 use crate::core::result::Result;
+use crate::core::teon::filter::cursor_where;
 use crate::prelude::{Graph, Value};
 use crate::teon;
 
+/// The largest page `find_many_and_count` reads in one `$facet` stage.
+static FACET_PAGE_LIMIT: i64 = 100;
+
 pub(crate) struct Aggregation { }
 
 impl Aggregation {
@@ -142,64 +149,99 @@ impl Aggregation {
         Ok(retval)
     }
 
+    /// Counts the records matching the finder's where and cursor, after its
+    /// distinct and its skip and take. Records aren't sorted, projected or
+    /// joined.
     pub(crate) fn build_for_count(model: &Model, graph: &Graph, value: &Value) -> Result<Vec<Document>> {
-        let mut retval = Self::build(model, graph, value)?;
-        retval.push(doc! {"$count": "count"});
+        let mut retval = Self::build_match(model, graph, value)?;
+        if let Some(distinct) = value.get("distinct") {
+            let mut group_id = doc!{};
+            for column in Self::distinct_columns(model, distinct)? {
+                group_id.insert(&column, format!("${column}"));
+            }
+            if !group_id.is_empty() {
+                retval.push(doc!{"$group": {"_id": group_id}});
+            }
+        }
+        retval.extend(Self::build_count(value));
         Ok(retval)
     }
 
-    pub(crate) fn build(model: &Model, graph: &Graph, value: &Value) -> Result<Vec<Document>> {
+    /// A single `$facet` stage returning the page under `data` and the count
+    /// of all matched records under `count`. The facet's output is one
+    /// document, so only pages up to `FACET_PAGE_LIMIT` records are read this
+    /// way; others are counted with a separate query and this returns none.
+    pub(crate) fn build_with_count(model: &Model, graph: &Graph, value: &Value) -> Result<Option<Vec<Document>>> {
+        if !Self::counts_in_facet(value) {
+            return Ok(None);
+        }
+        let r#match = Self::build_match(model, graph, value)?;
+        let mut data = Self::build(model, graph, value)?;
+        let data = data.split_off(r#match.len());
+        Ok(Some(Self::facet_data_and_count(r#match, data)))
+    }
+
+    /// Whether the finder reads a small page whose count is the count of its
+    /// `$match`. Cursors and distinct change the count, so they're excluded.
+    fn counts_in_facet(value: &Value) -> bool {
+        if value.get("cursor").is_some() || value.get("distinct").is_some() {
+            return false;
+        }
+        let page_size = match (value.get("pageSize"), value.get("pageNumber")) {
+            (Some(page_size), Some(_)) => Some(page_size),
+            _ => value.get("take"),
+        };
+        match page_size {
+            Some(page_size) => page_size.as_i64().unwrap().abs() <= FACET_PAGE_LIMIT,
+            None => false,
+        }
+    }
+
+    fn facet_data_and_count(mut stages: Vec<Document>, data: Vec<Document>) -> Vec<Document> {
+        stages.push(doc!{"$facet": {"data": data, "count": [{"$count": "count"}]}});
+        stages
+    }
+
+    fn build_count(value: &Value) -> Vec<Document> {
+        let mut retval = Self::build_skip_and_limit(value);
+        retval.push(doc! {"$count": "count"});
+        retval
+    }
+
+    /// `$match` stages of the finder's where and cursor, with the lookups for
+    /// relation filters.
+    fn build_match(model: &Model, graph: &Graph, value: &Value) -> Result<Vec<Document>> {
         let mut retval: Vec<Document> = vec![];
         let r#where = value.get("where");
-        let order_by = value.get("orderBy");
-        let distinct = value.get("distinct");
-        let skip = value.get("skip");
         let take = value.get("take");
-        let page_size = value.get("pageSize");
-        let page_number = value.get("pageNumber");
-        let select = value.get("select");
-        let include = value.get("include");
         // if cursor exists, we modify the actual where
         let cursor_where_additions = if let Some(cursor) = value.get("cursor") {
-            let cursor = cursor.as_hashmap().unwrap();
-            let cursor_key = cursor.keys().next().unwrap();
-            let cursor_value = cursor.values().next().unwrap();
-            let order_by = value.get("orderBy").unwrap().as_vec().unwrap().get(0).unwrap().as_hashmap().unwrap().values().next().unwrap().as_str().unwrap();
-            let mut order_asc = order_by == "asc";
-            if let Some(take) = take {
-                if take.as_i64().unwrap() < 0 {
-                    order_asc = !order_asc;
-                }
-            }
-            let cursor_where_key = if order_asc { "gte" } else { "lte" };
-            let cursor_additional_where = Self::build_where(model, graph, &teon!({cursor_key: {cursor_where_key: cursor_value}}));
+            let order_by = value.get("orderBy").unwrap();
+            let negative_take = take.map_or(false, |take| take.as_i64().unwrap() < 0);
+            let cursor_additional_where = Self::build_where(model, graph, &cursor_where(cursor, order_by, negative_take));
             Some(cursor_additional_where?)
         } else {
             None
         };
+        let mut r#match = match r#where {
+            Some(r#where) => Self::build_where(model, graph, r#where)?,
+            None => doc!{},
+        };
+        // MongoDB only accepts `$text` in the first stage
+        if let Some(text) = Self::take_text_search(&mut r#match)? {
+            retval.push(doc!{"$match": {"$text": text}});
+        }
         // build `$lookup`s for relation where
         if let Some(r#where) = r#where {
             let lookups_for_relation_where = Self::build_lookups_for_relation_where(model, graph, r#where)?;
             retval.extend(lookups_for_relation_where)
         }
         // $match
-        if let Some(r#where) = r#where {
-            let r#match = Self::build_where(model, graph, r#where)?;
-            if !r#match.is_empty() {
-                if let Some(cursor_where_additions) = cursor_where_additions {
-                    retval.push(doc!{"$match": {"$and": [r#match, cursor_where_additions]}});
-                } else {
-                    retval.push(doc!{"$match": r#match});
-                }
-            } else {
-                if let Some(cursor_where_additions) = cursor_where_additions {
-                    retval.push(doc!{"$match": cursor_where_additions});
-                }
-            }
-        } else {
-            if let Some(cursor_where_additions) = cursor_where_additions {
-                retval.push(doc!{"$match": cursor_where_additions});
-            }
+        match (r#match.is_empty(), cursor_where_additions) {
+            (false, Some(cursor_where_additions)) => retval.push(doc!{"$match": {"$and": [r#match, cursor_where_additions]}}),
+            (false, None) => retval.push(doc!{"$match": r#match}),
+            (true, Some(cursor_where_additions)) => retval.push(doc!{"$match": cursor_where_additions}),
+            (true, None) => (),
         }
         // remove lookup for matching here
         if let Some(r#where) = r#where {
@@ -208,25 +250,47 @@ impl Aggregation {
                 retval.extend(unsets);
             }
         }
-        // sort without distinct. If distinct, sort later in distinct
-        if distinct.is_none() {
-            if let Some(order_by) = order_by {
-                let reverse = match take {
-                    Some(take) => take.as_i64().unwrap() < 0,
-                    None => false
-                };
-                let sort = Self::build_order_by(model, order_by, reverse)?;
-                if !sort.is_empty() {
-                    retval.push(doc!{"$sort": sort});
+        Ok(retval)
+    }
+
+    /// Removes the `$text` filter from `document` and the documents it's
+    /// `$and`ed with. It can't be moved out of `$or` and `$nor`.
+    fn take_text_search(document: &mut Document) -> Result<Option<Bson>> {
+        let mut text = document.remove("$text");
+        if let Ok(items) = document.get_array_mut("$and") {
+            for item in items.iter_mut() {
+                if let Bson::Document(item) = item {
+                    if let Some(nested) = Self::take_text_search(item)? {
+                        if text.is_some() {
+                            return Err(Error::unexpected_input_value_with_reason("Only one fulltext search is allowed.", path!["where"]));
+                        }
+                        text = Some(nested);
+                    }
                 }
-            } else if let Some(take) = take {
-                if take.as_i64().unwrap() < 0 {
-                    let sort = Self::build_order_by(model, &Self::default_desc_order(model), false)?;
-                    retval.push(doc!{"$sort": sort});
+            }
+        }
+        for key in ["$or", "$nor"] {
+            if let Ok(items) = document.get_array(key) {
+                if items.iter().filter_map(|i| i.as_document()).any(Self::contains_text_search) {
+                    return Err(Error::unexpected_input_value_with_reason("Fulltext search can't be nested in OR or NOT.", path!["where"]));
                 }
             }
         }
-        // $skip and $limit
+        Ok(text)
+    }
+
+    fn contains_text_search(document: &Document) -> bool {
+        document.contains_key("$text") || ["$and", "$or", "$nor"].iter().any(|key| {
+            document.get_array(key).map_or(false, |items| items.iter().filter_map(|i| i.as_document()).any(Self::contains_text_search))
+        })
+    }
+
+    fn build_skip_and_limit(value: &Value) -> Vec<Document> {
+        let mut retval: Vec<Document> = vec![];
+        let skip = value.get("skip");
+        let take = value.get("take");
+        let page_size = value.get("pageSize");
+        let page_number = value.get("pageNumber");
         if page_size.is_some() && page_number.is_some() {
             retval.push(doc!{"$skip": ((page_number.unwrap().as_i64().unwrap() - 1) * page_size.unwrap().as_i64().unwrap()) as i64});
             retval.push(doc!{"$limit": page_size.unwrap().as_i64().unwrap()});
@@ -238,38 +302,45 @@ impl Aggregation {
                 retval.push(doc!{"$limit": take.unwrap().as_i64().unwrap().abs()});
             }
         }
-        // distinct or select
-        // distinct ($group and $project)
+        retval
+    }
+
+    pub(crate) fn build(model: &Model, graph: &Graph, value: &Value) -> Result<Vec<Document>> {
+        let mut retval = Self::build_match(model, graph, value)?;
+        let order_by = value.get("orderBy");
+        let distinct = value.get("distinct");
+        let take = value.get("take");
+        let select = value.get("select");
+        let include = value.get("include");
+        // $sort before $group, so that $first keeps the first record in order
+        let reverse = match take {
+            Some(take) => take.as_i64().unwrap() < 0,
+            None => false
+        };
+        let sort = if let Some(order_by) = order_by {
+            Self::build_order_by(model, order_by, reverse)?
+        } else if take.is_some() && reverse {
+            Self::build_order_by(model, &Self::default_desc_order(model), false)?
+        } else {
+            doc!{}
+        };
+        if !sort.is_empty() {
+            retval.push(doc!{"$sort": &sort});
+        }
+        // distinct ($group) before $skip and $limit
         if let Some(distinct) = distinct {
-            // $group
-            let mut group_id = doc!{};
-            for value in distinct.as_vec().unwrap().iter() {
-                let val = value.as_str().unwrap();
-                group_id.insert(val, format!("${val}"));
-            }
-            let _empty = teon!({});
-            let mut group_data = Self::build_select(model, graph, select.unwrap_or(&teon!({})), Some(distinct))?;
-            group_data.insert("_id", group_id);
-            retval.push(doc!{"$group": &group_data});
-            if group_data.get("__id").is_some() {
-                retval.push(doc!{"$addFields": {"_id": "$__id"}});
-                retval.push(doc!{"$unset": "__id"});
-            } else {
-                retval.push(doc!{"$unset": "_id"});
-            }
-            // $sort again if distinct
-            let reverse = match take {
-                Some(take) => take.as_i64().unwrap() < 0,
-                None => false
-            };
-            if let Some(order_by) = order_by {
-                let sort = Self::build_order_by(model, order_by, reverse)?;
-                if !sort.is_empty() {
-                    retval.push(doc!{"$sort": sort});
-                }
+            let columns = Self::distinct_columns(model, distinct)?;
+            let group_data = Self::build_select(model, graph, select.unwrap_or(&teon!({})), Some(distinct))?;
+            retval.extend(Self::build_distinct_group(&columns, group_data));
+            // $group doesn't keep the order, $sort again
+            if !sort.is_empty() {
+                retval.push(doc!{"$sort": &sort});
             }
-        } else {
-            // $project
+        }
+        // $skip and $limit
+        retval.extend(Self::build_skip_and_limit(value));
+        // $project
+        if distinct.is_none() {
             if let Some(select) = select {
                 if !select.as_hashmap().unwrap().is_empty() {
                     let select_input = Self::build_select(model, graph, select, distinct)?;
@@ -285,10 +356,56 @@ impl Aggregation {
             if !lookups.is_empty() {
                 retval.append(&mut lookups);
             }
+            let to_one_keys: Vec<&str> = include.as_hashmap().unwrap().iter().filter(|(k, v)| {
+                (v.is_hashmap() || v.as_bool() == Some(true)) && model.relation(k).unwrap().cardinality() == Cardinality::One
+            }).map(|(k, _)| k.as_str()).collect();
+            // `$lookup` always produces an array, replace the ones of to-one
+            // relations with their only element or null
+            if !to_one_keys.is_empty() {
+                let mut set = doc!{};
+                for key in to_one_keys {
+                    set.insert(key, doc!{"$ifNull": [{"$first": format!("${key}")}, Bson::Null]});
+                }
+                retval.push(doc!{"$set": set});
+            }
         }
         Ok(retval)
     }
 
+    fn distinct_columns(model: &Model, distinct: &Value) -> Result<Vec<String>> {
+        let keys = match distinct.as_vec() {
+            Some(keys) => keys,
+            None => return Err(Error::unexpected_input_type("array", path!["distinct"])),
+        };
+        keys.iter().enumerate().map(|(i, v)| {
+            let field = v.as_str().filter(|k| model.query_keys().iter().any(|q| q == k)).and_then(|k| model.field(k));
+            match field {
+                Some(field) => Ok(field.column_name().to_owned()),
+                None => Err(Error::unexpected_input_value("scalar fields enum", path!["distinct", i])),
+            }
+        }).collect()
+    }
+
+    /// `$group` keyed by the distinct columns, keeping the first value of
+    /// every selected column, then restore `_id` from its renamed key.
+    fn build_distinct_group(columns: &Vec<String>, mut group_data: Document) -> Vec<Document> {
+        let mut group_id = doc!{};
+        for column in columns {
+            group_id.insert(column, format!("${column}"));
+        }
+        group_data.insert("_id", group_id);
+        let mut retval = vec![];
+        let has_id = group_data.get("__id").is_some();
+        retval.push(doc!{"$group": group_data});
+        if has_id {
+            retval.push(doc!{"$addFields": {"_id": "$__id"}});
+            retval.push(doc!{"$unset": "__id"});
+        } else {
+            retval.push(doc!{"$unset": "_id"});
+        }
+        retval
+    }
+
     fn build_select(model: &Model, _graph: &Graph, select: &Value, distinct: Option<&Value>) -> Result<Document> {
         let map = select.as_hashmap().unwrap();
         let true_keys: Vec<&String> = map.iter().filter(|(_k, v)| v.as_bool().unwrap() == true).map(|(k, _)| k).collect();
@@ -342,6 +459,12 @@ impl Aggregation {
     }
 
     fn build_where(model: &Model, graph: &Graph, value: &Value) -> Result<Document> {
+        Self::build_where_at(model, graph, value, "")
+    }
+
+    /// `prefix` locates `value` inside the outermost where, relation filters
+    /// match the lookup named after their location.
+    fn build_where_at(model: &Model, graph: &Graph, value: &Value, prefix: &str) -> Result<Document> {
         let value_map = value.as_hashmap().unwrap();
         let mut retval = doc!{};
         for (key, value) in value_map.iter() {
@@ -349,40 +472,36 @@ impl Aggregation {
             match key {
                 "AND" => {
                     let mut vals: Vec<Document> = vec![];
-                    for val in value.as_vec().unwrap() {
-                        vals.push(Self::build_where(model, graph, val)?);
+                    for (i, val) in value.as_vec().unwrap().iter().enumerate() {
+                        vals.push(Self::build_where_at(model, graph, val, &format!("{prefix}AND_{i}_"))?);
                     }
                     retval.insert("$and", vals);
                 }
                 "OR" => {
                     let mut vals: Vec<Document> = vec![];
-                    for val in value.as_vec().unwrap() {
-                        vals.push(Self::build_where(model, graph, val)?);
+                    for (i, val) in value.as_vec().unwrap().iter().enumerate() {
+                        vals.push(Self::build_where_at(model, graph, val, &format!("{prefix}OR_{i}_"))?);
                     }
                     retval.insert("$or", vals);
                 }
                 "NOT" => {
-                    retval.insert("$nor", vec![Self::build_where(model, graph, value)?]);
+                    retval.insert("$nor", vec![Self::build_where_at(model, graph, value, &format!("{prefix}NOT_"))?]);
                 }
                 _ => {
                     if let Some(field) = model.field(key) {
                         let column_name = field.column_name();
-                        retval.insert(column_name, Self::build_where_item(model, graph, field.field_type(), field.is_optional(), value)?);
-                    } else if let Some(relation) = model.relation(key) {
-                        let relation_model = graph.model(relation.model()).unwrap();
-                        let (command, inner_where) = Input::key_value(value.as_hashmap().unwrap());
-                        let _inner_where = Self::build_where(relation_model, graph, inner_where)?;
-                        match command {
-                            "none" | "isNot" => {
-                                retval.insert(key, doc!{"$size": 0});
-                            }
-                            "some" | "is" => {
-                                retval.insert(key, doc!{"$size": 1});
-                            }
-                            "all" => {
-                                retval.insert(key, doc!{"$size": 0});
+                        if let Some(term) = value.get("search") {
+                            // the collection's only text index covers every field of the fulltext index
+                            retval.insert("$text", doc!{"$search": term.as_str().unwrap()});
+                            if value.as_hashmap().unwrap().keys().all(|k| k == "search" || k == "mode") {
+                                continue;
                             }
-                            _ => {}
+                        }
+                        retval.insert(column_name, Self::build_where_item(model, graph, field.field_type(), field.is_optional(), value)?);
+                    } else if model.relation(key).is_some() {
+                        let (command, _) = Input::key_value(value.as_hashmap().unwrap());
+                        if let Some(size) = Self::relation_where_size(command) {
+                            retval.insert(Self::relation_where_alias(prefix, key), doc!{"$size": size});
                         }
                     }
                 }
@@ -391,9 +510,59 @@ impl Aggregation {
         Ok(retval)
     }
 
-    fn build_where_item(_model: &Model, _graph: &Graph, _type: &FieldType, _optional: bool, value: &Value) -> Result<Bson> {
+    /// Relation filters are looked up with at most one record, which must be
+    /// found for `some` and `is`, and missing for the others.
+    fn relation_where_size(command: &str) -> Option<i32> {
+        match command {
+            "some" | "is" => Some(1),
+            "none" | "isNot" | "every" => Some(0),
+            _ => None,
+        }
+    }
+
+    /// The input of a relation filter's lookup. `every` looks up a related
+    /// record which doesn't match the inner where.
+    fn relation_where_lookup_input(command: &str, r_where: &Value) -> Option<Value> {
+        match command {
+            "some" | "is" | "none" | "isNot" => Some(teon!({"where": r_where, "take": 1})),
+            "every" => Some(teon!({"where": {"NOT": r_where}, "take": 1})),
+            _ => None,
+        }
+    }
+
+    fn relation_where_alias(prefix: &str, key: &str) -> String {
+        format!("__where_{prefix}{key}")
+    }
+
+    /// The relation filters anywhere inside `r#where`, with the names of their
+    /// lookups, relation keys, commands and inner wheres.
+    fn relation_wheres<'a, F>(r#where: &'a Value, is_relation: &F, prefix: &str) -> Vec<(String, &'a str, &'a str, &'a Value)> where F: Fn(&str) -> bool {
+        let mut retval = vec![];
+        for (key, value) in r#where.as_hashmap().unwrap().iter() {
+            match key.as_str() {
+                "AND" | "OR" => for (i, val) in value.as_vec().unwrap().iter().enumerate() {
+                    retval.extend(Self::relation_wheres(val, is_relation, &format!("{prefix}{key}_{i}_")));
+                },
+                "NOT" => retval.extend(Self::relation_wheres(value, is_relation, &format!("{prefix}NOT_"))),
+                _ => if is_relation(key) {
+                    let (command, r_where) = Input::key_value(value.as_hashmap().unwrap());
+                    retval.push((Self::relation_where_alias(prefix, key), key.as_str(), command, r_where));
+                }
+            }
+        }
+        retval
+    }
+
+    fn build_where_item(_model: &Model, _graph: &Graph, r#type: &FieldType, _optional: bool, value: &Value) -> Result<Bson> {
+        Ok(Self::build_where_operators(r#type, value))
+    }
+
+    /// Translates a decoded field filter into MongoDB query operators. Each
+    /// element of `in` and `notIn` lists is kept as decoded, integers in the
+    /// width of the field.
+    fn build_where_operators(r#type: &FieldType, value: &Value) -> Bson {
         if let Some(map) = value.as_hashmap() {
-            Ok(Bson::Document(map.iter().filter(|(k, _)| k.as_str() != "mode").map(|(k, v)| {
+            Bson::Document(map.iter().filter(|(k, _)| k.as_str() != "mode" && k.as_str() != "search").map(|(k, v)| {
                 let k = k.as_str();
                 match k {
                     "startsWith" => {
@@ -431,11 +600,32 @@ impl Aggregation {
                     "isEmpty" => {
                         ("$size".to_string(), Bson::from(0))
                     },
-                    _ => (Self::build_where_key(k).as_str().unwrap().to_string(), Bson::from(v))
+                    "not" if v.is_hashmap() => {
+                        ("$not".to_string(), Self::build_where_operators(r#type, v))
+                    },
+                    _ => (Self::build_where_key(k).as_str().unwrap().to_string(), Self::build_where_value(r#type, v))
                 }
-            }).collect()))
+            }).collect())
         } else {
-            Ok(Bson::from(value))
+            Self::build_where_value(r#type, value)
+        }
+    }
+
+    /// Integers are matched in the width they're stored in, values out of a
+    /// 32 bit field's range stay 64 bit and match no record.
+    fn build_where_value(r#type: &FieldType, value: &Value) -> Bson {
+        match (r#type, value) {
+            (FieldType::Vec(inner), _) => Self::build_where_value(inner.as_ref(), value),
+            (_, Value::Vec(vec)) => Bson::Array(vec.iter().map(|v| Self::build_where_value(r#type, v)).collect()),
+            (FieldType::I32, _) => match value.as_i64().map(i32::try_from) {
+                Some(Ok(i)) => Bson::Int32(i),
+                _ => Bson::from(value),
+            },
+            (FieldType::I64, _) => match value.as_i64() {
+                Some(i) => Bson::Int64(i),
+                None => Bson::from(value),
+            },
+            _ => Bson::from(value),
         }
     }
 
@@ -462,19 +652,23 @@ impl Aggregation {
         let mut retval: Vec<Document> = vec![];
         for (key, value) in include {
             let relation = model.relation(key).unwrap();
-            let _relation_model = graph.model(relation.model()).unwrap();
             if (value.is_bool() && (value.as_bool().unwrap() == true)) || (value.is_hashmap()) {
-                if relation.has_join_table() {
-                    retval.extend(Self::build_lookup_with_join_table(model, graph, key, relation, value)?)
-                } else {
-                    retval.extend(Self::build_lookup_without_join_table(model, graph, key, relation, value)?)
-                }
+                retval.extend(Self::build_lookup(model, graph, key, relation, value)?);
             }
         }
         Ok(retval)
     }
 
-    fn build_lookup_with_join_table(model: &Model, graph: &Graph, _key: &str, relation: &Relation, value: &Value) -> Result<Vec<Document>> {
+    /// Looks `relation` up into the `key` field.
+    fn build_lookup(model: &Model, graph: &Graph, key: &str, relation: &Relation, value: &Value) -> Result<Vec<Document>> {
+        if relation.has_join_table() {
+            Self::build_lookup_with_join_table(model, graph, key, relation, value)
+        } else {
+            Self::build_lookup_without_join_table(model, graph, key, relation, value)
+        }
+    }
+
+    fn build_lookup_with_join_table(model: &Model, graph: &Graph, key: &str, relation: &Relation, value: &Value) -> Result<Vec<Document>> {
         let mut retval = vec![];
         let join_model = graph.model(relation.through().unwrap()).unwrap();
         let local_relation_on_join_table = join_model.relation(relation.local()).unwrap();
@@ -493,7 +687,7 @@ impl Aggregation {
         }
         for (jt_field, foreign_field) in foreign_relation_on_join_table.iter() {
             let jt_column_name = join_model.field(jt_field).unwrap().column_name();
-            let foreign_column_name = model.field(foreign_field).unwrap().column_name();
+            let foreign_column_name = opposite_model.field(foreign_field).unwrap().column_name();
             inner_let_value.insert(jt_column_name, format!("${jt_column_name}"));
             inner_eq_values.push(doc! {"$eq": [format!("${foreign_column_name}"), format!("$${jt_column_name}")]});
         }
@@ -586,7 +780,7 @@ impl Aggregation {
         let mut target = doc! {
             "$lookup": {
                 "from": join_model.table_name(),
-                "as": relation.name(),
+                "as": key,
                 "let": outer_let_value,
                 "pipeline": [{
                     "$match": {
@@ -638,7 +832,7 @@ impl Aggregation {
         }
         retval.push(target);
         if inner_is_reversed {
-            retval.push(doc! {"$set": {relation.name(): {"$reverseArray": format!("${}", relation.name())}}});
+            retval.push(doc! {"$set": {key: {"$reverseArray": format!("${key}")}}});
         }
         Ok(retval)
     }
@@ -694,57 +888,30 @@ impl Aggregation {
         };
         retval.push(lookup);
         if inner_is_reversed {
-            retval.push(doc!{"$set": {relation.name(): {"$reverseArray": format!("${}", relation.name())}}});
+            retval.push(doc!{"$set": {key: {"$reverseArray": format!("${key}")}}});
         }
         Ok(retval)
     }
 
     fn build_unsets_for_relation_where(model: &Model, r#where: &Value) -> Result<Vec<Document>> {
-        let r#where = r#where.as_hashmap().unwrap();
-        let mut retval: Vec<Document> = vec![];
-        for (key, _) in r#where.iter() {
-            if let Some(_) = model.relation(key) {
-                retval.push(doc!{"$unset": key})
-            }
-        }
-        Ok(retval)
+        let aliases: Vec<String> = Self::relation_wheres(r#where, &|k| model.relation(k).is_some(), "").into_iter().map(|(alias, _, _, _)| alias).collect();
+        Ok(if aliases.is_empty() {
+            vec![]
+        } else {
+            vec![doc!{"$unset": aliases}]
+        })
     }
 
     fn build_lookups_for_relation_where(model: &Model, graph: &Graph, r#where: &Value) -> Result<Vec<Document>> {
-        let r#where = r#where.as_hashmap().unwrap();
-        let mut include_input = HashMap::new();
-        for (key, value) in r#where.iter() {
-            let relation = model.relation(key);
-            if relation.is_some() {
-                let (command, r_where) = Input::key_value(value.as_hashmap().unwrap());
-                match command {
-                    "some" | "is" => {
-                        include_input.insert(key.to_string(), teon!({
-                        "where": r_where,
-                        "take": 1
-                    }));
-                    }
-                    "none" | "isNot" => {
-                        include_input.insert(key.to_string(), teon!({
-                        "where": r_where,
-                        "take": 1
-                    }));
-                    }
-                    "all" => {
-                        include_input.insert(key.to_string(), teon!({
-                        "where": {"NOT": r_where},
-                        "take": 1
-                    }));
-                    }
-                    _ => {}
-                }
-            }
+        let mut retval: Vec<Document> = vec![];
+        for (alias, key, command, r_where) in Self::relation_wheres(r#where, &|k| model.relation(k).is_some(), "") {
+            let lookup_input = match Self::relation_where_lookup_input(command, r_where) {
+                Some(lookup_input) => lookup_input,
+                None => continue,
+            };
+            retval.extend(Self::build_lookup(model, graph, &alias, model.relation(key).unwrap(), &lookup_input)?);
         }
-        Ok(if !include_input.is_empty() {
-            Self::build_lookups(model, graph, &Value::HashMap(include_input))?
-        } else {
-            vec![]
-        })
+        Ok(retval)
     }
 
     fn distinct_key(original: impl AsRef<str>) -> String {
@@ -763,3 +930,254 @@ impl Aggregation {
         Value::Vec(vec)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use chrono::{TimeZone, Utc};
+    use crate::core::error::ErrorType;
+    use crate::core::field::Field;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::test_connector::TestConnector;
+    use crate::core::test_fixtures::{id_field, users_and_posts};
+    use super::*;
+
+    fn lookup_names(stages: &Vec<Document>) -> Vec<&str> {
+        stages.iter().filter_map(|stage| stage.get_document("$lookup").ok()).map(|lookup| lookup.get_str("as").unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn only_to_one_lookups_are_flattened() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let stages = Aggregation::build(graph.model("Post").unwrap(), &graph, &teon!({"include": {"author": true}})).unwrap();
+        assert!(stages.iter().any(|stage| stage.contains_key("$lookup")));
+        assert_eq!(stages.last().unwrap(), &doc!{"$set": {"author": {"$ifNull": [{"$first": "$author"}, Bson::Null]}}});
+        let stages = Aggregation::build(graph.model("User").unwrap(), &graph, &teon!({"include": {"posts": true}})).unwrap();
+        assert!(stages.iter().any(|stage| stage.contains_key("$lookup")));
+        assert!(stages.iter().all(|stage| !stage.contains_key("$set")));
+    }
+
+    #[tokio::test]
+    async fn relation_filters_inside_or_get_their_own_lookups() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let post = graph.model("Post").unwrap();
+        let finder = teon!({"where": {"OR": [{"title": {"equals": "Rust"}}, {"author": {"is": {"id": 1}}}]}});
+        let stages = Aggregation::build(post, &graph, &finder).unwrap();
+        assert_eq!(lookup_names(&stages), vec!["__where_OR_1_author"]);
+        let r#match = stages.iter().find_map(|stage| stage.get_document("$match").ok()).unwrap();
+        assert_eq!(r#match.get_array("$or").unwrap()[1], Bson::Document(doc!{"__where_OR_1_author": {"$size": 1}}));
+        assert_eq!(stages.last().unwrap(), &doc!{"$unset": ["__where_OR_1_author"]});
+        let finder = teon!({"where": {"author": {"isNot": {"id": 1}}}});
+        let stages = Aggregation::build(post, &graph, &finder).unwrap();
+        assert_eq!(lookup_names(&stages), vec!["__where_author"]);
+        assert!(stages.contains(&doc!{"$match": {"__where_author": {"$size": 0}}}));
+    }
+
+    #[tokio::test]
+    async fn every_looks_up_a_related_record_not_matching() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let user = graph.model("User").unwrap();
+        let finder = teon!({"where": {"posts": {"every": {"title": {"equals": "Rust"}}}}});
+        let stages = Aggregation::build(user, &graph, &finder).unwrap();
+        assert_eq!(lookup_names(&stages), vec!["__where_posts"]);
+        let lookup = stages[0].get_document("$lookup").unwrap();
+        let inner_match = lookup.get_array("pipeline").unwrap()[0].as_document().unwrap().get_document("$match").unwrap();
+        assert_eq!(inner_match.get_array("$nor").unwrap(), &vec![Bson::Document(doc!{"title": {"$eq": "Rust"}})]);
+        assert!(lookup.get_array("pipeline").unwrap().contains(&Bson::Document(doc!{"$limit": 1i64})));
+        assert!(stages.contains(&doc!{"$match": {"__where_posts": {"$size": 0}}}));
+    }
+
+    #[tokio::test]
+    async fn search_is_left_to_the_text_index() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let post = graph.model("Post").unwrap();
+        let r#where = teon!({"title": {"search": "rust", "mode": "caseInsensitive", "not": "Draft"}});
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{"$text": {"$search": "rust"}, "title": {"$ne": "Draft"}});
+    }
+
+    #[test]
+    fn text_search_is_taken_out_of_and() {
+        let mut r#match = doc!{"$and": [{"$text": {"$search": "rust"}}, {"published": true}], "authorId": 1};
+        assert_eq!(Aggregation::take_text_search(&mut r#match).unwrap(), Some(Bson::Document(doc!{"$search": "rust"})));
+        assert_eq!(r#match, doc!{"$and": [{}, {"published": true}], "authorId": 1});
+        let mut r#match = doc!{"$text": {"$search": "rust"}, "$and": [{"$text": {"$search": "go"}}]};
+        assert!(Aggregation::take_text_search(&mut r#match).is_err());
+        let mut r#match = doc!{"$or": [{"$and": [{"$text": {"$search": "rust"}}]}, {"published": true}]};
+        assert!(Aggregation::take_text_search(&mut r#match).is_err());
+        let mut r#match = doc!{"published": true};
+        assert_eq!(Aggregation::take_text_search(&mut r#match).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn text_search_is_matched_before_relation_lookups() {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            m.field(id_field());
+        });
+        builder.model("Post", |m| {
+            let mut title = Field::new("title".to_owned());
+            title.field_type = Some(FieldType::String);
+            let mut author_id = Field::new("authorId".to_owned());
+            author_id.field_type = Some(FieldType::I32);
+            author_id.foreign_key = true;
+            let mut author = Relation::new("author");
+            author.set_model("User".to_owned());
+            author.set_fields(vec!["authorId".to_owned()]);
+            author.set_references(vec!["id".to_owned()]);
+            m.field(id_field()).field(title).field(author_id).relation(author).fulltext(["title"]);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let model = graph.model("Post").unwrap();
+        let finder = teon!({"where": {"author": {"is": {"id": 1}}, "title": {"search": "rust"}}});
+        let stages = Aggregation::build(model, &graph, &finder).unwrap();
+        assert_eq!(stages[0], doc!{"$match": {"$text": {"$search": "rust"}}});
+        assert!(stages[1].contains_key("$lookup"));
+        assert!(stages.iter().skip(1).all(|stage| !stage.get_document("$match").map_or(false, |m| m.contains_key("$text"))));
+    }
+
+    #[tokio::test]
+    async fn not_negates_a_nested_filter() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let post = graph.model("Post").unwrap();
+        let r#where = teon!({"title": {"not": {"in": ["ARCHIVED", "DRAFT"]}}});
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{"title": {"$not": {"$in": ["ARCHIVED", "DRAFT"]}}});
+        let r#where = teon!({"authorId": {"not": {"gte": 5}}});
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{"authorId": {"$not": {"$gte": 5}}});
+        let r#where = teon!({"authorId": {"not": 5}});
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{"authorId": {"$ne": 5}});
+    }
+
+    #[tokio::test]
+    async fn enum_in_and_not_in_keep_each_choice() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let post = graph.model("Post").unwrap();
+        let r#where = teon!({"status": {"in": ["ACTIVE", "PENDING"]}});
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{"status": {"$in": ["ACTIVE", "PENDING"]}});
+        let r#where = teon!({"status": {"notIn": ["ARCHIVED"]}});
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{"status": {"$nin": ["ARCHIVED"]}});
+        let r#where = teon!({"status": {"not": {"in": ["ARCHIVED", "PENDING"]}}});
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{"status": {"$not": {"$in": ["ARCHIVED", "PENDING"]}}});
+    }
+
+    #[tokio::test]
+    async fn datetime_comparisons_use_mongodb_operators() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let post = graph.model("Post").unwrap();
+        let earlier = Utc.with_ymd_and_hms(2023, 3, 16, 0, 0, 0).unwrap();
+        let later = Utc.with_ymd_and_hms(2023, 4, 1, 12, 30, 0).unwrap();
+        let r#where = Value::HashMap(HashMap::from([
+            ("publishedAt".to_owned(), Value::HashMap(HashMap::from([
+                ("gt".to_owned(), Value::DateTime(earlier)),
+                ("lte".to_owned(), Value::DateTime(later)),
+            ]))),
+        ]));
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{
+            "publishedAt": {"$gt": Bson::DateTime(earlier.into()), "$lte": Bson::DateTime(later.into())},
+        });
+        let r#where = Value::HashMap(HashMap::from([
+            ("publishedAt".to_owned(), Value::HashMap(HashMap::from([("not".to_owned(), Value::DateTime(earlier))]))),
+        ]));
+        assert_eq!(Aggregation::build_where(post, &graph, &r#where).unwrap(), doc!{"publishedAt": {"$ne": Bson::DateTime(earlier.into())}});
+    }
+
+    #[test]
+    fn count_stages_skip_and_limit_before_counting() {
+        let stages = Aggregation::build_count(&teon!({"skip": 5, "take": -10}));
+        assert_eq!(stages, vec![doc!{"$skip": 5i64}, doc!{"$limit": 10i64}, doc!{"$count": "count"}]);
+        let stages = Aggregation::build_count(&teon!({"pageSize": 10, "pageNumber": 3}));
+        assert_eq!(stages, vec![doc!{"$skip": 20i64}, doc!{"$limit": 10i64}, doc!{"$count": "count"}]);
+        assert_eq!(Aggregation::build_count(&teon!({})), vec![doc!{"$count": "count"}]);
+    }
+
+    #[test]
+    fn facet_counts_matched_records_beside_the_page() {
+        let stages = Aggregation::facet_data_and_count(
+            vec![doc!{"$match": {"published": true}}],
+            vec![doc!{"$skip": 20i64}, doc!{"$limit": 10i64}],
+        );
+        assert_eq!(stages, vec![
+            doc!{"$match": {"published": true}},
+            doc!{"$facet": {"data": [{"$skip": 20i64}, {"$limit": 10i64}], "count": [{"$count": "count"}]}},
+        ]);
+    }
+
+    #[test]
+    fn only_small_pages_are_counted_in_a_facet() {
+        assert!(Aggregation::counts_in_facet(&teon!({"skip": 20, "take": 10})));
+        assert!(Aggregation::counts_in_facet(&teon!({"take": -100})));
+        assert!(Aggregation::counts_in_facet(&teon!({"pageSize": 20, "pageNumber": 3})));
+        assert!(!Aggregation::counts_in_facet(&teon!({"take": 101})));
+        assert!(!Aggregation::counts_in_facet(&teon!({})));
+        assert!(!Aggregation::counts_in_facet(&teon!({"take": 10, "cursor": {"id": 5}})));
+        assert!(!Aggregation::counts_in_facet(&teon!({"take": 10, "distinct": ["email"]})));
+    }
+
+    #[test]
+    fn distinct_groups_by_a_single_column() {
+        let group_data = doc!{"__id": {"$first": "$_id"}, "email": {"$first": "$email"}, "name": {"$first": "$name"}};
+        let stages = Aggregation::build_distinct_group(&vec!["email".to_owned()], group_data);
+        assert_eq!(stages, vec![
+            doc!{"$group": {"__id": {"$first": "$_id"}, "email": {"$first": "$email"}, "name": {"$first": "$name"}, "_id": {"email": "$email"}}},
+            doc!{"$addFields": {"_id": "$__id"}},
+            doc!{"$unset": "__id"},
+        ]);
+    }
+
+    #[tokio::test]
+    async fn integer_filters_match_in_the_width_of_their_field() {
+        let mut builder = GraphBuilder::new();
+        builder.model("Counter", |m| {
+            let mut total = Field::new("total".to_owned());
+            total.field_type = Some(FieldType::I64);
+            m.field(id_field()).field(total);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let model = graph.model("Counter").unwrap();
+        let r#where = Value::HashMap(HashMap::from([
+            ("id".to_owned(), Value::HashMap(HashMap::from([("in".to_owned(), Value::Vec(vec![Value::I64(1), Value::I64(2)]))]))),
+            ("total".to_owned(), Value::HashMap(HashMap::from([("gte".to_owned(), Value::I32(10))]))),
+        ]));
+        assert_eq!(Aggregation::build_where(model, &graph, &r#where).unwrap(), doc!{
+            "id": {"$in": [Bson::Int32(1), Bson::Int32(2)]},
+            "total": {"$gte": Bson::Int64(10)},
+        });
+        // a value out of the 32 bit range is kept and matches no record
+        let r#where = Value::HashMap(HashMap::from([
+            ("id".to_owned(), Value::HashMap(HashMap::from([("equals".to_owned(), Value::I64(1 << 40))]))),
+        ]));
+        assert_eq!(Aggregation::build_where(model, &graph, &r#where).unwrap(), doc!{"id": {"$eq": Bson::Int64(1 << 40)}});
+    }
+
+    #[tokio::test]
+    async fn distinct_count_groups_before_counting() {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut email = Field::new("email".to_owned());
+            email.field_type = Some(FieldType::String);
+            m.field(id_field()).field(email);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let model = graph.model("User").unwrap();
+        let stages = Aggregation::build_for_count(model, &graph, &teon!({"distinct": ["email"], "skip": 2})).unwrap();
+        assert_eq!(stages, vec![
+            doc!{"$group": {"_id": {"email": "$email"}}},
+            doc!{"$skip": 2i64},
+            doc!{"$count": "count"},
+        ]);
+        let stages = Aggregation::build_for_count(model, &graph, &teon!({"skip": 2})).unwrap();
+        assert_eq!(stages, vec![doc!{"$skip": 2i64}, doc!{"$count": "count"}]);
+    }
+
+    #[tokio::test]
+    async fn distinct_is_limited_to_queryable_fields() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let model = graph.model("Post").unwrap();
+        assert!(Aggregation::build_for_count(model, &graph, &teon!({"distinct": ["title"]})).is_ok());
+        for key in ["author", "body"] {
+            let error = Aggregation::build_for_count(model, &graph, &teon!({"distinct": [key]})).unwrap_err();
+            assert_eq!(error.r#type, ErrorType::ValidationError);
+            assert_eq!(error.message(), "Unexpected value found.");
+        }
+    }
+}
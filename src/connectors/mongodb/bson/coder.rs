@@ -1,13 +1,20 @@
 use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use bigdecimal::BigDecimal;
 use bson::Bson;
+use indexmap::IndexMap;
 use key_path::KeyPath;
 
+use crate::connectors::mongodb::bson::decimal_128;
 use crate::core::error::Error;
 use crate::core::field::r#type::{FieldType, FieldTypeOwner};
 use crate::core::model::Model;
 use crate::core::result::Result;
 use crate::prelude::{Graph, Value};
 
+/// The deepest nesting of arrays and documents accepted when encoding.
+pub(crate) static MAX_BSON_DEPTH: usize = 100;
+
 pub(crate) struct BsonCoder { }
 
 impl BsonCoder {
@@ -16,14 +23,47 @@ impl BsonCoder {
         value.clone().into()
     }
 
-    pub(crate) fn encode<'a>(r#type: &FieldType, value: Value) -> Result<Bson> {
+    pub(crate) fn encode<'a>(r#type: &FieldType, value: Value, path: impl AsRef<KeyPath<'a>>) -> Result<Bson> {
+        Self::check_depth(&value, path.as_ref(), 0)?;
         match r#type {
-            FieldType::I32 => Ok(Bson::Int32(value.as_i32().unwrap())),
-            FieldType::I64 => Ok(Bson::Int64(value.as_i64().unwrap())),
+            FieldType::I32 | FieldType::I64 if value.is_null() => Ok(Bson::Null),
+            FieldType::I32 => match value.as_i64() {
+                Some(i) => match i32::try_from(i) {
+                    Ok(i) => Ok(Bson::Int32(i)),
+                    Err(_) => Err(Error::unexpected_input_value_with_reason(format!("Value {} is out of the range of a 32 bit integer.", i), path.as_ref())),
+                },
+                None => Err(Error::unexpected_input_value_with_reason("Value is not an integer.", path.as_ref())),
+            },
+            FieldType::I64 => match value.as_i64() {
+                Some(i) => Ok(Bson::Int64(i)),
+                None => Err(Error::unexpected_input_value_with_reason("Value is not an integer.", path.as_ref())),
+            },
+            FieldType::Decimal => match value.as_decimal() {
+                Some(decimal) => match decimal_128(&decimal) {
+                    Some(decimal) => Ok(Bson::Decimal128(decimal)),
+                    None => Err(Error::unexpected_input_value_with_reason(format!("Decimal {} has more significant digits or a larger exponent than a Decimal128 holds.", decimal), path.as_ref())),
+                },
+                None => Ok(value.into()),
+            },
             _ => Ok(value.into()),
         }
     }
 
+    fn check_depth(value: &Value, path: &KeyPath, depth: usize) -> Result<()> {
+        if depth > MAX_BSON_DEPTH {
+            return Err(Error::value_too_deep(MAX_BSON_DEPTH, path));
+        }
+        match value {
+            Value::Vec(vec) => for (i, v) in vec.iter().enumerate() { Self::check_depth(v, &(path + i), depth + 1)?; },
+            Value::HashMap(map) => for (k, v) in map { Self::check_depth(v, &(path + k), depth + 1)?; },
+            Value::BTreeMap(map) => for (k, v) in map { Self::check_depth(v, &(path + k), depth + 1)?; },
+            Value::IndexMap(map) => for (k, v) in map { Self::check_depth(v, &(path + k), depth + 1)?; },
+            Value::Object(_) => return Err(Error::invalid_operation("Objects are saved through their relations, not as values.")),
+            _ => (),
+        }
+        Ok(())
+    }
+
     pub(crate) fn decode<'a>(model: &Model, graph: &Graph, r#type: &FieldType, optional: bool, bson_value: &Bson, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         if bson_value.as_null().is_some() && optional {
             return Ok(Value::Null);
@@ -54,7 +94,10 @@ impl BsonCoder {
                 Some(n) => Ok(Value::F64(n)),
                 None => Err(Error::record_decoding_error(model.name(), path, "double")),
             }
-            FieldType::Decimal => panic!("Decimal is not implemented by MongoDB."),
+            FieldType::Decimal => match Self::decode_decimal(bson_value) {
+                Some(d) => Ok(Value::Decimal(d)),
+                None => Err(Error::record_decoding_error(model.name(), path, "decimal 128")),
+            }
             FieldType::String => match bson_value.as_str() {
                 Some(s) => Ok(Value::String(s.to_owned())),
                 None => Err(Error::record_decoding_error(model.name(), path, "string")),
@@ -104,7 +147,123 @@ impl BsonCoder {
                     None => Err(Error::record_decoding_error(model.name(), path, "document")),
                 }
             }
-            FieldType::Object(_) => panic!("Saving embedded object into database is not implemented yet.")
+            FieldType::Object(_) => panic!("Saving embedded object into database is not implemented yet."),
+            FieldType::Json => match Self::decode_json(bson_value) {
+                Some(value) => Ok(value),
+                None => Err(Error::record_decoding_error(model.name(), path, "json")),
+            }
+        }
+    }
+
+    /// Decodes a document stored from JSON, `None` for non-JSON bson types.
+    fn decode_json(bson_value: &Bson) -> Option<Value> {
+        match bson_value {
+            Bson::Null => Some(Value::Null),
+            Bson::Boolean(b) => Some(Value::Bool(*b)),
+            Bson::Int32(i) => Some(Value::I64(*i as i64)),
+            Bson::Int64(i) => Some(Value::I64(*i)),
+            Bson::Double(f) => Some(Value::F64(*f)),
+            Bson::String(s) => Some(Value::String(s.clone())),
+            Bson::Array(vec) => vec.iter().map(|v| Self::decode_json(v)).collect::<Option<Vec<Value>>>().map(Value::Vec),
+            Bson::Document(doc) => doc.iter().map(|(k, v)| Self::decode_json(v).map(|v| (k.clone(), v))).collect::<Option<IndexMap<String, Value>>>().map(Value::IndexMap),
+            _ => None,
+        }
+    }
+
+    fn decode_decimal(bson_value: &Bson) -> Option<BigDecimal> {
+        match bson_value {
+            Bson::Decimal128(d) => BigDecimal::from_str(&d.to_string()).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{doc, Decimal128};
+    use key_path::path;
+    use crate::core::error::ErrorType;
+    use crate::teon;
+    use super::*;
+
+    #[test]
+    fn decimal_round_trips_through_decimal_128() {
+        let price = BigDecimal::from_str("1234.50").unwrap();
+        let bson = BsonCoder::encode(&FieldType::Decimal, Value::Decimal(price.clone()), path![]).unwrap();
+        assert_eq!(bson, Bson::Decimal128(Decimal128::from_str("1234.5").unwrap()));
+        assert_eq!(BsonCoder::decode_decimal(&bson), Some(price));
+        assert_eq!(BsonCoder::decode_decimal(&Bson::Double(1234.5)), None);
+    }
+
+    #[test]
+    fn decimal_with_more_digits_than_decimal_128_is_an_error() {
+        let digits = BigDecimal::from_str("1.23456789012345678901234567890123456").unwrap();
+        let err = BsonCoder::encode(&FieldType::Decimal, Value::Decimal(digits.clone()), path!["price"]).unwrap_err();
+        assert_eq!(err.r#type, ErrorType::ValidationError);
+        assert!(err.errors.unwrap().contains_key("price"));
+        // a filter bound is rounded instead
+        assert_eq!(Bson::from(&Value::Decimal(digits)), Bson::Decimal128(Decimal128::from_str("1.234567890123456789012345678901235").unwrap()));
+        let digits = BigDecimal::from_str("1.234567890123456789012345678901234").unwrap();
+        assert!(BsonCoder::encode(&FieldType::Decimal, Value::Decimal(digits), path!["price"]).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of the exponent range of a Decimal128")]
+    fn decimal_out_of_the_decimal_128_exponent_range_is_never_converted_lossily() {
+        let huge = BigDecimal::from_str("1e7000").unwrap();
+        let _: Bson = Value::Decimal(huge).into();
+    }
+
+    #[test]
+    fn decimal_range_filter_compares_decimal_128_values() {
+        let low = BigDecimal::from_str("9.99").unwrap();
+        let high = BigDecimal::from_str("100").unwrap();
+        let filter = teon!({"gte": Value::Decimal(low), "lt": Value::Decimal(high)});
+        let expected = doc!{"gte": Decimal128::from_str("9.99").unwrap(), "lt": Decimal128::from_str("100").unwrap()};
+        assert_eq!(Bson::from(&filter), Bson::Document(expected));
+    }
+
+    #[test]
+    fn integers_are_stored_in_their_declared_width() {
+        assert_eq!(BsonCoder::encode(&FieldType::I32, Value::I64(30), path![]).unwrap(), Bson::Int32(30));
+        assert_eq!(BsonCoder::encode(&FieldType::I64, Value::I32(30), path![]).unwrap(), Bson::Int64(30));
+        assert!(BsonCoder::encode(&FieldType::I32, Value::I64(1 << 40), path![]).is_err());
+        assert_eq!(BsonCoder::encode(&FieldType::I32, Value::Null, path![]).unwrap(), Bson::Null);
+        assert!(BsonCoder::encode(&FieldType::I64, Value::String("30".to_owned()), path!["count"]).is_err());
+    }
+
+    #[test]
+    fn json_round_trips_through_a_document() {
+        let json = serde_json::json!({"tags": ["a", 1], "meta": {"ok": true, "ratio": 0.5}});
+        let bson = BsonCoder::encode(&FieldType::Json, Value::from(&json), path![]).unwrap();
+        assert_eq!(bson, Bson::Document(doc!{"tags": ["a", 1i64], "meta": {"ok": true, "ratio": 0.5}}));
+        let decoded: serde_json::Value = BsonCoder::decode_json(&bson).unwrap().into();
+        assert_eq!(decoded, json);
+        assert_eq!(BsonCoder::decode_json(&Bson::Timestamp(bson::Timestamp { time: 0, increment: 0 })), None);
+    }
+
+    #[test]
+    fn deeply_nested_array_is_rejected() {
+        let mut value = Value::I32(1);
+        for _ in 0..1000 {
+            value = Value::Vec(vec![value]);
+        }
+        assert_eq!(BsonCoder::encode(&FieldType::String, value, path!["tags"]).unwrap_err().r#type, ErrorType::ValueTooDeep);
+        let mut shallow = Value::I32(1);
+        for _ in 0..10 {
+            shallow = Value::Vec(vec![shallow]);
+        }
+        assert!(BsonCoder::encode(&FieldType::String, shallow, path![]).is_ok());
+    }
+
+    #[test]
+    fn too_deep_error_has_the_path_and_the_limit() {
+        let mut value = Value::I32(1);
+        for _ in 0..=MAX_BSON_DEPTH {
+            value = teon!({"a": value});
         }
+        let error = BsonCoder::encode(&FieldType::Json, value, path!["meta"]).unwrap_err();
+        let key = format!("meta{}", ".a".repeat(MAX_BSON_DEPTH + 1));
+        assert_eq!(error.errors.unwrap().get(&key).unwrap(), &format!("Expect at most {} levels of nesting.", MAX_BSON_DEPTH));
     }
 }
@@ -1,9 +1,20 @@
-use bson::Bson;
+use std::str::FromStr;
+use bigdecimal::BigDecimal;
+use bson::{Bson, Decimal128};
 use bson::datetime::{DateTime as BsonDateTime};
 use crate::prelude::Value;
 
 pub(crate) mod coder;
 
+/// The most significant digits a Decimal128 holds.
+pub(crate) static MAX_DECIMAL_128_DIGITS: u64 = 34;
+
+/// The decimal as a Decimal128, `None` when it has more significant digits
+/// or a larger exponent than a Decimal128 holds.
+pub(crate) fn decimal_128(decimal: &BigDecimal) -> Option<Decimal128> {
+    Decimal128::from_str(&decimal.normalized().to_string()).ok()
+}
+
 impl Into<Bson> for Value {
     fn into(self) -> Bson {
         match self {
@@ -14,7 +25,12 @@ impl Into<Bson> for Value {
             Value::I64(i) => Bson::Int64(i),
             Value::F32(f) => Bson::Double(f as f64),
             Value::F64(f) => Bson::Double(f as f64),
-            Value::Decimal(_d) => panic!("Decimal is not implemented by MongoDB."),
+            // decimals of records are checked when they're decoded, other
+            // values like filter bounds are rounded to a Decimal128
+            Value::Decimal(d) => match decimal_128(&d).or_else(|| decimal_128(&d.with_prec(MAX_DECIMAL_128_DIGITS))) {
+                Some(decimal) => Bson::Decimal128(decimal),
+                None => panic!("Decimal {} is out of the exponent range of a Decimal128.", d),
+            },
             Value::String(s) => Bson::String(s),
             Value::Date(val) => Bson::DateTime(BsonDateTime::parse_rfc3339_str(val.format("%Y-%m-%d").to_string()).unwrap()),
             Value::DateTime(val) => Bson::DateTime(BsonDateTime::from(val)),
@@ -1,29 +1,37 @@
 pub mod save_session;
+pub(crate) mod pool_events;
 
+use std::collections::HashMap;
 use std::fmt::{Debug};
 use std::ops::Neg;
 use std::sync::Arc;
 use std::sync::atomic::{Ordering};
 use async_trait::async_trait;
+use bigdecimal::BigDecimal;
 use bson::{Bson, doc, Document};
 use futures_util::StreamExt;
 use key_path::path;
 use mongodb::{options::ClientOptions, Client, Database, Collection, IndexModel};
 use mongodb::error::{ErrorKind, WriteFailure, Error as MongoDBError};
-use mongodb::options::{FindOneAndUpdateOptions, IndexOptions, ReturnDocument};
+use mongodb::options::{AggregateOptions, FindOneAndUpdateOptions, IndexOptions, ReturnDocument};
 use regex::Regex;
 use crate::connectors::mongodb::aggregation::Aggregation;
 use crate::connectors::mongodb::bson::coder::BsonCoder;
+use crate::connectors::mongodb::bson::decimal_128;
+use crate::connectors::mongodb::connector::pool_events::PoolEventHandler;
 use crate::connectors::mongodb::connector::save_session::MongoDBSaveSession;
+use crate::connectors::mongodb::migration::index_model::case_insensitive_collation;
 use crate::core::action::{Action, FIND, MANY, NESTED, SINGLE};
 use crate::core::action::source::ActionSource;
-use crate::core::connector::Connector;
+use crate::core::connector::{count_finder, Connector};
 use crate::core::object::Object;
 use crate::core::field::Sort;
+use crate::core::field::sequence::SEQUENCE_COUNTERS;
 use crate::core::graph::Graph;
 use crate::core::model::{Model};
 use crate::core::model::index::{ModelIndex, ModelIndexType};
 use crate::core::connector::SaveSession;
+use crate::core::database::pool_metrics::PoolMetrics;
 use crate::core::database::r#type::DatabaseType;
 use crate::core::teon::Value;
 use crate::core::error::Error;
@@ -37,11 +45,12 @@ pub struct MongoDBConnector {
     loaded: bool,
     client: Client,
     database: Database,
+    pool_metrics: Arc<PoolMetrics>,
 }
 
 impl MongoDBConnector {
     pub(crate) async fn new(url: String) -> MongoDBConnector {
-        let options = match ClientOptions::parse(url).await {
+        let mut options = match ClientOptions::parse(url).await {
             Ok(options) => options,
             Err(_) => panic!("MongoDB url is invalid.")
         };
@@ -49,6 +58,8 @@ impl MongoDBConnector {
             Some(database_name) => database_name,
             None => panic!("No database name found in MongoDB url.")
         };
+        let pool_metrics = Arc::new(PoolMetrics::with_open_connections());
+        options.cmap_event_handler = Some(Arc::new(PoolEventHandler::new(pool_metrics.clone())));
         let client = match Client::with_options(options.clone()) {
             Ok(client) => client,
             Err(_) => panic!("MongoDB client creating error.")
@@ -62,6 +73,7 @@ impl MongoDBConnector {
             loaded: false,
             client,
             database,
+            pool_metrics,
         }
     }
 
@@ -69,6 +81,69 @@ impl MongoDBConnector {
         self.database.collection(name)
     }
 
+    /// The key value of an index item, fulltext indices are compound `$text` indices.
+    fn index_key_value(index: &ModelIndex, sort: Sort) -> Bson {
+        if index.r#type().is_fulltext() {
+            Bson::String("text".to_owned())
+        } else if sort == Sort::Asc {
+            Bson::Int32(1)
+        } else {
+            Bson::Int32(-1)
+        }
+    }
+
+    pub(crate) fn index_model(model: &Model, index: &ModelIndex) -> IndexModel {
+        let index_options = IndexOptions::builder()
+            .name(index.mongodb_name())
+            .unique(index.r#type() == ModelIndexType::Unique || index.r#type() == ModelIndexType::Primary)
+            .sparse(index.sparse())
+            .collation(if index.case_insensitive() { Some(case_insensitive_collation()) } else { None })
+            .build();
+        let mut keys = doc!{};
+        for item in index.items() {
+            let field = model.stored_field(item.field_name()).unwrap();
+            let column_name = field.column_name();
+            keys.insert(column_name, Self::index_key_value(index, item.sort()));
+        }
+        // live records index a null deletion time, tombstones never conflict with them
+        if let Some(column_name) = index.soft_delete() {
+            keys.insert(column_name, Bson::Int32(1));
+        }
+        IndexModel::builder().keys(keys).options(index_options).build()
+    }
+
+    async fn create_index(collection: &Collection<Document>, model: &Model, index: &ModelIndex) -> Result<()> {
+        match collection.create_index(Self::index_model(model, index), None).await {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::internal_server_error(format!("Cannot create index '{}' of model '{}': {}", index.mongodb_name(), model.name(), err))),
+        }
+    }
+
+    fn where_uses_case_insensitive_index(model: &Model, finder: &Value) -> bool {
+        match finder.get("where").and_then(|w| w.as_hashmap()) {
+            Some(r#where) => r#where.keys().any(|k| model.is_case_insensitive_field(k)),
+            None => false,
+        }
+    }
+
+    async fn documents_to_objects(&self, graph: &Graph, model: &Model, finder: &Value, documents: &Vec<Document>, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
+        let select = finder.get("select");
+        let include = finder.get("include");
+        let reverse = Input::has_negative_take(finder);
+        let mut result: Vec<Object> = vec![];
+        for doc in documents {
+            let obj = graph.new_object(model.name(), action, action_source.clone())?;
+            self.document_to_object(doc, &obj, select, include)?;
+            obj.trigger_after_find_callbacks().await?;
+            if reverse {
+                result.insert(0, obj);
+            } else {
+                result.push(obj);
+            }
+        }
+        Ok(result)
+    }
+
     fn document_to_object(&self, document: &Document, object: &Object, select: Option<&Value>, include: Option<&Value>) -> Result<()> {
         for key in document.keys() {
             let object_field = object.model().fields().iter().find(|f| f.column_name() == key);
@@ -110,12 +185,18 @@ impl MongoDBConnector {
                 };
                 let relation = relation.unwrap();
                 let model_name = relation.model();
-                let object_bsons = document.get(key).unwrap().as_array().unwrap();
+                // included to-many relations are arrays, to-one relations are
+                // flattened into a single document or null
+                let related_documents: Vec<&Document> = match document.get(key).unwrap() {
+                    Bson::Array(array) => array.iter().filter_map(|b| b.as_document()).collect(),
+                    Bson::Document(document) => vec![document],
+                    _ => vec![],
+                };
                 let mut related: Vec<Object> = vec![];
-                for related_object_bson in object_bsons {
+                for related_object_document in related_documents {
                     let action = Action::from_u32(NESTED | FIND | (if relation.is_vec() { MANY } else { SINGLE }));
                     let related_object = object.graph().new_object(model_name, action, object.action_source().clone())?;
-                    self.document_to_object(related_object_bson.as_document().unwrap(), &related_object, inner_select, inner_include)?;
+                    self.document_to_object(related_object_document, &related_object, inner_select, inner_include)?;
                     related.push(related_object);
                 }
                 object.inner.relation_query_map.lock().unwrap().insert(key.to_string(), related);
@@ -157,10 +238,9 @@ impl MongoDBConnector {
 
     async fn aggregate_or_group_by(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<Vec<Value>> {
         let aggregate_input = Aggregation::build_for_aggregate(model, graph, finder)?;
-        let col = self.get_collection(model.name());
+        let col = self.get_collection(model.storage_name());
         let cur = col.aggregate(aggregate_input, None).await;
         if cur.is_err() {
-            println!("{:?}", cur);
             return Err(Error::unknown_database_find_error());
         }
         let cur = cur.unwrap();
@@ -207,19 +287,19 @@ impl MongoDBConnector {
     async fn create_object(&self, object: &Object) -> Result<()> {
         let model = object.model();
         let keys = object.keys_for_save();
-        let col = self.get_collection(model.name());
+        let col = self.get_collection(model.storage_name());
         let auto_keys = model.auto_keys();
         // create
         let mut doc = doc!{};
         for key in keys {
             if let Some(field) = model.field(key) {
                 let column_name = field.column_name();
-                let val: Bson = BsonCoder::encode(field.field_type(), object.get_value(&key).unwrap())?;
+                let val: Bson = BsonCoder::encode(field.field_type(), object.get_value(&key).unwrap(), path![key])?;
                 if val != Bson::Null {
                     doc.insert(column_name, val);
                 }
             } else if let Some(property) = model.property(key) {
-                let val: Bson = BsonCoder::encode(property.field_type(), object.get_property(&key).await.unwrap())?;
+                let val: Bson = BsonCoder::encode(property.field_type(), object.get_property(&key).await.unwrap(), path![key])?;
                 if val != Bson::Null {
                     doc.insert(key, val);
                 }
@@ -244,10 +324,36 @@ impl MongoDBConnector {
         Ok(())
     }
 
+    /// An update pipeline applying the atomic updators to each document.
+    /// Divisions of integer fields are truncated like the SQL connector does.
+    fn atomic_update_pipeline(updators: &Vec<(&str, &FieldType, &Value)>) -> Vec<Document> {
+        let mut set = doc!{};
+        for (column_name, field_type, updator) in updators {
+            let (key, val) = Input::key_value(updator.as_hashmap().unwrap());
+            let current = doc!{"$ifNull": [format!("${}", column_name), 0]};
+            let expr = match key {
+                "increment" => doc!{"$add": [current, Bson::from(val)]},
+                "decrement" => doc!{"$subtract": [current, Bson::from(val)]},
+                "multiply" => doc!{"$multiply": [current, Bson::from(val)]},
+                "divide" => {
+                    let quotient = doc!{"$divide": [current, Bson::from(val)]};
+                    match field_type {
+                        FieldType::I32 => doc!{"$toInt": {"$trunc": quotient}},
+                        FieldType::I64 => doc!{"$toLong": {"$trunc": quotient}},
+                        _ => quotient,
+                    }
+                }
+                _ => panic!("Unhandled key."),
+            };
+            set.insert(*column_name, expr);
+        }
+        vec![doc!{"$set": set}]
+    }
+
     async fn update_object(&self, object: &Object) -> Result<()> {
         let model = object.model();
         let keys = object.keys_for_save();
-        let col = self.get_collection(model.name());
+        let col = self.get_collection(model.storage_name());
         let identifier: Bson = object.db_identifier().into();
         let identifier = identifier.as_document().unwrap();
         let mut set = doc!{};
@@ -269,7 +375,7 @@ impl MongoDBConnector {
                         _ => panic!("Unhandled key."),
                     };
                 } else {
-                    let bson_val: Bson = BsonCoder::encode(field.field_type(), object.get_value(&key).unwrap())?;
+                    let bson_val: Bson = BsonCoder::encode(field.field_type(), object.get_value(&key).unwrap(), path![key])?;
                     if bson_val == Bson::Null {
                         unset.insert(key, bson_val);
                     } else {
@@ -277,7 +383,7 @@ impl MongoDBConnector {
                     }
                 }
             } else if let Some(property) = model.property(key) {
-                let bson_val: Bson = BsonCoder::encode(property.field_type(), object.get_property(&key).await.unwrap())?;
+                let bson_val: Bson = BsonCoder::encode(property.field_type(), object.get_property(&key).await.unwrap(), path![key])?;
                 if bson_val != Bson::Null {
                     set.insert(key, bson_val);
                 } else {
@@ -340,6 +446,10 @@ impl MongoDBConnector {
 
 #[async_trait]
 impl Connector for MongoDBConnector {
+    fn decimal_is_storable(&self, decimal: &BigDecimal) -> bool {
+        decimal_128(decimal).is_some()
+    }
+
     fn default_database_type(&self, field_type: &FieldType) -> DatabaseType {
         match field_type {
             FieldType::ObjectId => DatabaseType::ObjectId,
@@ -357,14 +467,18 @@ impl Connector for MongoDBConnector {
             FieldType::HashMap(_) => panic!(""),
             FieldType::BTreeMap(_) => panic!(""),
             FieldType::Object(_) => panic!(""),
+            FieldType::Json => DatabaseType::Json,
         }
     }
 
-    async fn migrate(&mut self, models: &Vec<Model>, reset_database: bool) -> Result<()> {
+    async fn migrate(&self, models: &Vec<Model>, reset_database: bool) -> Result<()> {
         if reset_database {
             let _ = self.database.drop(None).await;
         }
         for model in models {
+            // subtypes share their base model's collection, which holds their indices
+            if model.base().is_some() { continue }
+            let indices = model.stored_indices(models);
             let name = model.name();
             let collection = self.get_collection(name);
             let mut reviewed_names: Vec<String> = Vec::new();
@@ -376,63 +490,39 @@ impl Connector for MongoDBConnector {
                         continue
                     }
                     let name = (&index).options.as_ref().unwrap().name.as_ref().unwrap();
-                    let result = model.indices().iter().find(|i| &i.mongodb_name() == name);
+                    let result = indices.iter().find(|i| &i.mongodb_name() == name);
                     if result.is_none() {
                         // not in our model definition, but in the database
                         // drop this index
                         let _ = collection.drop_index(name, None).await.unwrap();
                     } else {
                         let result = result.unwrap();
-                        let our_format_index: ModelIndex = (&index).into();
+                        let mut our_format_index: ModelIndex = (&index).into();
+                        if let Some(column_name) = result.soft_delete() {
+                            our_format_index.extract_soft_delete(column_name);
+                        }
                         if result != &our_format_index {
                             // alter this index
                             // drop first
                             let _ = collection.drop_index(name, None).await.unwrap();
                             // create index
-                            let index_options = IndexOptions::builder()
-                                .name(result.mongodb_name())
-                                .unique(result.r#type() == ModelIndexType::Unique || result.r#type() == ModelIndexType::Primary)
-                                .sparse(true)
-                                .build();
-                            let mut keys = doc!{};
-                            for item in result.items() {
-                                let field = model.field(item.field_name()).unwrap();
-                                let column_name = field.column_name();
-                                keys.insert(column_name, if item.sort() == Sort::Asc { 1 } else { -1 });
-                            }
-                            let index_model = IndexModel::builder().keys(keys).options(index_options).build();
-                            let _result = collection.create_index(index_model, None).await;
+                            Self::create_index(&collection, model, result).await?;
                         }
                     }
                     reviewed_names.push(name.clone());
                 }
             }
-            for index in model.indices() {
+            for index in &indices {
                 if !reviewed_names.contains(&index.mongodb_name()) {
                     // ignore primary
                     if index.keys().len() == 1 {
-                        let field = model.field(index.keys().get(0).unwrap()).unwrap();
+                        let field = model.stored_field(index.keys().get(0).unwrap()).unwrap();
                         if field.column_name() == "_id" {
                             continue
                         }
                     }
                     // create this index
-                    let index_options = IndexOptions::builder()
-                        .name(index.mongodb_name())
-                        .unique(index.r#type() == ModelIndexType::Unique || index.r#type() == ModelIndexType::Primary)
-                        .sparse(true)
-                        .build();
-                    let mut keys = doc!{};
-                    for item in index.items() {
-                        let field = model.field(item.field_name()).unwrap();
-                        let column_name = field.column_name();
-                        keys.insert(column_name, if item.sort() == Sort::Asc { 1 } else { -1 });
-                    }
-                    let index_model = IndexModel::builder().keys(keys).options(index_options).build();
-                    let result = collection.create_index(index_model, None).await;
-                    if result.is_err() {
-                        println!("index create error: {:?}", result.err().unwrap());
-                    }
+                    Self::create_index(&collection, model, index).await?;
                 }
             }
         }
@@ -456,12 +546,29 @@ impl Connector for MongoDBConnector {
         }
     }
 
+    async fn update_many_atomically(&self, model: &Model, objects: &Vec<Object>, updators: &HashMap<String, Value>) -> Result<()> {
+        if objects.is_empty() {
+            return Ok(());
+        }
+        let col = self.get_collection(model.storage_name());
+        let identifiers: Vec<Bson> = objects.iter().map(|o| o.db_identifier().into()).collect();
+        let updators: Vec<(&str, &FieldType, &Value)> = updators.iter().map(|(k, v)| {
+            let field = model.field(k).unwrap();
+            (field.column_name(), field.field_type(), v)
+        }).collect();
+        let result = col.update_many(doc!{"$or": identifiers}, Self::atomic_update_pipeline(&updators), None).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(error) => Err(self._handle_write_error(&error.kind, &objects[0])),
+        }
+    }
+
     async fn delete_object(&self, object: &Object, _session: Arc<dyn SaveSession>) -> Result<()> {
         if object.inner.is_new.load(Ordering::SeqCst) {
             return Err(Error::object_is_not_saved_thus_cant_be_deleted());
         }
         let model = object.model();
-        let col = self.get_collection(model.name());
+        let col = self.get_collection(model.storage_name());
         let bson_identifier: Bson = object.db_identifier().into();
         let document_identifier = bson_identifier.as_document().unwrap();
         let result = col.delete_one(document_identifier.clone(), None).await;
@@ -478,8 +585,14 @@ impl Connector for MongoDBConnector {
         let include = finder.get("include");
 
         let aggregate_input = Aggregation::build(model, graph, finder)?;
-        let col = self.get_collection(model.name());
-        let cur = col.aggregate(aggregate_input, None).await;
+        let col = self.get_collection(model.storage_name());
+        // match the collation of case-insensitive unique indices
+        let options = if Self::where_uses_case_insensitive_index(model, finder) {
+            Some(AggregateOptions::builder().collation(Some(case_insensitive_collation())).build())
+        } else {
+            None
+        };
+        let cur = col.aggregate(aggregate_input, options).await;
         if cur.is_err() {
             return Err(Error::unknown_database_find_unique_error());
         }
@@ -491,17 +604,15 @@ impl Connector for MongoDBConnector {
         for doc in results {
             let obj = graph.new_object(model.name(), action, action_source.clone())?;
             self.document_to_object(&doc.unwrap(), &obj, select, include)?;
+            obj.trigger_after_find_callbacks().await?;
             return Ok(obj);
         }
         Err(Error::object_not_found())
     }
 
     async fn find_many(&self, graph: &Graph, model: &Model, finder: &Value, _mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
-        let select = finder.get("select");
-        let include = finder.get("include");
         let aggregate_input = Aggregation::build(model, graph, finder)?;
-        let reverse = Input::has_negative_take(finder);
-        let col = self.get_collection(model.name());
+        let col = self.get_collection(model.storage_name());
         // println!("see aggregate input: {:?}", aggregate_input);
         let cur = col.aggregate(aggregate_input, None).await;
         if cur.is_err() {
@@ -509,29 +620,50 @@ impl Connector for MongoDBConnector {
             return Err(Error::unknown_database_find_error());
         }
         let cur = cur.unwrap();
-        let mut result: Vec<Object> = vec![];
         let results: Vec<std::result::Result<Document, MongoDBError>> = cur.collect().await;
-        for doc in results {
-            let obj = graph.new_object(model.name(), action, action_source.clone())?;
-            match self.document_to_object(&doc.unwrap(), &obj, select, include) {
-                Ok(_) => {
-                    if reverse {
-                        result.insert(0, obj);
-                    } else {
-                        result.push(obj);
-                    }
-                }
-                Err(err) => {
-                    return Err(err);
-                }
+        let documents: Vec<Document> = results.into_iter().map(|r| r.unwrap()).collect();
+        self.documents_to_objects(graph, model, finder, &documents, action, action_source).await
+    }
+
+    async fn find_many_and_count(&self, graph: &Graph, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<(Vec<Object>, usize)> {
+        let aggregate_input = match Aggregation::build_with_count(model, graph, finder)? {
+            Some(aggregate_input) => aggregate_input,
+            None => {
+                let objects = self.find_many(graph, model, finder, mutation_mode, action, action_source).await?;
+                let count = self.count(graph, model, &count_finder(finder)).await?;
+                return Ok((objects, count));
             }
-        }
-        Ok(result)
+        };
+        let col = self.get_collection(model.storage_name());
+        let cur = match col.aggregate(aggregate_input, None).await {
+            Ok(cur) => cur,
+            Err(_) => return Err(Error::unknown_database_find_error()),
+        };
+        let results: Vec<std::result::Result<Document, MongoDBError>> = cur.collect().await;
+        let facet = match results.into_iter().next() {
+            Some(Ok(facet)) => facet,
+            Some(Err(_)) => return Err(Error::unknown_database_find_error()),
+            None => return Ok((vec![], 0)),
+        };
+        let documents: Vec<Document> = match facet.get_array("data") {
+            Ok(data) => data.iter().filter_map(|d| d.as_document().cloned()).collect(),
+            Err(_) => return Err(Error::unknown_database_find_error()),
+        };
+        let count = match facet.get_array("count").ok().and_then(|count| count.first()) {
+            Some(Bson::Document(c)) => match c.get("count") {
+                Some(Bson::Int32(i)) => *i as usize,
+                Some(Bson::Int64(i)) => *i as usize,
+                _ => return Err(Error::unknown_database_count_error()),
+            },
+            _ => 0,
+        };
+        let objects = self.documents_to_objects(graph, model, finder, &documents, action, action_source).await?;
+        Ok((objects, count))
     }
 
     async fn count(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<usize> {
         let input = Aggregation::build_for_count(model, graph, finder)?;
-        let col = self.get_collection(model.name());
+        let col = self.get_collection(model.storage_name());
         let cur = col.aggregate(input, None).await;
         if cur.is_err() {
             println!("{:?}", cur);
@@ -557,7 +689,7 @@ impl Connector for MongoDBConnector {
         if results.is_empty() {
             // there is no record
             let mut retval = teon!({});
-            for (g, o) in finder.as_hashmap().unwrap() {
+            for (g, o) in finder.as_hashmap().unwrap().iter().filter(|(g, _)| g.starts_with("_")) {
                 retval.as_hashmap_mut().unwrap().insert(g.clone(), teon!({}));
                 for (k, _v) in o.as_hashmap().unwrap() {
                     let value = if g == "_count" { teon!(0) } else { teon!(null) };
@@ -574,10 +706,48 @@ impl Connector for MongoDBConnector {
         Ok(Value::Vec(self.aggregate_or_group_by(graph, model, finder).await?))
     }
 
+    async fn increment_sequence(&self, name: &str) -> Result<i64> {
+        let col = self.get_collection(SEQUENCE_COUNTERS);
+        let options = FindOneAndUpdateOptions::builder().upsert(true).return_document(ReturnDocument::After).build();
+        match col.find_one_and_update(doc!{"_id": name}, doc!{"$inc": {"value": 1i64}}, options).await {
+            Ok(Some(document)) => Ok(document.get_i64("value").unwrap()),
+            _ => Err(Error::unknown_database_write_error()),
+        }
+    }
+
     fn new_save_session(&self) -> Arc<dyn SaveSession> {
         Arc::new(MongoDBSaveSession {})
     }
+
+    fn pool_metrics(&self) -> &PoolMetrics {
+        self.pool_metrics.as_ref()
+    }
 }
 
 unsafe impl Sync for MongoDBConnector {}
 unsafe impl Send for MongoDBConnector {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atomic_updators_become_an_update_pipeline() {
+        let increment = teon!({"increment": 1});
+        let divide = teon!({"divide": 2.0});
+        let pipeline = MongoDBConnector::atomic_update_pipeline(&vec![("likes", &FieldType::I32, &increment), ("score", &FieldType::F64, &divide)]);
+        assert_eq!(pipeline, vec![doc!{"$set": {
+            "likes": {"$add": [{"$ifNull": ["$likes", 0]}, 1]},
+            "score": {"$divide": [{"$ifNull": ["$score", 0]}, 2.0]},
+        }}]);
+    }
+
+    #[test]
+    fn dividing_integers_truncates_the_quotient() {
+        let divide = teon!({"divide": 3});
+        let pipeline = MongoDBConnector::atomic_update_pipeline(&vec![("stock", &FieldType::I32, &divide)]);
+        assert_eq!(pipeline, vec![doc!{"$set": {
+            "stock": {"$toInt": {"$trunc": {"$divide": [{"$ifNull": ["$stock", 0]}, 3]}}},
+        }}]);
+    }
+}
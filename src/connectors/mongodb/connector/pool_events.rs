@@ -0,0 +1,52 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use mongodb::event::cmap::{CmapEventHandler, ConnectionCheckedInEvent, ConnectionCheckedOutEvent, ConnectionCheckoutFailedEvent, ConnectionCheckoutFailedReason, ConnectionCheckoutStartedEvent, ConnectionClosedEvent, ConnectionCreatedEvent};
+use crate::core::database::pool_metrics::PoolMetrics;
+
+/// Records the driver's connection pool events into `PoolMetrics`. The driver
+/// serves checkouts in order, so each finished checkout is matched with the
+/// earliest pending start to measure its wait.
+pub(crate) struct PoolEventHandler {
+    metrics: Arc<PoolMetrics>,
+    pending: Mutex<VecDeque<Instant>>,
+}
+
+impl PoolEventHandler {
+
+    pub(crate) fn new(metrics: Arc<PoolMetrics>) -> Self {
+        Self { metrics, pending: Mutex::new(VecDeque::new()) }
+    }
+
+    fn started_at(&self) -> Instant {
+        self.pending.lock().unwrap().pop_front().unwrap_or_else(Instant::now)
+    }
+}
+
+impl CmapEventHandler for PoolEventHandler {
+
+    fn handle_connection_created_event(&self, _event: ConnectionCreatedEvent) {
+        self.metrics.record_opened();
+    }
+
+    fn handle_connection_closed_event(&self, _event: ConnectionClosedEvent) {
+        self.metrics.record_closed();
+    }
+
+    fn handle_connection_checkout_started_event(&self, _event: ConnectionCheckoutStartedEvent) {
+        self.pending.lock().unwrap().push_back(Instant::now());
+    }
+
+    fn handle_connection_checkout_failed_event(&self, event: ConnectionCheckoutFailedEvent) {
+        let timeout = matches!(event.reason, ConnectionCheckoutFailedReason::Timeout);
+        self.metrics.record_checkout_failed(self.started_at().elapsed(), timeout);
+    }
+
+    fn handle_connection_checked_out_event(&self, _event: ConnectionCheckedOutEvent) {
+        self.metrics.record_checked_out(self.started_at().elapsed());
+    }
+
+    fn handle_connection_checked_in_event(&self, _event: ConnectionCheckedInEvent) {
+        self.metrics.record_checked_in();
+    }
+}
@@ -1,4 +1,5 @@
 use mongodb::IndexModel;
+use mongodb::options::{Collation, CollationStrength};
 use crate::core::field::Sort;
 use crate::core::model::index::{ModelIndex, ModelIndexItem, ModelIndexType};
 
@@ -9,11 +10,90 @@ impl From<&IndexModel> for ModelIndex {
             Some(bool) => bool,
             None => false
         };
+        let sparse = index_model.options.as_ref().unwrap().sparse.unwrap_or(false);
+        let case_insensitive = index_model.options.as_ref().unwrap().collation.as_ref().map_or(false, |c| {
+            matches!(c.strength, Some(CollationStrength::Secondary))
+        });
         let mut items: Vec<ModelIndexItem> = Vec::new();
-        for (k, v) in &index_model.keys {
-            let item = ModelIndexItem::new(k, if v.as_i32().unwrap() == 1 { Sort::Asc } else { Sort::Desc }, None);
-            items.push(item);
+        // text indices are listed as `{_fts: "text", _ftsx: 1}`, their fields are the weights' keys
+        let fulltext = index_model.keys.contains_key("_fts");
+        if fulltext {
+            if let Some(weights) = index_model.options.as_ref().unwrap().weights.as_ref() {
+                for (k, _) in weights {
+                    items.push(ModelIndexItem::new(k, Sort::Asc, None));
+                }
+            }
+        } else {
+            for (k, v) in &index_model.keys {
+                let item = ModelIndexItem::new(k, if v.as_i32().unwrap() == 1 { Sort::Asc } else { Sort::Desc }, None);
+                items.push(item);
+            }
         }
-        ModelIndex::new(if unique { ModelIndexType::Unique } else { ModelIndexType::Index }, Some(index_model.options.as_ref().unwrap().name.as_ref().unwrap().to_string()), items)
+        let r#type = if unique { ModelIndexType::Unique } else if fulltext { ModelIndexType::Fulltext } else { ModelIndexType::Index };
+        let mut index = ModelIndex::new(r#type, Some(index_model.options.as_ref().unwrap().name.as_ref().unwrap().to_string()), items);
+        index.set_sparse(sparse);
+        index.set_case_insensitive(case_insensitive);
+        index
+    }
+}
+
+/// The collation of case-insensitive indices. Queries must use the same collation to hit them.
+pub(crate) fn case_insensitive_collation() -> Collation {
+    Collation::builder().locale("en").strength(CollationStrength::Secondary).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use mongodb::bson::doc;
+    use mongodb::options::IndexOptions;
+    use super::*;
+
+    #[test]
+    fn sparse_unique_index_round_trips() {
+        let options = IndexOptions::builder().name("email".to_owned()).unique(true).sparse(true).build();
+        let index_model = IndexModel::builder().keys(doc!{"email": 1}).options(options).build();
+        let mut expected = ModelIndex::new(ModelIndexType::Unique, Some("email"), vec![ModelIndexItem::new("email", Sort::Asc, None)]);
+        expected.set_sparse(true);
+        assert_eq!(ModelIndex::from(&index_model), expected);
+    }
+
+    #[test]
+    fn case_insensitive_index_round_trips() {
+        let options = IndexOptions::builder().name("email".to_owned()).unique(true).collation(Some(case_insensitive_collation())).build();
+        let index_model = IndexModel::builder().keys(doc!{"email": 1}).options(options).build();
+        let mut expected = ModelIndex::new(ModelIndexType::Unique, Some("email"), vec![ModelIndexItem::new("email", Sort::Asc, None)]);
+        expected.set_case_insensitive(true);
+        assert_eq!(ModelIndex::from(&index_model), expected);
+    }
+
+    #[test]
+    fn soft_delete_key_round_trips() {
+        let options = IndexOptions::builder().name("email".to_owned()).unique(true).build();
+        let index_model = IndexModel::builder().keys(doc!{"email": 1, "deletedAt": 1}).options(options).build();
+        let mut expected = ModelIndex::new(ModelIndexType::Unique, Some("email"), vec![ModelIndexItem::new("email", Sort::Asc, None)]);
+        expected.set_soft_delete(Some("deletedAt".to_owned()));
+        let mut index = ModelIndex::from(&index_model);
+        index.extract_soft_delete("deletedAt");
+        assert_eq!(index, expected);
+    }
+
+    #[test]
+    fn text_index_round_trips_as_fulltext() {
+        let options = IndexOptions::builder().name("title_body".to_owned()).weights(doc!{"title": 1, "body": 1}).build();
+        let index_model = IndexModel::builder().keys(doc!{"_fts": "text", "_ftsx": 1}).options(options).build();
+        let expected = ModelIndex::new(ModelIndexType::Fulltext, Some("title_body"), vec![
+            ModelIndexItem::new("title", Sort::Asc, None),
+            ModelIndexItem::new("body", Sort::Asc, None),
+        ]);
+        assert_eq!(ModelIndex::from(&index_model), expected);
+    }
+
+    #[test]
+    fn non_sparse_index_differs_from_sparse_one() {
+        let options = IndexOptions::builder().name("email".to_owned()).unique(true).build();
+        let index_model = IndexModel::builder().keys(doc!{"email": 1}).options(options).build();
+        let mut sparse = ModelIndex::new(ModelIndexType::Unique, Some("email"), vec![ModelIndexItem::new("email", Sort::Asc, None)]);
+        sparse.set_sparse(true);
+        assert_ne!(ModelIndex::from(&index_model), sparse);
     }
 }
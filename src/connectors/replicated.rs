@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use crate::core::action::Action;
+use crate::core::action::source::ActionSource;
+use crate::core::connector::{Connector, SaveSession};
+use crate::core::database::pool_metrics::PoolMetrics;
+use crate::core::database::r#type::DatabaseType;
+use crate::core::field::r#type::FieldType;
+use crate::core::graph::Graph;
+use crate::core::model::Model;
+use crate::core::object::Object;
+use crate::core::result::Result;
+use crate::prelude::Value;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Pool {
+    Primary,
+    Replica,
+}
+
+/// Routes reads to a read-only replica and everything else to the primary.
+///
+/// Reads made while mutating always use the primary. `replica_lag` is the
+/// replication lag which can't be tolerated: reads within this duration after
+/// a write use the primary too. A zero lag tolerates stale reads.
+pub(crate) struct ReplicatedConnector {
+    primary: Arc<dyn Connector>,
+    replica: Arc<dyn Connector>,
+    replica_lag: Duration,
+    last_write: Mutex<Option<Instant>>,
+}
+
+impl ReplicatedConnector {
+
+    pub(crate) fn new(primary: Arc<dyn Connector>, replica: Arc<dyn Connector>, replica_lag: Duration) -> Self {
+        Self { primary, replica, replica_lag, last_write: Mutex::new(None) }
+    }
+
+    pub(crate) fn route(&self, read: bool, mutation_mode: bool) -> Pool {
+        if !read {
+            *self.last_write.lock().unwrap() = Some(Instant::now());
+            return Pool::Primary;
+        }
+        if mutation_mode {
+            return Pool::Primary;
+        }
+        match *self.last_write.lock().unwrap() {
+            Some(last_write) if last_write.elapsed() < self.replica_lag => Pool::Primary,
+            _ => Pool::Replica,
+        }
+    }
+
+    fn connector(&self, read: bool, mutation_mode: bool) -> &dyn Connector {
+        match self.route(read, mutation_mode) {
+            Pool::Primary => self.primary.as_ref(),
+            Pool::Replica => self.replica.as_ref(),
+        }
+    }
+}
+
+#[async_trait]
+impl Connector for ReplicatedConnector {
+
+    fn default_database_type(&self, field_type: &FieldType) -> DatabaseType {
+        self.primary.default_database_type(field_type)
+    }
+
+    async fn migrate(&self, models: &Vec<Model>, reset_database: bool) -> Result<()> {
+        self.primary.migrate(models, reset_database).await
+    }
+
+    async fn migrate_plan(&self, models: &Vec<Model>) -> Result<Vec<String>> {
+        self.primary.migrate_plan(models).await
+    }
+
+    async fn query_raw(&self, query: &Value) -> Result<Value> {
+        // raw queries may write
+        self.connector(false, true).query_raw(query).await
+    }
+
+    async fn save_object(&self, object: &Object, session: Arc<dyn SaveSession>) -> Result<()> {
+        self.connector(false, true).save_object(object, session).await
+    }
+
+    async fn delete_object(&self, object: &Object, session: Arc<dyn SaveSession>) -> Result<()> {
+        self.connector(false, true).delete_object(object, session).await
+    }
+
+    async fn update_many_atomically(&self, model: &Model, objects: &Vec<Object>, updators: &HashMap<String, Value>) -> Result<()> {
+        self.connector(false, true).update_many_atomically(model, objects, updators).await
+    }
+
+    async fn find_unique(&self, graph: &Graph, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Object> {
+        self.connector(true, mutation_mode).find_unique(graph, model, finder, mutation_mode, action, action_source).await
+    }
+
+    async fn find_many(&self, graph: &Graph, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
+        self.connector(true, mutation_mode).find_many(graph, model, finder, mutation_mode, action, action_source).await
+    }
+
+    async fn count(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<usize> {
+        self.connector(true, false).count(graph, model, finder).await
+    }
+
+    async fn find_many_and_count(&self, graph: &Graph, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<(Vec<Object>, usize)> {
+        self.connector(true, mutation_mode).find_many_and_count(graph, model, finder, mutation_mode, action, action_source).await
+    }
+
+    async fn aggregate(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<Value> {
+        self.connector(true, false).aggregate(graph, model, finder).await
+    }
+
+    async fn group_by(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<Value> {
+        self.connector(true, false).group_by(graph, model, finder).await
+    }
+
+    async fn increment_sequence(&self, name: &str) -> Result<i64> {
+        self.connector(false, true).increment_sequence(name).await
+    }
+
+    fn new_save_session(&self) -> Arc<dyn SaveSession> {
+        self.primary.new_save_session()
+    }
+
+    fn pool_metrics(&self) -> &PoolMetrics {
+        // the primary pool serves every write and mutating read
+        self.primary.pool_metrics()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::test_connector::TestConnector;
+    use super::*;
+
+    #[test]
+    fn find_uses_replica_and_create_uses_primary() {
+        let connector = ReplicatedConnector::new(Arc::new(TestConnector::default()), Arc::new(TestConnector::default()), Duration::ZERO);
+        assert_eq!(connector.route(true, false), Pool::Replica);
+        assert_eq!(connector.route(false, true), Pool::Primary);
+        // reads made while mutating see the primary
+        assert_eq!(connector.route(true, true), Pool::Primary);
+        // stale reads are tolerated
+        assert_eq!(connector.route(true, false), Pool::Replica);
+    }
+
+    #[test]
+    fn reads_use_primary_within_replica_lag_after_write() {
+        let connector = ReplicatedConnector::new(Arc::new(TestConnector::default()), Arc::new(TestConnector::default()), Duration::from_secs(60));
+        assert_eq!(connector.route(true, false), Pool::Replica);
+        assert_eq!(connector.route(false, true), Pool::Primary);
+        assert_eq!(connector.route(true, false), Pool::Primary);
+    }
+
+    #[tokio::test]
+    async fn writes_are_sent_to_primary_pool() {
+        let primary = Arc::new(TestConnector::default());
+        let replica = Arc::new(TestConnector::default());
+        let connector = ReplicatedConnector::new(primary.clone(), replica.clone(), Duration::ZERO);
+        connector.increment_sequence("Invoice.number").await.unwrap();
+        connector.query_raw(&Value::Null).await.unwrap();
+        assert_eq!(primary.operations(), vec!["incrementSequence Invoice.number", "queryRaw"]);
+        assert!(replica.operations().is_empty());
+    }
+}
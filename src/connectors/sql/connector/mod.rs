@@ -1,5 +1,6 @@
 pub mod save_session;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use async_trait::async_trait;
@@ -11,19 +12,24 @@ use crate::connectors::sql::schema::r#type::field::ToDatabaseType;
 use crate::connectors::sql::connector::save_session::SQLSaveSession;
 use crate::connectors::sql::execution::Execution;
 use crate::connectors::sql::migration::migrate::SQLMigration;
+use crate::connectors::sql::pool::SQLPool;
 use crate::connectors::sql::query::Query;
 use crate::connectors::sql::stmts::SQL;
+use crate::connectors::sql::schema::charset::SQLCharset;
 use crate::connectors::sql::schema::dialect::SQLDialect;
 use crate::connectors::sql::schema::value::decode::RowDecoder;
 use crate::connectors::sql::schema::value::encode::ToSQLString;
 use crate::connectors::sql::schema::value::encode::PSQLArrayToSQLString;
+use crate::connectors::sql::schema::value::encode::ToSQLInput;
 use crate::connectors::sql::url::url_utils;
 use crate::core::action::Action;
 use crate::core::action::source::ActionSource;
-use crate::core::connector::{Connector, SaveSession};
+use crate::core::connector::{count_finder, Connector, SaveSession};
+use crate::core::database::pool_metrics::PoolMetrics;
 use crate::core::database::r#type::DatabaseType;
 use crate::core::error::Error;
 use crate::core::field::r#type::{FieldType, FieldTypeOwner};
+use crate::core::field::sequence::SEQUENCE_COUNTERS;
 use crate::core::input::Input;
 use crate::core::result::Result;
 use crate::prelude::{Graph, Object, Value};
@@ -31,16 +37,18 @@ use crate::teon;
 
 pub(crate) struct SQLConnector {
     dialect: SQLDialect,
-    pool: Quaint,
+    pool: SQLPool,
+    foreign_keys: bool,
+    charset: SQLCharset,
 }
 
 impl SQLConnector {
 
-    pub(crate) async fn new(dialect: SQLDialect, url: &str, reset: bool) -> Self {
+    pub(crate) async fn new(dialect: SQLDialect, url: &str, reset: bool, foreign_keys: bool, charset: SQLCharset) -> Self {
         SQLMigration::create_database_if_needed(dialect, url, reset).await;
         let url = url_utils::normalized_url(dialect, url);
-        let pool = Quaint::builder(url.as_str()).unwrap().build();
-        Self { dialect, pool }
+        let pool = SQLPool::new(Quaint::builder(url.as_str()).unwrap().build());
+        Self { dialect, pool, foreign_keys, charset }
     }
 
     async fn create_object(&self, object: &Object) -> Result<()> {
@@ -62,7 +70,12 @@ impl SQLConnector {
             }
         }
         let value_refs: Vec<(&str, &str)> = values.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        let stmt = SQL::insert_into(model.table_name()).values(value_refs).returning(auto_keys).to_string(self.dialect);
+        // return every stored column so that database generated values are reflected
+        let returning: Vec<String> = model.save_keys().iter().map(|k| match model.field(k) {
+            Some(field) => format!("\"{}\"", field.column_name()),
+            None => format!("\"{}\"", k),
+        }).collect();
+        let stmt = SQL::insert_into(model.table_name()).values(value_refs).returning(&returning).to_string(self.dialect);
         if self.dialect == SQLDialect::PostgreSQL {
             match conn.query(QuaintQuery::from(stmt)).await {
                 Ok(result_set) => {
@@ -70,9 +83,7 @@ impl SQLConnector {
                     let result = result_set.into_iter().next();
                     if result.is_some() {
                         let value = Execution::row_to_value(model, object.graph(), &result.unwrap(), &columns, self.dialect);
-                        for (k, v) in value.as_hashmap().unwrap() {
-                            object.set_value(k, v.clone())?;
-                        }
+                        object.set_from_database_result_value(&value, None, None);
                     }
                     Ok(())
                 }
@@ -84,15 +95,29 @@ impl SQLConnector {
         } else {
             match conn.query(QuaintQuery::from(stmt)).await {
                 Ok(result) => {
-                    let id = result.last_insert_id().unwrap();
-                    for key in auto_keys {
-                        if model.field(key).unwrap().field_type().is_int32() {
-                            object.set_value(key, Value::I32(id as i32))?;
-                        } else {
-                            object.set_value(key, Value::I64(id as i64))?;
+                    if let Some(id) = result.last_insert_id() {
+                        for key in auto_keys {
+                            if model.field(key).unwrap().field_type().is_int32() {
+                                object.set_value(key, Value::I32(id as i32))?;
+                            } else {
+                                object.set_value(key, Value::I64(id as i64))?;
+                            }
                         }
                     }
-                    Ok(())
+                    // no RETURNING here, read back database generated values on the
+                    // inserting connection, which sees the row before it's committed
+                    let stmt = Query::build(model, object.graph(), &teon!({"where": object.identifier(), "take": 1}), self.dialect, None, None, None, false);
+                    match conn.query(QuaintQuery::from(stmt)).await {
+                        Ok(result_set) => {
+                            let columns = result_set.columns().clone();
+                            if let Some(row) = result_set.into_iter().next() {
+                                let value = Execution::row_to_value(model, object.graph(), &row, &columns, self.dialect);
+                                object.set_from_database_result_value(&value, None, None);
+                            }
+                            Ok(())
+                        }
+                        Err(err) => Err(self.handle_err_result(err)),
+                    }
                 }
                 Err(err) => {
                     println!("{:?}", err);
@@ -111,15 +136,7 @@ impl SQLConnector {
             if let Some(field) = model.field(key) {
                 let column_name = field.column_name();
                 if let Some(updator) = object.get_atomic_updator(key) {
-                    let (key, val) = Input::key_value(updator.as_hashmap().unwrap());
-                    match key {
-                        "increment" => values.push((column_name, format!("{} + {}", column_name, val.to_string(self.dialect)))),
-                        "decrement" => values.push((column_name, format!("{} - {}", column_name, val.to_string(self.dialect)))),
-                        "multiply" => values.push((column_name, format!("{} * {}", column_name, val.to_string(self.dialect)))),
-                        "divide" => values.push((column_name, format!("{} / {}", column_name, val.to_string(self.dialect)))),
-                        "push" => values.push((column_name, format!("ARRAY_APPEND({}, {})", column_name, val.to_string(self.dialect)))),
-                        _ => panic!("Unhandled key."),
-                    }
+                    values.push((column_name, Self::atomic_update_value(column_name, &updator, self.dialect)));
                 } else {
                     let val = object.get_value(key).unwrap();
                     values.push((column_name, PSQLArrayToSQLString::to_string_with_ft(&val, self.dialect, field.field_type())));
@@ -149,6 +166,36 @@ impl SQLConnector {
         }
     }
 
+    fn atomic_update_value(column_name: &str, updator: &Value, dialect: SQLDialect) -> String {
+        let (key, val) = Input::key_value(updator.as_hashmap().unwrap());
+        match key {
+            "increment" => format!("{} + {}", column_name, val.to_string(dialect)),
+            "decrement" => format!("{} - {}", column_name, val.to_string(dialect)),
+            "multiply" => format!("{} * {}", column_name, val.to_string(dialect)),
+            "divide" => format!("{} / {}", column_name, val.to_string(dialect)),
+            "push" => format!("ARRAY_APPEND({}, {})", column_name, val.to_string(dialect)),
+            _ => panic!("Unhandled key."),
+        }
+    }
+
+    /// One `UPDATE` applying the atomic updators to every identified row.
+    fn update_many_atomically_stmt(table: &str, updators: &Vec<(&str, &Value)>, identifiers: &Vec<String>, dialect: SQLDialect) -> String {
+        let values: Vec<(&str, String)> = updators.iter().map(|(column_name, updator)| {
+            (*column_name, Self::atomic_update_value(column_name, updator, dialect))
+        }).collect();
+        let value_refs: Vec<(&str, &str)> = values.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let r#where = identifiers.iter().map(|i| format!("({})", i)).collect::<Vec<String>>().join(" OR ");
+        SQL::update(table).values(value_refs).r#where(&r#where).to_string(dialect)
+    }
+
+    /// The counter value of a sequence statement's single row and column.
+    fn sequence_value(result_set: ResultSet) -> Result<i64> {
+        match result_set.into_single().ok().and_then(|row| row.into_single().ok()).and_then(|value| value.as_i64()) {
+            Some(value) => Ok(value),
+            None => Err(Error::unknown_database_write_error()),
+        }
+    }
+
     fn handle_err_result(&self, err: quaint_forked::error::Error) -> Error {
         match err.kind() {
             UniqueConstraintViolation { constraint } => {
@@ -178,9 +225,12 @@ impl Connector for SQLConnector {
         field_type.to_database_type(self.dialect)
     }
 
-    async fn migrate(&mut self, models: &Vec<Model>, _reset_database: bool) -> Result<()> {
-        SQLMigration::migrate(self.dialect, &self.pool, models).await;
-        Ok(())
+    async fn migrate(&self, models: &Vec<Model>, _reset_database: bool) -> Result<()> {
+        SQLMigration::migrate(self.dialect, &self.pool, models, self.foreign_keys, &self.charset).await
+    }
+
+    async fn migrate_plan(&self, models: &Vec<Model>) -> Result<Vec<String>> {
+        SQLMigration::migrate_plan(self.dialect, &self.pool, models, self.foreign_keys, &self.charset).await
     }
 
     async fn query_raw(&self, query: &Value) -> Result<Value> {
@@ -226,6 +276,23 @@ impl Connector for SQLConnector {
         }
     }
 
+    async fn update_many_atomically(&self, model: &Model, objects: &Vec<Object>, updators: &HashMap<String, Value>) -> Result<()> {
+        if objects.is_empty() {
+            return Ok(());
+        }
+        let conn = match self.pool.check_out().await {
+            Ok(conn) => conn,
+            Err(err) => return Err(Error::internal_server_error(format!("Cannot connect to the database: {}", err))),
+        };
+        let updators: Vec<(&str, &Value)> = updators.iter().map(|(k, v)| (model.field(k).unwrap().column_name(), v)).collect();
+        let identifiers: Vec<String> = objects.iter().map(|o| Query::where_from_identifier(o, self.dialect)).collect();
+        let stmt = Self::update_many_atomically_stmt(model.table_name(), &updators, &identifiers, self.dialect);
+        match conn.execute(QuaintQuery::from(stmt)).await {
+            Ok(_) => Ok(()),
+            Err(err) => Err(self.handle_err_result(err)),
+        }
+    }
+
     async fn find_unique(&self, graph: &Graph, model: &Model, finder: &Value, _mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Object> {
         let objects = Execution::query_objects(&self.pool, model, graph, finder, self.dialect, action, action_source.clone()).await?;
         if objects.is_empty() {
@@ -246,6 +313,17 @@ impl Connector for SQLConnector {
         }
     }
 
+    async fn find_many_and_count(&self, graph: &Graph, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<(Vec<Object>, usize)> {
+        // the window counts the rows after the cursor only
+        if finder.get("cursor").is_some() {
+            let objects = self.find_many(graph, model, finder, mutation_mode, action, action_source).await?;
+            let count = self.count(graph, model, &count_finder(finder)).await?;
+            return Ok((objects, count));
+        }
+        let (objects, count) = Execution::query_objects_and_count(&self.pool, model, graph, finder, self.dialect, action, action_source).await?;
+        Ok((objects, count as usize))
+    }
+
     async fn aggregate(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<Value> {
         Execution::query_aggregate(&self.pool, model, graph, finder, self.dialect).await
     }
@@ -254,7 +332,389 @@ impl Connector for SQLConnector {
         Execution::query_group_by(&self.pool, model, graph, finder, self.dialect).await
     }
 
+    async fn increment_sequence(&self, name: &str) -> Result<i64> {
+        let conn = match self.pool.check_out().await {
+            Ok(conn) => conn,
+            Err(err) => return Err(self.handle_err_result(err)),
+        };
+        let escape = self.dialect.escape();
+        let table = format!("{escape}{SEQUENCE_COUNTERS}{escape}");
+        let name = name.to_sql_input();
+        match self.dialect {
+            SQLDialect::MySQL => {
+                // LAST_INSERT_ID(expr) keeps the incremented value for this connection
+                let stmt = format!("INSERT INTO {table} (`name`, `value`) VALUES ({name}, LAST_INSERT_ID(1)) ON DUPLICATE KEY UPDATE `value` = LAST_INSERT_ID(`value` + 1)");
+                if let Err(err) = conn.execute(QuaintQuery::from(stmt)).await {
+                    return Err(self.handle_err_result(err));
+                }
+                match conn.query(QuaintQuery::from("SELECT LAST_INSERT_ID()")).await {
+                    Ok(result_set) => Self::sequence_value(result_set),
+                    Err(err) => Err(self.handle_err_result(err)),
+                }
+            }
+            SQLDialect::SQLite => {
+                // RETURNING needs SQLite 3.35, the value is read back inside
+                // a write transaction instead
+                let upsert = format!("INSERT INTO {table} (\"name\", \"value\") VALUES ({name}, 1) ON CONFLICT (\"name\") DO UPDATE SET \"value\" = \"value\" + 1");
+                let select = format!("SELECT \"value\" FROM {table} WHERE \"name\" = {name}");
+                if let Err(err) = conn.raw_cmd("BEGIN IMMEDIATE").await {
+                    return Err(self.handle_err_result(err));
+                }
+                let result = match conn.execute(QuaintQuery::from(upsert)).await {
+                    Ok(_) => conn.query(QuaintQuery::from(select)).await,
+                    Err(err) => Err(err),
+                };
+                match result {
+                    Ok(result_set) => match conn.raw_cmd("COMMIT").await {
+                        Ok(_) => Self::sequence_value(result_set),
+                        Err(err) => Err(self.handle_err_result(err)),
+                    },
+                    Err(err) => {
+                        let _ = conn.raw_cmd("ROLLBACK").await;
+                        Err(self.handle_err_result(err))
+                    }
+                }
+            }
+            _ => {
+                let stmt = format!("INSERT INTO {table} ({escape}name{escape}, {escape}value{escape}) VALUES ({name}, 1) ON CONFLICT ({escape}name{escape}) DO UPDATE SET {escape}value{escape} = {table}.{escape}value{escape} + 1 RETURNING {escape}value{escape}");
+                match conn.query(QuaintQuery::from(stmt)).await {
+                    Ok(result_set) => Self::sequence_value(result_set),
+                    Err(err) => Err(self.handle_err_result(err)),
+                }
+            }
+        }
+    }
+
     fn new_save_session(&self) -> Arc<dyn SaveSession> {
         Arc::new(SQLSaveSession { })
     }
+
+    fn pool_metrics(&self) -> &PoolMetrics {
+        self.pool.metrics()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::field::{Field, FieldIndex, IndexSettings};
+    use crate::core::field::optionality::Optionality;
+    use crate::core::field::sequence::FieldSequence;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::pipeline::items::string::transform::to_lower_case::ToLowerCaseItem;
+    use crate::core::pipeline::items::string::transform::trim::TrimItem;
+    use crate::core::relation::Relation;
+    use crate::core::relation::delete_rule::DeleteRule;
+    use uuid::Uuid;
+    use super::*;
+
+    /// A SQLite database in a file of its own, which is removed when it's
+    /// dropped. Tests running in parallel never share a database.
+    struct TestDatabase {
+        path: std::path::PathBuf,
+    }
+
+    impl TestDatabase {
+
+        fn new() -> Self {
+            Self { path: std::env::temp_dir().join(format!("teo_test_{}.sqlite", Uuid::new_v4())) }
+        }
+
+        fn url(&self) -> String {
+            format!("sqlite:{}", self.path.to_str().unwrap())
+        }
+    }
+
+    impl Drop for TestDatabase {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    #[test]
+    fn update_many_increments_every_identified_row_at_once() {
+        let increment = teon!({"increment": 1});
+        let updators = vec![("likes", &increment)];
+        let identifiers = vec!["`id` = 1".to_owned(), "`id` = 2".to_owned()];
+        let stmt = SQLConnector::update_many_atomically_stmt("Post", &updators, &identifiers, SQLDialect::MySQL);
+        assert_eq!(stmt, "UPDATE `Post` SET `likes` = likes + 1 WHERE (`id` = 1) OR (`id` = 2);");
+    }
+
+    #[tokio::test]
+    async fn created_objects_are_refreshed_with_database_generated_values() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let connector = Arc::new(SQLConnector::new(SQLDialect::SQLite, &url, true, false, SQLCharset::default()).await);
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            id.auto_increment = true;
+            let mut email = Field::new("email".to_owned());
+            email.field_type = Some(FieldType::String);
+            let mut display_name = Field::new("displayName".to_owned());
+            display_name.field_type = Some(FieldType::String);
+            display_name.optionality = Optionality::Optional;
+            display_name.r#virtual = true;
+            m.field(id).field(email).field(display_name);
+        });
+        let graph = builder.build(connector).await;
+        crate::core::app::migrate::migrate(&graph, false).await.unwrap();
+        for (index, email) in ["a@x.com", "b@x.com"].iter().enumerate() {
+            let user = graph.create_object("User", teon!({"email": *email})).await.unwrap();
+            user.save().await.unwrap();
+            assert_eq!(user.get_value("id").unwrap(), Value::I32(index as i32 + 1));
+            assert_eq!(user.get_value("email").unwrap(), Value::String(email.to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn find_many_and_count_reads_the_total_with_the_page() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let connector = Arc::new(SQLConnector::new(SQLDialect::SQLite, &url, true, false, SQLCharset::default()).await);
+        let mut builder = GraphBuilder::new();
+        builder.model("Post", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let mut published = Field::new("published".to_owned());
+            published.field_type = Some(FieldType::Bool);
+            m.field(id).field(published);
+        });
+        let graph = builder.build(connector.clone()).await;
+        crate::core::app::migrate::migrate(&graph, false).await.unwrap();
+        for id in 1..=5 {
+            let post = graph.create_object("Post", teon!({"id": id, "published": id != 3})).await.unwrap();
+            post.save().await.unwrap();
+        }
+        let model = graph.model("Post").unwrap();
+        let action = Action::from_u32(crate::core::action::FIND_MANY_HANDLER);
+        let finder = teon!({"where": {"published": {"equals": true}}, "orderBy": [{"id": "asc"}], "skip": 1, "take": 2});
+        let (objects, count) = connector.find_many_and_count(&graph, model, &finder, false, action, ActionSource::ProgramCode).await.unwrap();
+        assert_eq!(objects.iter().map(|o| o.get_value("id").unwrap()).collect::<Vec<Value>>(), vec![Value::I32(2), Value::I32(4)]);
+        assert_eq!(count, 4);
+        // a page past the last record still counts the records
+        let finder = teon!({"orderBy": [{"id": "asc"}], "skip": 10, "take": 2});
+        let (objects, count) = connector.find_many_and_count(&graph, model, &finder, false, action, ActionSource::ProgramCode).await.unwrap();
+        assert!(objects.is_empty());
+        assert_eq!(count, 5);
+        // records before the cursor are counted
+        let finder = teon!({"orderBy": [{"id": "asc"}], "cursor": {"id": 4}, "take": 2});
+        let (objects, count) = connector.find_many_and_count(&graph, model, &finder, false, action, ActionSource::ProgramCode).await.unwrap();
+        assert_eq!(objects.iter().map(|o| o.get_value("id").unwrap()).collect::<Vec<Value>>(), vec![Value::I32(4), Value::I32(5)]);
+        assert_eq!(count, 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_sequence_increments_never_repeat_a_value() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let connector = Arc::new(SQLConnector::new(SQLDialect::SQLite, &url, true, false, SQLCharset::default()).await);
+        let mut builder = GraphBuilder::new();
+        builder.model("Ticket", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let mut number = Field::new("number".to_owned());
+            number.field_type = Some(FieldType::I64);
+            number.sequence = Some(FieldSequence::default());
+            m.field(id).field(number);
+        });
+        let graph = builder.build(connector.clone()).await;
+        crate::core::app::migrate::migrate(&graph, false).await.unwrap();
+        let increments = (0..20).map(|_| {
+            let connector = connector.clone();
+            tokio::spawn(async move { connector.increment_sequence("Ticket.number").await })
+        });
+        let mut values: Vec<i64> = futures_util::future::join_all(increments).await.into_iter().map(|r| r.unwrap().unwrap()).collect();
+        values.sort();
+        assert_eq!(values, (1..=20).collect::<Vec<i64>>());
+    }
+
+    async fn user_graph(url: &str, name: Field) -> Graph {
+        let connector = Arc::new(SQLConnector::new(SQLDialect::SQLite, url, false, false, SQLCharset::default()).await);
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            m.field(id).field(name);
+        });
+        let graph = builder.build(connector).await;
+        crate::core::app::migrate::migrate(&graph, false).await.unwrap();
+        graph
+    }
+
+    #[tokio::test]
+    async fn renamed_column_keeps_its_values() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let mut name = Field::new("name".to_owned());
+        name.field_type = Some(FieldType::String);
+        let graph = user_graph(&url, name).await;
+        let user = graph.create_object("User", teon!({"id": 1, "name": "Ann"})).await.unwrap();
+        user.save().await.unwrap();
+        let mut full_name = Field::new("fullName".to_owned());
+        full_name.field_type = Some(FieldType::String);
+        full_name.renamed_from("name");
+        let graph = user_graph(&url, full_name).await;
+        let users: Vec<Object> = graph.find_many("User", &teon!({})).await.unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].get_value("fullName").unwrap(), Value::String("Ann".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn unique_values_are_compared_after_they_are_normalized() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let mut email = Field::new("email".to_owned());
+        email.field_type = Some(FieldType::String);
+        email.index = Some(FieldIndex::Unique(IndexSettings::default()));
+        email.on_set_pipeline.items.push(Arc::new(TrimItem::new()));
+        email.on_set_pipeline.items.push(Arc::new(ToLowerCaseItem::new()));
+        let graph = user_graph(&url, email).await;
+        let user = graph.create_object("User", teon!({"id": 1, "email": " A@X.com "})).await.unwrap();
+        user.save().await.unwrap();
+        let users: Vec<Object> = graph.find_many("User", &teon!({})).await.unwrap();
+        assert_eq!(users[0].get_value("email").unwrap(), Value::String("a@x.com".to_owned()));
+        let duplicate = graph.create_object("User", teon!({"id": 2, "email": "a@x.com"})).await.unwrap();
+        assert!(duplicate.save().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn case_insensitive_unique_values_differing_in_case_collide() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let mut email = Field::new("email".to_owned());
+        email.field_type = Some(FieldType::String);
+        email.index = Some(FieldIndex::Unique(IndexSettings { case_insensitive: true, ..Default::default() }));
+        let graph = user_graph(&url, email).await;
+        graph.create_object("User", teon!({"id": 1, "email": "Ann@X.com"})).await.unwrap().save().await.unwrap();
+        let duplicate = graph.create_object("User", teon!({"id": 2, "email": "ann@x.com"})).await.unwrap();
+        assert!(duplicate.save().await.is_err());
+        let user: Object = graph.find_unique("User", &teon!({"where": {"email": "ANN@X.COM"}})).await.unwrap();
+        assert_eq!(user.get_value("email").unwrap(), Value::String("Ann@X.com".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn indices_follow_the_schema_between_migrations() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let email = |index: Option<FieldIndex>| {
+            let mut email = Field::new("email".to_owned());
+            email.field_type = Some(FieldType::String);
+            email.index = index;
+            email
+        };
+        let graph = user_graph(&url, email(None)).await;
+        graph.create_object("User", teon!({"id": 1, "email": "a@x.com"})).await.unwrap().save().await.unwrap();
+        // a gained unique index is created
+        let graph = user_graph(&url, email(Some(FieldIndex::Unique(IndexSettings::default())))).await;
+        assert!(graph.create_object("User", teon!({"id": 2, "email": "a@x.com"})).await.unwrap().save().await.is_err());
+        // a changed index is dropped before it's created again
+        let graph = user_graph(&url, email(Some(FieldIndex::Index(IndexSettings::default())))).await;
+        graph.create_object("User", teon!({"id": 2, "email": "a@x.com"})).await.unwrap().save().await.unwrap();
+        // a lost index is dropped
+        let graph = user_graph(&url, email(None)).await;
+        graph.create_object("User", teon!({"id": 3, "email": "a@x.com"})).await.unwrap().save().await.unwrap();
+        let users: Vec<Object> = graph.find_many("User", &teon!({})).await.unwrap();
+        assert_eq!(users.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn negated_filters_keep_null_values() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let mut age = Field::new("age".to_owned());
+        age.field_type = Some(FieldType::I32);
+        age.optionality = Optionality::Optional;
+        let graph = user_graph(&url, age).await;
+        for user in [teon!({"id": 1, "age": 17}), teon!({"id": 2, "age": 31}), teon!({"id": 3, "age": null})] {
+            graph.create_object("User", user).await.unwrap().save().await.unwrap();
+        }
+        let ids = |users: Vec<Object>| users.iter().map(|u| u.get_value("id").unwrap()).collect::<Vec<Value>>();
+        let users: Vec<Object> = graph.find_many("User", &teon!({"where": {"age": {"not": {"in": [17]}}}, "orderBy": {"id": "asc"}})).await.unwrap();
+        assert_eq!(ids(users), vec![teon!(2), teon!(3)]);
+        let users: Vec<Object> = graph.find_many("User", &teon!({"where": {"age": {"not": {"gte": 20}}}, "orderBy": {"id": "asc"}})).await.unwrap();
+        assert_eq!(ids(users), vec![teon!(1), teon!(3)]);
+    }
+
+    #[tokio::test]
+    async fn or_matches_any_of_its_branches() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let mut age = Field::new("age".to_owned());
+        age.field_type = Some(FieldType::I32);
+        let graph = user_graph(&url, age).await;
+        for user in [teon!({"id": 1, "age": 17}), teon!({"id": 2, "age": 31}), teon!({"id": 3, "age": 45})] {
+            graph.create_object("User", user).await.unwrap().save().await.unwrap();
+        }
+        let users: Vec<Object> = graph.find_many("User", &teon!({"where": {"OR": [{"age": 17}, {"id": 3}]}, "orderBy": {"id": "asc"}})).await.unwrap();
+        assert_eq!(users.iter().map(|u| u.get_value("id").unwrap()).collect::<Vec<Value>>(), vec![teon!(1), teon!(3)]);
+    }
+
+    async fn round_trip_text(dialect: SQLDialect, url: &str, text: &str) -> Value {
+        let connector = Arc::new(SQLConnector::new(dialect, url, true, false, SQLCharset::default()).await);
+        let mut builder = GraphBuilder::new();
+        builder.model("Note", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let mut body = Field::new("body".to_owned());
+            body.field_type = Some(FieldType::String);
+            m.field(id).field(body);
+        });
+        let graph = builder.build(connector).await;
+        crate::core::app::migrate::migrate(&graph, false).await.unwrap();
+        let note = graph.create_object("Note", teon!({"id": 1, "body": text})).await.unwrap();
+        note.save().await.unwrap();
+        let note: Object = graph.find_unique("Note", &teon!({"where": {"id": 1}})).await.unwrap();
+        note.get_value("body").unwrap()
+    }
+
+    #[tokio::test]
+    async fn relations_are_migrated_to_foreign_keys_with_their_delete_rule() {
+        let database = TestDatabase::new();
+        let url = database.url();
+        let connector = Arc::new(SQLConnector::new(SQLDialect::SQLite, &url, true, true, SQLCharset::default()).await);
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            m.field(id);
+        });
+        builder.model("Post", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let mut author_id = Field::new("authorId".to_owned());
+            author_id.field_type = Some(FieldType::I32);
+            let mut author = Relation::new("author");
+            author.set_model("User".to_owned());
+            author.set_fields(vec!["authorId".to_owned()]);
+            author.set_references(vec!["id".to_owned()]);
+            author.set_delete_rule(DeleteRule::Cascade);
+            m.field(id).field(author_id).relation(author);
+        });
+        let graph = builder.build(connector).await;
+        crate::core::app::migrate::migrate(&graph, false).await.unwrap();
+        let tables = graph.connector().query_raw(&teon!("SELECT sql FROM sqlite_master WHERE name = 'Post'")).await.unwrap();
+        let sql = tables.as_vec().unwrap()[0].as_indexmap().unwrap().get("sql").unwrap().as_str().unwrap().to_owned();
+        assert!(sql.contains("CONSTRAINT `Post_authorId_fkey` FOREIGN KEY (`authorId`) REFERENCES `User`(`id`) ON DELETE CASCADE"));
+    }
+
+    /// Four byte characters need `utf8mb4` on MySQL, where `utf8` is three
+    /// bytes. MySQL is only tested when `TEO_TEST_MYSQL_URL` is set.
+    #[tokio::test]
+    async fn four_byte_characters_round_trip_with_the_default_charset() {
+        let text = "naïve 日本 🦀";
+        let database = TestDatabase::new();
+        let url = database.url();
+        assert_eq!(round_trip_text(SQLDialect::SQLite, &url, text).await, Value::String(text.to_owned()));
+        if let Ok(url) = std::env::var("TEO_TEST_MYSQL_URL") {
+            assert_eq!(round_trip_text(SQLDialect::MySQL, &url, text).await, Value::String(text.to_owned()));
+        }
+    }
 }
@@ -2,10 +2,10 @@ use std::borrow::Cow;
 use array_tool::vec::Uniq;
 use std::collections::HashMap;
 use async_recursion::async_recursion;
-use quaint_forked::pooled::Quaint;
-use quaint_forked::prelude::{Queryable, ResultRow};
+use crate::connectors::sql::pool::SQLPool;
+use quaint_forked::prelude::{Queryable, ResultRow, ResultSet};
 use quaint_forked::ast::{Query as QuaintQuery};
-use crate::connectors::sql::query::Query;
+use crate::connectors::sql::query::{Query, TOTAL_COUNT};
 use crate::connectors::sql::schema::dialect::SQLDialect;
 use crate::connectors::sql::schema::value::decode::RowDecoder;
 use crate::connectors::sql::schema::value::encode::{SQLEscape, ToSQLString, ToWrapped};
@@ -34,17 +34,15 @@ impl Execution {
                 }
             } else if let Some(property) = model.property(column_name) {
                 Some((property.name().to_owned(), RowDecoder::decode(property.field_type(), property.is_optional(), row, column_name, dialect)))
+            } else if column_name == TOTAL_COUNT {
+                None
             } else if column_name.contains(".") {
                 let names: Vec<&str> = column_name.split(".").collect();
                 let relation_name = names[0];
                 let field_name = names[1];
-                if relation_name == "c" { // cursor fetch, should remove
-                    None
-                } else {
-                    let opposite_model = graph.model(model.relation(relation_name).unwrap().model()).unwrap();
-                    let field = opposite_model.field(field_name).unwrap();
-                    Some((column_name.to_owned(), RowDecoder::decode(field.field_type(), field.is_optional(), row, column_name, dialect)))
-                }
+                let opposite_model = graph.model(model.relation(relation_name).unwrap().model()).unwrap();
+                let field = opposite_model.field(field_name).unwrap();
+                Some((column_name.to_owned(), RowDecoder::decode(field.field_type(), field.is_optional(), row, column_name, dialect)))
             } else {
                 panic!("Unhandled key {}.", column_name);
             }
@@ -82,21 +80,55 @@ impl Execution {
         Value::HashMap(retval)
     }
 
-    pub(crate) async fn query_objects(pool: &Quaint, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
+    pub(crate) async fn query_objects(pool: &SQLPool, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
         let values = Self::query(pool, model, graph, finder, dialect).await?;
+        Self::values_to_objects(values, model, graph, finder, action, action_source).await
+    }
+
+    /// Queries a page of objects and the count of all matched objects. The
+    /// count is read with a window function in the same query when possible.
+    pub(crate) async fn query_objects_and_count(pool: &SQLPool, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect, action: Action, action_source: ActionSource) -> Result<(Vec<Object>, u64)> {
+        let distinct = finder.get("distinct").map(|d| !d.as_vec().unwrap().is_empty()).unwrap_or(false);
+        if !dialect.supports_window_functions() || finder.get("cursor").is_some() || distinct {
+            let objects = Self::query_objects(pool, model, graph, finder, dialect, action, action_source).await?;
+            let count = Self::query_count(pool, model, graph, Self::without_paging_and_skip_take(finder).as_ref(), dialect).await?;
+            return Ok((objects, count));
+        }
+        let (values, total_count) = Self::query_internal(pool, model, graph, finder, dialect, None, None, None, false, None, true).await?;
+        let count = match total_count {
+            Some(count) => count,
+            // an empty page doesn't tell how many rows precede it
+            None => Self::query_count(pool, model, graph, Self::without_paging_and_skip_take(finder).as_ref(), dialect).await?,
+        };
+        let objects = Self::values_to_objects(values, model, graph, finder, action, action_source).await?;
+        Ok((objects, count))
+    }
+
+    fn total_count(rows: &ResultSet) -> Result<Option<u64>> {
+        match rows.first() {
+            Some(row) => match row.get(TOTAL_COUNT).and_then(|count| count.as_i64()) {
+                Some(count) => Ok(Some(count as u64)),
+                None => Err(Error::unknown_database_count_error()),
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn values_to_objects(values: Vec<Value>, model: &Model, graph: &Graph, finder: &Value, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
         let select = finder.as_hashmap().unwrap().get("select");
         let include = finder.as_hashmap().unwrap().get("include");
         let mut results = vec![];
         for value in values {
             let object = graph.new_object(model.name(), action, action_source.clone())?;
             object.set_from_database_result_value(&value, select, include);
+            object.trigger_after_find_callbacks().await?;
             results.push(object);
         }
         Ok(results)
     }
 
     #[async_recursion]
-    async fn query_internal(pool: &Quaint, model: &Model, graph: &Graph, value: &Value, dialect: SQLDialect, additional_where: Option<String>, additional_left_join: Option<String>, join_table_results: Option<Vec<String>>, force_negative_take: bool, additional_distinct: Option<Vec<String>>) -> Result<Vec<Value>> {
+    async fn query_internal(pool: &SQLPool, model: &Model, graph: &Graph, value: &Value, dialect: SQLDialect, additional_where: Option<String>, additional_left_join: Option<String>, join_table_results: Option<Vec<String>>, force_negative_take: bool, additional_distinct: Option<Vec<String>>, with_total_count: bool) -> Result<(Vec<Value>, Option<u64>)> {
         let conn = pool.check_out().await.unwrap();
         let _select = value.get("select");
         let include = value.get("include");
@@ -110,7 +142,11 @@ impl Execution {
         } else {
             Cow::Borrowed(value)
         };
-        let stmt = Query::build(model, graph, value_for_build.as_ref(), dialect, additional_where, additional_left_join, join_table_results, force_negative_take);
+        let stmt = if with_total_count {
+            Query::build_with_total_count(model, graph, value_for_build.as_ref(), dialect)
+        } else {
+            Query::build(model, graph, value_for_build.as_ref(), dialect, additional_where, additional_left_join, join_table_results, force_negative_take)
+        };
         // println!("sql stmt: {}", &stmt);
         let reverse = Input::has_negative_take(value);
         let rows = match conn.query(QuaintQuery::from(stmt)).await {
@@ -121,8 +157,9 @@ impl Execution {
             }
         };
         if rows.is_empty() {
-            return Ok(vec![])
+            return Ok((vec![], None))
         }
+        let total_count = if with_total_count { Self::total_count(&rows)? } else { None };
         let columns = rows.columns().clone();
         let mut results = rows.into_iter().map(|row| Self::row_to_value(model, graph, &row, &columns, dialect)).collect::<Vec<Value>>();
         if reverse {
@@ -180,7 +217,7 @@ impl Execution {
                     } else {
                         Cow::Owned(teon!({}))
                     };
-                    let included_values = Self::query_internal(pool, opposite_model, graph, &nested_query, dialect, Some(where_addition), None, None, negative_take, None).await?;
+                    let (included_values, _) = Self::query_internal(pool, opposite_model, graph, &nested_query, dialect, Some(where_addition), None, None, negative_take, None, false).await?;
                     // println!("see included: {:?}", included_values);
                     for result in results.iter_mut() {
                         let mut skipped = 0;
@@ -269,7 +306,7 @@ impl Execution {
                     } else {
                         None
                     };
-                    let included_values = Self::query_internal(pool, opposite_model, graph, &nested_query, dialect, Some(where_addition), Some(left_join), Some(join_table_results), negative_take, additional_inner_distinct).await?;
+                    let (included_values, _) = Self::query_internal(pool, opposite_model, graph, &nested_query, dialect, Some(where_addition), Some(left_join), Some(join_table_results), negative_take, additional_inner_distinct, false).await?;
                     // println!("see included {:?}", included_values);
                     for result in results.iter_mut() {
                         let mut skipped = 0;
@@ -310,14 +347,14 @@ impl Execution {
                 }
             }
         }
-        Ok(results)
+        Ok((results, total_count))
     }
 
-    pub(crate) async fn query(pool: &Quaint, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect) -> Result<Vec<Value>> {
-       Self::query_internal(pool, model, graph, finder, dialect, None, None, None, false, None).await
+    pub(crate) async fn query(pool: &SQLPool, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect) -> Result<Vec<Value>> {
+       Ok(Self::query_internal(pool, model, graph, finder, dialect, None, None, None, false, None, false).await?.0)
     }
 
-    pub(crate) async fn query_aggregate(pool: &Quaint, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect) -> Result<Value> {
+    pub(crate) async fn query_aggregate(pool: &SQLPool, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect) -> Result<Value> {
         let conn = pool.check_out().await.unwrap();
         let stmt = Query::build_for_aggregate(model, graph, finder, dialect);
         match conn.query(QuaintQuery::from(&*stmt)).await {
@@ -333,7 +370,7 @@ impl Execution {
         }
     }
 
-    pub(crate) async fn query_group_by(pool: &Quaint, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect) -> Result<Value> {
+    pub(crate) async fn query_group_by(pool: &SQLPool, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect) -> Result<Value> {
         let conn = pool.check_out().await.unwrap();
         let stmt = Query::build_for_group_by(model, graph, finder, dialect);
         let rows = match conn.query(QuaintQuery::from(stmt)).await {
@@ -349,7 +386,7 @@ impl Execution {
         }).collect::<Vec<Value>>()))
     }
 
-    pub(crate) async fn query_count(pool: &Quaint, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect) -> Result<u64> {
+    pub(crate) async fn query_count(pool: &SQLPool, model: &Model, graph: &Graph, finder: &Value, dialect: SQLDialect) -> Result<u64> {
         let conn = pool.check_out().await.unwrap();
         let stmt = Query::build_for_count(model, graph, finder, dialect, None, None, None, false);
         match conn.query(QuaintQuery::from(stmt)).await {
@@ -422,3 +459,22 @@ impl Execution {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quaint_forked::ast::Value as QuaintValue;
+    use super::*;
+
+    #[test]
+    fn total_count_is_read_from_the_page() {
+        let columns = vec!["id".to_owned(), TOTAL_COUNT.to_owned()];
+        let rows = ResultSet::new(columns.clone(), vec![
+            vec![QuaintValue::int64(11), QuaintValue::int64(42)],
+            vec![QuaintValue::int64(12), QuaintValue::int64(42)],
+        ]);
+        assert_eq!(Execution::total_count(&rows).unwrap(), Some(42));
+        assert_eq!(Execution::total_count(&ResultSet::new(columns, vec![])).unwrap(), None);
+        let rows = ResultSet::new(vec!["id".to_owned()], vec![vec![QuaintValue::int64(11)]]);
+        assert!(Execution::total_count(&rows).is_err());
+    }
+}
@@ -1,26 +1,57 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use itertools::Itertools;
 use maplit::hashset;
 use quaint_forked::pooled::{PooledConnection, Quaint};
-use quaint_forked::prelude::Queryable;
+use crate::connectors::sql::pool::{MeteredConnection, SQLPool};
+use quaint_forked::prelude::{Queryable, ResultSet};
 use quaint_forked::ast::Query;
 use crate::connectors::sql::migration::sql::{sqlite_auto_increment_query, sqlite_list_indices_query};
 use super::super::url::url_utils;
+use crate::connectors::sql::schema::charset::SQLCharset;
 use crate::connectors::sql::schema::column::decoder::{ColumnDecoder, ColumnManipulation};
 use crate::connectors::sql::stmts::create::table::SQLCreateTableStatement;
 use crate::connectors::sql::stmts::SQL;
 use crate::connectors::sql::schema::column::SQLColumn;
+use crate::connectors::sql::schema::foreign_key::SQLForeignKey;
+use crate::connectors::sql::migration::order::table_creation_order;
 use crate::connectors::sql::schema::dialect::SQLDialect;
 use crate::core::model::Model;
-use crate::connectors::sql::schema::value::encode::ToSQLString;
+use crate::connectors::sql::schema::value::encode::{SQLEscape, ToSQLString};
 use crate::core::field::Sort;
+use crate::core::field::sequence::SEQUENCE_COUNTERS;
 use crate::core::model::index::{ModelIndex, ModelIndexItem, ModelIndexType};
+use crate::core::pipeline::Pipeline;
 use crate::core::pipeline::ctx::Ctx;
 use crate::prelude::Value;
+use crate::core::error::Error;
+use crate::core::result::Result;
 
 pub(crate) struct SQLMigration { }
 
+/// Tables, columns and indices read from the database before planning a migration.
+#[derive(Default)]
+struct DatabaseState {
+    tables: Vec<String>,
+    table_states: HashMap<String, TableState>,
+    expression_indices: HashSet<String>,
+    sequence_counters: bool,
+}
+
+struct TableState {
+    columns: HashSet<SQLColumn>,
+    indices: HashSet<ModelIndex>,
+    has_records: bool,
+}
+
+/// A planned migration step, a statement to execute on a table or a migration action.
+/// `Backfill` fills a new column of existing records with a field's default value.
+enum MigrationStep {
+    Execute(String, String),
+    Process(Pipeline),
+    Backfill(String, String, Value),
+}
+
 impl SQLMigration {
 
     // Create database
@@ -83,82 +114,175 @@ impl SQLMigration {
 
     // Migrate
 
-    pub(crate) async fn db_columns(conn: &PooledConnection, dialect: SQLDialect, table_name: &str) -> HashSet<SQLColumn> {
+    pub(crate) async fn db_columns(conn: &PooledConnection, dialect: SQLDialect, table_name: &str) -> Result<HashSet<SQLColumn>> {
         match dialect {
             SQLDialect::SQLite => {
-                let columns_result = conn.query(Query::from(format!("pragma table_info('{}')", table_name))).await.unwrap();
-                let indices_result = conn.query(Query::from(sqlite_list_indices_query(table_name))).await.unwrap();
-                let auto_increment_result = conn.query(Query::from(sqlite_auto_increment_query(table_name))).await.unwrap();
+                let columns_result = Self::query(conn, format!("pragma table_info('{}')", table_name), table_name).await?;
+                let indices_result = Self::query(conn, sqlite_list_indices_query(table_name), table_name).await?;
+                let auto_increment_result = Self::query(conn, sqlite_auto_increment_query(table_name), table_name).await?;
                 let db_columns = ColumnDecoder::decode_sqlite_columns(columns_result, indices_result, auto_increment_result);
-                db_columns
+                Ok(db_columns)
             }
             _ => {
                 let mut results = hashset! {};
-                let db_table_columns = conn.query(if dialect == SQLDialect::PostgreSQL {
-                    let desc = format!("SELECT * FROM information_schema.columns where table_name = '{}'", table_name);
-                    Query::from(desc)
+                let desc = if dialect == SQLDialect::PostgreSQL {
+                    format!("SELECT * FROM information_schema.columns where table_name = '{}'", table_name)
                 } else {
-                    let desc = SQL::describe(table_name).to_string(dialect);
-                    Query::from(desc)
-                }).await.unwrap();
+                    SQL::describe(table_name).to_string(dialect)
+                };
+                let db_table_columns = Self::query(conn, desc, table_name).await?;
                 for db_table_column in db_table_columns {
                     let db_column = ColumnDecoder::decode(db_table_column, dialect, conn, table_name).await;
                     results.insert(db_column);
                 }
-                results
+                Ok(results)
             }
         }
     }
 
-    pub(crate) async fn get_db_user_tables(dialect: SQLDialect, conn: &PooledConnection) -> Vec<String> {
+    pub(crate) async fn get_db_user_tables(dialect: SQLDialect, conn: &PooledConnection) -> Result<Vec<String>> {
         match dialect {
             SQLDialect::MySQL => {
                 let sql = "SHOW TABLES";
-                let db_result = conn.query(Query::from(sql)).await.unwrap();
-                db_result.into_iter().map(|result| { result.into_single().unwrap().to_string().unwrap() }).collect()
+                let db_result = Self::query(conn, sql.to_owned(), "").await?;
+                Ok(db_result.into_iter().map(|result| { result.into_single().unwrap().to_string().unwrap() }).collect())
             }
             SQLDialect::PostgreSQL => {
                 let sql = "SELECT tablename FROM pg_catalog.pg_tables WHERE schemaname != 'pg_catalog' AND schemaname != 'information_schema'";
-                let db_result = conn.query(Query::from(sql)).await.unwrap();
-                db_result.into_iter().map(|result| { result.into_single().unwrap().to_string().unwrap() }).collect()
+                let db_result = Self::query(conn, sql.to_owned(), "").await?;
+                Ok(db_result.into_iter().map(|result| { result.into_single().unwrap().to_string().unwrap() }).collect())
             }
             SQLDialect::SQLite => {
                 let sql = "SELECT name FROM sqlite_master WHERE type in ('table') AND name not like 'sqlite?_%' escape '?'";
-                let db_result = conn.query(Query::from(sql)).await.unwrap();
-                db_result.into_iter().map(|result| { result.into_single().unwrap().to_string().unwrap() }).collect()
+                let db_result = Self::query(conn, sql.to_owned(), "").await?;
+                Ok(db_result.into_iter().map(|result| { result.into_single().unwrap().to_string().unwrap() }).collect())
             }
             _ => unreachable!()
         }
     }
 
-    pub(crate) async fn rename_table(dialect: SQLDialect, conn: &PooledConnection, old_name: &str, new_name: &str) {
+    pub(crate) async fn table_has_records(dialect: SQLDialect, conn: &PooledConnection, table_name: &str) -> Result<bool> {
         let escape = dialect.escape();
-        let sql = format!("ALTER TABLE {escape}{old_name}{escape} RENAME TO {escape}{new_name}{escape}");
-        conn.execute(Query::from(sql)).await.unwrap();
+        let sql = format!("select * from {escape}{table_name}{escape} limit 1");
+        Ok(!Self::query(conn, sql, table_name).await?.is_empty())
     }
 
-    pub(crate) async fn table_has_records(dialect: SQLDialect, conn: &PooledConnection, table_name: &str) -> bool {
-        let escape = dialect.escape();
-        let sql = format!("select * from {escape}{table_name}{escape} limit 1");
-        !conn.query(Query::from(sql)).await.unwrap().is_empty()
+    pub(crate) async fn migrate(dialect: SQLDialect, pool: &SQLPool, models: &Vec<Model>, foreign_keys: bool, charset: &SQLCharset) -> Result<()> {
+        let conn = Self::check_out(pool).await?;
+        let state = Self::database_state(dialect, &conn, models).await?;
+        for step in Self::plan(dialect, &state, models, foreign_keys, charset)? {
+            match step {
+                MigrationStep::Execute(sql, table) => Self::execute(&conn, sql, &table).await?,
+                MigrationStep::Process(action) => {
+                    let ctx = Ctx::initial_state_with_value(Value::Null);
+                    action.process(ctx).await?;
+                }
+                MigrationStep::Backfill(table, column, default) => match default {
+                    // computed defaults like cuid and uuid are unique per record
+                    Value::Pipeline(pipeline) => loop {
+                        let value = pipeline.process(Ctx::initial_state_with_value(Value::Null)).await?;
+                        if Self::execute_count(&conn, Self::backfill_row_sql(dialect, &table, &column, &value), &table).await? == 0 {
+                            break;
+                        }
+                    },
+                    value => Self::execute(&conn, Self::backfill_sql(dialect, &table, &column, &value), &table).await?,
+                }
+            }
+        }
+        Ok(())
     }
 
-    pub(crate) async fn migrate(dialect: SQLDialect, pool: &Quaint, models: &Vec<Model>) {
-        let conn = pool.check_out().await.unwrap();
-        let mut db_tables = Self::get_db_user_tables(dialect, &conn).await;
-        // compare each table and do migration
+    /// Returns the SQL statements `migrate` would execute, without changing the database.
+    /// Migration actions of added and removed columns and backfills with
+    /// computed defaults are not listed.
+    pub(crate) async fn migrate_plan(dialect: SQLDialect, pool: &SQLPool, models: &Vec<Model>, foreign_keys: bool, charset: &SQLCharset) -> Result<Vec<String>> {
+        let conn = Self::check_out(pool).await?;
+        let state = Self::database_state(dialect, &conn, models).await?;
+        Ok(Self::plan(dialect, &state, models, foreign_keys, charset)?.into_iter().filter_map(|step| match step {
+            MigrationStep::Execute(sql, _) => Some(sql),
+            MigrationStep::Process(_) => None,
+            MigrationStep::Backfill(table, column, default) => match default {
+                Value::Pipeline(_) => None,
+                value => Some(Self::backfill_sql(dialect, &table, &column, &value)),
+            },
+        }).collect())
+    }
+
+    async fn check_out(pool: &SQLPool) -> Result<MeteredConnection<'_>> {
+        match pool.check_out().await {
+            Ok(conn) => Ok(conn),
+            Err(err) => Err(Error::internal_server_error(format!("Migration cannot connect to the database: {}", err))),
+        }
+    }
+
+    /// Reads the tables, columns and indices which the migration diffs models against.
+    async fn database_state(dialect: SQLDialect, conn: &PooledConnection, models: &Vec<Model>) -> Result<DatabaseState> {
+        let mut state = DatabaseState::default();
+        state.tables = Self::get_db_user_tables(dialect, conn).await?;
+        // sequence counters are not a model table
+        state.sequence_counters = state.tables.iter().any(|t| t == SEQUENCE_COUNTERS);
+        state.tables.retain(|t| t != SEQUENCE_COUNTERS);
         for model in models {
+            if model.r#virtual() || model.base().is_some() { continue }
+            let table_name = match Self::existing_table(model, &state.tables) {
+                Some(table_name) => table_name,
+                None => continue,
+            };
+            let columns = Self::db_columns(conn, dialect, &table_name).await?;
+            let indices = Self::db_indices(dialect, conn, &table_name).await?;
+            let has_records = Self::table_has_records(dialect, conn, &table_name).await?;
+            for index in model.stored_indices(models).iter().filter(|i| Self::is_expression_index(i, dialect)) {
+                let index_name = index.sql_name(model.table_name(), dialect).as_ref().to_owned();
+                let sql = match dialect {
+                    SQLDialect::MySQL => format!("SHOW INDEX FROM `{}` WHERE Key_name = '{}'", table_name, index_name),
+                    SQLDialect::PostgreSQL => format!("SELECT indexname FROM pg_indexes WHERE indexname = '{}'", index_name),
+                    _ => format!("SELECT name FROM sqlite_master WHERE type = 'index' AND name = '{}'", index_name),
+                };
+                if !Self::query(conn, sql, &table_name).await?.is_empty() {
+                    state.expression_indices.insert(index_name);
+                }
+            }
+            state.table_states.insert(table_name, TableState { columns, indices, has_records });
+        }
+        Ok(state)
+    }
+
+    /// The database table of a model, under its current name or a name it's renamed from.
+    fn existing_table(model: &Model, tables: &Vec<String>) -> Option<String> {
+        if tables.iter().any(|t| t == model.table_name()) {
+            return Some(model.table_name().to_owned());
+        }
+        model.migration().and_then(|migration| migration.renamed.iter().find(|old_name| tables.contains(old_name)).cloned())
+    }
+
+    fn plan(dialect: SQLDialect, state: &DatabaseState, models: &Vec<Model>, foreign_keys: bool, charset: &SQLCharset) -> Result<Vec<MigrationStep>> {
+        let mut steps: Vec<MigrationStep> = vec![];
+        let mut pending_foreign_keys: Vec<(String, SQLForeignKey)> = vec![];
+        let mut db_tables = state.tables.clone();
+        // create referenced tables first
+        let (order, cyclic) = table_creation_order(&Self::table_dependencies(models));
+        let ordered_models: Vec<&Model> = order.iter().map(|name| models.iter().find(|m| m.name() == name).unwrap()).collect();
+        // compare each table and do migration
+        for model in ordered_models {
             if model.r#virtual() { continue }
+            // subtypes share their base model's table, which holds their indices
+            if model.base().is_some() { continue }
+            let indices = model.stored_indices(models);
             let table_name = model.table_name();
+            // the state of a renamed table is read under its old name
+            let mut state_name = table_name.to_owned();
             if let Some(migration) = model.migration() {
                 if !db_tables.iter().any(|x| x == table_name) {
                     for old_name in &migration.renamed {
                         if db_tables.contains(old_name) {
                             // rename
-                            Self::rename_table(dialect, &conn, old_name.as_str(), table_name).await;
+                            let escape = dialect.escape();
+                            let sql = format!("ALTER TABLE {escape}{old_name}{escape} RENAME TO {escape}{table_name}{escape}");
+                            steps.push(MigrationStep::Execute(sql, old_name.to_owned()));
                             let index = db_tables.clone().iter().find_position(|v| *v == old_name).unwrap().0;
                             db_tables.remove(index);
                             db_tables.push(table_name.to_string());
+                            state_name = old_name.to_owned();
                             break;
                         }
                     }
@@ -168,110 +292,232 @@ impl SQLMigration {
             let is_table_exist = db_tables.iter().any(|x| x == table_name);
             if !is_table_exist {
                 // table not exist, create table
-                Self::create_table(dialect, &conn, model).await;
+                let (inline, deferred) = Self::foreign_keys_if_needed(dialect, foreign_keys, model, models, &cyclic);
+                steps.extend(Self::create_table(dialect, model, &indices, inline, charset));
+                pending_foreign_keys.extend(deferred.into_iter().map(|f| (table_name.to_owned(), f)));
             } else {
                 // remove from list
                 let index = db_tables.clone().iter().find_position(|x| *x == table_name).unwrap().0;
                 db_tables.remove(index);
                 // start migrate for this table
+                let table_state = match state.table_states.get(&state_name) {
+                    Some(table_state) => table_state,
+                    None => return Err(Error::internal_server_error(format!("Migrating table `{}' failed: table is not read from the database.", table_name))),
+                };
                 let model_columns = ColumnDecoder::decode_model_columns(model);
-                let db_columns = Self::db_columns(&conn, dialect, table_name).await;
-                let need_to_alter_any_column = ColumnDecoder::need_to_alter_any_columns(&db_columns, &model_columns);
+                let db_columns = &table_state.columns;
+                let need_to_alter_any_column = ColumnDecoder::need_to_alter_any_columns(db_columns, &model_columns);
                 if need_to_alter_any_column && dialect == SQLDialect::SQLite {
-                    panic!("SQLite doesn't support column altering");
+                    return Err(Error::internal_server_error(format!("Migrating table `{}' failed: SQLite doesn't support column altering.", table_name)));
                 }
-                let table_has_records = Self::table_has_records(dialect, &conn, table_name).await;
-                let db_indices = Self::db_indices(dialect, &conn, model).await;
-                let model_indices = Self::normalized_model_indices(model.indices(), dialect, table_name);
+                let table_has_records = table_state.has_records;
+                let model_indices = Self::normalized_model_indices(&indices, dialect, table_name);
                 // here update columns and indices
-                let manipulations = ColumnDecoder::manipulations(&db_columns, &model_columns, &db_indices, &model_indices, model);
+                let manipulations = ColumnDecoder::manipulations(db_columns, &model_columns, &table_state.indices, &model_indices, model);
                 if table_has_records && manipulations.iter().find(|m| m.is_add_column_non_null()).is_some() && model.allows_drop_when_migrate() {
-                    Self::drop_table(dialect, &conn, table_name).await;
-                    Self::create_table(dialect, &conn, model).await;
+                    steps.push(Self::drop_table(dialect, table_name));
+                    let (inline, deferred) = Self::foreign_keys_if_needed(dialect, foreign_keys, model, models, &cyclic);
+                    steps.extend(Self::create_table(dialect, model, &indices, inline, charset));
+                    pending_foreign_keys.extend(deferred.into_iter().map(|f| (table_name.to_owned(), f)));
                 } else {
                     for m in manipulations.iter() {
                         match m {
                             ColumnManipulation::CreateIndex(index) => {
                                 let create = index.to_sql_create(dialect, table_name);
-                                conn.execute(Query::from(create)).await.unwrap();
+                                steps.push(MigrationStep::Execute(create, table_name.to_owned()));
                             }
                             ColumnManipulation::DropIndex(index) => {
                                 let drop = index.to_sql_drop(dialect, table_name);
-                                conn.execute(Query::from(drop)).await.unwrap();
+                                steps.push(MigrationStep::Execute(drop, table_name.to_owned()));
                             }
                             ColumnManipulation::AddColumn(column, action, default) => {
-                                if column.not_null() && default.is_none() {
-                                    // if any records, just raise here
-                                    let has_records = Self::table_has_records(dialect, &conn, table_name).await;
-                                    if has_records {
-                                        panic!("Cannot add new non null column `{}', table `{}' has records. Consider add a default value or drop the table.", column.name(), table_name)
-                                    }
-                                }
                                 let mut c = column.clone().clone();
                                 if default.is_some() {
                                     c.set_default(Some(default.as_ref().unwrap().to_string(dialect)));
                                 }
-                                let stmt = SQL::alter_table(table_name).add(c).to_string(dialect);
-                                conn.execute(Query::from(stmt)).await.unwrap();
+                                if column.not_null() && default.is_none() && table_has_records {
+                                    // existing records are backfilled with the field's default
+                                    let field_default = model.field_with_column_name(column.name()).and_then(|f| f.default.clone());
+                                    steps.extend(Self::add_column_with_backfill(dialect, table_name, c, field_default)?);
+                                } else {
+                                    let stmt = SQL::alter_table(table_name).add(c).to_string(dialect);
+                                    steps.push(MigrationStep::Execute(stmt, table_name.to_owned()));
+                                }
                                 if let Some(action)= action {
-                                    let ctx = Ctx::initial_state_with_value(Value::Null);
-                                    action.process(ctx).await.unwrap();
+                                    steps.push(MigrationStep::Process(action.clone()));
                                 }
                             }
                             ColumnManipulation::AlterColumn(old_column, new_column, _action) => {
                                 if dialect != SQLDialect::PostgreSQL {
                                     let alter = SQL::alter_table(table_name).modify(new_column.clone().clone()).to_string(dialect);
-                                    conn.execute(Query::from(alter)).await.unwrap();
+                                    steps.push(MigrationStep::Execute(alter, table_name.to_owned()));
                                 } else {
                                     let clauses = Self::psql_alter_clauses(table_name, *old_column, *new_column);
                                     for clause in clauses {
-                                        conn.execute(Query::from(clause)).await.unwrap();
+                                        steps.push(MigrationStep::Execute(clause, table_name.to_owned()));
                                     }
                                 }
                             }
                             ColumnManipulation::RemoveColumn(name, action) => {
+                                if model.append_only() {
+                                    return Err(Error::internal_server_error(format!("Cannot drop column `{}' of append only table `{}'.", name, table_name)));
+                                }
                                 if let Some(action)= action {
-                                    let ctx = Ctx::initial_state_with_value(Value::Null);
-                                    action.process(ctx).await.unwrap();
+                                    steps.push(MigrationStep::Process(action.clone()));
                                 }
                                 let stmt = SQL::alter_table(table_name).drop_column(name).to_string(dialect);
-                                conn.execute(Query::from(stmt)).await.unwrap();
+                                steps.push(MigrationStep::Execute(stmt, table_name.to_owned()));
                             }
                             ColumnManipulation::RenameColumn { old, new } => {
-                                let stmt = if dialect == SQLDialect::PostgreSQL {
-                                    format!("ALTER TABLE {} RENAME COLUMN '{}' TO '{}'", table_name, old, new)
-                                } else {
-                                    format!("ALTER TABLE {} RENAME COLUMN `{}` TO `{}`", table_name, old, new)
-                                };
-                                conn.execute(Query::from(stmt)).await.unwrap();
+                                let stmt = SQL::alter_table(table_name).rename_column(old, (*new).clone()).to_string(dialect);
+                                steps.push(MigrationStep::Execute(stmt, table_name.to_owned()));
                             }
                         }
                     }
                 }
+                // expression indices are created by name when missing
+                for index in indices.iter().filter(|i| Self::is_expression_index(i, dialect)) {
+                    if !state.expression_indices.contains(index.sql_name(table_name, dialect).as_ref()) {
+                        steps.push(MigrationStep::Execute(index.to_sql_create(dialect, table_name), table_name.to_owned()));
+                    }
+                }
             }
         }
         // drop tables
         for table in db_tables {
-            Self::drop_table(dialect, &conn, &table).await;
+            steps.push(Self::drop_table(dialect, &table));
+        }
+        // add foreign keys between cyclic tables after they are all created
+        for (table, foreign_key) in pending_foreign_keys {
+            let stmt = format!("ALTER TABLE {} ADD {}", table.as_str().escape(dialect), foreign_key.to_string(dialect));
+            steps.push(MigrationStep::Execute(stmt, table));
         }
+        // create sequence counters
+        if !state.sequence_counters && models.iter().any(|m| m.fields().iter().any(|f| f.sequence.is_some())) {
+            let escape = dialect.escape();
+            let sql = format!("CREATE TABLE IF NOT EXISTS {escape}{SEQUENCE_COUNTERS}{escape} ({escape}name{escape} VARCHAR(255) NOT NULL PRIMARY KEY, {escape}value{escape} BIGINT NOT NULL)");
+            steps.push(MigrationStep::Execute(sql, SEQUENCE_COUNTERS.to_owned()));
+        }
+        Ok(steps)
     }
 
-    async fn drop_table(dialect: SQLDialect, conn: &PooledConnection, table: &str) {
+    fn drop_table(dialect: SQLDialect, table: &str) -> MigrationStep {
         let escape = dialect.escape();
         let sql = format!("DROP TABLE {escape}{table}{escape}");
-        conn.execute(Query::from(sql)).await.unwrap();
+        MigrationStep::Execute(sql, table.to_owned())
+    }
+
+    fn table_dependencies(models: &Vec<Model>) -> Vec<(String, Vec<String>)> {
+        models.iter().map(|model| {
+            let referenced = model.relations().iter().filter(|r| r.has_foreign_key() && !r.has_join_table()).map(|r| r.model().to_owned()).collect();
+            (model.name().to_owned(), referenced)
+        }).collect()
     }
 
-    async fn create_table(dialect: SQLDialect, conn: &PooledConnection, model: &Model) {
+    /// Returns foreign keys created inline and those added after all tables exist. Keys
+    /// between cyclic tables are deferred, except on SQLite which can't add constraints
+    /// later and doesn't check referenced tables at creation.
+    fn foreign_keys_if_needed(dialect: SQLDialect, foreign_keys: bool, model: &Model, models: &Vec<Model>, cyclic: &HashSet<String>) -> (Vec<SQLForeignKey>, Vec<SQLForeignKey>) {
+        let mut inline = vec![];
+        let mut deferred = vec![];
+        if !foreign_keys { return (inline, deferred) }
+        for relation in model.relations().iter().filter(|r| r.has_foreign_key() && !r.has_join_table()) {
+            if let Some(opposite_model) = models.iter().find(|m| m.name() == relation.model()) {
+                let foreign_key = SQLForeignKey::new(model, relation, opposite_model);
+                let in_cycle = cyclic.contains(model.name()) && cyclic.contains(opposite_model.name()) && model.name() != opposite_model.name();
+                if in_cycle && dialect != SQLDialect::SQLite {
+                    deferred.push(foreign_key);
+                } else {
+                    inline.push(foreign_key);
+                }
+            }
+        }
+        (inline, deferred)
+    }
+
+    fn create_table(dialect: SQLDialect, model: &Model, indices: &Vec<ModelIndex>, foreign_keys: Vec<SQLForeignKey>, charset: &SQLCharset) -> Vec<MigrationStep> {
+        let mut steps = vec![];
         // create table
-        let stmt = SQLCreateTableStatement::from(model).to_string(dialect);
-        conn.execute(Query::from(stmt)).await.unwrap();
+        let mut stmt = SQLCreateTableStatement::from(model);
+        stmt.charset(charset.clone());
+        for foreign_key in foreign_keys {
+            stmt.foreign_key(foreign_key);
+        }
+        let stmt = stmt.to_string(dialect);
+        steps.push(MigrationStep::Execute(stmt, model.table_name().to_owned()));
         // create indices
-        for index in model.indices() {
+        for index in indices {
             // primary is created when creating table
             if index.r#type().is_primary() { continue }
             let stmt = index.to_sql_create(dialect, model.table_name());
-            conn.execute(Query::from(stmt)).await.unwrap();
+            steps.push(MigrationStep::Execute(stmt, model.table_name().to_owned()));
+        }
+        steps
+    }
+
+    async fn execute(conn: &PooledConnection, sql: String, table: &str) -> Result<()> {
+        Self::execute_count(conn, sql, table).await.map(|_| ())
+    }
+
+    async fn execute_count(conn: &PooledConnection, sql: String, table: &str) -> Result<u64> {
+        match conn.execute(Query::from(sql.as_str())).await {
+            Ok(count) => Ok(count),
+            Err(err) => Err(Error::internal_server_error(format!("Migrating table `{}' failed: {}. SQL: {}", table, err, sql))),
+        }
+    }
+
+    async fn query(conn: &PooledConnection, sql: String, table: &str) -> Result<ResultSet> {
+        match conn.query(Query::from(sql.as_str())).await {
+            Ok(result_set) => Ok(result_set),
+            Err(err) => Err(Error::internal_server_error(format!("Migrating table `{}' failed: {}. SQL: {}", table, err, sql))),
+        }
+    }
+
+    /// Adds a non null column to a table which has records. The column is
+    /// added nullable, filled with the default and then made non null. SQLite
+    /// can't alter columns, a constant default is added as the column default.
+    fn add_column_with_backfill(dialect: SQLDialect, table: &str, column: SQLColumn, default: Option<Value>) -> Result<Vec<MigrationStep>> {
+        let name = column.name().to_owned();
+        let default = match default {
+            Some(default) => default,
+            None => return Err(Error::internal_server_error(format!("Cannot add new non null column `{}', table `{}' has records and the field has no default value to backfill them. Consider add a default value or drop the table.", name, table))),
+        };
+        if dialect == SQLDialect::SQLite {
+            if default.is_pipeline() {
+                return Err(Error::internal_server_error(format!("Cannot add new non null column `{}', table `{}' has records and SQLite can only backfill them with a constant default value.", name, table)));
+            }
+            let mut column = column;
+            column.set_default(Some(default.to_string(dialect)));
+            return Ok(vec![MigrationStep::Execute(SQL::alter_table(table).add(column).to_string(dialect), table.to_owned())]);
+        }
+        let mut nullable = column.clone();
+        nullable.set_not_null(false);
+        let require = if dialect == SQLDialect::PostgreSQL {
+            let escape = dialect.escape();
+            format!("ALTER TABLE {escape}{table}{escape} ALTER COLUMN {escape}{name}{escape} SET NOT NULL")
+        } else {
+            SQL::alter_table(table).modify(column).to_string(dialect)
+        };
+        Ok(vec![
+            MigrationStep::Execute(SQL::alter_table(table).add(nullable).to_string(dialect), table.to_owned()),
+            MigrationStep::Backfill(table.to_owned(), name, default),
+            MigrationStep::Execute(require, table.to_owned()),
+        ])
+    }
+
+    fn backfill_sql(dialect: SQLDialect, table: &str, column: &str, value: &Value) -> String {
+        let escape = dialect.escape();
+        format!("UPDATE {escape}{table}{escape} SET {escape}{column}{escape} = {} WHERE {escape}{column}{escape} IS NULL", value.to_string(dialect))
+    }
+
+    /// Fills the column of one record which isn't backfilled yet.
+    fn backfill_row_sql(dialect: SQLDialect, table: &str, column: &str, value: &Value) -> String {
+        let escape = dialect.escape();
+        let value = value.to_string(dialect);
+        match dialect {
+            SQLDialect::MySQL => format!("UPDATE {escape}{table}{escape} SET {escape}{column}{escape} = {value} WHERE {escape}{column}{escape} IS NULL LIMIT 1"),
+            SQLDialect::PostgreSQL => format!("UPDATE {escape}{table}{escape} SET {escape}{column}{escape} = {value} WHERE ctid IN (SELECT ctid FROM {escape}{table}{escape} WHERE {escape}{column}{escape} IS NULL LIMIT 1)"),
+            _ => format!("UPDATE {escape}{table}{escape} SET {escape}{column}{escape} = {value} WHERE rowid IN (SELECT rowid FROM {escape}{table}{escape} WHERE {escape}{column}{escape} IS NULL LIMIT 1)"),
         }
     }
 
@@ -294,53 +540,75 @@ impl SQLMigration {
         result
     }
 
+    /// Expression indices aren't read back from the database, they are created
+    /// by name when missing.
+    fn is_expression_index(index: &ModelIndex, dialect: SQLDialect) -> bool {
+        index.case_insensitive() || index.soft_delete().is_some() || (index.r#type().is_fulltext() && dialect == SQLDialect::PostgreSQL)
+    }
+
     fn normalized_model_indices(indices: &Vec<ModelIndex>, dialect: SQLDialect, table_name: &str) -> HashSet<ModelIndex> {
-        indices.iter().map(|index| {
-            let mut index = index.clone();
+        indices.iter().filter(|index| !Self::is_expression_index(index, dialect)).map(|index| {
+            let mut index = if index.r#type().is_fulltext() && dialect == SQLDialect::SQLite {
+                // SQLite has no fulltext indices, a plain index is created instead
+                ModelIndex::new(ModelIndexType::Index, index.name(), index.items().clone())
+            } else if dialect != SQLDialect::MySQL && index.items().iter().any(|i| i.len().is_some()) {
+                // only MySQL indexes column prefixes, others index the whole column
+                ModelIndex::new(index.r#type(), index.name(), index.items().iter().map(|i| ModelIndexItem::new(i.field_name(), i.sort(), None)).collect())
+            } else {
+                index.clone()
+            };
             let sql_name_cow = index.sql_name(table_name, dialect);
             let sql_name = sql_name_cow.as_ref().to_owned();
             index.set_name(sql_name);
+            // SQL unique indices never conflict on null values, sparse is a no-op here
+            index.set_sparse(false);
             index
         }).collect()
     }
 
-    async fn db_indices(dialect: SQLDialect, conn: &PooledConnection, model: &Model) -> HashSet<ModelIndex> {
+    async fn db_indices(dialect: SQLDialect, conn: &PooledConnection, table_name: &str) -> Result<HashSet<ModelIndex>> {
         match dialect {
-            SQLDialect::PostgreSQL => Self::psql_db_indices(conn, model).await,
-            SQLDialect::MySQL => Self::mysql_db_indices(conn, model).await,
-            SQLDialect::SQLite => Self::sqlite_db_indices(conn, model).await,
+            SQLDialect::PostgreSQL => Self::psql_db_indices(conn, table_name).await,
+            SQLDialect::MySQL => Self::mysql_db_indices(conn, table_name).await,
+            SQLDialect::SQLite => Self::sqlite_db_indices(conn, table_name).await,
             _ => unreachable!(),
         }
     }
 
-    async fn mysql_db_indices(conn: &PooledConnection, model: &Model) -> HashSet<ModelIndex> {
-        let table_name = model.table_name();
+    async fn mysql_db_indices(conn: &PooledConnection, table_name: &str) -> Result<HashSet<ModelIndex>> {
         let sql = format!("SHOW INDEX FROM `{}`", table_name);
-        let result_set = conn.query(Query::from(sql)).await.unwrap();
+        let result_set = Self::query(conn, sql, table_name).await?;
         let mut indices = vec![];
         for row in result_set {
             let index_name = row.get("Key_name").unwrap().as_str().unwrap();
-            let column_name = row.get("Column_name").unwrap().as_str().unwrap();
-            let order = Sort::from_mysql_str(row.get("Collation").unwrap().as_str().unwrap()).unwrap();
+            // expression key parts have no column name
+            let column_name = match row.get("Column_name").unwrap().as_str() {
+                Some(column_name) => column_name,
+                None => continue,
+            };
+            // fulltext key parts have no collation
+            let order = row.get("Collation").unwrap().as_str().map_or(Sort::Asc, |c| Sort::from_mysql_str(c).unwrap());
+            // prefix length of partly indexed columns
+            let len = row.get("Sub_part").unwrap().as_i64().map(|l| l as usize);
             if let Some(position) = indices.iter().position(|m: &ModelIndex| m.name().unwrap() == index_name) {
                 let model_index = indices.get_mut(position).unwrap();
-                let item = ModelIndexItem::new(column_name, order, None);
+                let item = ModelIndexItem::new(column_name, order, len);
                 model_index.append_item(item);
             } else {
                 let is_unique = !row.get("Non_unique").unwrap().as_bool().unwrap();
-                let item = ModelIndexItem::new(column_name, order, None);
+                let is_fulltext = row.get("Index_type").unwrap().as_str() == Some("FULLTEXT");
+                let item = ModelIndexItem::new(column_name, order, len);
                 indices.push(ModelIndex::new(
-                    if index_name == "PRIMARY" { ModelIndexType::Primary } else if is_unique { ModelIndexType::Unique } else { ModelIndexType::Index },
+                    if index_name == "PRIMARY" { ModelIndexType::Primary } else if is_unique { ModelIndexType::Unique } else if is_fulltext { ModelIndexType::Fulltext } else { ModelIndexType::Index },
                     Some(index_name),
                     vec![item],
                 ))
             }
         }
-        indices.into_iter().collect()
+        Ok(indices.into_iter().collect())
     }
 
-    async fn psql_db_indices(conn: &PooledConnection, model: &Model) -> HashSet<ModelIndex> {
-        let table_name = model.table_name();
+    async fn psql_db_indices(conn: &PooledConnection, table_name: &str) -> Result<HashSet<ModelIndex>> {
         let sql = format!(r#"SELECT     irel.relname                           AS index_name,
            a.attname                              AS column_name,
            i.indisunique                          AS is_unique,
@@ -375,7 +643,7 @@ GROUP BY   tnsp.nspname,
            array_position(i.indkey, a.attnum),
            o.OPTION ORDER BY column_position
 "#);
-        let result_set = conn.query(Query::from(sql)).await.unwrap();
+        let result_set = Self::query(conn, sql, table_name).await?;
         let mut indices = vec![];
         for row in result_set {
             let index_name = row.get("index_name").unwrap().as_str().unwrap();
@@ -396,11 +664,10 @@ GROUP BY   tnsp.nspname,
                 ))
             }
         }
-        indices.into_iter().collect()
+        Ok(indices.into_iter().collect())
     }
 
-    async fn sqlite_db_indices(conn: &PooledConnection, model: &Model) -> HashSet<ModelIndex> {
-        let table_name = model.table_name();
+    async fn sqlite_db_indices(conn: &PooledConnection, table_name: &str) -> Result<HashSet<ModelIndex>> {
         let sql = format!(r#"SELECT
     il.name as index_name,
     ii.name as column_name,
@@ -426,7 +693,7 @@ GROUP BY
     il.seq,
     ii.`desc`
 ORDER BY 1,6"#, table_name);
-        let result_set = conn.query(Query::from(sql)).await.unwrap();
+        let result_set = Self::query(conn, sql, table_name).await?;
         let mut indices = vec![];
         for row in result_set {
             let index_name = row.get("index_name").unwrap().as_str().unwrap();
@@ -447,6 +714,183 @@ ORDER BY 1,6"#, table_name);
                 ))
             }
         }
-        indices.into_iter().collect()
+        Ok(indices.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::connectors::sql::schema::r#type::field::ToDatabaseType;
+    use crate::core::field::{Field, FieldIndex, IndexSettings};
+    use crate::core::field::r#type::FieldType;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::model::builder::ModelBuilder;
+    use crate::core::test_connector::TestConnector;
+    use crate::parser::ast::config::FieldNaming;
+    use super::*;
+
+    fn sqlite_types() -> Arc<TestConnector> {
+        Arc::new(TestConnector { database_type: Some(|field_type| field_type.to_database_type(SQLDialect::SQLite)), ..Default::default() })
+    }
+
+    #[tokio::test]
+    async fn subtype_indices_are_created_on_the_base_table() {
+        let mut builder = GraphBuilder::new();
+        builder.model("Animal", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let mut kind = Field::new("kind".to_owned());
+            kind.field_type = Some(FieldType::String);
+            m.field(id).field(kind).discriminator("kind");
+        });
+        builder.model("Dog", |m| {
+            let mut chip_number = Field::new("chipNumber".to_owned());
+            chip_number.field_type = Some(FieldType::String);
+            chip_number.index = Some(FieldIndex::Unique(IndexSettings::default()));
+            m.extends("Animal", "dog").field(chip_number);
+        });
+        let graph = builder.build(sqlite_types()).await;
+        let steps = SQLMigration::plan(SQLDialect::SQLite, &DatabaseState::default(), graph.models(), false, &SQLCharset::default()).unwrap();
+        let statements: Vec<(&String, &String)> = steps.iter().map(|step| match step {
+            MigrationStep::Execute(sql, table) => (sql, table),
+            _ => panic!("expected a statement"),
+        }).collect();
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].0.starts_with("CREATE TABLE"));
+        assert!(statements[1].0.starts_with("CREATE UNIQUE INDEX"));
+        assert!(statements[1].0.ends_with("(`chipNumber` ASC)"));
+        assert!(statements.iter().all(|(_, table)| table.as_str() == "animals"));
+    }
+
+    #[test]
+    fn new_model_is_planned_as_create_table() {
+        let mut id = Field::new("id".to_owned());
+        id.field_type = Some(FieldType::I32);
+        id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+        let mut builder = ModelBuilder::new("Post");
+        builder.field(id);
+        let model = builder.build(sqlite_types(), FieldNaming::AsDefined);
+        let steps = SQLMigration::plan(SQLDialect::SQLite, &DatabaseState::default(), &vec![model], false, &SQLCharset::default()).unwrap();
+        assert_eq!(steps.len(), 1);
+        match &steps[0] {
+            MigrationStep::Execute(sql, table) => {
+                assert!(sql.starts_with("CREATE TABLE"));
+                assert_eq!(table, "posts");
+            }
+            _ => panic!("expected a statement"),
+        }
+    }
+
+    fn user_model(name_field: Field) -> Model {
+        let mut id = Field::new("id".to_owned());
+        id.field_type = Some(FieldType::I32);
+        id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+        let mut builder = ModelBuilder::new("User");
+        builder.field(id).field(name_field);
+        builder.build(sqlite_types(), FieldNaming::AsDefined)
+    }
+
+    #[test]
+    fn renamed_field_renames_its_column_instead_of_dropping_it() {
+        let mut name = Field::new("name".to_owned());
+        name.field_type = Some(FieldType::String);
+        let old_model = user_model(name);
+        let mut full_name = Field::new("fullName".to_owned());
+        full_name.field_type = Some(FieldType::String);
+        full_name.renamed_from("name");
+        let model = user_model(full_name);
+        let mut state = DatabaseState::default();
+        state.tables = vec!["users".to_owned()];
+        state.table_states.insert("users".to_owned(), TableState {
+            columns: ColumnDecoder::decode_model_columns(&old_model),
+            indices: SQLMigration::normalized_model_indices(old_model.indices(), SQLDialect::SQLite, "users"),
+            has_records: true,
+        });
+        let steps = SQLMigration::plan(SQLDialect::SQLite, &state, &vec![model.clone()], false, &SQLCharset::default()).unwrap();
+        let statements: Vec<&String> = steps.iter().map(|step| match step {
+            MigrationStep::Execute(sql, _) => sql,
+            _ => panic!("expected a statement"),
+        }).collect();
+        assert_eq!(statements, vec!["ALTER TABLE `users` RENAME COLUMN `name` TO `fullName`"]);
+        // MySQL before 8.0 has no RENAME COLUMN
+        let column = ColumnDecoder::decode_model_columns(&model).into_iter().find(|c| c.name() == "fullName").unwrap();
+        let sql = SQL::alter_table("users").rename_column("name", column).to_string(SQLDialect::MySQL);
+        assert!(sql.starts_with("ALTER TABLE `users` CHANGE COLUMN `name` `fullName` "));
+        assert!(sql.ends_with(" NOT NULL"));
+    }
+
+    fn state_with_records(model: &Model) -> DatabaseState {
+        let mut state = DatabaseState::default();
+        state.tables = vec!["users".to_owned()];
+        state.table_states.insert("users".to_owned(), TableState {
+            columns: ColumnDecoder::decode_model_columns(model),
+            indices: SQLMigration::normalized_model_indices(model.indices(), SQLDialect::MySQL, "users"),
+            has_records: true,
+        });
+        state
+    }
+
+    fn required_age(default: Option<Value>) -> Field {
+        let mut age = Field::new("age".to_owned());
+        age.field_type = Some(FieldType::I32);
+        age.default = default;
+        age
+    }
+
+    #[test]
+    fn new_required_column_backfills_existing_records_with_default() {
+        let mut name = Field::new("name".to_owned());
+        name.field_type = Some(FieldType::String);
+        let old_model = user_model(name.clone());
+        let mut id = Field::new("id".to_owned());
+        id.field_type = Some(FieldType::I32);
+        id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+        let mut builder = ModelBuilder::new("User");
+        builder.field(id).field(name).field(required_age(Some(Value::I32(0))));
+        let model = builder.build(sqlite_types(), FieldNaming::AsDefined);
+        let steps = SQLMigration::plan(SQLDialect::MySQL, &state_with_records(&old_model), &vec![model], false, &SQLCharset::default()).unwrap();
+        assert_eq!(steps.len(), 3);
+        match &steps[0] {
+            MigrationStep::Execute(sql, _) => {
+                assert!(sql.starts_with("ALTER TABLE `users` ADD `age`"));
+                assert!(!sql.contains("NOT NULL"));
+            }
+            _ => panic!("expected a statement"),
+        }
+        match &steps[1] {
+            MigrationStep::Backfill(table, column, default) => {
+                assert_eq!(table, "users");
+                assert_eq!(column, "age");
+                assert_eq!(SQLMigration::backfill_sql(SQLDialect::MySQL, table, column, default), "UPDATE `users` SET `age` = 0 WHERE `age` IS NULL");
+                // computed defaults are evaluated for each record
+                assert_eq!(SQLMigration::backfill_row_sql(SQLDialect::MySQL, table, column, default), "UPDATE `users` SET `age` = 0 WHERE `age` IS NULL LIMIT 1");
+                assert_eq!(SQLMigration::backfill_row_sql(SQLDialect::PostgreSQL, table, column, default), "UPDATE \"users\" SET \"age\" = 0 WHERE ctid IN (SELECT ctid FROM \"users\" WHERE \"age\" IS NULL LIMIT 1)");
+            }
+            _ => panic!("expected a backfill"),
+        }
+        match &steps[2] {
+            MigrationStep::Execute(sql, _) => {
+                assert!(sql.starts_with("ALTER TABLE `users` MODIFY `age`"));
+                assert!(sql.contains("NOT NULL"));
+            }
+            _ => panic!("expected a statement"),
+        }
+    }
+
+    #[test]
+    fn new_required_column_without_default_cannot_be_added_to_records() {
+        let mut name = Field::new("name".to_owned());
+        name.field_type = Some(FieldType::String);
+        let old_model = user_model(name.clone());
+        let mut id = Field::new("id".to_owned());
+        id.field_type = Some(FieldType::I32);
+        id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+        let mut builder = ModelBuilder::new("User");
+        builder.field(id).field(name).field(required_age(None));
+        let model = builder.build(sqlite_types(), FieldNaming::AsDefined);
+        let result = SQLMigration::plan(SQLDialect::MySQL, &state_with_records(&old_model), &vec![model], false, &SQLCharset::default());
+        assert!(result.is_err());
     }
 }
@@ -1,2 +1,3 @@
 pub(crate) mod migrate;
 pub(crate) mod sql;
+pub(crate) mod order;
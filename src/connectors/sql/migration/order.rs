@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+
+/// Orders tables so that every table is created after the tables it references.
+///
+/// `dependencies` lists each table with the tables it references. References to
+/// itself or to unlisted tables are ignored. Tables that take part in or depend
+/// on a cycle can't be ordered; they are appended in their original order and
+/// returned in the second set, so that their constraints can be added after
+/// creation.
+pub(crate) fn table_creation_order(dependencies: &Vec<(String, Vec<String>)>) -> (Vec<String>, HashSet<String>) {
+    let names: HashSet<&str> = dependencies.iter().map(|(name, _)| name.as_str()).collect();
+    let mut remaining: Vec<(&str, Vec<&str>)> = dependencies.iter().map(|(name, deps)| {
+        (name.as_str(), deps.iter().map(|d| d.as_str()).filter(|d| d != name && names.contains(d)).collect())
+    }).collect();
+    let mut ordered: Vec<String> = vec![];
+    loop {
+        let ready: Vec<&str> = remaining.iter().filter(|(_, deps)| {
+            deps.iter().all(|d| ordered.iter().any(|o| o == d))
+        }).map(|(name, _)| *name).collect();
+        if ready.is_empty() { break }
+        ordered.extend(ready.iter().map(|n| n.to_string()));
+        remaining.retain(|(name, _)| !ready.contains(name));
+    }
+    let cyclic: HashSet<String> = remaining.iter().map(|(name, _)| name.to_string()).collect();
+    ordered.extend(remaining.iter().map(|(name, _)| name.to_string()));
+    (ordered, cyclic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deps(list: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+        list.iter().map(|(n, d)| (n.to_string(), d.iter().map(|s| s.to_string()).collect())).collect()
+    }
+
+    #[test]
+    fn dependency_chain_is_created_referenced_first() {
+        let (order, cyclic) = table_creation_order(&deps(&[
+            ("Comment", &["Post", "User"]),
+            ("Post", &["User"]),
+            ("User", &["User"]),
+            ("Tag", &[]),
+        ]));
+        assert_eq!(order, vec!["User", "Tag", "Post", "Comment"]);
+        assert!(cyclic.is_empty());
+    }
+
+    #[test]
+    fn cyclic_tables_are_appended_and_reported() {
+        let (order, cyclic) = table_creation_order(&deps(&[
+            ("Team", &["User"]),
+            ("User", &["Team"]),
+            ("Profile", &["User"]),
+            ("Country", &[]),
+        ]));
+        assert_eq!(order, vec!["Country", "Team", "User", "Profile"]);
+        assert_eq!(cyclic, HashSet::from(["Team".to_owned(), "User".to_owned(), "Profile".to_owned()]));
+    }
+}
@@ -5,3 +5,4 @@ pub mod schema;
 pub mod execution;
 pub mod query;
 pub(crate) mod url;
+pub(crate) mod pool;
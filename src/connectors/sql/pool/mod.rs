@@ -0,0 +1,58 @@
+use std::ops::Deref;
+use std::time::Instant;
+use quaint_forked::error::{Error as QuaintError, ErrorKind};
+use quaint_forked::pooled::{PooledConnection, Quaint};
+use crate::core::database::pool_metrics::PoolMetrics;
+
+/// A connection pool which records checkouts into `PoolMetrics`. Quaint
+/// doesn't report the connections its pool opens, so idle connections are
+/// unknown.
+pub(crate) struct SQLPool {
+    pool: Quaint,
+    metrics: PoolMetrics,
+}
+
+impl SQLPool {
+
+    pub(crate) fn new(pool: Quaint) -> Self {
+        Self { pool, metrics: PoolMetrics::new() }
+    }
+
+    pub(crate) fn metrics(&self) -> &PoolMetrics {
+        &self.metrics
+    }
+
+    pub(crate) async fn check_out(&self) -> Result<MeteredConnection<'_>, QuaintError> {
+        let start = Instant::now();
+        match self.pool.check_out().await {
+            Ok(conn) => {
+                self.metrics.record_checked_out(start.elapsed());
+                Ok(MeteredConnection { conn, metrics: &self.metrics })
+            }
+            Err(err) => {
+                self.metrics.record_checkout_failed(start.elapsed(), matches!(err.kind(), ErrorKind::PoolTimeout { .. }));
+                Err(err)
+            }
+        }
+    }
+}
+
+/// A checked out connection, which is counted as active until dropped.
+pub(crate) struct MeteredConnection<'a> {
+    conn: PooledConnection,
+    metrics: &'a PoolMetrics,
+}
+
+impl<'a> Deref for MeteredConnection<'a> {
+    type Target = PooledConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl<'a> Drop for MeteredConnection<'a> {
+    fn drop(&mut self) {
+        self.metrics.record_checked_in();
+    }
+}
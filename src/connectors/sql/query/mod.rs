@@ -10,8 +10,13 @@ use crate::connectors::sql::stmts::SQL;
 use crate::core::field::r#type::{FieldType, FieldTypeOwner};
 use crate::core::input::Input;
 use crate::core::model::Model;
+use crate::core::model::index::ModelIndex;
+use crate::core::teon::filter::cursor_where;
 use crate::prelude::{Graph, Object, Value};
 
+/// The column `build_with_total_count` reads the count of all matched rows into.
+pub(crate) const TOTAL_COUNT: &str = "_teo_total";
+
 pub(crate) struct Query { }
 
 impl Query {
@@ -40,6 +45,15 @@ impl Query {
         Query::where_item(column_name, op, &arr.join(", ").to_wrapped())
     }
 
+    /// JSON columns compare by value, not by text, in MySQL and PostgreSQL.
+    fn json_literal(literal: String, dialect: SQLDialect) -> String {
+        match dialect {
+            SQLDialect::MySQL => format!("CAST({literal} AS JSON)"),
+            SQLDialect::PostgreSQL => format!("{literal}::jsonb"),
+            _ => literal,
+        }
+    }
+
     fn where_entry_item(
         column_name: &str,
         r#type: &FieldType,
@@ -48,13 +62,31 @@ impl Query {
         graph: &Graph,
         dialect: SQLDialect,
     ) -> String {
+        let unescaped_column_name = column_name;
         let column_name = column_name.escape(dialect);
         if let Some(map) = value.as_hashmap() {
             let mut result: Vec<String> = vec![];
             for (key, value) in map {
                 match key.as_str() {
+                    "equals" if value.is_null() => {
+                        result.push(Self::where_item(&column_name, "IS", "NULL"));
+                    }
+                    "not" if value.is_null() => {
+                        result.push(Self::where_item(&column_name, "IS NOT", "NULL"));
+                    }
+                    "not" if value.is_hashmap() => {
+                        let negated = format!("NOT {}", Self::where_entry_item(unescaped_column_name, r#type, optional, value, graph, dialect));
+                        // NULL fails both a filter and its negation, but a negated filter keeps NULL in MongoDB
+                        if optional {
+                            result.push(WhereClause::Or(vec![negated, Self::where_item(&column_name, "IS", "NULL")]).to_wrapped_string(dialect));
+                        } else {
+                            result.push(negated);
+                        }
+                    }
                     "equals" => {
-                        result.push(Self::where_item(&column_name, "=", &value.to_sql_string(r#type, optional, graph)));
+                        let rhs = value.to_sql_string(r#type, optional, graph);
+                        let rhs = if r#type.is_json() && !value.is_null() { Self::json_literal(rhs, dialect) } else { rhs };
+                        result.push(Self::where_item(&column_name, "=", &rhs));
                     }
                     "not" => {
                         result.push(Self::where_item(&column_name, "<>", &value.to_sql_string(r#type, optional, graph)));
@@ -123,6 +155,8 @@ impl Query {
                 }
             }
             And(result).to_wrapped_string(dialect)
+        } else if value.is_null() {
+            Query::where_item(column_name, "IS", "NULL")
         } else {
             Query::where_item(column_name, "=", &value.to_sql_string(r#type, optional, graph))
         }
@@ -149,6 +183,24 @@ impl Query {
         And(retval).to_string(dialect)
     }
 
+    /// Matches `term` against every column of the fulltext index of `key`.
+    fn search(model: &Model, graph: &Graph, key: &str, term: &Value, dialect: SQLDialect, table_alias: Option<&str>) -> String {
+        let index = model.fulltext_index_for(key).unwrap();
+        let columns: Vec<String> = index.keys().iter().map(|k| {
+            let column_name = model.field(k).unwrap().column_name().escape(dialect);
+            match table_alias {
+                Some(alias) => format!("{}.{}", alias, column_name),
+                None => column_name,
+            }
+        }).collect();
+        let term = term.to_sql_string(&FieldType::String, false, graph);
+        match dialect {
+            SQLDialect::MySQL => format!("MATCH({}) AGAINST ({} IN NATURAL LANGUAGE MODE)", columns.join(","), term),
+            SQLDialect::PostgreSQL => format!("{} @@ plainto_tsquery('simple', {})", ModelIndex::psql_fulltext_document(&columns), term),
+            _ => WhereClause::Or(columns.iter().map(|c| Self::where_item(c, "LIKE", &term.to_like(true, true))).collect()).to_wrapped_string(dialect),
+        }
+    }
+
     pub(crate) fn r#where(model: &Model, graph: &Graph, r#where: &Value, dialect: SQLDialect, table_alias: Option<&str>) -> String {
         let r#where = r#where.as_hashmap().unwrap();
         let mut retval: Vec<String> = vec![];
@@ -158,7 +210,7 @@ impl Query {
                 let val = "(".to_owned() + &inner + ")";
                 retval.push(val);
             } else if key == "OR" {
-                let inner = WhereClause::Or(value.as_vec().unwrap().iter().map(|_w| Self::r#where(model, graph, value, dialect, table_alias)).collect()).to_string(dialect);
+                let inner = WhereClause::Or(value.as_vec().unwrap().iter().map(|w| Self::r#where(model, graph, w, dialect, table_alias)).collect()).to_string(dialect);
                 let val = "(".to_owned() + &inner + ")";
                 retval.push(val);
             } else if key == "NOT" {
@@ -174,7 +226,24 @@ impl Query {
                     } else {
                         Cow::Borrowed(column_name)
                     };
-                    let where_entry = Query::where_entry(&entry_column_name, field.field_type(), optional, value, graph, dialect);
+                    if let Some(term) = value.get("search") {
+                        retval.push(Self::search(model, graph, key, term, dialect, table_alias));
+                        let mut rest = value.as_hashmap().unwrap().clone();
+                        rest.remove("search");
+                        if !rest.is_empty() {
+                            retval.push(Query::where_entry(&entry_column_name, field.field_type(), optional, &Value::HashMap(rest), graph, dialect));
+                        }
+                        continue;
+                    }
+                    let equals = if value.is_hashmap() { value.get("equals").filter(|_| value.as_hashmap().unwrap().len() == 1) } else { Some(value) };
+                    let where_entry = match equals {
+                        // compare the same way as the case-insensitive index does
+                        Some(equals) if model.is_case_insensitive_field(key) && !equals.is_null() => {
+                            let column_name: &str = &entry_column_name;
+                            format!("LOWER({}) = LOWER({})", column_name.escape(dialect), equals.to_sql_string(field.field_type(), optional, graph))
+                        }
+                        _ => Query::where_entry(&entry_column_name, field.field_type(), optional, value, graph, dialect),
+                    };
                     retval.push(where_entry);
                 } else if let Some(relation) = model.relation(key) {
                     let has_join_table = relation.has_join_table();
@@ -210,13 +279,13 @@ impl Query {
                                 let f = join_model.field(f).unwrap().column_name();
                                 let r = opposite_model.field(r).unwrap().column_name();
                                 format!("j.{} = t.{}", r.escape(dialect), f.escape(dialect))
-                            }).collect::<Vec<String>>().join(",")
+                            }).collect::<Vec<String>>().join(" AND ")
                         } else {
                             relation.iter().map(|(f, r)| {
                                 let f = model.field(f).unwrap().column_name();
                                 let r = opposite_model.field(r).unwrap().column_name();
                                 format!("j.{} = t.{}", r.escape(dialect), f.escape(dialect))
-                            }).collect::<Vec<String>>().join(",")
+                            }).collect::<Vec<String>>().join(" AND ")
                         };
                         let addition_where = if has_join_table {
                             let (m, r) = graph.through_relation(relation);
@@ -299,6 +368,17 @@ impl Query {
         format!("SELECT COUNT(*) FROM ({}) AS _", Self::build(model, graph, value, dialect, additional_where, additional_left_join, join_table_results, force_negative_take))
     }
 
+    /// Selects the page of `build` together with the count of every row the
+    /// where clause matches. The window runs before limit and offset apply.
+    pub(crate) fn build_with_total_count(
+        model: &Model,
+        graph: &Graph,
+        value: &Value,
+        dialect: SQLDialect,
+    ) -> String {
+        Self::build_internal(model, graph, value, dialect, None, None, None, false, true)
+    }
+
     pub(crate) fn build_for_group_by(
         model: &Model,
         graph: &Graph,
@@ -372,6 +452,20 @@ impl Query {
         additional_left_join: Option<String>,
         join_table_results: Option<Vec<String>>,
         force_negative_take: bool,
+    ) -> String {
+        Self::build_internal(model, graph, value, dialect, additional_where, additional_left_join, join_table_results, force_negative_take, false)
+    }
+
+    fn build_internal(
+        model: &Model,
+        graph: &Graph,
+        value: &Value,
+        dialect: SQLDialect,
+        additional_where: Option<String>,
+        additional_left_join: Option<String>,
+        join_table_results: Option<Vec<String>>,
+        force_negative_take: bool,
+        with_total_count: bool,
     ) -> String {
         let r#where = value.get("where");
         let order_by = value.get("orderBy");
@@ -401,27 +495,14 @@ impl Query {
                 columns.push(result_key);
             }
         }
+        if with_total_count {
+            if columns.is_empty() {
+                columns.push("*".to_owned());
+            }
+            columns.push(format!("COUNT(*) OVER() AS {}", TOTAL_COUNT.escape(dialect)));
+        }
         let column_refs = columns.iter().map(|c| c.as_str()).collect::<Vec<&str>>();
-        let from = if let Some(cursor) = cursor {
-            let order_by = order_by.unwrap().as_vec().unwrap().get(0).unwrap().as_hashmap().unwrap();
-            let key = order_by.keys().next().unwrap();
-            let column_key = model.field(key).unwrap().column_name();
-            let columns = cursor.as_hashmap().unwrap().keys().map(|_k| {
-                if dialect == SQLDialect::PostgreSQL {
-                    format!("{} AS \"c.{}\"", column_key, column_key)
-                } else {
-                    format!("{} AS `c.{}`", column_key, column_key)
-                }
-            }).collect::<Vec<String>>();
-            let column_refs: Vec<&str> = columns.iter().map(|k| k.as_str()).collect();
-            let sub_where = Query::r#where(model, graph, cursor, dialect, None);
-            let mut query = SQL::select(Some(&column_refs), &table_name);
-            query.r#where(sub_where);
-            Cow::Owned(format!("{}, ({}) AS c", &table_name, &query.to_string(dialect)))
-        } else {
-            Cow::Borrowed(&table_name)
-        };
-        let mut stmt = SQL::select(if columns.is_empty() { None } else { Some(&column_refs) }, from.as_ref());
+        let mut stmt = SQL::select(if columns.is_empty() { None } else { Some(&column_refs) }, &table_name);
         if let Some(r#where) = r#where {
             if !r#where.as_hashmap().unwrap().is_empty() {
                 stmt.r#where(Query::r#where(model, graph, r#where, dialect, None));
@@ -434,16 +515,12 @@ impl Query {
                 stmt.r#where(additional_where.to_string());
             }
         }
-        if cursor.is_some() {
-            let order_by = order_by.unwrap().as_vec().unwrap().get(0).unwrap().as_hashmap().unwrap();
-            let key = order_by.keys().next().unwrap();
-            let order = if order_by.values().next().unwrap().as_str().unwrap() == if negative_take { "desc" } else { "asc" }
-                { ">=" } else { "<=" };
-            let cursor_where = Query::where_item(&key, order, &format!("`c.{}`", key));
+        if let Some(cursor) = cursor {
+            let cursor_clause = Query::r#where(model, graph, &cursor_where(cursor, order_by.unwrap(), negative_take), dialect, None);
             if stmt.r#where.is_some() {
-                stmt.r#where(And(vec![stmt.r#where.as_ref().unwrap().clone(), cursor_where]).to_string(dialect));
+                stmt.r#where(And(vec![stmt.r#where.as_ref().unwrap().clone(), cursor_clause]).to_string(dialect));
             } else {
-                stmt.r#where(cursor_where);
+                stmt.r#where(cursor_clause);
             }
         }
         if let Some(additional_left_join) = additional_left_join {
@@ -0,0 +1,26 @@
+/// Character set and collation of created tables. Only MySQL sets these per
+/// table, PostgreSQL and SQLite tables use the database encoding.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SQLCharset {
+    pub(crate) charset: String,
+    pub(crate) collation: Option<String>,
+}
+
+impl SQLCharset {
+
+    /// Without a charset, tables are `utf8mb4` with `utf8mb4_unicode_ci`
+    /// collation. A charset without a collation uses the charset's default
+    /// collation.
+    pub(crate) fn new(charset: Option<String>, collation: Option<String>) -> Self {
+        match charset {
+            Some(charset) => Self { charset, collation },
+            None => Self { charset: "utf8mb4".to_owned(), collation: collation.or(Some("utf8mb4_unicode_ci".to_owned())) },
+        }
+    }
+}
+
+impl Default for SQLCharset {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
@@ -18,7 +18,7 @@ use crate::prelude::Value;
 pub(crate) enum ColumnManipulation<'a> {
     AddColumn(&'a SQLColumn, Option<Pipeline>, Option<Value>),
     RemoveColumn(String, Option<Pipeline>),
-    RenameColumn{ old: String, new: String },
+    RenameColumn{ old: String, new: &'a SQLColumn },
     AlterColumn(&'a SQLColumn, &'a SQLColumn, Option<Pipeline>),
     CreateIndex(&'a ModelIndex),
     DropIndex(&'a ModelIndex),
@@ -30,7 +30,7 @@ impl<'a> ColumnManipulation<'a> {
         match self {
             ColumnManipulation::AddColumn(c, _, __) => model.field(c.name()),
             ColumnManipulation::RemoveColumn(c, _) => model.dropped_field(c.as_str()),
-            ColumnManipulation::RenameColumn {old: _, new} => model.field(new.as_str()),
+            ColumnManipulation::RenameColumn {old: _, new} => model.field_with_column_name(new.name()),
             ColumnManipulation::AlterColumn(__, c, _) => model.field(c.name()),
             ColumnManipulation::CreateIndex(_) => None,
             ColumnManipulation::DropIndex(_) => None,
@@ -39,8 +39,9 @@ impl<'a> ColumnManipulation<'a> {
 
     pub(crate) fn priority(&self, model: &Model) -> i64 {
         match self {
-            ColumnManipulation::CreateIndex(_) => -100,
-            ColumnManipulation::DropIndex(_) => -100,
+            // indices are dropped before and created after columns change
+            ColumnManipulation::CreateIndex(_) => i64::MAX,
+            ColumnManipulation::DropIndex(_) => i64::MIN,
             _ => self.get_field(model).map(|f| f.migration().map(|m| m.priority.unwrap_or(0))).unwrap_or(Some(0)).unwrap_or(0)
         }
     }
@@ -60,19 +61,19 @@ impl ColumnDecoder {
     pub(crate) fn manipulations<'a>(db_columns: &'a HashSet<SQLColumn>, model_columns: &'a HashSet<SQLColumn>, db_indices: &'a HashSet<ModelIndex>, model_indices: &'a HashSet<ModelIndex>, model: &Model) -> Vec<ColumnManipulation<'a>> {
         let mut to_create: Vec<&ModelIndex> = vec![];
         let mut to_drop: Vec<&ModelIndex> = vec![];
-        for index in db_indices {
+        for index in db_indices.iter().sorted_by(|a, b| a.name().cmp(&b.name())) {
             if !model_indices.contains(index) {
                 to_drop.push(index);
             }
         }
-        for index in model_indices {
+        for index in model_indices.iter().sorted_by(|a, b| a.name().cmp(&b.name())) {
             if !db_indices.contains(index) {
                 to_create.push(index);
             }
         }
         let mut to_add: Vec<&SQLColumn> = model_columns.iter().collect();
         let mut to_remove: Vec<&SQLColumn> = vec![];
-        let mut to_rename: Vec<(String, String)> = vec![];
+        let mut to_rename: Vec<(String, &SQLColumn)> = vec![];
         let mut to_alter: Vec<&SQLColumn> = vec![];
         // analyse add and remove
         for c in db_columns {
@@ -90,16 +91,16 @@ impl ColumnDecoder {
         }
         // analyse rename
         for c in to_add.clone() {
-            if let Some(field) = model.field(c.name()) {
+            if let Some(field) = model.field_with_column_name(c.name()) {
                 if let Some(migration) = field.migration() {
                     for name in &migration.renamed {
                         if let Some((remove_index, remove_column)) = to_remove.clone().iter().find_position(|c| c.name() == name.as_str()) {
                             to_remove.remove(remove_index);
-                            to_rename.push((remove_column.name().to_owned(), c.name().to_owned()));
+                            to_rename.push((remove_column.name().to_owned(), c));
                             let to_add_index = to_add.iter().position(|i| *i == c).unwrap();
                             to_add.remove(to_add_index);
+                            break;
                         }
-
                     }
                 }
             }
@@ -108,21 +109,22 @@ impl ColumnDecoder {
         // collect
         let mut result = vec![];
         for c in to_add {
-            let action = if let Some(field) = model.field(c.name()) {
+            let action = if let Some(field) = model.field_with_column_name(c.name()) {
                 field.migration().map(|m| m.action.clone()).flatten()
             } else { None };
-            let default = if let Some(field) = model.field(c.name()) {
+            let default = if let Some(field) = model.field_with_column_name(c.name()) {
                 field.migration().map(|m| m.default.clone()).flatten()
             } else { None };
 
             result.push(ColumnManipulation::AddColumn(c, action, default));
         }
-        for i in to_create {
-            result.push(ColumnManipulation::CreateIndex(i));
-        }
+        // a changed index is dropped and created again
         for i in to_drop {
             result.push(ColumnManipulation::DropIndex(i));
         }
+        for i in to_create {
+            result.push(ColumnManipulation::CreateIndex(i));
+        }
         for c in to_remove {
             let action = if let Some(field) = model.dropped_field(c.name()) {
                 field.migration().map(|m| m.action.clone()).flatten()
@@ -162,6 +164,9 @@ impl ColumnDecoder {
         for field in model.fields() {
             result.insert(field.into());
         }
+        for field in model.subtype_fields() {
+            result.insert(field.into());
+        }
         for property in model.properties() {
             if property.cached {
                 result.insert(property.into());
@@ -293,3 +298,4 @@ impl From<&Arc<Property>> for SQLColumn {
         SQLColumn::from(property.as_ref())
     }
 }
+
@@ -53,6 +53,10 @@ impl SQLColumn {
     pub(crate) fn set_default(&mut self, default: Option<String>) {
         self.default = default;
     }
+
+    pub(crate) fn set_not_null(&mut self, not_null: bool) {
+        self.not_null = not_null;
+    }
 }
 
 impl ToSQLString for SQLColumn {
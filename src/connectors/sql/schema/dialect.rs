@@ -13,4 +13,13 @@ impl SQLDialect {
             _ => "`",
         }
     }
+
+    /// Whether `COUNT(*) OVER()` can be used. MySQL gained window functions
+    /// in 8.0, older servers are still common.
+    pub(crate) fn supports_window_functions(&self) -> bool {
+        match self {
+            SQLDialect::MySQL => false,
+            _ => true,
+        }
+    }
 }
@@ -0,0 +1,63 @@
+use crate::connectors::sql::schema::dialect::SQLDialect;
+use crate::connectors::sql::schema::value::encode::{SQLEscape, ToSQLString};
+use crate::core::model::Model;
+use crate::core::relation::Relation;
+use crate::core::relation::delete_rule::DeleteRule;
+
+/// A foreign key constraint, possibly spanning multiple columns.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SQLForeignKey {
+    pub(crate) name: String,
+    pub(crate) columns: Vec<String>,
+    pub(crate) table: String,
+    pub(crate) references: Vec<String>,
+    pub(crate) on_delete: DeleteRule,
+}
+
+impl SQLForeignKey {
+
+    pub(crate) fn new(model: &Model, relation: &Relation, opposite_model: &Model) -> Self {
+        let columns: Vec<String> = relation.fields().iter().map(|f| model.field(f).unwrap().column_name().to_owned()).collect();
+        Self {
+            name: format!("{}_{}_fkey", model.table_name(), columns.join("_")),
+            columns,
+            table: opposite_model.table_name().to_owned(),
+            references: relation.references().iter().map(|r| opposite_model.field(r).unwrap().column_name().to_owned()).collect(),
+            on_delete: relation.delete_rule(),
+        }
+    }
+}
+
+impl ToSQLString for SQLForeignKey {
+    fn to_string(&self, dialect: SQLDialect) -> String {
+        let name = self.name.as_str().escape(dialect);
+        let columns = self.columns.iter().map(|c| c.as_str().escape(dialect)).collect::<Vec<String>>().join(", ");
+        let table = self.table.as_str().escape(dialect);
+        let references = self.references.iter().map(|r| r.as_str().escape(dialect)).collect::<Vec<String>>().join(", ");
+        let on_delete = match self.on_delete {
+            DeleteRule::Default => "",
+            DeleteRule::Nullify => " ON DELETE SET NULL",
+            DeleteRule::Cascade => " ON DELETE CASCADE",
+            DeleteRule::Deny => " ON DELETE RESTRICT",
+        };
+        format!("CONSTRAINT {name} FOREIGN KEY ({columns}) REFERENCES {table}({references}){on_delete}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_foreign_key_lists_every_column() {
+        let foreign_key = SQLForeignKey {
+            name: "Post_authorFirstName_authorLastName_fkey".to_owned(),
+            columns: vec!["authorFirstName".to_owned(), "authorLastName".to_owned()],
+            table: "User".to_owned(),
+            references: vec!["firstName".to_owned(), "lastName".to_owned()],
+            on_delete: DeleteRule::Default,
+        };
+        assert_eq!(foreign_key.to_string(SQLDialect::MySQL), "CONSTRAINT `Post_authorFirstName_authorLastName_fkey` FOREIGN KEY (`authorFirstName`, `authorLastName`) REFERENCES `User`(`firstName`, `lastName`)");
+        assert_eq!(foreign_key.to_string(SQLDialect::PostgreSQL), "CONSTRAINT \"Post_authorFirstName_authorLastName_fkey\" FOREIGN KEY (\"authorFirstName\", \"authorLastName\") REFERENCES \"User\"(\"firstName\", \"lastName\")");
+    }
+}
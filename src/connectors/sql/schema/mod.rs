@@ -1,5 +1,7 @@
 pub(crate) mod table;
 pub(crate) mod column;
+pub(crate) mod foreign_key;
 pub(crate) mod r#type;
 pub(crate) mod dialect;
+pub(crate) mod charset;
 pub(crate) mod value;
@@ -9,6 +9,9 @@ impl From<&Model> for SQLCreateTableStatement {
         for field in model.fields() {
             stmt.column(field.into());
         }
+        for field in model.subtype_fields() {
+            stmt.column(field.into());
+        }
         for property in model.properties() {
             if property.cached {
                 stmt.column(property.into());
@@ -38,6 +38,7 @@ fn mysql_type_to_database_type(r#type: &str) -> DatabaseType {
                 "char" => DatabaseType::Char { m: arg.map(|a| u8::from_str(a).unwrap()), n: None, c: None },
                 "varchar" => DatabaseType::VarChar { m: arg.map(|a| u16::from_str(a).unwrap()).unwrap(), n: None, c: None },
                 "date" => DatabaseType::Date,
+                "json" => DatabaseType::Json,
                 "datetime" => DatabaseType::DateTime(u8::from_str(arg.unwrap()).unwrap()),
                 "decimal" => {
                     if let Some(args) = arg {
@@ -66,6 +67,7 @@ fn postgresql_type_to_database_type(r#type: &str) -> DatabaseType {
         "real" | "float4" => DatabaseType::Real,
         "date" => DatabaseType::Date,
         "numeric" => DatabaseType::Decimal { m: Some(65), d: Some(30) },
+        "jsonb" => DatabaseType::Json,
         _ => if lower_str.starts_with("array|") {
             let inner = &lower_str[6..];
             DatabaseType::Vec(Box::new(postgresql_type_to_database_type(inner)))
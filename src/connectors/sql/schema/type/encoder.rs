@@ -151,6 +151,11 @@ impl ToSQLString for DatabaseType {
             DatabaseType::LongBlob => "LONGBLOB".to_string(),
             DatabaseType::Blob(l) => format!("BLOB({l})"),
             DatabaseType::ByteA => "bytea".to_string(),
+            DatabaseType::Json => if dialect == SQLDialect::PostgreSQL {
+                "JSONB".to_string()
+            } else {
+                "JSON".to_string()
+            },
             DatabaseType::Int32 => panic!("SQL databases don't support Int32."),
             DatabaseType::Int64 => panic!("SQL databases don't support Int64."),
             DatabaseType::String => panic!("SQL databases don't support String."),
@@ -35,6 +35,7 @@ fn default_database_type_mysql(field_type: &FieldType) -> DatabaseType {
         FieldType::DateTime => DatabaseType::DateTime(3),
         FieldType::Enum(_) => DatabaseType::String,
         FieldType::Decimal => DatabaseType::Decimal { m: Some(65), d: Some(30) },
+        FieldType::Json => DatabaseType::Json,
         FieldType::Vec(_) => panic!(),
         FieldType::HashMap(_) => panic!(),
         FieldType::BTreeMap(_) => panic!(),
@@ -55,6 +56,7 @@ fn default_database_type_postgresql(field_type: &FieldType) -> DatabaseType {
         FieldType::DateTime => DatabaseType::Timestamp { p: 3, z: false },
         FieldType::Decimal => DatabaseType::Decimal { m: Some(65), d: Some(30) },
         FieldType::Enum(_) => DatabaseType::String,
+        FieldType::Json => DatabaseType::Json,
         FieldType::Vec(inner) => DatabaseType::Vec(Box::new(default_database_type_postgresql(inner.field_type()))),
         FieldType::HashMap(_) => panic!(),
         FieldType::BTreeMap(_) => panic!(),
@@ -75,6 +77,8 @@ fn default_database_type_sqlite(field_type: &FieldType) -> DatabaseType {
         FieldType::DateTime => DatabaseType::Text { m: None, n: None, c: None },
         FieldType::Decimal => DatabaseType::Decimal { m: None, d: None },
         FieldType::Enum(_) => DatabaseType::String,
+        // SQLite has no JSON type, JSON is stored as text
+        FieldType::Json => DatabaseType::Text { m: None, n: None, c: None },
         FieldType::Vec(_) => panic!(),
         FieldType::HashMap(_) => panic!(),
         FieldType::BTreeMap(_) => panic!(),
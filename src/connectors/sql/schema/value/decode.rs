@@ -209,6 +209,15 @@ impl RowDecoder {
                 return Value::Null;
             }
         }
+        if r#type.is_json() {
+            if let Some(json) = value.as_json() {
+                return Value::from(json);
+            } else if let Some(text) = value.as_str() {
+                return serde_json::from_str::<serde_json::Value>(text).map_or(Value::Null, |json| Value::from(&json));
+            } else {
+                return Value::Null;
+            }
+        }
         panic!("Unhandled database when decoding type.")
     }
 
@@ -1,6 +1,7 @@
 use bigdecimal::BigDecimal;
 use chrono::{NaiveDate, Utc, DateTime, SecondsFormat};
 use itertools::Itertools;
+use serde_json::Value as JsonValue;
 use crate::connectors::sql::schema::dialect::SQLDialect;
 use crate::core::field::r#type::{FieldType, FieldTypeOwner};
 use crate::prelude::{Graph, Value};
@@ -56,6 +57,10 @@ impl ValueToSQLString for Value {
             FieldType::Date => self.as_date().unwrap().to_string().to_sql_input(),
             FieldType::DateTime => self.as_datetime().unwrap().to_string().to_sql_input(),
             FieldType::Decimal => self.as_decimal().unwrap().to_string().to_sql_input(),
+            FieldType::Json => {
+                let json: JsonValue = self.into();
+                json.to_string().to_sql_input()
+            }
             _ => { panic!() }
         }
     }
@@ -1,11 +1,13 @@
 use crate::connectors::sql::stmts::alter_table::add::SQLAlterTableAddStatement;
 use crate::connectors::sql::stmts::alter_table::drop_column::SQLAlterTableDropColumnStatement;
 use crate::connectors::sql::stmts::alter_table::modify::SQLAlterTableModifyStatement;
+use crate::connectors::sql::stmts::alter_table::rename_column::SQLAlterTableRenameColumnStatement;
 use crate::connectors::sql::schema::column::SQLColumn;
 
 pub mod add;
 pub mod drop_column;
 pub mod modify;
+pub mod rename_column;
 
 pub struct SQLAlterTableStatement {
     pub(crate) table: String
@@ -20,6 +22,10 @@ impl SQLAlterTableStatement {
         SQLAlterTableModifyStatement { table: self.table.clone(), column }
     }
 
+    pub(crate) fn rename_column(&self, old: impl Into<String>, column: SQLColumn) -> SQLAlterTableRenameColumnStatement {
+        SQLAlterTableRenameColumnStatement { table: self.table.clone(), old: old.into(), column }
+    }
+
     pub(crate) fn add(&self, column_def: SQLColumn) -> SQLAlterTableAddStatement {
         SQLAlterTableAddStatement { table: self.table.clone(), column_def }
     }
@@ -0,0 +1,27 @@
+use crate::connectors::sql::schema::column::SQLColumn;
+use crate::connectors::sql::schema::dialect::SQLDialect;
+use crate::connectors::sql::schema::value::encode::ToSQLString;
+
+pub struct SQLAlterTableRenameColumnStatement {
+    pub(crate) table: String,
+    pub(crate) old: String,
+    pub(crate) column: SQLColumn,
+}
+
+impl ToSQLString for SQLAlterTableRenameColumnStatement {
+    fn to_string(&self, dialect: SQLDialect) -> String {
+        let table = &self.table;
+        let old = &self.old;
+        let escape = dialect.escape();
+        if dialect == SQLDialect::MySQL {
+            // MySQL before 8.0 renames with the full definition, the primary
+            // key is an index and stays on the column
+            let c = &self.column;
+            let def = SQLColumn::new(c.name().to_owned(), c.r#type().clone(), c.not_null(), c.auto_increment(), c.default().map(|d| d.to_owned()), false).to_string(dialect);
+            format!("ALTER TABLE {escape}{table}{escape} CHANGE COLUMN {escape}{old}{escape} {def}")
+        } else {
+            let new = self.column.name();
+            format!("ALTER TABLE {escape}{table}{escape} RENAME COLUMN {escape}{old}{escape} TO {escape}{new}{escape}")
+        }
+    }
+}
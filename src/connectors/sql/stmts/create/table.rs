@@ -1,12 +1,16 @@
+use crate::connectors::sql::schema::charset::SQLCharset;
 use crate::connectors::sql::schema::column::SQLColumn;
 use crate::connectors::sql::schema::dialect::SQLDialect;
+use crate::connectors::sql::schema::foreign_key::SQLForeignKey;
 use crate::connectors::sql::schema::value::encode::ToSQLString;
 
 
 pub(crate) struct SQLCreateTableStatement {
     pub(crate) table: String,
     pub(crate) if_not_exists: bool,
-    pub(crate) columns: Vec<SQLColumn>
+    pub(crate) columns: Vec<SQLColumn>,
+    pub(crate) foreign_keys: Vec<SQLForeignKey>,
+    pub(crate) charset: Option<SQLCharset>,
 }
 
 impl SQLCreateTableStatement {
@@ -24,6 +28,16 @@ impl SQLCreateTableStatement {
         self.columns.extend(defs);
         self
     }
+
+    pub(crate) fn foreign_key(&mut self, foreign_key: SQLForeignKey) -> &mut Self {
+        self.foreign_keys.push(foreign_key);
+        self
+    }
+
+    pub(crate) fn charset(&mut self, charset: SQLCharset) -> &mut Self {
+        self.charset = Some(charset);
+        self
+    }
 }
 
 impl ToSQLString for SQLCreateTableStatement {
@@ -32,11 +46,33 @@ impl ToSQLString for SQLCreateTableStatement {
         let table_name = &self.table;
         let columns = self.columns.iter().map(|c| {
             c.to_string(dialect)
-        }).collect::<Vec<String>>().join(", ");
+        }).chain(self.foreign_keys.iter().map(|f| {
+            f.to_string(dialect)
+        })).collect::<Vec<String>>().join(", ");
         if dialect == SQLDialect::PostgreSQL {
             format!("CREATE TABLE{if_not_exists} \"{table_name}\"( {columns} );")
+        } else if dialect == SQLDialect::MySQL && self.charset.is_some() {
+            let charset = self.charset.as_ref().unwrap();
+            let collation = charset.collation.as_ref().map_or("".to_owned(), |c| format!(" COLLATE={c}"));
+            format!("CREATE TABLE{if_not_exists} `{table_name}`( {columns} ) DEFAULT CHARSET={}{collation};", charset.charset)
         } else {
             format!("CREATE TABLE{if_not_exists} `{table_name}`( {columns} );")
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::sql::stmts::SQL;
+    use super::*;
+
+    #[test]
+    fn mysql_tables_are_created_with_charset_and_collation() {
+        let mut stmt = SQL::create().table("posts");
+        stmt.charset(SQLCharset::default());
+        assert_eq!(stmt.to_string(SQLDialect::MySQL), "CREATE TABLE `posts`(  ) DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;");
+        stmt.charset(SQLCharset::new(Some("latin1".to_owned()), None));
+        assert_eq!(stmt.to_string(SQLDialect::MySQL), "CREATE TABLE `posts`(  ) DEFAULT CHARSET=latin1;");
+        assert_eq!(stmt.to_string(SQLDialect::SQLite), "CREATE TABLE `posts`(  );");
+    }
+}
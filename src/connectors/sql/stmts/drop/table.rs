@@ -1,5 +1,5 @@
 use crate::connectors::sql::schema::dialect::SQLDialect;
-use crate::connectors::sql::schema::value::encode::ToSQLString;
+use crate::connectors::sql::schema::value::encode::{SQLEscape, ToSQLString};
 
 pub(crate) struct SQLDropTableStatement {
     pub(crate) table: String,
@@ -14,9 +14,33 @@ impl SQLDropTableStatement {
 }
 
 impl ToSQLString for SQLDropTableStatement {
-    fn to_string(&self, _dialect: SQLDialect) -> String {
-        let table = &self.table;
+    fn to_string(&self, dialect: SQLDialect) -> String {
+        let table = self.table.as_str().escape(dialect);
         let if_exists = if self.if_exists { " IF EXISTS" } else { "" };
-        format!("DROP TABLE{if_exists} '{table}';")
+        format!("DROP TABLE{if_exists} {table};")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::sql::stmts::SQL;
+    use super::*;
+
+    #[test]
+    fn mysql_drop_table_quotes_with_backticks() {
+        let stmt = SQL::drop().table("User").if_exists().to_string(SQLDialect::MySQL);
+        assert_eq!(stmt, "DROP TABLE IF EXISTS `User`;");
+    }
+
+    #[test]
+    fn postgres_drop_table_quotes_with_double_quotes() {
+        let stmt = SQL::drop().table("User").if_exists().to_string(SQLDialect::PostgreSQL);
+        assert_eq!(stmt, "DROP TABLE IF EXISTS \"User\";");
+    }
+
+    #[test]
+    fn sqlite_drop_table_quotes_with_backticks() {
+        let stmt = SQL::drop().table("User").to_string(SQLDialect::SQLite);
+        assert_eq!(stmt, "DROP TABLE `User`;");
     }
 }
@@ -32,14 +32,28 @@ impl<'a> ToSQLString for SQLInsertIntoStatement<'a> {
             keys.push(k);
             values.push(v);
         }
-        if dialect == SQLDialect::PostgreSQL {
-            format!("INSERT INTO {}({}) VALUES({}){};", self.table, keys.iter().map(|k| format!("\"{}\"", k)).collect::<Vec<String>>().join(","), values.join(","), if self.returning.is_empty() {
-                "".to_owned()
-            } else {
-                "  RETURNING ".to_owned() + &self.returning.join(",")
-            })
+        let escape = dialect.escape();
+        let keys = keys.iter().map(|k| format!("{escape}{k}{escape}")).collect::<Vec<String>>().join(",");
+        let returning = if dialect == SQLDialect::PostgreSQL && !self.returning.is_empty() {
+            " RETURNING ".to_owned() + &self.returning.join(",")
         } else {
-            format!("INSERT INTO `{}`({}) VALUES({});", self.table, keys.iter().map(|k| format!("`{k}`")).collect::<Vec<String>>().join(","), values.join(","))
-        }
+            "".to_owned()
+        };
+        format!("INSERT INTO {escape}{}{escape}({}) VALUES({}){};", self.table, keys, values.join(","), returning)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::sql::stmts::SQL;
+    use super::*;
+
+    #[test]
+    fn postgres_insert_returns_generated_columns() {
+        let returning = vec!["\"id\"".to_owned(), "\"createdAt\"".to_owned()];
+        let stmt = SQL::insert_into("User").values(vec![("email", "'a@x.com'")]).returning(&returning).to_string(SQLDialect::PostgreSQL);
+        assert_eq!(stmt, "INSERT INTO \"User\"(\"email\") VALUES('a@x.com') RETURNING \"id\",\"createdAt\";");
+        let stmt = SQL::insert_into("User").values(vec![("email", "'a@x.com'")]).returning(&returning).to_string(SQLDialect::MySQL);
+        assert_eq!(stmt, "INSERT INTO `User`(`email`) VALUES('a@x.com');");
     }
 }
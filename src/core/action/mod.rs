@@ -1,6 +1,6 @@
 pub(crate) mod source;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::slice::Iter;
 use maplit::hashset;
 use once_cell::sync::Lazy;
@@ -34,8 +34,9 @@ pub(crate) const INTERNAL_POSITION: u32 = 1 << 17;
 pub(crate) const SINGLE: u32 = 1 << 18;
 pub(crate) const MANY: u32 = 1 << 19;
 pub(crate) const INTERNAL_AMOUNT: u32 = 1 << 20;
+pub(crate) const FACETS: u32 = 1 << 21;
 
-const ALL_NAMES: u32 = CREATE | UPDATE | UPSERT | DELETE | FIND | FIND_FIRST | CONNECT | CONNECT_OR_CREATE | DISCONNECT | SET | JOIN_CREATE | JOIN_DELETE | IDENTITY | SIGN_IN | COUNT | AGGREGATE | GROUP_BY;
+const ALL_NAMES: u32 = CREATE | UPDATE | UPSERT | DELETE | FIND | FIND_FIRST | CONNECT | CONNECT_OR_CREATE | DISCONNECT | SET | JOIN_CREATE | JOIN_DELETE | IDENTITY | SIGN_IN | COUNT | AGGREGATE | GROUP_BY | FACETS;
 const ALL_POSITIONS: u32 = ENTRY | NESTED | INTERNAL_POSITION;
 const ALL_AMOUNTS: u32 = SINGLE | MANY | INTERNAL_AMOUNT;
 
@@ -56,6 +57,7 @@ pub(crate) const DELETE_MANY_HANDLER: u32 = DELETE | ENTRY | MANY;
 pub(crate) const COUNT_HANDLER: u32 = COUNT | ENTRY;
 pub(crate) const AGGREGATE_HANDLER: u32 = AGGREGATE | ENTRY;
 pub(crate) const GROUP_BY_HANDLER: u32 = GROUP_BY | ENTRY;
+pub(crate) const FACETS_HANDLER: u32 = FACETS | ENTRY;
 pub(crate) const SIGN_IN_HANDLER: u32 = SIGN_IN | ENTRY;
 pub(crate) const IDENTITY_HANDLER: u32 = IDENTITY | ENTRY;
 
@@ -101,6 +103,7 @@ impl Action {
                 "count" => COUNT,
                 "aggregate" => AGGREGATE,
                 "groupBy" => GROUP_BY,
+                "facets" => FACETS,
                 "entry" => ENTRY,
                 "nested" => NESTED,
                 "internalLocation" => INTERNAL_POSITION,
@@ -197,14 +200,21 @@ impl Action {
         Self { value: self.value ^ other.value }
     }
 
+    /// Whether this action changes or removes existing records.
+    pub(crate) fn updates_or_deletes(&self) -> bool {
+        (self.value & (UPDATE | DELETE)) != 0
+    }
+
     pub(crate) fn passes(&self, matchers: &Vec<Action>) -> bool {
         for matcher in matchers {
             let copy = self.finalized();
             let finalized_matcher = matcher.finalized().value;
             let result = finalized_matcher & copy.value;
-            return ((result & ALL_NAMES) != 0) &&
+            if ((result & ALL_NAMES) != 0) &&
                 ((result & ALL_POSITIONS) != 0) &&
-                ((result & ALL_AMOUNTS) != 0)
+                ((result & ALL_AMOUNTS) != 0) {
+                return true
+            }
         }
         false
     }
@@ -225,6 +235,7 @@ impl Action {
             COUNT_HANDLER => &COUNT_INPUT_JSON_KEYS,
             AGGREGATE_HANDLER => &AGGREGATE_INPUT_JSON_KEYS,
             GROUP_BY_HANDLER => &GROUP_BY_INPUT_JSON_KEYS,
+            FACETS_HANDLER => &FACETS_INPUT_JSON_KEYS,
             SIGN_IN_HANDLER => &SIGN_IN_INPUT_JSON_KEYS,
             IDENTITY_HANDLER => &IDENTITY_INPUT_JSON_KEYS,
             _ => unreachable!()
@@ -239,6 +250,10 @@ impl Action {
         self.value == GROUP_BY_HANDLER
     }
 
+    pub(crate) fn handler_requires_field(&self) -> bool {
+        self.value == FACETS_HANDLER
+    }
+
     pub(crate) fn handler_requires_credentials(&self) -> bool {
         self.value == SIGN_IN_HANDLER
     }
@@ -288,6 +303,7 @@ impl Action {
             COUNT_HANDLER => ResMeta::NoMeta,
             AGGREGATE_HANDLER => ResMeta::NoMeta,
             GROUP_BY_HANDLER => ResMeta::NoMeta,
+            FACETS_HANDLER => ResMeta::NoMeta,
             SIGN_IN_HANDLER => ResMeta::TokenInfo,
             IDENTITY_HANDLER => ResMeta::NoMeta,
             _ => unreachable!()
@@ -309,6 +325,7 @@ impl Action {
             COUNT_HANDLER => ResData::Number,
             AGGREGATE_HANDLER => ResData::Other,
             GROUP_BY_HANDLER => ResData::Other,
+            FACETS_HANDLER => ResData::Other,
             SIGN_IN_HANDLER => ResData::Single,
             IDENTITY_HANDLER => ResData::Single,
             _ => unreachable!()
@@ -330,6 +347,7 @@ impl Action {
             COUNT_HANDLER => "count",
             AGGREGATE_HANDLER => "aggregate",
             GROUP_BY_HANDLER => "groupBy",
+            FACETS_HANDLER => "facets",
             SIGN_IN_HANDLER => "signIn",
             IDENTITY_HANDLER => "identity",
             _ => unreachable!()
@@ -352,6 +370,7 @@ impl Action {
                 "count" => COUNT_HANDLER,
                 "aggregate" => AGGREGATE_HANDLER,
                 "groupBy" => GROUP_BY_HANDLER,
+                "facets" => FACETS_HANDLER,
                 "signIn" => SIGN_IN_HANDLER,
                 "identity" => IDENTITY_HANDLER,
                 _ => None?
@@ -359,8 +378,13 @@ impl Action {
         })
     }
 
+    /// Resolves an action URL segment, an alias dispatches to the built-in handler it names.
+    pub(crate) fn handler_from_name_with_aliases(name: &str, aliases: &HashMap<String, Action>) -> Option<Self> {
+        Self::handler_from_name(name).or_else(|| aliases.get(name).copied())
+    }
+
     pub(crate) fn handlers_iter() -> Iter<'static, Action> {
-        static HANDLER_TYPES: [Action; 15] = [
+        static HANDLER_TYPES: [Action; 16] = [
             Action::from_u32(FIND_UNIQUE_HANDLER),
             Action::from_u32(FIND_FIRST_HANDLER),
             Action::from_u32(FIND_MANY_HANDLER),
@@ -374,6 +398,7 @@ impl Action {
             Action::from_u32(COUNT_HANDLER),
             Action::from_u32(AGGREGATE_HANDLER),
             Action::from_u32(GROUP_BY_HANDLER),
+            Action::from_u32(FACETS_HANDLER),
             Action::from_u32(SIGN_IN_HANDLER),
             Action::from_u32(IDENTITY_HANDLER),
         ];
@@ -395,6 +420,7 @@ impl Action {
             Action::from_u32(COUNT_HANDLER),
             Action::from_u32(AGGREGATE_HANDLER),
             Action::from_u32(GROUP_BY_HANDLER),
+            Action::from_u32(FACETS_HANDLER),
             Action::from_u32(SIGN_IN_HANDLER),
             Action::from_u32(IDENTITY_HANDLER),
         ].iter().map(|x| *x))
@@ -437,13 +463,13 @@ pub enum ResData {
 }
 
 static FIND_UNIQUE_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
-    hashset! {"include", "select", "where"}
+    hashset! {"include", "select", "where", "withDeleted"}
 });
 static FIND_FIRST_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
-    hashset! {"include", "select", "where", "orderBy", "skip", "cursor", "distinct"}
+    hashset! {"include", "select", "where", "orderBy", "skip", "cursor", "distinct", "withDeleted"}
 });
 static FIND_MANY_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
-    hashset! {"include", "select", "where", "orderBy", "skip", "take", "pageSize", "pageNumber", "cursor", "distinct"}
+    hashset! {"include", "select", "where", "orderBy", "skip", "take", "pageSize", "pageNumber", "cursor", "distinct", "withDeleted", "_withMeta"}
 });
 static CREATE_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset! {"include", "select", "create"}
@@ -467,13 +493,16 @@ static DELETE_MANY_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset! {"select", "where"}
 });
 static COUNT_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
-    hashset! {"where", "orderBy", "skip", "take", "pageSize", "pageNumber", "cursor", "distinct"}
+    hashset! {"where", "orderBy", "skip", "take", "pageSize", "pageNumber", "cursor", "distinct", "withDeleted"}
 });
 static AGGREGATE_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
-    hashset! {"_avg", "_count", "_sum", "_min", "_max", "where", "orderBy", "skip", "take", "pageSize", "pageNumber", "cursor"}
+    hashset! {"_avg", "_count", "_sum", "_min", "_max", "where", "orderBy", "skip", "take", "pageSize", "pageNumber", "cursor", "withDeleted"}
 });
 static GROUP_BY_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
-    hashset! {"_avg", "_count", "_sum", "_min", "_max", "by", "having", "where", "orderBy", "skip", "take", "pageSize", "pageNumber", "cursor"}
+    hashset! {"_avg", "_count", "_sum", "_min", "_max", "by", "having", "where", "orderBy", "skip", "take", "pageSize", "pageNumber", "cursor", "withDeleted"}
+});
+static FACETS_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
+    hashset! {"field", "where", "withDeleted"}
 });
 static SIGN_IN_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset! {"include", "select", "credentials"}
@@ -481,3 +510,21 @@ static SIGN_IN_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
 static IDENTITY_INPUT_JSON_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset! {"include", "select"}
 });
+
+#[cfg(test)]
+mod tests {
+    use maplit::hashmap;
+    use super::*;
+
+    #[test]
+    fn aliased_action_dispatches_to_its_handler() {
+        let aliases = hashmap!{
+            "list".to_owned() => Action::from_u32(FIND_MANY_HANDLER),
+            "get".to_owned() => Action::from_u32(FIND_UNIQUE_HANDLER),
+        };
+        assert_eq!(Action::handler_from_name_with_aliases("list", &aliases), Some(Action::from_u32(FIND_MANY_HANDLER)));
+        assert_eq!(Action::handler_from_name_with_aliases("get", &aliases), Some(Action::from_u32(FIND_UNIQUE_HANDLER)));
+        assert_eq!(Action::handler_from_name_with_aliases("findMany", &aliases), Some(Action::from_u32(FIND_MANY_HANDLER)));
+        assert_eq!(Action::handler_from_name_with_aliases("browse", &aliases), None);
+    }
+}
@@ -1,16 +1,20 @@
 use std::collections::HashMap;
 use std::env;
 use std::ffi::{OsString};
+use std::path::PathBuf;
 use std::fmt::{Debug};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use to_mut_proc_macro::ToMut;
 use to_mut::ToMut;
 use clap::{Arg, ArgAction, Command as ClapCommand};
 use dotenvy::dotenv;
 use crate::connectors::mongodb::connector::MongoDBConnector;
+use crate::connectors::replicated::ReplicatedConnector;
 use crate::connectors::sql::connector::SQLConnector;
+use crate::connectors::sql::schema::charset::SQLCharset;
 use crate::connectors::sql::schema::dialect::SQLDialect;
-use crate::core::app::command::{CLI, CLICommand, GenerateClientCommand, GenerateCommand, GenerateEntityCommand, MigrateCommand, ServeCommand};
+use crate::core::app::command::{CLI, CLICommand, GenerateClientCommand, GenerateCommand, GenerateEntityCommand, GenerateJSONSchemaCommand, MigrateCommand, ServeCommand};
 use crate::core::app::conf::{ClientGeneratorConf, EntityGeneratorConf, ServerConf};
 use crate::core::app::entrance::Entrance;
 use crate::core::app::environment::EnvironmentVersion;
@@ -19,15 +23,21 @@ use crate::core::field::Field;
 use crate::core::database::name::DatabaseName;
 use crate::core::field::r#type::FieldType;
 use crate::core::graph::builder::GraphBuilder;
+use crate::core::app::serve::cors::server_cors;
+use crate::parser::ast::config::{Cors, RateLimit};
+use crate::parser::ast::connector::Connector as ConnectorDeclaration;
 use crate::parser::ast::field::FieldClass;
 use crate::prelude::{App, Value};
 use crate::core::pipeline::item::Item;
 use crate::core::pipeline::items::function::compare::{CompareArgument, CompareItem};
+use crate::core::pipeline::items::function::custom::{CustomFunction, CustomFunctionArgument};
 use crate::core::pipeline::items::function::perform::{PerformArgument, PerformItem, PerformResult};
 use crate::core::pipeline::items::function::transform::{TransformResult, TransformArgument, TransformItem};
 use crate::core::pipeline::items::function::validate::{ValidateArgument, ValidateItem, ValidateResult};
 use crate::core::property::Property;
 use crate::core::relation::Relation;
+use crate::core::relation::cardinality::Cardinality;
+use crate::core::teon::timezone::parse_timezone_offset;
 use crate::parser::ast::r#type::Arity;
 use crate::parser::parser::Parser;
 
@@ -37,11 +47,12 @@ pub(crate) struct CallbackLookupTable {
     pub(crate) validators: HashMap<String, Arc<dyn Item>>,
     pub(crate) callbacks: HashMap<String, Arc<dyn Item>>,
     pub(crate) compares: HashMap<String, Arc<dyn Item>>,
+    pub(crate) functions: HashMap<String, CustomFunction>,
 }
 
 impl CallbackLookupTable {
-    fn new() -> Self {
-        Self { transforms: HashMap::new(), validators: HashMap::new(), callbacks: HashMap::new(), compares: HashMap::new() }
+    pub(crate) fn new() -> Self {
+        Self { transforms: HashMap::new(), validators: HashMap::new(), callbacks: HashMap::new(), compares: HashMap::new(), functions: HashMap::new() }
     }
 }
 
@@ -56,6 +67,9 @@ pub struct AppBuilder {
     pub(crate) environment_version: EnvironmentVersion,
     pub(crate) entrance: Entrance,
     pub(crate) args: Arc<CLI>,
+    pub(crate) schema_files: Vec<PathBuf>,
+    pub(crate) cors: Option<Arc<dyn Fn(&mut Cors) + Send + Sync>>,
+    pub(crate) rate_limit: Option<RateLimit>,
 }
 
 impl AppBuilder {
@@ -84,6 +98,27 @@ impl AppBuilder {
             environment_version: environment_version.clone(),
             entrance,
             args: Arc::new(Self::parse_cli_args(environment_version.clone(), entrance.clone())),
+            schema_files: vec![],
+            cors: None,
+            rate_limit: None,
+        }
+    }
+
+    /// Create a builder which loads the schema again with the callbacks and arguments of a running app.
+    pub(crate) fn new_for_reload(app: &App) -> Self {
+        Self {
+            connector: None,
+            graph_builder: GraphBuilder::new(),
+            server_conf: None,
+            entity_generator_confs: vec![],
+            client_generator_confs: vec![],
+            callback_lookup_table: app.callback_lookup_table.clone(),
+            environment_version: app.environment_version.clone(),
+            entrance: app.entrance.clone(),
+            args: app.args.clone(),
+            schema_files: vec![],
+            cors: app.cors.clone(),
+            rate_limit: app.rate_limit.clone(),
         }
     }
 
@@ -134,7 +169,12 @@ impl AppBuilder {
                         .action(ArgAction::Append)
                         .conflicts_with("all")
                         .help("Client names to generate")
-                        .num_args(1..)))
+                        .num_args(1..))
+                    .arg(Arg::new("watch")
+                        .short('w')
+                        .long("watch")
+                        .help("Regenerate clients with watch enabled when the schema changes")
+                        .action(ArgAction::SetTrue)))
                 .subcommand(ClapCommand::new("entity")
                     .about("Generate model entities")
                     .arg_required_else_help(false)
@@ -148,7 +188,16 @@ impl AppBuilder {
                         .action(ArgAction::Append)
                         .conflicts_with("all")
                         .help("Entity names to generate")
-                        .num_args(1..))))
+                        .num_args(1..)))
+                .subcommand(ClapCommand::new("schema")
+                    .about("Generate JSON Schema for action inputs")
+                    .arg_required_else_help(false)
+                    .arg(Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("The file to write to, print to stdout if omitted")
+                        .action(ArgAction::Set)
+                        .num_args(1))))
             .subcommand(ClapCommand::new("migrate")
                 .about("Run migration")
                 .arg(Arg::new("dry")
@@ -172,12 +221,16 @@ impl AppBuilder {
                 match submatches.subcommand() {
                     Some(("client", submatches)) => {
                         let names: Option<Vec<String>> = submatches.get_many::<String>("NAME").map(|s| s.map(|v| v.to_string()).collect::<Vec<String>>());
-                        CLICommand::Generate(GenerateCommand::GenerateClientCommand(GenerateClientCommand { all: false, names }))
+                        CLICommand::Generate(GenerateCommand::GenerateClientCommand(GenerateClientCommand { all: false, names, watch: submatches.get_flag("watch") }))
                     }
                     Some(("entity", submatches)) => {
                         let names: Option<Vec<String>> = submatches.get_many::<String>("NAME").map(|s| s.map(|v| v.to_string()).collect::<Vec<String>>());
                         CLICommand::Generate(GenerateCommand::GenerateEntityCommand(GenerateEntityCommand { all: false, names }))
                     }
+                    Some(("schema", submatches)) => {
+                        let output: Option<&String> = submatches.get_one("output");
+                        CLICommand::Generate(GenerateCommand::GenerateJSONSchemaCommand(GenerateJSONSchemaCommand { output: output.map(|s| s.to_string()) }))
+                    }
                     _ => unreachable!()
                 }
             }
@@ -225,6 +278,31 @@ impl AppBuilder {
         self
     }
 
+    pub fn function<F>(&mut self, name: impl Into<String>, f: F) -> &mut Self where
+        F: CustomFunctionArgument + 'static {
+        self.callback_lookup_table.lock().unwrap().functions.insert(name.into(), CustomFunction::new(f));
+        self
+    }
+
+    /// Configures the server's CORS settings over the ones of the schema.
+    pub fn cors<F>(&mut self, build: F) -> &mut Self where F: Fn(&mut Cors) + Send + Sync + 'static {
+        self.cors = Some(Arc::new(build));
+        self
+    }
+
+    /// Limits each identity, or each address when no one is signed in, to
+    /// `requests` within `window`, over the rate limit of the schema.
+    pub fn rate_limit(&mut self, requests: u32, window: Duration) -> &mut Self {
+        if requests == 0 {
+            panic!("Rate limit requests should be a positive integer.")
+        }
+        if window.is_zero() {
+            panic!("Rate limit window should be a positive duration.")
+        }
+        self.rate_limit = Some(RateLimit { requests, window });
+        self
+    }
+
     async fn load(&mut self) {
         let mut parser = Parser::new(self.callback_lookup_table.clone());
         let main = match self.args.schema.as_ref() {
@@ -232,6 +310,7 @@ impl AppBuilder {
             None => None
         };
         parser.parse(main);
+        self.schema_files = parser.source_paths();
         self.load_config_from_parser(&parser).await;
     }
 
@@ -245,43 +324,75 @@ impl AppBuilder {
             environment_version: self.environment_version.clone(),
             entrance: self.entrance.clone(),
             args: self.args.clone(),
+            callback_lookup_table: self.callback_lookup_table.clone(),
+            schema_files: self.schema_files.clone(),
+            cors: self.cors.clone(),
+            rate_limit: self.rate_limit.clone(),
         }
     }
 
-    async fn load_config_from_parser(&mut self, parser: &Parser) {
-        // connector
-        let connector_ref = parser.connector.unwrap();
-        let source = parser.get_source(connector_ref.0);
-        let connector_declaration = source.get_connector(connector_ref.1);
-        let url = connector_declaration.url.as_ref().unwrap();
-        if connector_declaration.debug {
-            env::set_var("_TEO_LOG_DB_OPERATION", "true");
-        }
-        let connector: Arc<dyn Connector> = match connector_declaration.provider.unwrap() {
+    async fn connect(provider: DatabaseName, url: &String, foreign_keys: bool, charset: SQLCharset) -> Arc<dyn Connector> {
+        match provider {
             DatabaseName::MySQL => {
                 #[cfg(feature = "data-source-mysql")]
-                Arc::new(SQLConnector::new(SQLDialect::MySQL, url, false).await)
+                Arc::new(SQLConnector::new(SQLDialect::MySQL, url, false, foreign_keys, charset).await)
             },
             DatabaseName::PostgreSQL => {
                 #[cfg(feature = "data-source-postgres")]
-                Arc::new(SQLConnector::new(SQLDialect::PostgreSQL, url, false).await)
+                Arc::new(SQLConnector::new(SQLDialect::PostgreSQL, url, false, foreign_keys, charset).await)
             },
             #[cfg(feature = "data-source-sqlite")]
             DatabaseName::SQLite => {
                 #[cfg(feature = "data-source-sqlite")]
-                Arc::new(SQLConnector::new(SQLDialect::SQLite, url, false).await)
+                Arc::new(SQLConnector::new(SQLDialect::SQLite, url, false, foreign_keys, charset).await)
             },
             DatabaseName::MongoDB => {
                 #[cfg(feature = "data-source-mongodb")]
                 Arc::new(MongoDBConnector::new(url.clone()).await)
             },
-        };
+        }
+    }
+
+    async fn connect_declaration(connector_declaration: &ConnectorDeclaration) -> Arc<dyn Connector> {
+        let url = connector_declaration.url.as_ref().unwrap();
+        if connector_declaration.debug {
+            env::set_var("_TEO_LOG_DB_OPERATION", "true");
+        }
+        let provider = connector_declaration.provider.unwrap();
+        let charset = SQLCharset::new(connector_declaration.charset.clone(), connector_declaration.collation.clone());
+        let primary = Self::connect(provider, url, connector_declaration.foreign_keys, charset.clone()).await;
+        match &connector_declaration.replica_url {
+            Some(replica_url) => {
+                let replica = Self::connect(provider, replica_url, false, charset).await;
+                Arc::new(ReplicatedConnector::new(primary, replica, Duration::from_millis(connector_declaration.replica_lag)))
+            }
+            None => primary,
+        }
+    }
+
+    async fn load_config_from_parser(&mut self, parser: &Parser) {
+        // connector
+        let connector_ref = parser.connector.unwrap();
+        let source = parser.get_source(connector_ref.0);
+        let connector = Self::connect_declaration(source.get_connector(connector_ref.1)).await;
         self.connector = Some(connector.clone());
+        // named connectors which models can choose instead of the default one
+        for connector_ref in parser.named_connectors.iter() {
+            let source = parser.get_source(connector_ref.0);
+            let connector_declaration = source.get_connector(connector_ref.1);
+            let name = connector_declaration.identifier.as_ref().unwrap().name.clone();
+            if self.graph_builder.connectors.contains_key(&name) {
+                panic!("Duplicated connector '{}' found.", name);
+            }
+            let connector = Self::connect_declaration(connector_declaration).await;
+            self.graph_builder.connectors.insert(name, connector);
+        }
         // server config
         let config_ref = parser.config.unwrap();
         let source = parser.get_source(config_ref.0);
         let config = source.get_server_config(config_ref.1);
         let bind = config.bind.as_ref().unwrap();
+        let (cors, cors_routes) = server_cors(&config.cors, &config.cors_routes, self.cors.as_ref());
         self.server_conf = Some(ServerConf {
             bind: bind.clone(),
             path_prefix: if let Some(path_prefix) = &config.path_prefix {
@@ -293,8 +404,28 @@ impl AppBuilder {
                 Some(jwt_secret.clone())
             } else {
                 None
-            }
+            },
+            client_path: config.client_path.clone(),
+            client_provider: config.client_provider,
+            lenient_input_keys: config.lenient_input_keys,
+            introspection: config.introspection,
+            metrics: config.metrics,
+            admin: config.admin.clone(),
+            date_time_format: config.date_time_format.clone(),
+            action_aliases: config.action_aliases.clone(),
+            cors,
+            cors_routes,
+            rate_limit: self.rate_limit.clone().or_else(|| config.rate_limit.clone()),
+            trust_proxy: config.trust_proxy,
         });
+        self.graph_builder.field_naming = config.field_naming;
+        self.graph_builder.date_time_format = config.date_time_format.clone();
+        if let Some(timezone) = &config.timezone {
+            self.graph_builder.default_timezone = match parse_timezone_offset(timezone) {
+                Some(offset) => offset,
+                None => panic!("Invalid timezone '{}', expect 'UTC' or an offset like '+08:00'.", timezone),
+            };
+        }
         // entity generators
         for entity_generator_ref in parser.generators.iter() {
             let source = parser.get_source(entity_generator_ref.0);
@@ -314,9 +445,12 @@ impl AppBuilder {
                 provider: client.provider.unwrap(),
                 dest: client.dest.clone().unwrap(),
                 package: client.package.unwrap(),
-                host: client.host.clone().unwrap(),
+                host: client.host.clone().unwrap_or_else(|| format!("http://localhost:{}", bind.1)),
+                path_prefix: config.path_prefix.clone(),
                 object_name: client.object_name.clone(),
                 git_commit: client.git_commit,
+                watch: client.watch,
+                date_time_format: config.date_time_format.clone(),
             })
         }
         // load enums
@@ -434,14 +568,14 @@ impl AppBuilder {
                                     } else {
                                         model_relation.set_optional();
                                     }
-                                    model_relation.set_is_vec(false);
+                                    model_relation.set_cardinality(Cardinality::One);
                                     model_relation.set_model(field.r#type.identifier.name.clone());
                                 }
                                 Arity::Array => {
                                     if !field.r#type.item_required {
                                         panic!("Relation cannot have optional items.")
                                     }
-                                    model_relation.set_is_vec(true);
+                                    model_relation.set_cardinality(Cardinality::Many);
                                     model_relation.set_model(field.r#type.identifier.name.clone());
                                 }
                                 Arity::Dictionary => panic!("Relations cannot be dictionary.")
@@ -532,6 +666,7 @@ impl AppBuilder {
             "Date" => field.field_type = Some(FieldType::Date),
             "DateTime" => field.field_type = Some(FieldType::DateTime),
             "Decimal" => field.field_type = Some(FieldType::Decimal),
+            "Json" => field.field_type = Some(FieldType::Json),
             #[cfg(feature = "data-source-mongodb")]
             "ObjectId" => field.field_type = Some(FieldType::ObjectId),
             // _ => panic!("Unrecognized type: '{}'.", name)
@@ -550,6 +685,7 @@ impl AppBuilder {
             "Date" =>  property.field_type = Some(FieldType::Date),
             "DateTime" =>  property.field_type = Some(FieldType::DateTime),
             "Decimal" => property.field_type = Some(FieldType::Decimal),
+            "Json" => property.field_type = Some(FieldType::Json),
             #[cfg(feature = "data-source-mongodb")]
             "ObjectId" =>  property.field_type = Some(FieldType::ObjectId),
             _ => property.field_type = Some(FieldType::Enum(name.to_string())),
@@ -20,12 +20,14 @@ pub(crate) struct ServeCommand {
 pub(crate) enum GenerateCommand {
     GenerateClientCommand(GenerateClientCommand),
     GenerateEntityCommand(GenerateEntityCommand),
+    GenerateJSONSchemaCommand(GenerateJSONSchemaCommand),
 }
 
 #[derive(Debug)]
 pub(crate) struct GenerateClientCommand {
     pub(crate) all: bool,
     pub(crate) names: Option<Vec<String>>,
+    pub(crate) watch: bool,
 }
 
 #[derive(Debug)]
@@ -34,6 +36,11 @@ pub(crate) struct GenerateEntityCommand {
     pub(crate) names: Option<Vec<String>>,
 }
 
+#[derive(Debug)]
+pub(crate) struct GenerateJSONSchemaCommand {
+    pub(crate) output: Option<String>,
+}
+
 #[derive(Debug)]
 pub(crate) struct MigrateCommand {
     pub(crate) dry: bool,
@@ -1,12 +1,31 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
+use crate::core::action::Action;
 use crate::core::app::environment::Environment;
 use crate::parser::ast::client::ClientLanguage;
+use crate::parser::ast::config::{Cors, DateTimeFormat, RateLimit};
 
 #[derive(Clone)]
 pub struct ServerConf {
     pub(crate) bind: (String, u16),
     pub(crate) jwt_secret: Option<String>,
     pub(crate) path_prefix: Option<String>,
+    pub(crate) client_path: Option<String>,
+    pub(crate) client_provider: Option<ClientLanguage>,
+    pub(crate) lenient_input_keys: bool,
+    pub(crate) introspection: bool,
+    pub(crate) metrics: bool,
+    /// The identity model whose identities can read `/__schema' and
+    /// `/__metrics'.
+    pub(crate) admin: Option<String>,
+    pub(crate) date_time_format: DateTimeFormat,
+    pub(crate) action_aliases: HashMap<String, Action>,
+    pub(crate) cors: Cors,
+    pub(crate) cors_routes: Vec<(String, Cors)>,
+    pub(crate) rate_limit: Option<RateLimit>,
+    /// Whether the client's address is read from the forwarding headers of a
+    /// proxy in front of the server, instead of the connection's peer.
+    pub(crate) trust_proxy: bool,
 }
 
 #[derive(Clone)]
@@ -23,6 +42,46 @@ pub struct ClientGeneratorConf {
     pub(crate) dest: PathBuf,
     pub(crate) package: bool,
     pub(crate) host: String,
+    pub(crate) path_prefix: Option<String>,
     pub(crate) object_name: Option<String>,
     pub(crate) git_commit: bool,
+    pub(crate) watch: bool,
+    pub(crate) date_time_format: DateTimeFormat,
+}
+
+impl ClientGeneratorConf {
+
+    /// The URL which generated clients send requests to, the host followed by
+    /// the server's path prefix.
+    pub(crate) fn base_url(&self) -> String {
+        let host = self.host.trim_end_matches('/');
+        match self.path_prefix.as_deref().map(|p| p.trim_matches('/')) {
+            Some(prefix) if !prefix.is_empty() => format!("{host}/{prefix}"),
+            _ => host.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_url_joins_host_and_path_prefix() {
+        let mut conf = ClientGeneratorConf {
+            name: None,
+            provider: ClientLanguage::TypeScript,
+            dest: PathBuf::from("client"),
+            package: false,
+            host: "https://example.com/".to_owned(),
+            path_prefix: None,
+            object_name: None,
+            git_commit: false,
+            watch: false,
+            date_time_format: DateTimeFormat::RFC3339,
+        };
+        assert_eq!(conf.base_url(), "https://example.com");
+        conf.path_prefix = Some("/api/".to_owned());
+        assert_eq!(conf.base_url(), "https://example.com/api");
+    }
 }
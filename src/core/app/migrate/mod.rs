@@ -1,8 +1,44 @@
+use crate::core::result::Result;
 use crate::prelude::{Graph};
 
-pub(crate) async fn migrate(graph: &mut Graph, _dry_run: bool) {
-    let result = graph.connector_mut().migrate(graph.models(), false).await;
-    if result.is_err() {
-        panic!("Migration error");
+/// Migrates the database of each connector. A dry run prints the statements
+/// instead of executing them.
+pub(crate) async fn migrate(graph: &Graph, dry_run: bool) -> Result<()> {
+    for (connector, models) in graph.connectors_with_models() {
+        if dry_run {
+            for statement in connector.migrate_plan(&models).await? {
+                println!("{};", statement);
+            }
+        } else {
+            connector.migrate(&models, false).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::core::field::{Field, FieldIndex, IndexSettings};
+    use crate::core::field::r#type::FieldType;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::test_connector::TestConnector;
+    use super::*;
+
+    #[tokio::test]
+    async fn dry_run_plans_without_migrating() {
+        let connector = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            m.field(id);
+        });
+        let graph = builder.build(connector.clone()).await;
+        migrate(&graph, true).await.unwrap();
+        assert_eq!(connector.operations(), vec!["migratePlan User".to_owned()]);
+        migrate(&graph, false).await.unwrap();
+        assert_eq!(connector.operations(), vec!["migratePlan User".to_owned(), "migrate User".to_owned()]);
     }
 }
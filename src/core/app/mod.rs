@@ -5,17 +5,23 @@ pub(crate) mod conf;
 pub(crate) mod serve;
 pub(crate) mod command;
 pub(crate) mod migrate;
+pub(crate) mod watch;
 
-use std::sync::Arc;
-use to_mut::ToMut;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::core::app::builder::{AppBuilder, CallbackLookupTable};
 use crate::core::app::command::{CLI, CLICommand, GenerateCommand};
 use crate::core::app::conf::{ClientGeneratorConf, EntityGeneratorConf, ServerConf};
 use crate::core::app::entrance::Entrance;
 use crate::core::app::environment::EnvironmentVersion;
 use crate::core::app::migrate::migrate;
 use crate::core::app::serve::serve;
+use crate::core::app::watch::SchemaWatcher;
 use crate::core::graph::Graph;
+use crate::parser::ast::config::{Cors, RateLimit};
 use crate::generator::client::generate_client;
+use crate::generator::json_schema::generate_json_schema;
 use crate::generator::server::generate_entity;
 
 pub struct App {
@@ -26,6 +32,10 @@ pub struct App {
     environment_version: EnvironmentVersion,
     entrance: Entrance,
     args: Arc<CLI>,
+    callback_lookup_table: Arc<Mutex<CallbackLookupTable>>,
+    schema_files: Vec<PathBuf>,
+    cors: Option<Arc<dyn Fn(&mut Cors) + Send + Sync>>,
+    rate_limit: Option<RateLimit>,
 }
 
 impl App {
@@ -40,6 +50,7 @@ impl App {
                 serve(
                     self.graph.clone(),
                     self.server_conf.clone(),
+                    self.client_generator_confs.clone(),
                     self.environment_version.clone(),
                     self.entrance.clone(),
                     serve_command.no_migration,
@@ -84,14 +95,40 @@ impl App {
                                 }
                             }
                         }
+                        if client_command.watch {
+                            self.watch_clients().await?;
+                        }
+                    }
+                    GenerateCommand::GenerateJSONSchemaCommand(schema_command) => {
+                        let schema = serde_json::to_string_pretty(&generate_json_schema(&self.graph)).unwrap();
+                        match &schema_command.output {
+                            Some(output) => std::fs::write(output, schema)?,
+                            None => println!("{}", schema),
+                        }
                     }
                 }
             }
             CLICommand::Migrate(migrate_command) => {
-                migrate(self.graph.to_mut(), migrate_command.dry).await;
+                migrate(&self.graph, migrate_command.dry).await.map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
             }
         }
         Ok(())
     }
 
+    async fn watch_clients(&self) -> Result<(), std::io::Error> {
+        if !self.client_generator_confs.iter().any(|c| c.watch) {
+            println!("Cannot find a client generator declaration with watch enabled.");
+            return Ok(());
+        }
+        let mut watcher = SchemaWatcher::new(&self.schema_files);
+        loop {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            if !watcher.changed() { continue }
+            let app = AppBuilder::new_for_reload(self).build().await;
+            for conf in app.client_generator_confs.iter().filter(|c| c.watch) {
+                generate_client(&app.graph, conf).await?;
+            }
+            watcher = SchemaWatcher::new(&app.schema_files);
+        }
+    }
 }
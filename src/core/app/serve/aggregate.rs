@@ -0,0 +1,87 @@
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use crate::parser::ast::config::{DateTimeFormat, FieldNaming};
+use crate::prelude::Value;
+
+static ACCUMULATORS: [&str; 5] = ["_count", "_sum", "_avg", "_min", "_max"];
+
+/// Serializes an aggregate result as `{"_count": {..}, "_sum": {..}, ..}`.
+/// Every requested accumulator and field is present, fields which the
+/// database has no value for are null, and counts of nothing are zero.
+pub(crate) fn aggregate_result_json(input: &Value, result: &Value, naming: FieldNaming, format: &DateTimeFormat) -> JsonValue {
+    let mut retval = JsonMap::new();
+    for accumulator in ACCUMULATORS {
+        let fields = match input.get(accumulator).and_then(|v| v.as_hashmap()) {
+            Some(fields) => fields,
+            None => continue,
+        };
+        let values = result.get(accumulator);
+        let mut map = JsonMap::new();
+        for (field, requested) in fields {
+            if !requested.as_bool().unwrap_or(false) { continue }
+            let value = values.and_then(|v| v.get(field)).cloned().unwrap_or(Value::Null);
+            let json_value = if accumulator == "_count" && value.is_null() {
+                JsonValue::from(0)
+            } else {
+                value.with_date_time_format(format).into()
+            };
+            map.insert(naming.api_key(field).into_owned(), json_value);
+        }
+        retval.insert(accumulator.to_owned(), JsonValue::Object(map));
+    }
+    JsonValue::Object(retval)
+}
+
+/// Serializes group by results as an array of groups. Each group has its
+/// `by` values followed by its accumulators, shaped as in `aggregate_result_json`.
+pub(crate) fn group_by_result_json(input: &Value, result: &Value, naming: FieldNaming, format: &DateTimeFormat) -> JsonValue {
+    let by: Vec<&str> = match input.get("by").and_then(|v| v.as_vec()) {
+        Some(by) => by.iter().filter_map(|k| k.as_str().or(k.as_raw_enum_choice())).collect(),
+        None => vec![],
+    };
+    let groups: Vec<&Value> = result.as_vec().map(|v| v.iter().collect()).unwrap_or_default();
+    JsonValue::Array(groups.into_iter().map(|group| {
+        let mut map = JsonMap::new();
+        for key in &by {
+            map.insert(naming.api_key(key).into_owned(), group.get(key).cloned().unwrap_or(Value::Null).with_date_time_format(format).into());
+        }
+        if let JsonValue::Object(accumulators) = aggregate_result_json(input, group, naming, format) {
+            map.extend(accumulators);
+        }
+        JsonValue::Object(map)
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::teon;
+    use super::*;
+
+    #[test]
+    fn group_by_result_has_by_values_and_every_requested_accumulator() {
+        let input = teon!({"by": ["category"], "_count": {"_all": true}, "_sum": {"price": true}, "_avg": {"price": true, "stock": true}});
+        let result = teon!([
+            {"category": "book", "_count": {"_all": 3i64}, "_sum": {"price": 45.5}, "_avg": {"price": 15.5, "stock": 4.0}},
+            {"category": "pen", "_count": {"_all": 1i64}, "_sum": {"price": 2.0}, "_avg": {"price": 2.0}},
+        ]);
+        assert_eq!(group_by_result_json(&input, &result, FieldNaming::AsDefined, &DateTimeFormat::RFC3339), json!([
+            {"category": "book", "_count": {"_all": 3}, "_sum": {"price": 45.5}, "_avg": {"price": 15.5, "stock": 4.0}},
+            {"category": "pen", "_count": {"_all": 1}, "_sum": {"price": 2.0}, "_avg": {"price": 2.0, "stock": null}},
+        ]));
+    }
+
+    #[test]
+    fn aggregate_of_no_records_has_zero_counts_and_null_values() {
+        let input = teon!({"_count": {"id": true}, "_max": {"price": true}});
+        assert_eq!(aggregate_result_json(&input, &teon!({}), FieldNaming::AsDefined, &DateTimeFormat::RFC3339), json!({"_count": {"id": 0}, "_max": {"price": null}}));
+    }
+
+    #[test]
+    fn accumulated_fields_follow_the_field_naming() {
+        let input = teon!({"by": ["createdOn"], "_sum": {"unitPrice": true}});
+        let result = teon!([{"createdOn": "2023-03-16", "_sum": {"unitPrice": 4.5}}]);
+        assert_eq!(group_by_result_json(&input, &result, FieldNaming::SnakeCase, &DateTimeFormat::RFC3339), json!([
+            {"created_on": "2023-03-16", "_sum": {"unit_price": 4.5}},
+        ]));
+    }
+}
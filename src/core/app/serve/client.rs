@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::core::app::conf::{ClientGeneratorConf, ServerConf};
+use crate::core::graph::Graph;
+use crate::generator::client::generate_client;
+use crate::parser::ast::client::ClientLanguage;
+
+/// Generated client files kept in memory and served over HTTP.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ClientAssets {
+    files: HashMap<String, String>,
+}
+
+impl ClientAssets {
+
+    pub(crate) fn new(files: HashMap<String, String>) -> Self {
+        Self { files }
+    }
+
+    pub(crate) async fn generate(graph: &Graph, conf: &ServerConf, client_confs: &Vec<ClientGeneratorConf>) -> std::io::Result<Self> {
+        let provider = conf.client_provider.unwrap_or(ClientLanguage::TypeScript);
+        let dest = std::env::temp_dir().join(format!("teo-client-{}", std::process::id()));
+        let mut client_conf = match client_confs.iter().find(|c| c.provider == provider) {
+            Some(c) => c.clone(),
+            None => ClientGeneratorConf {
+                name: None,
+                provider,
+                dest: dest.clone(),
+                package: false,
+                host: format!("http://{}:{}", conf.bind.0, conf.bind.1),
+                path_prefix: conf.path_prefix.clone(),
+                object_name: Some("teo".to_owned()),
+                git_commit: false,
+                watch: false,
+                date_time_format: conf.date_time_format.clone(),
+            }
+        };
+        client_conf.dest = dest.clone();
+        client_conf.package = false;
+        client_conf.git_commit = false;
+        generate_client(graph, &client_conf).await?;
+        let mut files = HashMap::new();
+        Self::read_dir(&dest, &dest, &mut files)?;
+        let _ = std::fs::remove_dir_all(&dest);
+        Ok(Self::new(files))
+    }
+
+    fn read_dir(base: &Path, dir: &PathBuf, files: &mut HashMap<String, String>) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                Self::read_dir(base, &path, files)?;
+            } else {
+                let relative = path.strip_prefix(base).unwrap().to_str().unwrap().replace("\\", "/");
+                files.insert(relative, std::fs::read_to_string(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn file_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.files.keys().map(|k| k.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    pub(crate) fn file(&self, name: &str) -> Option<&str> {
+        self.files.get(name.trim_start_matches("/")).map(|f| f.as_str())
+    }
+
+    pub(crate) fn content_type(name: &str) -> &'static str {
+        if name.ends_with(".js") {
+            "application/javascript"
+        } else if name.ends_with(".json") {
+            "application/json"
+        } else {
+            "text/plain; charset=utf-8"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::hashmap;
+    use super::*;
+
+    #[test]
+    fn file_lookup_ignores_leading_slash() {
+        let assets = ClientAssets::new(hashmap!{"index.d.ts".to_owned() => "export {}".to_owned()});
+        assert_eq!(assets.file("/index.d.ts"), Some("export {}"));
+        assert_eq!(assets.file("index.js"), None);
+    }
+
+    #[test]
+    fn content_type_is_inferred_from_extension() {
+        assert_eq!(ClientAssets::content_type("index.js"), "application/javascript");
+        assert_eq!(ClientAssets::content_type("index.d.ts"), "text/plain; charset=utf-8");
+    }
+}
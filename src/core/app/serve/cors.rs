@@ -0,0 +1,106 @@
+use std::sync::Arc;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::HttpResponse;
+use crate::parser::ast::config::{Cors, CorsOverride};
+
+/// The server's CORS settings of the schema updated by `build` of the app
+/// builder, and the ones of each route over them.
+pub(crate) fn server_cors(cors: &Cors, routes: &Vec<(String, CorsOverride)>, build: Option<&Arc<dyn Fn(&mut Cors) + Send + Sync>>) -> (Cors, Vec<(String, Cors)>) {
+    let mut cors = cors.clone();
+    if let Some(build) = build {
+        build(&mut cors);
+        cors.check("cors");
+    }
+    let routes = routes.iter().map(|(route, route_cors)| {
+        let route_cors = route_cors.merge(&cors);
+        route_cors.check(route);
+        (route.clone(), route_cors)
+    }).collect();
+    (cors, routes)
+}
+
+/// The CORS settings of a request path, of the route with the longest
+/// prefix of it, or the server's when no route matches.
+pub(crate) fn cors_for_path<'a>(cors: &'a Cors, routes: &'a Vec<(String, Cors)>, path: &str) -> &'a Cors {
+    routes.iter().filter(|(route, _)| {
+        let route = route.trim_end_matches('/');
+        path == route || path.starts_with(&format!("{route}/"))
+    }).max_by_key(|(route, _)| route.len()).map_or(cors, |(_, cors)| cors)
+}
+
+/// The CORS headers of a response to a request from `origin`, none if the
+/// origin isn't allowed.
+pub(crate) fn cors_headers(cors: &Cors, origin: Option<&str>) -> Vec<(&'static str, String)> {
+    let allowed_origin = match cors.allowed_origin(origin) {
+        Some(allowed_origin) => allowed_origin,
+        None => return vec![],
+    };
+    let mut headers = vec![];
+    if allowed_origin != "*" {
+        headers.push(("Vary", "Origin".to_owned()));
+    }
+    headers.push(("Access-Control-Allow-Origin", allowed_origin));
+    headers.push(("Access-Control-Allow-Methods", cors.allow_methods.join(", ")));
+    headers.push(("Access-Control-Allow-Headers", cors.allow_headers.join(", ")));
+    if cors.allow_credentials {
+        headers.push(("Access-Control-Allow-Credentials", "true".to_owned()));
+    }
+    if let Some(max_age) = cors.max_age {
+        headers.push(("Access-Control-Max-Age", max_age.to_string()));
+    }
+    headers
+}
+
+pub(crate) fn apply_cors(mut response: HttpResponse, cors: &Cors, origin: Option<&str>) -> HttpResponse {
+    for (name, value) in cors_headers(cors, origin) {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().insert(HeaderName::from_bytes(name.as_bytes()).unwrap(), value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cors_is_disabled_by_default() {
+        assert!(!Cors::default().is_enabled());
+        assert!(cors_headers(&Cors::default(), Some("https://app.example.com")).is_empty());
+    }
+
+    #[test]
+    fn only_allowed_origins_receive_headers() {
+        let mut cors = Cors::default();
+        cors.allow_origin("https://app.example.com").allow_methods(["POST"]).allow_headers(["Content-Type", "Authorization"]).allow_credentials(true);
+        let headers = cors_headers(&cors, Some("https://app.example.com"));
+        assert!(headers.contains(&("Access-Control-Allow-Origin", "https://app.example.com".to_owned())));
+        assert!(headers.contains(&("Access-Control-Allow-Methods", "POST".to_owned())));
+        assert!(headers.contains(&("Access-Control-Allow-Headers", "Content-Type, Authorization".to_owned())));
+        assert!(headers.contains(&("Access-Control-Allow-Credentials", "true".to_owned())));
+        assert!(headers.contains(&("Vary", "Origin".to_owned())));
+        assert!(cors_headers(&cors, Some("https://evil.example.com")).is_empty());
+        assert!(cors_headers(&cors, None).is_empty());
+    }
+
+    #[test]
+    fn routes_inherit_the_settings_of_the_builder() {
+        let routes = vec![("/webhooks".to_owned(), CorsOverride { allow_origins: Some(vec!["https://hooks.example.com".to_owned()]), ..Default::default() })];
+        let build: Arc<dyn Fn(&mut Cors) + Send + Sync> = Arc::new(|c: &mut Cors| { c.allow_origin("https://app.example.com").allow_methods(["POST"]).max_age(None); });
+        let (cors, routes) = server_cors(&Cors::default(), &routes, Some(&build));
+        assert_eq!(cors.allow_origins, vec!["https://app.example.com".to_owned()]);
+        let webhooks = &routes[0].1;
+        assert_eq!(webhooks.allow_origins, vec!["https://hooks.example.com".to_owned()]);
+        assert_eq!(webhooks.allow_methods, vec!["POST".to_owned()]);
+        assert_eq!(webhooks.max_age, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "CORS of '/webhooks' can't allow credentials from any origin, list the allowed origins instead.")]
+    fn routes_are_checked_with_the_settings_of_the_builder() {
+        let routes = vec![("/webhooks".to_owned(), CorsOverride { allow_origins: Some(vec!["*".to_owned()]), ..Default::default() })];
+        let build: Arc<dyn Fn(&mut Cors) + Send + Sync> = Arc::new(|c: &mut Cors| { c.allow_origin("https://app.example.com").allow_credentials(true); });
+        server_cors(&Cors::default(), &routes, Some(&build));
+    }
+}
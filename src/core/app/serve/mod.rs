@@ -1,41 +1,53 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use futures_util::future;
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
 use actix_http::body::BoxBody;
 use actix_http::{Method};
 use actix_web::{App, HttpRequest, HttpResponse, HttpServer, web};
 use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
-use actix_web::middleware::DefaultHeaders;
 use chrono::{DateTime, Duration, Local, Utc};
 use colored::Colorize;
 use futures_util::StreamExt;
 use key_path::{KeyPath, path};
 use serde_json::{json, Value as JsonValue};
-use to_mut::ToMut;
 use crate::core::action::{
     Action, CREATE, DELETE, ENTRY, FIND, IDENTITY, MANY, SINGLE, UPDATE, UPSERT,
     FIND_UNIQUE_HANDLER, FIND_FIRST_HANDLER, FIND_MANY_HANDLER, CREATE_HANDLER, UPDATE_HANDLER,
     UPSERT_HANDLER, DELETE_HANDLER, CREATE_MANY_HANDLER, UPDATE_MANY_HANDLER, DELETE_MANY_HANDLER,
-    COUNT_HANDLER, AGGREGATE_HANDLER, GROUP_BY_HANDLER, SIGN_IN_HANDLER, IDENTITY_HANDLER,
+    COUNT_HANDLER, AGGREGATE_HANDLER, GROUP_BY_HANDLER, FACETS_HANDLER, SIGN_IN_HANDLER, IDENTITY_HANDLER,
 };
 use crate::core::action::source::ActionSource;
-use crate::core::app::conf::ServerConf;
+use crate::core::app::conf::{ClientGeneratorConf, ServerConf};
 use crate::core::app::entrance::Entrance;
 use crate::core::app::environment::EnvironmentVersion;
 use crate::core::app::migrate::migrate;
 use crate::core::connector::SaveSession;
 use self::jwt_token::{Claims, decode_token, encode_token};
+use self::client::ClientAssets;
+use self::aggregate::{aggregate_result_json, group_by_result_json};
+use self::cors::{apply_cors, cors_for_path};
+use self::rate_limit::RateLimiter;
+use self::naming::api_error;
 use crate::core::graph::Graph;
+use crate::core::graph::introspection::introspect;
+use crate::core::field::write_rule::WriteRule;
 use crate::core::model::Model;
 use crate::core::object::Object;
 use crate::core::pipeline::ctx::{Ctx};
-use crate::core::error::Error;
+use crate::core::error::{Error, ErrorType};
 use crate::core::teon::decoder::Decoder;
+use crate::core::teon::redact::redact;
 use crate::prelude::Value;
 use crate::teon;
 
 pub(crate) mod response;
 pub(crate) mod jwt_token;
+pub(crate) mod client;
+pub(crate) mod aggregate;
+pub(crate) mod naming;
+pub(crate) mod cors;
+pub(crate) mod rate_limit;
 
 fn j(v: Value) -> JsonValue {
     v.into()
@@ -72,6 +84,12 @@ fn log_unhandled(start: SystemTime, method: &str, path: &str, code: u16) {
     println!("{} {} {} on {} - {} {}", local_formatted, unhandled, method.bold(), path, code_string, ms_str);
 }
 
+/// Logs the input of a failed request, with sensitive values redacted.
+fn log_input(graph: &Graph, model: &Model, input: &Value) {
+    let input: JsonValue = redact(graph, model.name(), input).into();
+    println!("{} {}", "Input".dimmed(), input);
+}
+
 fn log_request(start: SystemTime, action: &str, model: &str, code: u16) {
     let now = SystemTime::now();
     let local: DateTime<Local> = Local::now();
@@ -88,6 +106,19 @@ fn log_request(start: SystemTime, action: &str, model: &str, code: u16) {
     println!("{} {} on {} - {} {}", local_formatted, action.bold(), model, code_string, ms_str.dimmed());
 }
 
+/// Only identities of the configured admin model can read the server's own
+/// endpoints.
+async fn check_admin(r: &HttpRequest, graph: &Graph, conf: &ServerConf) -> Result<(), Error> {
+    let admin = match (&conf.admin, &conf.jwt_secret) {
+        (Some(admin), Some(_)) => admin,
+        _ => return Err(Error::permission_error(path![], "No admin model is configured.")),
+    };
+    match get_identity(r, graph, conf).await? {
+        Some(identity) if identity.model().name() == admin => Ok(()),
+        _ => Err(Error::permission_error(path![], "This endpoint requires an admin.")),
+    }
+}
+
 async fn get_identity(r: &HttpRequest, graph: &Graph, conf: &ServerConf) -> Result<Option<Object>, Error> {
     let header_value = r.headers().get("authorization");
     if let None = header_value {
@@ -119,6 +150,50 @@ async fn get_identity(r: &HttpRequest, graph: &Graph, conf: &ServerConf) -> Resu
     return Ok(Some(identity.unwrap()));
 }
 
+/// The key which action quotas are counted against, the signed in identity
+/// or else the client's address.
+fn quota_identity(r: &HttpRequest, conf: &ServerConf, identity: Option<&Object>) -> String {
+    match identity {
+        Some(identity) => format!("{}:{}", identity.model().name(), j(identity.identifier())),
+        None => client_address(r, conf),
+    }
+}
+
+/// The key which the rate limit is counted against. It's read from the token
+/// without looking the identity up, so that limited requests cost nothing.
+fn rate_limit_identity(r: &HttpRequest, conf: &ServerConf) -> String {
+    let token = r.headers().get("authorization").and_then(|h| h.to_str().ok()).and_then(|h| h.get(7..));
+    match (token, conf.jwt_secret.as_ref()) {
+        (Some(token), Some(secret)) => match decode_token(&token.to_string(), secret) {
+            Ok(claims) => format!("{}:{}", claims.model, claims.id),
+            Err(_) => client_address(r, conf),
+        },
+        _ => client_address(r, conf),
+    }
+}
+
+/// The client's address. Forwarding headers are only believed when a proxy
+/// in front of the server is trusted, clients could fake them otherwise.
+fn client_address(r: &HttpRequest, conf: &ServerConf) -> String {
+    if conf.trust_proxy {
+        r.connection_info().realip_remote_addr().unwrap_or("").to_owned()
+    } else {
+        r.peer_addr().map_or("".to_owned(), |addr| addr.ip().to_string())
+    }
+}
+
+/// The response of a single object action which failed, `null` data if the
+/// model responds so when the object is not found.
+fn not_found_response(graph: &Graph, model: &Model, action: Action, err: Error) -> HttpResponse {
+    if err.r#type != ErrorType::ObjectNotFound {
+        return api_error(graph, model, err).into();
+    }
+    match model.not_found(action).resolve(err) {
+        Ok(_) => HttpResponse::Ok().json(json!({"data": null})),
+        Err(err) => api_error(graph, model, err).into(),
+    }
+}
+
 async fn handle_find_unique(graph: &Graph, input: &Value, model: &Model, source: ActionSource) -> HttpResponse {
     let action = Action::from_u32(FIND | SINGLE | ENTRY);
     let result = graph.find_unique_internal(model.name(), input, false, action, source).await;
@@ -127,9 +202,7 @@ async fn handle_find_unique(graph: &Graph, input: &Value, model: &Model, source:
             let json_data: JsonValue = obj.to_json_internal(&path!["data"]).await.unwrap().into();
             HttpResponse::Ok().json(json!({"data": json_data}))
         }
-        Err(err) => {
-            err.into()
-        }
+        Err(err) => not_found_response(graph, model, Action::from_u32(FIND_UNIQUE_HANDLER), err),
     }
 }
 
@@ -141,24 +214,33 @@ async fn handle_find_first(graph: &Graph, input: &Value, model: &Model, source:
             let json_data: JsonValue = obj.to_json_internal(&path!["data"]).await.unwrap().into();
             HttpResponse::Ok().json(json!({"data": json_data}))
         }
-        Err(err) => {
-            err.into()
-        }
+        Err(err) => not_found_response(graph, model, Action::from_u32(FIND_FIRST_HANDLER), err),
     }
 }
 
 async fn handle_find_many(graph: &Graph, input: &Value, model: &Model, source: ActionSource) -> HttpResponse {
     let action = Action::from_u32(FIND | MANY | ENTRY);
-    let result = graph.find_many_internal(model.name(), input, false, action, source).await;
+    let mut input = input.clone();
+    // records are counted for the page info unless `_withMeta: false` skips it
+    let with_meta = input.as_hashmap_mut().unwrap().remove("_withMeta").map_or(true, |v| v.as_bool().unwrap());
+    let result = if with_meta {
+        graph.find_many_and_count_internal(model.name(), &input, false, action, source).await.map(|(results, count)| (results, Some(count)))
+    } else {
+        graph.find_many_internal(model.name(), &input, false, action, source).await.map(|results| (results, None))
+    };
     match result {
-        Ok(results) => {
-            let mut count_input = input.clone();
-            let count_input_obj = count_input.as_hashmap_mut().unwrap();
-            count_input_obj.remove("skip");
-            count_input_obj.remove("take");
-            count_input_obj.remove("pageSize");
-            count_input_obj.remove("pageNumber");
-            let count = graph.count(model.name(), &count_input).await.unwrap();
+        Ok((results, count)) => {
+            let mut result_json: Vec<JsonValue> = vec![];
+            for (index, result) in results.iter().enumerate() {
+                match result.to_json_internal(&path!["data", index]).await {
+                    Ok(result) => result_json.push(result.into()),
+                    Err(_) => return Error::permission_error(path!["data"], "not allowed to read").into(),
+                }
+            }
+            let count = match count {
+                Some(count) => count,
+                None => return HttpResponse::Ok().json(json!({"data": result_json})),
+            };
             let mut meta = json!({"count": count});
             let page_size = input.get("pageSize");
             if page_size.is_some() {
@@ -168,16 +250,11 @@ async fn handle_find_many(graph: &Graph, input: &Value, model: &Model, source: A
                 if count % page_size != 0 {
                     number_of_pages += 1;
                 }
+                let page_number = input.get("pageNumber").map_or(1, |n| n.as_i32().unwrap());
+                meta.as_object_mut().unwrap().insert("pageSize".to_string(), page_size.into());
+                meta.as_object_mut().unwrap().insert("pageNumber".to_string(), page_number.into());
                 meta.as_object_mut().unwrap().insert("numberOfPages".to_string(), number_of_pages.into());
             }
-
-            let mut result_json: Vec<JsonValue> = vec![];
-            for (index, result) in results.iter().enumerate() {
-                match result.to_json_internal(&path!["data", index]).await {
-                    Ok(result) => result_json.push(result.into()),
-                    Err(_) => return Error::permission_error(path!["data"], "not allowed to read").into(),
-                }
-            }
             HttpResponse::Ok().json(json!({
                     "meta": meta,
                     "data": result_json
@@ -185,7 +262,7 @@ async fn handle_find_many(graph: &Graph, input: &Value, model: &Model, source: A
         }
         Err(err) => {
             HttpResponse::BadRequest().json(json!({
-                    "error": err
+                    "error": api_error(graph, model, err)
                 }))
         }
     }
@@ -218,14 +295,14 @@ async fn handle_create(graph: &Graph, input: &Value, model: &Model, source: Acti
     let create = input.get("create");
     let include = input.get("include");
     let select = input.get("select");
-    let session = graph.connector().new_save_session();
+    let session = model.connector().new_save_session();
     let result = handle_create_internal(graph, create, include, select, model, &path!["create"], action, source, session).await;
     match result {
         Ok(val) => {
             let json_val: JsonValue = val.into();
             HttpResponse::Ok().json(json!({"data": json_val}))
         },
-        Err(err) => HttpResponse::BadRequest().json(json!({"error": err}))
+        Err(err) => HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
     }
 }
 
@@ -242,7 +319,7 @@ async fn handle_update(graph: &Graph, input: &Value, model: &Model, source: Acti
     let action = Action::from_u32(UPDATE | ENTRY | SINGLE);
     let result = graph.find_unique_internal(model.name(), input, true, action, source).await;
     if result.is_err() {
-        return HttpResponse::NotFound().json(json!({"error": result.err()}));
+        return HttpResponse::NotFound().json(json!({"error": result.err().map(|err| api_error(graph, model, err))}));
     }
     let result = result.unwrap();
     let update = input.get("update");
@@ -256,7 +333,7 @@ async fn handle_update(graph: &Graph, input: &Value, model: &Model, source: Acti
             HttpResponse::Ok().json(json!({"data": json_val}))
         }
         Err(err) => {
-            HttpResponse::BadRequest().json(json!({"error": err}))
+            HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
         }
     }
 }
@@ -289,12 +366,12 @@ async fn handle_upsert(graph: &Graph, input: &Value, model: &Model, source: Acti
                             HttpResponse::Ok().json(json!({"data": json_val}))
                         }
                         Err(err) => {
-                            HttpResponse::BadRequest().json(json!({"error": err}))
+                            HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
                         }
                     }
                 }
                 Err(err) => {
-                    HttpResponse::BadRequest().json(json!({"error": err}))
+                    HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
                 }
             }
         }
@@ -321,12 +398,12 @@ async fn handle_upsert(graph: &Graph, input: &Value, model: &Model, source: Acti
                             return HttpResponse::Ok().json(json!({"data": json_data}));
                         }
                         Err(err) => {
-                            HttpResponse::BadRequest().json(json!({"error": err}))
+                            HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
                         }
                     }
                 }
                 Err(err) => {
-                    HttpResponse::BadRequest().json(json!({"error": err}))
+                    HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
                 }
             }
         }
@@ -337,7 +414,7 @@ async fn handle_delete(graph: &Graph, input: &Value, model: &Model, source: Acti
     let action = Action::from_u32(DELETE | SINGLE | ENTRY);
     let result = graph.find_unique_internal(model.name(), input, true, action, source).await;
     if result.is_err() {
-        return HttpResponse::NotFound().json(json!({"error": result.err()}));
+        return HttpResponse::NotFound().json(json!({"error": result.err().map(|err| api_error(graph, model, err))}));
     }
     let result = result.unwrap();
     // find the object here
@@ -347,7 +424,7 @@ async fn handle_delete(graph: &Graph, input: &Value, model: &Model, source: Acti
             HttpResponse::Ok().json(json!({"data": json_data}))
         }
         Err(err) => {
-            err.into()
+            api_error(graph, model, err).into()
         }
     }
 }
@@ -360,22 +437,23 @@ async fn handle_create_many(graph: &Graph, input: &Value, model: &Model, source:
     let select = input.get("select");
     if create.is_none() {
         let err = Error::missing_required_input_with_type("array", path!["create"]);
-        return HttpResponse::BadRequest().json(json!({"error": err}));
+        return HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}));
     }
     let create = create.unwrap();
     if !create.is_vec() {
         let err = Error::unexpected_input_type("array", path!["create"]);
-        return HttpResponse::BadRequest().json(json!({"error": err}));
+        return HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}));
     }
     let create = create.as_vec().unwrap();
     let mut count = 0;
     let mut ret_data: Vec<Value> = vec![];
-    let session = graph.connector().new_save_session();
+    let session = model.connector().new_save_session();
     for (index, val) in create.iter().enumerate() {
         let result = handle_create_internal(graph, Some(val), include, select, model, &path!["create", index], action, source.clone(), session.clone()).await;
         match result {
             Err(err) => {
-                println!("{:?}", err.errors);
+                let val: JsonValue = redact(graph, model.name(), val).into();
+                println!("{:?} {}", err.errors, val);
             },
             Ok(val) => {
                 count += 1;
@@ -394,12 +472,15 @@ async fn handle_update_many(graph: &Graph, input: &Value, model: &Model, source:
     let action = Action::from_u32(UPDATE | MANY | ENTRY);
     let result = graph.find_many_internal(model.name(), input, true, action, source).await;
     if result.is_err() {
-        return HttpResponse::BadRequest().json(json!({"error": result.err()}));
+        return HttpResponse::BadRequest().json(json!({"error": result.err().map(|err| api_error(graph, model, err))}));
     }
     let result = result.unwrap();
     let update = input.get("update");
     let include = input.get("include");
     let select = input.get("select");
+    if let Some(updators) = bulk_atomic_updators(model, update) {
+        return handle_update_many_atomically(graph, input, model, result, &updators, include, select).await;
+    }
 
     let mut count = 0;
     let mut ret_data: Vec<Value> = vec![];
@@ -421,11 +502,74 @@ async fn handle_update_many(graph: &Graph, input: &Value, model: &Model, source:
         }))
 }
 
+/// The atomic updators of an update which can be applied to every record in
+/// one database operation, as nothing has to be run on each record.
+fn bulk_atomic_updators(model: &Model, update: Option<&Value>) -> Option<HashMap<String, Value>> {
+    let map = update?.as_hashmap()?;
+    if map.is_empty() {
+        return None;
+    }
+    if model.before_save_pipeline().has_any_items() || model.after_save_pipeline().has_any_items() {
+        return None;
+    }
+    if model.fields().iter().any(|f| f.needs_on_save_callback()) {
+        return None;
+    }
+    for (key, value) in map {
+        let field = model.field(key)?;
+        // write conditions are decided on the assigned value
+        if matches!(field.write_rule, WriteRule::WriteIf(_)) {
+            return None;
+        }
+        let updator = value.as_hashmap()?;
+        if updator.len() != 1 || !["increment", "decrement", "multiply", "divide"].contains(&updator.keys().next().unwrap().as_str()) {
+            return None;
+        }
+    }
+    Some(map.clone())
+}
+
+async fn handle_update_many_atomically(graph: &Graph, input: &Value, model: &Model, objects: Vec<Object>, updators: &HashMap<String, Value>, include: Option<&Value>, select: Option<&Value>) -> HttpResponse {
+    if input.get("where").map_or(true, |w| w.as_hashmap().map_or(true, |m| m.is_empty())) {
+        return Error::unexpected_input_value_with_reason("Updating many records at once requires a non-empty where.", path!["where"]).into();
+    }
+    // records which mustn't be updated are skipped like updating one by one does
+    let mut permitted: Vec<Object> = vec![];
+    for object in objects {
+        if object.check_atomic_update(updators, &path!["update"]).await.is_ok() {
+            permitted.push(object);
+        }
+    }
+    let objects = permitted;
+    if !objects.is_empty() {
+        if let Err(err) = model.connector().update_many_atomically(model, &objects, updators).await {
+            return api_error(graph, model, err).into();
+        }
+    }
+    let mut ret_data: Vec<Value> = vec![];
+    for (index, object) in objects.iter().enumerate() {
+        let refetched = match object.refreshed(include, select).await {
+            Ok(refetched) => refetched,
+            Err(err) => return api_error(graph, model, err).into(),
+        };
+        match refetched.to_json_internal(&path!["data", index]).await {
+            Ok(json_value) => ret_data.push(json_value),
+            Err(err) => return api_error(graph, model, err).into(),
+        }
+    }
+    HttpResponse::Ok().json(json!({
+            "meta": {
+                "count": ret_data.len()
+            },
+            "data": j(Value::Vec(ret_data))
+        }))
+}
+
 async fn handle_delete_many(graph: &Graph, input: &Value, model: &Model, source: ActionSource) -> HttpResponse {
     let action = Action::from_u32(DELETE | MANY | ENTRY);
     let result = graph.find_many_internal(model.name(), input, true, action, source).await;
     if result.is_err() {
-        return HttpResponse::BadRequest().json(json!({"error": result.err()}));
+        return HttpResponse::BadRequest().json(json!({"error": result.err().map(|err| api_error(graph, model, err))}));
     }
     let result = result.unwrap();
     let mut count = 0;
@@ -459,29 +603,47 @@ async fn handle_count(graph: &Graph, input: &Value, model: &Model, _source: Acti
             HttpResponse::Ok().json(json!({"data": count}))
         }
         Err(err) => {
-            HttpResponse::BadRequest().json(json!({"error": err}))
+            HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
         }
     }
 }
 
 async fn handle_aggregate(graph: &Graph, input: &Value, model: &Model, _source: ActionSource) -> HttpResponse {
     match graph.aggregate(model.name(), input).await {
-        Ok(count) => {
-            HttpResponse::Ok().json(json!({"data": j(count)}))
+        Ok(result) => {
+            HttpResponse::Ok().json(json!({"data": aggregate_result_json(input, &result, graph.field_naming(), graph.date_time_format())}))
         }
         Err(err) => {
-            HttpResponse::BadRequest().json(json!({"error": err}))
+            HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
         }
     }
 }
 
 async fn handle_group_by(graph: &Graph, input: &Value, model: &Model, _source: ActionSource) -> HttpResponse {
     match graph.group_by(model.name(), input).await {
-        Ok(count) => {
-            HttpResponse::Ok().json(json!({"data": j(count)}))
+        Ok(result) => {
+            HttpResponse::Ok().json(json!({"data": group_by_result_json(input, &result, graph.field_naming(), graph.date_time_format())}))
+        }
+        Err(err) => {
+            HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
+        }
+    }
+}
+
+async fn handle_facets(graph: &Graph, input: &Value, model: &Model, _source: ActionSource) -> HttpResponse {
+    if input.get("field").is_none() {
+        return Error::missing_required_input_with_type("string", path!["field"]).into();
+    }
+    match graph.facets(model.name(), input).await {
+        Ok(facets) => {
+            let data: Vec<JsonValue> = facets.into_iter().map(|(value, count)| {
+                let value: JsonValue = value.into();
+                json!({"value": value, "count": count})
+            }).collect();
+            HttpResponse::Ok().json(json!({"data": data}))
         }
         Err(err) => {
-            HttpResponse::BadRequest().json(json!({"error": err}))
+            HttpResponse::BadRequest().json(json!({"error": api_error(graph, model, err)}))
         }
     }
 }
@@ -507,17 +669,17 @@ async fn handle_sign_in(graph: &Graph, input: &Value, model: &Model, conf: &Serv
                 identity_key = Some(k);
                 identity_value = Some(v);
             } else {
-                return Error::unexpected_input_value_with_reason("Multiple auth identity provided", path!["credentials", k]).into();
+                return api_error(graph, model, Error::unexpected_input_value_with_reason("Multiple auth identity provided", path!["credentials", k])).into();
             }
         } else if model.auth_by_keys().contains(k) {
             if by_key == None {
                 by_key = Some(k);
                 by_value = Some(v);
             } else {
-                return Error::unexpected_input_value_with_reason("Multiple auth checker provided", path!["credentials", k]).into();
+                return api_error(graph, model, Error::unexpected_input_value_with_reason("Multiple auth checker provided", path!["credentials", k])).into();
             }
         } else {
-            return Error::unexpected_input_key(k, path!["credentials", k]).into();
+            return api_error(graph, model, Error::unexpected_input_key(k, path!["credentials", k])).into();
         }
     }
     if identity_key == None {
@@ -532,7 +694,7 @@ async fn handle_sign_in(graph: &Graph, input: &Value, model: &Model, conf: &Serv
         }
     }), true, Action::from_u32(FIND | SINGLE | ENTRY), ActionSource::ProgramCode).await;
     if let Err(_err) = obj_result {
-        return Error::unexpected_input_value("This identity is not found.", path!["credentials", identity_key.unwrap()]).into();
+        return api_error(graph, model, Error::unexpected_input_value("This identity is not found.", path!["credentials", identity_key.unwrap()])).into();
     }
     let obj = obj_result.unwrap();
     let auth_by_arg = by_field.identity_checker.as_ref().unwrap();
@@ -542,7 +704,7 @@ async fn handle_sign_in(graph: &Graph, input: &Value, model: &Model, conf: &Serv
     let result = pipeline.process(ctx).await;
     return match result {
         Err(_err) => {
-            return Error::unexpected_input_value_with_reason("Authentication failed.", path!["credentials", by_key.unwrap()]).into();
+            return api_error(graph, model, Error::unexpected_input_value_with_reason("Authentication failed.", path!["credentials", by_key.unwrap()])).into();
         }
         Ok(_v) => {
             let include = input.get("include");
@@ -597,14 +759,26 @@ pub fn make_app(graph: Graph, conf: ServerConf) ->  App<impl ServiceFactory<
     Config = (),
     InitError = (),
     Error = actix_web::Error,
+> + 'static> {
+    make_app_with_client_assets(graph, conf, None)
+}
+
+pub(crate) fn make_app_with_client_assets(graph: Graph, conf: ServerConf, client_assets: Option<ClientAssets>) ->  App<impl ServiceFactory<
+    ServiceRequest,
+    Response = ServiceResponse<BoxBody>,
+    Config = (),
+    InitError = (),
+    Error = actix_web::Error,
 > + 'static> {
     let leaked_graph = Box::leak(Box::new(graph));
     let leaked_conf = Box::leak(Box::new(conf));
+    let leaked_client_assets: Option<&'static ClientAssets> = client_assets.map(|a| &*Box::leak(Box::new(a)));
+    let leaked_rate_limiter: Option<&'static RateLimiter> = leaked_conf.rate_limit.clone().map(|r| &*Box::leak(Box::new(RateLimiter::new(r))));
     Graph::set_current(leaked_graph);
-    make_app_inner(leaked_graph, leaked_conf)
+    make_app_inner(leaked_graph, leaked_conf, leaked_client_assets, leaked_rate_limiter)
 }
 
-fn make_app_inner(graph: &'static Graph, conf: &'static ServerConf) -> App<impl ServiceFactory<
+fn make_app_inner(graph: &'static Graph, conf: &'static ServerConf, client_assets: Option<&'static ClientAssets>, rate_limiter: Option<&'static RateLimiter>) -> App<impl ServiceFactory<
     ServiceRequest,
     Response = ServiceResponse<BoxBody>,
     Config = (),
@@ -612,205 +786,232 @@ fn make_app_inner(graph: &'static Graph, conf: &'static ServerConf) -> App<impl
     Error = actix_web::Error,
 > + 'static> {
     let app = App::new()
-        .wrap(DefaultHeaders::new()
-            .add(("Access-Control-Allow-Origin", "*"))
-            .add(("Access-Control-Allow-Methods", "OPTIONS, POST, GET"))
-            .add(("Access-Control-Allow-Headers", "*"))
-            .add(("Access-Control-Max-Age", "86400")))
-        .default_service(web::route().to(move |r: HttpRequest, mut payload: web::Payload| async move {
-            let start = SystemTime::now();
-            let mut path = r.path().to_string();
-            if let Some(prefix) = &conf.path_prefix {
-                if !path.starts_with(prefix) {
-                    log_unhandled(start, r.method().as_str(), &path, 404);
-                    return Error::destination_not_found().into();
-                }
-                path = path.strip_prefix(prefix).unwrap().to_string();
-            }
-            let path = if path.len() > 1 && path.ends_with("/") {
-                path[0..path.len() - 1].to_string()
-            } else {
-                path
-            };
-            if (r.method() != Method::POST) && (r.method() != Method::OPTIONS) {
-                log_unhandled(start, r.method().as_str(), &path, 404);
-                return Error::destination_not_found().into();
-            }
-            let path_components = path_components(&path);
-            let first_component = path_components.get(1).unwrap();
-            if !(path_components.len() == 3 && first_component == &"action") {
-                log_unhandled(start, r.method().as_str(), &path, 404);
-                return Error::destination_not_found().into();
+        .default_service(web::route().to(move |r: HttpRequest, payload: web::Payload| async move {
+            let path = r.path().to_owned();
+            let path = conf.path_prefix.as_ref().and_then(|p| path.strip_prefix(p.as_str())).unwrap_or(&path);
+            let cors = cors_for_path(&conf.cors, &conf.cors_routes, path);
+            let origin = r.headers().get("Origin").and_then(|o| o.to_str().ok()).map(|o| o.to_owned());
+            let response = handle_request(r, payload, graph, conf, client_assets, rate_limiter).await;
+            apply_cors(response, cors, origin.as_deref())
+        }));
+    app
+}
+
+async fn handle_request(r: HttpRequest, mut payload: web::Payload, graph: &'static Graph, conf: &'static ServerConf, client_assets: Option<&'static ClientAssets>, rate_limiter: Option<&'static RateLimiter>) -> HttpResponse {
+    let start = SystemTime::now();
+    let mut path = r.path().to_string();
+    if let Some(prefix) = &conf.path_prefix {
+        if !path.starts_with(prefix) {
+            log_unhandled(start, r.method().as_str(), &path, 404);
+            return Error::destination_not_found().into();
+        }
+        path = path.strip_prefix(prefix).unwrap().to_string();
+    }
+    let path = if path.len() > 1 && path.ends_with("/") {
+        path[0..path.len() - 1].to_string()
+    } else {
+        path
+    };
+    if let (Some(client_path), Some(client_assets)) = (&conf.client_path, client_assets) {
+        // the client path itself or files under it, but not `/clientfoo'
+        let client_path = client_path.trim_end_matches("/");
+        let name = if r.method() != Method::GET {
+            None
+        } else if path == client_path {
+            Some("")
+        } else {
+            path.strip_prefix(client_path).and_then(|name| name.strip_prefix("/"))
+        };
+        if let Some(name) = name {
+            if name.is_empty() {
+                log_unhandled(start, r.method().as_str(), &path, 200);
+                return HttpResponse::Ok().json(json!({"files": client_assets.file_names()}));
             }
-            let model_url_segment_name = path_components[0];
-            let action_segment_name = path_components[2];
-            let action = Action::handler_from_name(action_segment_name);
-            let action = match action {
-                Some(a) => a,
-                None => {
-                    log_unhandled(start, r.method().as_str(), &path, 404);
-                    return Error::destination_not_found().into();
+            return match client_assets.file(name) {
+                Some(content) => {
+                    log_unhandled(start, r.method().as_str(), &path, 200);
+                    HttpResponse::Ok().content_type(ClientAssets::content_type(name)).body(content.to_owned())
                 }
-            };
-            let model_def = match graph.model_with_url_segment_name(model_url_segment_name) {
-                Some(name) => name,
                 None => {
                     log_unhandled(start, r.method().as_str(), &path, 404);
-                    return Error::destination_not_found().into();
-                }
-            };
-            if !model_def.has_action(action) {
-                log_unhandled(start, r.method().as_str(), &path, 400);
-                return Error::destination_not_found().into();
-            }
-            if r.method() == Method::OPTIONS {
-                return HttpResponse::Ok().json(json!({}));
-            }
-            // read body
-            let mut body = web::BytesMut::new();
-            while let Some(chunk) = payload.next().await {
-                let chunk = chunk.unwrap();
-                // limit max size of in-memory payload
-                if (body.len() + chunk.len()) > 262_144usize {
-                    return HttpResponse::InternalServerError()
-                        .json(json!({"error": Error::internal_server_error("Memory overflow.".to_string())}));
-                }
-                body.extend_from_slice(&chunk);
-            }
-            let parsed_body: Result<JsonValue, serde_json::Error> = serde_json::from_slice(&body);
-            let parsed_body = match parsed_body {
-                Ok(b) => b,
-                Err(_) => {
-                    log_unhandled(start, r.method().as_str(), &path, 400);
-                    return HttpResponse::BadRequest().json(json!({"error": Error::incorrect_json_format()}));
+                    Error::destination_not_found().into()
                 }
             };
+        }
+    }
+    if (r.method() != Method::POST) && (r.method() != Method::OPTIONS) {
+        log_unhandled(start, r.method().as_str(), &path, 404);
+        return Error::destination_not_found().into();
+    }
+    if path == "/__schema" {
+        if !conf.introspection {
+            log_unhandled(start, r.method().as_str(), &path, 404);
+            return Error::destination_not_found().into();
+        }
+        if let Err(err) = check_admin(&r, &graph, conf).await {
+            log_unhandled(start, r.method().as_str(), &path, 401);
+            return err.into();
+        }
+        log_unhandled(start, r.method().as_str(), &path, 200);
+        return HttpResponse::Ok().json(introspect(&graph));
+    }
+    if path == "/__metrics" {
+        if !conf.metrics {
+            log_unhandled(start, r.method().as_str(), &path, 404);
+            return Error::destination_not_found().into();
+        }
+        if let Err(err) = check_admin(&r, &graph, conf).await {
+            log_unhandled(start, r.method().as_str(), &path, 401);
+            return err.into();
+        }
+        log_unhandled(start, r.method().as_str(), &path, 200);
+        let connectors: serde_json::Map<String, JsonValue> = graph.named_connectors().iter().map(|(name, connector)| {
+            (name.clone(), json!({"pool": connector.pool_metrics().to_json()}))
+        }).collect();
+        return HttpResponse::Ok().json(json!({"pool": graph.connector().pool_metrics().to_json(), "connectors": connectors}));
+    }
+    let path_components = path_components(&path);
+    let len = path_components.len();
+    if !(len >= 3 && path_components[len - 2] == "action") {
+        log_unhandled(start, r.method().as_str(), &path, 404);
+        return Error::destination_not_found().into();
+    }
+    let model_path_components = &path_components[0..len - 2];
+    let action_segment_name = path_components[len - 1];
+    let action = Action::handler_from_name_with_aliases(action_segment_name, &conf.action_aliases);
+    let action = match action {
+        Some(a) => a,
+        None => {
+            log_unhandled(start, r.method().as_str(), &path, 404);
+            return Error::destination_not_found().into();
+        }
+    };
+    let model_def = match graph.model_with_route(model_path_components) {
+        Some(name) => name,
+        None => {
+            log_unhandled(start, r.method().as_str(), &path, 404);
+            return Error::destination_not_found().into();
+        }
+    };
+    if !model_def.has_action(action) {
+        log_unhandled(start, r.method().as_str(), &path, 400);
+        return Error::destination_not_found().into();
+    }
+    if r.method() == Method::OPTIONS {
+        return HttpResponse::Ok().json(json!({}));
+    }
+    if let Some(rate_limiter) = rate_limiter {
+        if !rate_limiter.acquire(&rate_limit_identity(&r, conf), Instant::now()) {
+            log_request(start, action.as_handler_str(), model_def.name(), 429);
+            let rate_limit = rate_limiter.rate_limit();
+            return Error::too_many_requests(format!("Rate limit of {} requests per {} seconds exceeded.", rate_limit.requests, rate_limit.window.as_secs())).into();
+        }
+    }
+    // read body
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.unwrap();
+        // limit max size of in-memory payload
+        if (body.len() + chunk.len()) > 262_144usize {
+            return HttpResponse::InternalServerError()
+                .json(json!({"error": Error::internal_server_error("Memory overflow.".to_string())}));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let parsed_body: Result<JsonValue, serde_json::Error> = serde_json::from_slice(&body);
+    let parsed_body = match parsed_body {
+        Ok(b) => b,
+        Err(_) => {
+            log_unhandled(start, r.method().as_str(), &path, 400);
+            return HttpResponse::BadRequest().json(json!({"error": Error::incorrect_json_format()}));
+        }
+    };
 
-            if !parsed_body.is_object() {
-                log_unhandled(start, r.method().as_str(), &path, 400);
-                return HttpResponse::BadRequest().json(json!({"error": Error::unexpected_input_root_type("object")}));
-            }
-            let identity = match get_identity(&r, &graph, conf).await {
-                Ok(identity) => { identity },
-                Err(err) => return HttpResponse::Unauthorized().json(json!({"error": err }))
-            };
+    if !parsed_body.is_object() {
+        log_unhandled(start, r.method().as_str(), &path, 400);
+        return HttpResponse::BadRequest().json(json!({"error": Error::unexpected_input_root_type("object")}));
+    }
+    let identity = match get_identity(&r, &graph, conf).await {
+        Ok(identity) => { identity },
+        Err(err) => return HttpResponse::Unauthorized().json(json!({"error": err }))
+    };
+    let identity_key = quota_identity(&r, conf, identity.as_ref());
+    if let Err(err) = model_def.check_action_quota(action, &identity_key) {
+        log_request(start, action.as_handler_str(), model_def.name(), 429);
+        return err.into();
+    }
 
-            let parsed_body = match Decoder::decode_action_arg(model_def, graph, action, &parsed_body) {
-                Ok(body) => body,
-                Err(err) => return err.into()
-            };
-            let (transformed_body, transformed_action) = if model_def.has_action_transformers() || parsed_body.as_hashmap().unwrap().get("include").is_some() {
-                if ((action.to_u32() == CREATE_MANY_HANDLER) || (action.to_u32() == CREATE_HANDLER)) && (parsed_body.get("create").unwrap().is_vec()) {
-                    // create with many items
-                    let entries = parsed_body.get("create").unwrap().as_vec().unwrap();
-                    let mut transformed_entries: Vec<Value> = vec![];
-                    let mut new_action = action;
-                    for (_index, entry) in entries.iter().enumerate() {
-                        let ctx = Ctx::initial_state_with_value(teon!({"create": entry})).with_action(action);
-                        match model_def.transformed_action(ctx).await {
-                            Ok(result) => {
-                                transformed_entries.push(result.0.get("create").unwrap().clone());
-                                new_action = result.1;
-                            },
-                            Err(err) => return err.into(),
-                        }
-                    }
-                    let mut new_val = parsed_body.clone();
-                    new_val.as_hashmap_mut().unwrap().insert("create".to_owned(), Value::Vec(transformed_entries));
-                    (new_val, new_action)
-                } else {
-                    let ctx = Ctx::initial_state_with_value(parsed_body).with_action(action);
-                    match model_def.transformed_action(ctx).await {
-                        Ok(result) => result,
-                        Err(err) => return err.into(),
-                    }
-                }
-            } else {
-                (parsed_body, action)
-            };
-            let source = ActionSource::Identity(identity);
-            match transformed_action.to_u32() {
-                FIND_UNIQUE_HANDLER => {
-                    let result = handle_find_unique(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    return result;
-                }
-                FIND_FIRST_HANDLER => {
-                    let result = handle_find_first(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                FIND_MANY_HANDLER => {
-                    let result = handle_find_many(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                CREATE_HANDLER => {
-                    let result = handle_create(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                UPDATE_HANDLER => {
-                    let result = handle_update(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                UPSERT_HANDLER => {
-                    let result = handle_upsert(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                DELETE_HANDLER => {
-                    let result = handle_delete(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                CREATE_MANY_HANDLER => {
-                    let result = handle_create_many(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                UPDATE_MANY_HANDLER => {
-                    let result = handle_update_many(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                DELETE_MANY_HANDLER => {
-                    let result = handle_delete_many(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                COUNT_HANDLER => {
-                    let result = handle_count(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                AGGREGATE_HANDLER => {
-                    let result = handle_aggregate(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                GROUP_BY_HANDLER => {
-                    let result = handle_group_by(&graph, &transformed_body, model_def, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                SIGN_IN_HANDLER => {
-                    let result = handle_sign_in(&graph, &transformed_body, model_def, conf).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
-                }
-                IDENTITY_HANDLER => {
-                    let result = handle_identity(&graph, &transformed_body, model_def, conf, source.clone()).await;
-                    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
-                    result
+    let parsed_body = if conf.lenient_input_keys {
+        Decoder::retain_allowed_action_arg_keys(action, &parsed_body)
+    } else {
+        parsed_body
+    };
+    let parsed_body = match Decoder::decode_action_arg(model_def, graph, action, &parsed_body) {
+        Ok(body) => body,
+        Err(err) => return api_error(graph, model_def, err).into()
+    };
+    let parsed_body = if action.handler_allowed_input_json_keys().contains("select") {
+        model_def.input_with_default_select(&parsed_body).into_owned()
+    } else {
+        parsed_body
+    };
+    let (transformed_body, transformed_action) = if model_def.has_action_transformers() || parsed_body.as_hashmap().unwrap().get("include").is_some() {
+        if ((action.to_u32() == CREATE_MANY_HANDLER) || (action.to_u32() == CREATE_HANDLER)) && (parsed_body.get("create").unwrap().is_vec()) {
+            // create with many items
+            let entries = parsed_body.get("create").unwrap().as_vec().unwrap();
+            let mut transformed_entries: Vec<Value> = vec![];
+            let mut new_action = action;
+            for (_index, entry) in entries.iter().enumerate() {
+                let ctx = Ctx::initial_state_with_value(teon!({"create": entry})).with_action(action);
+                match model_def.transformed_action(ctx).await {
+                    Ok(result) => {
+                        transformed_entries.push(result.0.get("create").unwrap().clone());
+                        new_action = result.1;
+                    },
+                    Err(err) => return err.into(),
                 }
-                _ => unreachable!()
             }
-        }));
-    app
+            let mut new_val = parsed_body.clone();
+            new_val.as_hashmap_mut().unwrap().insert("create".to_owned(), Value::Vec(transformed_entries));
+            (new_val, new_action)
+        } else {
+            let ctx = Ctx::initial_state_with_value(parsed_body).with_action(action);
+            match model_def.transformed_action(ctx).await {
+                Ok(result) => result,
+                Err(err) => return err.into(),
+            }
+        }
+    } else {
+        (parsed_body, action)
+    };
+    let source = ActionSource::Identity(identity);
+    let result = match transformed_action.to_u32() {
+        FIND_UNIQUE_HANDLER => handle_find_unique(&graph, &transformed_body, model_def, source.clone()).await,
+        FIND_FIRST_HANDLER => handle_find_first(&graph, &transformed_body, model_def, source.clone()).await,
+        FIND_MANY_HANDLER => handle_find_many(&graph, &transformed_body, model_def, source.clone()).await,
+        CREATE_HANDLER => handle_create(&graph, &transformed_body, model_def, source.clone()).await,
+        UPDATE_HANDLER => handle_update(&graph, &transformed_body, model_def, source.clone()).await,
+        UPSERT_HANDLER => handle_upsert(&graph, &transformed_body, model_def, source.clone()).await,
+        DELETE_HANDLER => handle_delete(&graph, &transformed_body, model_def, source.clone()).await,
+        CREATE_MANY_HANDLER => handle_create_many(&graph, &transformed_body, model_def, source.clone()).await,
+        UPDATE_MANY_HANDLER => handle_update_many(&graph, &transformed_body, model_def, source.clone()).await,
+        DELETE_MANY_HANDLER => handle_delete_many(&graph, &transformed_body, model_def, source.clone()).await,
+        COUNT_HANDLER => handle_count(&graph, &transformed_body, model_def, source.clone()).await,
+        AGGREGATE_HANDLER => handle_aggregate(&graph, &transformed_body, model_def, source.clone()).await,
+        GROUP_BY_HANDLER => handle_group_by(&graph, &transformed_body, model_def, source.clone()).await,
+        FACETS_HANDLER => handle_facets(&graph, &transformed_body, model_def, source.clone()).await,
+        SIGN_IN_HANDLER => handle_sign_in(&graph, &transformed_body, model_def, conf).await,
+        IDENTITY_HANDLER => handle_identity(&graph, &transformed_body, model_def, conf, source.clone()).await,
+        _ => unreachable!()
+    };
+    log_request(start, action.as_handler_str(), model_def.name(), result.status().as_u16());
+    if !result.status().is_success() {
+        log_input(graph, model_def, &transformed_body);
+    }
+    result
 }
 
+
 async fn server_start_message(port: u16, environment_version: EnvironmentVersion, entrance: Entrance) -> Result<(), std::io::Error> {
     // Introducing
     let now: DateTime<Local> = Local::now();
@@ -830,17 +1031,23 @@ async fn server_start_message(port: u16, environment_version: EnvironmentVersion
 pub(crate) async fn serve(
     graph: Graph,
     conf: ServerConf,
+    client_generator_confs: Vec<ClientGeneratorConf>,
     environment_version: EnvironmentVersion,
     entrance: Entrance,
     no_migration: bool,
 ) -> Result<(), std::io::Error> {
     if !no_migration {
-        migrate(graph.to_mut(), false).await;
+        migrate(&graph, false).await.map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
     }
+    let client_assets = if conf.client_path.is_some() {
+        Some(ClientAssets::generate(&graph, &conf, &client_generator_confs).await?)
+    } else {
+        None
+    };
     let bind = conf.bind.clone();
     let port = bind.1;
     let server = HttpServer::new(move || {
-        make_app(graph.clone(), conf.clone())
+        make_app_with_client_assets(graph.clone(), conf.clone(), client_assets.clone())
     })
         .bind(bind)
         .unwrap()
@@ -848,3 +1055,393 @@ pub(crate) async fn serve(
     let result = future::join(server, server_start_message(port, environment_version, entrance)).await;
     result.0
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::core::field::Field;
+    use crate::core::field::optionality::Optionality;
+    use crate::core::field::r#type::FieldType;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::test_connector::TestConnector;
+    use crate::core::test_fixtures::id_field;
+    use crate::parser::ast::config::{Cors, DateTimeFormat, FieldNaming, RateLimit};
+    use super::*;
+
+    fn conf(rate_limit: Option<RateLimit>) -> ServerConf {
+        ServerConf {
+            bind: ("127.0.0.1".to_owned(), 5100),
+            jwt_secret: Some("secret".to_owned()),
+            path_prefix: None,
+            client_path: None,
+            client_provider: None,
+            lenient_input_keys: false,
+            introspection: false,
+            metrics: false,
+            admin: None,
+            date_time_format: DateTimeFormat::RFC3339,
+            action_aliases: HashMap::new(),
+            cors: Cors::default(),
+            cors_routes: vec![],
+            rate_limit,
+            trust_proxy: false,
+        }
+    }
+
+    async fn posts(connector: Arc<TestConnector>) -> Graph {
+        let mut builder = GraphBuilder::new();
+        builder.model("Post", |m| {
+            let mut likes = Field::new("likes".to_owned());
+            likes.field_type = Some(FieldType::I32);
+            let mut score = Field::new("score".to_owned());
+            score.field_type = Some(FieldType::I32);
+            score.write_rule = WriteRule::NoWrite;
+            m.field(id_field()).field(likes).field(score);
+        });
+        builder.build(connector).await
+    }
+
+    #[tokio::test]
+    async fn atomic_update_many_respects_write_rules() {
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Post".to_owned(), vec![teon!({"id": 1, "likes": 1, "score": 1}), teon!({"id": 2, "likes": 2, "score": 2})])]),
+            ..Default::default()
+        });
+        let graph = posts(connector.clone()).await;
+        let model = graph.model("Post").unwrap();
+        let input = teon!({"where": {"id": {"gt": 0}}, "update": {"likes": {"increment": 1}}});
+        let response = handle_update_many(&graph, &input, model, ActionSource::ProgramCode).await;
+        assert_eq!(response.status(), 200);
+        assert!(connector.operations().contains(&"updateMany Post".to_owned()));
+        assert_eq!(connector.writes.lock().unwrap().last().unwrap().get("likes"), Some(&teon!({"increment": 1})));
+        let input = teon!({"where": {"id": {"gt": 0}}, "update": {"score": {"increment": 1}}});
+        let writes = connector.writes.lock().unwrap().len();
+        let response = handle_update_many(&graph, &input, model, ActionSource::ProgramCode).await;
+        assert_eq!(response.status(), 200);
+        // the readonly field isn't updated on any record
+        assert_eq!(connector.writes.lock().unwrap().len(), writes);
+    }
+
+    #[tokio::test]
+    async fn find_many_counts_records_unless_meta_is_skipped() {
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Post".to_owned(), vec![teon!({"id": 1, "likes": 1, "score": 1}), teon!({"id": 2, "likes": 2, "score": 2})])]),
+            ..Default::default()
+        });
+        let graph = posts(connector.clone()).await;
+        let model = graph.model("Post").unwrap();
+        let body = |response: HttpResponse| async move {
+            serde_json::from_slice::<JsonValue>(&actix_web::body::to_bytes(response.into_body()).await.unwrap()).unwrap()
+        };
+        let response = handle_find_many(&graph, &teon!({"_withMeta": false}), model, ActionSource::ProgramCode).await;
+        let json = body(response).await;
+        assert!(json.get("meta").is_none());
+        assert_eq!(json["data"].as_array().unwrap().len(), 2);
+        assert!(!connector.operations().contains(&"count Post".to_owned()));
+        let response = handle_find_many(&graph, &teon!({}), model, ActionSource::ProgramCode).await;
+        let json = body(response).await;
+        assert_eq!(json["meta"], json!({"count": 2}));
+        assert!(connector.operations().contains(&"count Post".to_owned()));
+        let response = handle_find_many(&graph, &teon!({"pageSize": 1, "pageNumber": 2}), model, ActionSource::ProgramCode).await;
+        let json = body(response).await;
+        assert_eq!(json["meta"], json!({"count": 2, "pageSize": 1, "pageNumber": 2, "numberOfPages": 2}));
+    }
+
+    #[actix_web::test]
+    async fn sign_in_is_rate_limited_per_peer_address() {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| { m.identity().field(id_field()); });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let rate_limit = RateLimit { requests: 1, window: std::time::Duration::from_secs(60) };
+        let app = actix_web::test::init_service(make_app(graph, conf(Some(rate_limit)))).await;
+        let sign_in = |peer: &str| actix_web::test::TestRequest::post()
+            .uri("/users/action/signIn")
+            .peer_addr(peer.parse().unwrap())
+            // forwarding headers aren't trusted without a proxy
+            .insert_header(("X-Forwarded-For", "203.0.113.7"))
+            .set_json(json!({"credentials": {"id": 1}}))
+            .to_request();
+        let response = actix_web::test::call_service(&app, sign_in("10.0.0.1:5000")).await;
+        assert_ne!(response.status(), 429);
+        let response = actix_web::test::call_service(&app, sign_in("10.0.0.1:5001")).await;
+        assert_eq!(response.status(), 429);
+        let response = actix_web::test::call_service(&app, sign_in("10.0.0.2:5000")).await;
+        assert_ne!(response.status(), 429);
+    }
+
+    #[actix_web::test]
+    async fn guest_quotas_are_counted_against_the_peer_address() {
+        let mut builder = GraphBuilder::new();
+        builder.model("Post", |m| {
+            m.action_quota(Action::from_u32(FIND_MANY_HANDLER), 1, std::time::Duration::from_secs(60)).field(id_field());
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let app = actix_web::test::init_service(make_app(graph, conf(None))).await;
+        let find_many = |forwarded_for: &str| actix_web::test::TestRequest::post()
+            .uri("/posts/action/findMany")
+            .peer_addr("10.0.0.1:5000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", forwarded_for))
+            .set_json(json!({}))
+            .to_request();
+        let response = actix_web::test::call_service(&app, find_many("203.0.113.7")).await;
+        assert_eq!(response.status(), 200);
+        // a faked forwarding header doesn't reset the quota
+        let response = actix_web::test::call_service(&app, find_many("203.0.113.8")).await;
+        assert_eq!(response.status(), 429);
+    }
+
+    fn post(uri: &str, body: JsonValue) -> actix_http::Request {
+        actix_web::test::TestRequest::post().uri(uri).set_json(body).to_request()
+    }
+
+    #[actix_web::test]
+    async fn client_files_are_only_served_under_the_client_path() {
+        let graph = posts(Arc::new(TestConnector::default())).await;
+        let mut conf = conf(None);
+        conf.client_path = Some("/client".to_owned());
+        let client_assets = ClientAssets::new(HashMap::from([("index.ts".to_owned(), "export {}".to_owned())]));
+        let app = actix_web::test::init_service(make_app_with_client_assets(graph, conf, Some(client_assets))).await;
+        let get = |uri: &str| actix_web::test::TestRequest::get().uri(uri).to_request();
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, get("/client")).await;
+        assert_eq!(json, json!({"files": ["index.ts"]}));
+        let response = actix_web::test::call_service(&app, get("/client/index.ts")).await;
+        assert_eq!(response.status(), 200);
+        let response = actix_web::test::call_service(&app, get("/clientindex.ts")).await;
+        assert_eq!(response.status(), 404);
+    }
+
+    #[actix_web::test]
+    async fn action_aliases_are_routed_to_their_actions() {
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Post".to_owned(), vec![teon!({"id": 1, "likes": 1, "score": 1})])]),
+            ..Default::default()
+        });
+        let graph = posts(connector).await;
+        let mut conf = conf(None);
+        conf.action_aliases = HashMap::from([("list".to_owned(), Action::from_u32(FIND_MANY_HANDLER))]);
+        let app = actix_web::test::init_service(make_app(graph, conf)).await;
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/posts/action/list", json!({}))).await;
+        assert_eq!(json["data"], json!([{"id": 1, "likes": 1, "score": 1}]));
+        let response = actix_web::test::call_service(&app, post("/posts/action/browse", json!({}))).await;
+        assert_eq!(response.status(), 404);
+    }
+
+    #[actix_web::test]
+    async fn responses_carry_the_cors_headers_of_their_route() {
+        let graph = posts(Arc::new(TestConnector::default())).await;
+        let mut conf = conf(None);
+        conf.cors.allow_origin("https://app.example.com").allow_methods(["POST"]);
+        let mut webhooks = conf.cors.clone();
+        webhooks.allow_origins = vec!["https://hooks.example.com".to_owned()];
+        conf.cors_routes = vec![("/webhooks".to_owned(), webhooks)];
+        let app = actix_web::test::init_service(make_app(graph, conf)).await;
+        let request = |method: Method, uri: &str, origin: &str| actix_web::test::TestRequest::default()
+            .method(method)
+            .uri(uri)
+            .insert_header(("Origin", origin))
+            .set_json(json!({}))
+            .to_request();
+        let response = actix_web::test::call_service(&app, request(Method::OPTIONS, "/posts/action/findMany", "https://app.example.com")).await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers().get("Access-Control-Allow-Origin").unwrap(), "https://app.example.com");
+        assert_eq!(response.headers().get("Access-Control-Allow-Methods").unwrap(), "POST");
+        let response = actix_web::test::call_service(&app, request(Method::POST, "/posts/action/findMany", "https://evil.example.com")).await;
+        assert!(response.headers().get("Access-Control-Allow-Origin").is_none());
+        let response = actix_web::test::call_service(&app, request(Method::POST, "/webhooks/stripe", "https://hooks.example.com")).await;
+        assert_eq!(response.headers().get("Access-Control-Allow-Origin").unwrap(), "https://hooks.example.com");
+        assert_eq!(response.headers().get("Access-Control-Allow-Methods").unwrap(), "POST");
+    }
+
+    #[actix_web::test]
+    async fn preflights_are_cached_for_the_max_age_of_their_route() {
+        let graph = posts(Arc::new(TestConnector::default())).await;
+        let mut conf = conf(None);
+        conf.cors.allow_origin("*").max_age(Some(600));
+        let mut webhooks = conf.cors.clone();
+        webhooks.max_age(None);
+        conf.cors_routes = vec![("/webhooks".to_owned(), webhooks)];
+        let app = actix_web::test::init_service(make_app(graph, conf)).await;
+        let preflight = |uri: &str| actix_web::test::TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri(uri)
+            .insert_header(("Origin", "https://app.example.com"))
+            .to_request();
+        let response = actix_web::test::call_service(&app, preflight("/posts/action/findMany")).await;
+        assert_eq!(response.headers().get("Access-Control-Max-Age").unwrap(), "600");
+        let response = actix_web::test::call_service(&app, preflight("/webhooks/stripe")).await;
+        assert!(response.headers().get("Access-Control-Max-Age").is_none());
+        // a route only covers its own path segments
+        let response = actix_web::test::call_service(&app, preflight("/webhooksLog/action/findMany")).await;
+        assert_eq!(response.headers().get("Access-Control-Max-Age").unwrap(), "600");
+    }
+
+    /// A graph with an admin and a user identity model, a served model and an
+    /// internal model.
+    async fn admin_graph(connector: Arc<TestConnector>) -> Graph {
+        let mut builder = GraphBuilder::new();
+        builder.model("Admin", |m| { m.identity().field(id_field()); });
+        builder.model("User", |m| { m.identity().field(id_field()); });
+        builder.model("Post", |m| { m.field(id_field()); });
+        builder.model("Secret", |m| { m.internal().field(id_field()); });
+        builder.build(connector).await
+    }
+
+    fn authorized_post(uri: &str, model: &str) -> actix_http::Request {
+        let claims = Claims { id: json!({"id": 1}), model: model.to_owned(), exp: 4_102_444_800 };
+        actix_web::test::TestRequest::post()
+            .uri(uri)
+            .insert_header(("Authorization", format!("Bearer {}", encode_token(claims, "secret"))))
+            .set_json(json!({}))
+            .to_request()
+    }
+
+    #[actix_web::test]
+    async fn only_admins_read_the_schema_without_internal_models() {
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Admin".to_owned(), vec![teon!({"id": 1})]), ("User".to_owned(), vec![teon!({"id": 1})])]),
+            ..Default::default()
+        });
+        let graph = admin_graph(connector).await;
+        let mut conf = conf(None);
+        conf.introspection = true;
+        conf.admin = Some("Admin".to_owned());
+        let app = actix_web::test::init_service(make_app(graph, conf)).await;
+        let response = actix_web::test::call_service(&app, post("/__schema", json!({}))).await;
+        assert_eq!(response.status(), 401);
+        let response = actix_web::test::call_service(&app, authorized_post("/__schema", "User")).await;
+        assert_eq!(response.status(), 401);
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, authorized_post("/__schema", "Admin")).await;
+        let models: HashSet<&str> = json["models"].as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        assert_eq!(models, HashSet::from(["Admin", "User", "Post"]));
+    }
+
+    #[actix_web::test]
+    async fn only_admins_read_the_metrics() {
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Admin".to_owned(), vec![teon!({"id": 1})]), ("User".to_owned(), vec![teon!({"id": 1})])]),
+            ..Default::default()
+        });
+        connector.pool_metrics.record_checked_out(std::time::Duration::ZERO);
+        let graph = admin_graph(connector).await;
+        let mut conf = conf(None);
+        conf.metrics = true;
+        conf.admin = Some("Admin".to_owned());
+        let app = actix_web::test::init_service(make_app(graph, conf)).await;
+        let response = actix_web::test::call_service(&app, post("/__metrics", json!({}))).await;
+        assert_eq!(response.status(), 401);
+        let response = actix_web::test::call_service(&app, authorized_post("/__metrics", "User")).await;
+        assert_eq!(response.status(), 401);
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, authorized_post("/__metrics", "Admin")).await;
+        assert_eq!(json["pool"]["active"], json!(1));
+        assert_eq!(json["pool"]["idle"], JsonValue::Null);
+    }
+
+    #[actix_web::test]
+    async fn api_names_are_used_in_requests_and_responses() {
+        let connector = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut user_name = Field::new("userName".to_owned());
+            user_name.field_type = Some(FieldType::String);
+            user_name.api_name = Some("name".to_owned());
+            m.field(id_field()).field(user_name);
+        });
+        let graph = builder.build(connector.clone()).await;
+        let app = actix_web::test::init_service(make_app(graph, conf(None))).await;
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/users/action/create", json!({"create": {"id": 1, "name": "Ann"}}))).await;
+        assert_eq!(json, json!({"data": {"id": 1, "name": "Ann"}}));
+        assert_eq!(connector.writes.lock().unwrap().last().unwrap().get("userName"), Some(&teon!("Ann")));
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/users/action/findMany", json!({"where": {"name": {"equals": "Ann"}}, "orderBy": [{"name": "asc"}]}))).await;
+        assert_eq!(json["data"], json!([{"id": 1, "name": "Ann"}]));
+        let response = actix_web::test::call_service(&app, post("/users/action/create", json!({"create": {"id": 2, "userName": "Bob"}}))).await;
+        assert_eq!(response.status(), 400);
+    }
+
+    #[actix_web::test]
+    async fn snake_case_names_round_trip_camel_case_schema_keys() {
+        let connector = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.field_naming = FieldNaming::SnakeCase;
+        builder.model("User", |m| {
+            let mut first_name = Field::new("firstName".to_owned());
+            first_name.field_type = Some(FieldType::String);
+            let mut preferences = Field::new("preferences".to_owned());
+            preferences.field_type = Some(FieldType::Json);
+            preferences.optionality = Optionality::Optional;
+            m.field(id_field()).field(first_name).field(preferences);
+        });
+        let graph = builder.build(connector.clone()).await;
+        let app = actix_web::test::init_service(make_app(graph, conf(None))).await;
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/users/action/create", json!({"create": {"id": 1, "first_name": "Ann", "preferences": {"first_name": "kept"}}}))).await;
+        assert_eq!(json, json!({"data": {"id": 1, "first_name": "Ann", "preferences": {"first_name": "kept"}}}));
+        let write = connector.writes.lock().unwrap().last().unwrap().clone();
+        assert_eq!(write.get("firstName"), Some(&teon!("Ann")));
+        assert_eq!(write.get("preferences"), Some(&teon!({"first_name": "kept"})));
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/users/action/findMany", json!({"where": {"first_name": {"equals": "Ann"}}, "orderBy": [{"first_name": "asc"}]}))).await;
+        assert_eq!(json["data"], json!([{"id": 1, "first_name": "Ann", "preferences": {"first_name": "kept"}}]));
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/users/action/create", json!({"create": {"id": 2}}))).await;
+        assert_eq!(json["error"]["errors"], json!({"create.first_name": "value is required"}));
+    }
+
+    #[actix_web::test]
+    async fn camel_case_names_round_trip_snake_case_schema_keys() {
+        let connector = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.field_naming = FieldNaming::CamelCase;
+        builder.model("User", |m| {
+            let mut first_name = Field::new("first_name".to_owned());
+            first_name.field_type = Some(FieldType::String);
+            m.field(id_field()).field(first_name);
+        });
+        let graph = builder.build(connector.clone()).await;
+        let app = actix_web::test::init_service(make_app(graph, conf(None))).await;
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/users/action/create", json!({"create": {"id": 1, "firstName": "Ann"}}))).await;
+        assert_eq!(json, json!({"data": {"id": 1, "firstName": "Ann"}}));
+        assert_eq!(connector.writes.lock().unwrap().last().unwrap().get("first_name"), Some(&teon!("Ann")));
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/users/action/findMany", json!({"where": {"firstName": {"equals": "Ann"}}}))).await;
+        assert_eq!(json["data"], json!([{"id": 1, "firstName": "Ann"}]));
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/users/action/create", json!({"create": {"id": 2}}))).await;
+        assert_eq!(json["error"]["errors"], json!({"create.firstName": "value is required"}));
+    }
+
+    #[actix_web::test]
+    async fn models_with_a_custom_route_are_served_only_under_it() {
+        let mut builder = GraphBuilder::new();
+        builder.model("Post", |m| { m.route("/v1/users/:userId/posts").field(id_field()); });
+        builder.model("Comment", |m| { m.field(id_field()); });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let app = actix_web::test::init_service(make_app(graph, conf(None))).await;
+        let response = actix_web::test::call_service(&app, post("/v1/users/5/posts/action/findMany", json!({}))).await;
+        assert_eq!(response.status(), 200);
+        let response = actix_web::test::call_service(&app, post("/v1/users/abc/posts/action/findMany", json!({}))).await;
+        assert_eq!(response.status(), 200);
+        let response = actix_web::test::call_service(&app, post("/v1/users/posts/action/findMany", json!({}))).await;
+        assert_eq!(response.status(), 404);
+        let response = actix_web::test::call_service(&app, post("/posts/action/findMany", json!({}))).await;
+        assert_eq!(response.status(), 404);
+        let response = actix_web::test::call_service(&app, post("/comments/action/findMany", json!({}))).await;
+        assert_eq!(response.status(), 200);
+    }
+
+    #[actix_web::test]
+    async fn default_select_applies_only_without_an_explicit_select() {
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Post".to_owned(), vec![teon!({"id": 1, "likes": 2, "score": 3})])]),
+            ..Default::default()
+        });
+        let mut builder = GraphBuilder::new();
+        builder.model("Post", |m| {
+            let mut likes = Field::new("likes".to_owned());
+            likes.field_type = Some(FieldType::I32);
+            let mut score = Field::new("score".to_owned());
+            score.field_type = Some(FieldType::I32);
+            m.default_select(["id", "likes"]).field(id_field()).field(likes).field(score);
+        });
+        let graph = builder.build(connector).await;
+        let app = actix_web::test::init_service(make_app(graph, conf(None))).await;
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/posts/action/findMany", json!({}))).await;
+        assert_eq!(json["data"], json!([{"id": 1, "likes": 2}]));
+        let json: JsonValue = actix_web::test::call_and_read_body_json(&app, post("/posts/action/findMany", json!({"select": {"id": true, "score": true}}))).await;
+        assert_eq!(json["data"], json!([{"id": 1, "score": 3}]));
+    }
+}
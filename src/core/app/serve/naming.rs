@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+use crate::core::error::Error;
+use crate::core::graph::Graph;
+use crate::core::model::Model;
+
+/// Names the model keys in the key paths of an error's field map as they're
+/// named in JSON. Components are looked up in `model`, and relations lead
+/// into their models. Other components, action keys, operators and indices,
+/// are kept.
+pub(crate) fn api_error(graph: &Graph, model: &Model, mut err: Error) -> Error {
+    if let Some(errors) = err.errors.take() {
+        err.errors = Some(errors.into_iter().map(|(key_path, message)| {
+            (api_key_path(graph, model, &key_path), message)
+        }).collect::<HashMap<String, String>>());
+    }
+    err
+}
+
+fn api_key_path(graph: &Graph, model: &Model, key_path: &str) -> String {
+    let naming = graph.field_naming();
+    let mut current = Some(model);
+    let mut components: Vec<String> = vec![];
+    for component in key_path.split('.') {
+        let model = match current {
+            Some(model) => model,
+            None => {
+                components.push(component.to_owned());
+                continue
+            }
+        };
+        if let Some(field) = model.field(component) {
+            components.push(naming.api_key(field.api_name()).into_owned());
+            current = None;
+        } else if let Some(relation) = model.relation(component) {
+            components.push(naming.api_key(relation.name()).into_owned());
+            current = graph.model(relation.model());
+        } else if let Some(property) = model.property(component) {
+            components.push(naming.api_key(property.name()).into_owned());
+            current = None;
+        } else {
+            components.push(component.to_owned());
+        }
+    }
+    components.join(".")
+}
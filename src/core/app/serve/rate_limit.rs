@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+use crate::parser::ast::config::RateLimit;
+
+/// A token bucket per identity. Each bucket holds up to the limit's requests
+/// and refills evenly over its window.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    rate_limit: RateLimit,
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    tokens: HashMap<String, (f64, Instant)>,
+    swept: Instant,
+}
+
+impl RateLimiter {
+
+    pub(crate) fn new(rate_limit: RateLimit) -> Self {
+        Self { rate_limit, buckets: Mutex::new(Buckets { tokens: HashMap::new(), swept: Instant::now() }) }
+    }
+
+    pub(crate) fn rate_limit(&self) -> &RateLimit {
+        &self.rate_limit
+    }
+
+    /// Takes a token from `identity`'s bucket at `now`. Returns false if the
+    /// bucket is empty.
+    pub(crate) fn acquire(&self, identity: &str, now: Instant) -> bool {
+        let capacity = self.rate_limit.requests as f64;
+        let refill_per_second = capacity / self.rate_limit.window.as_secs_f64();
+        let window = self.rate_limit.window;
+        let mut buckets = self.buckets.lock().unwrap();
+        // buckets idle for a whole window are full again, dropping them is the same
+        if now.saturating_duration_since(buckets.swept) >= window {
+            buckets.tokens.retain(|_, (_, last)| now.saturating_duration_since(*last) < window);
+            buckets.swept = now;
+        }
+        let (tokens, last) = buckets.tokens.entry(identity.to_owned()).or_insert((capacity, now));
+        let elapsed = now.saturating_duration_since(*last).as_secs_f64();
+        *tokens = (*tokens + elapsed * refill_per_second).min(capacity);
+        *last = now;
+        if *tokens < 1.0 {
+            return false;
+        }
+        *tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::*;
+
+    #[test]
+    fn bucket_empties_and_refills_per_identity() {
+        let limiter = RateLimiter::new(RateLimit { requests: 2, window: Duration::from_secs(10) });
+        let now = Instant::now();
+        assert!(limiter.acquire("User:1", now));
+        assert!(limiter.acquire("User:1", now));
+        assert!(!limiter.acquire("User:1", now));
+        assert!(limiter.acquire("127.0.0.1", now));
+        assert!(!limiter.acquire("User:1", now + Duration::from_secs(4)));
+        assert!(limiter.acquire("User:1", now + Duration::from_secs(5)));
+        assert!(!limiter.acquire("User:1", now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn idle_buckets_are_dropped_after_a_window() {
+        let limiter = RateLimiter::new(RateLimit { requests: 2, window: Duration::from_secs(10) });
+        let now = Instant::now();
+        assert!(limiter.acquire("10.0.0.1", now));
+        assert!(limiter.acquire("10.0.0.2", now + Duration::from_secs(5)));
+        assert_eq!(limiter.buckets.lock().unwrap().tokens.len(), 2);
+        assert!(limiter.acquire("10.0.0.3", now + Duration::from_secs(12)));
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(!buckets.tokens.contains_key("10.0.0.1"));
+        assert!(buckets.tokens.contains_key("10.0.0.2"));
+    }
+}
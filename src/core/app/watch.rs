@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Detects modifications of schema files by comparing their modification times.
+pub(crate) struct SchemaWatcher {
+    snapshot: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl SchemaWatcher {
+
+    pub(crate) fn new(files: &Vec<PathBuf>) -> Self {
+        Self { snapshot: files.iter().map(|f| (f.clone(), Self::modified(f))).collect() }
+    }
+
+    fn modified(file: &PathBuf) -> Option<SystemTime> {
+        std::fs::metadata(file).and_then(|m| m.modified()).ok()
+    }
+
+    /// Returns true if any of the watched files has been changed since the last call.
+    pub(crate) fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (file, modified) in self.snapshot.iter_mut() {
+            let current = Self::modified(file);
+            if current != *modified {
+                *modified = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use crate::core::app::builder::CallbackLookupTable;
+    use crate::parser::parser::Parser;
+    use super::*;
+
+    #[test]
+    fn modifying_an_imported_schema_file_is_detected() {
+        let dir = std::env::temp_dir().join(format!("teo-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("schema.teo");
+        let imported = dir.join("imported.teo");
+        std::fs::write(&imported, "").unwrap();
+        std::fs::write(&main, r#"import "./imported"
+
+connector {
+  provider: .mongo,
+  url: "mongodb://127.0.0.1:27017/teo"
+}
+"#).unwrap();
+        let mut parser = Parser::new(Arc::new(Mutex::new(CallbackLookupTable::new())));
+        parser.parse(Some(main.to_str().unwrap()));
+        let mut watcher = SchemaWatcher::new(&parser.source_paths());
+        assert!(!watcher.changed());
+        // the modification time is moved on explicitly, file systems with a
+        // coarse resolution may keep it for a quick write
+        std::fs::write(&imported, "\n").unwrap();
+        let file = std::fs::File::options().write(true).open(&imported).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,9 +1,13 @@
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 use async_trait::async_trait;
+use bigdecimal::BigDecimal;
 use crate::core::action::Action;
 use crate::core::action::source::ActionSource;
+use crate::core::database::pool_metrics::PoolMetrics;
 use crate::core::database::r#type::DatabaseType;
+use crate::core::error::Error;
 use crate::core::field::r#type::FieldType;
 use crate::core::graph::Graph;
 use crate::core::model::Model;
@@ -21,9 +25,22 @@ pub(crate) trait Connector: Send + Sync {
 
     fn default_database_type(&self, field_type: &FieldType) -> DatabaseType;
 
+    /// Whether the database stores this decimal without rounding. Inputs
+    /// which it can't store are rejected when they're decoded.
+    fn decimal_is_storable(&self, _decimal: &BigDecimal) -> bool {
+        true
+    }
+
     // Migration
 
-    async fn migrate(&mut self, models: &Vec<Model>, reset_database: bool) -> Result<()>;
+    async fn migrate(&self, models: &Vec<Model>, reset_database: bool) -> Result<()>;
+
+    /// The statements `migrate` would execute, without changing the database.
+    /// Connectors which can't list their migration return an error, so a dry
+    /// run doesn't look like there's nothing to migrate.
+    async fn migrate_plan(&self, _models: &Vec<Model>) -> Result<Vec<String>> {
+        Err(Error::invalid_operation("This database can't list its migration, a dry run is only supported by SQL databases."))
+    }
 
     // Raw query
 
@@ -35,17 +52,45 @@ pub(crate) trait Connector: Send + Sync {
 
     async fn delete_object(&self, object: &Object, session: Arc<dyn SaveSession>) -> Result<()>;
 
+    async fn update_many_atomically(&self, model: &Model, objects: &Vec<Object>, updators: &HashMap<String, Value>) -> Result<()>;
+
     async fn find_unique(&self, graph: &Graph, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Object>;
 
     async fn find_many(&self, graph: &Graph, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Vec<Object>>;
 
     async fn count(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<usize>;
 
+    /// A page of objects together with the count of all objects the finder
+    /// matches. Connectors which can read both in one query override this.
+    async fn find_many_and_count(&self, graph: &Graph, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<(Vec<Object>, usize)> {
+        let objects = self.find_many(graph, model, finder, mutation_mode, action, action_source).await?;
+        let count = self.count(graph, model, &count_finder(finder)).await?;
+        Ok((objects, count))
+    }
+
     async fn aggregate(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<Value>;
 
     async fn group_by(&self, graph: &Graph, model: &Model, finder: &Value) -> Result<Value>;
 
+    // Sequence
+
+    async fn increment_sequence(&self, name: &str) -> Result<i64>;
+
     // Save session
 
     fn new_save_session(&self) -> Arc<dyn SaveSession>;
+
+    // Metrics
+
+    fn pool_metrics(&self) -> &PoolMetrics;
+}
+
+/// The finder counting every record a page is taken from. The cursor is a
+/// position in the records like skip, so it's removed with the page keys.
+pub(crate) fn count_finder(finder: &Value) -> Value {
+    let mut count_finder = finder.as_hashmap().unwrap().clone();
+    for key in ["skip", "take", "pageSize", "pageNumber", "cursor"] {
+        count_finder.remove(key);
+    }
+    Value::HashMap(count_finder)
 }
@@ -1,2 +1,3 @@
 pub mod r#type;
 pub mod name;
+pub(crate) mod pool_metrics;
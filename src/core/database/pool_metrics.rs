@@ -0,0 +1,139 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use serde_json::{json, Value as JsonValue};
+
+/// Counters describing how a connector's connection pool is used, so that
+/// saturation shows up before requests start hanging.
+#[derive(Debug, Default)]
+pub(crate) struct PoolMetrics {
+    active: AtomicUsize,
+    /// Open connections, `None` when the pool doesn't report them.
+    open: Option<AtomicUsize>,
+    acquired: AtomicU64,
+    acquire_timeouts: AtomicU64,
+    wait_micros: AtomicU64,
+}
+
+impl PoolMetrics {
+
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Metrics of a pool which reports opening and closing its connections.
+    pub(crate) fn with_open_connections() -> Self {
+        Self { open: Some(AtomicUsize::new(0)), ..Self::default() }
+    }
+
+    /// The pool opened a connection.
+    pub(crate) fn record_opened(&self) {
+        if let Some(open) = &self.open {
+            open.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// The pool closed a connection.
+    pub(crate) fn record_closed(&self) {
+        if let Some(open) = &self.open {
+            let _ = open.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |open| open.checked_sub(1));
+        }
+    }
+
+    /// A connection was handed out after waiting `wait` for it.
+    pub(crate) fn record_checked_out(&self, wait: Duration) {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        self.acquired.fetch_add(1, Ordering::SeqCst);
+        self.wait_micros.fetch_add(wait.as_micros() as u64, Ordering::SeqCst);
+    }
+
+    /// A connection couldn't be handed out after waiting `wait` for it.
+    pub(crate) fn record_checkout_failed(&self, wait: Duration, timeout: bool) {
+        if timeout {
+            self.acquire_timeouts.fetch_add(1, Ordering::SeqCst);
+        }
+        self.wait_micros.fetch_add(wait.as_micros() as u64, Ordering::SeqCst);
+    }
+
+    /// A connection was returned to the pool.
+    pub(crate) fn record_checked_in(&self) {
+        let _ = self.active.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| active.checked_sub(1));
+    }
+
+    pub(crate) fn active(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Open connections which are not in use, `None` when the pool doesn't
+    /// report its connections.
+    pub(crate) fn idle(&self) -> Option<usize> {
+        self.open.as_ref().map(|open| open.load(Ordering::SeqCst).saturating_sub(self.active()))
+    }
+
+    pub(crate) fn acquired(&self) -> u64 {
+        self.acquired.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn acquire_timeouts(&self) -> u64 {
+        self.acquire_timeouts.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn wait_time(&self) -> Duration {
+        Duration::from_micros(self.wait_micros.load(Ordering::SeqCst))
+    }
+
+    pub(crate) fn to_json(&self) -> JsonValue {
+        json!({
+            "active": self.active(),
+            "idle": self.idle(),
+            "acquired": self.acquired(),
+            "acquireTimeouts": self.acquire_timeouts(),
+            "waitTimeMs": self.wait_time().as_millis() as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use super::*;
+
+    #[test]
+    fn concurrent_checkouts_are_recorded() {
+        let metrics = Arc::new(PoolMetrics::new());
+        let barrier = Arc::new(Barrier::new(8));
+        let handles: Vec<_> = (0..8).map(|_| {
+            let metrics = metrics.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                metrics.record_checked_out(Duration::from_millis(2));
+                barrier.wait();
+                metrics.record_checked_in();
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        metrics.record_checkout_failed(Duration::from_millis(30), true);
+        assert_eq!(metrics.acquired(), 8);
+        assert_eq!(metrics.active(), 0);
+        assert_eq!(metrics.idle(), None);
+        assert_eq!(metrics.to_json()["idle"], JsonValue::Null);
+        assert_eq!(metrics.acquire_timeouts(), 1);
+        assert_eq!(metrics.wait_time(), Duration::from_millis(46));
+        assert_eq!(metrics.to_json()["waitTimeMs"], json!(46));
+    }
+
+    #[test]
+    fn idle_connections_are_read_from_the_pool() {
+        let metrics = PoolMetrics::with_open_connections();
+        metrics.record_opened();
+        metrics.record_opened();
+        metrics.record_checked_out(Duration::ZERO);
+        assert_eq!(metrics.idle(), Some(1));
+        metrics.record_checked_in();
+        metrics.record_closed();
+        assert_eq!(metrics.idle(), Some(1));
+        assert_eq!(metrics.to_json()["idle"], json!(1));
+    }
+}
@@ -208,5 +208,9 @@ pub enum DatabaseType {
     // PostgreSQL only
     ByteA,
 
+    // Json type
+    // JSON in MySQL, JSONB in PostgreSQL and a document in MongoDB
+    Json,
+
     Vec(Box<DatabaseType>),
 }
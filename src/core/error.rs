@@ -1,12 +1,25 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
-use serde::{Serialize};
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeStruct;
 use maplit::hashmap;
 use key_path::KeyPath;
 use crate::core::model::Model;
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
-pub(crate) enum ErrorType {
+/// Declares `ErrorType` with its variants, and `ALL_ERROR_TYPES` listing
+/// each of them in order.
+macro_rules! error_types {
+    ($($variant:ident,)*) => {
+        #[derive(Debug, PartialEq, Serialize, Clone)]
+        pub(crate) enum ErrorType {
+            $($variant,)*
+        }
+
+        static ALL_ERROR_TYPES: &[ErrorType] = &[$(ErrorType::$variant,)*];
+    }
+}
+
+error_types! {
 
     // server errors
 
@@ -33,6 +46,7 @@ pub(crate) enum ErrorType {
     ValidationError,
     MissingRequiredInput,
     UnexpectedObjectLength,
+    ValueTooDeep,
 
     // request token
     InvalidAuthToken,
@@ -41,6 +55,9 @@ pub(crate) enum ErrorType {
     PermissionError,
     DeletionDenied,
 
+    // request quota
+    TooManyRequests,
+
     // response destination
     ObjectNotFound,
 
@@ -82,17 +99,57 @@ impl ErrorType {
             ErrorType::UnexpectedInputKey => { 400 }
             ErrorType::MissingRequiredInput => { 400 }
             ErrorType::UnexpectedObjectLength => { 400 }
+            ErrorType::ValueTooDeep => { 400 }
             ErrorType::InvalidKey => { 500 }
             ErrorType::InvalidOperation => { 500 }
             ErrorType::PermissionError => { 401 }
             ErrorType::UnexpectedOutputException => { 500 }
             ErrorType::DeletionDenied => { 400 }
+            ErrorType::TooManyRequests => { 429 }
             ErrorType::RecordDecodingError => { 500 }
         }
     }
+
+    /// The stable machine-readable code of this error type, sent as `code` in error responses.
+    pub(crate) fn error_code(&self) -> &'static str {
+        match self {
+            ErrorType::InternalServerError => "INTERNAL_SERVER_ERROR",
+            ErrorType::UnknownDatabaseWriteError => "UNKNOWN_DATABASE_WRITE_ERROR",
+            ErrorType::UnknownDatabaseDeleteError => "UNKNOWN_DATABASE_DELETE_ERROR",
+            ErrorType::UnknownDatabaseFindError => "UNKNOWN_DATABASE_FIND_ERROR",
+            ErrorType::UnknownDatabaseFindUniqueError => "UNKNOWN_DATABASE_FIND_UNIQUE_ERROR",
+            ErrorType::UnknownDatabaseCountError => "UNKNOWN_DATABASE_COUNT_ERROR",
+            ErrorType::WrongIdentityModel => "WRONG_IDENTITY_MODEL",
+            ErrorType::PropertySetterError => "PROPERTY_SETTER_ERROR",
+            ErrorType::DestinationNotFound => "DESTINATION_NOT_FOUND",
+            ErrorType::IncorrectJSONFormat => "INCORRECT_JSON_FORMAT",
+            ErrorType::UnexpectedInputRootType => "UNEXPECTED_INPUT_ROOT_TYPE",
+            ErrorType::UnexpectedInputType => "UNEXPECTED_INPUT_TYPE",
+            ErrorType::UnexpectedInputKey => "UNEXPECTED_INPUT_KEY",
+            ErrorType::ValidationError => "VALIDATION_ERROR",
+            ErrorType::MissingRequiredInput => "MISSING_REQUIRED_INPUT",
+            ErrorType::UnexpectedObjectLength => "UNEXPECTED_OBJECT_LENGTH",
+            ErrorType::ValueTooDeep => "VALUE_TOO_DEEP",
+            ErrorType::InvalidAuthToken => "INVALID_AUTH_TOKEN",
+            ErrorType::PermissionError => "PERMISSION_ERROR",
+            ErrorType::DeletionDenied => "DELETION_DENIED",
+            ErrorType::TooManyRequests => "TOO_MANY_REQUESTS",
+            ErrorType::ObjectNotFound => "OBJECT_NOT_FOUND",
+            ErrorType::UnexpectedOutputException => "UNEXPECTED_OUTPUT_EXCEPTION",
+            ErrorType::InvalidKey => "INVALID_KEY",
+            ErrorType::InvalidOperation => "INVALID_OPERATION",
+            ErrorType::CustomInternalServerError => "CUSTOM_INTERNAL_SERVER_ERROR",
+            ErrorType::CustomValidationError => "CUSTOM_VALIDATION_ERROR",
+            ErrorType::RecordDecodingError => "RECORD_DECODING_ERROR",
+        }
+    }
+
+    pub(crate) fn iter() -> std::slice::Iter<'static, ErrorType> {
+        ALL_ERROR_TYPES.iter()
+    }
 }
 
-#[derive(Debug, PartialEq, Serialize, Clone)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Error {
     pub(crate) r#type: ErrorType,
     pub(crate) message: String,
@@ -175,6 +232,14 @@ impl Error {
         }
     }
 
+    pub(crate) fn object_not_found_with_message(message: impl Into<String>) -> Self {
+        Error {
+            r#type: ErrorType::ObjectNotFound,
+            message: message.into(),
+            errors: None
+        }
+    }
+
     pub(crate) fn object_is_not_saved_thus_cant_be_deleted() -> Self {
         Error {
             r#type: ErrorType::InternalServerError,
@@ -183,6 +248,10 @@ impl Error {
         }
     }
 
+    pub(crate) fn new_object_cannot_disconnect<'a>(key_path: impl AsRef<KeyPath<'a>>) -> Self {
+        Self::unexpected_input_value_with_reason("A new object has no relations to disconnect.", key_path)
+    }
+
     pub(crate) fn unknown_database_find_error() -> Self {
         Error {
             r#type: ErrorType::UnknownDatabaseFindError,
@@ -329,6 +398,14 @@ impl Error {
         }
     }
 
+    pub(crate) fn value_too_deep<'a>(limit: usize, key_path: impl AsRef<KeyPath<'a>>) -> Self {
+        Error {
+            r#type: ErrorType::ValueTooDeep,
+            message: "Value nested too deep.".to_string(),
+            errors: Some(hashmap!{key_path.as_ref().to_string() => format!("Expect at most {} levels of nesting.", limit)})
+        }
+    }
+
     pub(crate) fn invalid_key(unexpected_key: impl AsRef<str>, model: &Model) -> Self {
         Error {
             r#type: ErrorType::InvalidKey,
@@ -345,6 +422,14 @@ impl Error {
         }
     }
 
+    pub(crate) fn too_many_requests(reason: impl AsRef<str>) -> Self {
+        Error {
+            r#type: ErrorType::TooManyRequests,
+            message: reason.as_ref().to_string(),
+            errors: None
+        }
+    }
+
     pub(crate) fn unexpected_output_exception<'a>(path: impl AsRef<KeyPath<'a>>, reason: impl AsRef<str>) -> Self {
         Error {
             r#type: ErrorType::UnexpectedOutputException,
@@ -353,10 +438,12 @@ impl Error {
         }
     }
 
-    pub(crate) fn deletion_denied(relation_name: impl AsRef<str>) -> Self {
+    /// A `restrict` relation has records which would lose their required
+    /// relation if the record is deleted.
+    pub(crate) fn required_relation_cannot_disconnect(relation_name: impl AsRef<str>) -> Self {
         Error {
             r#type: ErrorType::DeletionDenied,
-            message: format!("Deletion denied by `{}'.", relation_name.as_ref()),
+            message: format!("Deletion denied by `{}', its records require the relation.", relation_name.as_ref()),
             errors: None
         }
     }
@@ -402,6 +489,17 @@ impl Display for Error {
 
 impl std::error::Error for Error { }
 
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut state = serializer.serialize_struct("Error", 4)?;
+        state.serialize_field("type", &self.r#type)?;
+        state.serialize_field("code", self.r#type.error_code())?;
+        state.serialize_field("message", &self.message)?;
+        state.serialize_field("errors", &self.errors)?;
+        state.end()
+    }
+}
+
 impl From<&str> for Error {
     fn from(value: &str) -> Self {
         Error::custom_internal_server_error(value)
@@ -416,3 +514,71 @@ impl From<String> for Error {
 
 unsafe impl Sync for Error {}
 unsafe impl Send for Error {}
+
+/// Collects validation failures so that every invalid key is reported in one error.
+#[derive(Debug, Default)]
+pub(crate) struct ValidationErrors {
+    errors: HashMap<String, String>,
+}
+
+impl ValidationErrors {
+
+    pub(crate) fn new() -> Self {
+        Self { errors: HashMap::new() }
+    }
+
+    /// Records the key paths of a validation error. Other errors are returned unchanged.
+    pub(crate) fn collect(&mut self, error: Error) -> Result<(), Error> {
+        if error.r#type == ErrorType::ValidationError && error.errors.is_some() {
+            self.errors.extend(error.errors.unwrap());
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub(crate) fn into_result(self) -> Result<(), Error> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(Error {
+                r#type: ErrorType::ValidationError,
+                message: "Validation failed.".to_string(),
+                errors: Some(self.errors),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use key_path::path;
+    use super::*;
+
+    #[test]
+    fn other_errors_are_not_collected() {
+        let mut errors = ValidationErrors::new();
+        let error = errors.collect(Error::permission_error(path!["email"], "denied")).unwrap_err();
+        assert_eq!(error.r#type, ErrorType::PermissionError);
+        assert!(errors.into_result().is_ok());
+    }
+
+    #[test]
+    fn error_codes_are_screaming_snake_case_variant_names() {
+        assert_eq!(ErrorType::ValidationError.error_code(), "VALIDATION_ERROR");
+        assert_eq!(ErrorType::IncorrectJSONFormat.error_code(), "INCORRECT_JSON_FORMAT");
+        assert_eq!(ErrorType::TooManyRequests.error_code(), "TOO_MANY_REQUESTS");
+        assert_eq!(ErrorType::UnknownDatabaseFindUniqueError.error_code(), "UNKNOWN_DATABASE_FIND_UNIQUE_ERROR");
+    }
+
+    #[test]
+    fn serialized_error_carries_its_code() {
+        let json = serde_json::to_value(Error::too_many_requests("slow down")).unwrap();
+        assert_eq!(json["type"], "TooManyRequests");
+        assert_eq!(json["code"], "TOO_MANY_REQUESTS");
+    }
+}
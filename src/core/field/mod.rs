@@ -3,6 +3,7 @@ pub(crate) mod optionality;
 pub(crate) mod read_rule;
 pub(crate) mod write_rule;
 pub(crate) mod migration;
+pub(crate) mod sequence;
 
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
@@ -15,6 +16,7 @@ use crate::core::field::migration::FieldMigration;
 use crate::core::field::optionality::Optionality;
 use crate::core::field::r#type::{FieldType, FieldTypeOwner};
 use crate::core::field::read_rule::ReadRule;
+use crate::core::field::sequence::FieldSequence;
 use crate::core::field::write_rule::WriteRule;
 use crate::core::pipeline::Pipeline;
 use crate::core::pipeline::ctx::Ctx;
@@ -33,6 +35,12 @@ pub enum QueryAbility {
     Unqueryable,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortAbility {
+    Sortable,
+    Unsortable,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum ObjectAssignment {
     Reference,
@@ -82,6 +90,10 @@ pub struct IndexSettings {
     pub(crate) name: Option<String>,
     pub(crate) sort: Sort,
     pub(crate) length: Option<usize>,
+    /// Whether documents without the field are left out of the index, unique
+    /// indices of optional fields are sparse unless set otherwise.
+    pub(crate) sparse: Option<bool>,
+    pub(crate) case_insensitive: bool,
 }
 
 impl Default for IndexSettings {
@@ -89,7 +101,9 @@ impl Default for IndexSettings {
         IndexSettings {
             name: None,
             sort: Sort::Asc,
-            length: None
+            length: None,
+            sparse: None,
+            case_insensitive: false,
         }
     }
 }
@@ -128,18 +142,24 @@ pub struct Field {
     pub(crate) output_omissible: bool,
     pub(crate) index: Option<FieldIndex>,
     pub(crate) query_ability: QueryAbility,
+    pub(crate) sort_ability: SortAbility,
     pub(crate) auto: bool,
     pub(crate) auto_increment: bool,
+    pub(crate) sequence: Option<FieldSequence>,
     pub(crate) identity: bool,
     pub(crate) identity_checker: Option<Value>,
     pub(crate) default: Option<Value>,
     pub(crate) on_set_pipeline: Pipeline,
     pub(crate) on_save_pipeline: Pipeline,
+    pub(crate) unique_within: Option<Vec<String>>,
+    pub(crate) on_get_pipeline: Pipeline,
     pub(crate) on_output_pipeline: Pipeline,
     pub(crate) can_mutate_pipeline: Pipeline,
     pub(crate) can_read_pipeline: Pipeline,
     pub(crate) column_name: Option<String>,
+    pub(crate) api_name: Option<String>,
     pub(crate) foreign_key: bool,
+    pub(crate) sensitive: bool,
     pub(crate) migration: Option<FieldMigration>,
     pub(crate) dropped: bool,
 }
@@ -169,21 +189,27 @@ impl Field {
             write_rule: WriteRule::Write,
             index: None,
             query_ability: QueryAbility::Queryable,
+            sort_ability: SortAbility::Sortable,
             auto: false,
             auto_increment: false,
+            sequence: None,
             identity: false,
             identity_checker: None,
             default: None,
             on_set_pipeline: Pipeline::new(),
             on_save_pipeline: Pipeline::new(),
+            unique_within: None,
+            on_get_pipeline: Pipeline::new(),
             on_output_pipeline: Pipeline::new(),
             can_mutate_pipeline: Pipeline::new(),
             can_read_pipeline: Pipeline::new(),
             column_name: None,
+            api_name: None,
             previous_value_rule: PreviousValueRule::DontKeep,
             input_omissible: false,
             output_omissible: false,
             foreign_key: false,
+            sensitive: false,
             migration: None,
             dropped: false,
         }
@@ -193,6 +219,19 @@ impl Field {
         &self.name
     }
 
+    /// The name used in request and response JSON and generated clients.
+    pub fn api_name(&self) -> &str {
+        match &self.api_name {
+            Some(api_name) => api_name.as_str(),
+            None => self.name.as_str(),
+        }
+    }
+
+    /// Whether the value is hidden from logs.
+    pub(crate) fn is_redacted(&self) -> bool {
+        self.sensitive || self.read_rule.is_no_read()
+    }
+
     pub(crate) fn localized_name(&self) -> String {
         if self.localized_name.is_some() {
             self.localized_name.clone().unwrap()
@@ -237,10 +276,23 @@ impl Field {
         self.on_output_pipeline.has_any_items()
     }
 
+    /// Normalizes an input value. This runs before the value is stored, so unique
+    /// constraints always compare normalized values.
+    pub(crate) async fn perform_on_set_callback(&self, ctx: Ctx<'_>) -> Result<Value> {
+        self.on_set_pipeline.process(ctx).await
+    }
+
     pub(crate) async fn perform_on_save_callback(&self, ctx: Ctx<'_>) -> Result<Value> {
         self.on_save_pipeline.process(ctx).await
     }
 
+    /// Turns a stored value back into its readable form, e.g. decrypts a value
+    /// which `on_set` encrypted. Filters, sorting and indices see stored
+    /// values, so fields with an `on_get` pipeline are left out of them.
+    pub(crate) async fn perform_on_get_callback(&self, ctx: Ctx<'_>) -> Result<Value> {
+        self.on_get_pipeline.process(ctx).await
+    }
+
     pub(crate) async fn perform_on_output_callback(&self, ctx: Ctx<'_>) -> Result<Value> {
         self.on_output_pipeline.process(ctx).await
     }
@@ -262,6 +314,14 @@ impl Field {
     pub(crate) fn migration(&self) -> Option<&FieldMigration> {
         self.migration.as_ref()
     }
+
+    /// Records a previous column name of this field, migration renames that
+    /// column instead of dropping it.
+    pub(crate) fn renamed_from(&mut self, column_name: impl Into<String>) -> &mut Self {
+        let migration = self.migration.get_or_insert_with(|| FieldMigration { renamed: vec![], version: None, default: None, action: None, priority: None });
+        migration.renamed.push(column_name.into());
+        self
+    }
 }
 
 impl FieldTypeOwner for Field {
@@ -276,3 +336,21 @@ impl FieldTypeOwner for Field {
 
 unsafe impl Send for Field {}
 unsafe impl Sync for Field {}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::pipeline::items::string::transform::to_lower_case::ToLowerCaseItem;
+    use crate::core::pipeline::items::string::transform::to_upper_case::ToUpperCaseItem;
+    use super::*;
+
+    #[tokio::test]
+    async fn value_is_stored_encoded_and_read_decoded() {
+        let mut field = Field::new("secret".to_owned());
+        field.on_set_pipeline.items.push(Arc::new(ToUpperCaseItem::new()));
+        field.on_get_pipeline.items.push(Arc::new(ToLowerCaseItem::new()));
+        let stored = field.perform_on_set_callback(Ctx::initial_state_with_value(Value::String("abc".to_owned()))).await.unwrap();
+        assert_eq!(stored, Value::String("ABC".to_owned()));
+        let read = field.perform_on_get_callback(Ctx::initial_state_with_value(stored)).await.unwrap();
+        assert_eq!(read, Value::String("abc".to_owned()));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core::pipeline::ctx::Ctx;
 use crate::core::pipeline::Pipeline;
 use crate::prelude::Value;
 
@@ -25,3 +26,36 @@ impl Optionality {
         }
     }
 }
+
+/// Whether a field is absent while its `@presentIf` condition holds, which
+/// `@requiredIf` names, too. The condition is evaluated against the record
+/// being saved, so that it can read sibling values.
+pub(crate) async fn is_missing_when_required(condition: &Pipeline, ctx: Ctx<'_>, value: &Value) -> bool {
+    value.is_null() && condition.process(ctx).await.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::core::pipeline::items::object::get::GetItem;
+    use crate::core::pipeline::items::value::eq::EqItem;
+    use crate::teon;
+    use super::*;
+
+    fn business_only() -> Pipeline {
+        Pipeline { items: vec![Arc::new(GetItem::new(Value::String("accountType".to_owned()))), Arc::new(EqItem::new("business"))] }
+    }
+
+    #[tokio::test]
+    async fn field_is_required_when_condition_is_met() {
+        let ctx = Ctx::initial_state_with_value(teon!({"accountType": "business"}));
+        assert!(is_missing_when_required(&business_only(), ctx.clone(), &Value::Null).await);
+        assert!(!is_missing_when_required(&business_only(), ctx, &Value::String("Acme".to_owned())).await);
+    }
+
+    #[tokio::test]
+    async fn field_is_optional_when_condition_is_not_met() {
+        let ctx = Ctx::initial_state_with_value(teon!({"accountType": "personal"}));
+        assert!(!is_missing_when_required(&business_only(), ctx, &Value::Null).await);
+    }
+}
@@ -5,6 +5,8 @@ pub enum ReadRule {
     Read,
     NoRead,
     ReadIf(Pipeline),
+    /// Readable only by identities of these models.
+    ReadBy(Vec<String>),
 }
 
 impl ReadRule {
@@ -14,4 +16,29 @@ impl ReadRule {
             _ => false
         }
     }
+
+    pub fn is_read(&self) -> bool {
+        match self {
+            ReadRule::Read => true,
+            _ => false
+        }
+    }
+
+    pub fn is_read_if(&self) -> bool {
+        match self {
+            ReadRule::ReadIf(_) => true,
+            _ => false
+        }
+    }
+
+    pub fn is_read_by(&self, role: Option<&str>) -> bool {
+        match self {
+            ReadRule::NoRead => false,
+            ReadRule::ReadBy(roles) => match role {
+                Some(role) => roles.iter().any(|r| r == role),
+                None => false,
+            },
+            _ => true
+        }
+    }
 }
@@ -0,0 +1,53 @@
+use serde_json::Value as JsonValue;
+use crate::prelude::Value;
+
+/// The collection or table which stores sequence counters.
+pub(crate) static SEQUENCE_COUNTERS: &str = "__teo_sequences";
+
+/// A field whose value is taken from an atomically incremented counter on create.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct FieldSequence {
+    pub(crate) scope: Vec<String>,
+    pub(crate) start: i64,
+}
+
+impl Default for FieldSequence {
+    fn default() -> Self {
+        Self { scope: vec![], start: 1 }
+    }
+}
+
+impl FieldSequence {
+
+    /// The name of the counter shared by all records with the same scope values.
+    pub(crate) fn counter_name(&self, model: &str, field: &str, scope_values: &Vec<Value>) -> String {
+        let mut name = format!("{model}.{field}");
+        for (key, value) in self.scope.iter().zip(scope_values.iter()) {
+            let json: JsonValue = value.into();
+            name.push_str(&format!("|{key}={json}"));
+        }
+        name
+    }
+
+    /// Maps the counter's incremented count to the sequence value.
+    pub(crate) fn value_for_count(&self, count: i64) -> i64 {
+        self.start + count - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_are_isolated_by_scope_values() {
+        let sequence = FieldSequence { scope: vec!["tenantId".to_owned()], start: 1000 };
+        let a = sequence.counter_name("Invoice", "number", &vec![Value::String("a".to_owned())]);
+        let b = sequence.counter_name("Invoice", "number", &vec![Value::String("b".to_owned())]);
+        assert_eq!(a, "Invoice.number|tenantId=\"a\"");
+        assert_ne!(a, b);
+        assert_eq!(a, sequence.counter_name("Invoice", "number", &vec![Value::String("a".to_owned())]));
+        assert_eq!(sequence.value_for_count(1), 1000);
+        assert_eq!(sequence.value_for_count(2), 1001);
+    }
+}
@@ -22,6 +22,7 @@ pub enum FieldType {
     HashMap(Box<Field>),
     BTreeMap(Box<Field>),
     Object(String),
+    Json,
 }
 
 impl FieldType {
@@ -136,6 +137,13 @@ impl FieldType {
         }
     }
 
+    pub fn is_json(&self) -> bool {
+        match self {
+            FieldType::Json => true,
+            _ => false,
+        }
+    }
+
     pub fn element_field(&self) -> Option<&Field> {
         match self {
             FieldType::Vec(inner) => Some(inner.as_ref()),
@@ -165,14 +173,16 @@ impl FieldType {
             FieldType::ObjectId => &DEFAULT_FILTERS,
             FieldType::Bool => &BOOL_FILTERS,
             FieldType::I32 | FieldType::I64 |
-            FieldType::F32 | FieldType::F64 | FieldType::Date | FieldType::DateTime |
+            FieldType::F32 | FieldType::F64 |
             FieldType::Decimal => &DEFAULT_FILTERS,
+            FieldType::Date | FieldType::DateTime => &DATE_FILTERS,
             FieldType::String => &STRING_FILTERS,
             FieldType::Enum(_) => &ENUM_FILTERS,
             FieldType::Vec(_) => &VEC_FILTERS,
             FieldType::HashMap(_) => &MAP_FILTERS,
             FieldType::BTreeMap(_) => &MAP_FILTERS,
-            FieldType::Object(_) => panic!("Object filter is not implemented.")
+            FieldType::Object(_) => panic!("Object filter is not implemented."),
+            FieldType::Json => &JSON_FILTERS,
         }
     }
 
@@ -183,13 +193,14 @@ impl FieldType {
             FieldType::Bool => &BOOL_FILTERS,
             FieldType::I32 | FieldType::I64 |
             FieldType::F32 | FieldType::F64 | FieldType::Decimal => &NUMBER_FILTERS_WITH_AGGREGATE,
-            FieldType::Date | FieldType::DateTime => &DEFAULT_FILTERS_WITH_AGGREGATE,
+            FieldType::Date | FieldType::DateTime => &DATE_FILTERS_WITH_AGGREGATE,
             FieldType::String => &STRING_FILTERS_WITH_AGGREGATE,
             FieldType::Enum(_) => &ENUM_FILTERS_WITH_AGGREGATE,
             FieldType::Vec(_) => &VEC_FILTERS,
             FieldType::HashMap(_) => &MAP_FILTERS,
             FieldType::BTreeMap(_) => &MAP_FILTERS,
-            FieldType::Object(_) => panic!("Object filter is not implemented.")
+            FieldType::Object(_) => panic!("Object filter is not implemented."),
+            FieldType::Json => &JSON_FILTERS,
         }
     }
 }
@@ -212,11 +223,14 @@ static BOOL_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset!{"equals", "not"}
 });
 static STRING_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
-    hashset!{"equals", "not", "gt", "gte", "lt", "lte", "in", "notIn", "contains", "startsWith", "endsWith", "matches", "mode"}
+    hashset!{"equals", "not", "gt", "gte", "lt", "lte", "in", "notIn", "contains", "startsWith", "endsWith", "matches", "search", "mode"}
 });
 static DEFAULT_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset!{"equals", "not", "gt", "gte", "lt", "lte", "in", "notIn"}
 });
+static DATE_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
+    DEFAULT_FILTERS.bitor(&hashset!{"between"})
+});
 static ENUM_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset! {"equals", "not", "in", "notIn"}
 });
@@ -226,6 +240,9 @@ static VEC_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
 static MAP_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset! {"equals", "has", "hasEvery", "hasSome", "isEmpty", "length", "hasKey"}
 });
+static JSON_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
+    hashset! {"equals"}
+});
 static STRING_FILTERS_WITH_AGGREGATE: Lazy<HashSet<&str>> = Lazy::new(|| {
     STRING_FILTERS.bitor(&hashset!{"_min", "_max", "_count"})
 });
@@ -235,6 +252,9 @@ static NUMBER_FILTERS_WITH_AGGREGATE: Lazy<HashSet<&str>> = Lazy::new(|| {
 static DEFAULT_FILTERS_WITH_AGGREGATE: Lazy<HashSet<&str>> = Lazy::new(|| {
     DEFAULT_FILTERS.bitor(&hashset!{"_min", "_max", "_count"})
 });
+static DATE_FILTERS_WITH_AGGREGATE: Lazy<HashSet<&str>> = Lazy::new(|| {
+    DATE_FILTERS.bitor(&hashset!{"_min", "_max", "_count"})
+});
 static ENUM_FILTERS_WITH_AGGREGATE: Lazy<HashSet<&str>> = Lazy::new(|| {
     ENUM_FILTERS.bitor(&hashset!{"_count"})
 });
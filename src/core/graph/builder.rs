@@ -1,17 +1,24 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use chrono::FixedOffset;
 use crate::core::connector::Connector;
+use crate::core::field::r#type::FieldType;
 use crate::core::r#enum::builder::EnumBuilder;
 use crate::core::graph::GraphInner;
 use crate::core::model::builder::ModelBuilder;
 use crate::core::model::Model;
 use crate::core::r#enum::Enum;
-use crate::prelude::Graph;
+use crate::parser::ast::config::{DateTimeFormat, FieldNaming};
+use crate::prelude::{Graph, Value};
 
 pub struct GraphBuilder {
     pub(crate) enum_builders: HashMap<String, EnumBuilder>,
     pub(crate) model_builders: Vec<ModelBuilder>,
     pub(crate) reset_database: bool,
+    pub(crate) default_timezone: FixedOffset,
+    pub(crate) field_naming: FieldNaming,
+    pub(crate) date_time_format: DateTimeFormat,
+    pub(crate) connectors: HashMap<String, Arc<dyn Connector>>,
 }
 
 impl GraphBuilder {
@@ -21,6 +28,10 @@ impl GraphBuilder {
             enum_builders: HashMap::new(),
             model_builders: Vec::new(),
             reset_database: false,
+            default_timezone: FixedOffset::east_opt(0).unwrap(),
+            field_naming: FieldNaming::AsDefined,
+            date_time_format: DateTimeFormat::RFC3339,
+            connectors: HashMap::new(),
         }
     }
 
@@ -52,21 +63,159 @@ impl GraphBuilder {
         retval
     }
 
-    pub(crate) async fn build(&self, connector: Arc<dyn Connector>) -> Graph {
+    fn resolve_inheritance(&mut self) {
+        for index in 0..self.model_builders.len() {
+            let base_name = match &self.model_builders[index].base {
+                Some(base_name) => base_name.clone(),
+                None => continue,
+            };
+            let base_index = match self.model_builders.iter().position(|m| m.name == base_name) {
+                Some(base_index) => base_index,
+                None => panic!("Model '{}' extends undefined model '{}'.", self.model_builders[index].name, base_name),
+            };
+            if base_index == index || self.model_builders[base_index].base.is_some() {
+                panic!("Model '{}' cannot extend '{}'.", self.model_builders[index].name, base_name);
+            }
+            let (subtype, base) = if index < base_index {
+                let (left, right) = self.model_builders.split_at_mut(base_index);
+                (&mut left[index], &mut right[0])
+            } else {
+                let (left, right) = self.model_builders.split_at_mut(index);
+                (&mut right[0], &mut left[base_index])
+            };
+            subtype.inherit_from(base);
+            for field in subtype.fields.iter() {
+                if base.fields.iter().any(|f| f.name == field.name) { continue }
+                if base.subtype_fields.iter().any(|f| f.name == field.name) { continue }
+                let mut field = field.clone();
+                field.set_optional();
+                base.subtype_fields.push(field);
+            }
+        }
+    }
+
+    fn validate_relations(models: &Vec<Model>, models_map: &HashMap<String, Model>) {
+        for model in models {
+            for relation in model.relations() {
+                if let Some(through) = relation.through() {
+                    if let Some(through_model) = models_map.get(through) {
+                        if through_model.connector_name() != model.connector_name() {
+                            panic!("Relation '{}' of model '{}' goes through model '{}' which uses another connector.", relation.name(), model.name(), through);
+                        }
+                    }
+                    continue
+                }
+                let opposite_model = match models_map.get(relation.model()) {
+                    Some(m) => m,
+                    None => panic!("Relation '{}' of model '{}' refers to undefined model '{}'.", relation.name(), model.name(), relation.model()),
+                };
+                if opposite_model.connector_name() != model.connector_name() {
+                    panic!("Relation '{}' of model '{}' refers to model '{}' which uses another connector.", relation.name(), model.name(), relation.model());
+                }
+                for (field, reference) in relation.iter() {
+                    if model.field(field).is_none() {
+                        panic!("Relation '{}' of model '{}' refers to undefined field '{}'.", relation.name(), model.name(), field);
+                    }
+                    if opposite_model.field(reference).is_none() {
+                        panic!("Relation '{}' of model '{}' references undefined field '{}' of model '{}'.", relation.name(), model.name(), reference, opposite_model.name());
+                    }
+                }
+                if let Some(counter_cache) = relation.counter_cache() {
+                    match opposite_model.field(counter_cache).map(|f| f.field_type()) {
+                        Some(FieldType::I32) | Some(FieldType::I64) => (),
+                        _ => panic!("Counter cache '{}' of relation '{}' of model '{}' should be an Int or Int64 field of model '{}'.", counter_cache, relation.name(), model.name(), opposite_model.name()),
+                    }
+                }
+                // a composite foreign key must reference a composite primary or unique key
+                if relation.has_foreign_key() && relation.len() > 1 {
+                    let references_key = opposite_model.indices().iter().any(|index| {
+                        index.r#type().is_unique() && index.keys().len() == relation.len() && relation.references().iter().all(|r| index.keys().contains(r))
+                    });
+                    if !references_key {
+                        panic!("Relation '{}' of model '{}' should reference a primary or unique key of model '{}'.", relation.name(), model.name(), opposite_model.name());
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_enum_defaults(models: &Vec<Model>, enums: &HashMap<String, Enum>) {
+        for model in models {
+            for field in model.fields() {
+                let enum_name = match &field.field_type {
+                    Some(FieldType::Enum(enum_name)) => enum_name,
+                    _ => continue,
+                };
+                let r#enum = match enums.get(enum_name) {
+                    Some(r#enum) => r#enum,
+                    None => panic!("Field '{}' of model '{}' refers to undefined enum '{}'.", field.name(), model.name(), enum_name),
+                };
+                let choice = match &field.default {
+                    Some(Value::RawEnumChoice(choice, _)) => Some(choice),
+                    Some(Value::String(choice)) => Some(choice),
+                    // computed defaults are validated on create
+                    _ => None,
+                };
+                if let Some(choice) = choice {
+                    if !r#enum.values().contains(choice) {
+                        panic!("Default value '{}' of field '{}' of model '{}' is not a member of enum '{}'.", choice, field.name(), model.name(), enum_name);
+                    }
+                }
+                // the choices a value is constrained to, like `$oneOf`
+                for item in field.on_set_pipeline.items.iter() {
+                    let choices = match item.json_schema_keyword() {
+                        Some(("enum", Value::Vec(choices))) => choices,
+                        _ => continue,
+                    };
+                    for choice in choices.iter() {
+                        let choice = match choice {
+                            Value::RawEnumChoice(choice, _) => choice,
+                            Value::String(choice) => choice,
+                            _ => panic!("Constraint value '{:?}' of field '{}' of model '{}' is not a member of enum '{}'.", choice, field.name(), model.name(), enum_name),
+                        };
+                        if !r#enum.values().contains(choice) {
+                            panic!("Constraint value '{}' of field '{}' of model '{}' is not a member of enum '{}'.", choice, field.name(), model.name(), enum_name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn model_connector(&self, model_builder: &ModelBuilder, default: &Arc<dyn Connector>) -> Arc<dyn Connector> {
+        match &model_builder.connector {
+            Some(name) => match self.connectors.get(name) {
+                Some(connector) => connector.clone(),
+                None => panic!("Model '{}' uses undefined connector '{}'.", model_builder.name, name),
+            },
+            None => default.clone(),
+        }
+    }
+
+    pub(crate) async fn build(&mut self, connector: Arc<dyn Connector>) -> Graph {
+        self.resolve_inheritance();
         let mut graph = GraphInner {
             enums: self.build_enums(),
             models_vec: Vec::new(),
             models_map: HashMap::new(),
             url_segment_name_map: HashMap::new(),
             connector: None,
+            connectors: self.connectors.clone(),
+            default_timezone: self.default_timezone,
+            field_naming: self.field_naming,
+            date_time_format: self.date_time_format.clone(),
         };
-        graph.models_vec = self.model_builders.iter().map(|mb| { mb.build(connector.clone()) }).collect();
+        graph.models_vec = self.model_builders.iter().map(|mb| { mb.build(self.model_connector(mb, &connector), self.field_naming) }).collect();
         let mut models_map: HashMap<String, Model> = HashMap::new();
         let mut url_segment_name_map: HashMap<String, String> = HashMap::new();
         for model in graph.models_vec.iter() {
             models_map.insert(model.name().to_owned(), model.clone());
-            url_segment_name_map.insert(model.url_segment_name().to_owned(), model.name().to_owned());
+            if model.route().is_none() {
+                url_segment_name_map.insert(model.url_segment_name().to_owned(), model.name().to_owned());
+            }
         }
+        Self::validate_relations(&graph.models_vec, &models_map);
+        Self::validate_enum_defaults(&graph.models_vec, &graph.enums);
         graph.models_map = models_map;
         graph.url_segment_name_map = url_segment_name_map;
         graph.connector = Some(connector.clone());
@@ -0,0 +1,102 @@
+use serde_json::{json, Map, Value as JsonValue};
+use crate::core::action::Action;
+use crate::core::field::Field;
+use crate::core::field::r#type::{FieldType, FieldTypeOwner};
+use crate::core::graph::Graph;
+use crate::core::relation::Relation;
+
+/// Describes the graph's enums and the models served over HTTP, for tools
+/// which render forms or queries from the schema at runtime.
+pub(crate) fn introspect(graph: &Graph) -> JsonValue {
+    let mut models = Map::new();
+    for model in graph.models() {
+        if model.r#virtual() || model.internal() { continue }
+        let fields: Vec<&Field> = model.fields().iter().filter(|f| model.output_keys().contains(&f.name)).map(|f| f.as_ref()).collect();
+        let relations: Vec<&Relation> = model.relations().iter().map(|r| r.as_ref()).collect();
+        let actions: Vec<&str> = Action::handlers_iter().filter(|a| model.has_action(**a)).map(|a| a.as_handler_str()).collect();
+        models.insert(model.name().to_owned(), model_description(&model.localized_name(), model.description(), &fields, &relations, &actions));
+    }
+    let mut enums = Map::new();
+    for (name, r#enum) in graph.enums() {
+        enums.insert(name.clone(), json!(r#enum.values()));
+    }
+    json!({
+        "models": models,
+        "enums": enums,
+    })
+}
+
+fn model_description(localized_name: &str, description: &str, fields: &[&Field], relations: &[&Relation], actions: &[&str]) -> JsonValue {
+    let mut field_descriptions = Map::new();
+    for field in fields {
+        field_descriptions.insert(field.api_name().to_owned(), json!({
+            "type": type_name(field.field_type()),
+            "optional": field.is_optional(),
+            "localizedName": field.localized_name(),
+            "description": field.description(),
+        }));
+    }
+    let mut relation_descriptions = Map::new();
+    for relation in relations {
+        relation_descriptions.insert(relation.name().to_owned(), json!({
+            "model": relation.model(),
+            "many": relation.is_vec(),
+            "optional": relation.is_optional(),
+        }));
+    }
+    json!({
+        "localizedName": localized_name,
+        "description": description,
+        "fields": field_descriptions,
+        "relations": relation_descriptions,
+        "actions": actions,
+    })
+}
+
+/// The type's name as written in the schema.
+fn type_name(field_type: &FieldType) -> String {
+    match field_type {
+        #[cfg(feature = "data-source-mongodb")]
+        FieldType::ObjectId => "ObjectId".to_owned(),
+        FieldType::Bool => "Bool".to_owned(),
+        FieldType::I32 => "Int".to_owned(),
+        FieldType::I64 => "Int64".to_owned(),
+        FieldType::F32 => "Float32".to_owned(),
+        FieldType::F64 => "Float".to_owned(),
+        FieldType::Decimal => "Decimal".to_owned(),
+        FieldType::String => "String".to_owned(),
+        FieldType::Date => "Date".to_owned(),
+        FieldType::DateTime => "DateTime".to_owned(),
+        FieldType::Enum(name) => name.clone(),
+        FieldType::Vec(inner) => format!("{}[]", type_name(inner.field_type())),
+        FieldType::HashMap(inner) | FieldType::BTreeMap(inner) => format!("{}{{}}", type_name(inner.field_type())),
+        FieldType::Object(name) => name.clone(),
+        FieldType::Json => "Json".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::field::optionality::Optionality;
+    use crate::core::relation::cardinality::Cardinality;
+    use super::*;
+
+    #[test]
+    fn model_description_lists_fields_relations_and_actions() {
+        let mut id = Field::new("id".to_owned());
+        id.field_type = Some(FieldType::I32);
+        let mut email = Field::new("email".to_owned());
+        email.field_type = Some(FieldType::String);
+        email.optionality = Optionality::Optional;
+        let mut posts = Relation::new("posts");
+        posts.set_model("Post".to_owned());
+        posts.set_cardinality(Cardinality::Many);
+        let description = model_description("User", "", &[&id, &email], &[&posts], &["findMany", "create"]);
+        assert_eq!(description["fields"]["id"]["type"], json!("Int"));
+        assert_eq!(description["fields"]["id"]["optional"], json!(false));
+        assert_eq!(description["fields"]["email"]["type"], json!("String"));
+        assert_eq!(description["fields"]["email"]["optional"], json!(true));
+        assert_eq!(description["relations"]["posts"], json!({"model": "Post", "many": true, "optional": false}));
+        assert_eq!(description["actions"], json!(["findMany", "create"]));
+    }
+}
@@ -1,6 +1,8 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::future::Future;
 use std::sync::Arc;
+use chrono::FixedOffset;
 use key_path::KeyPath;
 use to_mut_proc_macro::ToMut;
 use to_mut::ToMut;
@@ -9,13 +11,18 @@ use crate::core::action::source::ActionSource;
 use crate::core::connector::Connector;
 use crate::core::model::Model;
 use crate::core::object::Object;
+use crate::core::pipeline::ctx::Ctx;
 use crate::core::r#enum::Enum;
 use crate::core::error::Error;
 use crate::core::relation::Relation;
 use crate::core::result::Result;
+use crate::core::teon::filter::cursor_where;
+use crate::parser::ast::config::{DateTimeFormat, FieldNaming};
 use crate::prelude::Value;
+use crate::teon;
 
 pub mod builder;
+pub(crate) mod introspection;
 
 #[derive(Clone, ToMut)]
 pub struct Graph {
@@ -28,6 +35,10 @@ pub(crate) struct GraphInner {
     pub(crate) models_map: HashMap<String, Model>,
     pub(crate) url_segment_name_map: HashMap<String, String>,
     pub(crate) connector: Option<Arc<dyn Connector>>,
+    pub(crate) connectors: HashMap<String, Arc<dyn Connector>>,
+    pub(crate) default_timezone: FixedOffset,
+    pub(crate) field_naming: FieldNaming,
+    pub(crate) date_time_format: DateTimeFormat,
 }
 
 static mut CURRENT: Option<&'static Graph> = None;
@@ -76,17 +87,34 @@ impl Graph {
         }
     }
 
+    /// Finds a page of objects along with the count of all objects the finder
+    /// matches, regardless of paging.
+    pub async fn find_many_and_count<T: From<Object>>(&self, model: &str, finder: &Value) -> Result<(Vec<T>, usize)> {
+        match self.find_many_and_count_internal(model, finder, false, Action::from_u32(PROGRAM_CODE | INTERNAL_AMOUNT | INTERNAL_POSITION), ActionSource::ProgramCode).await {
+            Ok((results, count)) => Ok((results.iter().map(|item| item.clone().into()).collect(), count)),
+            Err(err) => Err(err),
+        }
+    }
+
     pub(crate) async fn find_unique_internal(&self, model: &str, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Object> {
         let model = self.model(model).unwrap();
-        self.connector().find_unique(self, model, finder, mutation_mode, action, action_source).await
+        let finder = self.scoped_finder(model, finder)?;
+        Self::trigger_before_find_callbacks(model, finder.as_ref()).await?;
+        model.connector().find_unique(self, model, finder.as_ref(), mutation_mode, action, action_source).await
     }
 
     pub(crate) async fn find_first_internal(&self, model: &str, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Object> {
         let model = self.model(model).unwrap();
-        let mut finder = finder.as_hashmap().clone().unwrap().clone();
+        let finder = self.scoped_finder(model, finder)?;
+        let mut finder = model.finder_with_default_order_by(finder.as_ref()).as_hashmap().unwrap().clone();
         finder.insert("take".to_string(), 1.into());
         let finder = Value::HashMap(finder);
-        let result = self.connector().find_many(self, model, &finder, mutation_mode, action, action_source).await;
+        Self::trigger_before_find_callbacks(model, &finder).await?;
+        let result = if model.r#virtual() {
+            self.find_many_virtual(model, &finder, mutation_mode, action, action_source).await.map(|(objects, _)| objects)
+        } else {
+            model.connector().find_many(self, model, &finder, mutation_mode, action, action_source).await
+        };
         match result {
             Err(err) => Err(err),
             Ok(retval) => {
@@ -101,7 +129,127 @@ impl Graph {
 
     pub(crate) async fn find_many_internal(&self, model: &str, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
         let model = self.model(model).unwrap();
-        self.connector().find_many(self, model, finder, mutation_mode, action, action_source).await
+        let finder = self.scoped_finder(model, finder)?;
+        let finder = model.finder_with_default_order_by(finder.as_ref());
+        Self::trigger_before_find_callbacks(model, finder.as_ref()).await?;
+        if model.r#virtual() {
+            return self.find_many_virtual(model, finder.as_ref(), mutation_mode, action, action_source).await.map(|(objects, _)| objects);
+        }
+        model.connector().find_many(self, model, finder.as_ref(), mutation_mode, action, action_source).await
+    }
+
+    pub(crate) async fn find_many_and_count_internal(&self, model: &str, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<(Vec<Object>, usize)> {
+        let model = self.model(model).unwrap();
+        let finder = self.scoped_finder(model, finder)?;
+        let finder = model.finder_with_default_order_by(finder.as_ref());
+        Self::trigger_before_find_callbacks(model, finder.as_ref()).await?;
+        if model.r#virtual() {
+            return self.find_many_virtual(model, finder.as_ref(), mutation_mode, action, action_source).await;
+        }
+        model.connector().find_many_and_count(self, model, finder.as_ref(), mutation_mode, action, action_source).await
+    }
+
+    /// Virtual models aren't stored in a database which could evaluate their
+    /// filters. Their objects are read unfiltered, then filtered and paged in
+    /// memory. Returns the page and the count of all matched objects.
+    async fn find_many_virtual(&self, model: &Model, finder: &Value, mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<(Vec<Object>, usize)> {
+        let mut unfiltered = finder.as_hashmap().unwrap().clone();
+        for key in ["where", "cursor", "skip", "take", "pageSize", "pageNumber"] {
+            unfiltered.remove(key);
+        }
+        let objects = model.connector().find_many(self, model, &Value::HashMap(unfiltered), mutation_mode, action, action_source).await?;
+        let mut objects: Vec<Object> = match finder.get("where") {
+            Some(r#where) => objects.into_iter().filter(|o| o.matches_where(r#where)).collect(),
+            None => objects,
+        };
+        let count = objects.len();
+        let take = finder.get("take").map(|t| t.as_i64().unwrap());
+        if let (Some(cursor), Some(order_by)) = (finder.get("cursor"), finder.get("orderBy")) {
+            let cursor_where = cursor_where(cursor, order_by, take.map_or(false, |t| t < 0));
+            objects.retain(|o| o.matches_where(&cursor_where));
+        }
+        let (skip, page_take) = match (finder.get("pageSize"), finder.get("pageNumber")) {
+            (Some(page_size), Some(page_number)) => {
+                let page_size = page_size.as_i64().unwrap() as usize;
+                ((page_number.as_i64().unwrap() as usize - 1) * page_size, Some(page_size))
+            }
+            _ => (finder.get("skip").map_or(0, |s| s.as_i64().unwrap() as usize), take.map(|t| t.unsigned_abs() as usize)),
+        };
+        // a negative take pages backwards from the end
+        if take.map_or(false, |t| t < 0) {
+            objects.reverse();
+        }
+        let mut objects: Vec<Object> = objects.into_iter().skip(skip).take(page_take.unwrap_or(usize::MAX)).collect();
+        if take.map_or(false, |t| t < 0) {
+            objects.reverse();
+        }
+        Ok((objects, count))
+    }
+
+    /// Scopes the finder of `model` with `Model::scoped_finder`, and the
+    /// includes and relation filters in it with the related models, so that
+    /// soft deleted related records are left out, too.
+    fn scoped_finder<'a>(&self, model: &Model, finder: &'a Value) -> Result<Cow<'a, Value>> {
+        let mut finder = model.scoped_finder(finder)?.into_owned();
+        if let Some(map) = finder.as_hashmap_mut() {
+            if let Some(r#where) = map.get("where") {
+                let r#where = self.scoped_where(model, r#where);
+                map.insert("where".to_owned(), r#where);
+            }
+            if let Some(Value::HashMap(include)) = map.get("include") {
+                let include = include.iter().map(|(key, value)| {
+                    let value = match model.relation(key).and_then(|r| self.model(r.model())) {
+                        Some(related) if value.is_hashmap() => self.scoped_finder(related, value)?.into_owned(),
+                        Some(related) if related.soft_delete_field().is_some() && value.as_bool() == Some(true) => self.scoped_finder(related, &teon!({}))?.into_owned(),
+                        _ => value.clone(),
+                    };
+                    Ok((key.clone(), value))
+                }).collect::<Result<HashMap<String, Value>>>()?;
+                map.insert("include".to_owned(), Value::HashMap(include));
+            }
+        }
+        Ok(Cow::Owned(finder))
+    }
+
+    fn scoped_where(&self, model: &Model, r#where: &Value) -> Value {
+        let map = match r#where.as_hashmap() {
+            Some(map) => map,
+            None => return r#where.clone(),
+        };
+        Value::HashMap(map.iter().map(|(key, value)| {
+            let value = match (key.as_str(), value) {
+                ("AND" | "OR", Value::Vec(vec)) => Value::Vec(vec.iter().map(|w| self.scoped_where(model, w)).collect()),
+                ("AND" | "OR" | "NOT", _) => self.scoped_where(model, value),
+                _ => match model.relation(key).and_then(|r| self.model(r.model())) {
+                    Some(related) => self.scoped_relation_filter(related, value),
+                    None => value.clone(),
+                },
+            };
+            (key.clone(), value)
+        }).collect())
+    }
+
+    /// A relation filter like `{"some": {..}}` only matches related records
+    /// which aren't soft deleted, `every` ignores the deleted ones.
+    fn scoped_relation_filter(&self, related: &Model, filter: &Value) -> Value {
+        let map = match filter.as_hashmap() {
+            Some(map) => map,
+            None => return filter.clone(),
+        };
+        Value::HashMap(map.iter().map(|(op, r#where)| {
+            let r#where = self.scoped_where(related, r#where);
+            let r#where = match related.soft_delete_field() {
+                Some(key) if op == "every" => teon!({"OR": [r#where, {key: {"not": null}}]}),
+                Some(key) => teon!({"AND": [r#where, {key: {"equals": null}}]}),
+                None => r#where,
+            };
+            (op.clone(), r#where)
+        }).collect())
+    }
+
+    async fn trigger_before_find_callbacks(model: &Model, finder: &Value) -> Result<()> {
+        let ctx = Ctx::initial_state_with_value(finder.clone());
+        model.before_find_pipeline().process_into_permission_result(ctx).await
     }
 
     pub(crate) async fn batch<F, Fut>(&self, model: &str, finder: &Value, action: Action, action_source: ActionSource, f: F) -> Result<()> where
@@ -126,17 +274,30 @@ impl Graph {
 
     pub(crate) async fn count(&self, model: &str, finder: &Value) -> Result<usize> {
         let model = self.model(model).unwrap();
-        self.connector().count(self, model, finder).await
+        let finder = self.scoped_finder(model, finder)?;
+        model.connector().count(self, model, finder.as_ref()).await
     }
 
     pub(crate) async fn aggregate(&self, model: &str, finder: &Value) -> Result<Value> {
         let model = self.model(model).unwrap();
-        self.connector().aggregate(self, model, finder).await
+        let finder = self.scoped_finder(model, finder)?;
+        model.connector().aggregate(self, model, finder.as_ref()).await
     }
 
     pub(crate) async fn group_by(&self, model: &str, finder: &Value) -> Result<Value> {
         let model = self.model(model).unwrap();
-        self.connector().group_by(self, model, finder).await
+        let finder = self.scoped_finder(model, finder)?;
+        model.connector().group_by(self, model, finder.as_ref()).await
+    }
+
+    pub(crate) async fn facets(&self, model: &str, finder: &Value) -> Result<Vec<(Value, usize)>> {
+        let mut finder = finder.clone();
+        let map = finder.as_hashmap_mut().unwrap();
+        let field = map.remove("field").unwrap().as_str().unwrap().to_owned();
+        map.insert("by".to_owned(), Value::Vec(vec![Value::String(field.clone())]));
+        map.insert("_count".to_owned(), Value::HashMap(HashMap::from([("_all".to_owned(), Value::Bool(true))])));
+        let groups = self.group_by(model, &finder).await?;
+        Ok(facet_counts(&field, &groups))
     }
 
     // MARK: - Create an object
@@ -160,6 +321,21 @@ impl Graph {
         Ok(obj)
     }
 
+    /// The timezone in which date-only datetime inputs are interpreted.
+    pub(crate) fn default_timezone(&self) -> &FixedOffset {
+        &self.inner.default_timezone
+    }
+
+    /// The naming convention of model keys in action results.
+    pub(crate) fn field_naming(&self) -> FieldNaming {
+        self.inner.field_naming
+    }
+
+    /// The format of date times in action results.
+    pub(crate) fn date_time_format(&self) -> &DateTimeFormat {
+        &self.inner.date_time_format
+    }
+
     // MARK: - Getting the connector
 
     pub(crate) fn connector(&self) -> &dyn Connector {
@@ -169,19 +345,20 @@ impl Graph {
         }
     }
 
-    pub(crate) fn connector_mut(&self) -> &mut dyn Connector {
-        match &self.inner.connector {
-            Some(c) => {
-                let r = c.as_ref();
-                let result = unsafe {
-                    let d: * const dyn Connector = r;
-                    let e: * mut dyn Connector = d as *mut dyn Connector;
-                    &mut *e
-                };
-                result
-            }
-            None => { panic!() }
+    /// The connectors declared with a name, keyed by it.
+    pub(crate) fn named_connectors(&self) -> &HashMap<String, Arc<dyn Connector>> {
+        &self.inner.connectors
+    }
+
+    /// Each connector along with the models it stores, the default connector
+    /// first.
+    pub(crate) fn connectors_with_models(&self) -> Vec<(&dyn Connector, Vec<Model>)> {
+        let mut result = vec![(self.connector(), self.models().iter().filter(|m| m.connector_name().is_none()).cloned().collect())];
+        for (name, connector) in self.inner.connectors.iter() {
+            let models = self.models().iter().filter(|m| m.connector_name() == Some(name.as_str())).cloned().collect();
+            result.push((connector.as_ref(), models));
         }
+        result
     }
 
     pub(crate) fn model(&self, name: &str) -> Option<&Model> {
@@ -195,6 +372,19 @@ impl Graph {
         }
     }
 
+    /// Find the model served at the path components before `action`. Custom routes
+    /// take precedence over URL segment names.
+    pub(crate) fn model_with_route(&self, path_components: &[&str]) -> Option<&Model> {
+        if let Some(model) = self.models().iter().find(|m| m.route().map_or(false, |r| r.matches(path_components))) {
+            return Some(model);
+        }
+        if path_components.len() == 1 {
+            self.model_with_url_segment_name(path_components[0])
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn r#enum(&self, name: &str) -> Option<&Enum> {
         self.inner.enums.get(name)
     }
@@ -262,5 +452,720 @@ impl Graph {
     }
 }
 
+fn facet_counts(field: &str, groups: &Value) -> Vec<(Value, usize)> {
+    let mut counts: Vec<(Value, usize)> = groups.as_vec().unwrap().iter().map(|group| {
+        let value = group.get(field).cloned().unwrap_or(Value::Null);
+        let count = group.get("_count").and_then(|c| c.get("_all")).and_then(|c| c.as_i64()).unwrap_or(0) as usize;
+        (value, count)
+    }).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1));
+    counts
+}
+
 unsafe impl Send for Graph { }
 unsafe impl Sync for Graph { }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::core::field::{Field, FieldIndex, IndexSettings, Sort};
+    use crate::core::field::optionality::Optionality;
+    use crate::core::field::read_rule::ReadRule;
+    use crate::core::field::r#type::FieldType;
+    use crate::core::action::{CREATE_HANDLER, FIND_FIRST_HANDLER, UPDATE_HANDLER};
+    use crate::core::teon::decoder::Decoder;
+    use crate::core::teon::diff::FieldChange;
+    use crate::core::test_connector::TestConnector;
+    use crate::core::error::ErrorType;
+    use crate::core::test_fixtures::{id_field, users_and_posts, UsersAndPosts};
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::model::not_found::NotFound;
+    use crate::core::pipeline::Pipeline;
+    use crate::core::pipeline::items::function::perform::PerformItem;
+    use crate::core::pipeline::items::object::ctx_self::SelfItem;
+    use crate::core::pipeline::items::string::transform::to_lower_case::ToLowerCaseItem;
+    use crate::core::pipeline::items::string::transform::to_upper_case::ToUpperCaseItem;
+    use crate::core::pipeline::items::value::one_of::OneOfItem;
+    use crate::core::relation::delete_rule::DeleteRule;
+    use crate::teon;
+    use super::*;
+
+    /// Deletes user 1 with the rule on the posts' author relation. Returns
+    /// the connector and the result of the deletion.
+    async fn delete_author(delete_rule: DeleteRule, posts: Vec<Value>) -> (Arc<TestConnector>, Result<()>) {
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Post".to_owned(), posts)]),
+            ..Default::default()
+        });
+        let graph = UsersAndPosts { delete_rule, ..Default::default() }.build(connector.clone()).await;
+        let user = graph.create_object("User", teon!({"id": 1})).await.unwrap();
+        user.save().await.unwrap();
+        let result = user.delete().await;
+        (connector, result)
+    }
+
+    #[tokio::test]
+    async fn nested_relation_inputs_are_decoded() {
+        let graph = UsersAndPosts { delete_rule: DeleteRule::Nullify, ..Default::default() }.build(Arc::new(TestConnector::default())).await;
+        let user = graph.model("User").unwrap();
+        for nested in [
+            serde_json::json!({"create": {"id": 2}}),
+            serde_json::json!({"connect": {"id": 2}}),
+            serde_json::json!({"connectOrCreate": {"where": {"id": 2}, "create": {"id": 2}}}),
+        ] {
+            let input = serde_json::json!({"create": {"id": 1, "posts": nested}});
+            assert!(Decoder::decode_action_arg(user, &graph, Action::from_u32(CREATE_HANDLER), &input).is_ok());
+        }
+        let input = serde_json::json!({"where": {"id": 1}, "update": {"posts": {"disconnect": {"id": 2}, "delete": {"id": 3}}}});
+        assert!(Decoder::decode_action_arg(user, &graph, Action::from_u32(UPDATE_HANDLER), &input).is_ok());
+    }
+
+    #[tokio::test]
+    async fn setting_a_to_one_relation_to_null_disconnects_it() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = UsersAndPosts { delete_rule: DeleteRule::Default, ..Default::default() }.build(connector.clone()).await;
+        let post = graph.create_object("Post", teon!({"id": 1, "authorId": 1})).await.unwrap();
+        post.save().await.unwrap();
+        post.set_teon(&teon!({"author": {"set": null}})).await.unwrap();
+        post.save().await.unwrap();
+        assert_eq!(connector.writes.lock().unwrap().last().unwrap().get("authorId"), Some(&Value::Null));
+    }
+
+    #[tokio::test]
+    async fn setting_a_to_many_relation_keeps_exactly_the_listed_records() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = UsersAndPosts { delete_rule: DeleteRule::Default, ..Default::default() }.build(connector.clone()).await;
+        let user = graph.create_object("User", teon!({"id": 1})).await.unwrap();
+        user.save().await.unwrap();
+        for post in [teon!({"id": 1, "authorId": 1}), teon!({"id": 2, "authorId": 1}), teon!({"id": 3})] {
+            graph.create_object("Post", post).await.unwrap().save().await.unwrap();
+        }
+        user.set_teon(&teon!({"posts": {"set": [{"id": 2}, {"id": 3}, {"id": 3}]}})).await.unwrap();
+        user.save().await.unwrap();
+        let posts: Vec<Object> = graph.find_many("Post", &teon!({"where": {"authorId": 1}})).await.unwrap();
+        let mut ids: Vec<Value> = posts.iter().map(|p| p.get_value("id").unwrap()).collect();
+        ids.sort_by(|a, b| a.as_i64().cmp(&b.as_i64()));
+        assert_eq!(ids, vec![teon!(2), teon!(3)]);
+        let post: Object = graph.find_unique("Post", &teon!({"where": {"id": 1}})).await.unwrap();
+        assert_eq!(post.get_value("authorId").unwrap(), Value::Null);
+    }
+
+    #[tokio::test]
+    async fn new_object_cannot_disconnect_a_relation() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = UsersAndPosts { delete_rule: DeleteRule::Default, ..Default::default() }.build(connector.clone()).await;
+        let post = graph.create_object("Post", teon!({"id": 1, "author": {"disconnect": true}})).await.unwrap();
+        let err = post.save().await.unwrap_err();
+        assert_eq!(err.errors.unwrap().get("author.disconnect").unwrap(), "A new object has no relations to disconnect.");
+        assert!(connector.operations().is_empty());
+    }
+
+    #[tokio::test]
+    async fn no_action_leaves_related_records() {
+        let (connector, result) = delete_author(DeleteRule::Default, vec![teon!({"id": 1, "authorId": 1})]).await;
+        assert!(result.is_ok());
+        assert_eq!(connector.operations(), vec!["save User", "delete User"]);
+    }
+
+    #[tokio::test]
+    async fn set_null_clears_foreign_keys_of_related_records() {
+        let (connector, result) = delete_author(DeleteRule::Nullify, vec![teon!({"id": 1, "authorId": 1})]).await;
+        assert!(result.is_ok());
+        assert_eq!(connector.operations(), vec!["save User", "delete User", "find Post", "save Post"]);
+        assert_eq!(connector.writes.lock().unwrap().last().unwrap().get("authorId"), Some(&Value::Null));
+        assert_deleted_in_one_session(&connector);
+    }
+
+    #[tokio::test]
+    async fn cascade_deletes_related_records() {
+        let (connector, result) = delete_author(DeleteRule::Cascade, vec![teon!({"id": 1, "authorId": 1})]).await;
+        assert!(result.is_ok());
+        assert_eq!(connector.operations(), vec!["save User", "delete User", "find Post", "delete Post"]);
+        assert_eq!(connector.finders.lock().unwrap()[0].get("where"), Some(&teon!({"authorId": 1})));
+        assert_deleted_in_one_session(&connector);
+    }
+
+    /// Related records are changed in the session of the deletion, which
+    /// follows the author's save.
+    fn assert_deleted_in_one_session(connector: &TestConnector) {
+        let sessions = connector.sessions.lock().unwrap();
+        assert!(sessions[1..].iter().all(|s| *s == sessions[1]));
+    }
+
+    #[tokio::test]
+    async fn restrict_denies_deleting_a_record_with_related_records() {
+        let (connector, result) = delete_author(DeleteRule::Deny, vec![teon!({"id": 1, "authorId": 1})]).await;
+        assert_eq!(result.unwrap_err().message, "Deletion denied by `posts', its records require the relation.");
+        assert_eq!(connector.operations(), vec!["save User", "count Post"]);
+        assert_eq!(connector.finders.lock().unwrap()[0].get("where"), Some(&teon!({"authorId": 1})));
+        let (connector, result) = delete_author(DeleteRule::Deny, vec![]).await;
+        assert!(result.is_ok());
+        assert_eq!(connector.operations(), vec!["save User", "count Post", "delete User"]);
+    }
+
+    #[tokio::test]
+    async fn decimals_the_database_cannot_store_are_rejected_when_decoded() {
+        let connector = Arc::new(TestConnector { decimal_digits: Some(34), ..Default::default() });
+        let mut builder = GraphBuilder::new();
+        builder.model("Product", |m| {
+            let mut price = Field::new("price".to_owned());
+            price.field_type = Some(FieldType::Decimal);
+            m.field(id_field()).field(price);
+        });
+        let graph = builder.build(connector).await;
+        let product = graph.model("Product").unwrap();
+        let input = serde_json::json!({"create": {"id": 1, "price": "1.234567890123456789012345678901234"}});
+        assert!(Decoder::decode_action_arg(product, &graph, Action::from_u32(CREATE_HANDLER), &input).is_ok());
+        let input = serde_json::json!({"create": {"id": 1, "price": "1.23456789012345678901234567890123456"}});
+        let err = Decoder::decode_action_arg(product, &graph, Action::from_u32(CREATE_HANDLER), &input).unwrap_err();
+        assert!(err.errors.unwrap().contains_key("create.price"));
+        let input = serde_json::json!({"where": {"id": 1}, "update": {"price": {"set": "1.23456789012345678901234567890123456"}}});
+        let err = Decoder::decode_action_arg(product, &graph, Action::from_u32(UPDATE_HANDLER), &input).unwrap_err();
+        assert!(err.errors.unwrap().contains_key("update.price.set"));
+    }
+
+    #[tokio::test]
+    async fn saving_a_value_duplicated_within_its_scope_fails() {
+        let connector = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.model("Page", |m| {
+            let mut tenant_id = Field::new("tenantId".to_owned());
+            tenant_id.field_type = Some(FieldType::I32);
+            let mut slug = Field::new("slug".to_owned());
+            slug.field_type = Some(FieldType::String);
+            slug.unique_within = Some(vec!["tenantId".to_owned()]);
+            slug.on_save_pipeline = Pipeline { items: vec![Arc::new(ToLowerCaseItem::new())] };
+            m.field(id_field()).field(tenant_id).field(slug);
+        });
+        let graph = builder.build(connector.clone()).await;
+        let about = graph.create_object("Page", teon!({"id": 1, "tenantId": 1, "slug": "about"})).await.unwrap();
+        about.save().await.unwrap();
+        // the same slug is unique within another tenant
+        let other = graph.create_object("Page", teon!({"id": 2, "tenantId": 2, "slug": "about"})).await.unwrap();
+        other.save().await.unwrap();
+        // the onSave pipeline still runs, and its output is checked
+        let duplicate = graph.create_object("Page", teon!({"id": 3, "tenantId": 1, "slug": "About"})).await.unwrap();
+        let err = duplicate.save().await.unwrap_err();
+        assert_eq!(err.message, "Unique value duplicated.");
+        assert_eq!(err.errors, Some(HashMap::from([("slug".to_owned(), "value is not unique".to_owned())])));
+        // saving the record again doesn't conflict with itself
+        about.set_teon(&teon!({"slug": "ABOUT"})).await.unwrap();
+        about.save().await.unwrap();
+        assert_eq!(connector.operations().iter().filter(|o| *o == "save Page").count(), 3);
+    }
+
+    #[tokio::test]
+    async fn models_on_different_connectors_are_handled_independently() {
+        let default = Arc::new(TestConnector::default());
+        let analytics = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.connectors.insert("analytics".to_owned(), analytics.clone());
+        builder.model("User", |m| { m.field(id_field()); });
+        builder.model("Event", |m| { m.connector("analytics").field(id_field()); });
+        let graph = builder.build(default.clone()).await;
+        for name in ["User", "Event"] {
+            let object = graph.create_object(name, teon!({"id": 1})).await.unwrap();
+            object.save().await.unwrap();
+            graph.count(name, &teon!({})).await.unwrap();
+            graph.find_many_internal(name, &teon!({}), false, Action::from_u32(PROGRAM_CODE | INTERNAL_AMOUNT | INTERNAL_POSITION), ActionSource::ProgramCode).await.unwrap();
+            object.delete().await.unwrap();
+        }
+        crate::core::app::migrate::migrate(&graph, false).await.unwrap();
+        assert_eq!(default.operations(), vec!["save User", "count User", "find User", "delete User", "migrate User"]);
+        assert_eq!(analytics.operations(), vec!["save Event", "count Event", "find Event", "delete Event", "migrate Event"]);
+    }
+
+    #[tokio::test]
+    async fn facets_count_enum_values_under_a_filter() {
+        let connector = Arc::new(TestConnector {
+            groups: vec![
+                teon!({"status": "ACTIVE", "_count": {"_all": 2}}),
+                teon!({"status": "PENDING", "_count": {"_all": 5}}),
+                teon!({"status": "CLOSED", "_count": {"_all": 3}}),
+            ],
+            ..Default::default()
+        });
+        let mut builder = GraphBuilder::new();
+        builder.r#enum("Status", |e| { e.choice("ACTIVE", |_| {}).choice("PENDING", |_| {}).choice("CLOSED", |_| {}); });
+        builder.model("Ticket", |m| {
+            let mut status = Field::new("status".to_owned());
+            status.field_type = Some(FieldType::Enum("Status".to_owned()));
+            m.field(id_field()).field(status);
+        });
+        let graph = builder.build(connector.clone()).await;
+        let facets = graph.facets("Ticket", &teon!({"field": "status", "where": {"id": {"gt": 10}}})).await.unwrap();
+        assert_eq!(facets, vec![
+            (Value::String("PENDING".to_owned()), 5),
+            (Value::String("CLOSED".to_owned()), 3),
+            (Value::String("ACTIVE".to_owned()), 2),
+        ]);
+        let finder = connector.finders.lock().unwrap()[0].clone();
+        assert_eq!(finder.get("where"), Some(&teon!({"id": {"gt": 10}})));
+        assert_eq!(finder.get("by"), Some(&teon!(["status"])));
+        assert_eq!(connector.operations(), vec!["groupBy Ticket"]);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Default value 'ARCHIVED' of field 'status' of model 'Ticket' is not a member of enum 'Status'.")]
+    async fn enum_field_default_must_be_a_member_of_the_enum() {
+        let mut builder = GraphBuilder::new();
+        builder.r#enum("Status", |e| { e.choice("ACTIVE", |_| {}).choice("CLOSED", |_| {}); });
+        builder.model("Ticket", |m| {
+            let mut status = Field::new("status".to_owned());
+            status.field_type = Some(FieldType::Enum("Status".to_owned()));
+            status.default = Some(Value::RawEnumChoice("ARCHIVED".to_owned(), None));
+            m.field(id_field()).field(status);
+        });
+        builder.build(Arc::new(TestConnector::default())).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Constraint value 'ARCHIVED' of field 'status' of model 'Ticket' is not a member of enum 'Status'.")]
+    async fn enum_field_constraint_values_must_be_members_of_the_enum() {
+        let mut builder = GraphBuilder::new();
+        builder.r#enum("Status", |e| { e.choice("ACTIVE", |_| {}).choice("CLOSED", |_| {}); });
+        builder.model("Ticket", |m| {
+            let mut status = Field::new("status".to_owned());
+            status.field_type = Some(FieldType::Enum("Status".to_owned()));
+            let choices = Value::Vec(vec![Value::RawEnumChoice("ACTIVE".to_owned(), None), Value::RawEnumChoice("ARCHIVED".to_owned(), None)]);
+            status.on_set_pipeline = Pipeline { items: vec![Arc::new(OneOfItem::new(choices))] };
+            m.field(id_field()).field(status);
+        });
+        builder.build(Arc::new(TestConnector::default())).await;
+    }
+
+    #[tokio::test]
+    async fn virtual_models_are_filtered_and_paged_in_memory() {
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Score".to_owned(), vec![
+                teon!({"name": "Alice", "points": 31}),
+                teon!({"name": "bob", "points": 17}),
+                teon!({"name": "Carol", "points": 45}),
+                teon!({"name": "Dave", "points": 52}),
+            ])]),
+            ..Default::default()
+        });
+        let mut builder = GraphBuilder::new();
+        builder.model("Score", |m| {
+            let mut name = Field::new("name".to_owned());
+            name.field_type = Some(FieldType::String);
+            let mut points = Field::new("points".to_owned());
+            points.field_type = Some(FieldType::I32);
+            m.r#virtual().field(name).field(points);
+        });
+        let graph = builder.build(connector.clone()).await;
+        let names = |objects: Vec<Object>| objects.iter().map(|o| o.get_value("name").unwrap()).collect::<Vec<Value>>();
+        let scores: Vec<Object> = graph.find_many("Score", &teon!({"where": {"points": {"gt": 20}}, "skip": 1, "take": 1})).await.unwrap();
+        assert_eq!(names(scores), vec![Value::String("Carol".to_owned())]);
+        let (scores, count): (Vec<Object>, usize) = graph.find_many_and_count("Score", &teon!({"where": {"name": {"startsWith": "b", "mode": "caseInsensitive"}}})).await.unwrap();
+        assert_eq!(names(scores), vec![Value::String("bob".to_owned())]);
+        assert_eq!(count, 1);
+        let score: Object = graph.find_first("Score", &teon!({"where": {"points": {"lt": 30}}})).await.unwrap();
+        assert_eq!(score.get_value("name").unwrap(), Value::String("bob".to_owned()));
+        // the connector isn't asked to filter
+        assert!(connector.finders.lock().unwrap().iter().all(|f| f.get("where").is_none()));
+    }
+
+    #[tokio::test]
+    async fn missing_object_responds_by_the_model_not_found_setting() {
+        let connector = Arc::new(TestConnector::default());
+        let find_first = Action::from_u32(FIND_FIRST_HANDLER);
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            m.field(id_field());
+            m.not_found(find_first, NotFound::Null);
+        });
+        builder.model("Post", |m| {
+            m.field(id_field());
+        });
+        let graph = builder.build(connector.clone()).await;
+        let err = graph.find_first_internal("User", &teon!({"where": {"id": 1}}), false, find_first, ActionSource::ProgramCode).await.unwrap_err();
+        assert_eq!(graph.model("User").unwrap().not_found(find_first).resolve(err).unwrap(), Value::Null);
+        let err = graph.find_first_internal("Post", &teon!({"where": {"id": 1}}), false, find_first, ActionSource::ProgramCode).await.unwrap_err();
+        let err = graph.model("Post").unwrap().not_found(find_first).resolve(err).unwrap_err();
+        assert_eq!(err.r#type.code(), 404);
+    }
+
+    #[tokio::test]
+    async fn unique_indices_of_optional_fields_are_sparse_unless_set_otherwise() {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut email = Field::new("email".to_owned());
+            email.field_type = Some(FieldType::String);
+            email.optionality = Optionality::Optional;
+            email.index = Some(FieldIndex::Unique(IndexSettings::default()));
+            let mut phone = Field::new("phone".to_owned());
+            phone.field_type = Some(FieldType::String);
+            phone.optionality = Optionality::Optional;
+            phone.index = Some(FieldIndex::Unique(IndexSettings { sparse: Some(false), ..Default::default() }));
+            let mut name = Field::new("name".to_owned());
+            name.field_type = Some(FieldType::String);
+            name.index = Some(FieldIndex::Unique(IndexSettings::default()));
+            m.field(id_field()).field(email).field(phone).field(name);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let model = graph.model("User").unwrap();
+        let sparse = |field: &str| model.indices().iter().find(|i| i.items()[0].field_name() == field).unwrap().sparse();
+        assert!(sparse("email"));
+        assert!(!sparse("phone"));
+        assert!(!sparse("name"));
+        #[cfg(feature = "data-source-mongodb")] {
+            let email = model.indices().iter().find(|i| i.items()[0].field_name() == "email").unwrap();
+            let index_model = crate::connectors::mongodb::connector::MongoDBConnector::index_model(model, email);
+            assert_eq!(index_model.options.unwrap().sparse, Some(true));
+        }
+    }
+
+    #[tokio::test]
+    async fn unique_indices_of_soft_deleted_models_skip_tombstones() {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut email = Field::new("email".to_owned());
+            email.field_type = Some(FieldType::String);
+            email.index = Some(FieldIndex::Unique(IndexSettings::default()));
+            let mut name = Field::new("name".to_owned());
+            name.field_type = Some(FieldType::String);
+            name.index = Some(FieldIndex::Index(IndexSettings::default()));
+            let mut deleted_at = Field::new("deletedAt".to_owned());
+            deleted_at.field_type = Some(FieldType::DateTime);
+            deleted_at.optionality = Optionality::Optional;
+            m.field(id_field()).field(email).field(name).field(deleted_at).soft_delete("deletedAt");
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let model = graph.model("User").unwrap();
+        let index = |field: &str| model.indices().iter().find(|i| i.items()[0].field_name() == field).unwrap();
+        assert_eq!(index("email").soft_delete(), Some("deletedAt"));
+        assert_eq!(index("name").soft_delete(), None);
+        assert_eq!(index("id").soft_delete(), None);
+        #[cfg(feature = "data-source-mongodb")] {
+            let index_model = crate::connectors::mongodb::connector::MongoDBConnector::index_model(model, index("email"));
+            assert_eq!(index_model.keys, mongodb::bson::doc!{"email": 1, "deletedAt": 1});
+        }
+    }
+
+    #[tokio::test]
+    async fn field_naming_renames_model_keys_but_not_json_values() {
+        let mut builder = GraphBuilder::new();
+        builder.field_naming = FieldNaming::SnakeCase;
+        builder.model("User", |m| {
+            let mut first_name = Field::new("firstName".to_owned());
+            first_name.field_type = Some(FieldType::String);
+            let mut preferences = Field::new("preferences".to_owned());
+            preferences.field_type = Some(FieldType::Json);
+            m.field(id_field()).field(first_name).field(preferences);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let user = graph.create_object("User", teon!({"id": 1, "firstName": "Ann", "preferences": {"darkMode": true}})).await.unwrap();
+        let json: serde_json::Value = user.to_json_internal(&key_path::path![]).await.unwrap().into();
+        assert_eq!(json, serde_json::json!({"id": 1, "first_name": "Ann", "preferences": {"darkMode": true}}));
+    }
+
+    #[tokio::test]
+    async fn get_reads_on_get_fields_decoded_after_save_and_find() {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut secret = Field::new("secret".to_owned());
+            secret.field_type = Some(FieldType::String);
+            secret.on_set_pipeline.items.push(Arc::new(ToUpperCaseItem::new()));
+            secret.on_get_pipeline.items.push(Arc::new(ToLowerCaseItem::new()));
+            m.field(id_field()).field(secret);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let user = graph.create_object("User", teon!({"id": 1, "secret": "abc"})).await.unwrap();
+        user.save().await.unwrap();
+        assert_eq!(user.get_value("secret").unwrap(), teon!("ABC"));
+        assert_eq!(user.get::<Value>("secret").unwrap(), teon!("abc"));
+        let user: Object = graph.find_unique("User", &teon!({"where": {"id": 1}})).await.unwrap();
+        assert_eq!(user.get::<Value>("secret").unwrap(), teon!("abc"));
+        assert!(graph.model("User").unwrap().query_keys().iter().all(|k| k != "secret"));
+    }
+
+    #[tokio::test]
+    async fn nested_object_is_saved_before_the_object_holding_the_foreign_key() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = users_and_posts(connector.clone()).await;
+        let user = graph.create_object("User", teon!({"id": 1})).await.unwrap();
+        let post = graph.create_object("Post", teon!({"id": 2, "author": Value::Object(user)})).await.unwrap();
+        post.save().await.unwrap();
+        assert_eq!(*connector.operations.lock().unwrap(), vec!["save User".to_owned(), "save Post".to_owned()]);
+        assert_eq!(post.get_value("authorId").unwrap(), teon!(1));
+    }
+
+    #[tokio::test]
+    async fn nested_objects_holding_the_foreign_key_are_saved_after_the_object() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = users_and_posts(connector.clone()).await;
+        let post = graph.create_object("Post", teon!({"id": 2})).await.unwrap();
+        let user = graph.create_object("User", teon!({"id": 1, "posts": [Value::Object(post.clone())]})).await.unwrap();
+        user.save().await.unwrap();
+        assert_eq!(*connector.operations.lock().unwrap(), vec!["save User".to_owned(), "save Post".to_owned()]);
+        assert_eq!(post.get_value("authorId").unwrap(), teon!(1));
+    }
+
+    #[tokio::test]
+    async fn diff_is_taken_against_the_last_saved_values() {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut name = Field::new("name".to_owned());
+            name.field_type = Some(FieldType::String);
+            m.field(id_field()).field(name);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        graph.create_object("User", teon!({"id": 1, "name": "Ann"})).await.unwrap().save().await.unwrap();
+        let user: Object = graph.find_unique("User", &teon!({"where": {"id": 1}})).await.unwrap();
+        user.set_value("name", teon!("Anna")).unwrap();
+        assert_eq!(user.diff(), vec![FieldChange { field: "name".to_owned(), old: teon!("Ann"), new: teon!("Anna") }]);
+        user.save().await.unwrap();
+        assert!(user.diff().is_empty());
+        user.set_value("name", teon!("Hanna")).unwrap();
+        assert_eq!(user.diff(), vec![FieldChange { field: "name".to_owned(), old: teon!("Anna"), new: teon!("Hanna") }]);
+        user.save().await.unwrap();
+        assert!(user.diff().is_empty());
+    }
+
+    #[tokio::test]
+    async fn subtypes_are_created_with_and_found_by_their_discriminator() {
+        // records of all subtypes share the base model's table
+        let connector = Arc::new(TestConnector {
+            rows: HashMap::from([("Dog".to_owned(), vec![teon!({"id": 1, "kind": "dog", "name": "Max"}), teon!({"id": 2, "kind": "cat", "name": "Max"})])]),
+            ..Default::default()
+        });
+        let mut builder = GraphBuilder::new();
+        builder.model("Animal", |m| {
+            let mut kind = Field::new("kind".to_owned());
+            kind.field_type = Some(FieldType::String);
+            let mut name = Field::new("name".to_owned());
+            name.field_type = Some(FieldType::String);
+            m.field(id_field()).field(kind).field(name).discriminator("kind");
+        });
+        builder.model("Dog", |m| { m.extends("Animal", "dog"); });
+        let graph = builder.build(connector.clone()).await;
+        let ids = |dogs: Vec<Object>| dogs.iter().map(|d| d.get_value("id").unwrap()).collect::<Vec<Value>>();
+        let dogs: Vec<Object> = graph.find_many("Dog", &teon!({})).await.unwrap();
+        assert_eq!(ids(dogs), vec![teon!(1)]);
+        let dogs: Vec<Object> = graph.find_many("Dog", &teon!({"where": {"name": "Max"}})).await.unwrap();
+        assert_eq!(ids(dogs), vec![teon!(1)]);
+        let dog = graph.create_object("Dog", teon!({"id": 3, "name": "Rex"})).await.unwrap();
+        dog.save().await.unwrap();
+        assert_eq!(connector.writes.lock().unwrap().last().unwrap().get("kind"), Some(&teon!("dog")));
+    }
+
+    #[tokio::test]
+    async fn soft_deleted_records_are_hidden_unless_requested_with_deleted() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = UsersAndPosts { soft_delete: true, ..Default::default() }.build(connector.clone()).await;
+        for id in [1, 2] {
+            graph.create_object("User", teon!({"id": id})).await.unwrap().save().await.unwrap();
+        }
+        let user: Object = graph.find_unique("User", &teon!({"where": {"id": 1}})).await.unwrap();
+        user.delete().await.unwrap();
+        let ids = |users: Vec<Object>| users.iter().map(|u| u.get_value("id").unwrap()).collect::<Vec<Value>>();
+        let users: Vec<Object> = graph.find_many("User", &teon!({})).await.unwrap();
+        assert_eq!(ids(users), vec![teon!(2)]);
+        assert!(graph.find_unique::<Object>("User", &teon!({"where": {"id": 1}})).await.is_err());
+        let users: Vec<Object> = graph.find_many("User", &teon!({"withDeleted": true})).await.unwrap();
+        assert_eq!(ids(users), vec![teon!(1), teon!(2)]);
+        let error = graph.find_many::<Object>("User", &teon!({"withDeleted": "yes"})).await.unwrap_err();
+        assert_eq!(error.r#type, ErrorType::UnexpectedInputType);
+        let error = graph.find_many::<Object>("Post", &teon!({"include": {"author": {"withDeleted": 1}}})).await.unwrap_err();
+        assert_eq!(error.r#type, ErrorType::UnexpectedInputType);
+    }
+
+    #[tokio::test]
+    async fn includes_and_relation_filters_leave_out_soft_deleted_records() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = UsersAndPosts { soft_delete: true, ..Default::default() }.build(connector.clone()).await;
+        let finder = teon!({"where": {"posts": {"some": {"id": 1}}}, "include": {"posts": true}});
+        let _: Vec<Object> = graph.find_many("User", &finder).await.unwrap();
+        let finder = connector.finders.lock().unwrap().last().unwrap().clone();
+        assert_eq!(finder, teon!({
+            "where": {"AND": [{"posts": {"some": {"AND": [{"id": 1}, {"deletedAt": {"equals": null}}]}}}, {"deletedAt": {"equals": null}}]},
+            "include": {"posts": {"where": {"deletedAt": {"equals": null}}}},
+        }));
+        let finder = teon!({"where": {"posts": {"every": {"id": 1}}}});
+        let _ = graph.count("User", &finder).await.unwrap();
+        let finder = connector.finders.lock().unwrap().last().unwrap().clone();
+        assert_eq!(finder, teon!({
+            "where": {"AND": [{"posts": {"every": {"OR": [{"id": 1}, {"deletedAt": {"not": null}}]}}}, {"deletedAt": {"equals": null}}]},
+        }));
+    }
+
+    #[tokio::test]
+    async fn counter_caches_follow_create_reassign_and_delete() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = UsersAndPosts { counter_cache: Some(FieldType::I32), ..Default::default() }.build(connector.clone()).await;
+        for id in [1, 2] {
+            graph.create_object("User", teon!({"id": id, "postsCount": 0})).await.unwrap().save().await.unwrap();
+        }
+        let counts = || async {
+            let users: Vec<Object> = graph.find_many("User", &teon!({})).await.unwrap();
+            users.iter().map(|u| u.get_value("postsCount").unwrap()).collect::<Vec<Value>>()
+        };
+        let post = graph.create_object("Post", teon!({"id": 1, "authorId": 1})).await.unwrap();
+        post.save().await.unwrap();
+        assert_eq!(counts().await, vec![teon!(1), teon!(0)]);
+        post.set_value("authorId", teon!(2)).unwrap();
+        post.save().await.unwrap();
+        assert_eq!(counts().await, vec![teon!(0), teon!(1)]);
+        post.delete().await.unwrap();
+        assert_eq!(counts().await, vec![teon!(0), teon!(0)]);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Counter cache 'postsCount' of relation 'author' of model 'Post' should be an Int or Int64 field of model 'User'.")]
+    async fn counter_cache_must_be_an_int_field_of_the_related_model() {
+        UsersAndPosts { counter_cache: Some(FieldType::String), ..Default::default() }.build(Arc::new(TestConnector::default())).await;
+    }
+
+    #[tokio::test]
+    async fn read_if_hides_fields_and_is_only_run_for_guarded_keys() {
+        let evaluations = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = evaluations.clone();
+        let mut builder = GraphBuilder::new();
+        builder.model("Employee", |m| {
+            let mut name = Field::new("name".to_owned());
+            name.field_type = Some(FieldType::String);
+            let mut salary = Field::new("salary".to_owned());
+            salary.field_type = Some(FieldType::I64);
+            let counter = counter.clone();
+            salary.read_rule = ReadRule::ReadIf(Pipeline { items: vec![
+                Arc::new(PerformItem::new(move |value: Value| {
+                    counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    async move {
+                        if value.as_i64().unwrap() > 1000 {
+                            Err(Error::permission_error(key_path::path!["salary"], "hidden"))
+                        } else {
+                            Ok(())
+                        }
+                    }
+                })),
+            ] };
+            m.field(id_field()).field(name).field(salary);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let junior = graph.create_object("Employee", teon!({"id": 1, "name": "Ann", "salary": 500})).await.unwrap();
+        let senior = graph.create_object("Employee", teon!({"id": 2, "name": "Bob", "salary": 5000})).await.unwrap();
+        let junior: serde_json::Value = junior.to_json_internal(&key_path::path![]).await.unwrap().into();
+        let senior: serde_json::Value = senior.to_json_internal(&key_path::path![]).await.unwrap().into();
+        assert_eq!(junior, serde_json::json!({"id": 1, "name": "Ann", "salary": 500}));
+        assert_eq!(senior, serde_json::json!({"id": 2, "name": "Bob"}));
+        // only salary has a read predicate, id and name are output without one
+        assert_eq!(evaluations.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    async fn staff(fields: usize) -> Graph {
+        let mut builder = GraphBuilder::new();
+        builder.model("Admin", |m| { m.identity().field(id_field()); });
+        builder.model("Manager", |m| { m.identity().field(id_field()); });
+        builder.model("Employee", |m| {
+            let mut name = Field::new("name".to_owned());
+            name.field_type = Some(FieldType::String);
+            m.field(id_field()).field(name);
+            for index in 0..fields {
+                let mut salary = Field::new(if index == 0 { "salary".to_owned() } else { format!("salary{index}") });
+                salary.field_type = Some(FieldType::I64);
+                salary.optionality = Optionality::Optional;
+                salary.read_rule = ReadRule::ReadBy(vec!["Admin".to_owned()]);
+                m.field(salary);
+            }
+        });
+        builder.build(Arc::new(TestConnector::default())).await
+    }
+
+    #[tokio::test]
+    async fn read_by_fields_are_output_to_the_identity_roles_listed() {
+        let graph = staff(1).await;
+        let admin = graph.create_object("Admin", teon!({"id": 1})).await.unwrap();
+        let manager = graph.create_object("Manager", teon!({"id": 1})).await.unwrap();
+        let action = Action::from_u32(PROGRAM_CODE | CREATE | SINGLE | INTERNAL_POSITION);
+        let output = |source: ActionSource| {
+            let graph = graph.clone();
+            async move {
+                let employee = graph.new_object_with_tson_and_path("Employee", &teon!({"id": 1, "name": "Ann", "salary": 500}), &key_path::path![], action, source).await.unwrap();
+                serde_json::Value::from(employee.to_json_internal(&key_path::path![]).await.unwrap())
+            }
+        };
+        assert_eq!(output(ActionSource::Identity(Some(admin))).await, serde_json::json!({"id": 1, "name": "Ann", "salary": 500}));
+        assert_eq!(output(ActionSource::Identity(Some(manager))).await, serde_json::json!({"id": 1, "name": "Ann"}));
+        assert_eq!(output(ActionSource::Identity(None)).await, serde_json::json!({"id": 1, "name": "Ann"}));
+        assert_eq!(output(ActionSource::ProgramCode).await, serde_json::json!({"id": 1, "name": "Ann"}));
+    }
+
+    #[tokio::test]
+    async fn readable_projections_are_computed_once_per_role() {
+        let graph = staff(50).await;
+        let model = graph.model("Employee").unwrap();
+        let computed: HashSet<String> = model.fields().iter().filter(|f| f.read_rule.is_read_by(Some("Admin"))).map(|f| f.name().to_owned()).collect();
+        let admin = model.readable_output_keys(Some("Admin"));
+        assert_eq!(admin, &computed);
+        assert_eq!(admin.len(), 52);
+        // the projection stored on the model is returned for each output
+        assert!(std::ptr::eq(admin, model.readable_output_keys(Some("Admin"))));
+        assert!(std::ptr::eq(model.readable_output_keys(Some("Manager")), model.readable_output_keys(None)));
+        assert_eq!(model.readable_output_keys(None).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn date_time_format_applies_to_date_time_fields_but_not_json_values() {
+        let mut builder = GraphBuilder::new();
+        builder.date_time_format = DateTimeFormat::EpochMillis;
+        builder.model("Event", |m| {
+            let mut happened_at = Field::new("happenedAt".to_owned());
+            happened_at.field_type = Some(FieldType::DateTime);
+            let mut payload = Field::new("payload".to_owned());
+            payload.field_type = Some(FieldType::Json);
+            m.field(id_field()).field(happened_at).field(payload);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let event = graph.create_object("Event", teon!({"id": 1, "payload": {"$date": "2023-03-16T08:30:00.000Z"}})).await.unwrap();
+        event.set_value("happenedAt", Value::DateTime(chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2023, 3, 16, 8, 30, 0).unwrap())).unwrap();
+        let json: serde_json::Value = event.to_json_internal(&key_path::path![]).await.unwrap().into();
+        assert_eq!(json, serde_json::json!({"id": 1, "happenedAt": {"$date": 1678955400000i64}, "payload": {"$date": "2023-03-16T08:30:00.000Z"}}));
+    }
+
+    #[tokio::test]
+    async fn values_set_in_before_save_callbacks_are_written() {
+        let connector = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.model("Post", |m| {
+            let mut title = Field::new("title".to_owned());
+            title.field_type = Some(FieldType::String);
+            let mut slug = Field::new("slug".to_owned());
+            slug.field_type = Some(FieldType::String);
+            m.field(id_field()).field(title).field(slug);
+            m.before_save_pipeline = Pipeline { items: vec![
+                Arc::new(SelfItem::new()),
+                Arc::new(PerformItem::new(|object: Object| async move {
+                    let title: String = object.get("title").unwrap();
+                    object.set("slug", title.to_lowercase()).unwrap();
+                })),
+            ] };
+        });
+        let graph = builder.build(connector.clone()).await;
+        let object = graph.create_object("Post", teon!({"id": 1, "title": "Hello"})).await.unwrap();
+        object.save().await.unwrap();
+        object.set("title", "World").unwrap();
+        object.save().await.unwrap();
+        let writes = connector.writes.lock().unwrap();
+        assert_eq!(writes[0].get("slug"), Some(&Value::String("hello".to_owned())));
+        assert_eq!(writes[1].get("slug"), Some(&Value::String("world".to_owned())));
+    }
+
+    #[tokio::test]
+    async fn records_are_found_in_the_default_order_unless_ordered() {
+        let connector = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.model("Post", |m| {
+            let mut rank = Field::new("rank".to_owned());
+            rank.field_type = Some(FieldType::I32);
+            m.default_order_by([("rank", Sort::Desc), ("id", Sort::Asc)]).field(id_field()).field(rank);
+        });
+        let graph = builder.build(connector.clone()).await;
+        let _: Vec<Object> = graph.find_many("Post", &teon!({"take": 10})).await.unwrap();
+        let finder = connector.finders.lock().unwrap().last().unwrap().clone();
+        assert_eq!(finder.get("orderBy"), Some(&teon!([{"rank": "desc"}, {"id": "asc"}])));
+        let _: Vec<Object> = graph.find_many("Post", &teon!({"orderBy": [{"id": "desc"}]})).await.unwrap();
+        let finder = connector.finders.lock().unwrap().last().unwrap().clone();
+        assert_eq!(finder.get("orderBy"), Some(&teon!([{"id": "desc"}])));
+    }
+}
@@ -14,3 +14,7 @@ pub(crate) mod relation;
 pub(crate) mod property;
 pub(crate) mod input;
 pub(crate) mod action;
+#[cfg(test)]
+pub(crate) mod test_connector;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use inflector::Inflector;
 use to_mut::ToMut;
 use crate::core::action::{Action, CREATE_HANDLER, CREATE_MANY_HANDLER, IDENTITY_HANDLER, SIGN_IN_HANDLER};
@@ -7,6 +8,7 @@ use crate::core::connector::Connector;
 use crate::core::field::*;
 use crate::core::field::Field;
 use crate::core::field::r#type::FieldTypeOwner;
+use crate::core::field::read_rule::ReadRule;
 use crate::core::relation::Relation;
 use crate::core::property::Property;
 use crate::core::relation::delete_rule::DeleteRule;
@@ -14,17 +16,27 @@ use crate::core::model::index::{ModelIndex, ModelIndexItem, ModelIndexType};
 use crate::core::model::index::builder::{ModelIndexBuilder};
 use crate::core::model::{Model, ModelInner};
 use crate::core::model::migration::ModelMigration;
+use crate::core::model::not_found::NotFound;
+use crate::core::model::quota::ActionQuota;
+use crate::core::model::route::RoutePattern;
 use crate::core::pipeline::Pipeline;
+use crate::core::field::write_rule::WriteRule;
+use crate::parser::ast::config::FieldNaming;
+use crate::prelude::Value;
 
 pub struct ModelBuilder {
     pub(crate) name: String,
     pub(crate) table_name: String,
     pub(crate) url_segment_name: String,
+    pub(crate) route: Option<String>,
     pub(crate) localized_name: String,
     pub(crate) description: String,
     pub(crate) identity: bool,
     pub(crate) internal: bool,
     pub(crate) r#virtual: bool,
+    pub(crate) append_only: bool,
+    pub(crate) soft_delete: Option<String>,
+    pub(crate) connector: Option<String>,
     pub(crate) fields: Vec<Field>,
     pub(crate) dropped_fields: Vec<Field>,
     pub(crate) relations: Vec<Relation>,
@@ -33,13 +45,25 @@ pub struct ModelBuilder {
     pub(crate) indices: Vec<ModelIndex>,
     pub(crate) before_save_pipeline: Pipeline,
     pub(crate) after_save_pipeline: Pipeline,
+    pub(crate) validate_pipeline: Pipeline,
     pub(crate) before_delete_pipeline: Pipeline,
     pub(crate) after_delete_pipeline: Pipeline,
+    pub(crate) before_find_pipeline: Pipeline,
+    pub(crate) after_find_pipeline: Pipeline,
     pub(crate) can_read_pipeline: Pipeline,
     pub(crate) can_mutate_pipeline: Pipeline,
+    pub(crate) enabled_actions: Vec<Action>,
     pub(crate) disabled_actions: Option<Vec<Action>>,
     pub(crate) action_transformers: Vec<Pipeline>,
+    pub(crate) action_quotas: HashMap<Action, (u32, Duration)>,
+    pub(crate) not_found: HashMap<Action, NotFound>,
+    pub(crate) default_select: Option<Vec<String>>,
+    pub(crate) default_order_by: Option<Vec<(String, Sort)>>,
     pub(crate) migration: Option<ModelMigration>,
+    pub(crate) base: Option<String>,
+    pub(crate) discriminator: Option<String>,
+    pub(crate) discriminator_value: Option<String>,
+    pub(crate) subtype_fields: Vec<Field>,
 }
 
 impl ModelBuilder {
@@ -49,11 +73,15 @@ impl ModelBuilder {
             name: name.into(),
             table_name: "".to_string(),
             url_segment_name: "".to_string(),
+            route: None,
             localized_name: "".to_string(),
             description: "".to_string(),
             identity: false,
             internal: false,
             r#virtual: false,
+            append_only: false,
+            soft_delete: None,
+            connector: None,
             fields: vec![],
             dropped_fields: vec![],
             relations: vec![],
@@ -62,13 +90,25 @@ impl ModelBuilder {
             indices: Vec::new(),
             before_save_pipeline: Pipeline::new(),
             after_save_pipeline: Pipeline::new(),
+            validate_pipeline: Pipeline::new(),
             before_delete_pipeline: Pipeline::new(),
             after_delete_pipeline: Pipeline::new(),
+            before_find_pipeline: Pipeline::new(),
+            after_find_pipeline: Pipeline::new(),
             can_read_pipeline: Pipeline::new(),
             can_mutate_pipeline: Pipeline::new(),
+            enabled_actions: vec![],
             disabled_actions: None,
             action_transformers: vec![],
+            action_quotas: HashMap::new(),
+            not_found: HashMap::new(),
+            default_select: None,
+            default_order_by: None,
             migration: None,
+            base: None,
+            discriminator: None,
+            discriminator_value: None,
+            subtype_fields: vec![],
         }
     }
 
@@ -82,6 +122,24 @@ impl ModelBuilder {
         self
     }
 
+    /// Serve this model's actions under a custom, possibly nested route like
+    /// `/v1/users/:userId/posts` instead of its URL segment name.
+    pub fn route(&mut self, route: impl Into<String>) -> &mut Self {
+        self.route = Some(route.into());
+        self
+    }
+
+    /// Fields returned when a request doesn't select fields itself.
+    pub fn default_select<I, T>(&mut self, keys: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<String> {
+        self.default_select = Some(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn default_order_by<I, T>(&mut self, items: I) -> &mut Self where I: IntoIterator<Item = (T, Sort)>, T: Into<String> {
+        self.default_order_by = Some(items.into_iter().map(|(k, s)| (k.into(), s)).collect());
+        self
+    }
+
     pub fn localized_name(&mut self, localized_name: impl Into<String>) -> &mut Self {
         self.localized_name = localized_name.into();
         self
@@ -127,13 +185,86 @@ impl ModelBuilder {
         self
     }
 
+    /// Records of an append only model are never updated or deleted.
+    pub fn append_only(&mut self) -> &mut Self {
+        self.append_only = true;
+        self
+    }
+
+    /// Validates the whole object after all of its fields are validated.
+    pub fn validate(&mut self, pipeline: Pipeline) -> &mut Self {
+        self.validate_pipeline = pipeline;
+        self
+    }
+
+    /// Deleting a record sets this date time field instead of removing it.
+    /// Reads skip deleted records unless `withDeleted` is passed.
+    pub fn soft_delete(&mut self, field_name: impl Into<String>) -> &mut Self {
+        self.soft_delete = Some(field_name.into());
+        self
+    }
+
+    /// Stores this model's records with the named connector instead of the
+    /// graph's default one.
+    pub fn connector(&mut self, name: impl Into<String>) -> &mut Self {
+        self.connector = Some(name.into());
+        self
+    }
+
+    pub fn discriminator(&mut self, field_name: impl Into<String>) -> &mut Self {
+        self.discriminator = Some(field_name.into());
+        self
+    }
+
+    pub fn extends(&mut self, base: impl Into<String>, discriminator_value: impl Into<String>) -> &mut Self {
+        self.base = Some(base.into());
+        self.discriminator_value = Some(discriminator_value.into());
+        self
+    }
+
+    pub(crate) fn resolved_table_name(&self) -> String {
+        if self.table_name == "" { self.name.to_lowercase().to_plural() } else { self.table_name.to_string() }
+    }
+
+    pub(crate) fn inherit_from(&mut self, base: &ModelBuilder) {
+        let discriminator = match &base.discriminator {
+            Some(d) => d.clone(),
+            None => panic!("Model '{}' extends '{}' which doesn't declare a discriminator.", self.name, base.name),
+        };
+        let discriminator_value = self.discriminator_value.clone().unwrap();
+        let mut inherited: Vec<Field> = base.fields.iter().filter(|f| {
+            !self.fields.iter().any(|own| own.name == f.name)
+        }).cloned().collect();
+        inherited.append(&mut self.fields);
+        self.fields = inherited;
+        match self.fields.iter_mut().find(|f| f.name == discriminator) {
+            Some(field) => {
+                field.default = Some(Value::String(discriminator_value));
+                field.write_rule = WriteRule::NoWrite;
+            }
+            None => panic!("Discriminator field '{}' is not defined on model '{}'.", discriminator, base.name),
+        }
+        if self.primary.is_none() {
+            self.primary = base.primary.clone();
+        }
+        // subtypes share the base model's table
+        self.connector = base.connector.clone();
+        for index in base.indices.iter() {
+            if !self.indices.contains(index) {
+                self.indices.push(index.clone());
+            }
+        }
+        self.table_name = base.resolved_table_name();
+        self.discriminator = Some(discriminator);
+    }
+
     pub fn primary<I, T>(&mut self, keys: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<String> {
-        let string_keys: Vec<String> = keys.into_iter().map(Into::into).collect();
-        let name = string_keys.join("_");
-        let items: Vec<ModelIndexItem> = string_keys.iter().map(|k| {
-            ModelIndexItem::new(k, Sort::Asc, None)
-        }).collect();
-        let primary_index = ModelIndex::new(ModelIndexType::Primary, Some(name), items);
+        self.primary_with_sort(keys.into_iter().map(|k| (k, Sort::Asc)))
+    }
+
+    /// Like `primary`, with each key's sort and optional length, e.g. `("createdAt", Sort::Desc)`.
+    pub fn primary_with_sort<I, T>(&mut self, items: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<ModelIndexItem> {
+        let primary_index = Self::index_with_items(ModelIndexType::Primary, items);
         self.indices.push(primary_index.clone());
         self.primary = Some(primary_index);
         self
@@ -147,13 +278,12 @@ impl ModelBuilder {
     }
 
     pub fn index<I, T>(&mut self, keys: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<String> {
-        let string_keys: Vec<String> = keys.into_iter().map(Into::into).collect();
-        let name = string_keys.join("_");
-        let items: Vec<ModelIndexItem> = string_keys.iter().map(|k| {
-            ModelIndexItem::new(k, Sort::Asc, None)
-        }).collect();
-        let index = ModelIndex::new(ModelIndexType::Index, Some(name), items);
-        self.indices.push(index);
+        self.index_with_sort(keys.into_iter().map(|k| (k, Sort::Asc)))
+    }
+
+    /// Like `index`, with each key's sort and optional length, e.g. `("createdAt", Sort::Desc)`.
+    pub fn index_with_sort<I, T>(&mut self, items: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<ModelIndexItem> {
+        self.indices.push(Self::index_with_items(ModelIndexType::Index, items));
         self
     }
 
@@ -165,16 +295,28 @@ impl ModelBuilder {
     }
 
     pub fn unique<I, T>(&mut self, keys: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<String> {
-        let string_keys: Vec<String> = keys.into_iter().map(Into::into).collect();
-        let name = string_keys.join("_");
-        let items: Vec<ModelIndexItem> = string_keys.iter().map(|k| {
-            ModelIndexItem::new(k, Sort::Asc, None)
-        }).collect();
-        let index = ModelIndex::new(ModelIndexType::Unique, Some(name), items);
-        self.indices.push(index);
+        self.unique_with_sort(keys.into_iter().map(|k| (k, Sort::Asc)))
+    }
+
+    /// Like `unique`, with each key's sort and optional length, e.g. `("createdAt", Sort::Desc)`.
+    pub fn unique_with_sort<I, T>(&mut self, items: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<ModelIndexItem> {
+        self.indices.push(Self::index_with_items(ModelIndexType::Unique, items));
+        self
+    }
+
+    /// A fulltext index across `keys`, targeted by the `search` filter of each of them.
+    pub fn fulltext<I, T>(&mut self, keys: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<String> {
+        // unnamed, so each table gets its own index name
+        self.indices.push(ModelIndex::new(ModelIndexType::Fulltext, None::<String>, keys.into_iter().map(|k| ModelIndexItem::new(k, Sort::Asc, None)).collect()));
         self
     }
 
+    fn index_with_items<I, T>(r#type: ModelIndexType, items: I) -> ModelIndex where I: IntoIterator<Item = T>, T: Into<ModelIndexItem> {
+        let items: Vec<ModelIndexItem> = items.into_iter().map(Into::into).collect();
+        let name = items.iter().map(|i| i.field_name()).collect::<Vec<&str>>().join("_");
+        ModelIndex::new(r#type, Some(name), items)
+    }
+
     pub fn unique_settings<F: Fn(&mut ModelIndexBuilder)>(&mut self, build: F) -> &mut Self {
         let mut builder = ModelIndexBuilder::new(ModelIndexType::Unique);
         build(&mut builder);
@@ -182,9 +324,10 @@ impl ModelBuilder {
         self
     }
 
-    pub(crate) fn build(&self, connector: Arc<dyn Connector>) -> Model {
+    pub(crate) fn build(&self, connector: Arc<dyn Connector>, field_naming: FieldNaming) -> Model {
         let fields_vec: Vec<Arc<Field>> = self.fields.clone().iter_mut().map(|fb| { Arc::new({ fb.finalize(connector.clone()); fb.clone()}) }).collect();
         let dropped_fields_vec: Vec<Arc<Field>> = self.dropped_fields.clone().iter_mut().map(|fb| { Arc::new({ fb.finalize(connector.clone()); fb.clone()}) }).collect();
+        let subtype_fields_vec: Vec<Arc<Field>> = self.subtype_fields.clone().iter_mut().map(|fb| { Arc::new({ fb.finalize(connector.clone()); fb.clone()}) }).collect();
         let properties_vec: Vec<Arc<Property>> = self.properties.clone().iter_mut().map(|pb| { Arc::new({ pb.finalize(connector.clone()); pb.clone() }) }).collect();
         let mut fields_map: HashMap<String, Arc<Field>> = HashMap::new();
         let mut dropped_fields_map: HashMap<String, Arc<Field>> = HashMap::new();
@@ -199,15 +342,22 @@ impl ModelBuilder {
             if field.index.is_some() {
                 match &field.index.as_ref().unwrap() {
                     FieldIndex::Index(settings) => {
-                        indices.push(ModelIndex::new(ModelIndexType::Index, if settings.name.is_some() { Some(settings.name.as_ref().unwrap().clone()) } else { None }, vec![
+                        let mut index = ModelIndex::new(ModelIndexType::Index, if settings.name.is_some() { Some(settings.name.as_ref().unwrap().clone()) } else { None }, vec![
                             ModelIndexItem::new(field.name(), settings.sort, settings.length)
-                        ]));
+                        ]);
+                        index.set_sparse(settings.sparse.unwrap_or(false));
+                        index.set_case_insensitive(settings.case_insensitive);
+                        indices.push(index);
 
                     }
                     FieldIndex::Unique(settings) => {
-                        indices.push(ModelIndex::new(ModelIndexType::Unique, if settings.name.is_some() { Some(settings.name.as_ref().unwrap().clone()) } else { None }, vec![
+                        let mut index = ModelIndex::new(ModelIndexType::Unique, if settings.name.is_some() { Some(settings.name.as_ref().unwrap().clone()) } else { None }, vec![
                             ModelIndexItem::new(field.name(), settings.sort, settings.length)
-                        ]));
+                        ]);
+                        // null values of optional unique fields don't conflict
+                        index.set_sparse(settings.sparse.unwrap_or(field.optionality.is_optional()));
+                        index.set_case_insensitive(settings.case_insensitive);
+                        indices.push(index);
                     }
                     FieldIndex::Primary(settings) => {
                         primary = Some(ModelIndex::new(ModelIndexType::Primary, if settings.name.is_some() { Some(settings.name.as_ref().unwrap().clone()) } else { None }, vec![
@@ -226,6 +376,8 @@ impl ModelBuilder {
         for relation in relations_vec.iter() {
             relations_map.insert(relation.name().to_owned(), relation.clone());
         }
+        Self::add_foreign_key_indices(&relations_vec, &mut indices);
+        Self::check_indexed_fields_have_no_on_get(&self.name, &fields_map, &indices);
         for property in properties_vec.iter() {
             properties_map.insert(property.name.clone(), property.clone());
         }
@@ -233,6 +385,16 @@ impl ModelBuilder {
         if primary.is_none() && !self.r#virtual {
             panic!("Model '{}' must has a primary field.", self.name);
         }
+        if let Some(soft_delete) = &self.soft_delete {
+            let column_name = match fields_map.get(soft_delete) {
+                Some(field) if field.field_type().is_datetime() && field.is_optional() => field.column_name().to_owned(),
+                _ => panic!("Soft delete field '{}' of model '{}' should be an optional date time field.", soft_delete, self.name),
+            };
+            // soft deleted records don't hold their unique values
+            for index in indices.iter_mut().filter(|i| i.r#type() == ModelIndexType::Unique) {
+                index.set_soft_delete(Some(column_name.clone()));
+            }
+        }
         // install recordPrevious for primary
         for key in primary.as_ref().unwrap().keys() {
             let field = fields_map.get(key).unwrap();
@@ -241,12 +403,18 @@ impl ModelBuilder {
         let unique_query_keys = Self::unique_query_keys(self, &indices, primary.as_ref());
         let inner = ModelInner {
             name: self.name.clone(),
-            table_name: if self.table_name == "" { self.name.to_lowercase().to_plural() } else { self.table_name.to_string() },
+            table_name: self.resolved_table_name(),
             url_segment_name: if self.url_segment_name == "" { self.name.to_kebab_case().to_plural() } else { self.url_segment_name.to_string() },
+            route: self.route.as_ref().map(|r| RoutePattern::new(r)),
             localized_name: self.localized_name.clone(),
             description: self.description.clone(),
             identity: self.identity,
+            internal: self.internal,
             r#virtual: self.r#virtual,
+            append_only: self.append_only,
+            soft_delete: self.soft_delete.clone(),
+            connector: connector.clone(),
+            connector_name: self.connector.clone(),
             fields_vec,
             fields_map,
             dropped_fields: dropped_fields_vec,
@@ -259,8 +427,11 @@ impl ModelBuilder {
             indices: indices.clone(),
             before_save_pipeline: self.before_save_pipeline.clone(),
             after_save_pipeline: self.after_save_pipeline.clone(),
+            validate_pipeline: self.validate_pipeline.clone(),
             before_delete_pipeline: self.before_delete_pipeline.clone(),
             after_delete_pipeline: self.after_delete_pipeline.clone(),
+            before_find_pipeline: self.before_find_pipeline.clone(),
+            after_find_pipeline: self.after_find_pipeline.clone(),
             can_read_pipeline: self.can_read_pipeline.clone(),
             can_mutate_pipeline: self.can_mutate_pipeline.clone(),
             all_keys: self.all_keys(),
@@ -268,6 +439,7 @@ impl ModelBuilder {
             save_keys: self.save_keys(),
             output_keys: self.output_keys(),
             query_keys: self.query_keys(),
+            sort_keys: self.sort_keys(),
             unique_query_keys,
             auth_identity_keys: self.get_auth_identity_keys(),
             auth_by_keys: self.get_auth_by_keys(),
@@ -277,11 +449,23 @@ impl ModelBuilder {
             scalar_number_keys: self.get_scalar_number_keys(),
             local_output_keys: self.output_field_keys_and_property_keys(),
             relation_output_keys: self.output_relation_keys(),
+            readable_projections: self.readable_projections(),
+            default_readable_projection: self.readable_output_keys(None),
+            read_predicate_keys: self.read_predicate_keys(),
             field_property_map: self.get_field_property_map(),
             handler_actions: self.figure_out_actions(),
             disabled_actions: self.disabled_actions.clone(),
             action_transformers: self.action_transformers.clone(),
+            default_select: self.default_select.clone(),
+            default_order_by: self.default_order_by.clone(),
+            action_quotas: self.action_quotas.iter().map(|(action, (limit, window))| (*action, ActionQuota::new(*limit, *window))).collect(),
+            not_found: self.not_found.clone(),
             migration: self.migration.clone(),
+            base: self.base.clone(),
+            discriminator: self.discriminator.clone(),
+            discriminator_value: self.discriminator_value.clone(),
+            subtype_fields: subtype_fields_vec,
+            api_name_map: self.get_api_name_map(field_naming),
         };
         Model::new_with_inner(Arc::new(inner))
     }
@@ -352,6 +536,30 @@ impl ModelBuilder {
             .collect()
     }
 
+    fn readable_output_keys(&self, role: Option<&str>) -> HashSet<String> {
+        self.fields.iter()
+            .filter(|&f| { f.read_rule.is_read_by(role) })
+            .map(|f| { f.name.clone() })
+            .collect()
+    }
+
+    fn readable_projections(&self) -> HashMap<String, HashSet<String>> {
+        let mut roles: Vec<&String> = vec![];
+        for field in self.fields.iter() {
+            if let ReadRule::ReadBy(names) = &field.read_rule {
+                roles.extend(names.iter());
+            }
+        }
+        roles.into_iter().map(|role| (role.clone(), self.readable_output_keys(Some(role)))).collect()
+    }
+
+    fn read_predicate_keys(&self) -> HashSet<String> {
+        self.fields.iter()
+            .filter(|&f| { f.read_rule.is_read_if() || f.can_read_pipeline.has_any_items() })
+            .map(|f| { f.name.clone() })
+            .collect()
+    }
+
     fn output_relation_keys(&self) -> Vec<String> {
         self.all_relation_keys()
     }
@@ -377,13 +585,44 @@ impl ModelBuilder {
 
     pub(crate) fn query_keys(&self) -> Vec<String> {
         let mut fields: Vec<String> = self.fields.iter()
-            .filter(|&f| { f.query_ability == QueryAbility::Queryable })
+            .filter(|&f| { f.query_ability == QueryAbility::Queryable && !f.on_get_pipeline.has_any_items() })
             .map(|f| { f.name.clone() })
             .collect();
         fields.extend(self.all_relation_keys());
         fields
     }
 
+    pub(crate) fn sort_keys(&self) -> Vec<String> {
+        self.fields.iter()
+            .filter(|&f| { f.sort_ability == SortAbility::Sortable && !f.on_get_pipeline.has_any_items() })
+            .map(|f| { f.name.clone() })
+            .collect()
+    }
+
+    /// Indices hold stored values, which aren't what fields with an `on_get`
+    /// pipeline read as.
+    fn check_indexed_fields_have_no_on_get(model: &str, fields: &HashMap<String, Arc<Field>>, indices: &Vec<ModelIndex>) {
+        for index in indices {
+            for key in index.keys() {
+                if fields.get(key).map_or(false, |f| f.on_get_pipeline.has_any_items()) {
+                    panic!("Field '{}' of model '{}' has an onGet pipeline and can't be indexed.", key, model);
+                }
+            }
+        }
+    }
+
+    /// Index the foreign keys of relations unless an index starts with them
+    /// already or the relation opts out.
+    fn add_foreign_key_indices(relations: &Vec<Arc<Relation>>, indices: &mut Vec<ModelIndex>) {
+        for relation in relations.iter().filter(|r| r.has_foreign_key() && r.auto_index()) {
+            if indices.iter().any(|i| i.keys().starts_with(relation.fields())) {
+                continue
+            }
+            // unnamed, so each table gets its own index name
+            indices.push(ModelIndex::new(ModelIndexType::Index, None::<String>, relation.fields().iter().map(|f| ModelIndexItem::new(f.as_str(), Sort::Asc, None)).collect()));
+        }
+    }
+
     pub(crate) fn unique_query_keys(&self, indices: &Vec<ModelIndex>, primary: Option<&ModelIndex>) -> Vec<HashSet<String>> {
         let mut result: Vec<HashSet<String>> = Vec::new();
         for index in indices {
@@ -420,6 +659,26 @@ impl ModelBuilder {
             .collect()
     }
 
+    /// Request names of model keys, by `@apiName` and the field naming, mapped
+    /// to the keys. A converted name which is a key itself keeps naming that
+    /// key, so a model with `firstName` and `first_name` reads both.
+    fn get_api_name_map(&self, field_naming: FieldNaming) -> HashMap<String, String> {
+        let keys: HashSet<&str> = self.fields.iter().map(|f| f.name.as_str())
+            .chain(self.relations.iter().map(|r| r.name()))
+            .chain(self.properties.iter().map(|p| p.name.as_str()))
+            .collect();
+        let mut retval = HashMap::new();
+        for (api_name, name) in self.fields.iter().map(|f| (f.api_name(), f.name.as_str()))
+            .chain(self.relations.iter().map(|r| (r.name(), r.name())))
+            .chain(self.properties.iter().map(|p| (p.name.as_str(), p.name.as_str()))) {
+            let converted = field_naming.api_key(api_name);
+            if converted != name && (api_name != name || !keys.contains(converted.as_ref())) {
+                retval.insert(converted.into_owned(), name.to_owned());
+            }
+        }
+        retval
+    }
+
     fn get_deny_relation_keys(&self) -> Vec<String> {
         self.relations
             .iter()
@@ -447,6 +706,51 @@ impl ModelBuilder {
         self.action_transformers.push(pipeline);
     }
 
+    /// Allow each identity at most `limit` invocations of the handler action
+    /// within `window`.
+    pub(crate) fn action_quota(&mut self, action: Action, limit: u32, window: Duration) -> &mut Self {
+        self.action_quotas.insert(action, (limit, window));
+        self
+    }
+
+    /// Respond to the handler action with `not_found` when no object is found.
+    pub(crate) fn not_found(&mut self, action: Action, not_found: NotFound) -> &mut Self {
+        self.not_found.insert(action, not_found);
+        self
+    }
+
+    /// Serve the handler action named `name`, like `findMany`, or the handlers
+    /// of an action option like `find`, even if the model is internal.
+    pub fn enable_action(&mut self, name: impl AsRef<str>) -> &mut Self {
+        self.enable_actions_matching(Self::action_with_name(name.as_ref()))
+    }
+
+    /// Stop serving the handler action named `name`, or the handlers of an
+    /// action option.
+    pub fn disable_action(&mut self, name: impl AsRef<str>) -> &mut Self {
+        self.disable_actions_matching(Self::action_with_name(name.as_ref()))
+    }
+
+    fn action_with_name(name: &str) -> Action {
+        Action::handler_from_name(name).unwrap_or_else(|| Action::from_name(name))
+    }
+
+    /// Serve the handlers matching `action` even if the model is internal.
+    pub(crate) fn enable_actions_matching(&mut self, action: Action) -> &mut Self {
+        if let Some(disabled) = &mut self.disabled_actions {
+            disabled.retain(|a| *a != action);
+        }
+        self.enabled_actions.push(action);
+        self
+    }
+
+    /// Stop serving the handlers matching `action`.
+    pub(crate) fn disable_actions_matching(&mut self, action: Action) -> &mut Self {
+        self.enabled_actions.retain(|a| *a != action);
+        self.disabled_actions.get_or_insert_with(Vec::new).push(action);
+        self
+    }
+
     pub(crate) fn figure_out_actions(&self) -> HashSet<Action> {
         let mut default = if self.internal {
             HashSet::new()
@@ -459,6 +763,10 @@ impl ModelBuilder {
             default.insert(Action::from_u32(SIGN_IN_HANDLER));
             default.insert(Action::from_u32(IDENTITY_HANDLER));
         }
+        default.extend(Action::handlers_iter().filter(|a| a.passes(&self.enabled_actions)));
+        if self.append_only {
+            default.retain(|a| !a.updates_or_deletes());
+        }
         if let Some(disabled) = &self.disabled_actions {
             default.iter().filter(|a| {
                 !a.passes(disabled)
@@ -486,3 +794,168 @@ impl ModelBuilder {
 
 unsafe impl Send for ModelBuilder { }
 unsafe impl Sync for ModelBuilder { }
+
+#[cfg(test)]
+mod tests {
+    use maplit::{hashmap, hashset};
+    use crate::core::pipeline::items::value::is_null::IsNullItem;
+    use crate::core::action::{DELETE_HANDLER, DELETE_MANY_HANDLER, FIND_MANY_HANDLER, FIND_UNIQUE_HANDLER, UPDATE_HANDLER, UPDATE_MANY_HANDLER, UPSERT_HANDLER};
+    use crate::connectors::sql::schema::dialect::SQLDialect;
+    use super::*;
+
+    #[test]
+    fn field_naming_maps_converted_names_unless_they_are_keys() {
+        let mut builder = ModelBuilder::new("User");
+        builder.field(Field::new("firstName".to_owned())).field(Field::new("lastName".to_owned())).field(Field::new("last_name".to_owned()));
+        assert_eq!(builder.get_api_name_map(FieldNaming::SnakeCase), hashmap!{"first_name".to_owned() => "firstName".to_owned()});
+        assert_eq!(builder.get_api_name_map(FieldNaming::CamelCase), hashmap!{});
+    }
+
+    #[test]
+    fn unqueryable_and_unsortable_fields_are_left_out_of_filters_and_sorting() {
+        let id = Field::new("id".to_owned());
+        let mut secret = Field::new("secret".to_owned());
+        secret.query_ability = QueryAbility::Unqueryable;
+        let mut bio = Field::new("bio".to_owned());
+        bio.sort_ability = SortAbility::Unsortable;
+        let mut builder = ModelBuilder::new("User");
+        builder.field(id).field(secret).field(bio);
+        assert_eq!(builder.query_keys(), vec!["id".to_owned(), "bio".to_owned()]);
+        assert_eq!(builder.sort_keys(), vec!["id".to_owned(), "secret".to_owned()]);
+    }
+
+    #[test]
+    fn on_get_fields_are_left_out_of_filters_and_sorting() {
+        let id = Field::new("id".to_owned());
+        let mut secret = Field::new("secret".to_owned());
+        secret.on_get_pipeline.items.push(Arc::new(IsNullItem::new()));
+        let mut builder = ModelBuilder::new("User");
+        builder.field(id).field(secret);
+        assert_eq!(builder.query_keys(), vec!["id".to_owned()]);
+        assert_eq!(builder.sort_keys(), vec!["id".to_owned()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Field 'secret' of model 'User' has an onGet pipeline and can't be indexed.")]
+    fn on_get_fields_cannot_be_indexed() {
+        let mut secret = Field::new("secret".to_owned());
+        secret.on_get_pipeline.items.push(Arc::new(IsNullItem::new()));
+        let fields = hashmap!{"secret".to_owned() => Arc::new(secret)};
+        let indices = vec![ModelBuilder::index_with_items(ModelIndexType::Unique, [("secret", Sort::Asc)])];
+        ModelBuilder::check_indexed_fields_have_no_on_get("User", &fields, &indices);
+    }
+
+    #[test]
+    fn readable_output_keys_are_projected_per_role() {
+        let id = Field::new("id".to_owned());
+        let mut salary = Field::new("salary".to_owned());
+        salary.read_rule = ReadRule::ReadBy(vec!["Admin".to_owned(), "Manager".to_owned()]);
+        let mut notes = Field::new("notes".to_owned());
+        notes.read_rule = ReadRule::ReadBy(vec!["Admin".to_owned()]);
+        let mut email = Field::new("email".to_owned());
+        email.can_read_pipeline = Pipeline { items: vec![Arc::new(IsNullItem::new())] };
+        let mut bonus = Field::new("bonus".to_owned());
+        bonus.read_rule = ReadRule::ReadIf(Pipeline::new());
+        let mut password = Field::new("password".to_owned());
+        password.read_rule = ReadRule::NoRead;
+        let mut builder = ModelBuilder::new("User");
+        builder.field(id).field(salary).field(notes).field(email).field(bonus).field(password);
+        assert_eq!(builder.readable_projections(), hashmap!{
+            "Admin".to_owned() => hashset!{"id".to_owned(), "salary".to_owned(), "notes".to_owned(), "email".to_owned(), "bonus".to_owned()},
+            "Manager".to_owned() => hashset!{"id".to_owned(), "salary".to_owned(), "email".to_owned(), "bonus".to_owned()},
+        });
+        assert_eq!(builder.readable_output_keys(None), hashset!{"id".to_owned(), "email".to_owned(), "bonus".to_owned()});
+        assert_eq!(builder.read_predicate_keys(), hashset!{"email".to_owned(), "bonus".to_owned()});
+    }
+
+    #[test]
+    fn append_only_model_has_no_update_or_delete_actions() {
+        let mut builder = ModelBuilder::new("AuditLog");
+        builder.append_only();
+        let actions = builder.figure_out_actions();
+        assert!(actions.contains(&Action::from_u32(CREATE_HANDLER)));
+        assert!(actions.contains(&Action::from_u32(CREATE_MANY_HANDLER)));
+        assert!(actions.contains(&Action::from_u32(FIND_MANY_HANDLER)));
+        for handler in [UPDATE_HANDLER, UPSERT_HANDLER, DELETE_HANDLER, UPDATE_MANY_HANDLER, DELETE_MANY_HANDLER] {
+            assert!(!actions.contains(&Action::from_u32(handler)));
+        }
+    }
+
+    #[test]
+    fn internal_model_serves_only_enabled_actions() {
+        let mut builder = ModelBuilder::new("AuditLog");
+        builder.internal().enable_action("find");
+        let actions = builder.figure_out_actions();
+        assert!(actions.contains(&Action::from_u32(FIND_UNIQUE_HANDLER)));
+        assert!(actions.contains(&Action::from_u32(FIND_MANY_HANDLER)));
+        assert!(!actions.contains(&Action::from_u32(CREATE_HANDLER)));
+        builder.disable_action("findUnique");
+        let actions = builder.figure_out_actions();
+        assert!(!actions.contains(&Action::from_u32(FIND_UNIQUE_HANDLER)));
+        assert!(actions.contains(&Action::from_u32(FIND_MANY_HANDLER)));
+    }
+
+    #[test]
+    fn foreign_keys_are_indexed_unless_covered_or_opted_out() {
+        let mut fields: HashMap<String, Arc<Field>> = HashMap::new();
+        for name in ["authorId", "editorId", "categoryId"] {
+            let mut field = Field::new(name.to_owned());
+            field.foreign_key = true;
+            fields.insert(name.to_owned(), Arc::new(field));
+        }
+        let relations: Vec<Arc<Relation>> = [("author", "authorId", true), ("editor", "editorId", false), ("category", "categoryId", true)].into_iter().map(|(name, key, auto_index)| {
+            let mut relation = Relation::new(name);
+            relation.set_fields(vec![key.to_owned()]);
+            relation.set_references(vec!["id".to_owned()]);
+            relation.set_auto_index(auto_index);
+            relation.finalize(&fields);
+            Arc::new(relation)
+        }).collect();
+        let mut indices = vec![ModelBuilder::index_with_items(ModelIndexType::Unique, [("categoryId", Sort::Asc), ("slug", Sort::Asc)])];
+        ModelBuilder::add_foreign_key_indices(&relations, &mut indices);
+        assert_eq!(indices.len(), 2);
+        assert_eq!(indices[1].r#type(), ModelIndexType::Index);
+        assert_eq!(indices[1].keys(), &vec!["authorId".to_owned()]);
+        assert_eq!(indices[1].name(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Relation 'author' should have at least one field and as many references as fields, it has 2 fields and 1 references.")]
+    fn relation_fields_and_references_must_pair_up() {
+        let mut relation = Relation::new("author");
+        relation.set_fields(vec!["authorId".to_owned(), "authorOrg".to_owned()]);
+        relation.set_references(vec!["id".to_owned()]);
+        relation.finalize(&HashMap::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "Relation 'author' should have at least one field and as many references as fields, it has 0 fields and 0 references.")]
+    fn relation_without_fields_is_rejected() {
+        let mut relation = Relation::new("author");
+        relation.finalize(&HashMap::new());
+    }
+
+    #[test]
+    fn index_keeps_sort_and_length_of_each_key() {
+        let mut builder = ModelBuilder::new("Post");
+        builder.index_with_sort([("createdAt", Sort::Desc, 10), ("id", Sort::Asc, 4)]);
+        builder.unique_with_sort([("slug", Sort::Desc)]);
+        let index = &builder.indices[0];
+        assert_eq!(index.keys(), &vec!["createdAt".to_owned(), "id".to_owned()]);
+        assert_eq!(index.items()[0].sort(), Sort::Desc);
+        assert_eq!(index.items()[1].len(), Some(4));
+        assert_eq!(index.to_sql_create(SQLDialect::MySQL, "posts"), "CREATE INDEX `createdAt_id` ON `posts`(`createdAt`(10) DESC,`id`(4) ASC)");
+        assert_eq!(builder.indices[1].items()[0].sort(), Sort::Desc);
+    }
+
+    #[test]
+    fn fulltext_index_spans_each_key() {
+        let mut builder = ModelBuilder::new("Post");
+        builder.fulltext(["title", "body"]);
+        let index = &builder.indices[0];
+        assert!(index.r#type().is_fulltext());
+        assert_eq!(index.keys(), &vec!["title".to_owned(), "body".to_owned()]);
+        assert_eq!(index.to_sql_create(SQLDialect::MySQL, "posts"), "CREATE FULLTEXT INDEX `posts_title_body` ON `posts`(`title`,`body`)");
+        assert_eq!(index.to_sql_create(SQLDialect::PostgreSQL, "posts"), "CREATE INDEX \"posts_title_body_idx\" ON \"posts\" USING GIN (to_tsvector('simple', coalesce(\"title\", '') || ' ' || coalesce(\"body\", '')))");
+    }
+}
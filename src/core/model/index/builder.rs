@@ -5,6 +5,8 @@ pub struct ModelIndexBuilder {
     index_type: ModelIndexType,
     name: Option<String>,
     items: Vec<ModelIndexItem>,
+    sparse: bool,
+    case_insensitive: bool,
 }
 
 impl ModelIndexBuilder {
@@ -13,6 +15,8 @@ impl ModelIndexBuilder {
             index_type,
             name: None,
             items: Vec::new(),
+            sparse: false,
+            case_insensitive: false,
         }
     }
 
@@ -45,12 +49,24 @@ impl ModelIndexBuilder {
         self
     }
 
+    pub fn sparse(&mut self) -> &mut Self {
+        self.sparse = true;
+        self
+    }
+
+    pub fn case_insensitive(&mut self) -> &mut Self {
+        self.case_insensitive = true;
+        self
+    }
+
     pub(crate) fn build(&mut self) -> ModelIndex {
         ModelIndex {
             index_type: self.index_type,
             name: Some(self.name.clone().unwrap()),
             items: self.items.clone(),
             keys: self.items.iter().map(|i| i.field_name.to_owned()).collect(),
+            sparse: self.sparse,
+            case_insensitive: self.case_insensitive,
         }
     }
 }
@@ -10,6 +10,7 @@ pub enum ModelIndexType {
     Primary,
     Index,
     Unique,
+    Fulltext,
 }
 
 impl ModelIndexType {
@@ -26,10 +27,17 @@ impl ModelIndexType {
             _ => false,
         }
     }
+
+    pub(crate) fn is_fulltext(&self) -> bool {
+        match self {
+            ModelIndexType::Fulltext => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Hash, Eq)]
-pub(crate) struct ModelIndexItem {
+pub struct ModelIndexItem {
     pub(self) field_name: String,
     pub(self) sort: Sort,
     pub(self) len: Option<usize>,
@@ -58,12 +66,27 @@ impl ModelIndexItem {
     }
 }
 
+impl<T> From<(T, Sort)> for ModelIndexItem where T: Into<String> {
+    fn from((name, sort): (T, Sort)) -> Self {
+        ModelIndexItem::new(name, sort, None)
+    }
+}
+
+impl<T> From<(T, Sort, usize)> for ModelIndexItem where T: Into<String> {
+    fn from((name, sort, len): (T, Sort, usize)) -> Self {
+        ModelIndexItem::new(name, sort, Some(len))
+    }
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub(crate) struct ModelIndex {
     pub(self) index_type: ModelIndexType,
     pub(self) name: Option<String>,
     pub(self) items: Vec<ModelIndexItem>,
     pub(self) keys: Vec<String>,
+    pub(self) sparse: bool,
+    pub(self) case_insensitive: bool,
+    pub(self) soft_delete: Option<String>,
 }
 
 impl ModelIndex {
@@ -75,6 +98,9 @@ impl ModelIndex {
             name: name.map(|v| v.into()),
             items,
             keys,
+            sparse: false,
+            case_insensitive: false,
+            soft_delete: None,
         }
     }
     pub(crate) fn r#type(&self) -> ModelIndexType {
@@ -108,6 +134,43 @@ impl ModelIndex {
         self.name = Some(new_name);
     }
 
+    pub(crate) fn sparse(&self) -> bool {
+        self.sparse
+    }
+
+    pub(crate) fn set_sparse(&mut self, sparse: bool) {
+        self.sparse = sparse;
+    }
+
+    /// Whether string values are compared ignoring case, for example emails.
+    pub(crate) fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub(crate) fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
+    }
+
+    /// The soft delete column of a unique index. Records tombstoned in it
+    /// don't hold their unique values.
+    pub(crate) fn soft_delete(&self) -> Option<&str> {
+        self.soft_delete.as_deref()
+    }
+
+    pub(crate) fn set_soft_delete(&mut self, column: Option<String>) {
+        self.soft_delete = column;
+    }
+
+    /// Moves the soft delete column listed last by the database back out of
+    /// the items.
+    pub(crate) fn extract_soft_delete(&mut self, column: &str) {
+        if self.keys.last().map(|k| k.as_str()) == Some(column) {
+            self.items.pop();
+            self.keys.pop();
+            self.soft_delete = Some(column.to_owned());
+        }
+    }
+
     pub(crate) fn items(&self) -> &Vec<ModelIndexItem> {
         &self.items
     }
@@ -120,18 +183,62 @@ impl ModelIndex {
         let escape = dialect.escape();
         let index_name_cow = self.sql_name(table_name, dialect);
         let index_name = index_name_cow.as_ref();
-        format!("DROP INDEX {escape}{index_name}{escape} ON {escape}{table_name}{escape}")
+        match dialect {
+            SQLDialect::MySQL => format!("DROP INDEX {escape}{index_name}{escape} ON {escape}{table_name}{escape}"),
+            _ => format!("DROP INDEX {escape}{index_name}{escape}"),
+        }
     }
 
     pub(crate) fn to_sql_create(&self, dialect: SQLDialect, table_name: &str) -> String {
         let escape = dialect.escape();
         let index_name_cow = self.sql_name(table_name, dialect);
         let index_name = index_name_cow.as_ref();
+        if self.r#type().is_fulltext() {
+            let columns: Vec<&str> = self.keys.iter().map(|k| k.as_str()).collect();
+            return match dialect {
+                SQLDialect::MySQL => format!("CREATE FULLTEXT INDEX {escape}{index_name}{escape} ON {escape}{table_name}{escape}({})", Self::sql_escaped_columns(dialect, &columns)),
+                SQLDialect::PostgreSQL => {
+                    let columns: Vec<String> = columns.iter().map(|c| format!("{escape}{c}{escape}")).collect();
+                    format!("CREATE INDEX {escape}{index_name}{escape} ON {escape}{table_name}{escape} USING GIN ({})", Self::psql_fulltext_document(&columns))
+                }
+                // SQLite has no fulltext index outside of virtual tables, search scans instead
+                _ => format!("CREATE INDEX {escape}{index_name}{escape} ON {escape}{table_name}{escape}({})", Self::sql_escaped_columns(dialect, &columns)),
+            };
+        }
         let unique = if self.r#type().is_unique() { "UNIQUE " } else { "" };
+        let soft_delete = self.soft_delete().filter(|_| self.r#type().is_unique());
         let fields: Vec<String> = self.items.iter().map(|item| {
-            Self::sql_format_item(dialect, item)
+            match soft_delete {
+                // MySQL has no partial indices, tombstoned rows index nulls which never conflict
+                Some(column) if dialect == SQLDialect::MySQL => {
+                    let name = item.field_name();
+                    let value = if self.case_insensitive { format!("LOWER({escape}{name}{escape})") } else { format!("{escape}{name}{escape}") };
+                    format!("(IF({escape}{column}{escape} IS NULL, {value}, NULL)) {}", item.sort().to_str())
+                }
+                _ => if self.case_insensitive {
+                    Self::sql_format_lowercased_item(dialect, item)
+                } else {
+                    Self::sql_format_item(dialect, item)
+                }
+            }
         }).collect();
-        format!("CREATE {unique}INDEX {escape}{index_name}{escape} ON {escape}{table_name}{escape}({})", fields.join(","))
+        let filter = match soft_delete {
+            Some(column) if dialect != SQLDialect::MySQL => format!(" WHERE {escape}{column}{escape} IS NULL"),
+            _ => "".to_owned(),
+        };
+        format!("CREATE {unique}INDEX {escape}{index_name}{escape} ON {escape}{table_name}{escape}({}){filter}", fields.join(","))
+    }
+
+    fn sql_escaped_columns(dialect: SQLDialect, columns: &[&str]) -> String {
+        let escape = dialect.escape();
+        columns.iter().map(|c| format!("{escape}{c}{escape}")).collect::<Vec<String>>().join(",")
+    }
+
+    /// The searched document of a PostgreSQL fulltext index over escaped
+    /// columns. Searches must use the same expression to hit the index.
+    pub(crate) fn psql_fulltext_document(columns: &[String]) -> String {
+        let joined = columns.iter().map(|c| format!("coalesce({c}, '')")).collect::<Vec<String>>().join(" || ' ' || ");
+        format!("to_tsvector('simple', {joined})")
     }
 
     pub(crate) fn sql_format_item(dialect: SQLDialect, item: &ModelIndexItem) -> String {
@@ -150,6 +257,18 @@ impl ModelIndex {
         format!("{escape}{name}{escape}{len} {sort}")
     }
 
+    fn sql_format_lowercased_item(dialect: SQLDialect, item: &ModelIndexItem) -> String {
+        let escape = dialect.escape();
+        let name = item.field_name();
+        let sort = item.sort().to_str();
+        if dialect == SQLDialect::MySQL {
+            // MySQL functional key parts need their own parentheses
+            format!("(LOWER({escape}{name}{escape})) {sort}")
+        } else {
+            format!("LOWER({escape}{name}{escape}) {sort}")
+        }
+    }
+
     pub(crate) fn normalize_name(&self, table_name: &str, dialect: SQLDialect) -> String {
         match self.index_type {
             ModelIndexType::Primary => match dialect {
@@ -191,3 +310,71 @@ impl ModelIndex {
         self.keys.push(key);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fulltext_index_uses_dialect_facility() {
+        let index = ModelIndex::new(ModelIndexType::Fulltext, Some("Post_title_body"), vec![
+            ModelIndexItem::new("title", Sort::Asc, None),
+            ModelIndexItem::new("body", Sort::Asc, None),
+        ]);
+        assert_eq!(index.to_sql_create(SQLDialect::MySQL, "Post"), "CREATE FULLTEXT INDEX `Post_title_body` ON `Post`(`title`,`body`)");
+        assert_eq!(index.to_sql_create(SQLDialect::PostgreSQL, "Post"), "CREATE INDEX \"Post_title_body\" ON \"Post\" USING GIN (to_tsvector('simple', coalesce(\"title\", '') || ' ' || coalesce(\"body\", '')))");
+    }
+
+    #[test]
+    fn case_insensitive_unique_index_lowercases_columns() {
+        let mut index = ModelIndex::new(ModelIndexType::Unique, Some("User_email"), vec![
+            ModelIndexItem::new("email", Sort::Asc, None),
+        ]);
+        index.set_case_insensitive(true);
+        assert_eq!(index.to_sql_create(SQLDialect::MySQL, "User"), "CREATE UNIQUE INDEX `User_email` ON `User`((LOWER(`email`)) ASC)");
+        assert_eq!(index.to_sql_create(SQLDialect::PostgreSQL, "User"), "CREATE UNIQUE INDEX \"User_email\" ON \"User\"(LOWER(\"email\") ASC)");
+        assert_eq!(index.to_sql_create(SQLDialect::SQLite, "User"), "CREATE UNIQUE INDEX `User_email` ON `User`(LOWER(`email`) ASC)");
+    }
+
+    fn email_index_of_soft_deleted_model() -> ModelIndex {
+        let mut index = ModelIndex::new(ModelIndexType::Unique, Some("User_email"), vec![
+            ModelIndexItem::new("email", Sort::Asc, None),
+        ]);
+        index.set_soft_delete(Some("deletedAt".to_owned()));
+        index
+    }
+
+    #[test]
+    fn unique_index_of_soft_deleted_model_leaves_tombstones_out() {
+        let index = email_index_of_soft_deleted_model();
+        assert_eq!(index.to_sql_create(SQLDialect::MySQL, "User"), "CREATE UNIQUE INDEX `User_email` ON `User`((IF(`deletedAt` IS NULL, `email`, NULL)) ASC)");
+        assert_eq!(index.to_sql_create(SQLDialect::PostgreSQL, "User"), "CREATE UNIQUE INDEX \"User_email\" ON \"User\"(\"email\" ASC) WHERE \"deletedAt\" IS NULL");
+        assert_eq!(index.to_sql_create(SQLDialect::SQLite, "User"), "CREATE UNIQUE INDEX `User_email` ON `User`(`email` ASC) WHERE `deletedAt` IS NULL");
+    }
+
+    #[cfg(feature = "data-source-sqlite")]
+    #[tokio::test]
+    async fn soft_deleted_records_release_their_unique_values() {
+        use quaint_forked::prelude::Queryable;
+        use quaint_forked::single::Quaint;
+        let conn = Quaint::new_in_memory().unwrap();
+        conn.raw_cmd("CREATE TABLE `User` (`id` INTEGER PRIMARY KEY, `email` TEXT NOT NULL, `deletedAt` TEXT)").await.unwrap();
+        conn.raw_cmd(&email_index_of_soft_deleted_model().to_sql_create(SQLDialect::SQLite, "User")).await.unwrap();
+        conn.raw_cmd("INSERT INTO `User` VALUES (1, 'ada@example.com', NULL)").await.unwrap();
+        conn.raw_cmd("UPDATE `User` SET `deletedAt` = '2026-10-16T00:00:00Z' WHERE `id` = 1").await.unwrap();
+        // re-created after the soft deletion
+        conn.raw_cmd("INSERT INTO `User` VALUES (2, 'ada@example.com', NULL)").await.unwrap();
+        // a live duplicate is still rejected
+        assert!(conn.raw_cmd("INSERT INTO `User` VALUES (3, 'ada@example.com', NULL)").await.is_err());
+    }
+
+    #[test]
+    fn only_mysql_drops_index_on_table() {
+        let index = ModelIndex::new(ModelIndexType::Unique, Some("User_email"), vec![
+            ModelIndexItem::new("email", Sort::Asc, Some(10)),
+        ]);
+        assert_eq!(index.to_sql_drop(SQLDialect::MySQL, "User"), "DROP INDEX `User_email` ON `User`");
+        assert_eq!(index.to_sql_drop(SQLDialect::PostgreSQL, "User"), "DROP INDEX \"User_email\"");
+        assert_eq!(index.to_sql_create(SQLDialect::MySQL, "User"), "CREATE UNIQUE INDEX `User_email` ON `User`(`email`(10) ASC)");
+    }
+}
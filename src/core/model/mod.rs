@@ -1,12 +1,20 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::ops::BitOr;
 use std::sync::Arc;
+use std::time::Instant;
 use async_recursion::async_recursion;
 use inflector::Inflector;
+use key_path::path;
 use maplit::hashset;
-use crate::core::action::{Action, FIND, IDENTITY, MANY, NESTED, SIGN_IN, SINGLE};
-use crate::core::field::Field;
+use crate::core::connector::Connector;
+use crate::core::action::{Action, ENTRY, FIND, IDENTITY, MANY, NESTED, SIGN_IN, SINGLE};
+use crate::core::error::Error;
+use crate::core::field::{Field, Sort};
 use crate::core::model::migration::ModelMigration;
+use crate::core::model::not_found::NotFound;
+use crate::core::model::quota::{ActionQuota, check_action_quota};
+use crate::core::model::route::RoutePattern;
 use crate::core::pipeline::ctx::Ctx;
 use crate::core::relation::Relation;
 use crate::core::pipeline::Pipeline;
@@ -19,15 +27,24 @@ use self::index::ModelIndex;
 pub(crate) mod builder;
 pub(crate) mod index;
 pub(crate) mod migration;
+pub(crate) mod route;
+pub(crate) mod quota;
+pub(crate) mod not_found;
 
 pub struct ModelInner {
     pub(crate) name: String,
     pub(crate) table_name: String,
     pub(crate) url_segment_name: String,
+    pub(crate) route: Option<RoutePattern>,
     pub(crate) localized_name: String,
     pub(crate) description: String,
     pub(crate) identity: bool,
+    pub(crate) internal: bool,
     pub(crate) r#virtual: bool,
+    pub(crate) append_only: bool,
+    pub(crate) soft_delete: Option<String>,
+    pub(crate) connector: Arc<dyn Connector>,
+    pub(crate) connector_name: Option<String>,
     pub(crate) fields_vec: Vec<Arc<Field>>,
     pub(crate) fields_map: HashMap<String, Arc<Field>>,
     pub(crate) dropped_fields: Vec<Arc<Field>>,
@@ -40,8 +57,11 @@ pub struct ModelInner {
     pub(crate) primary: Option<ModelIndex>,
     pub(crate) before_save_pipeline: Pipeline,
     pub(crate) after_save_pipeline: Pipeline,
+    pub(crate) validate_pipeline: Pipeline,
     pub(crate) before_delete_pipeline: Pipeline,
     pub(crate) after_delete_pipeline: Pipeline,
+    pub(crate) before_find_pipeline: Pipeline,
+    pub(crate) after_find_pipeline: Pipeline,
     pub(crate) can_read_pipeline: Pipeline,
     pub(crate) can_mutate_pipeline: Pipeline,
     pub(crate) all_keys: Vec<String>,
@@ -49,6 +69,7 @@ pub struct ModelInner {
     pub(crate) save_keys: Vec<String>,
     pub(crate) output_keys: Vec<String>,
     pub(crate) query_keys: Vec<String>,
+    pub(crate) sort_keys: Vec<String>,
     pub(crate) unique_query_keys: Vec<HashSet<String>>,
     pub(crate) auth_identity_keys: Vec<String>,
     pub(crate) auth_by_keys: Vec<String>,
@@ -58,11 +79,23 @@ pub struct ModelInner {
     pub(crate) scalar_number_keys: Vec<String>,
     pub(crate) local_output_keys: Vec<String>,
     pub(crate) relation_output_keys: Vec<String>,
+    pub(crate) readable_projections: HashMap<String, HashSet<String>>,
+    pub(crate) default_readable_projection: HashSet<String>,
+    pub(crate) read_predicate_keys: HashSet<String>,
     pub(crate) field_property_map: HashMap<String, Vec<String>>,
     pub(crate) handler_actions: HashSet<Action>,
     pub(crate) disabled_actions: Option<Vec<Action>>,
     pub(crate) action_transformers: Vec<Pipeline>,
+    pub(crate) action_quotas: HashMap<Action, ActionQuota>,
+    pub(crate) not_found: HashMap<Action, NotFound>,
+    pub(crate) default_select: Option<Vec<String>>,
+    pub(crate) default_order_by: Option<Vec<(String, Sort)>>,
     pub(crate) migration: Option<ModelMigration>,
+    pub(crate) base: Option<String>,
+    pub(crate) discriminator: Option<String>,
+    pub(crate) discriminator_value: Option<String>,
+    pub(crate) subtype_fields: Vec<Arc<Field>>,
+    pub(crate) api_name_map: HashMap<String, String>,
 }
 
 #[derive(Clone)]
@@ -100,6 +133,10 @@ impl Model {
         &self.inner.url_segment_name
     }
 
+    pub(crate) fn route(&self) -> Option<&RoutePattern> {
+        self.inner.route.as_ref()
+    }
+
     pub(crate) fn localized_name(&self) -> String {
         if self.inner.localized_name.is_empty() {
             self.inner.name.to_title_case()
@@ -116,6 +153,10 @@ impl Model {
         self.inner.identity
     }
 
+    pub(crate) fn internal(&self) -> bool {
+        self.inner.internal
+    }
+
     pub(crate) fn r#virtual(&self) -> bool {
         self.inner.r#virtual
     }
@@ -194,6 +235,10 @@ impl Model {
         &self.inner.query_keys
     }
 
+    pub(crate) fn sort_keys(&self) -> &Vec<String> {
+        &self.inner.sort_keys
+    }
+
     pub(crate) fn unique_query_keys(&self) -> &Vec<HashSet<String>> {
         &self.inner.unique_query_keys
     }
@@ -224,11 +269,31 @@ impl Model {
         &self.inner.relation_output_keys
     }
 
+    /// Output fields an identity of the `role` model may read, precomputed
+    /// for each role named by a field's read rule. Other roles and requests
+    /// without an identity see the fields readable by anyone.
+    pub(crate) fn readable_output_keys(&self, role: Option<&str>) -> &HashSet<String> {
+        match role.and_then(|role| self.inner.readable_projections.get(role)) {
+            Some(keys) => keys,
+            None => &self.inner.default_readable_projection,
+        }
+    }
+
+    /// Output fields with a dynamic read predicate, which is evaluated for
+    /// each output object.
+    pub(crate) fn read_predicate_keys(&self) -> &HashSet<String> {
+        &self.inner.read_predicate_keys
+    }
+
     pub(crate) fn field_property_map(&self) -> &HashMap<String, Vec<String>> {
         &self.inner.field_property_map
     }
 
     pub(crate) fn has_action(&self, action: Action) -> bool {
+        // only handler actions are served by entry, nested actions are allowed by relations
+        if (action.to_u32() & ENTRY) != 0 && !self.inner.handler_actions.contains(&action) {
+            return false;
+        }
         if let Some(disabled_actions) = self.disabled_actions() {
             if action.passes(disabled_actions) {
                 return false;
@@ -237,9 +302,42 @@ impl Model {
         if ((action.to_u32() & IDENTITY) != 0) || ((action.to_u32() & SIGN_IN) != 0) {
             return self.inner.identity;
         }
+        if self.inner.append_only && action.updates_or_deletes() {
+            return false;
+        }
         true
     }
 
+    pub(crate) fn append_only(&self) -> bool {
+        self.inner.append_only
+    }
+
+    /// Fails for append only models, whose existing records can't be changed.
+    pub(crate) fn check_append_only(&self) -> Result<()> {
+        if self.inner.append_only {
+            Err(Error::invalid_operation(format!("Model '{}' is append only, its records can't be updated or deleted.", self.name())))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The date time field which marks records deleted, if records are soft
+    /// deleted.
+    pub(crate) fn soft_delete_field(&self) -> Option<&str> {
+        self.inner.soft_delete.as_deref()
+    }
+
+    /// The connector which stores this model's records.
+    pub(crate) fn connector(&self) -> &dyn Connector {
+        self.inner.connector.as_ref()
+    }
+
+    /// The name of the connector this model uses, or `None` for the graph's
+    /// default connector.
+    pub(crate) fn connector_name(&self) -> Option<&str> {
+        self.inner.connector_name.as_deref()
+    }
+
     pub(crate) fn has_field(&self, name: &str) -> bool {
         self.inner.fields_map.get(name).is_some()
     }
@@ -252,6 +350,16 @@ impl Model {
         &self.inner.indices
     }
 
+    /// Whether a field belongs to a case-insensitive index.
+    pub(crate) fn is_case_insensitive_field(&self, name: &str) -> bool {
+        self.indices().iter().any(|i| i.case_insensitive() && i.keys().iter().any(|k| k == name))
+    }
+
+    /// The fulltext index a field's `search` filter targets.
+    pub(crate) fn fulltext_index_for(&self, name: &str) -> Option<&ModelIndex> {
+        self.indices().iter().find(|i| i.r#type().is_fulltext() && i.keys().iter().any(|k| k == name))
+    }
+
     pub(crate) fn primary_index(&self) -> &ModelIndex {
         self.inner.primary.as_ref().unwrap()
     }
@@ -264,6 +372,10 @@ impl Model {
         &self.inner.after_save_pipeline
     }
 
+    pub(crate) fn validate_pipeline(&self) -> &Pipeline {
+        &self.inner.validate_pipeline
+    }
+
     pub(crate) fn before_delete_pipeline(&self) -> &Pipeline {
         &self.inner.before_delete_pipeline
     }
@@ -272,6 +384,14 @@ impl Model {
         &self.inner.after_delete_pipeline
     }
 
+    pub(crate) fn before_find_pipeline(&self) -> &Pipeline {
+        &self.inner.before_find_pipeline
+    }
+
+    pub(crate) fn after_find_pipeline(&self) -> &Pipeline {
+        &self.inner.after_find_pipeline
+    }
+
     pub(crate) fn can_mutate_pipeline(&self) -> &Pipeline { &self.inner.can_mutate_pipeline }
 
     pub(crate) fn can_read_pipeline(&self) -> &Pipeline { &self.inner.can_read_pipeline }
@@ -284,6 +404,15 @@ impl Model {
         self.inner.disabled_actions.as_ref()
     }
 
+    pub(crate) fn check_action_quota(&self, action: Action, identity: &str) -> Result<()> {
+        check_action_quota(&self.inner.action_quotas, action, identity, Instant::now())
+    }
+
+    /// How the handler action responds when no object is found.
+    pub(crate) fn not_found(&self, action: Action) -> NotFound {
+        self.inner.not_found.get(&action).cloned().unwrap_or_default()
+    }
+
     pub(crate) fn has_action_transformers(&self) -> bool {
         self.inner.action_transformers.len() > 0
     }
@@ -316,7 +445,146 @@ impl Model {
         Ok((surface_value, ctx.action))
     }
 
+    pub(crate) fn base(&self) -> Option<&str> {
+        self.inner.base.as_deref()
+    }
+
+    pub(crate) fn discriminator(&self) -> Option<&str> {
+        self.inner.discriminator.as_deref()
+    }
+
+    pub(crate) fn discriminator_value(&self) -> Option<&str> {
+        self.inner.discriminator_value.as_deref()
+    }
+
+    /// Fields which only exist on subtypes but are stored in this model's table.
+    pub(crate) fn subtype_fields(&self) -> &Vec<Arc<Field>> {
+        &self.inner.subtype_fields
+    }
+
+    /// A field of this model, or of a subtype when this model is its base.
+    pub(crate) fn stored_field(&self, name: &str) -> Option<&Field> {
+        match self.field(name) {
+            Some(field) => Some(field),
+            None => self.subtype_fields().iter().find(|f| f.name() == name).map(|f| f.as_ref()),
+        }
+    }
+
+    /// The indices of this model's table, a base model's table also holds the
+    /// indices of its subtypes.
+    pub(crate) fn stored_indices(&self, models: &Vec<Model>) -> Vec<ModelIndex> {
+        let mut retval = self.indices().clone();
+        for subtype in models.iter().filter(|m| m.base() == Some(self.name())) {
+            for index in subtype.indices() {
+                if !retval.contains(index) {
+                    // records of other subtypes leave the subtype's fields null
+                    let mut index = index.clone();
+                    index.set_sparse(true);
+                    retval.push(index);
+                }
+            }
+        }
+        retval
+    }
+
+    /// The collection name of this model. Subtypes share their base model's collection.
+    pub(crate) fn storage_name(&self) -> &str {
+        match self.base() {
+            Some(base) => base,
+            None => self.name(),
+        }
+    }
+
+    /// Restrict the finder to records of this subtype. Models without a discriminator
+    /// value are returned untouched. Soft deleted records are left out unless
+    /// `withDeleted` is true, a `withDeleted` which isn't a bool is an error.
+    pub(crate) fn scoped_finder<'a>(&self, finder: &'a Value) -> Result<Cow<'a, Value>> {
+        let finder = match (self.discriminator(), self.discriminator_value()) {
+            (Some(key), Some(value)) => {
+                let mut finder = finder.as_hashmap().cloned().unwrap_or_default();
+                let discriminator_filter = teon!({key: {"equals": value}});
+                let r#where = match finder.remove("where") {
+                    Some(Value::HashMap(map)) if !map.is_empty() => teon!({"AND": [Value::HashMap(map), discriminator_filter]}),
+                    _ => discriminator_filter,
+                };
+                finder.insert("where".to_owned(), r#where);
+                Cow::Owned(Value::HashMap(finder))
+            }
+            _ => Cow::Borrowed(finder),
+        };
+        let key = match self.soft_delete_field() {
+            Some(key) => key,
+            None => return Ok(finder),
+        };
+        let mut finder = finder.as_hashmap().cloned().unwrap_or_default();
+        let with_deleted = match finder.remove("withDeleted") {
+            Some(value) => match value.as_bool() {
+                Some(b) => b,
+                None => return Err(Error::unexpected_input_type("bool", path!["withDeleted"])),
+            },
+            None => false,
+        };
+        if with_deleted {
+            return Ok(Cow::Owned(Value::HashMap(finder)));
+        }
+        let deleted_filter = teon!({key: {"equals": null}});
+        let r#where = match finder.remove("where") {
+            Some(Value::HashMap(map)) if !map.is_empty() => teon!({"AND": [Value::HashMap(map), deleted_filter]}),
+            _ => deleted_filter,
+        };
+        finder.insert("where".to_owned(), r#where);
+        Ok(Cow::Owned(Value::HashMap(finder)))
+    }
+
+    /// Select this model's default projection when the request input doesn't
+    /// select fields itself.
+    pub(crate) fn input_with_default_select<'a>(&self, input: &'a Value) -> Cow<'a, Value> {
+        match &self.inner.default_select {
+            Some(keys) if !input.as_hashmap().map_or(false, |m| m.contains_key("select")) => {
+                let mut input = input.as_hashmap().cloned().unwrap_or_default();
+                input.insert("select".to_owned(), Value::HashMap(keys.iter().map(|k| (k.clone(), Value::Bool(true))).collect()));
+                Cow::Owned(Value::HashMap(input))
+            }
+            _ => Cow::Borrowed(input),
+        }
+    }
+
+    pub(crate) fn default_order_by(&self) -> Option<&Vec<(String, Sort)>> {
+        self.inner.default_order_by.as_ref()
+    }
+
+    /// Order by this model's default ordering when the finder doesn't order
+    /// records itself.
+    pub(crate) fn finder_with_default_order_by<'a>(&self, finder: &'a Value) -> Cow<'a, Value> {
+        match &self.inner.default_order_by {
+            Some(order_by) if !finder.as_hashmap().map_or(false, |m| m.contains_key("orderBy")) => {
+                let mut finder = finder.as_hashmap().cloned().unwrap_or_default();
+                finder.insert("orderBy".to_owned(), Value::Vec(order_by.iter().map(|(key, sort)| {
+                    let sort = match sort {
+                        Sort::Asc => "asc",
+                        Sort::Desc => "desc",
+                    };
+                    Value::HashMap(HashMap::from([(key.clone(), Value::String(sort.to_owned()))]))
+                }).collect()));
+                Cow::Owned(Value::HashMap(finder))
+            }
+            _ => Cow::Borrowed(finder),
+        }
+    }
+
+    pub(crate) fn has_api_names(&self) -> bool {
+        !self.inner.api_name_map.is_empty()
+    }
+
+    /// The internal field name of a field which is exposed under a different API name.
+    pub(crate) fn field_name_for_api_name(&self, api_name: &str) -> Option<&str> {
+        self.inner.api_name_map.get(api_name).map(|n| n.as_str())
+    }
+
     pub(crate) fn allows_drop_when_migrate(&self) -> bool {
+        if self.inner.append_only {
+            return false;
+        }
         if let Some(m) = self.migration() {
             m.drop
         } else {
@@ -335,3 +603,4 @@ unsafe impl Send for Model {}
 unsafe impl Sync for Model {}
 unsafe impl Send for ModelInner {}
 unsafe impl Sync for ModelInner {}
+
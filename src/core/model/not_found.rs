@@ -0,0 +1,46 @@
+use crate::core::error::Error;
+use crate::core::result::Result;
+use crate::prelude::Value;
+
+/// How a single object action responds when no object matches its finder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotFound {
+    /// A not found error, with a custom message if given.
+    Error(Option<String>),
+    /// `null` data.
+    Null,
+}
+
+impl Default for NotFound {
+    fn default() -> Self {
+        NotFound::Error(None)
+    }
+}
+
+impl NotFound {
+
+    /// The data to respond with, or the not found error `err` to raise.
+    pub(crate) fn resolve(&self, err: Error) -> Result<Value> {
+        match self {
+            NotFound::Null => Ok(Value::Null),
+            NotFound::Error(Some(message)) => Err(Error::object_not_found_with_message(message)),
+            NotFound::Error(None) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_found_responds_with_an_error_or_null() {
+        let err = NotFound::default().resolve(Error::object_not_found()).unwrap_err();
+        assert_eq!(err.r#type.code(), 404);
+        assert_eq!(err.message(), "The requested object does not exist.");
+        let err = NotFound::Error(Some("No such user.".to_owned())).resolve(Error::object_not_found()).unwrap_err();
+        assert_eq!(err.r#type.code(), 404);
+        assert_eq!(err.message(), "No such user.");
+        assert_eq!(NotFound::Null.resolve(Error::object_not_found()).unwrap(), Value::Null);
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::core::action::Action;
+use crate::core::error::Error;
+use crate::core::result::Result;
+
+/// Limits how many times a single identity may invoke a model action within a
+/// sliding time window.
+#[derive(Debug)]
+pub(crate) struct ActionQuota {
+    limit: u32,
+    window: Duration,
+    hits: Mutex<Hits>,
+}
+
+#[derive(Debug)]
+struct Hits {
+    entries: HashMap<String, VecDeque<Instant>>,
+    swept: Instant,
+}
+
+impl ActionQuota {
+
+    pub(crate) fn new(limit: u32, window: Duration) -> Self {
+        Self { limit, window, hits: Mutex::new(Hits { entries: HashMap::new(), swept: Instant::now() }) }
+    }
+
+    pub(crate) fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub(crate) fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Records a hit for `identity` at `now`. Returns false without recording
+    /// if the identity has already used up its allowance in the window.
+    pub(crate) fn acquire(&self, identity: &str, now: Instant) -> bool {
+        let window = self.window;
+        let mut hits = self.hits.lock().unwrap();
+        // identities without a hit in the last window have their whole allowance
+        if now.saturating_duration_since(hits.swept) >= window {
+            hits.entries.retain(|_, entries| entries.back().map_or(false, |last| now.saturating_duration_since(*last) < window));
+            hits.swept = now;
+        }
+        let entries = hits.entries.entry(identity.to_owned()).or_insert_with(VecDeque::new);
+        while let Some(first) = entries.front() {
+            if now.saturating_duration_since(*first) >= window {
+                entries.pop_front();
+            } else {
+                break;
+            }
+        }
+        if entries.len() as u32 >= self.limit {
+            return false;
+        }
+        entries.push_back(now);
+        true
+    }
+}
+
+/// Consumes one unit of `identity`'s allowance for `action`, failing with a
+/// too many requests error when it is exhausted. Actions without a quota are
+/// always allowed.
+pub(crate) fn check_action_quota(quotas: &HashMap<Action, ActionQuota>, action: Action, identity: &str, now: Instant) -> Result<()> {
+    match quotas.get(&action) {
+        Some(quota) => if quota.acquire(identity, now) {
+            Ok(())
+        } else {
+            Err(Error::too_many_requests(format!("Quota of {} '{}' requests per {} seconds exceeded.", quota.limit(), action.as_handler_str(), quota.window().as_secs())))
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::action::{AGGREGATE_HANDLER, FIND_MANY_HANDLER};
+    use crate::core::error::ErrorType;
+    use super::*;
+
+    #[test]
+    fn identity_is_limited_within_window_and_restored_after_it() {
+        let quota = ActionQuota::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(quota.acquire("User:1", start));
+        assert!(quota.acquire("User:1", start + Duration::from_secs(1)));
+        assert!(!quota.acquire("User:1", start + Duration::from_secs(2)));
+        assert!(quota.acquire("User:2", start + Duration::from_secs(2)));
+        assert!(quota.acquire("User:1", start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn idle_identities_are_dropped_after_a_window() {
+        let quota = ActionQuota::new(2, Duration::from_secs(10));
+        let start = Instant::now();
+        assert!(quota.acquire("10.0.0.1", start));
+        assert!(quota.acquire("10.0.0.2", start + Duration::from_secs(5)));
+        assert_eq!(quota.hits.lock().unwrap().entries.len(), 2);
+        assert!(quota.acquire("10.0.0.3", start + Duration::from_secs(12)));
+        let hits = quota.hits.lock().unwrap();
+        assert_eq!(hits.entries.len(), 2);
+        assert!(!hits.entries.contains_key("10.0.0.1"));
+    }
+
+    #[test]
+    fn exceeding_one_action_quota_leaves_other_actions_unaffected() {
+        let aggregate = Action::from_u32(AGGREGATE_HANDLER);
+        let find_many = Action::from_u32(FIND_MANY_HANDLER);
+        let mut quotas = HashMap::new();
+        quotas.insert(aggregate, ActionQuota::new(1, Duration::from_secs(60)));
+        let now = Instant::now();
+        assert!(check_action_quota(&quotas, aggregate, "User:1", now).is_ok());
+        let err = check_action_quota(&quotas, aggregate, "User:1", now).unwrap_err();
+        assert_eq!(err.r#type, ErrorType::TooManyRequests);
+        assert_eq!(err.r#type.code(), 429);
+        assert_eq!(err.message(), "Quota of 1 'aggregate' requests per 60 seconds exceeded.");
+        for _ in 0..10 {
+            assert!(check_action_quota(&quotas, find_many, "User:1", now).is_ok());
+        }
+    }
+}
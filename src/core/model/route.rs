@@ -0,0 +1,23 @@
+/// A custom route pattern which replaces a model's URL segment name.
+///
+/// Patterns may be nested, like `/v1/users/:userId/posts`. A `:name` segment
+/// matches any single path segment.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RoutePattern {
+    segments: Vec<String>,
+}
+
+impl RoutePattern {
+
+    pub(crate) fn new(pattern: impl AsRef<str>) -> Self {
+        Self {
+            segments: pattern.as_ref().split("/").filter(|s| !s.is_empty()).map(|s| s.to_owned()).collect()
+        }
+    }
+
+    pub(crate) fn matches(&self, path_components: &[&str]) -> bool {
+        self.segments.len() == path_components.len() && self.segments.iter().zip(path_components.iter()).all(|(segment, component)| {
+            segment.starts_with(":") || segment == component
+        })
+    }
+}
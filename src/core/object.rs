@@ -8,6 +8,7 @@ use tokio::sync::Mutex as TokioMutex;
 use std::sync::atomic::{AtomicBool, Ordering};
 use key_path::{KeyPath, path};
 use async_recursion::async_recursion;
+use chrono::Utc;
 use maplit::hashmap;
 use indexmap::IndexMap;
 use to_mut::ToMut;
@@ -15,7 +16,8 @@ use to_mut_proc_macro::ToMut;
 use crate::core::action::{Action, CONNECT, CONNECT_OR_CREATE, CREATE, PROGRAM_CODE, DELETE, DISCONNECT, FIND, JOIN_CREATE, JOIN_DELETE, MANY, NESTED, SINGLE, UPDATE, UPSERT, NESTED_CREATE_ACTION, NESTED_DISCONNECT_ACTION, NESTED_SET_ACTION, NESTED_CONNECT_ACTION, NESTED_DELETE_MANY_ACTION, NESTED_UPDATE_MANY_ACTION, NESTED_UPDATE_ACTION, NESTED_DELETE_ACTION, NESTED_CONNECT_OR_CREATE_ACTION, NESTED_UPSERT_ACTION, INTERNAL_POSITION, SET};
 use crate::core::action::source::ActionSource;
 use crate::core::field::{Field, PreviousValueRule};
-use crate::core::field::optionality::Optionality;
+use crate::core::field::optionality::{is_missing_when_required, Optionality};
+use crate::core::field::r#type::FieldTypeOwner;
 use crate::core::input::Input;
 use crate::core::input::Input::{AtomicUpdator, SetValue};
 use crate::core::graph::Graph;
@@ -23,8 +25,14 @@ use crate::core::model::Model;
 use crate::core::relation::Relation;
 use crate::core::connector::SaveSession;
 use crate::core::pipeline::ctx::{Ctx};
+use crate::core::pipeline::item::Item;
+use crate::core::pipeline::items::query::unique_within::UniqueWithinItem;
 use crate::core::teon::Value;
-use crate::core::error::{Error, ErrorType};
+use crate::core::teon::filter::matches_where;
+use crate::core::teon::diff::{diff, FieldChange};
+use crate::core::teon::redact::{redact, REDACTED};
+use crate::core::error::{Error, ErrorType, ValidationErrors};
+use crate::core::field::read_rule::ReadRule;
 use crate::core::field::write_rule::WriteRule;
 use crate::core::relation::delete_rule::DeleteRule;
 use crate::core::relation::delete_rule::DeleteRule::Deny;
@@ -53,10 +61,12 @@ pub(crate) struct ObjectInner {
     pub(crate) modified_fields: Arc<Mutex<HashSet<String>>>,
     pub(crate) value_map: Arc<Mutex<HashMap<String, Value>>>,
     pub(crate) previous_value_map: Arc<Mutex<HashMap<String, Value>>>,
+    pub(crate) original_value_map: Arc<Mutex<HashMap<String, Value>>>,
     pub(crate) atomic_updator_map: Arc<Mutex<HashMap<String, Value>>>,
     pub(crate) relation_mutation_map: Arc<TokioMutex<HashMap<String, Value>>>,
     pub(crate) relation_query_map: Arc<Mutex<HashMap<String, Vec<Object>>>>,
     pub(crate) cached_property_map: Arc<Mutex<HashMap<String, Value>>>,
+    pub(crate) read_value_map: Arc<Mutex<HashMap<String, Value>>>,
     pub(crate) object_set_map: Arc<TokioMutex<HashMap<String, Option<Object>>>>,
     pub(crate) object_set_many_map: Arc<TokioMutex<HashMap<String, Vec<Object>>>>,
     pub(crate) object_connect_map: Arc<TokioMutex<HashMap<String, Vec<Object>>>>,
@@ -90,11 +100,13 @@ impl Object {
                 selected_fields: Arc::new(Mutex::new(Vec::new())),
                 modified_fields: Arc::new(Mutex::new(HashSet::new())),
                 previous_value_map: Arc::new(Mutex::new(HashMap::new())),
+                original_value_map: Arc::new(Mutex::new(HashMap::new())),
                 value_map: Arc::new(Mutex::new(HashMap::new())),
                 atomic_updator_map: Arc::new(Mutex::new(HashMap::new())),
                 relation_query_map: Arc::new(Mutex::new(HashMap::new())),
                 relation_mutation_map: Arc::new(TokioMutex::new(HashMap::new())),
                 cached_property_map: Arc::new(Mutex::new(HashMap::new())),
+                read_value_map: Arc::new(Mutex::new(HashMap::new())),
                 object_set_map: Arc::new(TokioMutex::new(HashMap::new())),
                 object_set_many_map: Arc::new(TokioMutex::new(HashMap::new())),
                 object_connect_map: Arc::new(TokioMutex::new(HashMap::new())),
@@ -145,7 +157,8 @@ impl Object {
         } else {
             self.model().all_keys().iter().collect::<Vec<&String>>()
         };
-        // assign values
+        // assign values, collecting validation errors of all fields
+        let mut validation_errors = ValidationErrors::new();
         for key in keys {
             let path = path + key;
             if let Some(field) = self.model().field(key) {
@@ -181,7 +194,13 @@ impl Object {
                             let context = Ctx::initial_state_with_object(self.clone())
                                 .with_path(path.clone())
                                 .with_value(value);
-                            let value = field.on_set_pipeline.process(context).await?;
+                            let value = match field.perform_on_set_callback(context).await {
+                                Ok(value) => value,
+                                Err(err) => {
+                                    validation_errors.collect(err)?;
+                                    continue
+                                }
+                            };
                             self.check_write_rule(key, &value, &path).await?;
                             self.set_value_to_value_map(key, value.clone());
                         }
@@ -192,6 +211,11 @@ impl Object {
                     Some(value) => value,
                     None => continue,
                 };
+                // objects are linked on save, before or after this object by the side of the foreign key
+                if let Some(objects) = Self::relation_objects(manipulation) {
+                    self.inner.object_connect_map.lock().await.entry(key.to_owned()).or_insert_with(Vec::new).extend(objects);
+                    continue;
+                }
                 self.set_value_to_relation_manipulation_map(key, manipulation).await;
             } else if let Some(property) = self.model().property(key) {
                 if value_map_keys.contains(&key) {
@@ -209,11 +233,25 @@ impl Object {
                 }
             }
         };
+        validation_errors.into_result()?;
         // set flag
         self.inner.is_initialized.store(true, Ordering::SeqCst);
         Ok(())
     }
 
+    /// Checks updating this object with atomic updators which are applied to
+    /// many records at once, the same way assigning them does.
+    pub(crate) async fn check_atomic_update(&self, updators: &HashMap<String, Value>, path: &KeyPath<'_>) -> Result<()> {
+        self.check_model_write_permission(path).await?;
+        for (key, updator) in updators {
+            let path = path + key;
+            let field = self.model().field(key).unwrap();
+            self.check_field_write_permission(field, &path).await?;
+            self.check_write_rule(key, updator, &path).await?;
+        }
+        Ok(())
+    }
+
     async fn check_model_write_permission<'a>(&self, path: impl AsRef<KeyPath<'a>>) -> Result<()> {
         let ctx = Ctx::initial_state_with_object(self.clone()).with_path(path.as_ref());
         self.model().can_mutate_pipeline().process_into_permission_result(ctx).await
@@ -232,6 +270,9 @@ impl Object {
 
     async fn check_field_read_permission<'a>(&self, field: &Field, _path: impl AsRef<KeyPath<'a>>) -> Result<()> {
         let ctx = Ctx::initial_state_with_object(self.clone()).with_value(self.get_value(field.name()).unwrap()).with_path(path![field.name()]);
+        if let ReadRule::ReadIf(pipeline) = &field.read_rule {
+            pipeline.process_into_permission_result(ctx.clone()).await?;
+        }
         field.can_read_pipeline.process_into_permission_result(ctx).await
     }
 
@@ -330,11 +371,17 @@ impl Object {
     }
 
     fn set_value_to_value_map(&self, key: &str, value: Value) {
+        if !self.is_new() {
+            // the loaded value of each modified field, for diffs
+            let original = self.get_value_map_value(key);
+            self.inner.original_value_map.lock().unwrap().entry(key.to_string()).or_insert(original);
+        }
         if value.is_null() {
             self.inner.value_map.lock().unwrap().remove(key);
         } else {
             self.inner.value_map.lock().unwrap().insert(key.to_string(), value);
         }
+        self.inner.read_value_map.lock().unwrap().remove(key);
         if !self.is_new() {
             self.inner.is_modified.store(true, Ordering::SeqCst);
             self.inner.modified_fields.lock().unwrap().insert(key.to_string());
@@ -391,6 +438,25 @@ impl Object {
         }
     }
 
+    /// The object's values and fetched relations for logging, with values of
+    /// sensitive and unreadable fields replaced.
+    pub fn redacted(&self) -> Value {
+        redact(self.graph(), self.model().name(), &self.values_with_fetched_relations())
+    }
+
+    fn values_with_fetched_relations(&self) -> Value {
+        let mut map = self.inner.value_map.lock().unwrap().clone();
+        for (key, objects) in self.inner.relation_query_map.lock().unwrap().iter() {
+            let value = if self.model().relation(key).map_or(false, |r| r.is_vec()) {
+                Value::Vec(objects.iter().map(|o| o.values_with_fetched_relations()).collect())
+            } else {
+                objects.get(0).map_or(Value::Null, |o| o.values_with_fetched_relations())
+            };
+            map.insert(key.clone(), value);
+        }
+        Value::HashMap(map)
+    }
+
     pub async fn get_property<T>(&self, key: impl AsRef<str>) -> Result<T> where T: From<Value> {
         let property = self.model().property(key.as_ref()).unwrap();
         if property.cached {
@@ -407,7 +473,12 @@ impl Object {
         Ok(value.into())
     }
 
+    /// Values of fields with an `on_get` pipeline are returned in their
+    /// readable form once the object is loaded or saved.
     pub fn get<T>(&self, key: impl AsRef<str>) -> Result<T> where T: From<Value> {
+        if let Some(value) = self.inner.read_value_map.lock().unwrap().get(key.as_ref()) {
+            return Ok(value.clone().into());
+        }
         match self.get_value(key) {
             Ok(optional_value) => {
                 Ok(optional_value.into())
@@ -447,6 +518,13 @@ impl Object {
         Ok(self.get_value_map_value(key.as_ref()))
     }
 
+    /// Whether this object satisfies a decoded `where` input, evaluated in
+    /// memory. This is how virtual models, which aren't backed by a database,
+    /// are filtered.
+    pub fn matches_where(&self, r#where: &Value) -> bool {
+        matches_where(r#where, &|key: &str| self.get_value_map_value(key))
+    }
+
     pub(crate) fn get_atomic_updator(&self, key: &str) -> Option<Value> {
         self.inner.atomic_updator_map.lock().unwrap().get(key).cloned()
     }
@@ -510,6 +588,7 @@ impl Object {
     pub(crate) async fn apply_on_save_pipeline_and_validate_required_fields(&self, path: &KeyPath) -> Result<()> {
         // apply on save pipeline first
         let model_keys = self.model().save_keys();
+        let mut validation_errors = ValidationErrors::new();
         for key in model_keys {
             let field = self.model().field(key);
             if field.is_none() {
@@ -531,7 +610,7 @@ impl Object {
                 let result = field.perform_on_save_callback(context).await;
                 match result {
                     Err(err) => {
-                        return Err(Error::unexpected_input_value_with_reason(err.message, &(path + key)));
+                        validation_errors.collect(Error::unexpected_input_value_with_reason(err.message, &(path + key)))?;
                     }
                     Ok(value) => {
                         self.inner.value_map.lock().unwrap().insert(key.to_string(), value);
@@ -543,6 +622,22 @@ impl Object {
                 }
             }
         }
+        validation_errors.into_result()?;
+        // validate uniqueness within scopes
+        for key in model_keys {
+            if let Some(field) = self.model().field(key) {
+                if let Some(scope) = &field.unique_within {
+                    let context = Ctx::initial_state_with_object(self.clone())
+                        .with_value(self.get_value(key).unwrap())
+                        .with_path(path![field.name.as_str()]);
+                    UniqueWithinItem::new(field.name(), scope.clone()).call(context).await?;
+                }
+            }
+        }
+        // assign sequence values
+        if self.is_new() {
+            self.assign_sequence_values().await?;
+        }
         // validate required fields
         for key in model_keys {
             if let Some(field) = self.model().field(key) {
@@ -608,14 +703,11 @@ impl Object {
                             }
                         }
                     }
-                    Optionality::PresentIf(pipeline) => {
+                    Optionality::PresentIf(condition) => {
                         let value = self.get_value(key).unwrap();
-                        if value.is_null() {
-                            let ctx = Ctx::initial_state_with_object(self.clone());
-                            let invalid = pipeline.process(ctx).await.is_err();
-                            if invalid {
-                                return Err(Error::missing_required_input_with_type(key, path))
-                            }
+                        let ctx = Ctx::initial_state_with_object(self.clone()).with_value(Value::Object(self.clone()));
+                        if is_missing_when_required(condition, ctx, &value).await {
+                            return Err(Error::missing_required_input_with_type(key, path))
                         }
                     }
                 }
@@ -644,6 +736,34 @@ impl Object {
                 }
             }
         }
+        // validate model
+        let validate_pipeline = self.model().validate_pipeline();
+        if validate_pipeline.has_any_items() {
+            let ctx = Ctx::initial_state_with_object(self.clone())
+                .with_value(Value::Object(self.clone()))
+                .with_path(path);
+            validate_pipeline.process_into_validation_result(ctx).await?;
+        }
+        Ok(())
+    }
+
+    async fn assign_sequence_values(&self) -> Result<()> {
+        for field in self.model().fields() {
+            if let Some(sequence) = &field.sequence {
+                if !self.get_value(field.name()).unwrap().is_null() {
+                    continue
+                }
+                let scope_values = sequence.scope.iter().map(|k| self.get_value(k).unwrap()).collect();
+                let name = sequence.counter_name(self.model().name(), field.name(), &scope_values);
+                let count = self.model().connector().increment_sequence(&name).await?;
+                let value = sequence.value_for_count(count);
+                if field.field_type().is_int32() {
+                    self.set_value_to_value_map(field.name(), Value::I32(value as i32));
+                } else {
+                    self.set_value_to_value_map(field.name(), Value::I64(value));
+                }
+            }
+        }
         Ok(())
     }
 
@@ -666,6 +786,7 @@ impl Object {
     #[async_recursion]
     pub(crate) async fn delete_from_database(&self, session: Arc<dyn SaveSession>) -> Result<()> {
         let model = self.model();
+        model.check_append_only()?;
         let graph = self.graph();
         // check deny first
         for relation in model.relations() {
@@ -675,17 +796,25 @@ impl Object {
             let (opposite_model, opposite_relation) = graph.opposite_relation(relation);
             if let Some(opposite_relation) = opposite_relation {
                 if opposite_relation.delete_rule() == Deny {
-                    let finder = self.intrinsic_where_unique_for_relation(relation);
-                    let count = graph.count(opposite_model.name(), &finder).await.unwrap();
+                    let finder = teon!({"where": self.intrinsic_where_unique_for_relation(relation)});
+                    let count = graph.count(opposite_model.name(), &finder).await?;
                     if count > 0 {
-                        return Err(Error::deletion_denied(relation.name()));
+                        return Err(Error::required_relation_cannot_disconnect(relation.name()));
                     }
                 }
             }
         }
+        // soft delete only marks the record, its relations are kept
+        let connector = self.model().connector();
+        if let Some(field_name) = model.soft_delete_field() {
+            self.set_value_to_value_map(field_name, Value::DateTime(Utc::now()));
+            connector.save_object(self, session.clone()).await?;
+            self.clear_state();
+            return self.update_counter_caches(-1, session).await;
+        }
         // real delete
-        let connector = self.graph().connector();
         connector.delete_object(self, session.clone()).await?;
+        self.update_counter_caches(-1, session.clone()).await?;
         // nullify and cascade
         for relation in model.relations() {
             if relation.through().is_some() {
@@ -700,21 +829,22 @@ impl Object {
                         if !opposite_relation.has_foreign_key() {
                             continue
                         }
-                        let finder = self.intrinsic_where_unique_for_relation(relation);
-                        graph.batch(opposite_model.name(), &finder, Action::from_u32(PROGRAM_CODE | DISCONNECT | (if relation.is_vec() { MANY } else { SINGLE })), ActionSource::ProgramCode, |object| async move {
+                        // the records leave the finder as they're updated, so they're fetched at once
+                        let finder = teon!({"where": self.intrinsic_where_unique_for_relation(relation)});
+                        let action = Action::from_u32(PROGRAM_CODE | DISCONNECT | (if relation.is_vec() { MANY } else { SINGLE }));
+                        for object in graph.find_many_internal(opposite_model.name(), &finder, true, action, ActionSource::ProgramCode).await? {
                             for key in opposite_relation.fields() {
                                 object.set_value(key, Value::Null)?;
                             }
-                            object.save_with_session_and_path(self.graph().connector().new_save_session(), &path![]).await?;
-                            Ok(())
-                        }).await?;
+                            object.save_with_session_and_path(session.clone(), &path![]).await?;
+                        }
                     },
                     DeleteRule::Cascade => {
-                        let finder = self.intrinsic_where_unique_for_relation(relation);
-                        graph.batch(opposite_model.name(), &finder, Action::from_u32(PROGRAM_CODE | DELETE | (if relation.is_vec() { MANY } else { SINGLE })), ActionSource::ProgramCode, |object| async move {
-                            object.delete_from_database(self.graph().connector().new_save_session()).await?;
-                            Ok(())
-                        }).await?;
+                        let finder = teon!({"where": self.intrinsic_where_unique_for_relation(relation)});
+                        let action = Action::from_u32(PROGRAM_CODE | DELETE | (if relation.is_vec() { MANY } else { SINGLE }));
+                        for object in graph.find_many_internal(opposite_model.name(), &finder, true, action, ActionSource::ProgramCode).await? {
+                            object.delete_from_database(session.clone()).await?;
+                        }
                     }
                 }
             }
@@ -722,9 +852,60 @@ impl Object {
         Ok(())
     }
 
+    /// Atomically adjust the cached counts of the records this object points to.
+    async fn update_counter_caches(&self, delta: i64, session: Arc<dyn SaveSession>) -> Result<()> {
+        for relation in self.model().relations() {
+            if relation.counter_cache().is_none() {
+                continue
+            }
+            self.update_counter_cache(relation, self.intrinsic_where_unique_for_relation(relation), delta, session.clone()).await?;
+        }
+        Ok(())
+    }
+
+    /// Move the cached counts over when the foreign key of a relation is reassigned.
+    async fn move_counter_caches(&self, session: Arc<dyn SaveSession>) -> Result<()> {
+        if let Some(field_name) = self.model().soft_delete_field() {
+            if !self.get_value_map_value(field_name).is_null() {
+                return Ok(());
+            }
+        }
+        let modified_fields = self.inner.modified_fields.lock().unwrap().clone();
+        for relation in self.model().relations() {
+            if relation.counter_cache().is_none() || !relation.fields().iter().any(|f| modified_fields.contains(f)) {
+                continue
+            }
+            let original_value_map = self.inner.original_value_map.lock().unwrap().clone();
+            let previous_where = Value::HashMap(relation.iter().map(|(f, r)| {
+                (r.to_owned(), original_value_map.get(f).cloned().unwrap_or_else(|| self.get_value_map_value(f)))
+            }).collect());
+            self.update_counter_cache(relation, previous_where, -1, session.clone()).await?;
+            self.update_counter_cache(relation, self.intrinsic_where_unique_for_relation(relation), 1, session.clone()).await?;
+        }
+        Ok(())
+    }
+
+    async fn update_counter_cache(&self, relation: &Relation, r#where: Value, delta: i64, session: Arc<dyn SaveSession>) -> Result<()> {
+        if r#where.as_hashmap().unwrap().values().any(|v| v.is_null()) {
+            return Ok(());
+        }
+        let graph = self.graph();
+        let counter_cache = relation.counter_cache().unwrap();
+        let opposite_model = graph.model(relation.model()).unwrap();
+        let updator = Relation::counter_cache_updator(opposite_model.field(counter_cache).unwrap().field_type(), delta);
+        graph.batch(opposite_model.name(), &teon!({"where": r#where}), Action::from_u32(PROGRAM_CODE | UPDATE | SINGLE), ActionSource::ProgramCode, |object| {
+            let updator = updator.clone();
+            let session = session.clone();
+            async move {
+                object.set_value_to_atomic_updator_map(counter_cache, updator);
+                object.save_with_session_and_path(session, &path![]).await
+            }
+        }).await
+    }
+
     #[async_recursion]
     async fn save_to_database(&self, session: Arc<dyn SaveSession>) -> Result<()> {
-        let connector = self.graph().connector();
+        let connector = self.model().connector();
         connector.save_object(self, session).await?;
         self.clear_new_state();
         Ok(())
@@ -745,6 +926,9 @@ impl Object {
         let is_new = self.is_new();
         // validate and save
         let is_modified = self.is_modified();
+        if is_modified && !is_new {
+            self.model().check_append_only()?;
+        }
         if is_modified || is_new {
             // apply pipeline
             self.apply_on_save_pipeline_and_validate_required_fields(path).await?;
@@ -753,6 +937,11 @@ impl Object {
             self.perform_relation_manipulations(|r| r.has_foreign_key(), session.clone(), path).await?;
             if !self.model().r#virtual() {
                 self.save_to_database(session.clone()).await?;
+                if is_new {
+                    self.update_counter_caches(1, session.clone()).await?;
+                } else {
+                    self.move_counter_caches(session.clone()).await?;
+                }
             }
         } else {
             // perform relation manipulations (has foreign key)
@@ -763,13 +952,17 @@ impl Object {
         // clear properties
         self.clear_state();
         if is_modified || is_new {
-            self.trigger_after_save_callbacks(path).await?;
+            self.decode_read_values().await?;
+            let result = self.trigger_after_save_callbacks(path).await;
+            // after save callbacks see the diff, the next save diffs against the saved values
+            self.inner.original_value_map.lock().unwrap().clear();
+            result?;
         }
         Ok(())
     }
 
     pub async fn save(&self) -> Result<()> {
-        let session = self.graph().connector().new_save_session();
+        let session = self.model().connector().new_save_session();
         self.save_with_session_and_path(session, &path![]).await
     }
 
@@ -787,11 +980,40 @@ impl Object {
         pipeline.process_into_permission_result(ctx).await
     }
 
+    /// Runs the `on_get` pipelines of the stored values.
+    async fn decode_read_values(&self) -> Result<()> {
+        let mut read_values = HashMap::new();
+        for field in self.model().fields() {
+            if !field.on_get_pipeline.has_any_items() {
+                continue
+            }
+            let ctx = Ctx::initial_state_with_object(self.clone())
+                .with_value(self.get_value_map_value(field.name()))
+                .with_path(path![field.name()]);
+            read_values.insert(field.name().to_owned(), field.perform_on_get_callback(ctx).await?);
+        }
+        *self.inner.read_value_map.lock().unwrap() = read_values;
+        Ok(())
+    }
+
+    pub(crate) async fn trigger_after_find_callbacks(&self) -> Result<()> {
+        self.decode_read_values().await?;
+        let model = self.model();
+        let pipeline = model.after_find_pipeline();
+        let ctx = Ctx::initial_state_with_object(self.clone());
+        pipeline.process_into_permission_result(ctx).await
+    }
+
+    /// Values which the callbacks set on the object are written along with
+    /// the rest of the record. Saving the object inside them is an error.
     async fn trigger_before_save_callbacks<'a>(&self, path: impl AsRef<KeyPath<'a>>) -> Result<()> {
         let model = self.model();
         let pipeline = model.before_save_pipeline();
         let ctx = Ctx::initial_state_with_object(self.clone()).with_path(path.as_ref());
-        pipeline.process_into_permission_result(ctx).await
+        self.inner.inside_before_save_callback.store(true, Ordering::SeqCst);
+        let result = pipeline.process_into_permission_result(ctx).await;
+        self.inner.inside_before_save_callback.store(false, Ordering::SeqCst);
+        result
     }
 
     async fn trigger_after_save_callbacks<'a>(&self, path: impl AsRef<KeyPath<'a>>) -> Result<()> {
@@ -810,13 +1032,13 @@ impl Object {
 
     pub async fn delete(&self) -> Result<()> {
         self.trigger_before_delete_callbacks(path![]).await?;
-        self.delete_from_database(self.graph().connector().new_save_session()).await
+        self.delete_from_database(self.model().connector().new_save_session()).await
     }
 
     pub(crate) async fn delete_internal<'a>(&self, path: impl AsRef<KeyPath<'a>>) -> Result<()> {
         self.check_model_write_permission(path.as_ref()).await?;
         self.trigger_before_delete_callbacks(path.as_ref()).await?;
-        self.delete_from_database(self.graph().connector().new_save_session()).await?;
+        self.delete_from_database(self.model().connector().new_save_session()).await?;
         self.trigger_after_delete_callbacks(path.as_ref()).await
     }
 
@@ -829,6 +1051,9 @@ impl Object {
         let select_filter = if select_list.is_empty() { false } else { true };
         let mut map: IndexMap<String, Value> = IndexMap::new();
         let keys = self.model().output_keys();
+        let naming = self.graph().field_naming();
+        let role = self.action_source().as_identity().map(|identity| identity.model().name());
+        let readable = self.model().readable_output_keys(role);
         for key in keys {
             if let Some(relation) = self.model().relation(key) {
                 if self.has_query_relation_fetched(relation.name()) {
@@ -836,7 +1061,7 @@ impl Object {
                         let o = self.get_query_relation_object(key).unwrap();
                         match o {
                             Some(o) => {
-                                map.insert(key.to_string(), o.to_json_internal(&(path.as_ref() + relation.name())).await.unwrap());
+                                map.insert(naming.api_key(key).into_owned(), o.to_json_internal(&(path.as_ref() + relation.name())).await.unwrap());
                             },
                             None => ()
                         };
@@ -846,34 +1071,47 @@ impl Object {
                         for (index, o) in vec.iter().enumerate() {
                             result_vec.push(o.to_json_internal(&(path.as_ref() + relation.name() + index)).await?);
                         }
-                        map.insert(key.to_string(), Value::Vec(result_vec));
+                        map.insert(naming.api_key(key).into_owned(), Value::Vec(result_vec));
                     }
                 }
             } else if (!select_filter) || (select_filter && select_list.contains(key)) {
                 if let Some(field) = self.model().field(key) {
-                    let value = self.get_value(key).unwrap();
-                    if self.check_field_read_permission(field, path.as_ref()).await.is_err() {
+                    if !readable.contains(key) {
+                        continue
+                    }
+                    let guarded = self.model().read_predicate_keys().contains(key);
+                    if guarded && self.check_field_read_permission(field, path.as_ref()).await.is_err() {
                         continue
                     }
+                    let read_value = self.inner.read_value_map.lock().unwrap().get(key).cloned();
+                    let value = match read_value {
+                        Some(value) => value,
+                        None => {
+                            let context = Ctx::initial_state_with_object(self.clone())
+                                .with_value(self.get_value(key).unwrap())
+                                .with_path(path![key.as_str()]);
+                            field.perform_on_get_callback(context).await?
+                        }
+                    };
                     let context = Ctx::initial_state_with_object(self.clone())
                         .with_value(value)
                         .with_path(path![key.as_str()]);
                     let value = field.perform_on_output_callback(context).await?;
                     if !value.is_null() {
-                        map.insert(key.to_string(), value);
+                        map.insert(naming.api_key(field.api_name()).into_owned(), value.with_date_time_format(self.graph().date_time_format()));
                     }
                 } else if let Some(property) = self.model().property(key) {
                     if property.cached && self.inner.cached_property_map.lock().unwrap().contains_key(key) {
                         let value = self.inner.cached_property_map.lock().unwrap().get(key).unwrap().clone();
                         if !value.is_null() {
-                            map.insert(key.to_string(), value);
+                            map.insert(naming.api_key(key).into_owned(), value);
                         }
                     } else {
                         if let Some(getter) = &property.getter {
                             let ctx = Ctx::initial_state_with_object(self.clone());
                             let value = getter.process(ctx).await?;
                             if !value.is_null() {
-                                map.insert(key.to_string(), value);
+                                map.insert(naming.api_key(key).into_owned(), value);
                             }
                         }
                     }
@@ -891,6 +1129,24 @@ impl Object {
         self.inner.is_modified.load(Ordering::SeqCst)
     }
 
+    /// The fields changed since the object was loaded or last saved, with old
+    /// and new values.
+    pub fn diff(&self) -> Vec<FieldChange> {
+        self.diff_with_redaction(|_| false)
+    }
+
+    /// Like `diff`, values of sensitive and unreadable fields are replaced with `"***"`.
+    pub fn redacted_diff(&self) -> Vec<FieldChange> {
+        let model = self.model();
+        self.diff_with_redaction(|key| model.field(key).map_or(false, |f| f.is_redacted()))
+    }
+
+    fn diff_with_redaction(&self, is_redacted: impl Fn(&str) -> bool) -> Vec<FieldChange> {
+        let original = self.inner.original_value_map.lock().unwrap();
+        let current = self.inner.value_map.lock().unwrap();
+        diff(self.model().fields().iter().map(|f| f.name()), &original, &current, is_redacted)
+    }
+
     pub fn model(&self) -> &Model {
         &self.inner.model
     }
@@ -1072,36 +1328,44 @@ impl Object {
     }
 
     async fn nested_set_many_relation_object_object(&self, relation: &Relation, objects: &Vec<Object>, session: Arc<dyn SaveSession>, path: &KeyPath<'_>) -> Result<()> {
-        // disconnect previous
         let records = self.fetch_relation_objects(relation.name(), None).await?;
-        for record in records.iter() {
+        // disconnect records which aren't listed
+        for record in records.iter().filter(|r| !objects.contains(r)) {
             self.nested_disconnect_relation_object_object(relation, record, session.clone(), path).await?;
         }
-        // connect new
-        for object in objects {
-            self.link_and_save_relation_object(relation, &object, session.clone(), path).await?;
+        // connect listed records which aren't connected yet, each once
+        for (index, object) in objects.iter().enumerate() {
+            if !records.contains(object) && !objects[..index].contains(object) {
+                self.link_and_save_relation_object(relation, object, session.clone(), path).await?;
+            }
         }
         Ok(())
     }
 
     async fn nested_set_many_relation_object(&self, relation: &Relation, value: &Value, session: Arc<dyn SaveSession>, path: &KeyPath<'_>) -> Result<()> {
-        // disconnect previous
-        let records = self.fetch_relation_objects(relation.name(), None).await?;
-        for record in records.iter() {
-            self.nested_disconnect_relation_object_object(relation, record, session.clone(), path).await?;
-        }
-        // connect new
-        let value_vec = value.as_vec().unwrap();
-        for value in value_vec {
-            self.nested_connect_relation_object(relation, value, session.clone(), path).await?;
+        let values = match value.as_vec() {
+            Some(values) => values.clone(),
+            None => vec![value.clone()],
+        };
+        // find every listed record before the relation is changed
+        let action = Action::from_u32(NESTED | SET | MANY);
+        let mut objects = vec![];
+        for (index, value) in values.iter().enumerate() {
+            match self.graph().find_unique_internal(relation.model(), &teon!({ "where": value }), true, action, self.action_source().clone()).await {
+                Ok(object) => objects.push(object),
+                Err(_) => return Err(Error::unexpected_input_value_with_reason("Object is not found.", &(path + index))),
+            }
         }
-        Ok(())
+        self.nested_set_many_relation_object_object(relation, &objects, session, path).await
     }
 
     async fn nested_set_relation_object(&self, relation: &Relation, value: &Value, session: Arc<dyn SaveSession>, path: &KeyPath<'_>) -> Result<()> {
         // disconnect old
         let disconnect_value = self.intrinsic_where_unique_for_relation(relation);
         self.nested_disconnect_relation_object(relation, &disconnect_value, session.clone(), path).await?;
+        if value.is_null() {
+            return Ok(());
+        }
         // connect new
         let action = Action::from_u32(NESTED | SET | SINGLE);
         let object = match self.graph().find_unique_internal(relation.model(), &teon!({ "where": value }), true, action, self.action_source().clone()).await {
@@ -1342,15 +1606,27 @@ impl Object {
             let key = key.as_str();
             let path = path + key;
             let action = Action::nested_action_from_name(key).unwrap();
+            // `disconnect: false` and `delete: false` leave the relation as is
+            if (action.to_u32() == NESTED_DISCONNECT_ACTION || action.to_u32() == NESTED_DELETE_ACTION) && value.as_bool() == Some(false) {
+                continue;
+            }
+            self.check_relation_manipulation(action, &path)?;
             let other_model = self.graph().opposite_relation(relation).0;
             let normalized_value = self.normalize_relation_one_value(relation, action, value);
-            let ctx = Ctx::initial_state_with_value(normalized_value.as_ref().clone()).with_path(path.clone()).with_action(action);
+            let ctx = Ctx::initial_state_with_value(normalized_value.as_ref().clone()).with_path(path.clone()).with_action(action).with_identity(self.action_source().as_identity_value());
             let (transformed_value, new_action) = other_model.transformed_action(ctx).await?;
             self.perform_relation_manipulation_one_inner(relation, new_action, &transformed_value, session.clone(), &path).await?;
         }
         Ok(())
     }
 
+    fn check_relation_manipulation(&self, action: Action, path: &KeyPath<'_>) -> Result<()> {
+        if self.is_new() && action.to_u32() == NESTED_DISCONNECT_ACTION {
+            return Err(Error::new_object_cannot_disconnect(path));
+        }
+        Ok(())
+    }
+
     fn normalize_relation_many_value<'a>(&'a self, action: Action, value: &'a Value) -> Cow<Value> {
         match action.to_u32() {
             NESTED_CREATE_ACTION => Owned(Value::HashMap(hashmap! {"create".to_owned() => value.clone()})),
@@ -1379,17 +1655,18 @@ impl Object {
             let key = key.as_str();
             let path = path + key;
             let action = Action::nested_action_from_name(key).unwrap();
+            self.check_relation_manipulation(action, &path)?;
             let other_model = self.graph().opposite_relation(relation).0;
             if value.is_vec() && action.to_u32() != NESTED_SET_ACTION {
                 for (index, value) in value.as_vec().unwrap().iter().enumerate() {
                     let normalized_value = self.normalize_relation_many_value(action, value);
-                    let ctx = Ctx::initial_state_with_value(normalized_value.as_ref().clone()).with_path(&(path.clone() + index)).with_action(action);
+                    let ctx = Ctx::initial_state_with_value(normalized_value.as_ref().clone()).with_path(&(path.clone() + index)).with_action(action).with_identity(self.action_source().as_identity_value());
                     let (transformed_value, new_action) = other_model.transformed_action(ctx).await?;
                     self.perform_relation_manipulation_many_inner(relation, new_action, &transformed_value, session.clone(), &path).await?;
                 }
             }  else {
                 let normalized_value = self.normalize_relation_many_value(action, value);
-                let ctx = Ctx::initial_state_with_value(normalized_value.as_ref().clone()).with_path(path.clone()).with_action(action);
+                let ctx = Ctx::initial_state_with_value(normalized_value.as_ref().clone()).with_path(path.clone()).with_action(action).with_identity(self.action_source().as_identity_value());
                 let (transformed_value, new_action) = other_model.transformed_action(ctx).await?;
                 self.perform_relation_manipulation_many_inner(relation, new_action, &transformed_value, session.clone(), &path).await?;
             }
@@ -1415,6 +1692,15 @@ impl Object {
         graph.find_unique_internal(self.model().name(), &finder, false, self.action(), self.action_source().clone()).await
     }
 
+    /// The objects of a relation value which holds objects instead of nested inputs.
+    fn relation_objects(value: &Value) -> Option<Vec<Object>> {
+        match value {
+            Value::Object(object) => Some(vec![object.clone()]),
+            Value::Vec(vec) if !vec.is_empty() => vec.iter().map(|v| v.as_object().cloned()).collect(),
+            _ => None,
+        }
+    }
+
     pub async fn force_set_relation_objects(&self, key: impl AsRef<str>, objects: Vec<Object>) -> () {
         self.inner.object_set_many_map.lock().await.insert(key.as_ref().to_owned(), objects);
     }
@@ -1558,9 +1844,10 @@ impl Object {
 impl Debug for Object {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut result = f.debug_struct(self.model().name());
+        let redacted = Value::String(REDACTED.to_owned());
         for field in self.model().fields() {
             let map = self.inner.value_map.lock().unwrap();
-            let value = map.get(field.name()).unwrap_or(&Value::Null);
+            let value = if field.is_redacted() { &redacted } else { map.get(field.name()).unwrap_or(&Value::Null) };
             result.field(field.name(), value);
         }
         result.finish()
@@ -1570,9 +1857,10 @@ impl Debug for Object {
 impl Display for Object {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut result = f.debug_struct(self.model().name());
+        let redacted = Value::String(REDACTED.to_owned());
         for field in self.model().fields() {
             let map = self.inner.value_map.lock().unwrap();
-            let value = map.get(field.name()).unwrap_or(&Value::Null);
+            let value = if field.is_redacted() { &redacted } else { map.get(field.name()).unwrap_or(&Value::Null) };
             result.field(field.name(), value);
         }
         result.finish()
@@ -1587,3 +1875,143 @@ impl PartialEq for Object {
 
 unsafe impl Send for Object { }
 unsafe impl Sync for Object { }
+
+#[cfg(test)]
+mod tests {
+    use crate::core::field::{FieldIndex, IndexSettings};
+    use crate::core::field::r#type::FieldType;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::pipeline::items::identity::identity::IdentityItem;
+    use crate::core::pipeline::items::object::get::GetItem;
+    use crate::core::pipeline::items::string::validation::is_email::IsEmailItem;
+    use crate::core::pipeline::items::value::exists::ExistsItem;
+    use crate::core::pipeline::items::value::gt::GtItem;
+    use crate::core::pipeline::Pipeline;
+    use crate::core::test_connector::TestConnector;
+    use super::*;
+
+    fn i32_field(name: &str) -> Field {
+        let mut field = Field::new(name.to_owned());
+        field.field_type = Some(FieldType::I32);
+        field
+    }
+
+    /// Events which end after ten.
+    async fn events(connector: Arc<TestConnector>) -> Graph {
+        let mut builder = GraphBuilder::new();
+        builder.model("Event", |m| {
+            let mut id = i32_field("id");
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let ends_after_ten = Pipeline { items: vec![Arc::new(GetItem::new(Value::RawEnumChoice("end".to_owned(), None))), Arc::new(GtItem::new(Value::I32(10)))] };
+            m.field(id).field(i32_field("start")).field(i32_field("end")).validate(ends_after_ten);
+        });
+        builder.build(connector).await
+    }
+
+    /// Posts whose owner defaults to the id of the identity, guests can't set
+    /// the owner.
+    async fn owned_posts() -> Graph {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut id = i32_field("id");
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            m.field(id);
+        });
+        builder.model("Post", |m| {
+            let mut id = i32_field("id");
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let identity_id = Pipeline { items: vec![Arc::new(GetItem::new(Value::RawEnumChoice("id".to_owned(), None)))] };
+            let mut owner_id = i32_field("ownerId");
+            owner_id.default = Some(Value::Pipeline(Pipeline { items: vec![Arc::new(IdentityItem::new(identity_id))] }));
+            owner_id.can_mutate_pipeline = Pipeline { items: vec![Arc::new(IdentityItem::new(Pipeline { items: vec![Arc::new(ExistsItem::new())] }))] };
+            m.field(id).field(owner_id);
+        });
+        builder.build(Arc::new(TestConnector::default())).await
+    }
+
+    async fn post_created_by(graph: &Graph, identity: Option<Object>, initial: Value) -> Result<Object> {
+        let action = Action::from_u32(CREATE | SINGLE | INTERNAL_POSITION);
+        graph.new_object_with_tson_and_path("Post", &initial, &path![], action, ActionSource::Identity(identity)).await
+    }
+
+    #[tokio::test]
+    async fn omitted_owner_is_filled_from_the_identity() {
+        let graph = owned_posts().await;
+        let user = graph.create_object("User", teon!({"id": 5})).await.unwrap();
+        let post = post_created_by(&graph, Some(user), teon!({"id": 1})).await.unwrap();
+        assert_eq!(post.get_value("ownerId").unwrap(), teon!(5));
+    }
+
+    #[tokio::test]
+    async fn explicit_owner_is_kept_after_the_permission_check() {
+        let graph = owned_posts().await;
+        let user = graph.create_object("User", teon!({"id": 5})).await.unwrap();
+        let post = post_created_by(&graph, Some(user), teon!({"id": 1, "ownerId": 8})).await.unwrap();
+        assert_eq!(post.get_value("ownerId").unwrap(), teon!(8));
+        let error = post_created_by(&graph, None, teon!({"id": 2, "ownerId": 8})).await.unwrap_err();
+        assert_eq!(error.r#type, ErrorType::PermissionError);
+    }
+
+    #[tokio::test]
+    async fn validation_errors_of_all_fields_are_returned_together() {
+        let mut builder = GraphBuilder::new();
+        builder.model("Contact", |m| {
+            let mut id = i32_field("id");
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let mut email = Field::new("email".to_owned());
+            email.field_type = Some(FieldType::String);
+            email.on_set_pipeline.items.push(Arc::new(IsEmailItem::new()));
+            let mut age = i32_field("age");
+            age.on_set_pipeline.items.push(Arc::new(GtItem::new(Value::I32(0))));
+            m.field(id).field(email).field(age);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let error = graph.create_object("Contact", teon!({"id": 1, "email": "nope", "age": -1})).await.unwrap_err();
+        assert_eq!(error.r#type, ErrorType::ValidationError);
+        assert_eq!(error.errors.unwrap(), hashmap!{
+            "email".to_owned() => "string value is not email".to_owned(),
+            "age".to_owned() => "gt: value is not greater than rhs".to_owned(),
+        });
+    }
+
+    #[tokio::test]
+    async fn object_passing_model_validation_is_saved() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = events(connector.clone()).await;
+        let event = graph.create_object("Event", teon!({"id": 1, "start": 10, "end": 20})).await.unwrap();
+        event.save().await.unwrap();
+        assert_eq!(connector.saved.lock().unwrap().get("Event").map(|rows| rows.len()), Some(1));
+    }
+
+    #[tokio::test]
+    async fn object_failing_model_validation_is_not_saved() {
+        let connector = Arc::new(TestConnector::default());
+        let graph = events(connector.clone()).await;
+        let event = graph.create_object("Event", teon!({"id": 1, "start": 10, "end": 5})).await.unwrap();
+        let error = event.save().await.unwrap_err();
+        assert_eq!(error.r#type, ErrorType::ValidationError);
+        assert!(connector.saved.lock().unwrap().get("Event").is_none());
+    }
+
+    #[tokio::test]
+    async fn records_of_append_only_models_are_only_created() {
+        let connector = Arc::new(TestConnector::default());
+        let mut builder = GraphBuilder::new();
+        builder.model("AuditLog", |m| {
+            let mut id = i32_field("id");
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            m.append_only().field(id).field(i32_field("level"));
+        });
+        let graph = builder.build(connector.clone()).await;
+        let log = graph.create_object("AuditLog", teon!({"id": 1, "level": 1})).await.unwrap();
+        log.save().await.unwrap();
+        assert_eq!(connector.saved.lock().unwrap().get("AuditLog").map(|rows| rows.len()), Some(1));
+        log.set("level", 2).unwrap();
+        let error = log.save().await.unwrap_err();
+        assert_eq!(error.r#type, ErrorType::InvalidOperation);
+        assert_eq!(error.message(), "Model 'AuditLog' is append only, its records can't be updated or deleted.");
+        let error = log.delete().await.unwrap_err();
+        assert_eq!(error.r#type, ErrorType::InvalidOperation);
+        assert_eq!(connector.saved.lock().unwrap().get("AuditLog").map(|rows| rows.len()), Some(1));
+    }
+}
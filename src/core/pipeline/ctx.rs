@@ -9,6 +9,7 @@ use crate::prelude::Error;
 pub struct Ctx<'a> {
     pub(crate) value: Value,
     pub(crate) object: Option<Object>,
+    pub(crate) identity: Option<Value>,
     pub(crate) path: KeyPath<'a>,
     pub(crate) action: Action,
 }
@@ -19,15 +20,18 @@ impl<'a> Ctx<'a> {
         Self {
             value,
             object: None,
+            identity: None,
             path: KeyPath::default(),
             action: Action::empty(),
         }
     }
 
     pub(crate) fn initial_state_with_object(object: Object) -> Self {
+        let identity = object.action_source().as_identity_value();
         Self {
             value: Value::Null,
             object: Some(object),
+            identity,
             path: KeyPath::default(),
             action: Action::empty(),
         }
@@ -37,15 +41,29 @@ impl<'a> Ctx<'a> {
         Self {
             value: self.value.clone(),
             object: self.object.clone(),
+            identity: self.identity.clone(),
             path: path.as_ref().clone(),
             action: self.action.clone(),
         }
     }
 
+    /// `identity` is `Value::Null` for a guest, and `None` when the action
+    /// isn't sourced from an identity.
+    pub(crate) fn with_identity(&self, identity: Option<Value>) -> Self {
+        Self {
+            value: self.value.clone(),
+            object: self.object.clone(),
+            identity,
+            path: self.path.clone(),
+            action: self.action.clone(),
+        }
+    }
+
     pub(crate) fn with_value(&self, value: Value) -> Self {
         Self {
             value,
             object: self.object.clone(),
+            identity: self.identity.clone(),
             path: self.path.clone(),
             action: self.action.clone(),
         }
@@ -56,6 +74,7 @@ impl<'a> Ctx<'a> {
             Ok(value) => Ok(Self {
                 value,
                 object: self.object.clone(),
+                identity: self.identity.clone(),
                 path: self.path.clone(),
                 action: self.action.clone(),
             }),
@@ -71,6 +90,7 @@ impl<'a> Ctx<'a> {
         Self {
             value: self.value.clone(),
             object: self.object.clone(),
+            identity: self.identity.clone(),
             path: self.path.clone(),
             action,
         }
@@ -105,6 +125,7 @@ impl<'a> Ctx<'a> {
         Self {
             value: self.value.clone(),
             object: self.object.clone(),
+            identity: self.identity.clone(),
             path: self.path.clone(),
             action: self.action.redirect(action),
         }
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use async_trait::async_trait;
 use crate::core::pipeline::ctx::Ctx;
 use crate::core::result::Result;
+use crate::core::teon::Value;
 
 #[async_trait]
 pub trait Item: Debug + Send + Sync {
@@ -9,4 +10,7 @@ pub trait Item: Debug + Send + Sync {
     // fn new(args: Vec<Argument>, table: Arc<Mutex<CallbackLookupTable>>) -> Self where Self: Sized;
 
     async fn call<'a>(&self, ctx: Ctx<'a>) -> Result<Ctx<'a>>;
+
+    /// The JSON Schema keyword and value this item enforces on its input, if it can be described.
+    fn json_schema_keyword(&self) -> Option<(&'static str, Value)> { None }
 }
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use crate::core::pipeline::ctx::Ctx;
+use crate::core::pipeline::item::Item;
+use crate::core::teon::Value;
+use crate::core::result::Result;
+
+#[derive(Debug, Clone)]
+pub struct MaxItemsItem {
+    argument: Value
+}
+
+impl MaxItemsItem {
+    pub fn new(argument: impl Into<Value>) -> Self {
+        Self { argument: argument.into() }
+    }
+}
+
+#[async_trait]
+impl Item for MaxItemsItem {
+    async fn call<'a>(&self, ctx: Ctx<'a>) -> Result<Ctx<'a>> {
+        let bound = self.argument.resolve(ctx.clone()).await?.as_usize().unwrap();
+        let len = match ctx.value.as_vec() {
+            Some(v) => v.len(),
+            None => return Err(ctx.internal_server_error("maxItems: value is not array")),
+        };
+        if len > bound {
+            Err(ctx.with_invalid(format!("Array length is greater than {bound}.")))
+        } else {
+            Ok(ctx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(count: usize) -> Ctx<'static> {
+        Ctx::initial_state_with_value(Value::Vec((0..count).map(|i| Value::String(format!("tag{i}"))).collect()))
+    }
+
+    #[tokio::test]
+    async fn array_at_or_below_maximum_is_valid() {
+        assert!(MaxItemsItem::new(10).call(tags(10)).await.is_ok());
+        assert!(MaxItemsItem::new(10).call(tags(0)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn array_above_maximum_is_invalid() {
+        let err = MaxItemsItem::new(10).call(tags(11)).await.err().unwrap();
+        assert_eq!(err.errors.unwrap().values().next().unwrap(), "Array length is greater than 10.");
+    }
+}
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use crate::core::pipeline::ctx::Ctx;
+use crate::core::pipeline::item::Item;
+use crate::core::teon::Value;
+use crate::core::result::Result;
+
+#[derive(Debug, Clone)]
+pub struct MinItemsItem {
+    argument: Value
+}
+
+impl MinItemsItem {
+    pub fn new(argument: impl Into<Value>) -> Self {
+        Self { argument: argument.into() }
+    }
+}
+
+#[async_trait]
+impl Item for MinItemsItem {
+    async fn call<'a>(&self, ctx: Ctx<'a>) -> Result<Ctx<'a>> {
+        let bound = self.argument.resolve(ctx.clone()).await?.as_usize().unwrap();
+        let len = match ctx.value.as_vec() {
+            Some(v) => v.len(),
+            None => return Err(ctx.internal_server_error("minItems: value is not array")),
+        };
+        if len < bound {
+            Err(ctx.with_invalid(format!("Array length is less than {bound}.")))
+        } else {
+            Ok(ctx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(count: usize) -> Ctx<'static> {
+        Ctx::initial_state_with_value(Value::Vec((0..count).map(|i| Value::String(format!("tag{i}"))).collect()))
+    }
+
+    #[tokio::test]
+    async fn array_at_or_above_minimum_is_valid() {
+        assert!(MinItemsItem::new(1).call(tags(1)).await.is_ok());
+        assert!(MinItemsItem::new(1).call(tags(3)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn array_below_minimum_is_invalid() {
+        let err = MinItemsItem::new(1).call(tags(0)).await.err().unwrap();
+        assert_eq!(err.errors.unwrap().values().next().unwrap(), "Array length is less than 1.");
+    }
+}
@@ -4,3 +4,5 @@ pub mod prepend;
 pub mod truncate;
 pub mod get_length;
 pub mod reverse;
+pub mod min_items;
+pub mod max_items;
@@ -0,0 +1,107 @@
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use crate::core::app::builder::CallbackLookupTable;
+use crate::core::result::Result;
+use crate::core::pipeline::item::Item;
+use crate::core::pipeline::ctx::Ctx;
+use crate::core::teon::Value;
+
+pub trait CustomFunctionArgument: Send + Sync {
+    fn call(&self, value: Value) -> BoxFuture<'static, Result<Value>>;
+}
+
+impl<F, Fut> CustomFunctionArgument for F where
+    F: Fn(Value) -> Fut + Sync + Send,
+    Fut: Future<Output = Result<Value>> + Send + 'static {
+    fn call(&self, value: Value) -> BoxFuture<'static, Result<Value>> {
+        Box::pin(self(value))
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct CustomFunction {
+    callback: Arc<dyn CustomFunctionArgument>
+}
+
+impl Debug for CustomFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut result = f.debug_struct("CustomFunction");
+        result.finish()
+    }
+}
+
+impl CustomFunction {
+    pub(crate) fn new<F>(f: F) -> Self where F: CustomFunctionArgument + 'static {
+        Self { callback: Arc::new(f) }
+    }
+}
+
+/// Calls the function registered under `name` with the value. The function is
+/// looked up when called, so it may be registered after the schema is loaded.
+#[derive(Debug, Clone)]
+pub struct CustomFunctionItem {
+    name: String,
+    lookup_table: Arc<Mutex<CallbackLookupTable>>,
+}
+
+impl CustomFunctionItem {
+    pub(crate) fn new(name: impl Into<String>, lookup_table: Arc<Mutex<CallbackLookupTable>>) -> Self {
+        Self { name: name.into(), lookup_table }
+    }
+}
+
+#[async_trait]
+impl Item for CustomFunctionItem {
+    async fn call<'a>(&self, ctx: Ctx<'a>) -> Result<Ctx<'a>> {
+        let function = self.lookup_table.lock().unwrap().functions.get(&self.name).cloned();
+        match function {
+            Some(function) => match function.callback.call(ctx.get_value()).await {
+                Ok(value) => Ok(ctx.with_value(value)),
+                Err(error) => Err(ctx.unwrap_custom_error(error)),
+            },
+            None => Err(ctx.internal_server_error(format!("function: cannot find a function named '{}'", self.name))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::error::{Error, ErrorType};
+    use crate::core::pipeline::Pipeline;
+    use super::*;
+
+    fn lookup_table() -> Arc<Mutex<CallbackLookupTable>> {
+        let mut table = CallbackLookupTable::new();
+        table.functions.insert("double".to_owned(), CustomFunction::new(|value: Value| async move {
+            Ok::<Value, Error>(Value::I32(value.as_i32().unwrap() * 2))
+        }));
+        table.functions.insert("reject".to_owned(), CustomFunction::new(|_value: Value| async move {
+            Err::<Value, Error>(Error::custom_validation_error("value is rejected"))
+        }));
+        Arc::new(Mutex::new(table))
+    }
+
+    #[tokio::test]
+    async fn registered_function_transforms_value() {
+        let pipeline = Pipeline { items: vec![Arc::new(CustomFunctionItem::new("double", lookup_table()))] };
+        let value = pipeline.process(Ctx::initial_state_with_value(Value::I32(21))).await.unwrap();
+        assert_eq!(value, Value::I32(42));
+    }
+
+    #[tokio::test]
+    async fn function_error_is_returned() {
+        let pipeline = Pipeline { items: vec![Arc::new(CustomFunctionItem::new("reject", lookup_table()))] };
+        let error = pipeline.process(Ctx::initial_state_with_value(Value::I32(1))).await.unwrap_err();
+        assert_eq!(error.r#type, ErrorType::ValidationError);
+    }
+
+    #[tokio::test]
+    async fn unknown_function_is_an_error() {
+        let ctx = Ctx::initial_state_with_value(Value::I32(1));
+        let result = CustomFunctionItem::new("missing", lookup_table()).call(ctx).await;
+        assert!(result.is_err());
+    }
+}
@@ -2,3 +2,4 @@ pub mod transform;
 pub mod perform;
 pub mod validate;
 pub mod compare;
+pub mod custom;
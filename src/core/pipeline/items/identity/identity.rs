@@ -1,7 +1,5 @@
 use async_trait::async_trait;
-use crate::core::action::source::ActionSource;
 use crate::core::pipeline::item::Item;
-use crate::core::teon::Value;
 use crate::core::pipeline::ctx::Ctx;
 use crate::core::pipeline::Pipeline;
 use crate::core::result::Result;
@@ -20,15 +18,37 @@ impl IdentityItem {
 #[async_trait]
 impl Item for IdentityItem {
     async fn call<'a>(&self, ctx: Ctx<'a>) -> Result<Ctx<'a>> {
-        match ctx.get_object()?.action_source() {
-            ActionSource::Identity(user) => {
-                let user = match user {
-                    Some(u) => Value::Object(u.clone()),
-                    None => Value::Null,
-                };
-                Ok(ctx.with_value(self.pipeline.process(ctx.with_value(user)).await?))
+        match &ctx.identity {
+            Some(identity) => {
+                let identity = identity.clone();
+                Ok(ctx.with_value(self.pipeline.process(ctx.with_value(identity)).await?))
             }
-            _ => Ok(ctx)
+            None => Ok(ctx)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::core::pipeline::items::object::get::GetItem;
+    use crate::core::teon::Value;
+    use crate::teon;
+    use super::*;
+
+    fn owner_id_default() -> Pipeline {
+        Pipeline { items: vec![Arc::new(IdentityItem::new(Pipeline { items: vec![Arc::new(GetItem::new(Value::String("id".to_owned())))] }))] }
+    }
+
+    #[tokio::test]
+    async fn default_is_read_from_the_identity() {
+        let ctx = Ctx::initial_state_with_value(Value::Null).with_identity(Some(teon!({"id": 5})));
+        assert_eq!(owner_id_default().process(ctx).await.unwrap(), teon!(5));
+    }
+
+    #[tokio::test]
+    async fn value_is_kept_when_action_is_not_sourced_from_an_identity() {
+        let ctx = Ctx::initial_state_with_value(teon!(8));
+        assert_eq!(owner_id_default().process(ctx).await.unwrap(), teon!(8));
+    }
+}
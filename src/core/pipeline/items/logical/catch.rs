@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use crate::core::pipeline::item::Item;
+use crate::core::pipeline::Pipeline;
+use crate::core::pipeline::ctx::Ctx;
+use crate::prelude::Value;
+use crate::core::result::Result;
+
+/// Runs `pipeline`, and if it fails, recovers with `fallback` instead of
+/// returning the error. A pipeline fallback runs on the original value.
+#[derive(Debug, Clone)]
+pub struct CatchItem {
+    pipeline: Pipeline,
+    fallback: Value,
+}
+
+impl CatchItem {
+    pub fn new(pipeline: Pipeline, fallback: Value) -> Self {
+        Self { pipeline, fallback }
+    }
+}
+
+#[async_trait]
+impl Item for CatchItem {
+    async fn call<'a>(&self, ctx: Ctx<'a>) -> Result<Ctx<'a>> {
+        match self.pipeline.process(ctx.clone()).await {
+            Ok(value) => Ok(ctx.with_value(value)),
+            Err(_) => match &self.fallback {
+                Value::Pipeline(p) => Ok(ctx.with_value(p.process(ctx.clone()).await?)),
+                _ => Ok(ctx.with_value(self.fallback.clone())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::core::pipeline::items::logical::invalid::InvalidItem;
+    use crate::core::pipeline::items::string::transform::to_upper_case::ToUpperCaseItem;
+    use super::*;
+
+    fn pipeline(item: Arc<dyn Item>) -> Pipeline {
+        Pipeline { items: vec![item] }
+    }
+
+    #[tokio::test]
+    async fn fallback_supplies_the_value_when_the_pipeline_fails() {
+        let item = CatchItem::new(pipeline(Arc::new(InvalidItem::new())), Value::String("default".to_owned()));
+        let ctx = item.call(Ctx::initial_state_with_value(Value::String("draft".to_owned()))).await.unwrap();
+        assert_eq!(ctx.value, Value::String("default".to_owned()));
+        let item = CatchItem::new(pipeline(Arc::new(InvalidItem::new())), Value::Pipeline(pipeline(Arc::new(ToUpperCaseItem::new()))));
+        let ctx = item.call(Ctx::initial_state_with_value(Value::String("draft".to_owned()))).await.unwrap();
+        assert_eq!(ctx.value, Value::String("DRAFT".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn fallback_is_skipped_when_the_pipeline_succeeds() {
+        let item = CatchItem::new(pipeline(Arc::new(ToUpperCaseItem::new())), Value::Pipeline(pipeline(Arc::new(InvalidItem::new()))));
+        let ctx = item.call(Ctx::initial_state_with_value(Value::String("draft".to_owned()))).await.unwrap();
+        assert_eq!(ctx.value, Value::String("DRAFT".to_owned()));
+    }
+}
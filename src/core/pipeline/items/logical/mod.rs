@@ -9,3 +9,4 @@ pub mod validate_with;
 pub mod transform_with;
 pub mod valid;
 pub mod invalid;
+pub mod catch;
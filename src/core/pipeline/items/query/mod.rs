@@ -1 +1,2 @@
 pub mod query_raw;
+pub mod unique_within;
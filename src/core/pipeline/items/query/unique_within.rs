@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use async_trait::async_trait;
+use crate::core::error::Error;
+use crate::core::pipeline::item::Item;
+use crate::core::pipeline::ctx::Ctx;
+use crate::core::result::Result;
+use crate::prelude::Value;
+
+/// Validates that no other record has the same value for the field among the
+/// records sharing the object's values of the scope fields.
+#[derive(Debug, Clone)]
+pub struct UniqueWithinItem {
+    field: String,
+    scope: Vec<String>,
+}
+
+impl UniqueWithinItem {
+    pub fn new(field: impl Into<String>, scope: Vec<String>) -> Self {
+        Self { field: field.into(), scope }
+    }
+
+    fn finder(&self, value: &Value, scope_values: Vec<(&str, Value)>, exclude: Option<Value>) -> Value {
+        let mut r#where: HashMap<String, Value> = HashMap::new();
+        r#where.insert(self.field.clone(), Value::HashMap(HashMap::from([("equals".to_owned(), value.clone())])));
+        for (key, scope_value) in scope_values {
+            r#where.insert(key.to_owned(), Value::HashMap(HashMap::from([("equals".to_owned(), scope_value)])));
+        }
+        if let Some(identifier) = exclude {
+            r#where.insert("NOT".to_owned(), identifier);
+        }
+        Value::HashMap(HashMap::from([("where".to_owned(), Value::HashMap(r#where))]))
+    }
+}
+
+#[async_trait]
+impl Item for UniqueWithinItem {
+    async fn call<'a>(&self, ctx: Ctx<'a>) -> Result<Ctx<'a>> {
+        if ctx.value.is_null() {
+            return Ok(ctx);
+        }
+        let object = ctx.get_object()?;
+        let mut scope_values = vec![];
+        for key in self.scope.iter() {
+            scope_values.push((key.as_str(), object.get_value(key)?));
+        }
+        let exclude = if object.is_new() { None } else { Some(object.identifier()) };
+        let finder = self.finder(&ctx.value, scope_values, exclude);
+        let count = object.graph().count(object.model().name(), &finder).await?;
+        if count > 0 {
+            Err(Error::unique_value_duplicated(&self.field))
+        } else {
+            Ok(ctx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::teon;
+    use super::*;
+
+    #[test]
+    fn conflicts_are_looked_up_within_the_scope() {
+        let item = UniqueWithinItem::new("slug", vec!["tenantId".to_owned()]);
+        let finder = item.finder(&Value::String("about".to_owned()), vec![("tenantId", Value::I32(1))], None);
+        assert_eq!(finder, teon!({"where": {"slug": {"equals": "about"}, "tenantId": {"equals": 1}}}));
+    }
+
+    #[test]
+    fn current_object_is_excluded_on_update() {
+        let item = UniqueWithinItem::new("slug", vec!["tenantId".to_owned()]);
+        let finder = item.finder(&Value::String("about".to_owned()), vec![("tenantId", Value::I32(2))], Some(teon!({"id": 5})));
+        assert_eq!(finder, teon!({"where": {"slug": {"equals": "about"}, "tenantId": {"equals": 2}, "NOT": {"id": 5}}}));
+    }
+}
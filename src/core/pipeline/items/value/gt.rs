@@ -24,4 +24,12 @@ impl Item for GtItem {
             Err(ctx.with_invalid("gt: value is not greater than rhs"))
         }
     }
+
+    fn json_schema_keyword(&self) -> Option<(&'static str, Value)> {
+        if self.argument.is_number() {
+            Some(("exclusiveMinimum", self.argument.clone()))
+        } else {
+            None
+        }
+    }
 }
@@ -24,4 +24,12 @@ impl Item for GteItem {
             Err(ctx.with_invalid("gt: value is not greater than or equal to rhs"))
         }
     }
+
+    fn json_schema_keyword(&self) -> Option<(&'static str, Value)> {
+        if self.argument.is_number() {
+            Some(("minimum", self.argument.clone()))
+        } else {
+            None
+        }
+    }
 }
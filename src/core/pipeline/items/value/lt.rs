@@ -24,4 +24,12 @@ impl Item for LtItem {
             Err(ctx.with_invalid("lt: value is not less than rhs"))
         }
     }
+
+    fn json_schema_keyword(&self) -> Option<(&'static str, Value)> {
+        if self.argument.is_number() {
+            Some(("exclusiveMaximum", self.argument.clone()))
+        } else {
+            None
+        }
+    }
 }
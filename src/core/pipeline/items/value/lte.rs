@@ -24,4 +24,12 @@ impl Item for LteItem {
             Err(ctx.with_invalid("lte: value is not less than or equal to rhs"))
         }
     }
+
+    fn json_schema_keyword(&self) -> Option<(&'static str, Value)> {
+        if self.argument.is_number() {
+            Some(("maximum", self.argument.clone()))
+        } else {
+            None
+        }
+    }
 }
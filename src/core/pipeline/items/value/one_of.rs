@@ -26,4 +26,12 @@ impl Item for OneOfItem {
             Err(ctx.with_invalid("oneOf: value is not one of valid ones"))
         }
     }
+
+    fn json_schema_keyword(&self) -> Option<(&'static str, Value)> {
+        if self.argument.is_vec() {
+            Some(("enum", self.argument.clone()))
+        } else {
+            None
+        }
+    }
 }
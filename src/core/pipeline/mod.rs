@@ -3,7 +3,7 @@ pub mod item;
 pub mod items;
 
 use std::sync::Arc;
-use crate::core::error::ErrorType::InternalServerError;
+use crate::core::error::ErrorType::{InternalServerError, ValidationError};
 use crate::core::result::Result;
 use crate::core::pipeline::item::Item;
 use crate::core::pipeline::ctx::Ctx;
@@ -51,6 +51,18 @@ impl Pipeline {
             }
         }
     }
+
+    pub(crate) async fn process_into_validation_result(&self, ctx: Ctx<'_>) -> Result<()> {
+        let path = ctx.path.clone();
+        match self.process(ctx).await {
+            Ok(_) => Ok(()),
+            Err(error) => if error.r#type == InternalServerError || error.r#type == ValidationError {
+                Err(error)
+            } else {
+                Err(Error::validation_error(path, error.message))
+            }
+        }
+    }
 }
 
 unsafe impl Send for Pipeline {}
@@ -61,3 +73,4 @@ impl PartialEq for Pipeline {
         false
     }
 }
+
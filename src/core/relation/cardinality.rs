@@ -0,0 +1,6 @@
+/// How many records are on the other side of a relation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Cardinality {
+    One,
+    Many,
+}
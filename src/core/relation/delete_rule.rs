@@ -5,3 +5,17 @@ pub enum DeleteRule {
     Cascade,
     Deny,
 }
+
+impl DeleteRule {
+
+    /// The rule of an `onDelete` referential action name.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "noAction" => Some(DeleteRule::Default),
+            "setNull" => Some(DeleteRule::Nullify),
+            "cascade" => Some(DeleteRule::Cascade),
+            "restrict" => Some(DeleteRule::Deny),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 pub mod update_rule;
 pub mod delete_rule;
 pub mod disconnect_rule;
+pub mod cardinality;
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -9,7 +10,11 @@ use maplit::hashset;
 use once_cell::sync::Lazy;
 use crate::core::field::Field;
 use crate::core::field::optionality::Optionality;
+use crate::core::field::r#type::FieldType;
+use crate::core::relation::cardinality::Cardinality;
 use crate::core::relation::delete_rule::DeleteRule;
+use crate::core::teon::Value;
+use crate::teon;
 
 #[derive(Debug, Clone)]
 pub struct Relation {
@@ -19,11 +24,13 @@ pub struct Relation {
     pub(self) optionality: Optionality,
     pub(self) model: String,
     pub(self) through: Option<String>,
-    pub(self) is_vec: bool,
+    pub(self) cardinality: Cardinality,
     pub(self) fields: Vec<String>,
     pub(self) references: Vec<String>,
     pub(self) delete_rule: DeleteRule,
     pub(self) has_foreign_key: bool,
+    pub(self) auto_index: bool,
+    pub(self) counter_cache: Option<String>,
 }
 
 impl Relation {
@@ -36,11 +43,13 @@ impl Relation {
             optionality: Optionality::Required,
             model: "".into(),
             through: None,
-            is_vec: false,
+            cardinality: Cardinality::One,
             fields: Vec::new(),
             references: Vec::new(),
             delete_rule: DeleteRule::Default,
             has_foreign_key: false,
+            auto_index: true,
+            counter_cache: None,
         }
     }
     
@@ -82,8 +91,12 @@ impl Relation {
         self.through.as_deref()
     }
 
+    pub(crate) fn cardinality(&self) -> Cardinality {
+        self.cardinality
+    }
+
     pub(crate) fn is_vec(&self) -> bool {
-        self.is_vec
+        self.cardinality == Cardinality::Many
     }
 
     pub(crate) fn set_fields(&mut self, fields: Vec<String>) {
@@ -118,6 +131,10 @@ impl Relation {
         self.references.get(0).unwrap()
     }
 
+    pub(crate) fn set_delete_rule(&mut self, delete_rule: DeleteRule) {
+        self.delete_rule = delete_rule;
+    }
+
     pub(crate) fn delete_rule(&self) -> DeleteRule {
         self.delete_rule
     }
@@ -126,6 +143,38 @@ impl Relation {
         self.has_foreign_key
     }
 
+    /// Whether the foreign key columns are indexed when no index covers them.
+    pub(crate) fn auto_index(&self) -> bool {
+        self.auto_index
+    }
+
+    pub(crate) fn set_auto_index(&mut self, auto_index: bool) {
+        self.auto_index = auto_index;
+    }
+
+    /// The field on the related model which counts the records of this model
+    /// pointing to it. It's incremented on create, decremented on delete and
+    /// moved over when the foreign key is reassigned.
+    pub(crate) fn counter_cache(&self) -> Option<&str> {
+        self.counter_cache.as_deref()
+    }
+
+    pub(crate) fn set_counter_cache(&mut self, counter_cache: String) {
+        self.counter_cache = Some(counter_cache);
+    }
+
+    pub(crate) fn counter_cache_updator(field_type: &FieldType, delta: i64) -> Value {
+        let amount = match field_type {
+            FieldType::I32 => Value::I32(delta.abs() as i32),
+            _ => Value::I64(delta.abs()),
+        };
+        if delta < 0 {
+            teon!({"decrement": amount})
+        } else {
+            teon!({"increment": amount})
+        }
+    }
+
     pub(crate) fn has_join_table(&self) -> bool {
         self.through().is_some()
     }
@@ -139,7 +188,7 @@ impl Relation {
     }
 
     pub(crate) fn filters(&self) -> &HashSet<&str> {
-        if self.is_vec {
+        if self.is_vec() {
             &VEC_FILTERS
         } else {
             &OBJECT_FILTERS
@@ -154,8 +203,8 @@ impl Relation {
         self.optionality = Optionality::Optional;
     }
 
-    pub(crate) fn set_is_vec(&mut self, is_vec: bool) {
-        self.is_vec = is_vec
+    pub(crate) fn set_cardinality(&mut self, cardinality: Cardinality) {
+        self.cardinality = cardinality
     }
 
     pub(crate) fn set_model(&mut self, model: String) {
@@ -163,10 +212,14 @@ impl Relation {
     }
 
     pub(crate) fn finalize(&mut self, fields: &HashMap<String, Arc<Field>>) {
+        if self.through.is_none() && (self.fields.is_empty() || self.fields.len() != self.references.len()) {
+            panic!("Relation '{}' should have at least one field and as many references as fields, it has {} fields and {} references.", self.name, self.fields.len(), self.references.len());
+        }
         self.has_foreign_key = if self.through.is_some() {
             false
         } else {
-            self.fields.iter().find(|name| fields.get(name.as_str()).unwrap().foreign_key).is_some()
+            // undefined fields are reported when the graph validates its relations
+            self.fields.iter().any(|name| fields.get(name.as_str()).map_or(false, |f| f.foreign_key))
         }
     }
 }
@@ -196,3 +249,27 @@ static VEC_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
 static OBJECT_FILTERS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset!{"is", "isNot"}
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_follow_cardinality() {
+        let profile = Relation::new("profile");
+        assert_eq!(profile.cardinality(), Cardinality::One);
+        assert!(!profile.is_vec());
+        assert!(profile.filters().contains("is"));
+        let mut posts = Relation::new("posts");
+        posts.set_cardinality(Cardinality::Many);
+        assert!(posts.is_vec());
+        assert!(posts.filters().contains("some"));
+        assert!(!posts.filters().contains("is"));
+    }
+
+    #[test]
+    fn counter_cache_updators_follow_the_field_type() {
+        assert_eq!(Relation::counter_cache_updator(&FieldType::I32, 1), teon!({"increment": 1i32}));
+        assert_eq!(Relation::counter_cache_updator(&FieldType::I64, -1), teon!({"decrement": 1i64}));
+    }
+}
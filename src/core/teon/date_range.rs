@@ -0,0 +1,73 @@
+use chrono::{Datelike, DateTime, Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+use crate::core::field::r#type::FieldType;
+use crate::prelude::Value;
+
+pub(crate) static RELATIVE_DATE_RANGES: [&str; 6] = ["today", "yesterday", "thisWeek", "thisMonth", "last7Days", "last30Days"];
+
+/// Resolves a relative date range token into inclusive lower and upper bounds.
+/// Days are counted in the time zone of `now`, the graph's default timezone.
+pub(crate) fn relative_date_range(token: &str, r#type: &FieldType, now: DateTime<FixedOffset>) -> Option<(Value, Value)> {
+    let today = now.date_naive();
+    let timezone = now.timezone();
+    let (first, last) = match token {
+        "today" => (today, today),
+        "yesterday" => (today - Duration::days(1), today - Duration::days(1)),
+        "thisWeek" => {
+            let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            (monday, monday + Duration::days(6))
+        }
+        "thisMonth" => {
+            let first = today.with_day(1).unwrap();
+            let next = if first.month() == 12 {
+                NaiveDate::from_ymd_opt(first.year() + 1, 1, 1).unwrap()
+            } else {
+                NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1).unwrap()
+            };
+            (first, next - Duration::days(1))
+        }
+        "last7Days" => (today - Duration::days(6), today),
+        "last30Days" => (today - Duration::days(29), today),
+        _ => return None,
+    };
+    Some(match r#type {
+        FieldType::Date => (Value::Date(first), Value::Date(last)),
+        _ => (
+            Value::DateTime(timezone.from_local_datetime(&first.and_hms_opt(0, 0, 0).unwrap()).unwrap().with_timezone(&Utc)),
+            Value::DateTime(timezone.from_local_datetime(&last.and_hms_milli_opt(23, 59, 59, 999).unwrap()).unwrap().with_timezone(&Utc)),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn this_week_starts_on_monday_and_ends_on_sunday() {
+        // 2023-03-16 is a Thursday
+        let now = Utc.with_ymd_and_hms(2023, 3, 16, 10, 30, 0).unwrap().fixed_offset();
+        let (gte, lte) = relative_date_range("thisWeek", &FieldType::DateTime, now).unwrap();
+        assert_eq!(gte, Value::DateTime(Utc.with_ymd_and_hms(2023, 3, 13, 0, 0, 0).unwrap()));
+        assert_eq!(lte, Value::DateTime(Utc.with_ymd_and_hms(2023, 3, 19, 23, 59, 59).unwrap() + Duration::milliseconds(999)));
+    }
+
+    #[test]
+    fn today_on_date_field_is_a_single_day() {
+        let now = Utc.with_ymd_and_hms(2023, 3, 16, 10, 30, 0).unwrap().fixed_offset();
+        let today = NaiveDate::from_ymd_opt(2023, 3, 16).unwrap();
+        assert_eq!(relative_date_range("today", &FieldType::Date, now), Some((Value::Date(today), Value::Date(today))));
+        assert_eq!(relative_date_range("someday", &FieldType::Date, now), None);
+    }
+
+    #[test]
+    fn today_is_counted_in_the_time_zone_of_now() {
+        // 2023-03-16 23:30 UTC is already 2023-03-17 in UTC+8
+        let timezone = FixedOffset::east_opt(8 * 3600).unwrap();
+        let now = Utc.with_ymd_and_hms(2023, 3, 16, 23, 30, 0).unwrap().with_timezone(&timezone);
+        let (gte, lte) = relative_date_range("today", &FieldType::DateTime, now).unwrap();
+        assert_eq!(gte, Value::DateTime(Utc.with_ymd_and_hms(2023, 3, 16, 16, 0, 0).unwrap()));
+        assert_eq!(lte, Value::DateTime(Utc.with_ymd_and_hms(2023, 3, 17, 15, 59, 59).unwrap() + Duration::milliseconds(999)));
+        let day = NaiveDate::from_ymd_opt(2023, 3, 17).unwrap();
+        assert_eq!(relative_date_range("today", &FieldType::Date, now), Some((Value::Date(day), Value::Date(day))));
+    }
+}
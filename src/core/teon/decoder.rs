@@ -1,22 +1,28 @@
+use std::borrow::Cow;
 use std::collections::{HashSet, HashMap, BTreeMap};
 use std::ops::BitOr;
 use std::str::FromStr;
+use std::sync::Arc;
 use bigdecimal::{BigDecimal, FromPrimitive};
 #[cfg(feature = "data-source-mongodb")]
 use bson::oid::ObjectId;
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
 use key_path::{KeyPath, path};
 use maplit::{hashmap, hashset};
 use once_cell::sync::Lazy;
-use serde_json::{Value as JsonValue, Map as JsonMap};
+use serde_json::{json, Value as JsonValue, Map as JsonMap};
 use crate::core::action::{Action, CONNECT, CONNECT_OR_CREATE, CREATE, CREATE_MANY_HANDLER, DELETE, DISCONNECT, FIND_MANY_HANDLER, FIND_UNIQUE_HANDLER, MANY, NESTED, SET, SINGLE, UPDATE, UPSERT};
 use crate::core::error::Error;
 use crate::core::field::r#type::{FieldType, FieldTypeOwner};
 use crate::core::model::Model;
 use crate::core::result::Result;
 use crate::core::graph::Graph;
+use crate::core::teon::timezone::date_to_datetime;
 use crate::core::relation::Relation;
+use crate::core::relation::cardinality::Cardinality;
 use crate::core::teon::Value;
+use crate::core::teon::date_range::{relative_date_range, RELATIVE_DATE_RANGES};
+use crate::parser::ast::config::DateTimeFormat;
 
 pub(crate) struct Decoder { }
 
@@ -30,6 +36,17 @@ impl Decoder {
         Self::decode_action_arg_at_path(model, graph, action, json_value, path![])
     }
 
+    /// Drop top-level keys which are not accepted by the action, used when input keys are lenient.
+    pub(crate) fn retain_allowed_action_arg_keys(action: Action, json_value: &JsonValue) -> JsonValue {
+        match json_value.as_object() {
+            Some(json_map) => {
+                let allowed = action.handler_allowed_input_json_keys();
+                JsonValue::Object(json_map.iter().filter(|(k, _)| allowed.contains(k.as_str())).map(|(k, v)| (k.clone(), v.clone())).collect())
+            }
+            None => json_value.clone(),
+        }
+    }
+
     fn decode_object_at_path<'a>(model: &Model, graph: &Graph, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
         let json_map = if let Some(json_map) = json_value.as_object() {
@@ -37,11 +54,15 @@ impl Decoder {
         } else {
             return Err(Error::unexpected_input_type("object", path));
         };
+        let json_map = Self::translate_api_names(model, json_map, path)?;
+        let json_map = json_map.as_ref();
         Self::check_json_keys(json_map, &model.all_keys().iter().map(|k| k.as_str()).collect(), path)?;
         Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
             let path = path + k;
             if let Some(field) = model.field(k) {
-                Ok((k.to_owned(), Self::decode_value_for_field_type(graph, field.field_type(), field.is_optional(), v, path)?))
+                let value = Self::decode_value_for_field_type(graph, field.field_type(), field.is_optional(), v, &path)?;
+                Self::check_decimals(model, &value, &path)?;
+                Ok((k.to_owned(), value))
             } else if let Some(relation) = model.relation(k) {
                 if relation.is_vec() {
                     Ok((k.to_owned(), Self::decode_nested_many_create_arg(graph, relation, v, path)?))
@@ -69,7 +90,7 @@ impl Decoder {
             let key = key.as_str();
             let path = path + key;
             match key {
-                "where" => if action.handler_requires_where() {
+                "where" => if action.handler_requires_where() || action.handler_requires_field() {
                     retval.insert(key.to_owned(), Self::decode_where(model, graph, value, path)?);
                 } else if action.handler_requires_where_unique() {
                     retval.insert(key.to_owned(), Self::decode_where_unique(model, graph, value, path)?);
@@ -83,10 +104,17 @@ impl Decoder {
                 "include" => { retval.insert(key.to_owned(), Self::decode_include(model, graph, value, path)?); }
                 "_avg" | "_sum" | "_min" | "_max" | "_count" => { retval.insert(key.to_owned(), Self::decode_aggregate(model, key, value, path)?); }
                 "by" => { retval.insert(key.to_owned(), Self::decode_by(model, value, path)?); }
+                "field" => { retval.insert(key.to_owned(), Self::decode_facet_field(model, value, path)?); }
                 "having" => { retval.insert(key.to_owned(), Self::decode_having(model, graph, value, path)?); }
                 "create" => { retval.insert(key.to_owned(), if action.to_u32() == CREATE_MANY_HANDLER { Self::decode_enumerate(value, path, |v, p: &KeyPath| Self::decode_create(model, graph, v, p))? } else { Self::decode_create(model, graph, value, path)? } ); }
                 "update" => { retval.insert(key.to_owned(), Self::decode_update(model, graph, value, path)?); }
                 "credentials" => { retval.insert(key.to_owned(), Self::decode_credentials(model, graph, value, path)?); }
+                "_withMeta" => { retval.insert(key.to_owned(), Self::decode_bool(value, path)?); }
+                "withDeleted" => if model.soft_delete_field().is_some() {
+                    retval.insert(key.to_owned(), Self::decode_bool(value, path)?);
+                } else {
+                    return Err(Error::unexpected_input_key(key, path))
+                },
                 _ => unreachable!()
             }
         }
@@ -97,9 +125,49 @@ impl Decoder {
                 }
             }
         }
+        if let Some(cursor) = retval.get("cursor") {
+            Self::check_cursor_order_by(cursor, retval.get("orderBy"), path)?;
+        }
         Ok(Value::HashMap(retval))
     }
 
+    /// A cursor pages by its own keys, so `orderBy` has to sort by exactly
+    /// the keys of the cursor, in any order.
+    fn check_cursor_order_by<'a>(cursor: &Value, order_by: Option<&Value>, path: &KeyPath<'a>) -> Result<()> {
+        let order_by_keys: Vec<&str> = match order_by {
+            Some(order_by) => order_by.as_vec().unwrap().iter().map(|item| item.as_hashmap().unwrap().keys().next().unwrap().as_str()).collect(),
+            None => return Err(Error::missing_required_input_with_type("object or array", path + "orderBy")),
+        };
+        let cursor = cursor.as_hashmap().unwrap();
+        if order_by_keys.len() != cursor.len() || !order_by_keys.iter().all(|k| cursor.contains_key(*k)) {
+            return Err(Error::unexpected_input_value_with_reason("Cursor and orderBy should have the same keys.", path + "cursor"));
+        }
+        Ok(())
+    }
+
+    /// Replace API names, which follow `@apiName` and the field naming, with
+    /// internal key names. Internal names of aliased fields are rejected.
+    fn translate_api_names<'a, 'b>(model: &Model, json_map: &'b JsonMap<String, JsonValue>, path: &KeyPath<'a>) -> Result<Cow<'b, JsonMap<String, JsonValue>>> {
+        if !model.has_api_names() {
+            return Ok(Cow::Borrowed(json_map));
+        }
+        let mut translated = JsonMap::new();
+        for (key, value) in json_map {
+            translated.insert(Self::translate_api_name(model, key, path)?.to_owned(), value.clone());
+        }
+        Ok(Cow::Owned(translated))
+    }
+
+    fn translate_api_name<'a, 'b>(model: &'b Model, key: &'b str, path: &KeyPath<'a>) -> Result<&'b str> {
+        if let Some(name) = model.field_name_for_api_name(key) {
+            Ok(name)
+        } else if model.field(key).map_or(false, |f| f.api_name.is_some()) {
+            Err(Error::unexpected_input_key(key, path + key))
+        } else {
+            Ok(key)
+        }
+    }
+
     fn check_json_keys<'a>(map: &JsonMap<String, JsonValue>, allowed: &HashSet<&str>, path: &KeyPath<'a>) -> Result<()> {
         if let Some(unallowed) = map.keys().find(|k| !allowed.contains(k.as_str())) {
             return Err(Error::unexpected_input_key(unallowed, path + unallowed));
@@ -107,6 +175,32 @@ impl Decoder {
         Ok(())
     }
 
+    fn check_where_operators<'a>(map: &JsonMap<String, JsonValue>, allowed: &HashSet<&str>, path: &KeyPath<'a>) -> Result<()> {
+        if let Some(unknown) = map.keys().find(|k| !allowed.contains(k.as_str())) {
+            return Err(Error::unexpected_input_value_with_reason(format!("Unknown operator '{}' on '{}'.", unknown, path), path + unknown));
+        }
+        Ok(())
+    }
+
+    /// The default mode is left out of the decoded filter.
+    fn decode_mode<'a>(json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Option<Value>> {
+        match json_value.as_str() {
+            Some("caseInsensitive") => Ok(Some(Value::String("caseInsensitive".to_owned()))),
+            Some("default") => Ok(None),
+            Some(mode) => Err(Error::unexpected_input_value_with_reason(format!("Unknown mode '{}', expect 'caseInsensitive' or 'default'.", mode), path)),
+            None => Err(Error::unexpected_input_type("string", path)),
+        }
+    }
+
+    fn check_relation_foreign_key_conflict<'a>(relations: &Vec<Arc<Relation>>, map: &JsonMap<String, JsonValue>, path: &KeyPath<'a>) -> Result<()> {
+        for relation in relations.iter().filter(|r| r.has_foreign_key() && map.contains_key(r.name())) {
+            if let Some(field) = relation.fields().iter().find(|f| map.contains_key(f.as_str())) {
+                return Err(Error::unexpected_input_value_with_reason(format!("Relation '{}' and foreign key '{}' are mutually exclusive.", relation.name(), field), path + field));
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn check_length_1<'a, 'b>(json_value: &'a JsonValue, path: impl AsRef<KeyPath<'b>>) -> Result<(&'a str, &'a JsonValue)> {
         let path = path.as_ref();
         if let Some(json_map) = json_value.as_object() {
@@ -139,11 +233,16 @@ impl Decoder {
         } else {
             return Err(Error::unexpected_input_type("object", path));
         };
+        let json_map = Self::translate_api_names(model, json_map, path)?;
+        let json_map = json_map.as_ref();
         Self::check_json_keys(json_map, &model.input_keys().iter().map(|k| k.as_str()).collect(), path)?;
+        Self::check_relation_foreign_key_conflict(model.relations(), json_map, path)?;
         Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
             let path = path + k;
             if let Some(field) = model.field(k) {
-                Ok((k.to_owned(), Self::decode_value_for_field_type(graph, field.field_type(), field.is_optional(), v, path)?))
+                let value = Self::decode_value_for_field_type(graph, field.field_type(), field.is_optional(), v, &path)?;
+                Self::check_decimals(model, &value, &path)?;
+                Ok((k.to_owned(), value))
             } else if let Some(relation) = model.relation(k) {
                 if relation.is_vec() {
                     Ok((k.to_owned(), Self::decode_nested_many_create_arg(graph, relation, v, path)?))
@@ -306,6 +405,7 @@ impl Decoder {
             return Err(Error::unexpected_input_type("object", path));
         };
         Self::check_json_keys(json_map, &NESTED_CREATE_ONE_ARG_KEYS, path)?;
+        Self::check_to_one_connect(relation, json_map, path)?;
         Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
             let k = k.as_str();
             let path = path + k;
@@ -337,6 +437,20 @@ impl Decoder {
         }).collect::<Result<HashMap<String, Value>>>()?))
     }
 
+    /// To-one relations take a single unique where input for `connect` and `set`,
+    /// reject a list of records.
+    fn check_to_one_connect(relation: &Relation, map: &JsonMap<String, JsonValue>, path: &KeyPath) -> Result<()> {
+        if relation.cardinality() != Cardinality::One {
+            return Ok(());
+        }
+        for key in ["connect", "set"] {
+            if let Some(JsonValue::Array(_)) = map.get(key) {
+                return Err(Error::unexpected_input_value_with_reason(format!("Relation '{}' is to-one, only one record can be connected.", relation.name()), path + key));
+            }
+        }
+        Ok(())
+    }
+
     fn decode_nested_one_update_arg<'a>(graph: &Graph, relation: &Relation, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
         let json_map = if let Some(json_map) = json_value.as_object() {
@@ -345,6 +459,7 @@ impl Decoder {
             return Err(Error::unexpected_input_type("object", path));
         };
         Self::check_json_keys(json_map, &NESTED_UPDATE_ONE_ARG_KEYS, path)?;
+        Self::check_to_one_connect(relation, json_map, path)?;
         Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
             let k = k.as_str();
             let path = path + k;
@@ -361,7 +476,12 @@ impl Decoder {
                     Err(Error::unexpected_input_key(k, &path))?
                 },
                 "set" => if model.has_action(Action::from_u32(SET | NESTED | SINGLE)) {
-                    Ok((k.to_owned(), Self::decode_where_unique(model, graph, v, path)?))
+                    // setting to null disconnects the current record
+                    if v.is_null() {
+                        Ok((k.to_owned(), Value::Null))
+                    } else {
+                        Ok((k.to_owned(), Self::decode_where_unique(model, graph, v, path)?))
+                    }
                 } else {
                     Err(Error::unexpected_input_key(k, &path))?
                 },
@@ -474,6 +594,8 @@ impl Decoder {
             Some(json_map) => json_map,
             None => return Err(Error::unexpected_input_type("object", path))
         };
+        let json_map = Self::translate_api_names(model, json_map, path)?;
+        let json_map = json_map.as_ref();
         let without: Vec<&str> = if let Some(relation) = relation {
             let mut without = vec![relation.name()];
             without.extend(relation.fields().iter().map(|k| k.as_str()));
@@ -494,6 +616,8 @@ impl Decoder {
             Some(json_map) => json_map,
             None => return Err(Error::unexpected_input_type("object", path))
         };
+        let json_map = Self::translate_api_names(model, json_map, path)?;
+        let json_map = json_map.as_ref();
         let without: Vec<&str> = if let Some(relation) = relation {
             let mut without = vec![relation.name()];
             if relation.has_foreign_key() {
@@ -517,11 +641,16 @@ impl Decoder {
         } else {
             return Err(Error::unexpected_input_type("object", path));
         };
+        let json_map = Self::translate_api_names(model, json_map, path)?;
+        let json_map = json_map.as_ref();
         Self::check_json_keys(json_map, &model.input_keys().iter().map(|k| k.as_str()).collect(), path)?;
+        Self::check_relation_foreign_key_conflict(model.relations(), json_map, path)?;
         Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
             let path = path + k;
             if let Some(field) = model.field(k) {
-                Ok((k.to_owned(), Self::decode_value_or_updator_for_field_type(graph, field.field_type(), field.is_optional(), v, path, false)?))
+                let value = Self::decode_value_or_updator_for_field_type(graph, field.field_type(), field.is_optional(), v, &path, false)?;
+                Self::check_decimals(model, &value, &path)?;
+                Ok((k.to_owned(), value))
             } else if let Some(relation) = model.relation(k) {
                 if relation.is_vec() {
                     Ok((k.to_owned(), Self::decode_nested_many_update_arg(graph, relation, v, path)?))
@@ -539,6 +668,8 @@ impl Decoder {
     fn decode_having<'a>(model: &Model, graph: &Graph, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
         if let Some(json_map) = json_value.as_object() {
+            let json_map = Self::translate_api_names(model, json_map, path)?;
+            let json_map = json_map.as_ref();
             Self::check_json_keys(json_map, &model.scalar_keys().iter().map(|k| k.as_str()).collect(), path)?;
             Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
                 let path = path + k;
@@ -556,8 +687,8 @@ impl Decoder {
             Ok(Value::Vec(json_array.iter().enumerate().map(|(i, v)| {
                 let path = path + i;
                 match v.as_str() {
-                    Some(s) => if model.scalar_keys().contains(&s.to_string()) {
-                        Ok(Value::String(s.to_owned()))
+                    Some(s) => if model.scalar_keys().contains(&Self::translate_api_name(model, s, &path)?.to_string()) {
+                        Ok(Value::String(Self::translate_api_name(model, s, &path)?.to_owned()))
                     } else {
                         Err(Error::unexpected_input_value("scalar field name", path))
                     }
@@ -569,9 +700,26 @@ impl Decoder {
         }
     }
 
+    fn decode_facet_field<'a>(model: &Model, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
+        let path = path.as_ref();
+        match json_value.as_str() {
+            Some(s) => {
+                let name = Self::translate_api_name(model, s, path)?;
+                if model.field(name).is_some() && model.query_keys().contains(&name.to_string()) {
+                    Ok(Value::String(name.to_owned()))
+                } else {
+                    Err(Error::unexpected_input_value("queryable field name", path))
+                }
+            }
+            None => Err(Error::unexpected_input_type("string", path))
+        }
+    }
+
     fn decode_aggregate<'a>(model: &Model, key: &str, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
         if let Some(json_map) = json_value.as_object() {
+            let json_map = Self::translate_api_names(model, json_map, path)?;
+            let json_map = json_map.as_ref();
             Self::check_json_keys(json_map, &model.allowed_keys_for_aggregate(key), path)?;
             Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
                 Ok((k.to_owned(), Self::decode_bool(v, path + k)?))
@@ -584,6 +732,8 @@ impl Decoder {
     fn decode_include<'a>(model: &Model, graph: &Graph, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
         if let Some(json_map) = json_value.as_object() {
+            let json_map = Self::translate_api_names(model, json_map, path)?;
+            let json_map = json_map.as_ref();
             Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
                 let path = path + k;
                 if model.relation_output_keys().contains(k) {
@@ -617,6 +767,8 @@ impl Decoder {
     fn decode_select<'a>(model: &Model, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
         if let Some(json_map) = json_value.as_object() {
+            let json_map = Self::translate_api_names(model, json_map, path)?;
+            let json_map = json_map.as_ref();
             Ok(Value::HashMap(json_map.iter().map(|(k, v)| {
                 let path = path + k;
                 if model.local_output_keys().contains(k) {
@@ -660,7 +812,7 @@ impl Decoder {
     fn decode_distinct<'a>(model: &Model, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
         if let Some(_) = json_value.as_str() {
-            Ok(Self::decode_distinct_item(model, json_value, path)?)
+            Ok(Value::Vec(vec![Self::decode_distinct_item(model, json_value, path)?]))
         } else if let Some(json_array) = json_value.as_array() {
             Ok(Value::Vec(json_array.iter().enumerate().map(|(i, v)| {
                 Self::decode_distinct_item(model, v, path + i)
@@ -672,7 +824,8 @@ impl Decoder {
 
     fn decode_distinct_item<'a>(model: &Model, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         if let Some(s) = json_value.as_str() {
-            if model.scalar_keys().contains(&s.to_string()) {
+            let s = Self::translate_api_name(model, s, path.as_ref())?;
+            if model.query_keys().contains(&s.to_string()) && model.field(s).is_some() {
                 Ok(Value::String(s.to_owned()))
             } else {
                 Err(Error::unexpected_input_value("scalar fields enum", path))
@@ -684,21 +837,41 @@ impl Decoder {
 
     fn decode_order_by<'a>(model: &Model, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
-        if let Some(_) = json_value.as_object() {
+        let items = Self::order_by_items(json_value, path)?;
+        if json_value.is_object() {
             Ok(Value::Vec(vec![Self::decode_order_by_item(model, json_value, path)?]))
-        } else if let Some(json_array) = json_value.as_array() {
-            Ok(Value::Vec(json_array.iter().enumerate().map(|(i, v)| {
+        } else {
+            Ok(Value::Vec(items.iter().enumerate().map(|(i, v)| {
                 Self::decode_order_by_item(model, v, path + i)
             }).collect::<Result<Vec<Value>>>()?))
+        }
+    }
+
+    /// The sort items of an `orderBy` input in order. Object keys are
+    /// unordered, so sorting by multiple keys requires the array form.
+    fn order_by_items<'a, 'b>(json_value: &'a JsonValue, path: impl AsRef<KeyPath<'b>>) -> Result<Vec<&'a JsonValue>> {
+        let path = path.as_ref();
+        if let Some(json_map) = json_value.as_object() {
+            if json_map.len() > 1 {
+                Err(Error::unexpected_input_value_with_reason("Sorting by multiple keys requires an array of single key objects.", path))
+            } else {
+                Ok(vec![json_value])
+            }
+        } else if let Some(json_array) = json_value.as_array() {
+            Ok(json_array.iter().collect())
         } else {
             Err(Error::unexpected_input_type("object or array", path))
         }
     }
 
-    fn decode_order_by_item<'a>(_model: &Model, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
+    fn decode_order_by_item<'a>(model: &Model, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
         if let Some(_json_map) = json_value.as_object() {
             let (key, value) = Self::check_length_1(json_value, path)?;
+            let key = Self::translate_api_name(model, key, path)?;
+            if !model.sort_keys().contains(&key.to_string()) {
+                return Err(Error::unexpected_input_key(key, path));
+            }
             match value.as_str() {
                 Some(s) => match s {
                     "asc" | "desc" => Ok(Value::HashMap(hashmap!{key.to_owned() => Value::String(s.to_owned())})),
@@ -718,6 +891,8 @@ impl Decoder {
         } else {
             return Err(Error::unexpected_input_type("object", path));
         };
+        let json_map = Self::translate_api_names(model, json_map, path)?;
+        let json_map = json_map.as_ref();
         let mut retval: HashMap<String, Value> = hashmap!{};
         for (key, value) in json_map {
             let key = key.as_str();
@@ -755,6 +930,9 @@ impl Decoder {
                         return Err(Error::unexpected_input_key(key, path));
                     }
                     if let Some(field) = model.field(key) {
+                        if value.get("search").is_some() && model.fulltext_index_for(key).is_none() {
+                            return Err(Error::unexpected_input_value_with_reason("Search requires a fulltext index on this field.", path + "search"));
+                        }
                         let optional = field.optionality.is_optional();
                         retval.insert(key.to_owned(), Self::decode_where_for_field(graph, field.field_type(), optional, value, path)?);
                     } else if let Some(relation) = model.relation(key) {
@@ -773,18 +951,20 @@ impl Decoder {
         } else {
             return Err(Error::unexpected_input_type("object", path));
         };
+        let json_map = Self::translate_api_names(model, json_map, path)?;
+        let json_map = json_map.as_ref();
         if json_map.len() == 0 {
             return Err(Error::unexpected_input_value_with_reason("Unique where can't be empty.", path));
         }
         for index in model.indices() {
-            if index.keys() == &json_map.keys().into_iter().map(|k| k.to_owned()).collect::<Vec<String>>() {
+            if index.r#type().is_unique() && index.keys().len() == json_map.len() && index.keys().iter().all(|k| json_map.contains_key(k)) {
                 let mut retval: HashMap<String, Value> = HashMap::new();
                 for (key, value) in json_map {
                     let field = model.field(key).unwrap();
                     let path = path + key;
                     retval.insert(key.to_owned(), Self::decode_value_for_field_type(graph, field.field_type(), field.is_optional(), value, path)?);
-                    return Ok(Value::HashMap(retval));
                 }
+                return Ok(Value::HashMap(retval));
             }
         }
         Err(Error::unexpected_input_key(json_map.keys().next().unwrap(), path))
@@ -794,7 +974,7 @@ impl Decoder {
         let path = path.as_ref();
         if json_value.is_object() {
             let json_map = json_value.as_object().unwrap();
-            Self::check_json_keys(json_map, if aggregate { r#type.filters_with_aggregates() } else { r#type.filters() }, path)?;
+            Self::check_where_operators(json_map, if aggregate { r#type.filters_with_aggregates() } else { r#type.filters() }, path)?;
             let mut retval: HashMap<String, Value> = hashmap!{};
             for (key, value) in json_map {
                 let key = key.as_str();
@@ -803,22 +983,28 @@ impl Decoder {
                     "equals" => {
                         retval.insert(key.to_owned(), Self::decode_value_for_field_type(graph, r#type, optional, value, path)?);
                     }
-                    "not" => {
+                    "not" => if value.is_object() && !r#type.is_json() {
+                        // negate a nested filter
                         retval.insert(key.to_owned(), Self::decode_where_for_field(graph, r#type, optional, value, path)?);
+                    } else {
+                        retval.insert(key.to_owned(), Self::decode_value_for_field_type(graph, r#type, optional, value, path)?);
                     }
-                    "gt" | "gte" | "lt" | "lte" | "contains" | "startsWith" | "endsWith" | "matches" => {
+                    "gt" | "gte" | "lt" | "lte" | "contains" | "startsWith" | "endsWith" | "matches" | "search" => {
                         retval.insert(key.to_owned(), Self::decode_value_for_field_type(graph, r#type, false, value, path)?);
                     }
                     "in" | "notIn" => {
                         retval.insert(key.to_owned(), Self::decode_value_array_for_field_type(graph, r#type, false, value, path)?);
                     }
-                    "mode" => match value.as_str() {
-                        Some(s) => if s == "caseInsensitive" {
-                            retval.insert(key.to_owned(), Value::String("caseInsensitive".to_owned()));
-                        } else {
-                            return Err(Error::unexpected_input_type("'caseInsensitive'", path));
-                        },
-                        None => return Err(Error::unexpected_input_type("string", path)),
+                    "between" => {
+                        if json_map.contains_key("gte") || json_map.contains_key("lte") {
+                            return Err(Error::unexpected_input_value_with_reason("Between cannot be used with gte or lte.", path));
+                        }
+                        let (gte, lte) = Self::decode_between_for_field_type(graph, r#type, value, path)?;
+                        retval.insert("gte".to_owned(), gte);
+                        retval.insert("lte".to_owned(), lte);
+                    }
+                    "mode" => if let Some(mode) = Self::decode_mode(value, path)? {
+                        retval.insert(key.to_owned(), mode);
                     }
                     "has" => {
                         let element_field = r#type.element_field().unwrap();
@@ -852,6 +1038,28 @@ impl Decoder {
         }
     }
 
+    fn decode_between_for_field_type<'a>(graph: &Graph, r#type: &FieldType, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<(Value, Value)> {
+        let path = path.as_ref();
+        if let Some(token) = json_value.as_str() {
+            match relative_date_range(token, r#type, Utc::now().with_timezone(graph.default_timezone())) {
+                Some(range) => Ok(range),
+                None => Err(Error::unexpected_input_value(RELATIVE_DATE_RANGES.join(", "), path)),
+            }
+        } else if let Some(array) = json_value.as_array() {
+            if array.len() != 2 {
+                return Err(Error::unexpected_input_value_with_reason("Between requires exactly 2 values.", path));
+            }
+            let gte = Self::decode_value_for_field_type(graph, r#type, false, array.get(0).unwrap(), path + 0usize)?;
+            let lte = Self::decode_value_for_field_type(graph, r#type, false, array.get(1).unwrap(), path + 1usize)?;
+            if gte > lte {
+                return Err(Error::unexpected_input_value_with_reason("Between's lower bound is greater than its upper bound.", path));
+            }
+            Ok((gte, lte))
+        } else {
+            Err(Error::unexpected_input_type("array or string", path))
+        }
+    }
+
     fn decode_where_with_aggregates_for_field<'a>(graph: &Graph, r#type: &FieldType, optional: bool, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         Self::decode_where_for_field_internal(graph, r#type, optional, json_value, path, true)
     }
@@ -862,6 +1070,18 @@ impl Decoder {
 
     fn decode_where_for_relation<'a>(graph: &Graph, relation: &Relation, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         let path = path.as_ref();
+        // a scalar is shorthand for the related record's primary key
+        if !relation.is_vec() && (json_value.is_string() || json_value.is_number()) {
+            let model = graph.model(relation.model()).unwrap();
+            let key = if relation.has_foreign_key() && relation.references().len() == 1 {
+                relation.references().get(0).unwrap().as_str()
+            } else if model.primary_index().keys().len() == 1 {
+                model.primary_index().keys().get(0).unwrap().as_str()
+            } else {
+                return Err(Error::unexpected_input_type("object", path));
+            };
+            return Self::decode_where_for_relation(graph, relation, &json!({"is": {key: json_value}}), path);
+        }
         if let Some(json_map) = json_value.as_object() {
             Self::check_json_keys(json_map, relation.filters(), path)?;
             let mut retval: HashMap<String, Value> = hashmap!{};
@@ -911,6 +1131,52 @@ impl Decoder {
         }
     }
 
+    /// Rejects decimals in a decoded value which the model's database can't
+    /// store without rounding them.
+    fn check_decimals<'a>(model: &Model, value: &Value, path: impl AsRef<KeyPath<'a>>) -> Result<()> {
+        let path = path.as_ref();
+        match value {
+            Value::Decimal(decimal) => if !model.connector().decimal_is_storable(decimal) {
+                return Err(Error::unexpected_input_value_with_reason(format!("Decimal {} can't be stored without rounding.", decimal), path));
+            }
+            Value::Vec(vec) => for (i, v) in vec.iter().enumerate() {
+                Self::check_decimals(model, v, path + i)?;
+            }
+            Value::HashMap(map) => for (k, v) in map {
+                Self::check_decimals(model, v, path + k)?;
+            }
+            _ => ()
+        }
+        Ok(())
+    }
+
+    /// Parses an RFC 3339 datetime, or a date-only string taken as midnight in `timezone`.
+    fn parse_datetime(s: &str, timezone: FixedOffset) -> Option<DateTime<Utc>> {
+        match DateTime::parse_from_rfc3339(s) {
+            Ok(fixed_offset_datetime) => Some(fixed_offset_datetime.with_timezone(&Utc)),
+            Err(_) => NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().map(|date| date_to_datetime(date, timezone)),
+        }
+    }
+
+    /// Under `EpochMillis`, a date time is also read as clients write it,
+    /// `{"$date": millis}`.
+    fn decode_epoch_millis(graph: &Graph, json_value: &JsonValue) -> Option<DateTime<Utc>> {
+        if graph.date_time_format() != &DateTimeFormat::EpochMillis { return None }
+        let millis = Self::json_integer(json_value.as_object()?.get("$date")?)?;
+        Utc.timestamp_millis_opt(millis).single()
+    }
+
+    /// The integer a JSON number holds without loss, `30.0` is `30` while
+    /// `30.5` isn't an integer.
+    fn json_integer(json_value: &JsonValue) -> Option<i64> {
+        match json_value.as_i64() {
+            Some(i) => Some(i),
+            None => json_value.as_f64()
+                .filter(|f| f.fract() == 0.0 && *f >= i64::MIN as f64 && *f < i64::MAX as f64)
+                .map(|f| f as i64),
+        }
+    }
+
     pub(crate) fn decode_value_for_field_type<'a>(graph: &Graph, r#type: &FieldType, optional: bool, json_value: &JsonValue, path: impl AsRef<KeyPath<'a>>) -> Result<Value> {
         if optional && json_value.is_null() {
             return Ok(Value::Null);
@@ -929,12 +1195,15 @@ impl Decoder {
                 Some(b) => Ok(Value::Bool(b)),
                 None => Err(Error::unexpected_input_type("bool", path))
             }
-            FieldType::I32 => match json_value.as_i64() {
-                Some(i) => Ok(Value::I32(i as i32)),
+            FieldType::I32 => match Self::json_integer(json_value) {
+                Some(i) => match i32::try_from(i) {
+                    Ok(i) => Ok(Value::I32(i)),
+                    Err(_) => Err(Error::unexpected_input_value("32 bit integer", path))
+                },
                 None => Err(Error::unexpected_input_type("32 bit integer", path))
             }
-            FieldType::I64 => match json_value.as_i64() {
-                Some(i) => Ok(Value::I64(i as i64)),
+            FieldType::I64 => match Self::json_integer(json_value) {
+                Some(i) => Ok(Value::I64(i)),
                 None => Err(Error::unexpected_input_type("64 bit integer", path))
             }
             FieldType::F32 => match json_value.as_f64() {
@@ -967,11 +1236,14 @@ impl Decoder {
                 None => Err(Error::unexpected_input_type("date string", path))
             }
             FieldType::DateTime => match json_value.as_str() {
-                Some(s) => match DateTime::parse_from_rfc3339(s) {
-                    Ok(fixed_offset_datetime) => Ok(Value::DateTime(fixed_offset_datetime.with_timezone(&Utc))),
-                    Err(_) => Err(Error::unexpected_input_value("datetime string", path))
+                Some(s) => match Self::parse_datetime(s, *graph.default_timezone()) {
+                    Some(datetime) => Ok(Value::DateTime(datetime)),
+                    None => Err(Error::unexpected_input_value("datetime string", path))
+                }
+                None => match Self::decode_epoch_millis(graph, json_value) {
+                    Some(datetime) => Ok(Value::DateTime(datetime)),
+                    None => Err(Error::unexpected_input_type("datetime string", path))
                 }
-                None => Err(Error::unexpected_input_type("datetime string", path))
             }
             FieldType::Enum(enum_name) => match json_value.as_str() {
                 Some(s) => if graph.enum_values(enum_name.as_str()).unwrap().contains(&s.to_string()) {
@@ -1005,7 +1277,8 @@ impl Decoder {
                 },
                 None => Err(Error::unexpected_input_type("object", path))
             }
-            FieldType::Object(_) => panic!("Object input is not implemented yet.")
+            FieldType::Object(_) => panic!("Object input is not implemented yet."),
+            FieldType::Json => Ok(Value::from(json_value)),
         }
     }
 }
@@ -1026,18 +1299,182 @@ static NESTED_UPSERT_INPUT_KEYS_WITHOUT_WHERE: Lazy<HashSet<&str>> = Lazy::new(|
     hashset!{"create", "update"}
 });
 
-static NESTED_CREATE_ONE_ARG_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
+pub(crate) static NESTED_CREATE_ONE_ARG_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset!{"create", "connect", "connectOrCreate"}
 });
 
-static NESTED_CREATE_MANY_ARG_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
+pub(crate) static NESTED_CREATE_MANY_ARG_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset!{"create", "createMany", "connect", "connectOrCreate"}
 });
 
-static NESTED_UPDATE_ONE_ARG_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
+pub(crate) static NESTED_UPDATE_ONE_ARG_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset!{"create", "connect", "connectOrCreate", "set", "disconnect", "update", "delete", "upsert"}
 });
 
-static NESTED_UPDATE_MANY_ARG_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
+pub(crate) static NESTED_UPDATE_MANY_ARG_KEYS: Lazy<HashSet<&str>> = Lazy::new(|| {
     hashset!{"create", "createMany", "connect", "connectOrCreate", "set", "disconnect", "update", "updateMany", "upsert", "delete", "deleteMany"}
 });
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+    use crate::core::action::{CREATE_HANDLER, FIND_MANY_HANDLER, UPDATE_HANDLER};
+    use crate::core::error::ErrorType;
+    use crate::core::field::Field;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::test_connector::TestConnector;
+    use crate::core::test_fixtures::users_and_posts;
+    use crate::teon;
+    use super::*;
+
+    #[test]
+    fn unknown_top_level_key_is_rejected_in_strict_mode() {
+        let action = Action::from_u32(FIND_MANY_HANDLER);
+        let input = json!({"where": {}, "foo": 1});
+        let err = Decoder::check_json_keys(input.as_object().unwrap(), action.handler_allowed_input_json_keys(), &path![]).unwrap_err();
+        assert_eq!(err.r#type, ErrorType::UnexpectedInputKey);
+        assert_eq!(err.errors.unwrap().get("foo").unwrap(), "Unexpected key 'foo'.");
+    }
+
+    #[test]
+    fn unknown_top_level_key_is_dropped_in_lenient_mode() {
+        let action = Action::from_u32(FIND_MANY_HANDLER);
+        let input = json!({"where": {}, "foo": 1});
+        assert_eq!(Decoder::retain_allowed_action_arg_keys(action, &input), json!({"where": {}}));
+    }
+
+    #[tokio::test]
+    async fn unique_where_matches_every_key_of_a_composite_unique_index() {
+        let mut builder = GraphBuilder::new();
+        builder.model("Membership", |m| {
+            let mut user_id = Field::new("userId".to_owned());
+            user_id.field_type = Some(FieldType::I32);
+            let mut org_id = Field::new("orgId".to_owned());
+            org_id.field_type = Some(FieldType::I32);
+            let mut role = Field::new("role".to_owned());
+            role.field_type = Some(FieldType::String);
+            m.field(user_id).field(org_id).field(role).primary(["userId", "orgId"]).index(["role"]);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let model = graph.model("Membership").unwrap();
+        let unique = Decoder::decode_where_unique(model, &graph, &json!({"orgId": 1, "userId": 2}), path![]).unwrap();
+        assert_eq!(unique, teon!({"orgId": 1, "userId": 2}));
+        assert!(Decoder::decode_where_unique(model, &graph, &json!({"userId": 2}), path![]).is_err());
+        // a non unique index doesn't identify a record
+        assert!(Decoder::decode_where_unique(model, &graph, &json!({"role": "owner"}), path![]).is_err());
+    }
+
+    #[test]
+    fn order_by_array_keeps_the_order_of_its_items() {
+        let input = json!([{"createdAt": "desc"}, {"id": "asc"}]);
+        let items = Decoder::order_by_items(&input, &path!["orderBy"]).unwrap();
+        assert_eq!(items, vec![&json!({"createdAt": "desc"}), &json!({"id": "asc"})]);
+        let input = json!({"id": "asc"});
+        assert_eq!(Decoder::order_by_items(&input, &path!["orderBy"]).unwrap(), vec![&input]);
+    }
+
+    #[test]
+    fn order_by_object_with_many_keys_is_rejected() {
+        let input = json!({"createdAt": "desc", "id": "asc"});
+        let err = Decoder::order_by_items(&input, &path!["orderBy"]).unwrap_err();
+        assert_eq!(err.r#type, ErrorType::ValidationError);
+        assert_eq!(err.errors.unwrap().get("orderBy").unwrap(), "Sorting by multiple keys requires an array of single key objects.");
+    }
+
+    #[test]
+    fn date_only_datetime_is_midnight_in_default_timezone() {
+        let timezone = FixedOffset::east_opt(8 * 3600).unwrap();
+        let stored = Decoder::parse_datetime("2023-03-16", timezone).unwrap();
+        assert_eq!(stored, Utc.with_ymd_and_hms(2023, 3, 15, 16, 0, 0).unwrap());
+        let explicit = Decoder::parse_datetime("2023-03-16T00:00:00Z", timezone).unwrap();
+        assert_eq!(explicit, Utc.with_ymd_and_hms(2023, 3, 16, 0, 0, 0).unwrap());
+        assert_eq!(Decoder::parse_datetime("16/03/2023", timezone), None);
+    }
+
+    #[tokio::test]
+    async fn epoch_millis_date_times_are_read_as_they_are_written() {
+        let millis = json!({"$date": 1678955400000i64});
+        let mut builder = GraphBuilder::new();
+        builder.date_time_format = DateTimeFormat::EpochMillis;
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let date_time = Decoder::decode_value_for_field_type(&graph, &FieldType::DateTime, false, &millis, path!["createdAt"]).unwrap();
+        assert_eq!(date_time, Value::DateTime(Utc.with_ymd_and_hms(2023, 3, 16, 8, 30, 0).unwrap()));
+        let string = json!("2023-03-16T08:30:00Z");
+        assert_eq!(Decoder::decode_value_for_field_type(&graph, &FieldType::DateTime, false, &string, path!["createdAt"]).unwrap(), date_time);
+        let graph = GraphBuilder::new().build(Arc::new(TestConnector::default())).await;
+        assert!(Decoder::decode_value_for_field_type(&graph, &FieldType::DateTime, false, &millis, path!["createdAt"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn scalar_relation_filter_is_the_related_primary_key() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let action = Action::from_u32(FIND_MANY_HANDLER);
+        let post = graph.model("Post").unwrap();
+        let shorthand = Decoder::decode_action_arg(post, &graph, action, &json!({"where": {"author": 5}})).unwrap();
+        let explicit = Decoder::decode_action_arg(post, &graph, action, &json!({"where": {"author": {"is": {"id": 5}}}})).unwrap();
+        assert_eq!(shorthand, explicit);
+        // a scalar doesn't identify the records of a to-many relation
+        let user = graph.model("User").unwrap();
+        assert!(Decoder::decode_action_arg(user, &graph, action, &json!({"where": {"posts": 5}})).is_err());
+    }
+
+    #[tokio::test]
+    async fn many_records_cannot_be_connected_to_a_to_one_relation() {
+        let graph = users_and_posts(Arc::new(TestConnector::default())).await;
+        let post = graph.model("Post").unwrap();
+        let create = Action::from_u32(CREATE_HANDLER);
+        let err = Decoder::decode_action_arg(post, &graph, create, &json!({"create": {"id": 1, "author": {"connect": [{"id": 1}, {"id": 2}]}}})).unwrap_err();
+        assert_eq!(err.r#type, ErrorType::ValidationError);
+        assert_eq!(err.errors.unwrap().get("create.author.connect").unwrap(), "Relation 'author' is to-one, only one record can be connected.");
+        assert!(Decoder::decode_action_arg(post, &graph, create, &json!({"create": {"id": 1, "author": {"connect": {"id": 1}}}})).is_ok());
+        let update = Action::from_u32(UPDATE_HANDLER);
+        let err = Decoder::decode_action_arg(post, &graph, update, &json!({"where": {"id": 1}, "update": {"author": {"set": [{"id": 1}, {"id": 2}]}}})).unwrap_err();
+        assert_eq!(err.errors.unwrap().get("update.author.set").unwrap(), "Relation 'author' is to-one, only one record can be connected.");
+        let user = graph.model("User").unwrap();
+        assert!(Decoder::decode_action_arg(user, &graph, create, &json!({"create": {"id": 1, "posts": {"connect": [{"id": 1}, {"id": 2}]}}})).is_ok());
+    }
+
+    #[test]
+    fn relation_and_its_foreign_key_cannot_be_set_together() {
+        let mut author_id = Field::new("authorId".to_owned());
+        author_id.foreign_key = true;
+        let mut author = Relation::new("author");
+        author.set_fields(vec!["authorId".to_owned()]);
+        author.set_references(vec!["id".to_owned()]);
+        author.finalize(&hashmap!{"authorId".to_owned() => Arc::new(author_id)});
+        let relations = vec![Arc::new(author)];
+        let input = json!({"author": {"connect": {"id": 1}}, "authorId": 1});
+        let err = Decoder::check_relation_foreign_key_conflict(&relations, input.as_object().unwrap(), &path![]).unwrap_err();
+        assert_eq!(err.r#type, ErrorType::ValidationError);
+        assert_eq!(err.errors.unwrap().get("authorId").unwrap(), "Relation 'author' and foreign key 'authorId' are mutually exclusive.");
+        let input = json!({"author": {"connect": {"id": 1}}});
+        assert!(Decoder::check_relation_foreign_key_conflict(&relations, input.as_object().unwrap(), &path![]).is_ok());
+    }
+
+    #[test]
+    fn unknown_where_operator_is_named_with_its_field() {
+        let input = json!({"equals": "Ann", "like": "A%"});
+        let err = Decoder::check_where_operators(input.as_object().unwrap(), &hashset!{"equals", "contains"}, &path!["where", "name"]).unwrap_err();
+        assert_eq!(err.r#type, ErrorType::ValidationError);
+        assert_eq!(err.errors.unwrap().get("where.name.like").unwrap(), "Unknown operator 'like' on 'where.name'.");
+    }
+
+    #[test]
+    fn integer_inputs_are_read_without_loss() {
+        assert_eq!(Decoder::json_integer(&json!(30)), Some(30));
+        assert_eq!(Decoder::json_integer(&json!(30.0)), Some(30));
+        assert_eq!(Decoder::json_integer(&json!(-9007199254740993i64)), Some(-9007199254740993));
+        assert_eq!(Decoder::json_integer(&json!(30.5)), None);
+        assert_eq!(Decoder::json_integer(&json!(u64::MAX)), None);
+        assert_eq!(Decoder::json_integer(&json!("30")), None);
+    }
+
+    #[test]
+    fn mode_is_case_insensitive_or_default() {
+        assert_eq!(Decoder::decode_mode(&json!("caseInsensitive"), path!["mode"]).unwrap(), Some(Value::String("caseInsensitive".to_owned())));
+        assert_eq!(Decoder::decode_mode(&json!("default"), path!["mode"]).unwrap(), None);
+        let err = Decoder::decode_mode(&json!("insensitive"), path!["mode"]).unwrap_err();
+        assert_eq!(err.errors.unwrap().get("mode").unwrap(), "Unknown mode 'insensitive', expect 'caseInsensitive' or 'default'.");
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use crate::core::teon::redact::REDACTED;
+use crate::prelude::Value;
+
+/// A field whose value differs from the one the object was loaded with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Changes of `keys` recorded in `original`, in key order. Fields set back to
+/// their original value are left out, redacted fields carry `"***"` values.
+pub(crate) fn diff<'a>(keys: impl Iterator<Item = &'a str>, original: &HashMap<String, Value>, current: &HashMap<String, Value>, is_redacted: impl Fn(&str) -> bool) -> Vec<FieldChange> {
+    keys.filter_map(|key| {
+        let old = original.get(key)?;
+        let new = current.get(key).unwrap_or(&Value::Null);
+        if old == new { return None }
+        Some(if is_redacted(key) {
+            FieldChange { field: key.to_owned(), old: Value::String(REDACTED.to_owned()), new: Value::String(REDACTED.to_owned()) }
+        } else {
+            FieldChange { field: key.to_owned(), old: old.clone(), new: new.clone() }
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use maplit::hashmap;
+    use super::*;
+
+    #[test]
+    fn diff_contains_only_changed_fields() {
+        let original = hashmap!{"name".to_owned() => Value::String("Ann".to_owned()), "age".to_owned() => Value::I32(30), "bio".to_owned() => Value::Null};
+        let current = hashmap!{"id".to_owned() => Value::I32(1), "name".to_owned() => Value::String("Anna".to_owned()), "age".to_owned() => Value::I32(30), "bio".to_owned() => Value::String("Hi".to_owned())};
+        let changes = diff(["id", "name", "age", "bio"].into_iter(), &original, &current, |_| false);
+        assert_eq!(changes, vec![
+            FieldChange { field: "name".to_owned(), old: Value::String("Ann".to_owned()), new: Value::String("Anna".to_owned()) },
+            FieldChange { field: "bio".to_owned(), old: Value::Null, new: Value::String("Hi".to_owned()) },
+        ]);
+    }
+
+    #[test]
+    fn redacted_fields_hide_their_values() {
+        let original = hashmap!{"password".to_owned() => Value::String("old".to_owned())};
+        let current = hashmap!{"password".to_owned() => Value::String("new".to_owned())};
+        let changes = diff(["password"].into_iter(), &original, &current, |key| key == "password");
+        assert_eq!(changes[0].old, Value::String("***".to_owned()));
+        assert_eq!(changes[0].new, Value::String("***".to_owned()));
+    }
+}
@@ -0,0 +1,173 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use regex::Regex;
+use crate::core::input::Input;
+use crate::prelude::Value;
+
+/// Evaluates a decoded `where` input in memory, for records which the
+/// database can't filter, like ones of virtual models. `get` returns the
+/// record's value for a key.
+pub(crate) fn matches_where<F>(r#where: &Value, get: &F) -> bool where F: Fn(&str) -> Value {
+    let r#where = match r#where.as_hashmap() {
+        Some(map) => map,
+        None => return true,
+    };
+    r#where.iter().all(|(key, filter)| match key.as_str() {
+        "AND" => filter.as_vec().unwrap().iter().all(|w| matches_where(w, get)),
+        "OR" => filter.as_vec().unwrap().iter().any(|w| matches_where(w, get)),
+        "NOT" => !matches_where(filter, get),
+        _ => matches_value(&get(key), filter),
+    })
+}
+
+/// Evaluates a field filter like `{"gt": 5, "lte": 10}` or a bare value
+/// against `value`.
+pub(crate) fn matches_value(value: &Value, filter: &Value) -> bool {
+    let map = match filter.as_hashmap() {
+        Some(map) => map,
+        None => return compare(value, filter) == Some(Ordering::Equal) || (value.is_null() && filter.is_null()),
+    };
+    let i_mode = Input::has_i_mode(map);
+    map.iter().all(|(op, rhs)| match op.as_str() {
+        "equals" => matches_value(value, rhs),
+        "not" => !matches_value(value, rhs),
+        "gt" => compare(value, rhs) == Some(Ordering::Greater),
+        "gte" => matches!(compare(value, rhs), Some(Ordering::Greater | Ordering::Equal)),
+        "lt" => compare(value, rhs) == Some(Ordering::Less),
+        "lte" => matches!(compare(value, rhs), Some(Ordering::Less | Ordering::Equal)),
+        "in" => rhs.as_vec().unwrap().iter().any(|v| matches_value(value, v)),
+        "notIn" => !rhs.as_vec().unwrap().iter().any(|v| matches_value(value, v)),
+        "contains" => string_op(value, rhs, i_mode, |s, p| s.contains(p)),
+        "startsWith" => string_op(value, rhs, i_mode, |s, p| s.starts_with(p)),
+        "endsWith" => string_op(value, rhs, i_mode, |s, p| s.ends_with(p)),
+        "matches" => string_op(value, rhs, i_mode, |s, p| Regex::new(p).map(|r| r.is_match(s)).unwrap_or(false)),
+        "search" => string_op(value, rhs, true, |s, p| p.split_whitespace().any(|w| s.contains(w))),
+        "mode" => true,
+        "has" => value.as_vec().map_or(false, |vec| vec.iter().any(|v| matches_value(v, rhs))),
+        "hasEvery" => value.as_vec().map_or(false, |vec| rhs.as_vec().unwrap().iter().all(|r| vec.iter().any(|v| matches_value(v, r)))),
+        "hasSome" => value.as_vec().map_or(false, |vec| rhs.as_vec().unwrap().iter().any(|r| vec.iter().any(|v| matches_value(v, r)))),
+        "isEmpty" => value.as_vec().map_or(false, |vec| vec.is_empty() == rhs.as_bool().unwrap_or(true)),
+        "length" => value.as_vec().map_or(false, |vec| Some(vec.len() as i64) == rhs.as_i64()),
+        _ => false,
+    })
+}
+
+/// Orders two values, numbers of different types are compared by their value.
+fn compare(lhs: &Value, rhs: &Value) -> Option<Ordering> {
+    lhs.partial_cmp(rhs)
+}
+
+/// The `where` of the records from a cursor on, in the order of a decoded
+/// `orderBy` over the cursor's keys. Multiple keys expand to the keyset
+/// pagination form, `a > x OR (a = x AND b >= y)`, and the cursor record
+/// itself is included. A negative take pages backwards.
+pub(crate) fn cursor_where(cursor: &Value, order_by: &Value, negative_take: bool) -> Value {
+    let items: Vec<(&String, bool)> = order_by.as_vec().unwrap().iter().map(|item| {
+        let (key, direction) = item.as_hashmap().unwrap().iter().next().unwrap();
+        (key, (direction.as_str().unwrap() == "asc") != negative_take)
+    }).collect();
+    let mut branches: Vec<Value> = items.iter().enumerate().map(|(i, (key, asc))| {
+        let mut branch: HashMap<String, Value> = items[..i].iter().map(|(k, _)| {
+            ((*k).clone(), Value::HashMap(HashMap::from([("equals".to_owned(), cursor.get(k.as_str()).unwrap().clone())])))
+        }).collect();
+        let op = match (asc, i == items.len() - 1) {
+            (true, true) => "gte",
+            (true, false) => "gt",
+            (false, true) => "lte",
+            (false, false) => "lt",
+        };
+        branch.insert((*key).clone(), Value::HashMap(HashMap::from([(op.to_owned(), cursor.get(key.as_str()).unwrap().clone())])));
+        Value::HashMap(branch)
+    }).collect();
+    if branches.len() == 1 {
+        branches.remove(0)
+    } else {
+        Value::HashMap(HashMap::from([("OR".to_owned(), Value::Vec(branches))]))
+    }
+}
+
+fn string_op<F>(value: &Value, pattern: &Value, i_mode: bool, op: F) -> bool where F: Fn(&str, &str) -> bool {
+    match (value.as_str(), pattern.as_str()) {
+        (Some(s), Some(p)) => if i_mode {
+            op(&s.to_lowercase(), &p.to_lowercase())
+        } else {
+            op(s, p)
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::teon;
+    use super::*;
+
+    fn records() -> Vec<HashMap<String, Value>> {
+        vec![
+            teon!({"name": "Alice", "age": 31, "tags": ["admin", "staff"]}),
+            teon!({"name": "bob", "age": 17, "tags": []}),
+            teon!({"name": "Carol", "age": 45, "tags": ["staff"]}),
+        ].into_iter().map(|v| v.as_hashmap().unwrap().clone()).collect()
+    }
+
+    fn names(r#where: Value) -> Vec<String> {
+        records().iter().filter(|record| {
+            matches_where(&r#where, &|key: &str| record.get(key).cloned().unwrap_or(Value::Null))
+        }).map(|record| record.get("name").unwrap().as_str().unwrap().to_owned()).collect()
+    }
+
+    #[test]
+    fn comparison_and_string_operators() {
+        assert_eq!(names(teon!({"age": {"gte": 31}})), vec!["Alice", "Carol"]);
+        assert_eq!(names(teon!({"age": {"gt": 17, "lt": 45}})), vec!["Alice"]);
+        assert_eq!(names(teon!({"name": {"equals": "bob"}})), vec!["bob"]);
+        assert_eq!(names(teon!({"name": {"startsWith": "b", "mode": "caseInsensitive"}})), vec!["bob"]);
+        assert_eq!(names(teon!({"name": {"contains": "AR", "mode": "caseInsensitive"}})), vec!["Carol"]);
+        assert_eq!(names(teon!({"age": {"in": [17, 45]}})), vec!["bob", "Carol"]);
+        assert_eq!(names(teon!({"age": {"notIn": [17, 45]}})), vec!["Alice"]);
+        assert_eq!(names(teon!({"OR": [{"name": {"search": "carol dave"}}, {"tags": {"has": "carol"}}]})), vec!["Carol"]);
+    }
+
+    #[test]
+    fn integers_of_any_width_match_by_value() {
+        let large = 9007199254740993i64;
+        for stored in [Value::I32(30), Value::I64(30), Value::F32(30.0), Value::F64(30.0)] {
+            assert!(matches_value(&stored, &Value::I64(30)));
+            assert!(matches_value(&stored, &teon!({"gte": Value::I32(30), "lt": Value::I64(31)})));
+            assert!(matches_value(&stored, &teon!({"in": [Value::I64(1), Value::I64(30)]})));
+        }
+        // beyond 2^53 integers still differ by one
+        assert!(!matches_value(&Value::I64(large), &Value::I64(large - 1)));
+        assert!(matches_value(&Value::I64(large), &teon!({"gt": Value::I64(large - 1)})));
+    }
+
+    #[test]
+    fn logical_and_array_operators() {
+        assert_eq!(names(teon!({"OR": [{"age": {"lt": 18}}, {"name": "Carol"}]})), vec!["bob", "Carol"]);
+        assert_eq!(names(teon!({"NOT": {"age": {"lt": 18}}})), vec!["Alice", "Carol"]);
+        assert_eq!(names(teon!({"tags": {"has": "admin"}})), vec!["Alice"]);
+        assert_eq!(names(teon!({"tags": {"isEmpty": true}})), vec!["bob"]);
+        assert_eq!(names(teon!({"age": {"gt": 30.5}})), vec!["Alice", "Carol"]);
+    }
+
+    #[test]
+    fn not_negates_a_nested_filter() {
+        assert_eq!(names(teon!({"age": {"not": {"in": [17, 45]}}})), vec!["Alice"]);
+        assert_eq!(names(teon!({"age": {"not": {"gte": 31}}})), vec!["bob"]);
+        assert_eq!(names(teon!({"age": {"not": 17}})), vec!["Alice", "Carol"]);
+    }
+
+    #[test]
+    fn multi_key_cursor_pages_in_keyset_order() {
+        let cursor = teon!({"age": 31, "name": "Alice"});
+        let order_by = teon!([{"age": "asc"}, {"name": "desc"}]);
+        assert_eq!(cursor_where(&cursor, &order_by, false), teon!({"OR": [
+            {"age": {"gt": 31}},
+            {"age": {"equals": 31}, "name": {"lte": "Alice"}},
+        ]}));
+        assert_eq!(names(cursor_where(&cursor, &order_by, false)), vec!["Alice", "Carol"]);
+        assert_eq!(names(cursor_where(&cursor, &order_by, true)), vec!["Alice", "bob"]);
+        assert_eq!(cursor_where(&teon!({"age": 31}), &teon!([{"age": "desc"}]), false), teon!({"age": {"lte": 31}}));
+    }
+}
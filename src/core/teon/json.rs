@@ -1,6 +1,33 @@
-use chrono::SecondsFormat;
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde_json::{Value as JsonValue, Number as JsonNumber, Map as JsonMap, json};
 use crate::core::teon::Value;
+use crate::parser::ast::config::DateTimeFormat;
+
+/// Writes a date time in `format`, wrapped in `$date` so clients can tell it
+/// apart from a plain string.
+pub(crate) fn date_time_to_json(val: &DateTime<Utc>, format: &DateTimeFormat) -> JsonValue {
+    match format {
+        DateTimeFormat::RFC3339 => json!({"$date": val.to_rfc3339_opts(SecondsFormat::Millis, true)}),
+        DateTimeFormat::EpochMillis => json!({"$date": val.timestamp_millis()}),
+        DateTimeFormat::Custom(format) => json!({"$date": val.format(format).to_string()}),
+    }
+}
+
+impl Value {
+
+    /// Replaces date times, also those in arrays, with their `$date` values
+    /// in `format`. Maps are left as they are, they hold user JSON.
+    pub(crate) fn with_date_time_format(self, format: &DateTimeFormat) -> Value {
+        if *format == DateTimeFormat::RFC3339 {
+            return self;
+        }
+        match self {
+            Value::DateTime(val) => Value::from(&date_time_to_json(&val, format)),
+            Value::Vec(val) => Value::Vec(val.into_iter().map(|v| v.with_date_time_format(format)).collect()),
+            _ => self,
+        }
+    }
+}
 
 impl Into<JsonValue> for Value {
     fn into(self) -> JsonValue {
@@ -37,7 +64,7 @@ impl Into<JsonValue> for Value {
                 JsonValue::String(val.format("%Y-%m-%d").to_string())
             }
             Value::DateTime(val) => {
-                json!({"$date": val.to_rfc3339_opts(SecondsFormat::Millis, true)})
+                date_time_to_json(&val, &DateTimeFormat::RFC3339)
             }
             Value::Vec(val) => {
                 JsonValue::Array(val.iter().map(|v| v.into()).collect())
@@ -78,3 +105,60 @@ impl Into<JsonValue> for &Value {
         self.clone().into()
     }
 }
+
+impl From<&JsonValue> for Value {
+
+    /// Converts JSON verbatim, objects keep their key order.
+    fn from(json: &JsonValue) -> Self {
+        match json {
+            JsonValue::Null => Value::Null,
+            JsonValue::Bool(b) => Value::Bool(*b),
+            JsonValue::Number(n) => match n.as_i64() {
+                Some(i) => Value::I64(i),
+                None => Value::F64(n.as_f64().unwrap()),
+            },
+            JsonValue::String(s) => Value::String(s.clone()),
+            JsonValue::Array(vec) => Value::Vec(vec.iter().map(|v| v.into()).collect()),
+            JsonValue::Object(map) => Value::IndexMap(map.iter().map(|(k, v)| (k.clone(), v.into())).collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn date_time_is_written_as_rfc3339_by_default() {
+        let value = Value::DateTime(Utc.with_ymd_and_hms(2023, 3, 16, 8, 30, 0).unwrap());
+        let json: JsonValue = value.into();
+        assert_eq!(json, json!({"$date": "2023-03-16T08:30:00.000Z"}));
+    }
+
+    #[test]
+    fn date_time_is_written_as_epoch_millis() {
+        let date_time = Utc.with_ymd_and_hms(2023, 3, 16, 8, 30, 0).unwrap();
+        assert_eq!(date_time_to_json(&date_time, &DateTimeFormat::EpochMillis), json!({"$date": 1678955400000i64}));
+    }
+
+    #[test]
+    fn date_times_are_formatted_but_user_json_is_not() {
+        let format = DateTimeFormat::Custom("%Y/%m/%d %H:%M".to_owned());
+        let date_time = Value::DateTime(Utc.with_ymd_and_hms(2023, 3, 16, 8, 30, 0).unwrap());
+        let json: JsonValue = Value::Vec(vec![date_time]).with_date_time_format(&format).into();
+        assert_eq!(json, json!([{"$date": "2023/03/16 08:30"}]));
+        let user_json = Value::from(&json!({"$date": "2023-03-16T08:30:00.000Z"}));
+        assert_eq!(user_json.clone().with_date_time_format(&format), user_json);
+    }
+
+    #[test]
+    fn json_round_trips_verbatim() {
+        let json = json!({"b": [1, 2.5, null], "a": {"nested": true, "name": "x"}});
+        let value = Value::from(&json);
+        assert_eq!(value.as_indexmap().unwrap().keys().collect::<Vec<&String>>(), vec!["b", "a"]);
+        let back: JsonValue = value.into();
+        assert_eq!(back, json);
+    }
+}
@@ -4,6 +4,11 @@ pub mod from;
 pub mod macros;
 pub mod range;
 pub(crate) mod decoder;
+pub(crate) mod date_range;
+pub(crate) mod timezone;
+pub(crate) mod filter;
+pub(crate) mod redact;
+pub mod diff;
 pub(crate) mod utils;
 
 use std::cmp::Ordering;
@@ -586,6 +591,13 @@ impl Default for Value {
 impl PartialOrd for Value {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         use Value::*;
+        // numbers of different types compare by value, integers without loss
+        if self.is_i() && other.is_i() {
+            return self.as_i64().unwrap().partial_cmp(&other.as_i64().unwrap());
+        }
+        if self.is_number() && other.is_number() {
+            return self.as_f64().unwrap().partial_cmp(&other.as_f64().unwrap());
+        }
         match (self, other) {
             (Null, Null) => Some(Ordering::Equal),
             #[cfg(feature = "data-source-mongodb")]
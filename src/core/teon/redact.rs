@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use crate::core::graph::Graph;
+use crate::prelude::Value;
+
+pub(crate) static REDACTED: &str = "***";
+
+/// A copy of records or action inputs of `model` safe for logging. Values of
+/// sensitive and unreadable fields are replaced with `"***"`, included
+/// relations are redacted likewise. Other nested objects, like `create` or
+/// `where` of an input, are redacted as values of `model`.
+pub(crate) fn redact(graph: &Graph, model: &str, value: &Value) -> Value {
+    let model_def = match graph.model(model) {
+        Some(model_def) => model_def,
+        None => return value.clone(),
+    };
+    match value {
+        Value::HashMap(map) => Value::HashMap(map.iter().map(|(key, value)| {
+            let value = if model_def.field(key).map_or(false, |f| f.is_redacted()) {
+                Value::String(REDACTED.to_owned())
+            } else if let Some(relation) = model_def.relation(key) {
+                redact(graph, relation.model(), value)
+            } else {
+                redact(graph, model, value)
+            };
+            (key.clone(), value)
+        }).collect::<HashMap<String, Value>>()),
+        Value::Vec(vec) => Value::Vec(vec.iter().map(|v| redact(graph, model, v)).collect()),
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::core::field::Field;
+    use crate::core::field::r#type::FieldType;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::relation::Relation;
+    use crate::core::relation::cardinality::Cardinality;
+    use crate::core::test_connector::TestConnector;
+    use crate::core::test_fixtures::id_field;
+    use crate::teon;
+    use super::*;
+
+    fn string_field(name: &str, sensitive: bool) -> Field {
+        let mut field = Field::new(name.to_owned());
+        field.field_type = Some(FieldType::String);
+        field.sensitive = sensitive;
+        field
+    }
+
+    async fn users_and_sessions() -> Graph {
+        let mut builder = GraphBuilder::new();
+        builder.model("User", |m| {
+            let mut sessions = Relation::new("sessions");
+            sessions.set_model("Session".to_owned());
+            sessions.set_cardinality(Cardinality::Many);
+            sessions.set_fields(vec!["id".to_owned()]);
+            sessions.set_references(vec!["userId".to_owned()]);
+            m.field(id_field()).field(string_field("email", false)).field(string_field("password", true)).relation(sessions);
+        });
+        builder.model("Session", |m| {
+            let mut user_id = Field::new("userId".to_owned());
+            user_id.field_type = Some(FieldType::I32);
+            let mut user = Relation::new("user");
+            user.set_model("User".to_owned());
+            user.set_fields(vec!["userId".to_owned()]);
+            user.set_references(vec!["id".to_owned()]);
+            m.field(id_field()).field(user_id).field(string_field("token", true)).relation(user);
+        });
+        builder.build(Arc::new(TestConnector::default())).await
+    }
+
+    #[tokio::test]
+    async fn sensitive_fields_are_redacted_and_others_pass_through() {
+        let graph = users_and_sessions().await;
+        let user = teon!({"id": 1, "email": "a@b.com", "password": "secret"});
+        assert_eq!(redact(&graph, "User", &user), teon!({"id": 1, "email": "a@b.com", "password": "***"}));
+    }
+
+    #[tokio::test]
+    async fn included_relations_are_redacted() {
+        let graph = users_and_sessions().await;
+        let user = teon!({"id": 1, "sessions": [{"token": "abc", "user": {"id": 1, "password": "secret"}}]});
+        assert_eq!(redact(&graph, "User", &user), teon!({"id": 1, "sessions": [{"token": "***", "user": {"id": 1, "password": "***"}}]}));
+    }
+
+    #[tokio::test]
+    async fn nested_inputs_are_redacted() {
+        let graph = users_and_sessions().await;
+        let input = teon!({"where": {"password": {"equals": "old"}}, "update": {"password": {"set": "new"}, "sessions": {"create": [{"token": "abc"}]}}});
+        assert_eq!(redact(&graph, "User", &input), teon!({"where": {"password": "***"}, "update": {"password": "***", "sessions": {"create": [{"token": "***"}]}}}));
+    }
+}
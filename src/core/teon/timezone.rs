@@ -0,0 +1,53 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+
+/// Parses a timezone setting such as `UTC`, `Z`, `+08:00` or `-05:30` into a fixed offset.
+pub(crate) fn parse_timezone_offset(timezone: &str) -> Option<FixedOffset> {
+    let timezone = timezone.trim();
+    if timezone.eq_ignore_ascii_case("utc") || timezone == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+    let sign = match timezone.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = timezone[1..].split_once(':')?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return None;
+    }
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// The instant of midnight on `date` in `timezone`.
+pub(crate) fn date_to_datetime(date: NaiveDate, timezone: FixedOffset) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    timezone.from_local_datetime(&midnight).unwrap().with_timezone(&Utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_utc_and_signed_offsets() {
+        assert_eq!(parse_timezone_offset("UTC"), FixedOffset::east_opt(0));
+        assert_eq!(parse_timezone_offset("+08:00"), FixedOffset::east_opt(8 * 3600));
+        assert_eq!(parse_timezone_offset("-05:30"), FixedOffset::west_opt(5 * 3600 + 30 * 60));
+        assert_eq!(parse_timezone_offset("Asia/Shanghai"), None);
+        assert_eq!(parse_timezone_offset("+24:00"), None);
+    }
+
+    #[test]
+    fn date_is_midnight_in_the_timezone() {
+        let date = NaiveDate::from_ymd_opt(2023, 3, 16).unwrap();
+        let east = parse_timezone_offset("+08:00").unwrap();
+        assert_eq!(date_to_datetime(date, east), Utc.with_ymd_and_hms(2023, 3, 15, 16, 0, 0).unwrap());
+        let west = parse_timezone_offset("-05:00").unwrap();
+        assert_eq!(date_to_datetime(date, west), Utc.with_ymd_and_hms(2023, 3, 16, 5, 0, 0).unwrap());
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use crate::core::action::Action;
+use crate::core::action::source::ActionSource;
+use crate::core::connector::{Connector, SaveSession};
+use crate::core::database::pool_metrics::PoolMetrics;
+use crate::core::database::r#type::DatabaseType;
+use crate::core::error::Error;
+use crate::core::field::r#type::FieldType;
+use crate::core::graph::Graph;
+use crate::core::model::Model;
+use crate::core::object::Object;
+use crate::core::result::Result;
+use crate::core::teon::filter::matches_where;
+use crate::prelude::Value;
+
+#[derive(Debug)]
+pub(crate) struct TestSaveSession { }
+
+impl SaveSession for TestSaveSession { }
+
+/// An in memory connector shared by unit tests. It records each operation
+/// performed and answers finders and counts from the prepared and the saved rows
+/// which match their `where`.
+#[derive(Default)]
+pub(crate) struct TestConnector {
+    pub(crate) operations: Mutex<Vec<String>>,
+    pub(crate) finders: Mutex<Vec<Value>>,
+    pub(crate) writes: Mutex<Vec<HashMap<String, Value>>>,
+    pub(crate) sequences: Mutex<HashMap<String, i64>>,
+    pub(crate) groups: Vec<Value>,
+    pub(crate) rows: HashMap<String, Vec<Value>>,
+    pub(crate) saved: Mutex<HashMap<String, Vec<Value>>>,
+    /// The address of the session of each save and delete.
+    pub(crate) sessions: Mutex<Vec<usize>>,
+    pub(crate) database_type: Option<fn(&FieldType) -> DatabaseType>,
+    pub(crate) decimal_digits: Option<u64>,
+    pub(crate) pool_metrics: PoolMetrics,
+}
+
+impl TestConnector {
+
+    fn record(&self, operation: &str, name: &str) {
+        self.operations.lock().unwrap().push(format!("{} {}", operation, name));
+    }
+
+    fn record_finder(&self, operation: &str, model: &Model, finder: &Value) {
+        self.record(operation, model.name());
+        self.finders.lock().unwrap().push(finder.clone());
+    }
+
+    fn record_session(&self, session: &Arc<dyn SaveSession>) {
+        self.sessions.lock().unwrap().push(Arc::as_ptr(session) as *const () as usize);
+    }
+
+    pub(crate) fn operations(&self) -> Vec<String> {
+        self.operations.lock().unwrap().clone()
+    }
+
+    fn matching_rows(&self, model: &Model, finder: &Value) -> Vec<Value> {
+        let mut rows = self.rows.get(model.name()).cloned().unwrap_or_default();
+        rows.extend(self.saved.lock().unwrap().get(model.name()).cloned().unwrap_or_default());
+        let r#where = finder.get("where").cloned().unwrap_or(Value::Null);
+        rows.retain(|row| matches_where(&r#where, &|key| row.get(key).cloned().unwrap_or(Value::Null)));
+        rows
+    }
+
+    async fn objects(&self, graph: &Graph, model: &Model, finder: &Value, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
+        let rows = self.matching_rows(model, finder);
+        let mut objects = vec![];
+        for row in rows.iter() {
+            let object = graph.new_object(model.name(), action, action_source.clone())?;
+            object.set_from_database_result_value(row, finder.get("select"), None);
+            object.trigger_after_find_callbacks().await?;
+            objects.push(object);
+        }
+        Ok(objects)
+    }
+}
+
+#[async_trait]
+impl Connector for TestConnector {
+
+    fn default_database_type(&self, field_type: &FieldType) -> DatabaseType {
+        match self.database_type {
+            Some(database_type) => database_type(field_type),
+            None => DatabaseType::Int { m: None, u: false },
+        }
+    }
+
+    fn decimal_is_storable(&self, decimal: &BigDecimal) -> bool {
+        match self.decimal_digits {
+            Some(digits) => decimal.normalized().digits() <= digits,
+            None => true,
+        }
+    }
+
+    async fn migrate(&self, models: &Vec<Model>, _reset_database: bool) -> Result<()> {
+        for model in models {
+            self.record("migrate", model.name());
+        }
+        Ok(())
+    }
+
+    async fn migrate_plan(&self, models: &Vec<Model>) -> Result<Vec<String>> {
+        for model in models {
+            self.record("migratePlan", model.name());
+        }
+        Ok(models.iter().map(|m| format!("CREATE TABLE `{}`", m.table_name())).collect())
+    }
+
+    async fn query_raw(&self, _query: &Value) -> Result<Value> {
+        self.operations.lock().unwrap().push("queryRaw".to_owned());
+        Ok(Value::Null)
+    }
+
+    async fn save_object(&self, object: &Object, session: Arc<dyn SaveSession>) -> Result<()> {
+        self.record("save", object.model().name());
+        self.record_session(&session);
+        self.writes.lock().unwrap().push(object.keys_for_save().into_iter().map(|k| (k.to_owned(), object.get_value(k).unwrap())).collect());
+        let mut row: HashMap<String, Value> = object.model().fields().iter().map(|f| (f.name().to_owned(), object.get_value(f.name()).unwrap())).collect();
+        let mut saved = self.saved.lock().unwrap();
+        let rows = saved.entry(object.model().name().to_owned()).or_default();
+        let position = rows.iter().position(|r| r.get("id") == row.get("id"));
+        for (key, value) in row.iter_mut() {
+            if let Some(updator) = object.get_atomic_updator(key) {
+                let current = position.and_then(|i| rows[i].get(key).cloned()).unwrap_or(Value::Null);
+                *value = match (updator.get("increment"), updator.get("decrement")) {
+                    (Some(amount), _) => (current + amount.clone())?,
+                    (_, Some(amount)) => (current - amount.clone())?,
+                    _ => updator,
+                };
+            }
+        }
+        match position {
+            Some(index) => rows[index] = Value::HashMap(row),
+            None => rows.push(Value::HashMap(row)),
+        }
+        Ok(())
+    }
+
+    async fn delete_object(&self, object: &Object, session: Arc<dyn SaveSession>) -> Result<()> {
+        self.record("delete", object.model().name());
+        self.record_session(&session);
+        if let Some(rows) = self.saved.lock().unwrap().get_mut(object.model().name()) {
+            rows.retain(|r| r.get("id") != object.get_value("id").ok().as_ref());
+        }
+        Ok(())
+    }
+
+    async fn update_many_atomically(&self, model: &Model, _objects: &Vec<Object>, updators: &HashMap<String, Value>) -> Result<()> {
+        self.record("updateMany", model.name());
+        self.writes.lock().unwrap().push(updators.clone());
+        Ok(())
+    }
+
+    async fn find_unique(&self, graph: &Graph, model: &Model, finder: &Value, _mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Object> {
+        self.record_finder("findUnique", model, finder);
+        self.objects(graph, model, finder, action, action_source).await?.into_iter().next().ok_or(Error::object_not_found())
+    }
+
+    async fn find_many(&self, graph: &Graph, model: &Model, finder: &Value, _mutation_mode: bool, action: Action, action_source: ActionSource) -> Result<Vec<Object>> {
+        self.record_finder("find", model, finder);
+        self.objects(graph, model, finder, action, action_source).await
+    }
+
+    async fn count(&self, _graph: &Graph, model: &Model, finder: &Value) -> Result<usize> {
+        self.record_finder("count", model, finder);
+        Ok(self.matching_rows(model, finder).len())
+    }
+
+    async fn aggregate(&self, _graph: &Graph, model: &Model, finder: &Value) -> Result<Value> {
+        self.record_finder("aggregate", model, finder);
+        Ok(Value::HashMap(HashMap::new()))
+    }
+
+    async fn group_by(&self, _graph: &Graph, model: &Model, finder: &Value) -> Result<Value> {
+        self.record_finder("groupBy", model, finder);
+        Ok(Value::Vec(self.groups.clone()))
+    }
+
+    async fn increment_sequence(&self, name: &str) -> Result<i64> {
+        self.record("incrementSequence", name);
+        let mut sequences = self.sequences.lock().unwrap();
+        let value = sequences.entry(name.to_owned()).or_insert(0);
+        *value += 1;
+        Ok(*value)
+    }
+
+    fn new_save_session(&self) -> Arc<dyn SaveSession> {
+        Arc::new(TestSaveSession { })
+    }
+
+    fn pool_metrics(&self) -> &PoolMetrics {
+        &self.pool_metrics
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use crate::core::field::{Field, FieldIndex, IndexSettings};
+use crate::core::field::optionality::Optionality;
+use crate::core::field::r#type::FieldType;
+use crate::core::graph::Graph;
+use crate::core::graph::builder::GraphBuilder;
+use crate::core::relation::Relation;
+use crate::core::relation::cardinality::Cardinality;
+use crate::core::relation::delete_rule::DeleteRule;
+use crate::core::test_connector::TestConnector;
+
+/// The `Int` primary key `id`.
+pub(crate) fn id_field() -> Field {
+    let mut id = Field::new("id".to_owned());
+    id.field_type = Some(FieldType::I32);
+    id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+    id
+}
+
+fn optional_field(name: &str, field_type: FieldType) -> Field {
+    let mut field = Field::new(name.to_owned());
+    field.field_type = Some(field_type);
+    field.optionality = Optionality::Optional;
+    field
+}
+
+/// A graph of users and the posts they author, shared by unit tests. A post
+/// has an optional `author` through `authorId`, and an optional `title`, a
+/// `status` of enum `Status` and a `publishedAt`.
+#[derive(Clone)]
+pub(crate) struct UsersAndPosts {
+    /// The delete rule of the posts' author relation.
+    pub(crate) delete_rule: DeleteRule,
+    /// Users and posts are soft deleted through `deletedAt`.
+    pub(crate) soft_delete: bool,
+    /// The type of the user's `postsCount`, the counter cache of the posts'
+    /// author relation.
+    pub(crate) counter_cache: Option<FieldType>,
+}
+
+impl Default for UsersAndPosts {
+    fn default() -> Self {
+        Self { delete_rule: DeleteRule::Default, soft_delete: false, counter_cache: None }
+    }
+}
+
+impl UsersAndPosts {
+
+    pub(crate) async fn build(self, connector: Arc<TestConnector>) -> Graph {
+        let mut builder = GraphBuilder::new();
+        builder.r#enum("Status", |e| { e.choice("ACTIVE", |_| {}).choice("PENDING", |_| {}).choice("ARCHIVED", |_| {}); });
+        let fixture = self.clone();
+        builder.model("User", move |m| {
+            let mut posts = Relation::new("posts");
+            posts.set_model("Post".to_owned());
+            posts.set_cardinality(Cardinality::Many);
+            posts.set_fields(vec!["id".to_owned()]);
+            posts.set_references(vec!["authorId".to_owned()]);
+            m.field(id_field()).relation(posts);
+            if let Some(counter_type) = &fixture.counter_cache {
+                let mut posts_count = Field::new("postsCount".to_owned());
+                posts_count.field_type = Some(counter_type.clone());
+                m.field(posts_count);
+            }
+            if fixture.soft_delete {
+                m.field(optional_field("deletedAt", FieldType::DateTime)).soft_delete("deletedAt");
+            }
+        });
+        let fixture = self;
+        builder.model("Post", move |m| {
+            let mut author_id = optional_field("authorId", FieldType::I32);
+            author_id.foreign_key = true;
+            let mut author = Relation::new("author");
+            author.set_model("User".to_owned());
+            author.set_optional();
+            author.set_fields(vec!["authorId".to_owned()]);
+            author.set_references(vec!["id".to_owned()]);
+            author.set_delete_rule(fixture.delete_rule);
+            if fixture.counter_cache.is_some() {
+                author.set_counter_cache("postsCount".to_owned());
+            }
+            m.field(id_field())
+                .field(author_id)
+                .field(optional_field("title", FieldType::String))
+                .field(optional_field("status", FieldType::Enum("Status".to_owned())))
+                .field(optional_field("publishedAt", FieldType::DateTime))
+                .relation(author);
+            if fixture.soft_delete {
+                m.field(optional_field("deletedAt", FieldType::DateTime)).soft_delete("deletedAt");
+            }
+        });
+        builder.build(connector).await
+    }
+}
+
+/// Users and posts without soft deletion or counter caches.
+pub(crate) async fn users_and_posts(connector: Arc<TestConnector>) -> Graph {
+    UsersAndPosts::default().build(connector).await
+}
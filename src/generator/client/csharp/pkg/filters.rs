@@ -92,6 +92,22 @@ namespace Teo {{
         }}
     }}
 
+    public class JsonFilter {{
+        public new System.Text.Json.JsonElement? Equals {{ get; set; }}
+
+        public JsonFilter(System.Text.Json.JsonElement? equals = null) {{
+            Equals = equals;
+        }}
+    }}
+
+    public class JsonNullableFilter {{
+        public new Optional<System.Text.Json.JsonElement>? Equals {{ get; set; }}
+
+        public JsonNullableFilter(Optional<System.Text.Json.JsonElement>? equals = null) {{
+            Equals = equals;
+        }}
+    }}
+
     public class NumberFilter<T> where T: struct {{
         public new T? Equals {{ get; set; }}
         public T[]? In {{ get; set; }}
@@ -166,6 +182,7 @@ namespace Teo {{
         public string? StartsWith {{ get; set; }}
         public string? EndsWith {{ get; set; }}
         public string? Matches {{ get; set; }}
+        public string? Search {{ get; set; }}
         public OneOf<string, StringFilter>? Not {{ get; set; }}
 
         public StringFilter(
@@ -180,6 +197,7 @@ namespace Teo {{
             string? startsWith = null,
             string? endsWith = null,
             string? matches = null,
+            string? search = null,
             OneOf<string, StringFilter>? not = null
         ) {{
             Equals = equals;
@@ -193,6 +211,7 @@ namespace Teo {{
             StartsWith = startsWith;
             EndsWith = endsWith;
             Matches = matches;
+            Search = search;
             Not = not;
         }}
     }}
@@ -209,6 +228,7 @@ namespace Teo {{
         public string? StartsWith {{ get; set; }}
         public string? EndsWith {{ get; set; }}
         public string? Matches {{ get; set; }}
+        public string? Search {{ get; set; }}
         public OneOf<Optional<string>, StringNullableFilter>? Not {{ get; set; }}
 
         public StringNullableFilter(
@@ -223,6 +243,7 @@ namespace Teo {{
             string? startsWith = null,
             string? endsWith = null,
             string? matches = null,
+            string? search = null,
             OneOf<Optional<string>, StringNullableFilter>? not = null
         ) {{
             Equals = equals;
@@ -236,6 +257,7 @@ namespace Teo {{
             StartsWith = startsWith;
             EndsWith = endsWith;
             Matches = matches;
+            Search = search;
             Not = not;
         }}
     }}
@@ -1,5 +1,5 @@
 use inflector::Inflector;
-use crate::core::action::{Action, FIND_MANY_HANDLER, FIND_FIRST_HANDLER, FIND_UNIQUE_HANDLER, ResData, UPSERT_HANDLER, AGGREGATE_HANDLER};
+use crate::core::action::{Action, FIND_MANY_HANDLER, FIND_FIRST_HANDLER, FIND_UNIQUE_HANDLER, ResData, UPSERT_HANDLER, AGGREGATE_HANDLER, FACETS_HANDLER};
 use crate::generator::lib::cases::{field_description, field_localized_name, model_api_object_description, model_localized_name, model_localized_name_word_case, relation_description, relation_localized_name};
 use crate::core::field::Field;
 use crate::core::graph::Graph;
@@ -61,6 +61,7 @@ pub(crate) fn action_and_model(r#type: Action, model: &Model) -> String {
         FIND_FIRST_HANDLER | FIND_UNIQUE_HANDLER => "find".to_owned(),
         UPSERT_HANDLER => "create or update".to_owned(),
         AGGREGATE_HANDLER => "aggregate on".to_owned(),
+        FACETS_HANDLER => "count values of".to_owned(),
         _ => r#type.as_handler_str().to_word_case(),
     };
     let object = match r#type.handler_res_data() {
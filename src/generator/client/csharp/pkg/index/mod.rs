@@ -27,6 +27,16 @@ fn escape(before: impl AsRef<str>) -> String {
     }
 }
 
+/// The JSON key of a property when it isn't the camel cased property name
+/// the serializer writes by default.
+fn json_key(api_key: &str) -> Option<String> {
+    if api_key.to_pascal_case().to_camel_case() == api_key {
+        None
+    } else {
+        Some(api_key.to_owned())
+    }
+}
+
 struct CSharpClassField {
     n: String,
     t: String,
@@ -190,7 +200,7 @@ fn generate_model_create_input(graph: &Graph, model: &Model, without: Option<&st
     let mut class_fields = Vec::<CSharpClassField>::new();
     model.input_keys().iter().for_each(|k| {
         if let Some(field) = model.field(k) {
-            let field_name = &field.name;
+            let field_name = graph.field_naming().api_key(field.api_name());
             let field_cs_type = field.field_type().to_csharp_type(false);
             let ignore_this_field = if let Some(without_relation) = without_relation {
                 without_relation.fields().contains(k)
@@ -203,11 +213,11 @@ fn generate_model_create_input(graph: &Graph, model: &Model, without: Option<&st
                     t: field_cs_type,
                     o: field.optionality.is_optional(),
                     d: Some(field_doc(field)),
-                    j: None
+                    j: json_key(&field_name)
                 });
             }
         } else if let Some(relation) = model.relation(k) {
-            let relation_name = relation.name();
+            let relation_name = graph.field_naming().api_key(relation.name());
             let relation_model_name = relation.model();
             let relation_model = graph.model(relation_model_name).unwrap();
             let num = if relation.is_vec() { "Many" } else { "One" };
@@ -230,7 +240,7 @@ fn generate_model_create_input(graph: &Graph, model: &Model, without: Option<&st
                     t: format!("{relation_model_name}CreateNested{num}{without}Input"),
                     o: true,
                     d: Some(relation_doc(relation)),
-                    j: None
+                    j: json_key(&relation_name)
                 });
             }
         }
@@ -456,7 +466,7 @@ fn generate_model_update_input(graph: &Graph, model: &Model, without: Option<&st
     let mut class_fields = Vec::<CSharpClassField>::new();
     model.input_keys().iter().for_each(|k| {
         if let Some(field) = model.field(k) {
-            let field_name = &field.name;
+            let field_name = graph.field_naming().api_key(field.api_name());
             let field_cs_type = field.field_type().to_csharp_update_input_type(field.optionality.is_optional(), true);
             let ignore_this_field = if let Some(without_relation) = without_relation {
                 without_relation.fields().contains(k)
@@ -469,11 +479,11 @@ fn generate_model_update_input(graph: &Graph, model: &Model, without: Option<&st
                     t: field_cs_type,
                     o: true,
                     d: Some(field_doc(field)),
-                    j: None
+                    j: json_key(&field_name)
                 });
             }
         } else if let Some(relation) = model.relation(k) {
-            let relation_name = relation.name();
+            let relation_name = graph.field_naming().api_key(relation.name());
             let relation_model_name = relation.model();
             let relation_model = graph.model(relation_model_name).unwrap();
             let num = if relation.is_vec() { "Many" } else { "One" };
@@ -496,7 +506,7 @@ fn generate_model_update_input(graph: &Graph, model: &Model, without: Option<&st
                     t: format!("{relation_model_name}UpdateNested{num}{without}Input"),
                     o: true,
                     d: Some(relation_doc(relation)),
-                    j: None
+                    j: json_key(&relation_name)
                 });
             }
         }
@@ -510,7 +520,7 @@ fn generate_model_update_input(graph: &Graph, model: &Model, without: Option<&st
     builder.build()
 }
 
-fn generate_model_credentials_input(model: &Model) -> String {
+fn generate_model_credentials_input(graph: &Graph, model: &Model) -> String {
     let model_name = model.name();
     let _class_name = format!("{model_name}CredentialsInput");
     let mut class_fields = Vec::<CSharpClassField>::new();
@@ -520,26 +530,26 @@ fn generate_model_credentials_input(model: &Model) -> String {
     let auth_by_keys_optional = auth_by_keys.len() != 1;
     for key in auth_identity_keys {
         let field = model.field(key).unwrap();
-        let field_name = &field.name;
+        let field_name = graph.field_naming().api_key(field.api_name());
         let field_type = field.field_type().to_csharp_type(auth_identity_optional);
         class_fields.push(CSharpClassField {
             n: field_name.to_pascal_case(),
             t: field_type,
             o: auth_identity_optional,
             d: Some(field_doc(field)),
-            j: None
+            j: json_key(&field_name)
         });
     }
     for key in auth_by_keys {
         let field = model.field(key).unwrap();
-        let field_name = &field.name;
+        let field_name = graph.field_naming().api_key(field.api_name());
         let field_type = field.field_type().to_csharp_type(auth_by_keys_optional);
         class_fields.push(CSharpClassField {
             n: field_name.to_pascal_case(),
             t: field_type,
             o: auth_by_keys_optional,
             d: Some(field_doc(field)),
-            j: None
+            j: json_key(&field_name)
         });
     }
     let builder = CSharpClassBuilder {
@@ -578,25 +588,25 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                 let mut model_fields = Vec::<CSharpClassField>::new();
                 m.output_keys().iter().for_each(|k| {
                     if let Some(field) = m.field(k) {
-                        let field_name = field.name.to_pascal_case();
+                        let field_name = graph.field_naming().api_key(field.api_name());
                         let field_type = field.field_type().to_csharp_type(false);
                         model_fields.push(CSharpClassField {
-                            n: field_name,
+                            n: field_name.to_pascal_case(),
                             t: field_type,
                             o: true,
                             d: Some(field_doc(field)),
-                            j: None
+                            j: json_key(&field_name)
                         });
                     } else if let Some(relation) = m.relation(k) {
-                        let relation_name = relation.name().to_pascal_case();
+                        let relation_name = graph.field_naming().api_key(relation.name());
                         let relation_type = relation.model();
                         let array = if relation.is_vec() { "[]" } else { "" };
                         model_fields.push(CSharpClassField {
-                            n: relation_name,
+                            n: relation_name.to_pascal_case(),
                             t: format!("{relation_type}{array}"),
                             o: true,
                             d: Some(relation_doc(relation)),
-                            j: None
+                            j: json_key(&relation_name)
                         });
                     }
                 });
@@ -616,13 +626,13 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                 let mut select_fields = Vec::<CSharpClassField>::new();
                 m.output_keys().iter().for_each(|k| {
                     if let Some(field) = m.field(k) {
-                        let field_name = &field.name;
+                        let field_name = graph.field_naming().api_key(field.api_name());
                         select_fields.push(CSharpClassField {
                             n: field_name.to_pascal_case(),
                             t: "bool".to_string(),
                             o: true,
                             d: Some(field_doc(field)),
-                            j: None
+                            j: json_key(&field_name)
                         });
                     }
                 });
@@ -636,7 +646,7 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                 // include
                 let mut include_fields = Vec::<CSharpClassField>::new();
                 for relation in m.relations() {
-                    let name = relation.name();
+                    let name = graph.field_naming().api_key(relation.name());
                     let is_vec = relation.is_vec();
                     let find_many = if is_vec { "FindMany" } else { "" };
                     let r_model = relation.model();
@@ -645,7 +655,7 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                         t: format!("OneOf<bool, {r_model}{find_many}Args>"),
                         o: true,
                         d: Some(relation_doc(relation)),
-                        j: None
+                        j: json_key(&name)
                     });
                 }
                 let builder = CSharpClassBuilder {
@@ -681,25 +691,25 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                 ];
                 m.query_keys().iter().for_each(|k| {
                     if let Some(field) = m.field(k) {
-                        let field_name = &field.name;
+                        let field_name = graph.field_naming().api_key(field.api_name());
                         let field_filter = field.field_type().to_csharp_filter_type(field.optionality.is_optional());
                         where_fields.push(CSharpClassField {
                             n: field_name.to_pascal_case(),
                             t: field_filter,
                             o: true,
                             d: Some(field_doc(field)),
-                            j: None,
+                            j: json_key(&field_name),
                         });
                     } else if let Some(relation) = m.relation(k) {
                         let list = if relation.is_vec() { "List" } else { "" };
-                        let relation_name = relation.name();
+                        let relation_name = graph.field_naming().api_key(relation.name());
                         let relation_model = relation.model();
                         where_fields.push(CSharpClassField {
                             n: relation_name.to_pascal_case(),
                             t: format!("{relation_model}{list}RelationFilter"),
                             o: true,
                             d: Some(relation_doc(relation)),
-                            j: None,
+                            j: json_key(&relation_name),
                         });
                     }
                 });
@@ -719,13 +729,13 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                             if !used_where_unique_field_names.contains(&&***&&item.field_name()) {
                                 if let Some(field) = m.field(&item.field_name()) {
                                     let cs_type = field.field_type().to_csharp_type(false);
-                                    let field_name = &item.field_name();
+                                    let field_name = graph.field_naming().api_key(field.api_name());
                                     where_unique_fields.push(CSharpClassField {
                                         n: field_name.to_pascal_case(),
                                         t: cs_type,
                                         o: true,
                                         d: Some(field_doc(field)),
-                                        j: None
+                                        j: json_key(&field_name)
                                     });
                                 }
                                 used_where_unique_field_names.push(item.field_name());
@@ -797,15 +807,15 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                 c.indented(builder.build());
                 // order by
                 let mut order_by_fields = Vec::<CSharpClassField>::new();
-                m.query_keys().iter().for_each(|k| {
+                m.sort_keys().iter().for_each(|k| {
                     if let Some(field) = m.field(k) {
-                        let field_name = &field.name;
+                        let field_name = graph.field_naming().api_key(field.api_name());
                         order_by_fields.push(CSharpClassField {
                             n: field_name.to_pascal_case(),
                             t: "SortOrder".to_owned(),
                             o: true,
                             d: Some(field_doc(field)),
-                            j: None
+                            j: json_key(&field_name)
                         });
                     }
                 });
@@ -842,7 +852,7 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                     c.indented(generate_model_update_many_with_where_input(m, Some(r.name())));
                 });
                 if m.identity() {
-                    c.indented(generate_model_credentials_input(m));
+                    c.indented(generate_model_credentials_input(graph, m));
                 }
                 // action args
                 let builder = CSharpClassBuilder {
@@ -889,6 +899,22 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                             j: None,
                         });
                     }
+                    if a.handler_requires_field() {
+                        fields.push(CSharpClassField {
+                            n: "Where".to_owned(),
+                            t: format!("{model_name}WhereInput"),
+                            o: true,
+                            d: Some(where_doc(m)),
+                            j: None,
+                        });
+                        fields.push(CSharpClassField {
+                            n: "Field".to_owned(),
+                            t: "string".to_owned(),
+                            o: false,
+                            d: None,
+                            j: None,
+                        });
+                    }
                     fields.push(CSharpClassField {
                         n: "Select".to_owned(),
                         t: format!("{model_name}Select"),
@@ -947,6 +973,15 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                             j: None,
                         });
                     }
+                    if a.handler_allowed_input_json_keys().contains("_withMeta") {
+                        fields.push(CSharpClassField {
+                            n: "WithMeta".to_owned(),
+                            t: "bool".to_owned(),
+                            o: true,
+                            d: Some("Whether to count the records and return the page info in meta, `true` by default.".to_owned()),
+                            j: Some("_withMeta".to_owned()),
+                        });
+                    }
                     if a.handler_requires_create() {
                         fields.push(CSharpClassField {
                             n: "Create".to_owned(),
@@ -1005,7 +1040,7 @@ pub(crate) async fn generate_index_cs(graph: &Graph, _client: &ClientGeneratorCo
                                 let action_var_name = a.as_handler_str().to_pascal_case();
                                 let action_url_name = a.as_handler_str();
                                 let res_meta = match a.handler_res_meta() {
-                                    ResMeta::PagingInfo => "PagingInfo, ",
+                                    ResMeta::PagingInfo => "PagingInfo?, ",
                                     ResMeta::TokenInfo => "TokenInfo, ",
                                     ResMeta::NoMeta => "",
                                     ResMeta::Other => "",
@@ -76,6 +76,16 @@ namespace Teo {{
         public NullableBoolFieldUpdateOperationsInput(Optional<bool>? set) {{ Set = set; }}
     }}
 
+    public class JsonFieldUpdateOperationsInput {{
+        public System.Text.Json.JsonElement? Set {{ get; set; }}
+        public JsonFieldUpdateOperationsInput(System.Text.Json.JsonElement? set) {{ Set = set; }}
+    }}
+
+    public class NullableJsonFieldUpdateOperationsInput {{
+        public Optional<System.Text.Json.JsonElement>? Set {{ get; set; }}
+        public NullableJsonFieldUpdateOperationsInput(Optional<System.Text.Json.JsonElement>? set) {{ Set = set; }}
+    }}
+
     public class DateOnlyFieldUpdateOperationsInput {{
         public DateOnly? Set {{ get; set; }}
         public DateOnlyFieldUpdateOperationsInput(DateOnly? set) {{ Set = set; }}
@@ -27,6 +27,8 @@ namespace Teo {{
 
     public struct PagingInfo {{
         public uint Count {{ get; set; }}
+        public uint? PageSize {{ get; set; }}
+        public uint? PageNumber {{ get; set; }}
         public uint? NumberOfPages {{ get; set; }}
     }}
 
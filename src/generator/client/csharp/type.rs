@@ -60,6 +60,7 @@ impl ToCSharpType for FieldType {
             FieldType::HashMap(_) => panic!(),
             FieldType::BTreeMap(_) => panic!(),
             FieldType::Object(name) => name.to_string(),
+            FieldType::Json => "System.Text.Json.JsonElement".to_string(),
         };
         if optional {
             base + "?"
@@ -78,6 +79,7 @@ impl ToCSharpType for FieldType {
             FieldType::Date => one_of(base_type, format!("DateOnly{nullable}Filter")),
             FieldType::DateTime => one_of(base_type, format!("DateTime{nullable}Filter")),
             FieldType::Bool => one_of(base_type, format!("Bool{nullable}Filter")),
+            FieldType::Json => format!("Json{nullable}Filter"),
             FieldType::I32 | FieldType::I64 | FieldType::F32 | FieldType::F64 | FieldType::Decimal => {
                 let number_type = self.to_csharp_type(false);
                 one_of(base_type, format!("Number{nullable}Filter<{number_type}>"))
@@ -123,6 +125,7 @@ impl ToCSharpType for FieldType {
             FieldType::Date => format!("{prefix}DateOnlyFieldUpdateOperationsInput"),
             FieldType::DateTime => format!("{prefix}DateTimeFieldUpdateOperationsInput"),
             FieldType::Bool => format!("{prefix}BoolFieldUpdateOperationsInput"),
+            FieldType::Json => format!("{prefix}JsonFieldUpdateOperationsInput"),
             FieldType::I32 | FieldType::I64 | FieldType::F32 | FieldType::F64 | FieldType::Decimal => {
                 let number_type = self.to_csharp_type(false);
                 format!("{prefix}NumberFieldUpdateOperationsInput<{number_type}>")
@@ -1,10 +1,17 @@
 use async_trait::async_trait;
 use crate::core::app::conf::ClientGeneratorConf;
 use crate::generator::client::ClientGenerator;
+use crate::generator::client::dart::pkg::gitignore::generate_gitignore;
+use crate::generator::client::dart::pkg::lib::teo_dart::generate_teo_dart;
+use crate::generator::client::dart::pkg::pubspec_yaml::generate_pubspec_yaml;
+use crate::generator::client::dart::pkg::readme_md::generate_readme_md;
 use crate::generator::lib::generator::Generator;
 
 use crate::prelude::Graph;
 
+pub(crate) mod pkg;
+pub(crate) mod r#type;
+
 pub(crate) struct DartClientGenerator { }
 
 impl DartClientGenerator {
@@ -14,18 +21,24 @@ impl DartClientGenerator {
 #[async_trait]
 impl ClientGenerator for DartClientGenerator {
     fn module_directory_in_package(&self, _client: &ClientGeneratorConf) -> String {
-        todo!()
+        return "lib".to_owned()
     }
 
-    async fn generate_module_files(&self, _graph: &Graph, _client: &ClientGeneratorConf, _generator: &Generator) -> std::io::Result<()> {
-        todo!()
+    async fn generate_module_files(&self, graph: &Graph, client: &ClientGeneratorConf, generator: &Generator) -> std::io::Result<()> {
+        generator.ensure_root_directory().await?;
+        generator.clear_root_directory().await?;
+        generator.generate_file("teo.dart", generate_teo_dart(graph, client).await).await
     }
 
-    async fn generate_package_files(&self, _graph: &Graph, _client: &ClientGeneratorConf, _generator: &Generator) -> std::io::Result<()> {
-        todo!()
+    async fn generate_package_files(&self, graph: &Graph, _client: &ClientGeneratorConf, generator: &Generator) -> std::io::Result<()> {
+        generator.ensure_root_directory().await?;
+        generator.generate_file("README.md", generate_readme_md(graph).await).await?;
+        generator.generate_file(".gitignore", generate_gitignore(graph).await).await?;
+        generator.generate_file("pubspec.yaml", generate_pubspec_yaml(graph).await).await?;
+        Ok(())
     }
 
     async fn generate_main(&self, _graph: &Graph, _client: &ClientGeneratorConf, _generator: &Generator) -> std::io::Result<()> {
-        todo!()
+        Ok(())
     }
 }
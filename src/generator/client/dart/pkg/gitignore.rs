@@ -0,0 +1,10 @@
+use crate::core::graph::Graph;
+
+
+pub(crate) async fn generate_gitignore(_graph: &Graph) -> String {
+    format!(r#".dart_tool/
+.packages
+build/
+pubspec.lock
+"#)
+}
@@ -0,0 +1,177 @@
+use inflector::Inflector;
+use itertools::Itertools;
+use crate::core::action::{Action, ResMeta};
+use crate::core::app::conf::ClientGeneratorConf;
+use crate::core::field::Field;
+use crate::core::field::r#type::{FieldType, FieldTypeOwner};
+use crate::core::graph::Graph;
+use crate::core::model::Model;
+use crate::generator::client::dart::r#type::ToDartType;
+use crate::generator::lib::code::Code;
+use crate::parser::ast::config::{DateTimeFormat, FieldNaming};
+
+static ESCAPE_LIST: [&str; 33] = [
+    "assert", "break", "case", "catch", "class", "const", "continue", "default", "do", "else", "enum", "extends",
+    "false", "final", "finally", "for", "if", "in", "is", "new", "null", "rethrow", "return", "super", "switch",
+    "this", "throw", "true", "try", "var", "void", "while", "with",
+];
+
+/// Dart has no escaped identifiers, reserved words get a trailing underscore.
+fn escape(before: impl AsRef<str>) -> String {
+    let before = before.as_ref();
+    if ESCAPE_LIST.contains(&before) {
+        format!("{before}_")
+    } else {
+        before.to_string()
+    }
+}
+
+/// A model class, any field may be left out of a response by select.
+fn generate_model_class(c: &mut Code, model: &Model, naming: FieldNaming, date_time_format: &DateTimeFormat) {
+    let model_name = model.name();
+    let mut fields: Vec<(String, FieldType)> = model.output_keys().iter().filter_map(|k| model.field(k)).map(|f| (naming.api_key(f.api_name()).into_owned(), f.field_type().clone())).collect();
+    for relation in model.output_keys().iter().filter_map(|k| model.relation(k)) {
+        let object = FieldType::Object(relation.model().to_owned());
+        let field_type = if relation.is_vec() {
+            let mut internal = Field::new("".to_owned());
+            internal.field_type = Some(object);
+            FieldType::Vec(Box::new(internal))
+        } else {
+            object
+        };
+        fields.push((naming.api_key(relation.name()).into_owned(), field_type));
+    }
+    c.block(format!("class {model_name} {{"), |b| {
+        for (name, field_type) in &fields {
+            b.line(format!("final {} {};", field_type.to_dart_type(true, date_time_format), escape(name)));
+        }
+        b.empty_line();
+        if fields.is_empty() {
+            b.line(format!("const {model_name}();"));
+        } else {
+            b.line(format!("const {model_name}({{{}}});", fields.iter().map(|(n, _)| format!("this.{}", escape(n))).join(", ")));
+        }
+        b.empty_line();
+        b.block(format!("factory {model_name}.fromJson(Map<String, dynamic> json) => {model_name}("), |b| {
+            for (name, field_type) in &fields {
+                b.line(format!("{}: {},", escape(name), field_type.to_dart_from_json(&format!("json['{name}']"), date_time_format)));
+            }
+        }, ");");
+        b.empty_line();
+        b.block("Map<String, dynamic> toJson() => {", |b| {
+            for (name, field_type) in &fields {
+                let property = escape(name);
+                b.line(format!("if ({property} != null) '{name}': {},", field_type.to_dart_to_json(&property, true, date_time_format)));
+            }
+        }, "};");
+    }, "}");
+    c.empty_line();
+}
+
+fn generate_model_delegate(c: &mut Code, model: &Model) {
+    let model_name = model.name();
+    let url_segment_name = model.url_segment_name();
+    c.block(format!("class {model_name}Delegate {{"), |b| {
+        b.line("final Teo _teo;");
+        b.empty_line();
+        b.line(format!("{model_name}Delegate._(this._teo);"));
+        for action in Action::handlers_iter() {
+            if !model.has_action(*action) { continue }
+            let action_name = action.as_handler_str();
+            b.empty_line();
+            match action.handler_res_meta() {
+                // the paging info in `meta` is skipped by `withMeta: false`
+                ResMeta::PagingInfo => b.line(format!("Future<Map<String, dynamic>> {action_name}([Map<String, dynamic> args = const {{}}, bool withMeta = true]) => _teo._request('{url_segment_name}', '{action_name}', {{...args, '_withMeta': withMeta}});")),
+                _ => b.line(format!("Future<Map<String, dynamic>> {action_name}([Map<String, dynamic> args = const {{}}]) => _teo._request('{url_segment_name}', '{action_name}', args);")),
+            }
+        }
+    }, "}");
+    c.empty_line();
+}
+
+pub(crate) async fn generate_teo_dart(graph: &Graph, client: &ClientGeneratorConf) -> String {
+    let base_url = client.base_url();
+    Code::new(0, 2, |c| {
+        c.line("import 'dart:convert';");
+        c.line("import 'package:http/http.dart' as http;");
+        c.empty_line();
+        c.block("class TeoException implements Exception {", |b| {
+            b.line("final Map<String, dynamic> error;");
+            b.empty_line();
+            b.line("TeoException(this.error);");
+            b.empty_line();
+            b.line("@override");
+            b.line("String toString() => 'TeoException: ${error['message']}';");
+        }, "}");
+        c.empty_line();
+        c.block("class PagingInfo {", |b| {
+            b.line("final int count;");
+            b.line("final int? pageSize;");
+            b.line("final int? pageNumber;");
+            b.line("final int? numberOfPages;");
+            b.empty_line();
+            b.line("const PagingInfo({required this.count, this.pageSize, this.pageNumber, this.numberOfPages});");
+            b.empty_line();
+            b.block("factory PagingInfo.fromJson(Map<String, dynamic> json) => PagingInfo(", |b| {
+                b.line("count: json['count'] as int,");
+                for key in ["pageSize", "pageNumber", "numberOfPages"] {
+                    b.line(format!("{key}: json['{key}'] as int?,"));
+                }
+            }, ");");
+        }, "}");
+        c.empty_line();
+        // enum definitions
+        graph.enums().iter().sorted_by(|a, b| a.0.cmp(b.0)).for_each(|(name, e)| {
+            // choices keep their JSON value, their names may be escaped
+            c.block(format!("enum {name} {{"), |b| {
+                b.line(format!("{};", e.values().iter().map(|v| format!("{}('{v}')", escape(v))).join(", ")));
+                b.empty_line();
+                b.line("final String value;");
+                b.line(format!("const {name}(this.value);"));
+                b.empty_line();
+                b.line(format!("static {name} fromJson(String value) => values.firstWhere((e) => e.value == value);"));
+            }, "}");
+            c.empty_line();
+        });
+        // model definitions
+        graph.models().iter().for_each(|m| generate_model_class(c, m, graph.field_naming(), &client.date_time_format));
+        // delegates
+        graph.models().iter().filter(|m| m.actions().len() > 0).for_each(|m| generate_model_delegate(c, m));
+        // main class
+        c.block("class Teo {", |b| {
+            b.line("final String baseUrl;");
+            b.line("String? token;");
+            b.empty_line();
+            graph.models().iter().filter(|m| m.actions().len() > 0).for_each(|m| {
+                let model_name = m.name();
+                b.line(format!("late final {model_name}Delegate {} = {model_name}Delegate._(this);", model_name.to_camel_case()));
+            });
+            b.empty_line();
+            b.line(format!("Teo({{this.baseUrl = '{base_url}', this.token}});"));
+            b.empty_line();
+            b.block("Future<Map<String, dynamic>> _request(String urlSegmentName, String action, Map<String, dynamic> args) async {", |b| {
+                b.block("final response = await http.post(", |b| {
+                    b.line("Uri.parse('$baseUrl/$urlSegmentName/action/$action'),");
+                    b.line("headers: {'Content-Type': 'application/json', if (token != null) 'Authorization': 'Bearer $token'},");
+                    b.line("body: jsonEncode(args),");
+                }, ");");
+                b.line("final body = jsonDecode(response.body) as Map<String, dynamic>;");
+                b.line("if (response.statusCode >= 400) throw TeoException(body['error'] as Map<String, dynamic>? ?? body);");
+                b.line("return body;");
+            }, "}");
+        }, "}");
+    }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dart_reserved_words_are_escaped() {
+        for keyword in ["class", "default", "is", "in"] {
+            assert_eq!(escape(keyword), format!("{keyword}_"));
+        }
+        assert_eq!(escape("name"), "name");
+    }
+}
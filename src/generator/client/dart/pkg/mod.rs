@@ -0,0 +1,4 @@
+pub(crate) mod gitignore;
+pub(crate) mod lib;
+pub(crate) mod pubspec_yaml;
+pub(crate) mod readme_md;
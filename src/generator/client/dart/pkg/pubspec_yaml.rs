@@ -0,0 +1,16 @@
+use crate::core::graph::Graph;
+
+
+pub(crate) async fn generate_pubspec_yaml(_graph: &Graph) -> String {
+    format!(r#"name: teo
+description: Generated by TEO.
+version: 0.0.1
+publish_to: none
+
+environment:
+  sdk: ">=2.17.0 <4.0.0"
+
+dependencies:
+  http: ^1.1.0
+"#)
+}
@@ -0,0 +1,121 @@
+use crate::core::field::r#type::{FieldType, FieldTypeOwner};
+use crate::parser::ast::config::DateTimeFormat;
+
+/// Date times are sent as `{"$date": ..}` in the server's date time format,
+/// custom formats can't be parsed back reliably, they are kept as strings.
+pub(crate) trait ToDartType {
+    fn to_dart_type(&self, optional: bool, format: &DateTimeFormat) -> String;
+    fn to_dart_from_json(&self, value: &str, format: &DateTimeFormat) -> String;
+    fn to_dart_to_json(&self, value: &str, optional: bool, format: &DateTimeFormat) -> String;
+}
+
+impl ToDartType for FieldType {
+    fn to_dart_type(&self, optional: bool, format: &DateTimeFormat) -> String {
+        let base: String = match self {
+            #[cfg(feature = "data-source-mongodb")]
+            FieldType::ObjectId => "String".to_string(),
+            FieldType::String => "String".to_string(),
+            FieldType::Bool => "bool".to_string(),
+            FieldType::I32 | FieldType::I64 => "int".to_string(),
+            FieldType::F32 | FieldType::F64 => "double".to_string(),
+            FieldType::Decimal => "String".to_string(),
+            FieldType::Date => "DateTime".to_string(),
+            FieldType::DateTime => match format {
+                DateTimeFormat::Custom(_) => "String".to_string(),
+                _ => "DateTime".to_string(),
+            },
+            FieldType::Enum(name) => name.to_string(),
+            FieldType::Vec(internal) => format!("List<{}>", internal.field_type().to_dart_type(false, format)),
+            FieldType::HashMap(internal) | FieldType::BTreeMap(internal) => format!("Map<String, {}>", internal.field_type().to_dart_type(false, format)),
+            FieldType::Object(name) => name.to_string(),
+            FieldType::Json => "dynamic".to_string(),
+        };
+        if optional && base != "dynamic" {
+            base + "?"
+        } else {
+            base
+        }
+    }
+
+    /// A nullable Dart expression decoding the JSON `value`.
+    fn to_dart_from_json(&self, value: &str, format: &DateTimeFormat) -> String {
+        match self {
+            FieldType::I32 | FieldType::I64 => format!("({value} as num?)?.toInt()"),
+            FieldType::F32 | FieldType::F64 => format!("({value} as num?)?.toDouble()"),
+            FieldType::Decimal => format!("{value} == null ? null : {value}['\\$decimal'] as String"),
+            FieldType::Date => format!("{value} == null ? null : DateTime.parse({value} as String)"),
+            FieldType::DateTime => match format {
+                DateTimeFormat::RFC3339 => format!("{value} == null ? null : DateTime.parse({value}['\\$date'] as String)"),
+                DateTimeFormat::EpochMillis => format!("{value} == null ? null : DateTime.fromMillisecondsSinceEpoch({value}['\\$date'] as int, isUtc: true)"),
+                DateTimeFormat::Custom(_) => format!("{value} == null ? null : {value}['\\$date'] as String"),
+            },
+            FieldType::Enum(name) => format!("{value} == null ? null : {name}.fromJson({value} as String)"),
+            FieldType::Object(name) => format!("{value} == null ? null : {name}.fromJson({value} as Map<String, dynamic>)"),
+            FieldType::Vec(internal) => format!("({value} as List?)?.map((e) => ({})!).toList()", internal.field_type().to_dart_from_json("e", format)),
+            FieldType::HashMap(internal) | FieldType::BTreeMap(internal) => format!("({value} as Map<String, dynamic>?)?.map((k, e) => MapEntry(k, ({})!))", internal.field_type().to_dart_from_json("e", format)),
+            FieldType::Json => value.to_string(),
+            _ => format!("{value} as {}", self.to_dart_type(true, format)),
+        }
+    }
+
+    /// A Dart expression encoding `value` into JSON.
+    fn to_dart_to_json(&self, value: &str, optional: bool, format: &DateTimeFormat) -> String {
+        let dot = if optional { "?." } else { "." };
+        match self {
+            FieldType::Date => format!("{value}{dot}toIso8601String(){dot}substring(0, 10)"),
+            FieldType::DateTime => match format {
+                DateTimeFormat::RFC3339 => format!("{value}{dot}toUtc(){dot}toIso8601String()"),
+                DateTimeFormat::EpochMillis => if optional {
+                    format!("{value} == null ? null : {{'\\$date': {value}!.millisecondsSinceEpoch}}")
+                } else {
+                    format!("{{'\\$date': {value}.millisecondsSinceEpoch}}")
+                },
+                DateTimeFormat::Custom(_) => value.to_string(),
+            },
+            FieldType::Enum(_) => format!("{value}{dot}value"),
+            FieldType::Object(_) => format!("{value}{dot}toJson()"),
+            FieldType::Vec(internal) => format!("{value}{dot}map((e) => {}).toList()", internal.field_type().to_dart_to_json("e", false, format)),
+            FieldType::HashMap(internal) | FieldType::BTreeMap(internal) => format!("{value}{dot}map((k, e) => MapEntry(k, {}))", internal.field_type().to_dart_to_json("e", false, format)),
+            _ => value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::field::Field;
+    use super::*;
+
+    #[test]
+    fn field_types_map_to_dart_types() {
+        let format = DateTimeFormat::RFC3339;
+        assert_eq!(FieldType::I64.to_dart_type(false, &format), "int");
+        assert_eq!(FieldType::F32.to_dart_type(true, &format), "double?");
+        assert_eq!(FieldType::Date.to_dart_type(false, &format), "DateTime");
+        assert_eq!(FieldType::Date.to_dart_from_json("json['bornOn']", &format), "json['bornOn'] == null ? null : DateTime.parse(json['bornOn'] as String)");
+        let mut internal = Field::new("".to_owned());
+        internal.field_type = Some(FieldType::I32);
+        let list = FieldType::Vec(Box::new(internal));
+        assert_eq!(list.to_dart_type(true, &format), "List<int>?");
+        assert_eq!(list.to_dart_from_json("v", &format), "(v as List?)?.map((e) => ((e as num?)?.toInt())!).toList()");
+    }
+
+    #[test]
+    fn date_times_and_decimals_are_unwrapped() {
+        let value = "json['createdAt']";
+        assert_eq!(FieldType::DateTime.to_dart_from_json(value, &DateTimeFormat::RFC3339), "json['createdAt'] == null ? null : DateTime.parse(json['createdAt']['\\$date'] as String)");
+        assert_eq!(FieldType::DateTime.to_dart_from_json(value, &DateTimeFormat::EpochMillis), "json['createdAt'] == null ? null : DateTime.fromMillisecondsSinceEpoch(json['createdAt']['\\$date'] as int, isUtc: true)");
+        let custom = DateTimeFormat::Custom("%Y/%m/%d".to_owned());
+        assert_eq!(FieldType::DateTime.to_dart_type(true, &custom), "String?");
+        assert_eq!(FieldType::DateTime.to_dart_from_json(value, &custom), "json['createdAt'] == null ? null : json['createdAt']['\\$date'] as String");
+        assert_eq!(FieldType::Decimal.to_dart_from_json("v", &custom), "v == null ? null : v['\\$decimal'] as String");
+    }
+
+    #[test]
+    fn epoch_millis_date_times_are_sent_as_millis() {
+        let format = DateTimeFormat::EpochMillis;
+        assert_eq!(FieldType::DateTime.to_dart_to_json("createdAt", true, &format), "createdAt == null ? null : {'\\$date': createdAt!.millisecondsSinceEpoch}");
+        assert_eq!(FieldType::DateTime.to_dart_to_json("e", false, &format), "{'\\$date': e.millisecondsSinceEpoch}");
+        assert_eq!(FieldType::DateTime.to_dart_to_json("createdAt", true, &DateTimeFormat::RFC3339), "createdAt?.toUtc()?.toIso8601String()");
+    }
+}
@@ -1,10 +1,17 @@
 use async_trait::async_trait;
 use crate::core::app::conf::ClientGeneratorConf;
 use crate::generator::client::ClientGenerator;
+use crate::generator::client::kotlin::pkg::build_gradle_kts::generate_build_gradle_kts;
+use crate::generator::client::kotlin::pkg::gitignore::generate_gitignore;
+use crate::generator::client::kotlin::pkg::readme_md::generate_readme_md;
+use crate::generator::client::kotlin::pkg::src::teo_kt::generate_teo_kt;
 use crate::generator::lib::generator::Generator;
 
 use crate::prelude::Graph;
 
+pub(crate) mod pkg;
+pub(crate) mod r#type;
+
 pub(crate) struct KotlinClientGenerator { }
 
 impl KotlinClientGenerator {
@@ -14,18 +21,24 @@ impl KotlinClientGenerator {
 #[async_trait]
 impl ClientGenerator for KotlinClientGenerator {
     fn module_directory_in_package(&self, _client: &ClientGeneratorConf) -> String {
-        todo!()
+        return "src/main/kotlin/teo".to_owned()
     }
 
-    async fn generate_module_files(&self, _graph: &Graph, _client: &ClientGeneratorConf, _generator: &Generator) -> std::io::Result<()> {
-        todo!()
+    async fn generate_module_files(&self, graph: &Graph, client: &ClientGeneratorConf, generator: &Generator) -> std::io::Result<()> {
+        generator.ensure_root_directory().await?;
+        generator.clear_root_directory().await?;
+        generator.generate_file("Teo.kt", generate_teo_kt(graph, client).await).await
     }
 
-    async fn generate_package_files(&self, _graph: &Graph, _client: &ClientGeneratorConf, _generator: &Generator) -> std::io::Result<()> {
-        todo!()
+    async fn generate_package_files(&self, graph: &Graph, _client: &ClientGeneratorConf, generator: &Generator) -> std::io::Result<()> {
+        generator.ensure_root_directory().await?;
+        generator.generate_file("README.md", generate_readme_md(graph).await).await?;
+        generator.generate_file(".gitignore", generate_gitignore(graph).await).await?;
+        generator.generate_file("build.gradle.kts", generate_build_gradle_kts(graph).await).await?;
+        Ok(())
     }
 
     async fn generate_main(&self, _graph: &Graph, _client: &ClientGeneratorConf, _generator: &Generator) -> std::io::Result<()> {
-        todo!()
+        Ok(())
     }
 }
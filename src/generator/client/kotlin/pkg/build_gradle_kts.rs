@@ -0,0 +1,24 @@
+use crate::core::graph::Graph;
+
+
+pub(crate) async fn generate_build_gradle_kts(_graph: &Graph) -> String {
+    format!(r#"plugins {{
+    kotlin("jvm") version "1.8.21"
+    kotlin("plugin.serialization") version "1.8.21"
+}}
+
+group = "teo"
+version = "0.0.1"
+
+repositories {{
+    mavenCentral()
+}}
+
+dependencies {{
+    implementation("org.jetbrains.kotlinx:kotlinx-serialization-json:1.5.1")
+    implementation("org.jetbrains.kotlinx:kotlinx-datetime:0.4.0")
+    implementation("io.ktor:ktor-client-core:2.3.1")
+    implementation("io.ktor:ktor-client-cio:2.3.1")
+}}
+"#)
+}
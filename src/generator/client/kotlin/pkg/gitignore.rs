@@ -0,0 +1,11 @@
+use crate::core::graph::Graph;
+
+
+pub(crate) async fn generate_gitignore(_graph: &Graph) -> String {
+    format!(r#".gradle
+build/
+.idea/
+*.iml
+local.properties
+"#)
+}
@@ -0,0 +1,4 @@
+pub(crate) mod build_gradle_kts;
+pub(crate) mod gitignore;
+pub(crate) mod readme_md;
+pub(crate) mod src;
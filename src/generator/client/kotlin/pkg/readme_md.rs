@@ -0,0 +1,8 @@
+use crate::core::graph::Graph;
+
+
+pub(crate) async fn generate_readme_md(_graph: &Graph) -> String {
+    format!(r#"# TEO Kotlin Client
+This project is generated by TEO -- backend framework, Redefined.
+"#)
+}
@@ -0,0 +1,248 @@
+use inflector::Inflector;
+use itertools::Itertools;
+use crate::core::action::{Action, CREATE_HANDLER, DELETE_HANDLER, FIND_MANY_HANDLER, FIND_UNIQUE_HANDLER, UPDATE_HANDLER};
+use crate::core::app::conf::ClientGeneratorConf;
+use crate::core::field::r#type::FieldTypeOwner;
+use crate::core::graph::Graph;
+use crate::core::model::Model;
+use crate::generator::client::kotlin::r#type::ToKotlinType;
+use crate::generator::lib::code::Code;
+use crate::parser::ast::config::{DateTimeFormat, FieldNaming};
+
+static ESCAPE_LIST: [&str; 28] = [
+    "as", "break", "class", "continue", "do", "else", "false", "for", "fun", "if", "in", "interface", "is", "null",
+    "object", "package", "return", "super", "this", "throw", "true", "try", "typealias", "typeof", "val", "var",
+    "when", "while",
+];
+
+fn escape(before: impl AsRef<str>) -> String {
+    let before = before.as_ref();
+    if ESCAPE_LIST.contains(&before) {
+        format!("`{before}`")
+    } else {
+        before.to_string()
+    }
+}
+
+/// A serializable data class whose properties are nullable and omitted when null.
+fn data_class(c: &mut Code, name: &str, fields: Vec<(String, String)>, supertype: Option<&str>) {
+    let supertype = supertype.map_or("".to_owned(), |s| format!(" : {s}"));
+    c.line("@Serializable");
+    if fields.is_empty() {
+        c.line(format!("class {name}{supertype}"));
+    } else {
+        c.block(format!("data class {name}("), |b| {
+            for (field_name, field_type) in &fields {
+                b.line(format!("val {}: {field_type}? = null,", escape(field_name)));
+            }
+        }, format!("){supertype}"));
+    }
+    c.empty_line();
+}
+
+fn scalar_fields(model: &Model, keys: &Vec<String>, naming: FieldNaming) -> Vec<(String, String)> {
+    keys.iter().filter_map(|k| model.field(k)).map(|f| (naming.api_key(f.api_name()).into_owned(), f.field_type().to_kotlin_type(false))).collect()
+}
+
+fn generate_model_arguments(c: &mut Code, model: &Model, naming: FieldNaming) {
+    let model_name = model.name();
+    let select: Vec<(String, String)> = model.output_keys().iter().filter_map(|k| model.field(k)).map(|f| (naming.api_key(f.api_name()).into_owned(), "Boolean".to_owned())).collect();
+    data_class(c, &format!("{model_name}Select"), select, None);
+    let mut filter: Vec<(String, String)> = model.query_keys().iter().filter_map(|k| model.field(k)).map(|f| (naming.api_key(f.api_name()).into_owned(), format!("Filter<{}>", f.field_type().to_kotlin_type(false)))).collect();
+    filter.push(("AND".to_owned(), format!("List<{model_name}WhereInput>")));
+    filter.push(("OR".to_owned(), format!("List<{model_name}WhereInput>")));
+    filter.push(("NOT".to_owned(), format!("{model_name}WhereInput")));
+    data_class(c, &format!("{model_name}WhereInput"), filter, Some("WhereInput"));
+    let unique_keys: Vec<String> = model.unique_query_keys().iter().flatten().unique().sorted().cloned().collect();
+    data_class(c, &format!("{model_name}WhereUniqueInput"), scalar_fields(model, &unique_keys, naming), Some("WhereInput"));
+    let order_by: Vec<(String, String)> = model.sort_keys().iter().filter_map(|k| model.field(k)).map(|f| (naming.api_key(f.api_name()).into_owned(), "SortOrder".to_owned())).collect();
+    data_class(c, &format!("{model_name}OrderByInput"), order_by, None);
+    data_class(c, &format!("{model_name}CreateInput"), scalar_fields(model, model.input_keys(), naming), None);
+    data_class(c, &format!("{model_name}UpdateInput"), scalar_fields(model, model.input_keys(), naming), None);
+    let select = ("select".to_owned(), format!("{model_name}Select"));
+    let where_unique = ("where".to_owned(), format!("{model_name}WhereUniqueInput"));
+    data_class(c, &format!("{model_name}FindUniqueArgs"), vec![where_unique.clone(), select.clone()], None);
+    data_class(c, &format!("{model_name}FindManyArgs"), vec![
+        ("where".to_owned(), format!("{model_name}WhereInput")),
+        ("orderBy".to_owned(), format!("List<{model_name}OrderByInput>")),
+        ("skip".to_owned(), "Int".to_owned()),
+        ("take".to_owned(), "Int".to_owned()),
+        select.clone(),
+        ("_withMeta".to_owned(), "Boolean".to_owned()),
+    ], None);
+    data_class(c, &format!("{model_name}CreateArgs"), vec![("create".to_owned(), format!("{model_name}CreateInput")), select.clone()], None);
+    data_class(c, &format!("{model_name}UpdateArgs"), vec![where_unique.clone(), ("update".to_owned(), format!("{model_name}UpdateInput")), select.clone()], None);
+    data_class(c, &format!("{model_name}DeleteArgs"), vec![where_unique, select], None);
+}
+
+fn generate_model_delegate(c: &mut Code, model: &Model) {
+    let model_name = model.name();
+    // the server routes actions by the url segment name of a model
+    let url_segment_name = model.url_segment_name();
+    c.block(format!("class {model_name}Delegate internal constructor(private val teo: Teo) {{"), |b| {
+        for handler in [FIND_UNIQUE_HANDLER, FIND_MANY_HANDLER, CREATE_HANDLER, UPDATE_HANDLER, DELETE_HANDLER] {
+            let action = Action::from_u32(handler);
+            if !model.has_action(action) { continue }
+            let action_name = action.as_handler_str();
+            let args_name = format!("{model_name}{}Args", action_name.to_pascal_case());
+            b.block(format!("suspend fun {action_name}(args: {args_name}): JsonObject {{"), |b| {
+                b.line(format!(r#"return teo.request("{url_segment_name}", "{action_name}", Json.encodeToJsonElement(args))"#));
+            }, "}");
+        }
+    }, "}");
+    c.empty_line();
+}
+
+/// Date times are sent as `{"$date": ..}` in the server's date time format,
+/// custom formats can't be parsed back reliably, they are kept as strings.
+fn date_time_serializer(format: &DateTimeFormat) -> String {
+    let (value_type, decode) = match format {
+        DateTimeFormat::RFC3339 => ("Instant", "Instant.parse(date.content)"),
+        DateTimeFormat::EpochMillis => ("Instant", "Instant.fromEpochMilliseconds(date.long)"),
+        DateTimeFormat::Custom(_) => ("String", "date.content"),
+    };
+    format!(r#"typealias DateTime = @Serializable(with = DateTimeSerializer::class) {value_type}
+
+object DateTimeSerializer : KSerializer<{value_type}> {{
+    override val descriptor = PrimitiveSerialDescriptor("DateTime", PrimitiveKind.STRING)
+
+    override fun serialize(encoder: Encoder, value: {value_type}) = encoder.encodeString(value.toString())
+
+    override fun deserialize(decoder: Decoder): {value_type} {{
+        val date = (decoder as JsonDecoder).decodeJsonElement().jsonObject["\$date"]!!.jsonPrimitive
+        return {decode}
+    }}
+}}"#)
+}
+
+pub(crate) async fn generate_teo_kt(graph: &Graph, client: &ClientGeneratorConf) -> String {
+    let base_url = client.base_url();
+    Code::new(0, 4, |c| {
+        c.line("package teo");
+        c.empty_line();
+        c.line("import io.ktor.client.*");
+        c.line("import io.ktor.client.engine.cio.*");
+        c.line("import io.ktor.client.request.*");
+        c.line("import io.ktor.client.statement.*");
+        c.line("import io.ktor.http.*");
+        c.line("import java.math.BigDecimal");
+        c.line("import kotlinx.datetime.Instant");
+        c.line("import kotlinx.datetime.LocalDate");
+        c.line("import kotlinx.serialization.KSerializer");
+        c.line("import kotlinx.serialization.Serializable");
+        c.line("import kotlinx.serialization.descriptors.*");
+        c.line("import kotlinx.serialization.encoding.*");
+        c.line("import kotlinx.serialization.json.*");
+        c.empty_line();
+        c.indented(date_time_serializer(&client.date_time_format));
+        c.empty_line();
+        c.indented(DECIMAL_SERIALIZER);
+        c.empty_line();
+        c.block("enum class SortOrder {", |b| {
+            b.line("asc,");
+            b.line("desc,");
+        }, "}");
+        c.empty_line();
+        c.line("@Serializable");
+        c.block("data class Filter<T>(", |b| {
+            for operator in ["equals", "not", "lt", "lte", "gt", "gte"] {
+                b.line(format!("val {operator}: T? = null,"));
+            }
+            b.line("val `in`: List<T>? = null,");
+            b.line("val notIn: List<T>? = null,");
+            for operator in ["contains", "startsWith", "endsWith"] {
+                b.line(format!("val {operator}: String? = null,"));
+            }
+        }, ")");
+        c.empty_line();
+        c.line("sealed interface WhereInput");
+        c.empty_line();
+        // find many responses carry the paging info in `meta`
+        c.line("@Serializable");
+        c.block("data class PagingInfo(", |b| {
+            b.line("val count: Int,");
+            for key in ["pageSize", "pageNumber", "numberOfPages"] {
+                b.line(format!("val {key}: Int? = null,"));
+            }
+        }, ")");
+        c.empty_line();
+        c.line(r#"class TeoException(val error: JsonObject) : Exception(error["message"]?.jsonPrimitive?.content)"#);
+        c.empty_line();
+        // enum definitions
+        graph.enums().iter().sorted_by(|a, b| a.0.cmp(b.0)).for_each(|(name, e)| {
+            c.line("@Serializable");
+            c.block(format!("enum class {name} {{"), |b| {
+                for choice in e.values() {
+                    b.line(format!("{},", escape(choice)));
+                }
+            }, "}");
+            c.empty_line();
+        });
+        // model definitions, any field may be left out by select
+        graph.models().iter().for_each(|m| {
+            let mut fields = scalar_fields(m, m.output_keys(), graph.field_naming());
+            for relation in m.output_keys().iter().filter_map(|k| m.relation(k)) {
+                let relation_type = relation.model();
+                fields.push((graph.field_naming().api_key(relation.name()).into_owned(), if relation.is_vec() { format!("List<{relation_type}>") } else { relation_type.to_owned() }));
+            }
+            data_class(c, m.name(), fields, None);
+        });
+        // model input arguments
+        graph.models().iter().for_each(|m| generate_model_arguments(c, m, graph.field_naming()));
+        // delegates
+        graph.models().iter().filter(|m| m.actions().len() > 0).for_each(|m| generate_model_delegate(c, m));
+        // main class
+        c.block(format!(r#"class Teo(private val baseUrl: String = "{base_url}", var token: String? = null) {{"#), |b| {
+            b.line("private val client = HttpClient(CIO)");
+            b.empty_line();
+            graph.models().iter().filter(|m| m.actions().len() > 0).for_each(|m| {
+                let model_name = m.name();
+                b.line(format!("val {} = {model_name}Delegate(this)", m.name().to_camel_case()));
+            });
+            b.empty_line();
+            b.block("internal suspend fun request(urlSegmentName: String, action: String, args: JsonElement): JsonObject {", |b| {
+                b.block(r#"val response = client.post("$baseUrl/$urlSegmentName/action/$action") {"#, |b| {
+                    b.line("contentType(ContentType.Application.Json)");
+                    b.line(r#"token?.let { header(HttpHeaders.Authorization, "Bearer $it") }"#);
+                    b.line("setBody(args.toString())");
+                }, "}");
+                b.line("val body = Json.parseToJsonElement(response.bodyAsText()).jsonObject");
+                b.line(r#"if (response.status.value >= 400) throw TeoException(body["error"]?.jsonObject ?: body)"#);
+                b.line("return body");
+            }, "}");
+        }, "}");
+    }).to_string()
+}
+
+static DECIMAL_SERIALIZER: &str = r#"typealias Decimal = @Serializable(with = DecimalSerializer::class) BigDecimal
+
+object DecimalSerializer : KSerializer<BigDecimal> {
+    override val descriptor = PrimitiveSerialDescriptor("Decimal", PrimitiveKind.STRING)
+
+    override fun serialize(encoder: Encoder, value: BigDecimal) = encoder.encodeString(value.toPlainString())
+
+    override fun deserialize(decoder: Decoder): BigDecimal {
+        val decimal = (decoder as JsonDecoder).decodeJsonElement().jsonObject["\$decimal"]!!.jsonPrimitive
+        return BigDecimal(decimal.content)
+    }
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kotlin_keywords_are_escaped() {
+        for keyword in ["object", "when", "typealias", "null", "this"] {
+            assert_eq!(escape(keyword), format!("`{keyword}`"));
+        }
+        assert_eq!(escape("name"), "name");
+    }
+
+    #[test]
+    fn date_times_are_deserialized_in_the_server_format() {
+        assert!(date_time_serializer(&DateTimeFormat::RFC3339).contains("Instant.parse(date.content)"));
+        assert!(date_time_serializer(&DateTimeFormat::EpochMillis).contains("Instant.fromEpochMilliseconds(date.long)"));
+        assert!(date_time_serializer(&DateTimeFormat::Custom("%Y".to_owned())).contains("typealias DateTime = @Serializable(with = DateTimeSerializer::class) String"));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::core::field::r#type::{FieldType, FieldTypeOwner};
+
+pub(crate) trait ToKotlinType {
+    fn to_kotlin_type(&self, optional: bool) -> String;
+}
+
+impl ToKotlinType for FieldType {
+    fn to_kotlin_type(&self, optional: bool) -> String {
+        let base: String = match self {
+            #[cfg(feature = "data-source-mongodb")]
+            FieldType::ObjectId => "String".to_string(),
+            FieldType::String => "String".to_string(),
+            FieldType::Bool => "Boolean".to_string(),
+            FieldType::I32 => "Int".to_string(),
+            FieldType::I64 => "Long".to_string(),
+            FieldType::F32 | FieldType::F64 => "Double".to_string(),
+            FieldType::Decimal => "Decimal".to_string(),
+            FieldType::Date => "LocalDate".to_string(),
+            FieldType::DateTime => "DateTime".to_string(),
+            FieldType::Enum(name) => name.to_string(),
+            FieldType::Vec(internal) => format!("List<{}>", internal.field_type().to_kotlin_type(internal.optionality.is_optional())),
+            FieldType::HashMap(internal) | FieldType::BTreeMap(internal) => format!("Map<String, {}>", internal.field_type().to_kotlin_type(internal.optionality.is_optional())),
+            FieldType::Object(name) => name.to_string(),
+            FieldType::Json => "JsonElement".to_string(),
+        };
+        if optional {
+            base + "?"
+        } else {
+            base
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::field::Field;
+    use super::*;
+
+    #[test]
+    fn field_types_map_to_kotlin_types() {
+        assert_eq!(FieldType::I32.to_kotlin_type(false), "Int");
+        assert_eq!(FieldType::I64.to_kotlin_type(true), "Long?");
+        assert_eq!(FieldType::F64.to_kotlin_type(false), "Double");
+        assert_eq!(FieldType::Bool.to_kotlin_type(false), "Boolean");
+        assert_eq!(FieldType::Date.to_kotlin_type(false), "LocalDate");
+        assert_eq!(FieldType::DateTime.to_kotlin_type(false), "DateTime");
+        assert_eq!(FieldType::Decimal.to_kotlin_type(true), "Decimal?");
+        let mut internal = Field::new("".to_owned());
+        internal.field_type = Some(FieldType::String);
+        assert_eq!(FieldType::Vec(Box::new(internal)).to_kotlin_type(false), "List<String>");
+    }
+}
@@ -19,7 +19,7 @@ let package = Package(
     ],
     targets: [
         .target(
-            name: "API",
+            name: "Teo",
             dependencies: [])
     ]
 )
@@ -0,0 +1,392 @@
+use inflector::Inflector;
+use itertools::Itertools;
+use crate::core::action::{Action, CREATE_HANDLER, DELETE_HANDLER, FIND_MANY_HANDLER, FIND_UNIQUE_HANDLER, UPDATE_HANDLER};
+use crate::core::app::conf::ClientGeneratorConf;
+use crate::core::field::r#type::FieldTypeOwner;
+use crate::core::graph::Graph;
+use crate::core::model::Model;
+use crate::generator::client::swift::r#type::ToSwiftType;
+use crate::generator::lib::code::Code;
+use crate::parser::ast::config::{DateTimeFormat, FieldNaming};
+
+static ESCAPE_LIST: [&str; 55] = [
+    "Any", "Protocol", "Self", "Type", "as", "associatedtype", "break", "case", "catch", "class", "continue",
+    "default", "defer", "deinit", "do", "else", "enum", "extension", "fallthrough", "false", "fileprivate",
+    "for", "func", "guard", "if", "import", "in", "init", "inout", "internal", "is", "let", "nil", "open",
+    "operator", "private", "protocol", "public", "repeat", "rethrows", "return", "self", "static", "struct",
+    "subscript", "super", "switch", "throw", "throws", "true", "try", "typealias", "var", "where", "while"
+];
+
+fn escape(before: impl AsRef<str>) -> String {
+    let before = before.as_ref();
+    if ESCAPE_LIST.contains(&before) {
+        format!("`{before}`")
+    } else {
+        before.to_string()
+    }
+}
+
+/// A public struct with optional properties and a public initializer, the
+/// memberwise initializer of a struct is internal to its module.
+fn swift_struct(c: &mut Code, name: &str, fields: Vec<(String, String)>) {
+    c.block(format!("public struct {name}: Codable {{"), |b| {
+        for (field_name, field_type) in &fields {
+            b.line(format!("public var {}: {field_type}?", escape(field_name)));
+        }
+        if fields.is_empty() {
+            b.line("public init() { }");
+            return
+        }
+        b.empty_line();
+        let params = fields.iter().map(|(n, t)| format!("{}: {t}? = nil", escape(n))).join(", ");
+        b.block(format!("public init({params}) {{"), |b| {
+            for (field_name, _) in &fields {
+                b.line(format!("self.{} = {}", escape(field_name), escape(field_name)));
+            }
+        }, "}");
+    }, "}");
+    c.empty_line();
+}
+
+fn scalar_fields(model: &Model, keys: &Vec<String>, naming: FieldNaming) -> Vec<(String, String)> {
+    keys.iter().filter_map(|k| model.field(k)).map(|f| (naming.api_key(f.api_name()).into_owned(), f.field_type().to_swift_type(false))).collect()
+}
+
+fn generate_model_arguments(c: &mut Code, model: &Model, naming: FieldNaming) {
+    let model_name = model.name();
+    // select
+    let select: Vec<(String, String)> = model.output_keys().iter().filter_map(|k| model.field(k)).map(|f| (naming.api_key(f.api_name()).into_owned(), "Bool".to_owned())).collect();
+    swift_struct(c, &format!("{model_name}Select"), select);
+    // where
+    let mut filter = scalar_fields(model, model.query_keys(), naming);
+    filter.push(("AND".to_owned(), format!("[{model_name}WhereInput]")));
+    filter.push(("OR".to_owned(), format!("[{model_name}WhereInput]")));
+    swift_struct(c, &format!("{model_name}WhereInput"), filter);
+    // where unique
+    let unique_keys: Vec<String> = model.unique_query_keys().iter().flatten().unique().sorted().cloned().collect();
+    swift_struct(c, &format!("{model_name}WhereUniqueInput"), scalar_fields(model, &unique_keys, naming));
+    // order by
+    let order_by: Vec<(String, String)> = model.sort_keys().iter().filter_map(|k| model.field(k)).map(|f| (naming.api_key(f.api_name()).into_owned(), "SortOrder".to_owned())).collect();
+    swift_struct(c, &format!("{model_name}OrderByInput"), order_by);
+    // create and update
+    swift_struct(c, &format!("{model_name}CreateInput"), scalar_fields(model, model.input_keys(), naming));
+    swift_struct(c, &format!("{model_name}UpdateInput"), scalar_fields(model, model.input_keys(), naming));
+    // action arguments
+    let select = ("select".to_owned(), format!("{model_name}Select"));
+    let where_unique = ("where".to_owned(), format!("{model_name}WhereUniqueInput"));
+    swift_struct(c, &format!("{model_name}FindUniqueArgs"), vec![where_unique.clone(), select.clone()]);
+    swift_struct(c, &format!("{model_name}FindManyArgs"), vec![
+        ("where".to_owned(), format!("{model_name}WhereInput")),
+        ("orderBy".to_owned(), format!("[{model_name}OrderByInput]")),
+        ("skip".to_owned(), "Int".to_owned()),
+        ("take".to_owned(), "Int".to_owned()),
+        select.clone(),
+        ("_withMeta".to_owned(), "Bool".to_owned()),
+    ]);
+    swift_struct(c, &format!("{model_name}CreateArgs"), vec![("create".to_owned(), format!("{model_name}CreateInput")), select.clone()]);
+    swift_struct(c, &format!("{model_name}UpdateArgs"), vec![where_unique.clone(), ("update".to_owned(), format!("{model_name}UpdateInput")), select.clone()]);
+    swift_struct(c, &format!("{model_name}DeleteArgs"), vec![where_unique, select]);
+}
+
+fn generate_model_delegate(c: &mut Code, model: &Model) {
+    let model_name = model.name();
+    // the server routes actions by the url segment name of a model
+    let url_segment_name = model.url_segment_name();
+    c.block(format!("public struct {model_name}Delegate {{"), |b| {
+        b.line("unowned let teo: Teo");
+        for handler in [FIND_UNIQUE_HANDLER, FIND_MANY_HANDLER, CREATE_HANDLER, UPDATE_HANDLER, DELETE_HANDLER] {
+            let action = Action::from_u32(handler);
+            if !model.has_action(action) { continue }
+            let action_name = action.as_handler_str();
+            let args_name = format!("{model_name}{}Args", action_name.to_pascal_case());
+            // find many keeps the whole response for the paging info in meta
+            let (result, request) = if handler == FIND_MANY_HANDLER {
+                (format!("TeoResponse<[{model_name}]>"), "response")
+            } else {
+                (model_name.to_owned(), "request")
+            };
+            b.empty_line();
+            b.block(format!("public func {action_name}(_ args: {args_name}) async throws -> {result} {{"), |b| {
+                b.line(format!(r#"try await teo.{request}("{url_segment_name}", "{action_name}", args)"#));
+            }, "}");
+        }
+    }, "}");
+    c.empty_line();
+}
+
+/// Date times are sent as `{"$date": ..}` in the server's date time format,
+/// custom formats can't be parsed back reliably, they are kept as strings.
+/// Epoch millis are written back the same way, other formats as strings.
+fn date_time_wrapper(format: &DateTimeFormat) -> String {
+    let (value_type, decode, encode) = match format {
+        DateTimeFormat::RFC3339 => ("Date", r#"let string = try container.decode(String.self, forKey: .date)
+        guard let value = TeoDateTime.formatter.date(from: string) else {
+            throw DecodingError.dataCorruptedError(forKey: .date, in: container, debugDescription: "Invalid date time \(string)")
+        }
+        self.value = value"#, r#"var container = encoder.singleValueContainer()
+        try container.encode(TeoDateTime.formatter.string(from: value))"#),
+        DateTimeFormat::EpochMillis => ("Date", r#"let millis = try container.decode(Double.self, forKey: .date)
+        self.value = Date(timeIntervalSince1970: millis / 1000)"#, r#"var container = encoder.container(keyedBy: CodingKeys.self)
+        try container.encode(Int64((value.timeIntervalSince1970 * 1000).rounded()), forKey: .date)"#),
+        DateTimeFormat::Custom(_) => ("String", "self.value = try container.decode(String.self, forKey: .date)", r#"var container = encoder.singleValueContainer()
+        try container.encode(value)"#),
+    };
+    format!(r#"public struct TeoDateTime: Codable, Equatable {{
+    static let formatter: ISO8601DateFormatter = {{
+        let formatter = ISO8601DateFormatter()
+        formatter.formatOptions = [.withInternetDateTime, .withFractionalSeconds]
+        return formatter
+    }}()
+
+    private enum CodingKeys: String, CodingKey {{
+        case date = "$date"
+    }}
+
+    public var value: {value_type}
+
+    public init(_ value: {value_type}) {{
+        self.value = value
+    }}
+
+    public init(from decoder: Decoder) throws {{
+        let container = try decoder.container(keyedBy: CodingKeys.self)
+        {decode}
+    }}
+
+    public func encode(to encoder: Encoder) throws {{
+        {encode}
+    }}
+}}"#)
+}
+
+pub(crate) async fn generate_teo_swift(graph: &Graph, client: &ClientGeneratorConf) -> String {
+    let base_url = client.base_url();
+    Code::new(0, 4, |c| {
+        c.line("import Foundation");
+        c.empty_line();
+        c.block("public enum SortOrder: String, Codable {", |b| {
+            b.line("case asc");
+            b.line("case desc");
+        }, "}");
+        c.empty_line();
+        c.indented(JSON_VALUE);
+        c.empty_line();
+        c.indented(date_time_wrapper(&client.date_time_format));
+        c.empty_line();
+        c.indented(DECIMAL);
+        c.empty_line();
+        c.indented(RESPONSE);
+        c.empty_line();
+        // enum definitions
+        graph.enums().iter().sorted_by(|a, b| a.0.cmp(b.0)).for_each(|(name, e)| {
+            c.block(format!("public enum {name}: String, Codable {{"), |b| {
+                for choice in e.values() {
+                    b.line(format!("case {}", escape(choice)));
+                }
+            }, "}");
+            c.empty_line();
+        });
+        // model definitions, any field may be left out by select
+        graph.models().iter().for_each(|m| {
+            let mut fields = scalar_fields(m, m.output_keys(), graph.field_naming());
+            for relation in m.output_keys().iter().filter_map(|k| m.relation(k)) {
+                let relation_type = relation.model();
+                fields.push((graph.field_naming().api_key(relation.name()).into_owned(), if relation.is_vec() { format!("[{relation_type}]") } else { relation_type.to_owned() }));
+            }
+            swift_struct(c, m.name(), fields);
+        });
+        // model input arguments
+        graph.models().iter().for_each(|m| generate_model_arguments(c, m, graph.field_naming()));
+        // delegates
+        graph.models().iter().filter(|m| m.actions().len() > 0).for_each(|m| generate_model_delegate(c, m));
+        // main class
+        c.block("public final class Teo {", |b| {
+            b.line("public let baseURL: URL");
+            b.line("public var token: String?");
+            b.empty_line();
+            graph.models().iter().filter(|m| m.actions().len() > 0).for_each(|m| {
+                let model_name = m.name();
+                b.line(format!("public lazy var {}: {model_name}Delegate = {model_name}Delegate(teo: self)", escape(model_name.to_camel_case())));
+            });
+            b.empty_line();
+            b.block(format!(r#"public init(baseURL: URL = URL(string: "{base_url}")!, token: String? = nil) {{"#), |b| {
+                b.line("self.baseURL = baseURL");
+                b.line("self.token = token");
+            }, "}");
+            b.empty_line();
+            b.block("func response<Args: Encodable, Data: Decodable>(_ urlSegmentName: String, _ action: String, _ args: Args) async throws -> TeoResponse<Data> {", |b| {
+                b.line(r#"var request = URLRequest(url: baseURL.appendingPathComponent("\(urlSegmentName)/action/\(action)"))"#);
+                b.line(r#"request.httpMethod = "POST""#);
+                b.line(r#"request.setValue("application/json", forHTTPHeaderField: "Content-Type")"#);
+                b.line(r#"if let token = token { request.setValue("Bearer \(token)", forHTTPHeaderField: "Authorization") }"#);
+                b.line("request.httpBody = try JSONEncoder().encode(args)");
+                b.line("let (body, response) = try await URLSession.shared.data(for: request)");
+                b.block("if let response = response as? HTTPURLResponse, response.statusCode >= 400 {", |b| {
+                    b.line("throw try JSONDecoder().decode(TeoErrorResponse.self, from: body).error");
+                }, "}");
+                b.line("return try JSONDecoder().decode(TeoResponse<Data>.self, from: body)");
+            }, "}");
+            b.empty_line();
+            b.block("func request<Args: Encodable, Data: Decodable>(_ urlSegmentName: String, _ action: String, _ args: Args) async throws -> Data {", |b| {
+                b.line("try await response(urlSegmentName, action, args).data");
+            }, "}");
+        }, "}");
+    }).to_string()
+}
+
+static DECIMAL: &str = r#"public struct TeoDecimal: Codable, Equatable {
+    private enum CodingKeys: String, CodingKey {
+        case decimal = "$decimal"
+    }
+
+    public var value: Decimal
+
+    public init(_ value: Decimal) {
+        self.value = value
+    }
+
+    public init(from decoder: Decoder) throws {
+        let container = try decoder.container(keyedBy: CodingKeys.self)
+        let string = try container.decode(String.self, forKey: .decimal)
+        guard let value = Decimal(string: string, locale: Locale(identifier: "en_US_POSIX")) else {
+            throw DecodingError.dataCorruptedError(forKey: .decimal, in: container, debugDescription: "Invalid decimal \(string)")
+        }
+        self.value = value
+    }
+
+    public func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        try container.encode(value.description)
+    }
+}"#;
+
+static RESPONSE: &str = r#"public struct TeoPagingInfo: Decodable {
+    public let count: Int
+    public let pageSize: Int?
+    public let pageNumber: Int?
+    public let numberOfPages: Int?
+}
+
+public struct TeoResponse<Data: Decodable>: Decodable {
+    public let meta: TeoPagingInfo?
+    public let data: Data
+}
+
+public struct TeoError: Error, Decodable {
+    public let type: String
+    public let message: String
+    public let errors: [String: String]?
+}
+
+struct TeoErrorResponse: Decodable {
+    let error: TeoError
+}"#;
+
+static JSON_VALUE: &str = r#"public enum JSONValue: Codable {
+    case string(String)
+    case number(Double)
+    case bool(Bool)
+    case object([String: JSONValue])
+    case array([JSONValue])
+    case null
+
+    public init(from decoder: Decoder) throws {
+        let container = try decoder.singleValueContainer()
+        if container.decodeNil() {
+            self = .null
+        } else if let value = try? container.decode(Bool.self) {
+            self = .bool(value)
+        } else if let value = try? container.decode(Double.self) {
+            self = .number(value)
+        } else if let value = try? container.decode(String.self) {
+            self = .string(value)
+        } else if let value = try? container.decode([JSONValue].self) {
+            self = .array(value)
+        } else {
+            self = .object(try container.decode([String: JSONValue].self))
+        }
+    }
+
+    public func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        switch self {
+        case .string(let value): try container.encode(value)
+        case .number(let value): try container.encode(value)
+        case .bool(let value): try container.encode(value)
+        case .object(let value): try container.encode(value)
+        case .array(let value): try container.encode(value)
+        case .null: try container.encodeNil()
+        }
+    }
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use crate::core::field::{Field, FieldIndex, IndexSettings};
+    use crate::core::field::r#type::FieldType;
+    use crate::core::graph::builder::GraphBuilder;
+    use crate::core::relation::Relation;
+    use crate::core::relation::cardinality::Cardinality;
+    use crate::core::test_connector::TestConnector;
+    use crate::parser::ast::client::ClientLanguage;
+    use super::*;
+
+    #[test]
+    fn swift_keywords_are_escaped() {
+        for keyword in ["Type", "Self", "protocol", "associatedtype", "self"] {
+            assert_eq!(escape(keyword), format!("`{keyword}`"));
+        }
+        assert_eq!(escape("name"), "name");
+    }
+
+    #[test]
+    fn date_times_are_decoded_in_the_server_format() {
+        assert!(date_time_wrapper(&DateTimeFormat::RFC3339).contains("TeoDateTime.formatter.date(from: string)"));
+        assert!(date_time_wrapper(&DateTimeFormat::EpochMillis).contains("Date(timeIntervalSince1970: millis / 1000)"));
+        assert!(date_time_wrapper(&DateTimeFormat::EpochMillis).contains("try container.encode(Int64((value.timeIntervalSince1970 * 1000).rounded()), forKey: .date)"));
+        assert!(!date_time_wrapper(&DateTimeFormat::EpochMillis).contains("formatter.string(from: value)"));
+        assert!(date_time_wrapper(&DateTimeFormat::Custom("%Y".to_owned())).contains("public var value: String"));
+    }
+
+    #[tokio::test]
+    async fn model_keys_follow_the_field_naming() {
+        let mut builder = GraphBuilder::new();
+        builder.field_naming = FieldNaming::SnakeCase;
+        builder.model("User", |m| {
+            let mut id = Field::new("id".to_owned());
+            id.field_type = Some(FieldType::I32);
+            id.index = Some(FieldIndex::Primary(IndexSettings::default()));
+            let mut first_name = Field::new("firstName".to_owned());
+            first_name.field_type = Some(FieldType::String);
+            let mut invited_by_id = Field::new("invitedById".to_owned());
+            invited_by_id.field_type = Some(FieldType::I32);
+            invited_by_id.foreign_key = true;
+            let mut invited_users = Relation::new("invitedUsers");
+            invited_users.set_model("User".to_owned());
+            invited_users.set_cardinality(Cardinality::Many);
+            invited_users.set_fields(vec!["id".to_owned()]);
+            invited_users.set_references(vec!["invitedById".to_owned()]);
+            m.field(id).field(first_name).field(invited_by_id).relation(invited_users);
+        });
+        let graph = builder.build(Arc::new(TestConnector::default())).await;
+        let client = ClientGeneratorConf {
+            name: None,
+            provider: ClientLanguage::Swift,
+            dest: PathBuf::from("client"),
+            package: false,
+            host: "https://example.com".to_owned(),
+            path_prefix: None,
+            object_name: None,
+            git_commit: false,
+            watch: false,
+            date_time_format: DateTimeFormat::RFC3339,
+        };
+        let swift = generate_teo_swift(&graph, &client).await;
+        assert!(swift.contains("public var first_name: String?"));
+        assert!(swift.contains("public var invited_by_id: Int?"));
+        assert!(swift.contains("public var invited_users: [User]?"));
+        assert!(!swift.contains("firstName"));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::core::field::r#type::{FieldType, FieldTypeOwner};
+
+pub(crate) trait ToSwiftType {
+    fn to_swift_type(&self, optional: bool) -> String;
+}
+
+impl ToSwiftType for FieldType {
+    fn to_swift_type(&self, optional: bool) -> String {
+        let base: String = match self {
+            #[cfg(feature = "data-source-mongodb")]
+            FieldType::ObjectId => "String".to_string(),
+            FieldType::String => "String".to_string(),
+            FieldType::Bool => "Bool".to_string(),
+            FieldType::I32 | FieldType::I64 => "Int".to_string(),
+            FieldType::F32 | FieldType::F64 => "Double".to_string(),
+            FieldType::Decimal => "TeoDecimal".to_string(),
+            // dates are sent as "yyyy-MM-dd" strings
+            FieldType::Date => "String".to_string(),
+            FieldType::DateTime => "TeoDateTime".to_string(),
+            FieldType::Enum(name) => name.to_string(),
+            FieldType::Vec(internal) => format!("[{}]", internal.field_type().to_swift_type(internal.optionality.is_optional())),
+            FieldType::HashMap(internal) | FieldType::BTreeMap(internal) => format!("[String: {}]", internal.field_type().to_swift_type(internal.optionality.is_optional())),
+            FieldType::Object(name) => name.to_string(),
+            FieldType::Json => "JSONValue".to_string(),
+        };
+        if optional {
+            base + "?"
+        } else {
+            base
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::field::Field;
+    use super::*;
+
+    #[test]
+    fn field_types_map_to_swift_types() {
+        assert_eq!(FieldType::String.to_swift_type(false), "String");
+        assert_eq!(FieldType::I64.to_swift_type(false), "Int");
+        assert_eq!(FieldType::F32.to_swift_type(true), "Double?");
+        assert_eq!(FieldType::DateTime.to_swift_type(false), "TeoDateTime");
+        assert_eq!(FieldType::Decimal.to_swift_type(true), "TeoDecimal?");
+        assert_eq!(FieldType::Bool.to_swift_type(false), "Bool");
+        let mut internal = Field::new("".to_owned());
+        internal.field_type = Some(FieldType::I32);
+        assert_eq!(FieldType::Vec(Box::new(internal)).to_swift_type(false), "[Int]");
+    }
+}
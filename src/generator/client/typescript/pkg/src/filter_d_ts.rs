@@ -35,6 +35,14 @@ export type BoolNullableFilter = {{
     not?: BoolNullableFilter | boolean | null
 }}
 
+export type JsonFilter = {{
+    equals?: any
+}}
+
+export type JsonNullableFilter = {{
+    equals?: any | null
+}}
+
 export type NumberFilter = {{
     equals?: number
     in?: number[]
@@ -91,6 +99,7 @@ export type StringFilter = {{
     startsWith?: string
     endsWith?: string
     matches?: string
+    search?: string
     not?: StringFilter | string
 }}
 
@@ -106,9 +115,12 @@ export type StringNullableFilter = {{
     startsWith?: string
     endsWith?: string
     matches?: string
+    search?: string
     not?: StringNullableFilter | string | null
 }}
 
+export type DateRange = "today" | "yesterday" | "thisWeek" | "thisMonth" | "last7Days" | "last30Days"
+
 export type DateFilter = {{
     equals?: string
     in?: string[]
@@ -117,6 +129,7 @@ export type DateFilter = {{
     lte?: string
     gt?: string
     gte?: string
+    between?: [string, string] | DateRange
     not?: DateFilter | string
 }}
 
@@ -128,6 +141,7 @@ export type DateNullableFilter = {{
     lte?: string
     gt?: string
     gte?: string
+    between?: [string, string] | DateRange
     not?: DateNullableFilter | string | null
 }}
 
@@ -139,6 +153,7 @@ export type DateTimeFilter = {{
     lte?: string
     gt?: string
     gte?: string
+    between?: [string, string] | DateRange
     not?: DateFilter | string
 }}
 
@@ -150,6 +165,7 @@ export type DateTimeNullableFilter = {{
     lte?: string
     gt?: string
     gte?: string
+    between?: [string, string] | DateRange
     not?: DateNullableFilter | string | null
 }}
 
@@ -1,5 +1,5 @@
 use inflector::Inflector;
-use crate::core::action::{Action, FIND_FIRST_HANDLER, FIND_UNIQUE_HANDLER, AGGREGATE_HANDLER, ResData, FIND_MANY_HANDLER, UPSERT_HANDLER};
+use crate::core::action::{Action, FIND_FIRST_HANDLER, FIND_UNIQUE_HANDLER, AGGREGATE_HANDLER, FACETS_HANDLER, ResData, FIND_MANY_HANDLER, UPSERT_HANDLER};
 use crate::generator::lib::cases::{field_description, field_localized_name, model_api_object_description, model_localized_name, model_localized_name_word_case, relation_description, relation_localized_name};
 use crate::core::field::Field;
 use crate::core::graph::Graph;
@@ -61,6 +61,7 @@ pub(crate) fn action_and_model(r#type: Action, model: &Model) -> String {
         FIND_FIRST_HANDLER | FIND_UNIQUE_HANDLER => "find".to_owned(),
         UPSERT_HANDLER => "create or update".to_owned(),
         AGGREGATE_HANDLER => "aggregate on".to_owned(),
+        FACETS_HANDLER => "count values of".to_owned(),
         _ => r#type.as_handler_str().to_word_case(),
     };
     let object = match r#type.handler_res_data() {
@@ -159,9 +160,17 @@ pub(crate) fn where_doc_first(model: &Model) -> String {
 
 pub(crate) fn order_by_doc(model: &Model) -> String {
     let object = model_localized_name_word_case(model).articlize();
-    format!(r#"/**
- * Determine the order of {object} to fetch.
+    match model.default_order_by() {
+        Some(order_by) => {
+            let default = order_by.iter().map(|(key, sort)| format!("{key} {}", sort.to_str().to_lowercase())).collect::<Vec<String>>().join(", ");
+            format!(r#"/**
+ * Determine the order of {object} to fetch. Sort by multiple keys with an array of single key objects. Defaults to {default}.
+ */"#)
+        }
+        None => format!(r#"/**
+ * Determine the order of {object} to fetch. Sort by multiple keys with an array of single key objects.
  */"#)
+    }
 }
 
 pub(crate) fn take_doc(model: &Model) -> String {
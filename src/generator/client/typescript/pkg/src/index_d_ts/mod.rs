@@ -1,13 +1,14 @@
 use inflector::Inflector;
-use crate::core::action::{ResMeta, ResData, Action, UPDATE_HANDLER, CREATE_HANDLER, FIND_FIRST_HANDLER, UPSERT_HANDLER, UPDATE_MANY_HANDLER};
+use crate::core::action::{ResMeta, ResData, Action, UPDATE_HANDLER, CREATE_HANDLER, FIND_FIRST_HANDLER, UPSERT_HANDLER, UPDATE_MANY_HANDLER, AGGREGATE_HANDLER, GROUP_BY_HANDLER, FACETS_HANDLER};
 use crate::core::app::conf::ClientGeneratorConf;
-use crate::core::field::r#type::FieldTypeOwner;
+use crate::core::field::r#type::{FieldType, FieldTypeOwner};
 use crate::generator::client::typescript::pkg::src::index_d_ts::docs::{action_doc, action_group_doc, create_or_update_doc, credentials_doc, cursor_doc, field_doc, include_doc, main_object_doc, nested_connect_doc, nested_create_doc, nested_create_or_connect_doc, nested_delete_doc, nested_disconnect_doc, nested_set_doc, nested_update_doc, nested_upsert_doc, order_by_doc, page_number_doc, page_size_doc, relation_doc, select_doc, skip_doc, take_doc, unique_connect_create_doc, unique_connect_doc, unique_where_doc, where_doc, where_doc_first, with_token_doc};
 use crate::generator::client::typescript::r#type::ToTypeScriptType;
 
 use crate::core::graph::Graph;
 use crate::core::model::{Model};
 use crate::core::model::index::ModelIndexType::{Primary, Unique};
+use crate::parser::ast::config::DateTimeFormat;
 use crate::generator::lib::code::Code;
 
 
@@ -78,7 +79,7 @@ fn generate_model_create_input(graph: &Graph, model: &Model, without: Option<&st
         c.block(format!("export type {model_name}Create{without_title}Input = {{"), |b| {
             model.input_keys().iter().for_each(|k| {
                 if let Some(field) = model.field(k) {
-                    let field_name = &field.name;
+                    let field_name = graph.field_naming().api_key(field.api_name());
                     let field_ts_type = field.field_type().to_typescript_create_input_type(field.optionality.is_optional());
                     if let Some(without_relation) = without_relation {
                         if !without_relation.fields().contains(k) {
@@ -89,8 +90,12 @@ fn generate_model_create_input(graph: &Graph, model: &Model, without: Option<&st
                         b.doc(field_doc(field));
                         b.line(format!("{field_name}?: {field_ts_type}"));
                     }
+                } else if let Some(property) = model.property(k) {
+                    let property_name = graph.field_naming().api_key(property.name());
+                    let property_ts_type = property.field_type().to_typescript_create_input_type(property.is_optional());
+                    b.line(format!("{property_name}?: {property_ts_type}"));
                 } else if let Some(relation) = model.relation(k) {
-                    let relation_name = relation.name();
+                    let relation_name = graph.field_naming().api_key(relation.name());
                     let relation_model_name = relation.model();
                     let relation_model = graph.model(relation_model_name).unwrap();
                     let num = if relation.is_vec() { "Many" } else { "One" };
@@ -252,12 +257,16 @@ fn generate_model_update_input(graph: &Graph, model: &Model, without: Option<&st
         c.block(format!("export type {model_name}Update{without_title}Input = {{"), |b| {
             model.input_keys().iter().for_each(|k| {
                 if let Some(field) = model.field(k) {
-                    let field_name = &field.name;
+                    let field_name = graph.field_naming().api_key(field.api_name());
                     let field_ts_type = field.field_type().to_typescript_update_input_type(field.optionality.is_optional());
                     b.doc(field_doc(field));
                     b.line(format!("{field_name}?: {field_ts_type}"));
+                } else if let Some(property) = model.property(k) {
+                    let property_name = graph.field_naming().api_key(property.name());
+                    let property_ts_type = property.field_type().to_typescript_update_input_type(property.is_optional());
+                    b.line(format!("{property_name}?: {property_ts_type}"));
                 } else if let Some(relation) = model.relation(k) {
-                    let relation_name = relation.name();
+                    let relation_name = graph.field_naming().api_key(relation.name());
                     let relation_model_name = relation.model();
                     let relation_model = graph.model(relation_model_name).unwrap();
                     let num = if relation.is_vec() { "Many" } else { "One" };
@@ -292,7 +301,7 @@ fn generate_model_update_input(graph: &Graph, model: &Model, without: Option<&st
     }).to_string()
 }
 
-fn generate_model_credentials_input(model: &Model) -> String {
+fn generate_model_credentials_input(graph: &Graph, model: &Model) -> String {
     let model_name = model.name();
     Code::new(0, 4, |c| {
         c.block(format!(r#"export type {model_name}CredentialsInput = {{"#), |b| {
@@ -302,14 +311,14 @@ fn generate_model_credentials_input(model: &Model) -> String {
             let auth_by_keys_optional = auth_by_keys.len() != 1;
             for key in auth_identity_keys {
                 let field = model.field(key).unwrap();
-                let field_name = &field.name;
+                let field_name = graph.field_naming().api_key(field.api_name());
                 let field_type = field.field_type().to_typescript_type(auth_identity_optional);
                 b.doc(field_doc(field));
                 b.line(format!("{field_name}: {field_type}"));
             }
             for key in auth_by_keys {
                 let field = model.field(key).unwrap();
-                let field_name = &field.name;
+                let field_name = graph.field_naming().api_key(field.api_name());
                 let field_type = field.field_type().to_typescript_type(auth_by_keys_optional);
                 b.doc(field_doc(field));
                 b.line(format!("{field_name}: {field_type}"));
@@ -318,6 +327,14 @@ fn generate_model_credentials_input(model: &Model) -> String {
     }).to_string()
 }
 
+fn output_typescript_type(field_type: &FieldType, optional: bool, client: &ClientGeneratorConf) -> String {
+    if field_type.is_datetime() && matches!(client.date_time_format, DateTimeFormat::Custom(_)) {
+        FieldType::String.to_typescript_type(optional)
+    } else {
+        field_type.to_typescript_type(optional)
+    }
+}
+
 pub(crate) async fn generate_index_d_ts(graph: &Graph, client: &ClientGeneratorConf) -> String {
     Code::new(0, 4, |c| {
         c.line(r#"import { Response, PagingInfo, TokenInfo, SortOrder, Enumerable, CheckSelectInclude, SelectSubset, ExistKeys, ResponseError } from "./runtime""#);
@@ -366,10 +383,16 @@ export declare class TeoError extends Error {
             let model_name = m.name();
             c.block(format!("export type {model_name} = {{"), |b| {
                 m.output_keys().iter().for_each(|k| {
+                    // keys which can't be written, like calculated properties, are read only
+                    let readonly = if m.input_keys().contains(k) { "" } else { "readonly " };
                     if let Some(field) = m.field(k) {
-                        let field_name = &field.name;
-                        let field_type = field.field_type().to_typescript_type(field.optionality.is_optional());
-                        b.line(format!("{field_name}: {field_type}"));
+                        let field_name = graph.field_naming().api_key(field.api_name());
+                        let field_type = output_typescript_type(field.field_type(), field.optionality.is_optional(), client);
+                        b.line(format!("{readonly}{field_name}: {field_type}"));
+                    } else if let Some(property) = m.property(k) {
+                        let property_name = graph.field_naming().api_key(property.name());
+                        let property_type = output_typescript_type(property.field_type(), property.is_optional(), client);
+                        b.line(format!("{readonly}{property_name}: {property_type}"));
                     }
                 });
             }, "}");
@@ -382,7 +405,7 @@ export declare class TeoError extends Error {
             c.block(format!("export type {model_name}Select = {{"), |b| {
                 m.output_keys().iter().for_each(|k| {
                     if let Some(field) = m.field(k) {
-                        let field_name = &field.name;
+                        let field_name = graph.field_naming().api_key(field.api_name());
                         b.doc(field_doc(field));
                         b.line(format!("{field_name}?: boolean"));
                     }
@@ -391,7 +414,7 @@ export declare class TeoError extends Error {
             // include
             c.block(format!("export type {model_name}Include = {{"), |b| {
                 for relation in m.relations() {
-                    let name = relation.name();
+                    let name = graph.field_naming().api_key(relation.name());
                     let is_vec = relation.is_vec();
                     let find_many = if is_vec { "FindMany" } else { "" };
                     let r_model = relation.model();
@@ -406,13 +429,13 @@ export declare class TeoError extends Error {
                 }
                 m.query_keys().iter().for_each(|k| {
                     if let Some(field) = m.field(k) {
-                        let field_name = &field.name;
+                        let field_name = graph.field_naming().api_key(field.api_name());
                         let field_filter = field.field_type().to_typescript_filter_type(field.optionality.is_optional());
                         b.doc(field_doc(field));
                         b.line(format!("{field_name}?: {field_filter}"));
                     } else if let Some(relation) = m.relation(k) {
                         let list = if relation.is_vec() { "List" } else { "" };
-                        let relation_name = relation.name();
+                        let relation_name = graph.field_naming().api_key(relation.name());
                         let relation_model = relation.model();
                         b.doc(relation_doc(relation));
                         b.line(format!("{relation_name}?: {relation_model}{list}RelationFilter"));
@@ -428,7 +451,7 @@ export declare class TeoError extends Error {
                             if !used_field_names.contains(&&***&&item.field_name()) {
                                 if let Some(field) = m.field(&item.field_name()) {
                                     let ts_type = field.field_type().to_typescript_type(field.optionality.is_optional());
-                                    let field_name = &item.field_name();
+                                    let field_name = graph.field_naming().api_key(field.api_name());
                                     b.doc(field_doc(field));
                                     b.line(format!("{field_name}?: {ts_type}"));
                                 }
@@ -451,9 +474,9 @@ export declare class TeoError extends Error {
             }, "}");
             // order by
             c.block(format!("export type {model_name}OrderByInput = {{"), |b| {
-                m.query_keys().iter().for_each(|k| {
+                m.sort_keys().iter().for_each(|k| {
                     if let Some(field) = m.field(k) {
-                        let field_name = &field.name;
+                        let field_name = graph.field_naming().api_key(field.api_name());
                         b.doc(field_doc(field));
                         b.line(format!("{field_name}?: SortOrder"));
                     } else if let Some(relation) = m.relation(k) {
@@ -463,6 +486,17 @@ export declare class TeoError extends Error {
                     }
                 })
             }, "}");
+            // aggregate and group by results
+            if m.has_action(Action::from_u32(AGGREGATE_HANDLER)) || m.has_action(Action::from_u32(GROUP_BY_HANDLER)) {
+                c.block(format!("export type {model_name}AggregateResult = {{"), |b| {
+                    b.line(format!("_count?: {{ [K in keyof {model_name} | '_all']?: number }}"));
+                    b.line(format!("_sum?: {{ [K in keyof {model_name}]?: number | null }}"));
+                    b.line(format!("_avg?: {{ [K in keyof {model_name}]?: number | null }}"));
+                    b.line(format!("_min?: {{ [K in keyof {model_name}]?: {model_name}[K] | null }}"));
+                    b.line(format!("_max?: {{ [K in keyof {model_name}]?: {model_name}[K] | null }}"));
+                }, "}");
+                c.line(format!("export type {model_name}GroupByResult = {{ [K in keyof {model_name}]?: {model_name}[K] }} & {model_name}AggregateResult"));
+            }
             // create and update inputs without anything
             c.line(generate_model_create_input(graph, m, None));
             c.line(generate_model_create_nested_input(graph, m, None, true));
@@ -489,7 +523,7 @@ export declare class TeoError extends Error {
                 c.line(generate_model_update_many_with_where_input(m, Some(r.name())));
             });
             if m.identity() {
-                c.line(generate_model_credentials_input(m));
+                c.line(generate_model_credentials_input(graph, m));
             }
             // action args
             c.block(format!(r#"export type {model_name}Args = {{"#), |b| {
@@ -503,6 +537,16 @@ export declare class TeoError extends Error {
                 let action_name = a.as_handler_str();
                 let capitalized_action_name = action_name.to_pascal_case();
                 c.block(format!(r#"export type {model_name}{capitalized_action_name}Args = {{"#), |b| {
+                    if a.handler_requires_field() {
+                        b.doc(where_doc(m));
+                        b.line(format!(r#"where?: {model_name}WhereInput"#));
+                        let fields: Vec<String> = m.query_keys().iter().filter_map(|k| m.field(k)).map(|f| format!("'{}'", graph.field_naming().api_key(f.api_name()))).collect();
+                        b.line(format!("field: {}", fields.join(" | ")));
+                        if m.soft_delete_field().is_some() {
+                            b.line("withDeleted?: boolean");
+                        }
+                        return;
+                    }
                     if a.handler_requires_where() {
                         if a == &Action::from_u32(FIND_FIRST_HANDLER) {
                             b.doc(where_doc_first(m));
@@ -519,6 +563,9 @@ export declare class TeoError extends Error {
                     b.line(format!(r#"select?: {model_name}Select"#));
                     b.doc(include_doc(m));
                     b.line(format!(r#"include?: {model_name}Include"#));
+                    if m.soft_delete_field().is_some() && a.handler_allowed_input_json_keys().contains("withDeleted") {
+                        b.line("withDeleted?: boolean");
+                    }
                     if a.handler_requires_where() {
                         b.doc(order_by_doc(m));
                         b.line(format!(r#"orderBy?: Enumerable<{model_name}OrderByInput>"#));
@@ -532,6 +579,10 @@ export declare class TeoError extends Error {
                         b.line(format!(r#"pageSize?: number"#));
                         b.doc(page_number_doc(m));
                         b.line(format!(r#"pageNumber?: number"#));
+                        if a.handler_allowed_input_json_keys().contains("_withMeta") {
+                            b.doc("Whether to count the records and return the page info in meta, `true` by default.");
+                            b.line("_withMeta?: boolean");
+                        }
                         //b.line(format!{r#"distinct? {model_name}ScalarFieldEnum"#})
                     }
                     if a.handler_requires_create() {
@@ -558,7 +609,7 @@ export declare class TeoError extends Error {
                             b.block(format!("? SelectSubset<{model_name}, S> & {{"), |b| {
                                 b.block(format!("[P in ExistKeys<S['include']>]:"), |b| {
                                     for relation in m.relations() {
-                                        let name = relation.name();
+                                        let name = graph.field_naming().api_key(relation.name());
                                         let is_array = relation.is_vec();
                                         let required = relation.is_required();
                                         let required_mark = if required { "" } else { " | undefined" };
@@ -589,7 +640,7 @@ export declare class TeoError extends Error {
                             let action_var_name = a.as_handler_str().to_camel_case();
                             let action_capitalized_name = action_var_name.to_pascal_case();
                             let res_meta = match a.handler_res_meta() {
-                                ResMeta::PagingInfo => "PagingInfo",
+                                ResMeta::PagingInfo => "PagingInfo | undefined",
                                 ResMeta::TokenInfo => "TokenInfo",
                                 ResMeta::NoMeta => "undefined",
                                 ResMeta::Other => "undefined",
@@ -606,7 +657,15 @@ export declare class TeoError extends Error {
                             };
                             b.empty_line();
                             b.doc(action_doc(object_name, a.clone(), m));
-                            b.line(format!("{action_var_name}<T extends {model_name}{action_capitalized_name}Args>(args?: T): Promise<Response<{res_meta}, CheckSelectInclude<T, {res_data}, {model_name}GetPayload<T>{payload_array}>>>"));
+                            if a == &Action::from_u32(AGGREGATE_HANDLER) {
+                                b.line(format!("{action_var_name}<T extends {model_name}{action_capitalized_name}Args>(args?: T): Promise<Response<{res_meta}, {model_name}AggregateResult>>"));
+                            } else if a == &Action::from_u32(GROUP_BY_HANDLER) {
+                                b.line(format!("{action_var_name}<T extends {model_name}{action_capitalized_name}Args>(args?: T): Promise<Response<{res_meta}, {model_name}GroupByResult[]>>"));
+                            } else if a == &Action::from_u32(FACETS_HANDLER) {
+                                b.line(format!("{action_var_name}<T extends {model_name}{action_capitalized_name}Args>(args: T): Promise<Response<{res_meta}, {{ value: {model_name}[T['field']], count: number }}[]>>"));
+                            } else {
+                                b.line(format!("{action_var_name}<T extends {model_name}{action_capitalized_name}Args>(args?: T): Promise<Response<{res_meta}, CheckSelectInclude<T, {res_data}, {model_name}GetPayload<T>{payload_array}>>>"));
+                            }
                         }
                     });
                 }, "}");
@@ -1,15 +1,21 @@
 use inflector::Inflector;
 use crate::core::app::conf::ClientGeneratorConf;
+use crate::parser::ast::config::DateTimeFormat;
 use crate::prelude::Graph;
 
 pub(crate) async fn generate_index_js(graph: &Graph, client: &ClientGeneratorConf) -> String {
     let mut name_map = "".to_owned();
-    let host = &client.host;
+    let base_url = client.base_url();
     let object_name = client.object_name.clone().unwrap_or("teo".to_owned());
     let mut class_name = object_name.to_pascal_case();
     if object_name == class_name { // in case of object name is capitalized
         class_name = class_name + "Class";
     }
+    // custom formats can't be parsed back reliably, they are kept as strings
+    let decode_date = match client.date_time_format {
+        DateTimeFormat::Custom(_) => "value['$date']",
+        _ => "new Date(value['$date'])",
+    };
     for model in graph.models() {
         if model.url_segment_name() != &model.name().to_camel_case() {
             name_map += &format!("  '{}': '{}',\n", model.name().to_camel_case(), model.url_segment_name());
@@ -17,6 +23,8 @@ pub(crate) async fn generate_index_js(graph: &Graph, client: &ClientGeneratorCon
     }
     format!(r#"const Decimal = require('./decimal')
 
+const baseUrl = "{base_url}"
+
 const nameMap = {{
 {name_map}}}
 
@@ -83,7 +91,7 @@ function getBearerToken() {{
 }}
 
 async function request(urlSegmentName, action, args, token = getBearerToken()) {{
-  let url = "{host}/" + urlSegmentName + "/action/" + action
+  let url = baseUrl + "/" + urlSegmentName + "/action/" + action
   let response = await fetch(url, {{
       method: "POST",
       headers: token ? {{ "Authorization": `Bearer ${{token}}` }} : undefined,
@@ -92,8 +100,8 @@ async function request(urlSegmentName, action, args, token = getBearerToken()) {
   let response_text = await response.text()
   let response_json = JSON.parse(response_text, (key, value) => {{
     if (typeof value === 'object' && value != null) {{
-      if (value['$date']) {{
-        return new Date(value['$date'])
+      if (value['$date'] !== undefined) {{
+        return {decode_date}
       }} else if (value['$decimal']) {{
         return new Decimal(value['$decimal'])
       }}  else {{
@@ -60,6 +60,14 @@ export type NullableBoolFieldUpdateOperationsInput = {{
     set?: boolean | null
 }}
 
+export type JsonFieldUpdateOperationsInput = {{
+    set?: any
+}}
+
+export type NullableJsonFieldUpdateOperationsInput = {{
+    set?: any | null
+}}
+
 export type DateFieldUpdateOperationsInput = {{
     set?: string | Date
 }}
@@ -1,13 +1,21 @@
 use crate::core::action::Action;
 use crate::core::app::conf::ClientGeneratorConf;
+use crate::core::error::ErrorType;
 use crate::core::graph::Graph;
 
 
+fn error_code_union() -> String {
+    ErrorType::iter().map(|t| { String::from("\"") + t.error_code() + "\"" }).collect::<Vec<String>>().join(" | ")
+}
+
 pub(crate) async fn generate_runtime_d_ts(_graph: &Graph, conf: &ClientGeneratorConf) -> String {
     let actions = Action::handlers_iter().map(|a| { String::from("\"") + a.as_handler_str() + "\"" }).collect::<Vec<String>>().join(" | ");
+    let error_codes = error_code_union();
     let _url = &conf.host;
     format!(r#"type Action = {actions}
 
+export type ErrorCode = {error_codes}
+
 export type ExistKeys<T> = {{
     [key in keyof T]: T[key] extends false | undefined | null ? never : key
 }}[keyof T]
@@ -43,12 +51,15 @@ export interface Response<Meta, Data> {{
 
 export interface ResponseError {{
     type: string
+    code: ErrorCode
     message: string
     errors: {{[key: string]: string}} | null
 }}
 
 export type PagingInfo = {{
     count: number
+    pageSize?: number
+    pageNumber?: number
     numberOfPages?: number
 }}
 
@@ -57,3 +68,17 @@ export type TokenInfo = {{
 }}
 "#)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_code_union_lists_every_error_type() {
+        let union = error_code_union();
+        assert!(union.starts_with("\"INTERNAL_SERVER_ERROR\" | "));
+        assert!(union.contains("\"VALIDATION_ERROR\""));
+        assert!(union.ends_with("\"RECORD_DECODING_ERROR\""));
+        assert_eq!(union.split(" | ").count(), ErrorType::iter().count());
+    }
+}
@@ -23,6 +23,7 @@ impl ToTypeScriptType for FieldType {
             FieldType::HashMap(_) => panic!(),
             FieldType::BTreeMap(_) => panic!(),
             FieldType::Object(name) => name.to_string(),
+            FieldType::Json => "any".to_string(),
         };
         if optional {
             base + " | undefined"
@@ -62,6 +63,7 @@ impl ToTypeScriptType for FieldType {
             FieldType::HashMap(_) => panic!(),
             FieldType::BTreeMap(_) => panic!(),
             FieldType::Object(_name) => "undefined | Unimplemented".to_string(),
+            FieldType::Json => "Json".to_string(),
         };
         if !with_generic {
             if optional {
@@ -88,6 +90,7 @@ impl ToTypeScriptType for FieldType {
             FieldType::HashMap(_) => panic!(),
             FieldType::BTreeMap(_) => panic!(),
             FieldType::Object(name) => name.to_string(),
+            FieldType::Json => "any".to_string(),
         };
         if optional {
             base + " | null"
@@ -112,6 +115,7 @@ impl ToTypeScriptType for FieldType {
             FieldType::DateTime => "DateTime",
             FieldType::Decimal => "Decimal",
             FieldType::Bool => "Bool",
+            FieldType::Json => "Json",
             FieldType::I32 | FieldType::I64 | FieldType::F32 | FieldType::F64 => "Number",
             FieldType::Enum(name) => {
                 generic = format!("<{name}>");
@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet};
+use serde_json::{json, Map, Value as JsonValue};
+use crate::core::field::Field;
+use crate::core::field::r#type::{FieldType, FieldTypeOwner};
+use crate::core::graph::Graph;
+use crate::core::model::Model;
+use crate::core::r#enum::Enum;
+use crate::core::relation::Relation;
+use crate::core::teon::decoder::{NESTED_CREATE_MANY_ARG_KEYS, NESTED_CREATE_ONE_ARG_KEYS, NESTED_UPDATE_MANY_ARG_KEYS, NESTED_UPDATE_ONE_ARG_KEYS};
+use crate::core::teon::date_range::RELATIVE_DATE_RANGES;
+
+static JSON_SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+pub(crate) fn generate_json_schema(graph: &Graph) -> JsonValue {
+    let mut defs = Map::new();
+    for model in graph.models() {
+        if model.r#virtual() { continue }
+        let name = model.name();
+        defs.insert(format!("{name}CreateInput"), create_input_schema(model_input_fields(model), model_input_relations(model), graph.enums()));
+        defs.insert(format!("{name}UpdateInput"), update_input_schema(model_input_fields(model), model_input_relations(model), graph.enums()));
+        defs.insert(format!("{name}WhereInput"), where_input_schema(name, model_query_fields(model), graph.enums()));
+    }
+    json!({
+        "$schema": JSON_SCHEMA_DRAFT,
+        "$defs": defs,
+    })
+}
+
+fn model_input_fields(model: &Model) -> Vec<&Field> {
+    model.fields().iter().filter(|f| model.input_keys().contains(&f.name)).map(|f| f.as_ref()).collect()
+}
+
+fn model_input_relations(model: &Model) -> Vec<&Relation> {
+    model.relations().iter().filter(|r| model.input_keys().contains(&r.name().to_owned())).map(|r| r.as_ref()).collect()
+}
+
+fn model_query_fields(model: &Model) -> Vec<&Field> {
+    model.fields().iter().filter(|f| model.query_keys().contains(&f.name)).map(|f| f.as_ref()).collect()
+}
+
+pub(crate) fn create_input_schema(fields: Vec<&Field>, relations: Vec<&Relation>, enums: &HashMap<String, Enum>) -> JsonValue {
+    let mut properties = Map::new();
+    let mut required: Vec<JsonValue> = vec![];
+    for field in fields {
+        properties.insert(field.api_name().to_owned(), field_schema(field, enums));
+        if field.is_required() && field.default.is_none() && !field.auto && !field.auto_increment && !field.input_omissible {
+            required.push(JsonValue::String(field.api_name().to_owned()));
+        }
+    }
+    for relation in relations {
+        let commands = if relation.is_vec() { &NESTED_CREATE_MANY_ARG_KEYS } else { &NESTED_CREATE_ONE_ARG_KEYS };
+        properties.insert(relation.name().to_owned(), relation_input_schema(relation, commands));
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+pub(crate) fn update_input_schema(fields: Vec<&Field>, relations: Vec<&Relation>, enums: &HashMap<String, Enum>) -> JsonValue {
+    let mut properties = Map::new();
+    for field in fields {
+        properties.insert(field.api_name().to_owned(), field_schema(field, enums));
+    }
+    for relation in relations {
+        let commands = if relation.is_vec() { &NESTED_UPDATE_MANY_ARG_KEYS } else { &NESTED_UPDATE_ONE_ARG_KEYS };
+        properties.insert(relation.name().to_owned(), relation_input_schema(relation, commands));
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
+
+pub(crate) fn where_input_schema(model_name: &str, fields: Vec<&Field>, enums: &HashMap<String, Enum>) -> JsonValue {
+    let self_ref = json!({"$ref": format!("#/$defs/{model_name}WhereInput")});
+    let mut properties = Map::new();
+    for field in fields {
+        let value_schema = type_schema(field.field_type(), field.is_optional(), enums);
+        let mut filter_properties = Map::new();
+        let mut filters: Vec<&str> = field.field_type().filters().iter().map(|f| *f).collect();
+        filters.sort();
+        for filter in filters {
+            filter_properties.insert(filter.to_owned(), match filter {
+                "in" | "notIn" => json!({"type": "array", "items": value_schema}),
+                "between" => json!({"anyOf": [
+                    {"type": "array", "items": value_schema, "minItems": 2, "maxItems": 2},
+                    {"enum": RELATIVE_DATE_RANGES},
+                ]}),
+                "mode" => json!({"enum": ["caseInsensitive", "default"]}),
+                "isEmpty" => json!({"type": "boolean"}),
+                "length" => json!({"type": "integer"}),
+                "has" => match field.field_type().element_field() {
+                    Some(element) => type_schema(element.field_type(), element.is_optional(), enums),
+                    None => value_schema.clone(),
+                },
+                "hasEvery" | "hasSome" => match field.field_type().element_field() {
+                    Some(element) => json!({"type": "array", "items": type_schema(element.field_type(), element.is_optional(), enums)}),
+                    None => value_schema.clone(),
+                },
+                _ => value_schema.clone(),
+            });
+        }
+        properties.insert(field.api_name().to_owned(), json!({
+            "anyOf": [
+                value_schema,
+                {"type": "object", "properties": filter_properties, "additionalProperties": false},
+            ]
+        }));
+    }
+    properties.insert("AND".to_owned(), json!({"anyOf": [self_ref, {"type": "array", "items": self_ref}]}));
+    properties.insert("OR".to_owned(), json!({"anyOf": [self_ref, {"type": "array", "items": self_ref}]}));
+    properties.insert("NOT".to_owned(), self_ref);
+    json!({
+        "type": "object",
+        "properties": properties,
+    })
+}
+
+/// The nested commands of a relation input. Their records are decoded by
+/// the related model, so they're only described as objects here.
+fn relation_input_schema(relation: &Relation, commands: &HashSet<&str>) -> JsonValue {
+    let mut properties = Map::new();
+    for command in commands {
+        let schema = match (relation.is_vec(), *command) {
+            (true, _) => json!({"anyOf": [{"type": "object"}, {"type": "array", "items": {"type": "object"}}]}),
+            (false, "disconnect") | (false, "delete") => json!({"type": "boolean"}),
+            (false, "set") => json!({"type": ["object", "null"]}),
+            (false, _) => json!({"type": "object"}),
+        };
+        properties.insert(command.to_string(), schema);
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
+
+fn field_schema(field: &Field, enums: &HashMap<String, Enum>) -> JsonValue {
+    let mut schema = type_schema(field.field_type(), field.is_optional(), enums);
+    if let Some(description) = field.description() {
+        schema.as_object_mut().unwrap().insert("description".to_owned(), JsonValue::String(description.to_owned()));
+    }
+    for item in field.on_set_pipeline.items.iter() {
+        if let Some((keyword, value)) = item.json_schema_keyword() {
+            schema.as_object_mut().unwrap().insert(keyword.to_owned(), value.into());
+        }
+    }
+    schema
+}
+
+fn type_schema(field_type: &FieldType, optional: bool, enums: &HashMap<String, Enum>) -> JsonValue {
+    let schema = match field_type {
+        #[cfg(feature = "data-source-mongodb")]
+        FieldType::ObjectId => json!({"type": "string", "pattern": "^[0-9a-fA-F]{24}$"}),
+        FieldType::Bool => json!({"type": "boolean"}),
+        FieldType::I32 | FieldType::I64 => json!({"type": "integer"}),
+        FieldType::F32 | FieldType::F64 => json!({"type": "number"}),
+        FieldType::Decimal => json!({"type": ["string", "number"]}),
+        FieldType::String => json!({"type": "string"}),
+        FieldType::Date => json!({"type": "string", "format": "date"}),
+        FieldType::DateTime => json!({"type": "string", "format": "date-time"}),
+        FieldType::Enum(name) => match enums.get(name) {
+            Some(e) => json!({"enum": e.values()}),
+            None => json!({"type": "string"}),
+        },
+        FieldType::Vec(inner) => json!({"type": "array", "items": type_schema(inner.field_type(), inner.is_optional(), enums)}),
+        FieldType::HashMap(inner) | FieldType::BTreeMap(inner) => json!({"type": "object", "additionalProperties": type_schema(inner.field_type(), inner.is_optional(), enums)}),
+        FieldType::Object(_) => json!({"type": "object"}),
+        FieldType::Json => json!({}),
+    };
+    if optional {
+        json!({"anyOf": [schema, {"type": "null"}]})
+    } else {
+        schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use maplit::hashmap;
+    use crate::core::pipeline::items::value::gte::GteItem;
+    use crate::core::r#enum::builder::EnumBuilder;
+    use crate::core::relation::cardinality::Cardinality;
+    use super::*;
+
+    #[test]
+    fn create_input_schema_includes_enum_and_required_fields() {
+        let mut sex_builder = EnumBuilder::new("Sex");
+        sex_builder.choice("MALE", |_| {});
+        sex_builder.choice("FEMALE", |_| {});
+        let sex: Enum = (&sex_builder).into();
+        let enums = hashmap!{"Sex".to_owned() => sex};
+        let mut name = Field::new("name".to_owned());
+        name.field_type = Some(FieldType::String);
+        let mut age = Field::new("age".to_owned());
+        age.field_type = Some(FieldType::I32);
+        age.set_optional();
+        age.on_set_pipeline.items.push(Arc::new(GteItem::new(0)));
+        let mut sex = Field::new("sex".to_owned());
+        sex.field_type = Some(FieldType::Enum("Sex".to_owned()));
+        let schema = create_input_schema(vec![&name, &age, &sex], vec![], &enums);
+        assert_eq!(schema, json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "age": {"anyOf": [{"type": "integer"}, {"type": "null"}], "minimum": 0},
+                "sex": {"enum": ["MALE", "FEMALE"]},
+            },
+            "required": ["name", "sex"],
+            "additionalProperties": false,
+        }));
+    }
+
+    #[test]
+    fn relation_inputs_are_properties_of_create_and_update_inputs() {
+        let mut title = Field::new("title".to_owned());
+        title.field_type = Some(FieldType::String);
+        let mut posts = Relation::new("posts");
+        posts.set_model("Post".to_owned());
+        posts.set_cardinality(Cardinality::Many);
+        let create = create_input_schema(vec![&title], vec![&posts], &HashMap::new());
+        let posts_input = &create["properties"]["posts"];
+        assert_eq!(posts_input["additionalProperties"], json!(false));
+        assert_eq!(posts_input["properties"]["connect"], json!({"anyOf": [{"type": "object"}, {"type": "array", "items": {"type": "object"}}]}));
+        assert!(posts_input["properties"].get("disconnect").is_none());
+        let mut author = Relation::new("author");
+        author.set_model("User".to_owned());
+        let update = update_input_schema(vec![&title], vec![&author], &HashMap::new());
+        let author_input = &update["properties"]["author"];
+        assert_eq!(author_input["properties"]["disconnect"], json!({"type": "boolean"}));
+        assert_eq!(author_input["properties"]["update"], json!({"type": "object"}));
+    }
+}
@@ -1,3 +1,4 @@
 pub(crate) mod client;
 pub(crate) mod server;
 pub(crate) mod lib;
+pub(crate) mod json_schema;
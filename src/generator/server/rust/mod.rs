@@ -67,6 +67,7 @@ impl RustEntityGenerator {
             FieldType::HashMap(inner) => format!("HashMap<String, {}>", self.getter_type_for_field(inner.as_ref())),
             FieldType::BTreeMap(inner) => format!("BTreemap<String, {}>", self.getter_type_for_field(inner.as_ref())),
             FieldType::Object(name) => name.clone(),
+            FieldType::Json => "Value".to_owned(),
         }
     }
 
@@ -3,7 +3,7 @@ use crate::parser::ast::span::Span;
 use crate::parser::ast::item::Item;
 use crate::parser::ast::identifier::Identifier;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ClientLanguage {
     TypeScript,
     Swift,
@@ -25,6 +25,7 @@ pub struct Client {
     pub(crate) host: Option<String>,
     pub(crate) object_name: Option<String>,
     pub(crate) git_commit: bool,
+    pub(crate) watch: bool,
 }
 
 impl Client {
@@ -41,6 +42,7 @@ impl Client {
             host: None,
             object_name: Some("teo".to_owned()),
             git_commit: false,
+            watch: false,
         }
     }
 }
@@ -1,5 +1,143 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::time::Duration;
+use inflector::Inflector;
+use crate::core::action::Action;
 use crate::parser::ast::span::Span;
 use crate::parser::ast::item::Item;
+use crate::parser::ast::client::ClientLanguage;
+
+/// The naming convention of model keys in request and response JSON.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FieldNaming {
+    AsDefined,
+    CamelCase,
+    SnakeCase,
+}
+
+impl FieldNaming {
+
+    /// The name of a model key in JSON.
+    pub(crate) fn api_key<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self {
+            FieldNaming::AsDefined => Cow::Borrowed(name),
+            FieldNaming::CamelCase => Cow::Owned(name.to_camel_case()),
+            FieldNaming::SnakeCase => Cow::Owned(name.to_snake_case()),
+        }
+    }
+}
+
+/// How date time values are written into response JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateTimeFormat {
+    RFC3339,
+    EpochMillis,
+    Custom(String),
+}
+
+/// The `Access-Control-*` headers of responses. CORS is disabled until an
+/// origin is allowed, `origin: "*"` restores the any origin headers servers
+/// sent before CORS was configurable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cors {
+    pub(crate) allow_origins: Vec<String>,
+    pub(crate) allow_methods: Vec<String>,
+    pub(crate) allow_headers: Vec<String>,
+    pub(crate) allow_credentials: bool,
+    pub(crate) max_age: Option<u32>,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self {
+            allow_origins: vec![],
+            allow_methods: vec!["OPTIONS".to_owned(), "POST".to_owned(), "GET".to_owned()],
+            allow_headers: vec!["*".to_owned()],
+            allow_credentials: false,
+            max_age: Some(86400),
+        }
+    }
+}
+
+impl Cors {
+
+    pub fn allow_origin(&mut self, origin: impl Into<String>) -> &mut Self {
+        self.allow_origins.push(origin.into());
+        self
+    }
+
+    pub fn allow_methods<I, T>(&mut self, methods: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<String> {
+        self.allow_methods = methods.into_iter().map(|m| m.into()).collect();
+        self
+    }
+
+    pub fn allow_headers<I, T>(&mut self, headers: I) -> &mut Self where I: IntoIterator<Item = T>, T: Into<String> {
+        self.allow_headers = headers.into_iter().map(|h| h.into()).collect();
+        self
+    }
+
+    pub fn allow_credentials(&mut self, allow_credentials: bool) -> &mut Self {
+        self.allow_credentials = allow_credentials;
+        self
+    }
+
+    pub fn max_age(&mut self, max_age: Option<u32>) -> &mut Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.allow_origins.is_empty()
+    }
+
+    /// Browsers reject credentialed responses to a wildcard origin, so the
+    /// allowed origins have to be listed when credentials are allowed.
+    pub(crate) fn check(&self, name: &str) {
+        if self.allow_credentials && self.allow_origins.iter().any(|o| o == "*") {
+            panic!("CORS of '{}' can't allow credentials from any origin, list the allowed origins instead.", name)
+        }
+    }
+
+    /// The `Access-Control-Allow-Origin` of a request from `origin`.
+    pub(crate) fn allowed_origin(&self, origin: Option<&str>) -> Option<String> {
+        if self.allow_origins.iter().any(|o| o == "*") {
+            return Some("*".to_owned());
+        }
+        origin.filter(|origin| self.allow_origins.iter().any(|o| o == origin)).map(|o| o.to_owned())
+    }
+}
+
+/// The CORS settings a route sets, the others are the server's.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CorsOverride {
+    pub(crate) allow_origins: Option<Vec<String>>,
+    pub(crate) allow_methods: Option<Vec<String>>,
+    pub(crate) allow_headers: Option<Vec<String>>,
+    pub(crate) allow_credentials: Option<bool>,
+    pub(crate) max_age: Option<Option<u32>>,
+}
+
+impl CorsOverride {
+
+    /// The server's settings `base` with the ones of this route.
+    pub(crate) fn merge(&self, base: &Cors) -> Cors {
+        Cors {
+            allow_origins: self.allow_origins.clone().unwrap_or_else(|| base.allow_origins.clone()),
+            allow_methods: self.allow_methods.clone().unwrap_or_else(|| base.allow_methods.clone()),
+            allow_headers: self.allow_headers.clone().unwrap_or_else(|| base.allow_headers.clone()),
+            allow_credentials: self.allow_credentials.unwrap_or(base.allow_credentials),
+            max_age: self.max_age.unwrap_or(base.max_age),
+        }
+    }
+}
+
+/// How many requests an identity, or an address when no one is signed in, may
+/// make within a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    pub(crate) requests: u32,
+    pub(crate) window: Duration,
+}
 
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
@@ -10,6 +148,20 @@ pub struct ServerConfig {
     pub(crate) bind: Option<(String, u16)>,
     pub(crate) jwt_secret: Option<String>,
     pub(crate) path_prefix: Option<String>,
+    pub(crate) client_path: Option<String>,
+    pub(crate) client_provider: Option<ClientLanguage>,
+    pub(crate) lenient_input_keys: bool,
+    pub(crate) timezone: Option<String>,
+    pub(crate) introspection: bool,
+    pub(crate) metrics: bool,
+    pub(crate) admin: Option<String>,
+    pub(crate) field_naming: FieldNaming,
+    pub(crate) date_time_format: DateTimeFormat,
+    pub(crate) action_aliases: HashMap<String, Action>,
+    pub(crate) cors: Cors,
+    pub(crate) cors_routes: Vec<(String, CorsOverride)>,
+    pub(crate) rate_limit: Option<RateLimit>,
+    pub(crate) trust_proxy: bool,
 }
 
 impl ServerConfig {
@@ -22,6 +174,20 @@ impl ServerConfig {
             bind: None,
             jwt_secret: None,
             path_prefix: None,
+            client_path: None,
+            client_provider: None,
+            lenient_input_keys: false,
+            timezone: None,
+            introspection: false,
+            metrics: false,
+            admin: None,
+            field_naming: FieldNaming::AsDefined,
+            date_time_format: DateTimeFormat::RFC3339,
+            action_aliases: HashMap::new(),
+            cors: Cors::default(),
+            cors_routes: vec![],
+            rate_limit: None,
+            trust_proxy: false,
         }
     }
 }
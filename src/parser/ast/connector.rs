@@ -1,22 +1,29 @@
 use crate::core::database::name::DatabaseName;
 use crate::parser::ast::span::Span;
 use crate::parser::ast::item::Item;
+use crate::parser::ast::identifier::Identifier;
 
 #[derive(Debug, Clone)]
 pub struct Connector {
     pub(crate) id: usize,
     pub(crate) source_id: usize,
+    pub(crate) identifier: Option<Identifier>,
     pub(crate) items: Vec<Item>,
     pub(crate) span: Span,
     pub(crate) provider: Option<DatabaseName>,
     pub(crate) url: Option<String>,
     pub(crate) debug: bool,
+    pub(crate) foreign_keys: bool,
+    pub(crate) replica_url: Option<String>,
+    pub(crate) replica_lag: u64,
+    pub(crate) charset: Option<String>,
+    pub(crate) collation: Option<String>,
 }
 
 impl Connector {
-    pub(crate) fn new(items: Vec<Item>, span: Span, source_id: usize, item_id: usize) -> Self {
+    pub(crate) fn new(identifier: Option<Identifier>, items: Vec<Item>, span: Span, source_id: usize, item_id: usize) -> Self {
         Self {
-            id: item_id, items, span, source_id, provider: None, url: None, debug: false
+            id: item_id, identifier, items, span, source_id, provider: None, url: None, debug: false, foreign_keys: false, replica_url: None, replica_lag: 0, charset: None, collation: None
         }
     }
 }
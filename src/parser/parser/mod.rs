@@ -71,6 +71,7 @@ pub(crate) struct Parser {
     pub(crate) enums: Vec<(usize, usize)>,
     pub(crate) models: Vec<(usize, usize)>,
     pub(crate) connector: Option<(usize, usize)>,
+    pub(crate) named_connectors: Vec<(usize, usize)>,
     pub(crate) config: Option<(usize, usize)>,
     pub(crate) generators: Vec<(usize, usize)>,
     pub(crate) clients: Vec<(usize, usize)>,
@@ -87,12 +88,18 @@ pub(crate) struct Parser {
 
 impl Parser {
 
+    /// The paths of the parsed schema files, the main one and the ones it imports.
+    pub(crate) fn source_paths(&self) -> Vec<PathBuf> {
+        self.sources.values().map(|s| s.path.clone()).collect()
+    }
+
     pub(crate) fn new(callback_lookup_table: Arc<Mutex<CallbackLookupTable>>) -> Self {
         Self {
             sources: btreemap!{},
             enums: vec![],
             models: vec![],
             connector: None,
+            named_connectors: vec![],
             config: None,
             generators: vec![],
             clients: vec![],
@@ -444,11 +451,15 @@ impl Parser {
                 Top::ServerConfig(ServerConfig::new(item_id, source_id, items, span))
             },
             "connector" => {
-                if self.connector.is_some() {
-                    panic!("Duplicated connector found.");
+                if identifier.is_some() {
+                    self.named_connectors.push((source_id, item_id));
+                } else {
+                    if self.connector.is_some() {
+                        panic!("Duplicated connector found.");
+                    }
+                    self.connector = Some((source_id, item_id));
                 }
-                self.connector = Some((source_id, item_id));
-                Top::Connector(Connector::new(items, span, source_id, item_id))
+                Top::Connector(Connector::new(identifier, items, span, source_id, item_id))
             },
             "entity" => {
                 self.generators.push((source_id, item_id));
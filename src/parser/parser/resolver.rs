@@ -2,6 +2,8 @@ use std::collections::HashMap;
 use std::i64;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
+use chrono::format::{Item as StrftimeItem, StrftimeItems};
 use path_absolutize::Absolutize;
 use regex::Regex;
 use snailquote::unescape;
@@ -9,7 +11,7 @@ use crate::core::database::name::DatabaseName;
 use crate::core::teon::range::Range;
 use crate::parser::ast::accessible::{Accessible, ASTPipeline, ASTPipelineItem, Container};
 use crate::parser::ast::argument::ArgumentList;
-use crate::parser::ast::config::ServerConfig;
+use crate::parser::ast::config::{Cors, CorsOverride, DateTimeFormat, FieldNaming, RateLimit, ServerConfig};
 use crate::parser::ast::constant::Constant;
 use crate::parser::ast::decorator::Decorator;
 use crate::parser::ast::entity::Entity;
@@ -424,7 +426,13 @@ impl Resolver {
         if parser.connector.is_none() {
             panic!("Connector is not defined.");
         }
-        let connector_ref = parser.connector.unwrap();
+        for connector_ref in parser.named_connectors.iter() {
+            Self::resolve_connector_block(parser, *connector_ref);
+        }
+        Self::resolve_connector_block(parser, parser.connector.unwrap())
+    }
+
+    fn resolve_connector_block(parser: &Parser, connector_ref: (usize, usize)) -> DatabaseName {
         let source = parser.get_source(connector_ref.0);
         let top = source.to_mut().tops.get_mut(&connector_ref.1).unwrap();
         let mut connector = top.as_connector_mut().unwrap();
@@ -455,6 +463,32 @@ impl Resolver {
                     let bool = bool_value.as_bool().unwrap();
                     connector.debug = bool;
                 }
+                "foreignKeys" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let bool_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    connector.foreign_keys = bool_value.as_bool().unwrap();
+                }
+                "replicaUrl" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let url_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    connector.replica_url = Some(url_value.as_str().unwrap().to_owned());
+                }
+                "replicaLag" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let lag_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    // a negative lag means no lag
+                    connector.replica_lag = lag_value.as_i64().unwrap().max(0) as u64;
+                }
+                "charset" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let charset_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    connector.charset = Some(charset_value.as_str().unwrap().to_owned());
+                }
+                "collation" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let collation_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    connector.collation = Some(collation_value.as_str().unwrap().to_owned());
+                }
                 _ => { panic!("Undefined name '{}' in connector block.", item.identifier.name.as_str())}
             }
         }
@@ -511,6 +545,11 @@ impl Resolver {
                     let git_commit_bool = git_commit_value.as_bool().unwrap();
                     client.git_commit = git_commit_bool;
                 }
+                "watch" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let watch_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    client.watch = watch_value.as_bool().unwrap();
+                }
                 _ => { panic!("Undefined name '{}' in client generator block.", item.identifier.name.as_str())}
             }
         }
@@ -550,6 +589,11 @@ impl Resolver {
         for item in config.items.iter_mut() {
             match item.identifier.name.as_str() {
                 "bind" => {
+                    if let ExpressionKind::TupleLiteral(tuple) = &item.expression.kind {
+                        for expression in tuple.expressions.iter() {
+                            Self::ensure_env_is_set(expression, "bind");
+                        }
+                    }
                     Self::resolve_expression(parser, source, &mut item.expression);
                     let bind_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
                     match bind_value.as_tuple() {
@@ -557,13 +601,21 @@ impl Resolver {
                             let arg1 = tuple_vec.get(0).unwrap();
                             let arg2 = tuple_vec.get(1).unwrap();
                             let str = arg1.as_str().unwrap().to_owned();
-                            let int = arg2.as_i32().unwrap().to_owned();
-                            config.bind = Some((str, int as u16));
+                            // a port read from ENV is a string
+                            let port = match arg2.as_str() {
+                                Some(s) => match s.parse::<u16>() {
+                                    Ok(port) => port,
+                                    Err(_) => panic!("Port of 'bind' should be an integer, found '{}'.", s),
+                                },
+                                None => arg2.as_i32().unwrap() as u16,
+                            };
+                            config.bind = Some((str, port));
                         }
                         None => panic!("Argument to 'bind' should be a tuple.")
                     }
                 }
                 "jwtSecret" => {
+                    Self::ensure_env_is_set(&item.expression.kind, "jwtSecret");
                     Self::resolve_expression(parser, source, &mut item.expression);
                     let jwt_secret_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
                     match jwt_secret_value {
@@ -581,11 +633,220 @@ impl Resolver {
                         _ => panic!("Value of 'pathPrefix' should be string.")
                     }
                 }
+                "clientPath" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let client_path_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    match client_path_value {
+                        Value::Null => (),
+                        Value::String(s) => config.client_path = Some(s.clone()),
+                        _ => panic!("Value of 'clientPath' should be string.")
+                    }
+                }
+                "clientProvider" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let provider_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    let provider_str = provider_value.as_raw_enum_choice().unwrap();
+                    config.client_provider = Some(match provider_str {
+                        "javaScript" | "typeScript" => ClientLanguage::TypeScript,
+                        "swift" => ClientLanguage::Swift,
+                        "kotlin" => ClientLanguage::Kotlin,
+                        "cSharp" => ClientLanguage::CSharp,
+                        "dart" => ClientLanguage::Dart,
+                        _ => panic!("Unrecognized client provider. {}", provider_str)
+                    });
+                }
+                "lenientInputKeys" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let lenient_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    config.lenient_input_keys = lenient_value.as_bool().unwrap();
+                }
+                "introspection" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let introspection_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    config.introspection = introspection_value.as_bool().unwrap();
+                }
+                "metrics" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let metrics_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    config.metrics = metrics_value.as_bool().unwrap();
+                }
+                "admin" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let admin_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    match admin_value {
+                        Value::Null => (),
+                        Value::String(s) => config.admin = Some(s.clone()),
+                        _ => panic!("Value of 'admin' should be string.")
+                    }
+                }
+                "fieldNaming" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let naming_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    let naming_str = naming_value.as_raw_enum_choice().unwrap();
+                    config.field_naming = match naming_str {
+                        "asDefined" => FieldNaming::AsDefined,
+                        "camelCase" => FieldNaming::CamelCase,
+                        "snakeCase" => FieldNaming::SnakeCase,
+                        _ => panic!("Unrecognized field naming. {}", naming_str)
+                    };
+                }
+                "dateTimeFormat" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let format_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    config.date_time_format = match format_value {
+                        Value::RawEnumChoice(choice, _) => match choice.as_str() {
+                            "rfc3339" => DateTimeFormat::RFC3339,
+                            "epochMillis" => DateTimeFormat::EpochMillis,
+                            _ => panic!("Unrecognized date time format. {}", choice)
+                        },
+                        Value::String(format) => {
+                            if StrftimeItems::new(format.as_str()).any(|item| item == StrftimeItem::Error) {
+                                panic!("Invalid date time format '{}'.", format);
+                            }
+                            DateTimeFormat::Custom(format.clone())
+                        }
+                        _ => panic!("Value of 'dateTimeFormat' should be .rfc3339, .epochMillis or a format string.")
+                    };
+                }
+                "actionAliases" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let aliases_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    let aliases = match aliases_value.as_hashmap() {
+                        Some(aliases) => aliases,
+                        None => panic!("Value of 'actionAliases' should be a dictionary.")
+                    };
+                    for (alias, target) in aliases {
+                        let target = match target.as_raw_enum_choice().or(target.as_str()) {
+                            Some(target) => target,
+                            None => panic!("Alias '{}' should name an action.", alias)
+                        };
+                        let action = match Action::handler_from_name(target) {
+                            Some(action) => action,
+                            None => panic!("Unrecognized action '{}' for alias '{}'.", target, alias)
+                        };
+                        if Action::handler_from_name(alias).is_some() {
+                            panic!("Alias '{}' shadows a built-in action.", alias);
+                        }
+                        config.action_aliases.insert(alias.clone(), action);
+                    }
+                }
+                "cors" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let cors_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    config.cors = Self::resolve_cors(&cors_value, "cors").merge(&Cors::default());
+                    config.cors.check("cors");
+                    if let Some(routes) = cors_value.get("routes") {
+                        let routes = match routes.as_hashmap() {
+                            Some(routes) => routes,
+                            None => panic!("Value of 'cors.routes' should be a dictionary.")
+                        };
+                        for (route, route_value) in routes {
+                            if !route.starts_with("/") {
+                                panic!("CORS route '{}' should start with '/'.", route);
+                            }
+                            let route_cors = Self::resolve_cors(route_value, route);
+                            route_cors.merge(&config.cors).check(route);
+                            config.cors_routes.push((route.clone(), route_cors));
+                        }
+                    }
+                }
+                "timezone" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let timezone_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    match timezone_value {
+                        Value::Null => (),
+                        Value::String(s) => config.timezone = Some(s.clone()),
+                        _ => panic!("Value of 'timezone' should be string.")
+                    }
+                }
+                "trustProxy" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let trust_proxy_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    config.trust_proxy = trust_proxy_value.as_bool().unwrap();
+                }
+                "rateLimit" => {
+                    Self::resolve_expression(parser, source, &mut item.expression);
+                    let rate_limit_value = Self::unwrap_into_value_if_needed(parser, source, item.expression.resolved.as_ref().unwrap());
+                    config.rate_limit = match rate_limit_value {
+                        Value::Null => None,
+                        _ => Some(Self::resolve_rate_limit(&rate_limit_value)),
+                    };
+                }
                 _ => { panic!("Undefined name '{}' in config block.", item.identifier.name.as_str())}
             }
         }
     }
 
+    /// Settings of a CORS dictionary, for the server or a route.
+    fn resolve_cors(value: &Value, name: &str) -> CorsOverride {
+        let map = match value.as_hashmap() {
+            Some(map) => map,
+            None => panic!("Value of '{}' should be a dictionary.", name)
+        };
+        let mut cors = CorsOverride::default();
+        for (key, value) in map {
+            match key.as_str() {
+                "origin" => cors.allow_origins = Some(Self::resolve_cors_strings(value, "origin", name)),
+                "methods" => cors.allow_methods = Some(Self::resolve_cors_strings(value, "methods", name)),
+                "headers" => cors.allow_headers = Some(Self::resolve_cors_strings(value, "headers", name)),
+                "credentials" => match value.as_bool() {
+                    Some(credentials) => cors.allow_credentials = Some(credentials),
+                    None => panic!("CORS credentials of '{}' should be bool.", name)
+                },
+                "maxAge" => match value {
+                    Value::Null => cors.max_age = Some(None),
+                    _ => match value.as_i64() {
+                        Some(max_age) if max_age >= 0 => cors.max_age = Some(Some(max_age as u32)),
+                        _ => panic!("CORS max age of '{}' should be a non-negative integer.", name)
+                    }
+                },
+                "routes" if name == "cors" => (),
+                _ => panic!("Undefined name '{}' in CORS settings of '{}'.", key, name)
+            }
+        }
+        cors
+    }
+
+    /// A rate limit dictionary of requests per window in seconds.
+    fn resolve_rate_limit(value: &Value) -> RateLimit {
+        let map = match value.as_hashmap() {
+            Some(map) => map,
+            None => panic!("Value of 'rateLimit' should be a dictionary.")
+        };
+        let mut rate_limit = RateLimit { requests: 0, window: Duration::from_secs(1) };
+        for (key, value) in map {
+            match key.as_str() {
+                "requests" => match value.as_i64() {
+                    Some(requests) if requests > 0 => rate_limit.requests = requests as u32,
+                    _ => panic!("Rate limit requests should be a positive integer.")
+                },
+                "window" => match value.as_i64() {
+                    Some(window) if window > 0 => rate_limit.window = Duration::from_secs(window as u64),
+                    _ => panic!("Rate limit window should be a positive integer of seconds.")
+                },
+                _ => panic!("Undefined name '{}' in rate limit settings.", key)
+            }
+        }
+        if rate_limit.requests == 0 {
+            panic!("Rate limit requires 'requests'.")
+        }
+        rate_limit
+    }
+
+    /// A string or an array of strings of CORS settings.
+    fn resolve_cors_strings(value: &Value, key: &str, name: &str) -> Vec<String> {
+        if let Some(s) = value.as_str() {
+            return vec![s.to_owned()];
+        }
+        match value.as_vec() {
+            Some(vec) => vec.iter().map(|v| match v.as_str() {
+                Some(s) => s.to_owned(),
+                None => panic!("CORS {} of '{}' should be string or array of strings.", key, name)
+            }).collect(),
+            None => panic!("CORS {} of '{}' should be string or array of strings.", key, name)
+        }
+    }
+
     // Expression
 
     pub(crate) fn resolve_expression<'a>(parser: &Parser, source: &Source, expression: &mut Expression) {
@@ -729,6 +990,27 @@ impl Resolver {
         }
     }
 
+    /// Panics if a config value is read from an environment variable which
+    /// isn't set, instead of silently leaving the config value empty.
+    fn ensure_env_is_set(kind: &ExpressionKind, config_name: &str) {
+        let unit = match kind {
+            ExpressionKind::Unit(unit) => unit,
+            _ => return,
+        };
+        if unit.expressions.len() != 2 { return }
+        match (&unit.expressions[0], &unit.expressions[1]) {
+            (ExpressionKind::Identifier(identifier), ExpressionKind::Subscript(subscript)) if identifier.name == "ENV" => {
+                if let ExpressionKind::StringLiteral(key) = subscript.expression.as_ref() {
+                    let key = Self::resolve_string_literal(key).as_value().unwrap().as_str().unwrap().to_owned();
+                    if std::env::var(&key).is_err() {
+                        panic!("Environment variable '{}' of config '{}' is not set.", key, config_name);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
     fn resolve_subscript(parser: &Parser, source: &Source, subscript: &Subscript, entity: &Entity) -> Entity {
         let index_entity = Self::resolve_expression_kind(parser, source, &subscript.expression, false);
         let index_value = Self::unwrap_into_value_if_needed(parser, source, &index_entity);
@@ -1060,3 +1342,127 @@ impl Resolver {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+    use crate::core::app::builder::CallbackLookupTable;
+    use super::*;
+
+    /// A schema file of its own, removed when it's dropped, also after the
+    /// parser panics.
+    struct SchemaFile(PathBuf);
+
+    impl Drop for SchemaFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn parse_schema(content: &str) -> Parser {
+        let file = SchemaFile(std::env::temp_dir().join(format!("teo_test_{}.teo", Uuid::new_v4())));
+        fs::write(&file.0, content).unwrap();
+        let mut parser = Parser::new(Arc::new(Mutex::new(CallbackLookupTable::new())));
+        parser.parse(Some(file.0.to_str().unwrap()));
+        parser
+    }
+
+    /// An environment variable name no other test or environment uses.
+    fn env_name(name: &str) -> String {
+        format!("TEO_TEST_{}_{}", name, Uuid::new_v4().simple())
+    }
+
+    #[test]
+    fn server_config_reads_bind_port_and_jwt_secret_from_env() {
+        let port = env_name("PORT");
+        let jwt_secret = env_name("JWT_SECRET");
+        std::env::set_var(&port, "5123");
+        std::env::set_var(&jwt_secret, "my-secret");
+        let parser = parse_schema(&format!(r#"
+connector {{
+  provider: .mongo,
+  url: "mongodb://127.0.0.1:27017/teo"
+}}
+
+server {{
+  bind: ("0.0.0.0", ENV["{port}"]),
+  jwtSecret: ENV["{jwt_secret}"]
+}}
+"#));
+        let config_ref = parser.config.unwrap();
+        let config = parser.get_source(config_ref.0).get_server_config(config_ref.1);
+        assert_eq!(config.bind, Some(("0.0.0.0".to_owned(), 5123)));
+        assert_eq!(config.jwt_secret, Some("my-secret".to_owned()));
+    }
+
+    #[test]
+    #[should_panic(expected = "of config 'jwtSecret' is not set.")]
+    fn server_config_with_missing_env_is_an_error() {
+        let jwt_secret = env_name("MISSING_SECRET");
+        parse_schema(&format!(r#"
+connector {{
+  provider: .mongo,
+  url: "mongodb://127.0.0.1:27017/teo"
+}}
+
+server {{
+  bind: ("0.0.0.0", 5100),
+  jwtSecret: ENV["{jwt_secret}"]
+}}
+"#));
+    }
+
+    #[test]
+    fn negative_replica_lag_is_clamped_at_zero() {
+        let parser = parse_schema(r#"
+connector {
+  provider: .postgres,
+  url: "postgres://127.0.0.1:5432/teo",
+  replicaUrl: "postgres://127.0.0.1:5433/teo",
+  replicaLag: -500
+}
+"#);
+        let connector_ref = parser.connector.unwrap();
+        assert_eq!(parser.get_source(connector_ref.0).get_connector(connector_ref.1).replica_lag, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unrecognized action 'browse' for alias 'list'.")]
+    fn action_alias_of_an_unknown_action_is_an_error() {
+        parse_schema(r#"
+connector {
+  provider: .mongo,
+  url: "mongodb://127.0.0.1:27017/teo"
+}
+
+server {
+  bind: ("0.0.0.0", 5100),
+  actionAliases: {
+    "list": "browse"
+  }
+}
+"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "CORS of 'cors' can't allow credentials from any origin, list the allowed origins instead.")]
+    fn cors_with_credentials_from_any_origin_is_an_error() {
+        parse_schema(r#"
+connector {
+  provider: .mongo,
+  url: "mongodb://127.0.0.1:27017/teo"
+}
+
+server {
+  bind: ("0.0.0.0", 5100),
+  cors: {
+    origin: "*",
+    credentials: true
+  }
+}
+"#);
+    }
+}
@@ -0,0 +1,6 @@
+use crate::core::field::Field;
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn api_name_decorator(args: Vec<Argument>, field: &mut Field) {
+    field.api_name = Some(args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_str().unwrap().to_string());
+}
@@ -0,0 +1,10 @@
+use crate::core::field::{Field, FieldIndex};
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn case_insensitive_decorator(_args: Vec<Argument>, field: &mut Field) {
+    match field.index.as_mut() {
+        Some(FieldIndex::Index(settings)) | Some(FieldIndex::Unique(settings)) => settings.case_insensitive = true,
+        Some(FieldIndex::Primary(_)) => panic!("@caseInsensitive cannot be applied to primary key."),
+        None => panic!("@caseInsensitive requires @unique or @index."),
+    }
+}
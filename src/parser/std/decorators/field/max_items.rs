@@ -0,0 +1,9 @@
+use std::sync::Arc;
+use crate::core::field::Field;
+use crate::core::pipeline::items::array::max_items::MaxItemsItem;
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn max_items_decorator(args: Vec<Argument>, field: &mut Field) {
+    let value = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap();
+    field.on_set_pipeline.items.push(Arc::new(MaxItemsItem::new(value.clone())));
+}
@@ -7,6 +7,7 @@ pub(crate) mod write_on_create;
 pub(crate) mod write_once;
 pub(crate) mod write_nonnull;
 pub(crate) mod read_if;
+pub(crate) mod read_by;
 pub(crate) mod write_if;
 pub(crate) mod atomic;
 pub(crate) mod nonatomic;
@@ -15,6 +16,9 @@ pub(crate) mod index;
 pub(crate) mod present_with;
 pub(crate) mod present_without;
 pub(crate) mod present_if;
+pub(crate) mod min_items;
+pub(crate) mod max_items;
+pub(crate) mod unique_within;
 pub(crate) mod r#virtual;
 pub(crate) mod record_previous;
 pub(crate) mod input_omissible;
@@ -26,14 +30,22 @@ pub(crate) mod foreign_key;
 pub(crate) mod on_set;
 pub(crate) mod on_save;
 pub(crate) mod on_output;
+pub(crate) mod on_get;
 pub(crate) mod auth_identity;
 pub(crate) mod auth_by;
 pub(crate) mod queryable;
 pub(crate) mod unqueryable;
+pub(crate) mod sortable;
+pub(crate) mod unsortable;
 pub(crate) mod can_read;
 pub(crate) mod can_mutate;
 pub(crate) mod migration_decorator;
 pub(crate) mod dropped;
+pub(crate) mod sparse;
+pub(crate) mod case_insensitive;
+pub(crate) mod sequence;
+pub(crate) mod api_name;
+pub(crate) mod sensitive;
 
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
@@ -44,6 +56,7 @@ use crate::parser::std::decorators::field::auth_by::auth_by_decorator;
 use crate::parser::std::decorators::field::auth_identity::auth_identity_decorator;
 use crate::parser::std::decorators::field::auto::auto_decorator;
 use crate::parser::std::decorators::field::auto_increment::auto_increment_decorator;
+use crate::parser::std::decorators::field::sequence::sequence_decorator;
 use crate::parser::std::decorators::field::can_mutate::can_mutate_decorator;
 use crate::parser::std::decorators::field::can_read::can_read_decorator;
 use crate::parser::std::decorators::field::db::db_container;
@@ -54,17 +67,23 @@ use crate::parser::std::decorators::field::index::{id_decorator, index_decorator
 use crate::parser::std::decorators::field::input_omissible::input_omissible_decorator;
 use crate::parser::std::decorators::field::internal::{internal_decorator};
 use crate::parser::std::decorators::field::map::map_decorator;
+use crate::parser::std::decorators::field::api_name::api_name_decorator;
 use crate::parser::std::decorators::field::migration_decorator::migration_decorator;
 use crate::parser::std::decorators::field::nonatomic::{nonatomic_decorator};
 use crate::parser::std::decorators::field::on_output::on_output_decorator;
+use crate::parser::std::decorators::field::on_get::on_get_decorator;
 use crate::parser::std::decorators::field::on_save::on_save_decorator;
 use crate::parser::std::decorators::field::on_set::on_set_decorator;
 use crate::parser::std::decorators::field::output_omissible::output_omissible_decorator;
 use crate::parser::std::decorators::field::present_if::present_if_decorator;
+use crate::parser::std::decorators::field::min_items::min_items_decorator;
+use crate::parser::std::decorators::field::max_items::max_items_decorator;
+use crate::parser::std::decorators::field::unique_within::unique_within_decorator;
 use crate::parser::std::decorators::field::present_with::present_with_decorator;
 use crate::parser::std::decorators::field::present_without::present_without_decorator;
 use crate::parser::std::decorators::field::queryable::queryable_decorator;
 use crate::parser::std::decorators::field::read_if::read_if_decorator;
+use crate::parser::std::decorators::field::read_by::read_by_decorator;
 use crate::parser::std::decorators::field::readonly::{readonly_decorator};
 use crate::parser::std::decorators::field::readwrite::{readwrite_decorator};
 use crate::parser::std::decorators::field::write_if::write_if_decorator;
@@ -74,7 +93,12 @@ use crate::parser::std::decorators::field::write_once::write_once_decorator;
 use crate::parser::std::decorators::field::writeonly::{writeonly_decorator};
 use crate::parser::std::decorators::field::r#virtual::virtual_decorator;
 use crate::parser::std::decorators::field::record_previous::record_previous_decorator;
+use crate::parser::std::decorators::field::sparse::sparse_decorator;
+use crate::parser::std::decorators::field::sensitive::sensitive_decorator;
+use crate::parser::std::decorators::field::case_insensitive::case_insensitive_decorator;
 use crate::parser::std::decorators::field::unqueryable::unqueryable_decorator;
+use crate::parser::std::decorators::field::sortable::sortable_decorator;
+use crate::parser::std::decorators::field::unsortable::unsortable_decorator;
 
 
 pub(crate) struct GlobalFieldDecorators {
@@ -94,16 +118,21 @@ impl GlobalFieldDecorators {
         objects.insert("db".to_owned(), Accessible::Container(db_container(database_name)));
         objects.insert("id".to_owned(), Accessible::FieldDecorator(id_decorator));
         objects.insert("map".to_owned(), Accessible::FieldDecorator(map_decorator));
+        objects.insert("apiName".to_owned(), Accessible::FieldDecorator(api_name_decorator));
         objects.insert("unique".to_owned(), Accessible::FieldDecorator(unique_decorator));
         objects.insert("index".to_owned(), Accessible::FieldDecorator(index_decorator));
+        objects.insert("sparse".to_owned(), Accessible::FieldDecorator(sparse_decorator));
+        objects.insert("caseInsensitive".to_owned(), Accessible::FieldDecorator(case_insensitive_decorator));
         objects.insert("internal".to_owned(), Accessible::FieldDecorator(internal_decorator));
         objects.insert("readonly".to_owned(), Accessible::FieldDecorator(readonly_decorator));
         objects.insert("writeonly".to_owned(), Accessible::FieldDecorator(writeonly_decorator));
+        objects.insert("sensitive".to_owned(), Accessible::FieldDecorator(sensitive_decorator));
         objects.insert("readwrite".to_owned(), Accessible::FieldDecorator(readwrite_decorator));
         objects.insert("writeNonnull".to_owned(), Accessible::FieldDecorator(write_nonnull_decorator));
         objects.insert("writeOnCreate".to_owned(), Accessible::FieldDecorator(write_on_create_decorator));
         objects.insert("writeOnce".to_owned(), Accessible::FieldDecorator(write_once_decorator));
         objects.insert("readIf".to_owned(), Accessible::FieldDecorator(read_if_decorator));
+        objects.insert("readBy".to_owned(), Accessible::FieldDecorator(read_by_decorator));
         objects.insert("writeIf".to_owned(), Accessible::FieldDecorator(write_if_decorator));
         objects.insert("atomic".to_owned(), Accessible::FieldDecorator(atomic_decorator));
         objects.insert("nonatomic".to_owned(), Accessible::FieldDecorator(nonatomic_decorator));
@@ -111,20 +140,28 @@ impl GlobalFieldDecorators {
         objects.insert("presentWith".to_owned(), Accessible::FieldDecorator(present_with_decorator));
         objects.insert("presentWithout".to_owned(), Accessible::FieldDecorator(present_without_decorator));
         objects.insert("presentIf".to_owned(), Accessible::FieldDecorator(present_if_decorator));
+        objects.insert("requiredIf".to_owned(), Accessible::FieldDecorator(present_if_decorator));
+        objects.insert("minItems".to_owned(), Accessible::FieldDecorator(min_items_decorator));
+        objects.insert("maxItems".to_owned(), Accessible::FieldDecorator(max_items_decorator));
+        objects.insert("uniqueWithin".to_owned(), Accessible::FieldDecorator(unique_within_decorator));
         objects.insert("recordPrevious".to_owned(), Accessible::FieldDecorator(record_previous_decorator));
         objects.insert("inputOmissible".to_owned(), Accessible::FieldDecorator(input_omissible_decorator));
         objects.insert("outputOmissible".to_owned(), Accessible::FieldDecorator(output_omissible_decorator));
         objects.insert("auto".to_owned(), Accessible::FieldDecorator(auto_decorator));
         objects.insert("autoIncrement".to_owned(), Accessible::FieldDecorator(auto_increment_decorator));
+        objects.insert("sequence".to_owned(), Accessible::FieldDecorator(sequence_decorator));
         objects.insert("default".to_owned(), Accessible::FieldDecorator(default_decorator));
         objects.insert("foreignKey".to_owned(), Accessible::FieldDecorator(foreign_key_decorator));
         objects.insert("onSet".to_owned(), Accessible::FieldDecorator(on_set_decorator));
         objects.insert("onSave".to_owned(), Accessible::FieldDecorator(on_save_decorator));
         objects.insert("onOutput".to_owned(), Accessible::FieldDecorator(on_output_decorator));
+        objects.insert("onGet".to_owned(), Accessible::FieldDecorator(on_get_decorator));
         objects.insert("identity".to_owned(), Accessible::FieldDecorator(auth_identity_decorator));
         objects.insert("identityChecker".to_owned(), Accessible::FieldDecorator(auth_by_decorator));
         objects.insert("queryable".to_owned(), Accessible::FieldDecorator(queryable_decorator));
         objects.insert("unqueryable".to_owned(), Accessible::FieldDecorator(unqueryable_decorator));
+        objects.insert("sortable".to_owned(), Accessible::FieldDecorator(sortable_decorator));
+        objects.insert("unsortable".to_owned(), Accessible::FieldDecorator(unsortable_decorator));
         objects.insert("canMutate".to_owned(), Accessible::FieldDecorator(can_mutate_decorator));
         objects.insert("canRead".to_owned(), Accessible::FieldDecorator(can_read_decorator));
         objects.insert("migration".to_owned(), Accessible::FieldDecorator(migration_decorator));
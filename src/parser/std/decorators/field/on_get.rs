@@ -0,0 +1,13 @@
+use crate::core::field::Field;
+
+use crate::parser::ast::argument::Argument;
+use crate::prelude::Value;
+
+pub(crate) fn on_get_decorator(args: Vec<Argument>, field: &mut Field) {
+    match args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap() {
+        Value::Pipeline(p) => {
+            field.on_get_pipeline = p.clone();
+        }
+        _ => panic!("Wrong argument passed to onGet.")
+    }
+}
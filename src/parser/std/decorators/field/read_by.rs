@@ -0,0 +1,21 @@
+use crate::core::field::Field;
+use crate::core::field::read_rule::ReadRule;
+use crate::parser::ast::argument::Argument;
+use crate::prelude::Value;
+
+pub(crate) fn read_by_decorator(args: Vec<Argument>, field: &mut Field) {
+    let mut roles: Vec<String> = vec![];
+    for arg in args.iter() {
+        match arg.resolved.as_ref().unwrap().as_value().unwrap() {
+            Value::String(role) => roles.push(role.clone()),
+            Value::Vec(vec) => for role in vec {
+                match role.as_str() {
+                    Some(role) => roles.push(role.to_owned()),
+                    None => panic!("Wrong argument passed to readBy.")
+                }
+            }
+            _ => panic!("Wrong argument passed to readBy.")
+        }
+    }
+    field.read_rule = ReadRule::ReadBy(roles);
+}
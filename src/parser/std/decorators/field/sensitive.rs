@@ -0,0 +1,6 @@
+use crate::core::field::Field;
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn sensitive_decorator(_args: Vec<Argument>, field: &mut Field) {
+    field.sensitive = true;
+}
@@ -0,0 +1,26 @@
+use crate::core::field::Field;
+use crate::core::field::sequence::FieldSequence;
+use crate::parser::ast::argument::Argument;
+use crate::prelude::Value;
+
+pub(crate) fn sequence_decorator(args: Vec<Argument>, field: &mut Field) {
+    let mut sequence = FieldSequence::default();
+    for arg in args {
+        let value = arg.resolved.as_ref().unwrap().as_value().unwrap();
+        match arg.name.as_ref().map(|n| n.name.as_str()) {
+            Some("scope") => {
+                sequence.scope = match value {
+                    Value::Vec(vec) => vec.iter().map(|v| v.as_raw_enum_choice().unwrap().to_owned()).collect(),
+                    Value::RawEnumChoice(name, _) => vec![name.clone()],
+                    _ => panic!("@sequence scope should be field names."),
+                };
+            }
+            Some("start") => {
+                sequence.start = value.as_i64().unwrap();
+            }
+            _ => panic!("@sequence only accepts scope and start."),
+        }
+    }
+    field.sequence = Some(sequence);
+    field.input_omissible = true;
+}
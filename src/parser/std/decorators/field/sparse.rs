@@ -0,0 +1,17 @@
+use crate::core::field::{Field, FieldIndex};
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn sparse_decorator(args: Vec<Argument>, field: &mut Field) {
+    let sparse = match args.get(0) {
+        Some(arg) => match arg.resolved.as_ref().unwrap().as_value().unwrap().as_bool() {
+            Some(sparse) => sparse,
+            None => panic!("@sparse argument should be bool."),
+        },
+        None => true,
+    };
+    match field.index.as_mut() {
+        Some(FieldIndex::Index(settings)) | Some(FieldIndex::Unique(settings)) => settings.sparse = Some(sparse),
+        Some(FieldIndex::Primary(_)) => panic!("@sparse cannot be applied to primary key."),
+        None => panic!("@sparse requires @unique or @index."),
+    }
+}
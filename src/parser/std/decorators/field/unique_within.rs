@@ -0,0 +1,13 @@
+use crate::core::field::Field;
+use crate::parser::ast::argument::Argument;
+use crate::prelude::Value;
+
+pub(crate) fn unique_within_decorator(args: Vec<Argument>, field: &mut Field) {
+    let value = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap();
+    let scope = match value {
+        Value::Vec(keys) => keys.iter().map(|k| k.as_raw_enum_choice().unwrap().to_owned()).collect(),
+        Value::RawEnumChoice(key, _) => vec![key.clone()],
+        _ => panic!("Wrong argument passed to uniqueWithin.")
+    };
+    field.unique_within = Some(scope);
+}
@@ -0,0 +1,7 @@
+use crate::core::field::Field;
+use crate::core::field::{SortAbility};
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn unsortable_decorator(_args: Vec<Argument>, field: &mut Field) {
+    field.sort_ability = SortAbility::Unsortable;
+}
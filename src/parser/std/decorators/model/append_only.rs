@@ -0,0 +1,6 @@
+use crate::core::model::builder::ModelBuilder;
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn append_only_decorator(_args: Vec<Argument>, model: &mut ModelBuilder) {
+    model.append_only = true;
+}
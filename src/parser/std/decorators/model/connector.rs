@@ -0,0 +1,6 @@
+use crate::core::model::builder::ModelBuilder;
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn connector_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
+    model.connector(args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_str().unwrap());
+}
@@ -17,5 +17,7 @@ pub(crate) fn disable_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
             panic!()
         }
     };
-    model.disabled_actions = Some(actions);
+    for action in actions {
+        model.disable_actions_matching(action);
+    }
 }
@@ -0,0 +1,12 @@
+use crate::core::model::builder::ModelBuilder;
+
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn extends_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
+    if args.len() != 2 {
+        panic!("@extends requires a base model name and a discriminator value.");
+    }
+    let base = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_str().unwrap();
+    let value = args.get(1).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_str().unwrap();
+    model.extends(base, value);
+}
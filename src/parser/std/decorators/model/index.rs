@@ -7,6 +7,7 @@ use crate::prelude::Value;
 static MODEL_INDEX_PRIMARY: u8 = 0;
 static MODEL_INDEX_INDEX: u8 = 1;
 static MODEL_INDEX_UNIQUE: u8 = 2;
+static MODEL_INDEX_FULLTEXT: u8 = 3;
 
 pub(crate) fn id_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
     decorator(args, model, MODEL_INDEX_PRIMARY)
@@ -20,6 +21,10 @@ pub(crate) fn unique_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
     decorator(args, model, MODEL_INDEX_UNIQUE)
 }
 
+pub(crate) fn fulltext_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
+    decorator(args, model, MODEL_INDEX_FULLTEXT)
+}
+
 fn decorator(args: Vec<Argument>, model: &mut ModelBuilder, index_kind: u8) {
     let mut items: Vec<ModelIndexItem> = vec![];
     let mut map: Option<String> = None;
@@ -62,6 +67,7 @@ fn decorator(args: Vec<Argument>, model: &mut ModelBuilder, index_kind: u8) {
         },
         1 => model.indices.push(ModelIndex::new(ModelIndexType::Index, map, items)),
         2 => model.indices.push(ModelIndex::new(ModelIndexType::Unique, map, items)),
+        3 => model.indices.push(ModelIndex::new(ModelIndexType::Fulltext, map, items)),
         _ => unreachable!(),
     }
 }
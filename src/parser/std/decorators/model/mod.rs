@@ -1,35 +1,57 @@
 pub(crate) mod map;
 pub(crate) mod index;
 pub(crate) mod url;
+pub(crate) mod route;
 pub(crate) mod identity;
 pub(crate) mod r#virtual;
+pub(crate) mod append_only;
 pub(crate) mod before_save;
 pub(crate) mod after_save;
 pub(crate) mod before_delete;
 pub(crate) mod after_delete;
+pub(crate) mod before_find;
+pub(crate) mod after_find;
 pub(crate) mod can_read;
 pub(crate) mod can_mutate;
 pub(crate) mod disable;
 pub(crate) mod action;
 pub(crate) mod migration;
+pub(crate) mod discriminator;
+pub(crate) mod extends;
+pub(crate) mod quota;
+pub(crate) mod not_found;
+pub(crate) mod validate;
+pub(crate) mod soft_delete;
+pub(crate) mod connector;
 
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use crate::parser::ast::accessible::Accessible;
 use crate::parser::std::decorators::model::action::action_decorator;
 use crate::parser::std::decorators::model::after_delete::after_delete_decorator;
+use crate::parser::std::decorators::model::append_only::append_only_decorator;
+use crate::parser::std::decorators::model::after_find::after_find_decorator;
 use crate::parser::std::decorators::model::after_save::after_save_decorator;
 use crate::parser::std::decorators::model::before_delete::before_delete_decorator;
+use crate::parser::std::decorators::model::before_find::before_find_decorator;
 use crate::parser::std::decorators::model::before_save::before_save_decorator;
 use crate::parser::std::decorators::model::can_mutate::can_mutate_decorator;
 use crate::parser::std::decorators::model::can_read::can_read_decorator;
 use crate::parser::std::decorators::model::disable::disable_decorator;
+use crate::parser::std::decorators::model::discriminator::discriminator_decorator;
+use crate::parser::std::decorators::model::extends::extends_decorator;
 use crate::parser::std::decorators::model::identity::identity_decorator;
-use crate::parser::std::decorators::model::index::{index_decorator, id_decorator, unique_decorator};
+use crate::parser::std::decorators::model::index::{index_decorator, id_decorator, unique_decorator, fulltext_decorator};
 use crate::parser::std::decorators::model::map::map_decorator;
 use crate::parser::std::decorators::model::migration::migration_decorator;
 use crate::parser::std::decorators::model::r#virtual::virtual_decorator;
 use crate::parser::std::decorators::model::url::url_decorator;
+use crate::parser::std::decorators::model::route::route_decorator;
+use crate::parser::std::decorators::model::quota::quota_decorator;
+use crate::parser::std::decorators::model::not_found::not_found_decorator;
+use crate::parser::std::decorators::model::validate::validate_decorator;
+use crate::parser::std::decorators::model::soft_delete::soft_delete_decorator;
+use crate::parser::std::decorators::model::connector::connector_decorator;
 
 pub(crate) struct GlobalModelDecorators {
     objects: HashMap<String, Accessible>
@@ -47,20 +69,32 @@ impl GlobalModelDecorators {
         let mut objects: HashMap<String, Accessible> = HashMap::new();
         objects.insert("map".to_owned(), Accessible::ModelDecorator(map_decorator));
         objects.insert("url".to_owned(), Accessible::ModelDecorator(url_decorator));
+        objects.insert("route".to_owned(), Accessible::ModelDecorator(route_decorator));
         objects.insert("identity".to_owned(), Accessible::ModelDecorator(identity_decorator));
         objects.insert("id".to_owned(), Accessible::ModelDecorator(id_decorator));
         objects.insert("unique".to_owned(), Accessible::ModelDecorator(unique_decorator));
         objects.insert("index".to_owned(), Accessible::ModelDecorator(index_decorator));
+        objects.insert("fulltext".to_owned(), Accessible::ModelDecorator(fulltext_decorator));
         objects.insert("virtual".to_owned(), Accessible::ModelDecorator(virtual_decorator));
+        objects.insert("appendOnly".to_owned(), Accessible::ModelDecorator(append_only_decorator));
+        objects.insert("softDelete".to_owned(), Accessible::ModelDecorator(soft_delete_decorator));
+        objects.insert("connector".to_owned(), Accessible::ModelDecorator(connector_decorator));
         objects.insert("beforeSave".to_owned(), Accessible::ModelDecorator(before_save_decorator));
         objects.insert("afterSave".to_owned(), Accessible::ModelDecorator(after_save_decorator));
+        objects.insert("validate".to_owned(), Accessible::ModelDecorator(validate_decorator));
         objects.insert("beforeDelete".to_owned(), Accessible::ModelDecorator(before_delete_decorator));
         objects.insert("afterDelete".to_owned(), Accessible::ModelDecorator(after_delete_decorator));
+        objects.insert("beforeFind".to_owned(), Accessible::ModelDecorator(before_find_decorator));
+        objects.insert("afterFind".to_owned(), Accessible::ModelDecorator(after_find_decorator));
         objects.insert("disable".to_owned(), Accessible::ModelDecorator(disable_decorator));
         objects.insert("action".to_owned(), Accessible::ModelDecorator(action_decorator));
+        objects.insert("quota".to_owned(), Accessible::ModelDecorator(quota_decorator));
+        objects.insert("notFound".to_owned(), Accessible::ModelDecorator(not_found_decorator));
         objects.insert("canRead".to_owned(), Accessible::ModelDecorator(can_read_decorator));
         objects.insert("canMutate".to_owned(), Accessible::ModelDecorator(can_mutate_decorator));
         objects.insert("migration".to_owned(), Accessible::ModelDecorator(migration_decorator));
+        objects.insert("discriminator".to_owned(), Accessible::ModelDecorator(discriminator_decorator));
+        objects.insert("extends".to_owned(), Accessible::ModelDecorator(extends_decorator));
         Self { objects }
     }
 
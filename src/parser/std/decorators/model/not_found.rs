@@ -0,0 +1,33 @@
+use crate::core::action::{Action, FIND_FIRST_HANDLER, FIND_UNIQUE_HANDLER};
+use crate::core::model::builder::ModelBuilder;
+use crate::core::model::not_found::NotFound;
+use crate::parser::ast::argument::Argument;
+use crate::prelude::Value;
+
+pub(crate) fn not_found_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
+    if args.len() < 2 || args.len() > 3 {
+        panic!("Model not found decorator takes an action, a behavior and an optional error message.")
+    }
+    let action_value = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap();
+    let action_name = match action_value {
+        Value::RawEnumChoice(name, _) => name.as_str(),
+        Value::String(name) => name.as_str(),
+        _ => panic!("Model not found decorator's action should be an action name.")
+    };
+    let action = match Action::handler_from_name(action_name) {
+        Some(action) if action.to_u32() == FIND_UNIQUE_HANDLER || action.to_u32() == FIND_FIRST_HANDLER => action,
+        _ => panic!("Model not found decorator's action should be 'findUnique' or 'findFirst', found '{}'.", action_name)
+    };
+    let behavior = args.get(1).unwrap().resolved.as_ref().unwrap().as_value().unwrap();
+    let message = args.get(2).map(|arg| match arg.resolved.as_ref().unwrap().as_value().unwrap().as_str() {
+        Some(message) => message.to_owned(),
+        None => panic!("Model not found decorator's message should be string.")
+    });
+    let not_found = match behavior.as_raw_enum_choice() {
+        Some("error") => NotFound::Error(message),
+        Some("null") if message.is_none() => NotFound::Null,
+        Some("null") => panic!("Model not found decorator's message is only for the 'error' behavior."),
+        _ => panic!("Model not found decorator's behavior should be '.error' or '.null'.")
+    };
+    model.not_found(action, not_found);
+}
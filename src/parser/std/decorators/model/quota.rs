@@ -0,0 +1,31 @@
+use std::time::Duration;
+use crate::core::action::Action;
+use crate::core::model::builder::ModelBuilder;
+use crate::parser::ast::argument::Argument;
+use crate::prelude::Value;
+
+pub(crate) fn quota_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
+    if args.len() != 3 {
+        panic!("Model quota decorator takes an action, a limit and a window in seconds.")
+    }
+    let action_value = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap();
+    let action_name = match action_value {
+        Value::RawEnumChoice(name, _) => name.as_str(),
+        Value::String(name) => name.as_str(),
+        _ => panic!("Model quota decorator's action should be an action name.")
+    };
+    let action = match Action::handler_from_name(action_name) {
+        Some(action) => action,
+        None => panic!("Unrecognized action name '{}' in model quota decorator.", action_name)
+    };
+    let limit = args.get(1).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_i64().unwrap();
+    let limit = match u32::try_from(limit) {
+        Ok(limit) if limit > 0 => limit,
+        _ => panic!("Model quota decorator's limit should be a positive 32 bit integer, found {}.", limit)
+    };
+    let window = args.get(2).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_i64().unwrap();
+    if window <= 0 {
+        panic!("Model quota decorator's window should be a positive number of seconds, found {}.", window)
+    }
+    model.action_quota(action, limit, Duration::from_secs(window as u64));
+}
@@ -0,0 +1,7 @@
+use crate::core::model::builder::ModelBuilder;
+
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn route_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
+    model.route(args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_str().unwrap());
+}
@@ -0,0 +1,6 @@
+use crate::core::model::builder::ModelBuilder;
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn soft_delete_decorator(args: Vec<Argument>, model: &mut ModelBuilder) {
+    model.soft_delete(args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_str().unwrap());
+}
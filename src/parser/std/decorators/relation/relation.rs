@@ -1,4 +1,5 @@
 use crate::core::relation::Relation;
+use crate::core::relation::delete_rule::DeleteRule;
 use crate::parser::ast::argument::Argument;
 
 pub(crate) fn relation_decorator(args: Vec<Argument>, relation: &mut Relation) {
@@ -52,15 +53,27 @@ pub(crate) fn relation_decorator(args: Vec<Argument>, relation: &mut Relation) {
     } else {
         panic!("One of 'fields' or 'through' must be provided.")
     }
+    // foreign key index
+    if let Some(index_arg) = args.iter().find(|a| a.name.as_ref().unwrap().name == "index") {
+        relation.set_auto_index(index_arg.resolved.as_ref().unwrap().as_value().unwrap().as_bool().unwrap());
+    }
+    // cached count on the related model
+    if let Some(counter_cache_arg) = args.iter().find(|a| a.name.as_ref().unwrap().name == "counterCache") {
+        if relation.through().is_some() {
+            panic!("A relation with 'through' cannot have 'counterCache'.");
+        }
+        relation.set_counter_cache(counter_cache_arg.resolved.as_ref().unwrap().as_value().unwrap().as_raw_enum_choice().unwrap().to_owned());
+    }
     // delete rule
-    // let on_delete_arg = args.iter().find(|a| {
-    //     &a.name.unwrap().name == "onDelete"
-    // });
-    // if on_delete_arg.is_some() {
-    //     let rule = on_delete_arg.unwrap().resolved.unwrap().as_value().unwrap().as_raw_enum_choice().unwrap();
-    //     match rule {
-    //
-    //     }
-    // }
+    if let Some(on_delete_arg) = args.iter().find(|a| a.name.as_ref().unwrap().name == "onDelete") {
+        if relation.through().is_some() {
+            panic!("A relation with 'through' cannot have 'onDelete'.");
+        }
+        let rule = on_delete_arg.resolved.as_ref().unwrap().as_value().unwrap().as_raw_enum_choice().unwrap();
+        match DeleteRule::from_name(rule) {
+            Some(delete_rule) => relation.set_delete_rule(delete_rule),
+            None => panic!("Unknown delete rule '{}'.", rule),
+        }
+    }
     // update rule
 }
@@ -0,0 +1,10 @@
+use std::sync::Arc;
+use crate::core::pipeline::item::Item;
+
+use crate::core::pipeline::items::array::max_items::MaxItemsItem;
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn max_items(args: Vec<Argument>) -> Arc<dyn Item> {
+    let value = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap();
+    Arc::new(MaxItemsItem::new(value.clone()))
+}
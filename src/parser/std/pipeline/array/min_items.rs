@@ -0,0 +1,10 @@
+use std::sync::Arc;
+use crate::core::pipeline::item::Item;
+
+use crate::core::pipeline::items::array::min_items::MinItemsItem;
+use crate::parser::ast::argument::Argument;
+
+pub(crate) fn min_items(args: Vec<Argument>) -> Arc<dyn Item> {
+    let value = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap();
+    Arc::new(MinItemsItem::new(value.clone()))
+}
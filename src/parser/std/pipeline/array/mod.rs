@@ -4,3 +4,5 @@ pub(crate) mod prepend;
 pub(crate) mod truncate;
 pub(crate) mod get_length;
 pub(crate) mod reverse;
+pub(crate) mod min_items;
+pub(crate) mod max_items;
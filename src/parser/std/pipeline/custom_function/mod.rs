@@ -1,6 +1,7 @@
 use std::sync::{Arc, Mutex};
 use crate::core::app::builder::CallbackLookupTable;
 use crate::core::pipeline::item::Item;
+use crate::core::pipeline::items::function::custom::CustomFunctionItem;
 
 
 
@@ -70,3 +71,8 @@ pub(crate) fn custom_compare(lookup_table: Arc<Mutex<CallbackLookupTable>>, args
         panic!("Cannot find a compare named '{}'.", name)
     }
 }
+
+pub(crate) fn custom_function(lookup_table: Arc<Mutex<CallbackLookupTable>>, args: Vec<Argument>) -> Arc<dyn Item> {
+    let name = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_str().unwrap();
+    Arc::new(CustomFunctionItem::new(name, lookup_table))
+}
@@ -4,17 +4,19 @@ use crate::parser::ast::accessible::{ASTFunctionInstaller, ASTPipelineInstaller}
 use crate::parser::std::pipeline::array::append::append;
 use crate::parser::std::pipeline::array::get_length::get_length;
 use crate::parser::std::pipeline::array::has_length::has_length;
+use crate::parser::std::pipeline::array::max_items::max_items;
+use crate::parser::std::pipeline::array::min_items::min_items;
 use crate::parser::std::pipeline::array::prepend::prepend;
 use crate::parser::std::pipeline::array::reverse::reverse;
 use crate::parser::std::pipeline::array::truncate::truncate;
 use crate::parser::std::pipeline::bcrypt::bcrypt_salt::bcrypt_salt;
 use crate::parser::std::pipeline::bcrypt::bcrypt_verify::bcrypt_verify;
-use crate::parser::std::pipeline::custom_function::{custom_callback, custom_compare, custom_transform, custom_validate};
+use crate::parser::std::pipeline::custom_function::{custom_callback, custom_compare, custom_function, custom_transform, custom_validate};
 use crate::parser::std::pipeline::datetime::{now, today};
 use crate::parser::std::pipeline::debug::print;
 use crate::parser::std::pipeline::identity::identity;
 use crate::parser::std::pipeline::action::{redirect, when};
-use crate::parser::std::pipeline::logical::{all_modifier, and_modifier, any_modifier, if_modifier, invalid, not_modifier, or_modifier, passed, valid};
+use crate::parser::std::pipeline::logical::{all_modifier, and_modifier, any_modifier, catch_modifier, if_modifier, invalid, not_modifier, or_modifier, passed, valid};
 use crate::parser::std::pipeline::math::{abs, add, cbrt, ceil, divide, floor, max, min, modular, multiply, pow, root, round, sqrt, subtract};
 use crate::parser::std::pipeline::number::{is_even, is_odd};
 use crate::parser::std::pipeline::object::{assign, ctx_self, is, is_a, object_get, object_previous_value, object_set};
@@ -47,6 +49,8 @@ impl GlobalPipelineInstallers {
         objects.insert("prepend".to_owned(), prepend);
         objects.insert("getLength".to_owned(), get_length);
         objects.insert("hasLength".to_owned(), has_length);
+        objects.insert("minItems".to_owned(), min_items);
+        objects.insert("maxItems".to_owned(), max_items);
         objects.insert("reverse".to_owned(), reverse);
         objects.insert("truncate".to_owned(), truncate);
         // bcrypt
@@ -69,6 +73,7 @@ impl GlobalPipelineInstallers {
         objects.insert("passed".to_owned(), passed);
         objects.insert("and".to_owned(), and_modifier);
         objects.insert("or".to_owned(), or_modifier);
+        objects.insert("catch".to_owned(), catch_modifier);
         // math
         objects.insert("abs".to_owned(), abs);
         objects.insert("add".to_owned(), add);
@@ -171,6 +176,7 @@ impl GlobalFunctionInstallers {
         objects.insert("validate".to_owned(), custom_validate);
         objects.insert("callback".to_owned(), custom_callback);
         objects.insert("compare".to_owned(), custom_compare);
+        objects.insert("function".to_owned(), custom_function);
         Self { objects }
     }
 
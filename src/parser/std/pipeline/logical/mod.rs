@@ -3,6 +3,7 @@ use crate::core::pipeline::item::Item;
 use crate::core::pipeline::items::logical::all::AllItem;
 use crate::core::pipeline::items::logical::and::AndItem;
 use crate::core::pipeline::items::logical::any::AnyItem;
+use crate::core::pipeline::items::logical::catch::CatchItem;
 use crate::core::pipeline::items::logical::valid::ValidItem;
 use crate::core::pipeline::items::logical::invalid::InvalidItem;
 use crate::core::pipeline::items::logical::not::NotItem;
@@ -104,3 +105,12 @@ pub(crate) fn or_modifier(args: Vec<Argument>) -> Arc<dyn Item> {
     let value = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap().clone();
     Arc::new(OrItem::new(value))
 }
+
+pub(crate) fn catch_modifier(args: Vec<Argument>) -> Arc<dyn Item> {
+    if args.len() != 2 {
+        panic!("`catch` takes exactly 2 arguments.")
+    }
+    let pipeline = args.get(0).unwrap().resolved.as_ref().unwrap().as_value().unwrap().as_pipeline().unwrap().clone();
+    let fallback = args.get(1).unwrap().resolved.as_ref().unwrap().as_value().unwrap().clone();
+    Arc::new(CatchItem::new(pipeline, fallback))
+}